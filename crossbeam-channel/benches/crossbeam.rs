@@ -595,6 +595,30 @@ mod bounded_0 {
         .unwrap();
     }
 
+    #[bench]
+    fn pingpong(b: &mut Bencher) {
+        let steps = TOTAL_STEPS;
+        let (s1, r1) = bounded::<i32>(0);
+        let (s2, r2) = bounded::<i32>(0);
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                while let Ok(x) = r1.recv() {
+                    s2.send(x).unwrap();
+                }
+            });
+
+            b.iter(|| {
+                for i in 0..steps {
+                    s1.send(i as i32).unwrap();
+                    r2.recv().unwrap();
+                }
+            });
+            drop(s1);
+        })
+        .unwrap();
+    }
+
     #[bench]
     fn spmc(b: &mut Bencher) {
         let threads = num_cpus::get() - 1;
@@ -0,0 +1,518 @@
+//! Channels that carry a stream of bytes.
+
+use std::cell::UnsafeCell;
+use std::cmp;
+use std::fmt;
+use std::io;
+use std::ptr;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crossbeam_utils::CachePadded;
+
+use crate::channel::Receiver;
+use crate::context::Context;
+use crate::err::{RecvTimeoutError, TryRecvError};
+use crate::flavors::adapter::RecvAdapter;
+use crate::select::{Operation, SelectHandle, Selected, Token};
+use crate::waker::SyncWaker;
+
+/// Creates a single-producer single-consumer channel for a stream of bytes.
+///
+/// The channel is a ring buffer holding at most `cap` bytes. [`ByteSender`] implements
+/// [`io::Write`] and [`ByteReceiver`] implements [`io::Read`], so data can be streamed between
+/// two threads without allocating a buffer for every chunk.
+///
+/// Writes block while the buffer is full and reads block while it is empty. A write may accept
+/// only part of its input, as usual for [`io::Write`]. Once the receiver is dropped, writes fail
+/// with [`io::ErrorKind::BrokenPipe`]. Once the sender is dropped, reads return the remaining
+/// bytes and then `Ok(0)` to signal the end of the stream.
+///
+/// To wait for data together with other channel operations, use [`ByteReceiver::readable`].
+///
+/// # Panics
+///
+/// Panics if `cap` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::{Read, Write};
+/// use std::thread;
+/// use crossbeam_channel::byte_channel;
+///
+/// let (mut s, mut r) = byte_channel(16);
+///
+/// thread::spawn(move || {
+///     for _ in 0..100 {
+///         s.write_all(b"hello ").unwrap();
+///     }
+/// });
+///
+/// let mut text = String::new();
+/// r.read_to_string(&mut text).unwrap();
+/// assert_eq!(text, "hello ".repeat(100));
+/// ```
+pub fn byte_channel(cap: usize) -> (ByteSender, ByteReceiver) {
+    assert!(cap > 0, "capacity must be positive");
+
+    let chan = Arc::new(Channel {
+        buffer: (0..cap).map(|_| UnsafeCell::new(0)).collect(),
+        head: CachePadded::new(AtomicUsize::new(0)),
+        tail: CachePadded::new(AtomicUsize::new(0)),
+        is_disconnected: AtomicBool::new(false),
+        readers: SyncWaker::new(),
+        writers: SyncWaker::new(),
+    });
+
+    let s = ByteSender { chan: chan.clone() };
+    let r = ByteReceiver { chan };
+    (s, r)
+}
+
+/// The ring buffer shared by both sides of a byte channel.
+struct Channel {
+    /// The buffer holding bytes.
+    buffer: Box<[UnsafeCell<u8>]>,
+
+    /// The total number of bytes ever read.
+    ///
+    /// Only the receiving side modifies this counter.
+    head: CachePadded<AtomicUsize>,
+
+    /// The total number of bytes ever written.
+    ///
+    /// Only the sending side modifies this counter.
+    tail: CachePadded<AtomicUsize>,
+
+    /// Equals `true` when either side has been dropped.
+    is_disconnected: AtomicBool,
+
+    /// Threads waiting for bytes to read.
+    readers: SyncWaker,
+
+    /// Threads waiting for space to write.
+    writers: SyncWaker,
+}
+
+unsafe impl Send for Channel {}
+unsafe impl Sync for Channel {}
+
+impl Channel {
+    /// Returns the capacity of the buffer.
+    fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the number of bytes in the buffer.
+    fn len(&self) -> usize {
+        let tail = self.tail.load(Ordering::SeqCst);
+        let head = self.head.load(Ordering::SeqCst);
+        tail.wrapping_sub(head)
+    }
+
+    /// Returns `true` if either side has been dropped.
+    fn is_disconnected(&self) -> bool {
+        self.is_disconnected.load(Ordering::SeqCst)
+    }
+
+    /// Returns `true` if a read would not block.
+    fn is_readable(&self) -> bool {
+        self.len() > 0 || self.is_disconnected()
+    }
+
+    /// Returns `true` if a write would not block.
+    fn is_writable(&self) -> bool {
+        self.len() < self.capacity() || self.is_disconnected()
+    }
+
+    /// Copies as many bytes from `buf` into the buffer as fit, without blocking.
+    ///
+    /// # Safety
+    ///
+    /// Only the sending side may call this method.
+    unsafe fn try_write(&self, buf: &[u8]) -> usize {
+        let tail = self.tail.load(Ordering::Relaxed);
+        let head = self.head.load(Ordering::SeqCst);
+        let free = self.capacity() - tail.wrapping_sub(head);
+        let n = cmp::min(free, buf.len());
+
+        if n > 0 {
+            let ptr = self.buffer.as_ptr() as *mut u8;
+            let start = tail % self.capacity();
+            let first = cmp::min(n, self.capacity() - start);
+
+            ptr::copy_nonoverlapping(buf.as_ptr(), ptr.add(start), first);
+            ptr::copy_nonoverlapping(buf.as_ptr().add(first), ptr, n - first);
+
+            self.tail.store(tail.wrapping_add(n), Ordering::SeqCst);
+            self.readers.notify();
+        }
+        n
+    }
+
+    /// Copies as many bytes from the buffer into `buf` as are available, without blocking.
+    ///
+    /// # Safety
+    ///
+    /// Only the receiving side may call this method.
+    unsafe fn try_read(&self, buf: &mut [u8]) -> usize {
+        let head = self.head.load(Ordering::Relaxed);
+        let tail = self.tail.load(Ordering::SeqCst);
+        let n = cmp::min(tail.wrapping_sub(head), buf.len());
+
+        if n > 0 {
+            let ptr = self.buffer.as_ptr() as *const u8;
+            let start = head % self.capacity();
+            let first = cmp::min(n, self.capacity() - start);
+
+            ptr::copy_nonoverlapping(ptr.add(start), buf.as_mut_ptr(), first);
+            ptr::copy_nonoverlapping(ptr, buf.as_mut_ptr().add(first), n - first);
+
+            self.head.store(head.wrapping_add(n), Ordering::SeqCst);
+            self.writers.notify();
+        }
+        n
+    }
+
+    /// Blocks the current thread until it is woken up by `waker` or the deadline passes.
+    ///
+    /// Returns early if `is_ready` holds after the thread has started waiting.
+    fn wait(&self, waker: &SyncWaker, is_ready: fn(&Channel) -> bool, deadline: Option<Instant>) {
+        let mut token = Token::default();
+
+        Context::with(|cx| {
+            // Prepare for blocking until the other side wakes us up.
+            let oper = Operation::hook(&mut token);
+            waker.watch(oper, cx);
+
+            // Has the channel become ready just now?
+            if is_ready(self) {
+                let _ = cx.try_select(Selected::Aborted);
+            }
+
+            // Block the current thread.
+            cx.wait_until(deadline);
+            waker.unwatch(oper);
+        });
+    }
+
+    /// Disconnects the channel and wakes up all blocked threads.
+    fn disconnect(&self) {
+        self.is_disconnected.store(true, Ordering::SeqCst);
+        self.readers.disconnect();
+        self.writers.disconnect();
+    }
+}
+
+/// The sending side of a byte channel.
+///
+/// Created by the [`byte_channel`] function.
+pub struct ByteSender {
+    /// The shared ring buffer.
+    chan: Arc<Channel>,
+}
+
+impl ByteSender {
+    /// Returns the number of bytes in the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use crossbeam_channel::byte_channel;
+    ///
+    /// let (mut s, _r) = byte_channel(16);
+    /// s.write_all(b"abc").unwrap();
+    /// assert_eq!(s.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.chan.len()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Returns the capacity of the channel in bytes.
+    pub fn capacity(&self) -> usize {
+        self.chan.capacity()
+    }
+}
+
+impl io::Write for ByteSender {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            if self.chan.is_disconnected() {
+                return Err(io::ErrorKind::BrokenPipe.into());
+            }
+
+            let n = unsafe { self.chan.try_write(buf) };
+            if n > 0 {
+                return Ok(n);
+            }
+
+            self.chan
+                .wait(&self.chan.writers, Channel::is_writable, None);
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+impl Drop for ByteSender {
+    fn drop(&mut self) {
+        self.chan.disconnect();
+    }
+}
+
+impl fmt::Debug for ByteSender {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("ByteSender { .. }")
+    }
+}
+
+/// The receiving side of a byte channel.
+///
+/// Created by the [`byte_channel`] function.
+pub struct ByteReceiver {
+    /// The shared ring buffer.
+    chan: Arc<Channel>,
+}
+
+impl ByteReceiver {
+    /// Returns a receiver that becomes ready when this channel has bytes to read.
+    ///
+    /// The returned receiver yields `()` whenever the channel is not empty, without consuming any
+    /// bytes, and becomes disconnected once the sender is dropped and all bytes have been read.
+    /// Since it is a regular [`Receiver`], it can be used in [`select!`] and [`Select`] to wait for
+    /// bytes together with other operations.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::{Read, Write};
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{byte_channel, select};
+    ///
+    /// let (mut s, mut r) = byte_channel(16);
+    /// let readable = r.readable();
+    ///
+    /// s.write_all(b"abc").unwrap();
+    ///
+    /// select! {
+    ///     recv(readable) -> _ => {
+    ///         let mut buf = [0; 16];
+    ///         assert_eq!(r.read(&mut buf).unwrap(), 3);
+    ///     }
+    ///     default(Duration::from_secs(1)) => panic!(),
+    /// }
+    /// ```
+    ///
+    /// [`Select`]: crate::Select
+    pub fn readable(&self) -> Receiver<()> {
+        Receiver::from_adapter(Arc::new(Readable {
+            chan: self.chan.clone(),
+        }))
+    }
+
+    /// Returns the number of bytes in the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::io::Write;
+    /// use crossbeam_channel::byte_channel;
+    ///
+    /// let (mut s, r) = byte_channel(16);
+    /// s.write_all(b"abc").unwrap();
+    /// assert_eq!(r.len(), 3);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.chan.len()
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns `true` if the channel is full.
+    pub fn is_full(&self) -> bool {
+        self.len() == self.capacity()
+    }
+
+    /// Returns the capacity of the channel in bytes.
+    pub fn capacity(&self) -> usize {
+        self.chan.capacity()
+    }
+}
+
+impl io::Read for ByteReceiver {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if buf.is_empty() {
+            return Ok(0);
+        }
+
+        loop {
+            // Check for disconnection first so that bytes written before it are not missed.
+            let is_disconnected = self.chan.is_disconnected();
+
+            let n = unsafe { self.chan.try_read(buf) };
+            if n > 0 || is_disconnected {
+                return Ok(n);
+            }
+
+            self.chan
+                .wait(&self.chan.readers, Channel::is_readable, None);
+        }
+    }
+}
+
+impl Drop for ByteReceiver {
+    fn drop(&mut self) {
+        self.chan.disconnect();
+    }
+}
+
+impl fmt::Debug for ByteReceiver {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("ByteReceiver { .. }")
+    }
+}
+
+/// Readiness notifications of a byte channel, used by [`ByteReceiver::readable`].
+struct Readable {
+    /// The shared ring buffer.
+    chan: Arc<Channel>,
+}
+
+impl RecvAdapter<()> for Readable {
+    fn try_recv(&self) -> Result<(), TryRecvError> {
+        let token = &mut Token::default();
+        if self.try_select(token) {
+            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    fn recv(&self, deadline: Option<Instant>) -> Result<(), RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            if self.try_select(token) {
+                return unsafe { self.read(token).map_err(|_| RecvTimeoutError::Disconnected) };
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            self.chan
+                .wait(&self.chan.readers, Channel::is_readable, deadline);
+        }
+    }
+
+    unsafe fn read(&self, token: &mut Token) -> Result<(), ()> {
+        if token.adapter {
+            Ok(())
+        } else {
+            Err(())
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.chan.len()
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        Some(self.chan.capacity())
+    }
+
+    fn is_empty(&self) -> bool {
+        self.chan.len() == 0
+    }
+
+    fn is_full(&self) -> bool {
+        self.chan.len() == self.chan.capacity()
+    }
+
+    fn sender_count(&self) -> usize {
+        if self.chan.is_disconnected() {
+            0
+        } else {
+            1
+        }
+    }
+
+    fn shrink_to_fit(&self) {
+        // The ring buffer has a fixed size.
+    }
+
+    fn handle(&self) -> &dyn SelectHandle {
+        self
+    }
+}
+
+impl SelectHandle for Readable {
+    fn try_select(&self, token: &mut Token) -> bool {
+        // Check for disconnection first so that bytes written before it are not missed.
+        let is_disconnected = self.chan.is_disconnected();
+
+        if self.chan.len() > 0 {
+            token.adapter = true;
+            true
+        } else if is_disconnected {
+            token.adapter = false;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        // Readiness is reported to every waiting thread, so register as an observer.
+        self.chan.readers.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.chan.readers.unwatch(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        self.chan.is_readable()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.chan.readers.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.chan.readers.unwatch(oper);
+    }
+}
@@ -0,0 +1,163 @@
+//! Tokens for interrupting blocking operations.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::context::Context;
+use crate::select::{Operation, SelectHandle, Token};
+use crate::waker::SyncWaker;
+
+/// A token that interrupts blocking operations when cancelled.
+///
+/// A token is passed to methods like [`Sender::send_cancellable`] and
+/// [`Receiver::recv_cancellable`], which block until the operation completes or the token gets
+/// cancelled. Clones of a token share the same state, so cancelling any one of them wakes up every
+/// thread blocked on an operation with any of the clones.
+///
+/// Once cancelled, a token stays cancelled forever.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::{unbounded, CancelToken, RecvCancelError};
+///
+/// let (s, r) = unbounded::<i32>();
+/// let token = CancelToken::new();
+///
+/// let t = token.clone();
+/// thread::spawn(move || {
+///     thread::sleep(Duration::from_millis(100));
+///     t.cancel();
+/// });
+///
+/// // Blocks until the token is cancelled because no message is ever sent.
+/// assert_eq!(r.recv_cancellable(&token), Err(RecvCancelError::Cancelled));
+/// # drop(s);
+/// ```
+///
+/// [`Sender::send_cancellable`]: super::Sender::send_cancellable
+/// [`Receiver::recv_cancellable`]: super::Receiver::recv_cancellable
+#[derive(Clone)]
+pub struct CancelToken {
+    inner: Arc<Inner>,
+}
+
+/// Inner representation of `CancelToken`.
+struct Inner {
+    /// `true` if the token has been cancelled.
+    is_cancelled: AtomicBool,
+
+    /// Operations blocked on the token.
+    waker: SyncWaker,
+}
+
+impl CancelToken {
+    /// Creates a new token that is not cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::CancelToken;
+    ///
+    /// let token = CancelToken::new();
+    /// assert!(!token.is_cancelled());
+    /// ```
+    pub fn new() -> CancelToken {
+        CancelToken {
+            inner: Arc::new(Inner {
+                is_cancelled: AtomicBool::new(false),
+                waker: SyncWaker::new(),
+            }),
+        }
+    }
+
+    /// Cancels the token and wakes up all operations blocked on it.
+    ///
+    /// Cancelling an already cancelled token does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::CancelToken;
+    ///
+    /// let token = CancelToken::new();
+    /// token.cancel();
+    /// assert!(token.is_cancelled());
+    /// ```
+    pub fn cancel(&self) {
+        if !self.inner.is_cancelled.swap(true, Ordering::SeqCst) {
+            self.inner.waker.disconnect();
+        }
+    }
+
+    /// Returns `true` if the token has been cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::CancelToken;
+    ///
+    /// let token = CancelToken::new();
+    /// let t = token.clone();
+    ///
+    /// t.cancel();
+    /// assert!(token.is_cancelled());
+    /// ```
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.is_cancelled.load(Ordering::SeqCst)
+    }
+}
+
+impl Default for CancelToken {
+    fn default() -> CancelToken {
+        CancelToken::new()
+    }
+}
+
+impl fmt::Debug for CancelToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CancelToken")
+            .field("is_cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+impl SelectHandle for CancelToken {
+    fn try_select(&self, _token: &mut Token) -> bool {
+        self.is_cancelled()
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.inner.waker.register(oper, cx);
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.inner.waker.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        self.is_cancelled()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.inner.waker.watch(oper, cx);
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.inner.waker.unwatch(oper);
+    }
+}
@@ -1,19 +1,23 @@
 //! The channel interface.
 
 use std::fmt;
+use std::io;
 use std::iter::FusedIterator;
 use std::mem;
 use std::panic::{RefUnwindSafe, UnwindSafe};
 use std::sync::Arc;
+use std::thread;
 use std::time::{Duration, Instant};
 
+use crate::cancel::CancelToken;
 use crate::context::Context;
 use crate::counter;
 use crate::err::{
-    RecvError, RecvTimeoutError, SendError, SendTimeoutError, TryRecvError, TrySendError,
+    RecvCancelError, RecvError, RecvTimeoutError, SendCancelError, SendError, SendTimeoutError,
+    TryRecvError, TrySendError,
 };
 use crate::flavors;
-use crate::select::{Operation, SelectHandle, Token};
+use crate::select::{self, Operation, SelectHandle, Token};
 
 /// Creates a channel of unbounded capacity.
 ///
@@ -53,10 +57,56 @@ pub fn unbounded<T>() -> (Sender<T>, Receiver<T>) {
     (s, r)
 }
 
+/// Creates a channel of unbounded capacity that allocates its buffer in blocks of a given size.
+///
+/// An unbounded channel stores messages in a linked list of blocks, each holding up to 31 messages
+/// by default. Larger blocks mean fewer allocations when many messages are sent in a burst, while
+/// smaller blocks waste less memory when the channel mostly holds a few messages.
+///
+/// Each block holds at least `block_size` messages. The actual size is rounded up to one less than
+/// a power of two, since one index per block is reserved for linking blocks together.
+///
+/// Regardless of the block size, the channel keeps a few freed blocks around and reuses them, so
+/// repeatedly filling and draining the channel doesn't allocate a new block every time.
+///
+/// # Panics
+///
+/// Panics if `block_size` is zero or too large.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::unbounded_with_block_size;
+///
+/// // Allocate space for 255 messages at a time.
+/// let (s, r) = unbounded_with_block_size(255);
+///
+/// for i in 0..1000 {
+///     s.send(i).unwrap();
+/// }
+/// assert_eq!(r.len(), 1000);
+/// assert_eq!(r.iter().take(3).collect::<Vec<_>>(), [0, 1, 2]);
+/// ```
+pub fn unbounded_with_block_size<T>(block_size: usize) -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::list::Channel::with_block_cap(block_size));
+    let s = Sender {
+        flavor: SenderFlavor::List(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::List(r),
+    };
+    (s, r)
+}
+
 /// Creates a channel of bounded capacity.
 ///
 /// This channel has a buffer that can hold at most `cap` messages at a time.
 ///
+/// The buffer of a channel with a large capacity is allocated in chunks, each one the first time
+/// it is needed, so a generous bound doesn't cost memory until it is used. Note that messages go
+/// around the buffer in order, so the whole buffer gets allocated once `cap` messages have been
+/// sent, even if the channel never holds that many at a time.
+///
 /// A special case is zero-capacity channel, which cannot hold any messages. Instead, send and
 /// receive operations must appear at the same time in order to pair up and pass the message over.
 ///
@@ -125,6 +175,162 @@ pub fn bounded<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
     }
 }
 
+/// Creates a channel of bounded capacity that services blocked senders in FIFO order.
+///
+/// This channel behaves like one created by [`bounded`], except that when several senders are
+/// blocked because the channel is full, they send their messages in the order in which they
+/// started waiting. Senders that arrive later never overtake them, so every sender makes progress
+/// even when the channel is constantly full. The price is some extra synchronization on every
+/// send operation.
+///
+/// Non-blocking [`try_send`] fails with [`TrySendError::Full`] while other senders are waiting
+/// for their turn. Send operations inside [`select!`] don't wait in line and may overtake blocked
+/// senders.
+///
+/// Zero-capacity channels already pair blocked senders with receivers in FIFO order, so with
+/// `cap` of zero this function is equivalent to [`bounded`].
+///
+/// [`try_send`]: Sender::try_send
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use std::time::Duration;
+/// use crossbeam_channel::bounded_fair;
+///
+/// let (s, r) = bounded_fair(1);
+/// s.send(0).unwrap();
+///
+/// // Senders block one after another, and get to send in the same order.
+/// for i in 1..4 {
+///     let s = s.clone();
+///     thread::spawn(move || s.send(i).unwrap());
+///     thread::sleep(Duration::from_millis(100));
+/// }
+///
+/// assert_eq!(r.iter().take(4).collect::<Vec<_>>(), [0, 1, 2, 3]);
+/// ```
+pub fn bounded_fair<T>(cap: usize) -> (Sender<T>, Receiver<T>) {
+    if cap == 0 {
+        bounded(0)
+    } else {
+        let (s, r) = counter::new(flavors::array::Channel::with_capacity_fair(cap));
+        let s = Sender {
+            flavor: SenderFlavor::Array(s),
+        };
+        let r = Receiver {
+            flavor: ReceiverFlavor::Array(r),
+        };
+        (s, r)
+    }
+}
+
+/// Creates a channel of bounded capacity that is split into multiple lanes.
+///
+/// This channel behaves like one created by [`bounded`], but is designed for many threads sending
+/// messages at the same time. The capacity is divided as evenly as possible among `lanes` smaller
+/// channels, and each sending thread always sends into the same lane, so concurrent senders mostly
+/// don't contend with each other. Receivers take messages from all lanes.
+///
+/// Messages sent by a single thread are received in the order they were sent, but messages sent
+/// by different threads may be reordered, even if one message was sent before the other. A send
+/// operation blocks when the sending thread's lane is full, even if other lanes have free space.
+///
+/// If `cap` is smaller than `lanes`, the channel will have `cap` lanes of capacity one.
+///
+/// # Panics
+///
+/// Panics if `cap` or `lanes` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::bounded_with_lanes;
+///
+/// let (s, r) = bounded_with_lanes(64, 8);
+/// assert_eq!(s.capacity(), Some(64));
+///
+/// for i in 0..8 {
+///     let s = s.clone();
+///     thread::spawn(move || {
+///         for j in 0..100 {
+///             s.send(i * 100 + j).unwrap();
+///         }
+///     });
+/// }
+/// drop(s);
+///
+/// let mut v = r.iter().collect::<Vec<_>>();
+/// v.sort();
+/// assert_eq!(v, (0..800).collect::<Vec<_>>());
+/// ```
+pub fn bounded_with_lanes<T>(cap: usize, lanes: usize) -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::lanes::Channel::with_capacity(cap, lanes));
+    let s = Sender {
+        flavor: SenderFlavor::Lanes(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Lanes(r),
+    };
+    (s, r)
+}
+
+/// Creates a channel of unbounded capacity that is split into multiple shards.
+///
+/// This channel behaves like one created by [`unbounded`], but is designed for many threads
+/// sending messages to a single consumer, as in logging or telemetry pipelines. Messages are kept
+/// in `shards` separate queues, and each sending thread always sends into the same shard, so
+/// concurrent senders mostly don't contend with each other. Receivers merge messages from all
+/// shards, taking turns between them.
+///
+/// A good choice for `shards` is the number of CPU cores or the number of sending threads.
+///
+/// Messages sent by a single thread are received in the order they were sent, but messages sent
+/// by different threads may be reordered, even if one message was sent before the other.
+///
+/// # Panics
+///
+/// Panics if `shards` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::unbounded_sharded;
+///
+/// let (s, r) = unbounded_sharded(4);
+///
+/// for i in 0..4 {
+///     let s = s.clone();
+///     thread::spawn(move || {
+///         for j in 0..100 {
+///             s.send((i, j)).unwrap();
+///         }
+///     });
+/// }
+/// drop(s);
+///
+/// let mut next = [0; 4];
+/// for (i, j) in r.iter() {
+///     // Messages from the same thread arrive in order.
+///     assert_eq!(next[i], j);
+///     next[i] += 1;
+/// }
+/// assert_eq!(next, [100; 4]);
+/// ```
+pub fn unbounded_sharded<T>(shards: usize) -> (Sender<T>, Receiver<T>) {
+    let (s, r) = counter::new(flavors::sharded::Channel::new(shards));
+    let s = Sender {
+        flavor: SenderFlavor::Sharded(s),
+    };
+    let r = Receiver {
+        flavor: ReceiverFlavor::Sharded(r),
+    };
+    (s, r)
+}
+
 /// Creates a receiver that delivers a message after a certain duration of time.
 ///
 /// The channel is bounded with capacity of 1 and never gets disconnected. Exactly one message will
@@ -322,6 +528,106 @@ pub fn tick(duration: Duration) -> Receiver<Instant> {
     }
 }
 
+/// Creates a receiver that delivers lines read from the standard input.
+///
+/// A background thread reads lines from the standard input and sends them into a zero-capacity
+/// channel. Each message is a line without the trailing newline (`\n` or `\r\n`). The channel
+/// gets disconnected when the end of input is reached or reading fails.
+///
+/// The background thread exits when the input ends or, after all receivers are dropped, as soon
+/// as it reads the next line. Lines are read on demand, so at most one line is read ahead of the
+/// receiver. Each call spawns its own thread, so it is best to call this function once and clone
+/// the returned receiver if needed.
+///
+/// The standard input is locked only while a line is being read. To read lines from another
+/// source, use [`lines`].
+///
+/// # Examples
+///
+/// Handling user input while periodically printing elapsed time:
+///
+/// ```no_run
+/// use std::time::{Duration, Instant};
+/// use crossbeam_channel::{select, stdin_lines, tick};
+///
+/// let start = Instant::now();
+/// let ticker = tick(Duration::from_secs(1));
+/// let input = stdin_lines();
+///
+/// loop {
+///     select! {
+///         recv(ticker) -> _ => println!("elapsed: {:?}", start.elapsed()),
+///         recv(input) -> line => match line {
+///             Ok(line) => println!("read: {}", line),
+///             Err(_) => break,
+///         },
+///     }
+/// }
+/// ```
+pub fn stdin_lines() -> Receiver<String> {
+    let stdin = io::stdin();
+    spawn_line_reader("crossbeam-channel-stdin", move |line| stdin.read_line(line))
+}
+
+/// Creates a receiver that delivers lines read from `reader`.
+///
+/// This works just like [`stdin_lines`], except that a background thread takes ownership of
+/// `reader` and reads lines from it. The reader is dropped when the background thread exits.
+///
+/// # Examples
+///
+/// ```
+/// use std::io::Cursor;
+/// use crossbeam_channel::lines;
+///
+/// let r = lines(Cursor::new("first\nsecond\r\nthird"));
+///
+/// assert_eq!(r.recv(), Ok("first".to_string()));
+/// assert_eq!(r.recv(), Ok("second".to_string()));
+/// assert_eq!(r.recv(), Ok("third".to_string()));
+/// assert!(r.recv().is_err());
+/// ```
+pub fn lines<R>(mut reader: R) -> Receiver<String>
+where
+    R: io::BufRead + Send + 'static,
+{
+    spawn_line_reader("crossbeam-channel-lines", move |line| {
+        reader.read_line(line)
+    })
+}
+
+/// Spawns a thread that sends lines produced by `read_line` into a zero-capacity channel.
+fn spawn_line_reader<F>(name: &str, mut read_line: F) -> Receiver<String>
+where
+    F: FnMut(&mut String) -> io::Result<usize> + Send + 'static,
+{
+    let (s, r) = bounded(0);
+
+    thread::Builder::new()
+        .name(name.to_string())
+        .spawn(move || loop {
+            let mut line = String::new();
+            match read_line(&mut line) {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {}
+            }
+
+            if line.ends_with('\n') {
+                line.pop();
+                if line.ends_with('\r') {
+                    line.pop();
+                }
+            }
+
+            if s.send(line).is_err() {
+                break;
+            }
+        })
+        .expect("failed to spawn the line reader thread");
+
+    r
+}
+
 /// The sending side of a channel.
 ///
 /// # Examples
@@ -350,11 +656,20 @@ enum SenderFlavor<T> {
     /// Bounded channel based on a preallocated array.
     Array(counter::Sender<flavors::array::Channel<T>>),
 
+    /// Bounded channel split into multiple lanes.
+    Lanes(counter::Sender<flavors::lanes::Channel<T>>),
+
+    /// Unbounded channel split into multiple shards.
+    Sharded(counter::Sender<flavors::sharded::Channel<T>>),
+
     /// Unbounded channel implemented as a linked list.
     List(counter::Sender<flavors::list::Channel<T>>),
 
     /// Zero-capacity channel.
     Zero(counter::Sender<flavors::zero::Channel<T>>),
+
+    /// Adapter that transforms messages before sending them into another channel.
+    Adapter(Arc<dyn flavors::adapter::SendAdapter<T>>),
 }
 
 unsafe impl<T: Send> Send for Sender<T> {}
@@ -388,8 +703,11 @@ impl<T> Sender<T> {
     pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.try_send(msg),
+            SenderFlavor::Lanes(chan) => chan.try_send(msg),
+            SenderFlavor::Sharded(chan) => chan.try_send(msg),
             SenderFlavor::List(chan) => chan.try_send(msg),
             SenderFlavor::Zero(chan) => chan.try_send(msg),
+            SenderFlavor::Adapter(chan) => chan.try_send(msg),
         }
     }
 
@@ -424,8 +742,11 @@ impl<T> Sender<T> {
     pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.send(msg, None),
+            SenderFlavor::Lanes(chan) => chan.send(msg, None),
+            SenderFlavor::Sharded(chan) => chan.send(msg, None),
             SenderFlavor::List(chan) => chan.send(msg, None),
             SenderFlavor::Zero(chan) => chan.send(msg, None),
+            SenderFlavor::Adapter(chan) => chan.send(msg, None),
         }
         .map_err(|err| match err {
             SendTimeoutError::Disconnected(msg) => SendError(msg),
@@ -516,8 +837,64 @@ impl<T> Sender<T> {
     pub fn send_deadline(&self, msg: T, deadline: Instant) -> Result<(), SendTimeoutError<T>> {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.send(msg, Some(deadline)),
+            SenderFlavor::Lanes(chan) => chan.send(msg, Some(deadline)),
+            SenderFlavor::Sharded(chan) => chan.send(msg, Some(deadline)),
             SenderFlavor::List(chan) => chan.send(msg, Some(deadline)),
             SenderFlavor::Zero(chan) => chan.send(msg, Some(deadline)),
+            SenderFlavor::Adapter(chan) => chan.send(msg, Some(deadline)),
+        }
+    }
+
+    /// Blocks the current thread until a message is sent, the channel is disconnected, or the
+    /// token is cancelled.
+    ///
+    /// If the channel is full and not disconnected, this call will block until the send operation
+    /// can proceed or the token gets cancelled. If the channel becomes disconnected, this call will
+    /// wake up and return an error. The returned error contains the original message.
+    ///
+    /// If the token is already cancelled, this call returns an error without attempting to send
+    /// the message.
+    ///
+    /// If called on a zero-capacity channel, this method will wait for a receive operation to
+    /// appear on the other side of the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{bounded, CancelToken, SendCancelError};
+    ///
+    /// let (s, r) = bounded(1);
+    /// let token = CancelToken::new();
+    /// assert_eq!(s.send_cancellable(1, &token), Ok(()));
+    ///
+    /// let t = token.clone();
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     t.cancel();
+    /// });
+    ///
+    /// // The channel is full, so this call blocks until the token is cancelled.
+    /// assert_eq!(s.send_cancellable(2, &token), Err(SendCancelError::Cancelled(2)));
+    /// assert_eq!(r.recv(), Ok(1));
+    /// ```
+    pub fn send_cancellable(&self, msg: T, token: &CancelToken) -> Result<(), SendCancelError<T>> {
+        if token.is_cancelled() {
+            return Err(SendCancelError::Cancelled(msg));
+        }
+
+        let mut handles: [(&dyn SelectHandle, usize, *const u8); 2] = [
+            (self, 0, self as *const Sender<T> as *const u8),
+            (token, 1, token as *const CancelToken as *const u8),
+        ];
+        let oper = select::select(&mut handles);
+
+        if oper.index() == 0 {
+            oper.send(self, msg).map_err(SendCancelError::from)
+        } else {
+            oper.complete();
+            Err(SendCancelError::Cancelled(msg))
         }
     }
 
@@ -539,8 +916,11 @@ impl<T> Sender<T> {
     pub fn is_empty(&self) -> bool {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.is_empty(),
+            SenderFlavor::Lanes(chan) => chan.is_empty(),
+            SenderFlavor::Sharded(chan) => chan.is_empty(),
             SenderFlavor::List(chan) => chan.is_empty(),
             SenderFlavor::Zero(chan) => chan.is_empty(),
+            SenderFlavor::Adapter(chan) => chan.is_empty(),
         }
     }
 
@@ -562,8 +942,11 @@ impl<T> Sender<T> {
     pub fn is_full(&self) -> bool {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.is_full(),
+            SenderFlavor::Lanes(chan) => chan.is_full(),
+            SenderFlavor::Sharded(chan) => chan.is_full(),
             SenderFlavor::List(chan) => chan.is_full(),
             SenderFlavor::Zero(chan) => chan.is_full(),
+            SenderFlavor::Adapter(chan) => chan.is_full(),
         }
     }
 
@@ -584,8 +967,11 @@ impl<T> Sender<T> {
     pub fn len(&self) -> usize {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.len(),
+            SenderFlavor::Lanes(chan) => chan.len(),
+            SenderFlavor::Sharded(chan) => chan.len(),
             SenderFlavor::List(chan) => chan.len(),
             SenderFlavor::Zero(chan) => chan.len(),
+            SenderFlavor::Adapter(chan) => chan.len(),
         }
     }
 
@@ -608,8 +994,11 @@ impl<T> Sender<T> {
     pub fn capacity(&self) -> Option<usize> {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.capacity(),
+            SenderFlavor::Lanes(chan) => chan.capacity(),
+            SenderFlavor::Sharded(chan) => chan.capacity(),
             SenderFlavor::List(chan) => chan.capacity(),
             SenderFlavor::Zero(chan) => chan.capacity(),
+            SenderFlavor::Adapter(chan) => chan.capacity(),
         }
     }
 
@@ -631,11 +1020,123 @@ impl<T> Sender<T> {
     pub fn same_channel(&self, other: &Sender<T>) -> bool {
         match (&self.flavor, &other.flavor) {
             (SenderFlavor::Array(ref a), SenderFlavor::Array(ref b)) => a == b,
+            (SenderFlavor::Lanes(ref a), SenderFlavor::Lanes(ref b)) => a == b,
+            (SenderFlavor::Sharded(ref a), SenderFlavor::Sharded(ref b)) => a == b,
             (SenderFlavor::List(ref a), SenderFlavor::List(ref b)) => a == b,
             (SenderFlavor::Zero(ref a), SenderFlavor::Zero(ref b)) => a == b,
+            (SenderFlavor::Adapter(a), SenderFlavor::Adapter(b)) => {
+                &**a as *const _ as *const u8 == &**b as *const _ as *const u8
+            }
             _ => false,
         }
     }
+
+    /// Returns `true` if the sender and the receiver belong to the same channel.
+    ///
+    /// Senders returned by [`Sender::with`] and receivers returned by [`Receiver::map`] or
+    /// [`Receiver::filter`] are separate handles, so they never belong to the same channel as
+    /// another sender or receiver.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded::<usize>();
+    /// assert!(s.same_channel_as_receiver(&r));
+    ///
+    /// let (_, r2) = unbounded();
+    /// assert!(!s.same_channel_as_receiver(&r2));
+    /// ```
+    pub fn same_channel_as_receiver(&self, receiver: &Receiver<T>) -> bool {
+        match (&self.flavor, &receiver.flavor) {
+            (SenderFlavor::Array(a), ReceiverFlavor::Array(b)) => a == b,
+            (SenderFlavor::Lanes(a), ReceiverFlavor::Lanes(b)) => a == b,
+            (SenderFlavor::Sharded(a), ReceiverFlavor::Sharded(b)) => a == b,
+            (SenderFlavor::List(a), ReceiverFlavor::List(b)) => a == b,
+            (SenderFlavor::Zero(a), ReceiverFlavor::Zero(b)) => a == b,
+            _ => false,
+        }
+    }
+
+    /// Returns the number of receivers associated with the channel.
+    ///
+    /// This can be used to stop producing messages when nobody is listening, without waiting for a
+    /// send operation to fail. Note that the count may change at any moment if receivers are
+    /// cloned or dropped on other threads.
+    ///
+    /// All clones of a receiver returned by [`Receiver::map`] or [`Receiver::filter`] count as a
+    /// single receiver.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// assert_eq!(s.receiver_count(), 1);
+    ///
+    /// let r2 = r.clone();
+    /// assert_eq!(s.receiver_count(), 2);
+    ///
+    /// drop(r);
+    /// drop(r2);
+    /// assert_eq!(s.receiver_count(), 0);
+    /// ```
+    pub fn receiver_count(&self) -> usize {
+        match &self.flavor {
+            SenderFlavor::Array(chan) => chan.receiver_count(),
+            SenderFlavor::Lanes(chan) => chan.receiver_count(),
+            SenderFlavor::Sharded(chan) => chan.receiver_count(),
+            SenderFlavor::List(chan) => chan.receiver_count(),
+            SenderFlavor::Zero(chan) => chan.receiver_count(),
+            SenderFlavor::Adapter(chan) => chan.receiver_count(),
+        }
+    }
+
+    /// Returns a sender that transforms messages with `f` before sending them into this channel.
+    ///
+    /// The returned sender is a regular [`Sender`], so it can be used in [`select!`] and
+    /// [`Select`] without a forwarding thread. The function is applied only once the message is
+    /// certain to be sent, so failed send operations return the original message.
+    ///
+    /// The returned sender shares the channel with this one, and its methods like [`len`] and
+    /// [`capacity`] report the state of the channel.
+    ///
+    /// [`Select`]: crate::Select
+    /// [`len`]: Sender::len
+    /// [`capacity`]: Sender::capacity
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, TrySendError};
+    ///
+    /// let (s, r) = bounded(1);
+    /// let s = s.with(|x: i32| x.to_string());
+    ///
+    /// assert_eq!(s.try_send(1), Ok(()));
+    /// assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    /// assert_eq!(r.recv(), Ok("1".to_string()));
+    /// ```
+    pub fn with<U, F>(self, f: F) -> Sender<U>
+    where
+        T: Send + 'static,
+        F: Fn(U) -> T + Send + Sync + 'static,
+    {
+        Sender {
+            flavor: SenderFlavor::Adapter(Arc::new(flavors::adapter::With::new(self, f))),
+        }
+    }
+}
+
+impl<T> Sender<T> {
+    /// Creates a sender backed by an adapter.
+    pub(crate) fn from_adapter(chan: Arc<dyn flavors::adapter::SendAdapter<T>>) -> Sender<T> {
+        Sender {
+            flavor: SenderFlavor::Adapter(chan),
+        }
+    }
 }
 
 impl<T> Drop for Sender<T> {
@@ -643,8 +1144,11 @@ impl<T> Drop for Sender<T> {
         unsafe {
             match &self.flavor {
                 SenderFlavor::Array(chan) => chan.release(|c| c.disconnect()),
+                SenderFlavor::Lanes(chan) => chan.release(|c| c.disconnect()),
+                SenderFlavor::Sharded(chan) => chan.release(|c| c.disconnect()),
                 SenderFlavor::List(chan) => chan.release(|c| c.disconnect()),
                 SenderFlavor::Zero(chan) => chan.release(|c| c.disconnect()),
+                SenderFlavor::Adapter(_) => {}
             }
         }
     }
@@ -654,8 +1158,11 @@ impl<T> Clone for Sender<T> {
     fn clone(&self) -> Self {
         let flavor = match &self.flavor {
             SenderFlavor::Array(chan) => SenderFlavor::Array(chan.acquire()),
+            SenderFlavor::Lanes(chan) => SenderFlavor::Lanes(chan.acquire()),
+            SenderFlavor::Sharded(chan) => SenderFlavor::Sharded(chan.acquire()),
             SenderFlavor::List(chan) => SenderFlavor::List(chan.acquire()),
             SenderFlavor::Zero(chan) => SenderFlavor::Zero(chan.acquire()),
+            SenderFlavor::Adapter(chan) => SenderFlavor::Adapter(chan.clone()),
         };
 
         Sender { flavor }
@@ -697,6 +1204,12 @@ enum ReceiverFlavor<T> {
     /// Bounded channel based on a preallocated array.
     Array(counter::Receiver<flavors::array::Channel<T>>),
 
+    /// Bounded channel split into multiple lanes.
+    Lanes(counter::Receiver<flavors::lanes::Channel<T>>),
+
+    /// Unbounded channel split into multiple shards.
+    Sharded(counter::Receiver<flavors::sharded::Channel<T>>),
+
     /// Unbounded channel implemented as a linked list.
     List(counter::Receiver<flavors::list::Channel<T>>),
 
@@ -711,6 +1224,9 @@ enum ReceiverFlavor<T> {
 
     /// The never flavor.
     Never(flavors::never::Channel<T>),
+
+    /// Adapter that transforms messages received from another channel.
+    Adapter(Arc<dyn flavors::adapter::RecvAdapter<T>>),
 }
 
 unsafe impl<T: Send> Send for Receiver<T> {}
@@ -745,6 +1261,8 @@ impl<T> Receiver<T> {
     pub fn try_recv(&self) -> Result<T, TryRecvError> {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.try_recv(),
+            ReceiverFlavor::Lanes(chan) => chan.try_recv(),
+            ReceiverFlavor::Sharded(chan) => chan.try_recv(),
             ReceiverFlavor::List(chan) => chan.try_recv(),
             ReceiverFlavor::Zero(chan) => chan.try_recv(),
             ReceiverFlavor::At(chan) => {
@@ -764,6 +1282,7 @@ impl<T> Receiver<T> {
                 }
             }
             ReceiverFlavor::Never(chan) => chan.try_recv(),
+            ReceiverFlavor::Adapter(chan) => chan.try_recv(),
         }
     }
 
@@ -798,6 +1317,8 @@ impl<T> Receiver<T> {
     pub fn recv(&self) -> Result<T, RecvError> {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.recv(None),
+            ReceiverFlavor::Lanes(chan) => chan.recv(None),
+            ReceiverFlavor::Sharded(chan) => chan.recv(None),
             ReceiverFlavor::List(chan) => chan.recv(None),
             ReceiverFlavor::Zero(chan) => chan.recv(None),
             ReceiverFlavor::At(chan) => {
@@ -819,6 +1340,7 @@ impl<T> Receiver<T> {
                 }
             }
             ReceiverFlavor::Never(chan) => chan.recv(None),
+            ReceiverFlavor::Adapter(chan) => chan.recv(None),
         }
         .map_err(|_| RecvError)
     }
@@ -906,6 +1428,8 @@ impl<T> Receiver<T> {
     pub fn recv_deadline(&self, deadline: Instant) -> Result<T, RecvTimeoutError> {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::Lanes(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::Sharded(chan) => chan.recv(Some(deadline)),
             ReceiverFlavor::List(chan) => chan.recv(Some(deadline)),
             ReceiverFlavor::Zero(chan) => chan.recv(Some(deadline)),
             ReceiverFlavor::At(chan) => {
@@ -927,6 +1451,59 @@ impl<T> Receiver<T> {
                 }
             }
             ReceiverFlavor::Never(chan) => chan.recv(Some(deadline)),
+            ReceiverFlavor::Adapter(chan) => chan.recv(Some(deadline)),
+        }
+    }
+
+    /// Blocks the current thread until a message is received, the channel is empty and
+    /// disconnected, or the token is cancelled.
+    ///
+    /// If the channel is empty and not disconnected, this call will block until the receive
+    /// operation can proceed or the token gets cancelled. If the channel is empty and becomes
+    /// disconnected, this call will wake up and return an error.
+    ///
+    /// If the token is already cancelled, this call returns an error without attempting to
+    /// receive a message.
+    ///
+    /// If called on a zero-capacity channel, this method will wait for a send operation to appear
+    /// on the other side of the channel.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, CancelToken, RecvCancelError};
+    ///
+    /// let (s, r) = unbounded();
+    /// let token = CancelToken::new();
+    /// s.send(5).unwrap();
+    ///
+    /// let t = token.clone();
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     t.cancel();
+    /// });
+    ///
+    /// assert_eq!(r.recv_cancellable(&token), Ok(5));
+    /// assert_eq!(r.recv_cancellable(&token), Err(RecvCancelError::Cancelled));
+    /// ```
+    pub fn recv_cancellable(&self, token: &CancelToken) -> Result<T, RecvCancelError> {
+        if token.is_cancelled() {
+            return Err(RecvCancelError::Cancelled);
+        }
+
+        let mut handles: [(&dyn SelectHandle, usize, *const u8); 2] = [
+            (self, 0, self as *const Receiver<T> as *const u8),
+            (token, 1, token as *const CancelToken as *const u8),
+        ];
+        let oper = select::select(&mut handles);
+
+        if oper.index() == 0 {
+            oper.recv(self).map_err(RecvCancelError::from)
+        } else {
+            oper.complete();
+            Err(RecvCancelError::Cancelled)
         }
     }
 
@@ -948,11 +1525,14 @@ impl<T> Receiver<T> {
     pub fn is_empty(&self) -> bool {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.is_empty(),
+            ReceiverFlavor::Lanes(chan) => chan.is_empty(),
+            ReceiverFlavor::Sharded(chan) => chan.is_empty(),
             ReceiverFlavor::List(chan) => chan.is_empty(),
             ReceiverFlavor::Zero(chan) => chan.is_empty(),
             ReceiverFlavor::At(chan) => chan.is_empty(),
             ReceiverFlavor::Tick(chan) => chan.is_empty(),
             ReceiverFlavor::Never(chan) => chan.is_empty(),
+            ReceiverFlavor::Adapter(chan) => chan.is_empty(),
         }
     }
 
@@ -974,11 +1554,14 @@ impl<T> Receiver<T> {
     pub fn is_full(&self) -> bool {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.is_full(),
+            ReceiverFlavor::Lanes(chan) => chan.is_full(),
+            ReceiverFlavor::Sharded(chan) => chan.is_full(),
             ReceiverFlavor::List(chan) => chan.is_full(),
             ReceiverFlavor::Zero(chan) => chan.is_full(),
             ReceiverFlavor::At(chan) => chan.is_full(),
             ReceiverFlavor::Tick(chan) => chan.is_full(),
             ReceiverFlavor::Never(chan) => chan.is_full(),
+            ReceiverFlavor::Adapter(chan) => chan.is_full(),
         }
     }
 
@@ -999,11 +1582,14 @@ impl<T> Receiver<T> {
     pub fn len(&self) -> usize {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.len(),
+            ReceiverFlavor::Lanes(chan) => chan.len(),
+            ReceiverFlavor::Sharded(chan) => chan.len(),
             ReceiverFlavor::List(chan) => chan.len(),
             ReceiverFlavor::Zero(chan) => chan.len(),
             ReceiverFlavor::At(chan) => chan.len(),
             ReceiverFlavor::Tick(chan) => chan.len(),
             ReceiverFlavor::Never(chan) => chan.len(),
+            ReceiverFlavor::Adapter(chan) => chan.len(),
         }
     }
 
@@ -1026,11 +1612,14 @@ impl<T> Receiver<T> {
     pub fn capacity(&self) -> Option<usize> {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.capacity(),
+            ReceiverFlavor::Lanes(chan) => chan.capacity(),
+            ReceiverFlavor::Sharded(chan) => chan.capacity(),
             ReceiverFlavor::List(chan) => chan.capacity(),
             ReceiverFlavor::Zero(chan) => chan.capacity(),
             ReceiverFlavor::At(chan) => chan.capacity(),
             ReceiverFlavor::Tick(chan) => chan.capacity(),
             ReceiverFlavor::Never(chan) => chan.capacity(),
+            ReceiverFlavor::Adapter(chan) => chan.capacity(),
         }
     }
 
@@ -1120,14 +1709,202 @@ impl<T> Receiver<T> {
     pub fn same_channel(&self, other: &Receiver<T>) -> bool {
         match (&self.flavor, &other.flavor) {
             (ReceiverFlavor::Array(a), ReceiverFlavor::Array(b)) => a == b,
+            (ReceiverFlavor::Lanes(a), ReceiverFlavor::Lanes(b)) => a == b,
+            (ReceiverFlavor::Sharded(a), ReceiverFlavor::Sharded(b)) => a == b,
             (ReceiverFlavor::List(a), ReceiverFlavor::List(b)) => a == b,
             (ReceiverFlavor::Zero(a), ReceiverFlavor::Zero(b)) => a == b,
             (ReceiverFlavor::At(a), ReceiverFlavor::At(b)) => Arc::ptr_eq(a, b),
             (ReceiverFlavor::Tick(a), ReceiverFlavor::Tick(b)) => Arc::ptr_eq(a, b),
             (ReceiverFlavor::Never(_), ReceiverFlavor::Never(_)) => true,
+            (ReceiverFlavor::Adapter(a), ReceiverFlavor::Adapter(b)) => {
+                &**a as *const _ as *const u8 == &**b as *const _ as *const u8
+            }
             _ => false,
         }
     }
+
+    /// Returns `true` if the receiver and the sender belong to the same channel.
+    ///
+    /// This is the same as [`Sender::same_channel_as_receiver`]. Channels created by [`after`],
+    /// [`at`], [`tick`] and [`never`] have no senders, so this method always returns `false` for
+    /// them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded::<usize>();
+    /// assert!(r.same_channel_as_sender(&s));
+    ///
+    /// let (s2, _) = unbounded();
+    /// assert!(!r.same_channel_as_sender(&s2));
+    /// ```
+    pub fn same_channel_as_sender(&self, sender: &Sender<T>) -> bool {
+        sender.same_channel_as_receiver(self)
+    }
+
+    /// Returns the number of senders associated with the channel.
+    ///
+    /// Note that the count may change at any moment if senders are cloned or dropped on other
+    /// threads.
+    ///
+    /// All clones of a sender returned by [`Sender::with`] count as a single sender. Channels
+    /// created by [`after`], [`at`], [`tick`] and [`never`] have no senders, so this method
+    /// always returns 0 for them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// assert_eq!(r.sender_count(), 1);
+    ///
+    /// let s2 = s.clone();
+    /// assert_eq!(r.sender_count(), 2);
+    ///
+    /// drop(s);
+    /// drop(s2);
+    /// assert_eq!(r.sender_count(), 0);
+    /// ```
+    pub fn sender_count(&self) -> usize {
+        match &self.flavor {
+            ReceiverFlavor::Array(chan) => chan.sender_count(),
+            ReceiverFlavor::Lanes(chan) => chan.sender_count(),
+            ReceiverFlavor::Sharded(chan) => chan.sender_count(),
+            ReceiverFlavor::List(chan) => chan.sender_count(),
+            ReceiverFlavor::Zero(chan) => chan.sender_count(),
+            ReceiverFlavor::At(_) | ReceiverFlavor::Tick(_) | ReceiverFlavor::Never(_) => 0,
+            ReceiverFlavor::Adapter(chan) => chan.sender_count(),
+        }
+    }
+
+    /// Releases memory that the channel keeps around for reuse.
+    ///
+    /// Unbounded channels keep a few blocks of freed slots around, so that repeatedly filling and
+    /// draining the channel doesn't allocate every time. Zero-capacity channels similarly cache a
+    /// few packets used by selection. This method deallocates them, which is useful for
+    /// long-lived channels that only see rare bursts of messages. Memory holding messages that
+    /// are still in the channel is never released.
+    ///
+    /// Bounded channels keep their buffer until they are dropped, including the chunks of a large
+    /// buffer that were allocated on demand, so this method does nothing for them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    ///
+    /// // A burst of messages that is drained right away.
+    /// for i in 0..1000 {
+    ///     s.send(i).unwrap();
+    /// }
+    /// assert_eq!(r.try_iter().count(), 1000);
+    ///
+    /// r.shrink_to_fit();
+    ///
+    /// s.send(1000).unwrap();
+    /// assert_eq!(r.recv(), Ok(1000));
+    /// ```
+    pub fn shrink_to_fit(&self) {
+        match &self.flavor {
+            ReceiverFlavor::Sharded(chan) => chan.shrink_to_fit(),
+            ReceiverFlavor::List(chan) => chan.shrink_to_fit(),
+            ReceiverFlavor::Zero(chan) => chan.shrink_to_fit(),
+            ReceiverFlavor::Adapter(chan) => chan.shrink_to_fit(),
+            ReceiverFlavor::Array(_)
+            | ReceiverFlavor::Lanes(_)
+            | ReceiverFlavor::At(_)
+            | ReceiverFlavor::Tick(_)
+            | ReceiverFlavor::Never(_) => {}
+        }
+    }
+
+    /// Returns a receiver that transforms received messages with `f`.
+    ///
+    /// The returned receiver is a regular [`Receiver`], so it can be used in [`select!`] and
+    /// [`Select`] without a forwarding thread and an extra channel.
+    ///
+    /// The returned receiver shares the channel with this one, and its methods like [`len`] and
+    /// [`capacity`] report the state of the channel.
+    ///
+    /// [`Select`]: crate::Select
+    /// [`len`]: Receiver::len
+    /// [`capacity`]: Receiver::capacity
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{select, unbounded};
+    ///
+    /// let (s1, r1) = unbounded::<i32>();
+    /// let (s2, r2) = unbounded::<&str>();
+    ///
+    /// let r1 = r1.map(|x| x.to_string());
+    /// let r2 = r2.map(|x| x.to_string());
+    ///
+    /// s1.send(7).unwrap();
+    /// drop(s2);
+    ///
+    /// select! {
+    ///     recv(r1) -> msg => assert_eq!(msg, Ok("7".to_string())),
+    ///     recv(r2) -> msg => assert!(msg.is_err()),
+    /// }
+    /// ```
+    pub fn map<U, F>(self, f: F) -> Receiver<U>
+    where
+        T: Send + 'static,
+        F: Fn(T) -> U + Send + Sync + 'static,
+    {
+        Receiver {
+            flavor: ReceiverFlavor::Adapter(Arc::new(flavors::adapter::Map::new(self, f))),
+        }
+    }
+
+    /// Returns a receiver that only receives messages matching `predicate`.
+    ///
+    /// Messages not matching the predicate are received from the channel and dropped. The returned
+    /// receiver is a regular [`Receiver`], so it can be used in [`select!`] and [`Select`] without
+    /// a forwarding thread and an extra channel. However, a receive operation might be reported as
+    /// ready even though all remaining messages will be dropped by the predicate.
+    ///
+    /// The returned receiver shares the channel with this one, and its methods like [`len`] and
+    /// [`capacity`] report the state of the channel, including messages that haven't been checked
+    /// against the predicate yet.
+    ///
+    /// [`Select`]: crate::Select
+    /// [`len`]: Receiver::len
+    /// [`capacity`]: Receiver::capacity
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::unbounded;
+    ///
+    /// let (s, r) = unbounded();
+    /// let r = r.filter(|x| x % 2 == 0);
+    ///
+    /// for i in 0..5 {
+    ///     s.send(i).unwrap();
+    /// }
+    /// drop(s);
+    ///
+    /// assert_eq!(r.iter().collect::<Vec<_>>(), [0, 2, 4]);
+    /// ```
+    pub fn filter<P>(self, predicate: P) -> Receiver<T>
+    where
+        T: Send + 'static,
+        P: Fn(&T) -> bool + Send + Sync + 'static,
+    {
+        Receiver {
+            flavor: ReceiverFlavor::Adapter(Arc::new(flavors::adapter::Filter::new(
+                self, predicate,
+            ))),
+        }
+    }
 }
 
 impl<T> Drop for Receiver<T> {
@@ -1135,11 +1912,14 @@ impl<T> Drop for Receiver<T> {
         unsafe {
             match &self.flavor {
                 ReceiverFlavor::Array(chan) => chan.release(|c| c.disconnect()),
+                ReceiverFlavor::Lanes(chan) => chan.release(|c| c.disconnect()),
+                ReceiverFlavor::Sharded(chan) => chan.release(|c| c.disconnect()),
                 ReceiverFlavor::List(chan) => chan.release(|c| c.disconnect()),
                 ReceiverFlavor::Zero(chan) => chan.release(|c| c.disconnect()),
                 ReceiverFlavor::At(_) => {}
                 ReceiverFlavor::Tick(_) => {}
                 ReceiverFlavor::Never(_) => {}
+                ReceiverFlavor::Adapter(_) => {}
             }
         }
     }
@@ -1149,17 +1929,29 @@ impl<T> Clone for Receiver<T> {
     fn clone(&self) -> Self {
         let flavor = match &self.flavor {
             ReceiverFlavor::Array(chan) => ReceiverFlavor::Array(chan.acquire()),
+            ReceiverFlavor::Lanes(chan) => ReceiverFlavor::Lanes(chan.acquire()),
+            ReceiverFlavor::Sharded(chan) => ReceiverFlavor::Sharded(chan.acquire()),
             ReceiverFlavor::List(chan) => ReceiverFlavor::List(chan.acquire()),
             ReceiverFlavor::Zero(chan) => ReceiverFlavor::Zero(chan.acquire()),
             ReceiverFlavor::At(chan) => ReceiverFlavor::At(chan.clone()),
             ReceiverFlavor::Tick(chan) => ReceiverFlavor::Tick(chan.clone()),
             ReceiverFlavor::Never(_) => ReceiverFlavor::Never(flavors::never::Channel::new()),
+            ReceiverFlavor::Adapter(chan) => ReceiverFlavor::Adapter(chan.clone()),
         };
 
         Receiver { flavor }
     }
 }
 
+impl<T> Receiver<T> {
+    /// Creates a receiver backed by an adapter.
+    pub(crate) fn from_adapter(chan: Arc<dyn flavors::adapter::RecvAdapter<T>>) -> Receiver<T> {
+        Receiver {
+            flavor: ReceiverFlavor::Adapter(chan),
+        }
+    }
+}
+
 impl<T> fmt::Debug for Receiver<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.pad("Receiver { .. }")
@@ -1334,60 +2126,84 @@ impl<T> SelectHandle for Sender<T> {
     fn try_select(&self, token: &mut Token) -> bool {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.sender().try_select(token),
+            SenderFlavor::Lanes(chan) => chan.sender().try_select(token),
+            SenderFlavor::Sharded(chan) => chan.sender().try_select(token),
             SenderFlavor::List(chan) => chan.sender().try_select(token),
             SenderFlavor::Zero(chan) => chan.sender().try_select(token),
+            SenderFlavor::Adapter(chan) => chan.handle().try_select(token),
         }
     }
 
     fn deadline(&self) -> Option<Instant> {
-        None
+        match &self.flavor {
+            SenderFlavor::Adapter(chan) => chan.handle().deadline(),
+            _ => None,
+        }
     }
 
     fn register(&self, oper: Operation, cx: &Context) -> bool {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.sender().register(oper, cx),
+            SenderFlavor::Lanes(chan) => chan.sender().register(oper, cx),
+            SenderFlavor::Sharded(chan) => chan.sender().register(oper, cx),
             SenderFlavor::List(chan) => chan.sender().register(oper, cx),
             SenderFlavor::Zero(chan) => chan.sender().register(oper, cx),
+            SenderFlavor::Adapter(chan) => chan.handle().register(oper, cx),
         }
     }
 
     fn unregister(&self, oper: Operation) {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.sender().unregister(oper),
+            SenderFlavor::Lanes(chan) => chan.sender().unregister(oper),
+            SenderFlavor::Sharded(chan) => chan.sender().unregister(oper),
             SenderFlavor::List(chan) => chan.sender().unregister(oper),
             SenderFlavor::Zero(chan) => chan.sender().unregister(oper),
+            SenderFlavor::Adapter(chan) => chan.handle().unregister(oper),
         }
     }
 
     fn accept(&self, token: &mut Token, cx: &Context) -> bool {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.sender().accept(token, cx),
+            SenderFlavor::Lanes(chan) => chan.sender().accept(token, cx),
+            SenderFlavor::Sharded(chan) => chan.sender().accept(token, cx),
             SenderFlavor::List(chan) => chan.sender().accept(token, cx),
             SenderFlavor::Zero(chan) => chan.sender().accept(token, cx),
+            SenderFlavor::Adapter(chan) => chan.handle().accept(token, cx),
         }
     }
 
     fn is_ready(&self) -> bool {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.sender().is_ready(),
+            SenderFlavor::Lanes(chan) => chan.sender().is_ready(),
+            SenderFlavor::Sharded(chan) => chan.sender().is_ready(),
             SenderFlavor::List(chan) => chan.sender().is_ready(),
             SenderFlavor::Zero(chan) => chan.sender().is_ready(),
+            SenderFlavor::Adapter(chan) => chan.handle().is_ready(),
         }
     }
 
     fn watch(&self, oper: Operation, cx: &Context) -> bool {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.sender().watch(oper, cx),
+            SenderFlavor::Lanes(chan) => chan.sender().watch(oper, cx),
+            SenderFlavor::Sharded(chan) => chan.sender().watch(oper, cx),
             SenderFlavor::List(chan) => chan.sender().watch(oper, cx),
             SenderFlavor::Zero(chan) => chan.sender().watch(oper, cx),
+            SenderFlavor::Adapter(chan) => chan.handle().watch(oper, cx),
         }
     }
 
     fn unwatch(&self, oper: Operation) {
         match &self.flavor {
             SenderFlavor::Array(chan) => chan.sender().unwatch(oper),
+            SenderFlavor::Lanes(chan) => chan.sender().unwatch(oper),
+            SenderFlavor::Sharded(chan) => chan.sender().unwatch(oper),
             SenderFlavor::List(chan) => chan.sender().unwatch(oper),
             SenderFlavor::Zero(chan) => chan.sender().unwatch(oper),
+            SenderFlavor::Adapter(chan) => chan.handle().unwatch(oper),
         }
     }
 }
@@ -1396,88 +2212,112 @@ impl<T> SelectHandle for Receiver<T> {
     fn try_select(&self, token: &mut Token) -> bool {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::Lanes(chan) => chan.receiver().try_select(token),
+            ReceiverFlavor::Sharded(chan) => chan.receiver().try_select(token),
             ReceiverFlavor::List(chan) => chan.receiver().try_select(token),
             ReceiverFlavor::Zero(chan) => chan.receiver().try_select(token),
             ReceiverFlavor::At(chan) => chan.try_select(token),
             ReceiverFlavor::Tick(chan) => chan.try_select(token),
             ReceiverFlavor::Never(chan) => chan.try_select(token),
+            ReceiverFlavor::Adapter(chan) => chan.handle().try_select(token),
         }
     }
 
     fn deadline(&self) -> Option<Instant> {
         match &self.flavor {
             ReceiverFlavor::Array(_) => None,
+            ReceiverFlavor::Lanes(_) => None,
+            ReceiverFlavor::Sharded(_) => None,
             ReceiverFlavor::List(_) => None,
             ReceiverFlavor::Zero(_) => None,
             ReceiverFlavor::At(chan) => chan.deadline(),
             ReceiverFlavor::Tick(chan) => chan.deadline(),
             ReceiverFlavor::Never(chan) => chan.deadline(),
+            ReceiverFlavor::Adapter(chan) => chan.handle().deadline(),
         }
     }
 
     fn register(&self, oper: Operation, cx: &Context) -> bool {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::Lanes(chan) => chan.receiver().register(oper, cx),
+            ReceiverFlavor::Sharded(chan) => chan.receiver().register(oper, cx),
             ReceiverFlavor::List(chan) => chan.receiver().register(oper, cx),
             ReceiverFlavor::Zero(chan) => chan.receiver().register(oper, cx),
             ReceiverFlavor::At(chan) => chan.register(oper, cx),
             ReceiverFlavor::Tick(chan) => chan.register(oper, cx),
             ReceiverFlavor::Never(chan) => chan.register(oper, cx),
+            ReceiverFlavor::Adapter(chan) => chan.handle().register(oper, cx),
         }
     }
 
     fn unregister(&self, oper: Operation) {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::Lanes(chan) => chan.receiver().unregister(oper),
+            ReceiverFlavor::Sharded(chan) => chan.receiver().unregister(oper),
             ReceiverFlavor::List(chan) => chan.receiver().unregister(oper),
             ReceiverFlavor::Zero(chan) => chan.receiver().unregister(oper),
             ReceiverFlavor::At(chan) => chan.unregister(oper),
             ReceiverFlavor::Tick(chan) => chan.unregister(oper),
             ReceiverFlavor::Never(chan) => chan.unregister(oper),
+            ReceiverFlavor::Adapter(chan) => chan.handle().unregister(oper),
         }
     }
 
     fn accept(&self, token: &mut Token, cx: &Context) -> bool {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::Lanes(chan) => chan.receiver().accept(token, cx),
+            ReceiverFlavor::Sharded(chan) => chan.receiver().accept(token, cx),
             ReceiverFlavor::List(chan) => chan.receiver().accept(token, cx),
             ReceiverFlavor::Zero(chan) => chan.receiver().accept(token, cx),
             ReceiverFlavor::At(chan) => chan.accept(token, cx),
             ReceiverFlavor::Tick(chan) => chan.accept(token, cx),
             ReceiverFlavor::Never(chan) => chan.accept(token, cx),
+            ReceiverFlavor::Adapter(chan) => chan.handle().accept(token, cx),
         }
     }
 
     fn is_ready(&self) -> bool {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.receiver().is_ready(),
+            ReceiverFlavor::Lanes(chan) => chan.receiver().is_ready(),
+            ReceiverFlavor::Sharded(chan) => chan.receiver().is_ready(),
             ReceiverFlavor::List(chan) => chan.receiver().is_ready(),
             ReceiverFlavor::Zero(chan) => chan.receiver().is_ready(),
             ReceiverFlavor::At(chan) => chan.is_ready(),
             ReceiverFlavor::Tick(chan) => chan.is_ready(),
             ReceiverFlavor::Never(chan) => chan.is_ready(),
+            ReceiverFlavor::Adapter(chan) => chan.handle().is_ready(),
         }
     }
 
     fn watch(&self, oper: Operation, cx: &Context) -> bool {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.receiver().watch(oper, cx),
+            ReceiverFlavor::Lanes(chan) => chan.receiver().watch(oper, cx),
+            ReceiverFlavor::Sharded(chan) => chan.receiver().watch(oper, cx),
             ReceiverFlavor::List(chan) => chan.receiver().watch(oper, cx),
             ReceiverFlavor::Zero(chan) => chan.receiver().watch(oper, cx),
             ReceiverFlavor::At(chan) => chan.watch(oper, cx),
             ReceiverFlavor::Tick(chan) => chan.watch(oper, cx),
             ReceiverFlavor::Never(chan) => chan.watch(oper, cx),
+            ReceiverFlavor::Adapter(chan) => chan.handle().watch(oper, cx),
         }
     }
 
     fn unwatch(&self, oper: Operation) {
         match &self.flavor {
             ReceiverFlavor::Array(chan) => chan.receiver().unwatch(oper),
+            ReceiverFlavor::Lanes(chan) => chan.receiver().unwatch(oper),
+            ReceiverFlavor::Sharded(chan) => chan.receiver().unwatch(oper),
             ReceiverFlavor::List(chan) => chan.receiver().unwatch(oper),
             ReceiverFlavor::Zero(chan) => chan.receiver().unwatch(oper),
             ReceiverFlavor::At(chan) => chan.unwatch(oper),
             ReceiverFlavor::Tick(chan) => chan.unwatch(oper),
             ReceiverFlavor::Never(chan) => chan.unwatch(oper),
+            ReceiverFlavor::Adapter(chan) => chan.handle().unwatch(oper),
         }
     }
 }
@@ -1486,8 +2326,21 @@ impl<T> SelectHandle for Receiver<T> {
 pub(crate) unsafe fn write<T>(s: &Sender<T>, token: &mut Token, msg: T) -> Result<(), T> {
     match &s.flavor {
         SenderFlavor::Array(chan) => chan.write(token, msg),
+        SenderFlavor::Lanes(chan) => chan.write(token, msg),
+        SenderFlavor::Sharded(chan) => chan.write(token, msg),
         SenderFlavor::List(chan) => chan.write(token, msg),
         SenderFlavor::Zero(chan) => chan.write(token, msg),
+        SenderFlavor::Adapter(chan) => chan.write(token, msg),
+    }
+}
+
+/// Returns `true` if a selected send operation will succeed in writing a message.
+pub(crate) fn can_write<T>(s: &Sender<T>, token: &Token) -> bool {
+    match &s.flavor {
+        SenderFlavor::Array(_) | SenderFlavor::Lanes(_) => !token.array.is_disconnected(),
+        SenderFlavor::List(_) | SenderFlavor::Sharded(_) => !token.list.is_disconnected(),
+        SenderFlavor::Zero(_) => token.zero != 0,
+        SenderFlavor::Adapter(chan) => chan.can_write(token),
     }
 }
 
@@ -1495,6 +2348,8 @@ pub(crate) unsafe fn write<T>(s: &Sender<T>, token: &mut Token, msg: T) -> Resul
 pub(crate) unsafe fn read<T>(r: &Receiver<T>, token: &mut Token) -> Result<T, ()> {
     match &r.flavor {
         ReceiverFlavor::Array(chan) => chan.read(token),
+        ReceiverFlavor::Lanes(chan) => chan.read(token),
+        ReceiverFlavor::Sharded(chan) => chan.read(token),
         ReceiverFlavor::List(chan) => chan.read(token),
         ReceiverFlavor::Zero(chan) => chan.read(token),
         ReceiverFlavor::At(chan) => {
@@ -1504,5 +2359,6 @@ pub(crate) unsafe fn read<T>(r: &Receiver<T>, token: &mut Token) -> Result<T, ()
             mem::transmute_copy::<Result<Instant, ()>, Result<T, ()>>(&chan.read(token))
         }
         ReceiverFlavor::Never(chan) => chan.read(token),
+        ReceiverFlavor::Adapter(chan) => chan.read(token),
     }
 }
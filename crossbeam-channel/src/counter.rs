@@ -44,6 +44,11 @@ impl<C> Sender<C> {
         unsafe { &*self.counter }
     }
 
+    /// Returns the number of receivers associated with the channel.
+    pub(crate) fn receiver_count(&self) -> usize {
+        self.counter().receivers.load(Ordering::SeqCst)
+    }
+
     /// Acquires another sender reference.
     pub(crate) fn acquire(&self) -> Sender<C> {
         let count = self.counter().senders.fetch_add(1, Ordering::Relaxed);
@@ -88,6 +93,12 @@ impl<C> PartialEq for Sender<C> {
     }
 }
 
+impl<C> PartialEq<Receiver<C>> for Sender<C> {
+    fn eq(&self, other: &Receiver<C>) -> bool {
+        self.counter == other.counter
+    }
+}
+
 /// The receiving side.
 pub(crate) struct Receiver<C> {
     counter: *mut Counter<C>,
@@ -99,6 +110,11 @@ impl<C> Receiver<C> {
         unsafe { &*self.counter }
     }
 
+    /// Returns the number of senders associated with the channel.
+    pub(crate) fn sender_count(&self) -> usize {
+        self.counter().senders.load(Ordering::SeqCst)
+    }
+
     /// Acquires another receiver reference.
     pub(crate) fn acquire(&self) -> Receiver<C> {
         let count = self.counter().receivers.fetch_add(1, Ordering::Relaxed);
@@ -142,3 +158,9 @@ impl<C> PartialEq for Receiver<C> {
         self.counter == other.counter
     }
 }
+
+impl<C> PartialEq<Sender<C>> for Receiver<C> {
+    fn eq(&self, other: &Sender<C>) -> bool {
+        self.counter == other.counter
+    }
+}
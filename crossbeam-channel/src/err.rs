@@ -1,5 +1,6 @@
 use std::error;
 use std::fmt;
+use std::io;
 
 /// An error returned from the [`send`] method.
 ///
@@ -28,6 +29,23 @@ pub enum TrySendError<T> {
     Disconnected(T),
 }
 
+/// An error returned from the [`send_to_all`] method.
+///
+/// A member of the group could not accept the message. Members before it have already received
+/// the message, and members after it have not.
+///
+/// The error contains the message being sent so it can be recovered.
+///
+/// [`send_to_all`]: super::Group::send_to_all
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub struct GroupSendError<T> {
+    /// The index of the member that could not accept the message.
+    pub index: usize,
+
+    /// The error returned by the member.
+    pub error: TrySendError<T>,
+}
+
 /// An error returned from the [`send_timeout`] method.
 ///
 /// The error contains the message being sent so it can be recovered.
@@ -83,6 +101,32 @@ pub enum RecvTimeoutError {
     Disconnected,
 }
 
+/// An error returned from the [`send_cancellable`] method.
+///
+/// The error contains the message being sent so it can be recovered.
+///
+/// [`send_cancellable`]: super::Sender::send_cancellable
+#[derive(PartialEq, Eq, Clone, Copy)]
+pub enum SendCancelError<T> {
+    /// The message could not be sent because the operation was cancelled.
+    Cancelled(T),
+
+    /// The message could not be sent because the channel is disconnected.
+    Disconnected(T),
+}
+
+/// An error returned from the [`recv_cancellable`] method.
+///
+/// [`recv_cancellable`]: super::Receiver::recv_cancellable
+#[derive(PartialEq, Eq, Clone, Copy, Debug)]
+pub enum RecvCancelError {
+    /// A message could not be received because the operation was cancelled.
+    Cancelled,
+
+    /// The message could not be received because the channel is empty and disconnected.
+    Disconnected,
+}
+
 /// An error returned from the [`try_select`] method.
 ///
 /// Failed because none of the channel operations were ready.
@@ -129,6 +173,15 @@ impl<T> fmt::Display for SendError<T> {
 
 impl<T: Send> error::Error for SendError<T> {}
 
+impl<T> From<SendError<T>> for io::Error {
+    /// Converts the error into an [`io::Error`] of kind [`BrokenPipe`], dropping the message.
+    ///
+    /// [`BrokenPipe`]: io::ErrorKind::BrokenPipe
+    fn from(_: SendError<T>) -> io::Error {
+        io::Error::new(io::ErrorKind::BrokenPipe, SendError(()))
+    }
+}
+
 impl<T> SendError<T> {
     /// Unwraps the message.
     ///
@@ -169,6 +222,24 @@ impl<T> fmt::Display for TrySendError<T> {
 
 impl<T: Send> error::Error for TrySendError<T> {}
 
+impl<T> From<TrySendError<T>> for io::Error {
+    /// Converts the error into an [`io::Error`] of kind [`WouldBlock`] if the channel is full, or
+    /// [`BrokenPipe`] if it is disconnected, dropping the message.
+    ///
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    /// [`BrokenPipe`]: io::ErrorKind::BrokenPipe
+    fn from(err: TrySendError<T>) -> io::Error {
+        match err {
+            TrySendError::Full(_) => {
+                io::Error::new(io::ErrorKind::WouldBlock, TrySendError::Full(()))
+            }
+            TrySendError::Disconnected(_) => {
+                io::Error::new(io::ErrorKind::BrokenPipe, TrySendError::Disconnected(()))
+            }
+        }
+    }
+}
+
 impl<T> From<SendError<T>> for TrySendError<T> {
     fn from(err: SendError<T>) -> TrySendError<T> {
         match err {
@@ -215,6 +286,53 @@ impl<T> TrySendError<T> {
     }
 }
 
+impl<T> fmt::Debug for GroupSendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("GroupSendError")
+            .field("index", &self.index)
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl<T> fmt::Display for GroupSendError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (group member {})", self.error, self.index)
+    }
+}
+
+impl<T: Send> error::Error for GroupSendError<T> {}
+
+impl<T> From<GroupSendError<T>> for io::Error {
+    /// Converts the error into an [`io::Error`] the same way as the underlying [`TrySendError`],
+    /// dropping the message.
+    fn from(err: GroupSendError<T>) -> io::Error {
+        err.error.into()
+    }
+}
+
+impl<T> GroupSendError<T> {
+    /// Unwraps the message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, Group, SendPolicy};
+    ///
+    /// let (s, _r) = bounded(0);
+    /// let mut group = Group::new(SendPolicy::Error);
+    /// group.push(s);
+    ///
+    /// if let Err(err) = group.send_to_all("foo") {
+    ///     assert_eq!(err.index, 0);
+    ///     assert_eq!(err.into_inner(), "foo");
+    /// }
+    /// ```
+    pub fn into_inner(self) -> T {
+        self.error.into_inner()
+    }
+}
+
 impl<T> fmt::Debug for SendTimeoutError<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         "SendTimeoutError(..)".fmt(f)
@@ -232,6 +350,25 @@ impl<T> fmt::Display for SendTimeoutError<T> {
 
 impl<T: Send> error::Error for SendTimeoutError<T> {}
 
+impl<T> From<SendTimeoutError<T>> for io::Error {
+    /// Converts the error into an [`io::Error`] of kind [`TimedOut`] if the operation timed out,
+    /// or [`BrokenPipe`] if the channel is disconnected, dropping the message.
+    ///
+    /// [`TimedOut`]: io::ErrorKind::TimedOut
+    /// [`BrokenPipe`]: io::ErrorKind::BrokenPipe
+    fn from(err: SendTimeoutError<T>) -> io::Error {
+        match err {
+            SendTimeoutError::Timeout(_) => {
+                io::Error::new(io::ErrorKind::TimedOut, SendTimeoutError::Timeout(()))
+            }
+            SendTimeoutError::Disconnected(_) => io::Error::new(
+                io::ErrorKind::BrokenPipe,
+                SendTimeoutError::Disconnected(()),
+            ),
+        }
+    }
+}
+
 impl<T> From<SendError<T>> for SendTimeoutError<T> {
     fn from(err: SendError<T>) -> SendTimeoutError<T> {
         match err {
@@ -287,6 +424,15 @@ impl fmt::Display for RecvError {
 
 impl error::Error for RecvError {}
 
+impl From<RecvError> for io::Error {
+    /// Converts the error into an [`io::Error`] of kind [`UnexpectedEof`].
+    ///
+    /// [`UnexpectedEof`]: io::ErrorKind::UnexpectedEof
+    fn from(err: RecvError) -> io::Error {
+        io::Error::new(io::ErrorKind::UnexpectedEof, err)
+    }
+}
+
 impl fmt::Display for TryRecvError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
@@ -298,6 +444,21 @@ impl fmt::Display for TryRecvError {
 
 impl error::Error for TryRecvError {}
 
+impl From<TryRecvError> for io::Error {
+    /// Converts the error into an [`io::Error`] of kind [`WouldBlock`] if the channel is empty,
+    /// or [`UnexpectedEof`] if it is disconnected.
+    ///
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    /// [`UnexpectedEof`]: io::ErrorKind::UnexpectedEof
+    fn from(err: TryRecvError) -> io::Error {
+        let kind = match err {
+            TryRecvError::Empty => io::ErrorKind::WouldBlock,
+            TryRecvError::Disconnected => io::ErrorKind::UnexpectedEof,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
 impl From<RecvError> for TryRecvError {
     fn from(err: RecvError) -> TryRecvError {
         match err {
@@ -337,6 +498,21 @@ impl fmt::Display for RecvTimeoutError {
 
 impl error::Error for RecvTimeoutError {}
 
+impl From<RecvTimeoutError> for io::Error {
+    /// Converts the error into an [`io::Error`] of kind [`TimedOut`] if the operation timed out,
+    /// or [`UnexpectedEof`] if the channel is disconnected.
+    ///
+    /// [`TimedOut`]: io::ErrorKind::TimedOut
+    /// [`UnexpectedEof`]: io::ErrorKind::UnexpectedEof
+    fn from(err: RecvTimeoutError) -> io::Error {
+        let kind = match err {
+            RecvTimeoutError::Timeout => io::ErrorKind::TimedOut,
+            RecvTimeoutError::Disconnected => io::ErrorKind::UnexpectedEof,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
 impl From<RecvError> for RecvTimeoutError {
     fn from(err: RecvError) -> RecvTimeoutError {
         match err {
@@ -365,6 +541,143 @@ impl RecvTimeoutError {
     }
 }
 
+impl<T> fmt::Debug for SendCancelError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "SendCancelError(..)".fmt(f)
+    }
+}
+
+impl<T> fmt::Display for SendCancelError<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            SendCancelError::Cancelled(..) => "send operation was cancelled".fmt(f),
+            SendCancelError::Disconnected(..) => "sending on a disconnected channel".fmt(f),
+        }
+    }
+}
+
+impl<T: Send> error::Error for SendCancelError<T> {}
+
+impl<T> From<SendCancelError<T>> for io::Error {
+    /// Converts the error into an [`io::Error`] of kind [`Other`] if the operation was cancelled,
+    /// or [`BrokenPipe`] if the channel is disconnected, dropping the message.
+    ///
+    /// [`Other`]: io::ErrorKind::Other
+    /// [`BrokenPipe`]: io::ErrorKind::BrokenPipe
+    fn from(err: SendCancelError<T>) -> io::Error {
+        match err {
+            SendCancelError::Cancelled(_) => {
+                io::Error::new(io::ErrorKind::Other, SendCancelError::Cancelled(()))
+            }
+            SendCancelError::Disconnected(_) => {
+                io::Error::new(io::ErrorKind::BrokenPipe, SendCancelError::Disconnected(()))
+            }
+        }
+    }
+}
+
+impl<T> From<SendError<T>> for SendCancelError<T> {
+    fn from(err: SendError<T>) -> SendCancelError<T> {
+        match err {
+            SendError(e) => SendCancelError::Disconnected(e),
+        }
+    }
+}
+
+impl<T> SendCancelError<T> {
+    /// Unwraps the message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{bounded, CancelToken};
+    ///
+    /// let (s, r) = bounded(0);
+    /// let token = CancelToken::new();
+    /// token.cancel();
+    ///
+    /// if let Err(err) = s.send_cancellable("foo", &token) {
+    ///     assert_eq!(err.into_inner(), "foo");
+    /// }
+    /// ```
+    pub fn into_inner(self) -> T {
+        match self {
+            SendCancelError::Cancelled(v) => v,
+            SendCancelError::Disconnected(v) => v,
+        }
+    }
+
+    /// Returns `true` if the send operation was cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        match self {
+            SendCancelError::Cancelled(_) => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the send operation failed because the channel is disconnected.
+    pub fn is_disconnected(&self) -> bool {
+        match self {
+            SendCancelError::Disconnected(_) => true,
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for RecvCancelError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            RecvCancelError::Cancelled => "receive operation was cancelled".fmt(f),
+            RecvCancelError::Disconnected => "channel is empty and disconnected".fmt(f),
+        }
+    }
+}
+
+impl error::Error for RecvCancelError {}
+
+impl From<RecvCancelError> for io::Error {
+    /// Converts the error into an [`io::Error`] of kind [`Other`] if the operation was cancelled,
+    /// or [`UnexpectedEof`] if the channel is disconnected.
+    ///
+    /// [`Other`]: io::ErrorKind::Other
+    /// [`UnexpectedEof`]: io::ErrorKind::UnexpectedEof
+    fn from(err: RecvCancelError) -> io::Error {
+        let kind = match err {
+            RecvCancelError::Cancelled => io::ErrorKind::Other,
+            RecvCancelError::Disconnected => io::ErrorKind::UnexpectedEof,
+        };
+        io::Error::new(kind, err)
+    }
+}
+
+impl From<RecvError> for RecvCancelError {
+    fn from(err: RecvError) -> RecvCancelError {
+        match err {
+            RecvError => RecvCancelError::Disconnected,
+        }
+    }
+}
+
+impl RecvCancelError {
+    /// Returns `true` if the receive operation was cancelled.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn is_cancelled(&self) -> bool {
+        match self {
+            RecvCancelError::Cancelled => true,
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the receive operation failed because the channel is disconnected.
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn is_disconnected(&self) -> bool {
+        match self {
+            RecvCancelError::Disconnected => true,
+            _ => false,
+        }
+    }
+}
+
 impl fmt::Display for TrySelectError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         "all operations in select would block".fmt(f)
@@ -373,6 +686,15 @@ impl fmt::Display for TrySelectError {
 
 impl error::Error for TrySelectError {}
 
+impl From<TrySelectError> for io::Error {
+    /// Converts the error into an [`io::Error`] of kind [`WouldBlock`].
+    ///
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    fn from(err: TrySelectError) -> io::Error {
+        io::Error::new(io::ErrorKind::WouldBlock, err)
+    }
+}
+
 impl fmt::Display for SelectTimeoutError {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         "timed out waiting on select".fmt(f)
@@ -380,3 +702,46 @@ impl fmt::Display for SelectTimeoutError {
 }
 
 impl error::Error for SelectTimeoutError {}
+
+impl From<SelectTimeoutError> for io::Error {
+    /// Converts the error into an [`io::Error`] of kind [`TimedOut`].
+    ///
+    /// [`TimedOut`]: io::ErrorKind::TimedOut
+    fn from(err: SelectTimeoutError) -> io::Error {
+        io::Error::new(io::ErrorKind::TimedOut, err)
+    }
+}
+
+impl fmt::Display for TryReadyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "all operations in select would block".fmt(f)
+    }
+}
+
+impl error::Error for TryReadyError {}
+
+impl From<TryReadyError> for io::Error {
+    /// Converts the error into an [`io::Error`] of kind [`WouldBlock`].
+    ///
+    /// [`WouldBlock`]: io::ErrorKind::WouldBlock
+    fn from(err: TryReadyError) -> io::Error {
+        io::Error::new(io::ErrorKind::WouldBlock, err)
+    }
+}
+
+impl fmt::Display for ReadyTimeoutError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "timed out waiting on select".fmt(f)
+    }
+}
+
+impl error::Error for ReadyTimeoutError {}
+
+impl From<ReadyTimeoutError> for io::Error {
+    /// Converts the error into an [`io::Error`] of kind [`TimedOut`].
+    ///
+    /// [`TimedOut`]: io::ErrorKind::TimedOut
+    fn from(err: ReadyTimeoutError) -> io::Error {
+        io::Error::new(io::ErrorKind::TimedOut, err)
+    }
+}
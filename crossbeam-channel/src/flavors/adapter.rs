@@ -0,0 +1,409 @@
+//! Channels that transform messages passing through another channel.
+//!
+//! An adapter wraps a sender or a receiver of another channel and applies a function to every
+//! message. Adapters are type-erased so that they can be used through ordinary `Sender`s and
+//! `Receiver`s, which means they also work with selection.
+
+use std::collections::VecDeque;
+use std::time::Instant;
+
+use crate::channel::{self, Receiver, Sender};
+use crate::context::Context;
+use crate::err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use crate::select::{self, Operation, SelectHandle, Token};
+use crate::utils::Spinlock;
+
+/// The token type for adapters.
+///
+/// Equals `true` if a filter has received a message that can be read. All other information is
+/// stored in the token of the underlying channel.
+pub(crate) type AdapterToken = bool;
+
+/// The receiving side of an adapter.
+pub(crate) trait RecvAdapter<T>: Send + Sync {
+    /// Attempts to receive a message without blocking.
+    fn try_recv(&self) -> Result<T, TryRecvError>;
+
+    /// Receives a message from the channel.
+    fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError>;
+
+    /// Reads a message after a successful selection.
+    unsafe fn read(&self, token: &mut Token) -> Result<T, ()>;
+
+    /// Returns the current number of messages inside the underlying channel.
+    fn len(&self) -> usize;
+
+    /// Returns the capacity of the underlying channel.
+    fn capacity(&self) -> Option<usize>;
+
+    /// Returns `true` if the underlying channel is empty.
+    fn is_empty(&self) -> bool;
+
+    /// Returns `true` if the underlying channel is full.
+    fn is_full(&self) -> bool;
+
+    /// Returns the number of senders of the underlying channel.
+    fn sender_count(&self) -> usize;
+
+    /// Releases memory the underlying channel keeps around for reuse.
+    fn shrink_to_fit(&self);
+
+    /// Returns the handle used for selection.
+    fn handle(&self) -> &dyn SelectHandle;
+}
+
+/// The sending side of an adapter.
+pub(crate) trait SendAdapter<T>: Send + Sync {
+    /// Attempts to send a message without blocking.
+    fn try_send(&self, msg: T) -> Result<(), TrySendError<T>>;
+
+    /// Sends a message into the channel.
+    fn send(&self, msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>>;
+
+    /// Writes a message after a successful selection.
+    unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T>;
+
+    /// Returns `true` if the selected operation will succeed in writing a message.
+    fn can_write(&self, token: &Token) -> bool;
+
+    /// Returns the current number of messages inside the underlying channel.
+    fn len(&self) -> usize;
+
+    /// Returns the capacity of the underlying channel.
+    fn capacity(&self) -> Option<usize>;
+
+    /// Returns `true` if the underlying channel is empty.
+    fn is_empty(&self) -> bool;
+
+    /// Returns `true` if the underlying channel is full.
+    fn is_full(&self) -> bool;
+
+    /// Returns the number of receivers of the underlying channel.
+    fn receiver_count(&self) -> usize;
+
+    /// Returns the handle used for selection.
+    fn handle(&self) -> &dyn SelectHandle;
+}
+
+/// A receiver that applies a function to every received message.
+pub(crate) struct Map<T, F> {
+    /// The underlying receiver.
+    receiver: Receiver<T>,
+
+    /// The function applied to messages.
+    f: F,
+}
+
+impl<T, F> Map<T, F> {
+    /// Creates a new mapping adapter.
+    pub(crate) fn new(receiver: Receiver<T>, f: F) -> Self {
+        Map { receiver, f }
+    }
+}
+
+impl<T, U, F> RecvAdapter<U> for Map<T, F>
+where
+    T: Send,
+    F: Fn(T) -> U + Send + Sync,
+{
+    fn try_recv(&self) -> Result<U, TryRecvError> {
+        self.receiver.try_recv().map(&self.f)
+    }
+
+    fn recv(&self, deadline: Option<Instant>) -> Result<U, RecvTimeoutError> {
+        match deadline {
+            None => self
+                .receiver
+                .recv()
+                .map_err(|_| RecvTimeoutError::Disconnected),
+            Some(d) => self.receiver.recv_deadline(d),
+        }
+        .map(&self.f)
+    }
+
+    unsafe fn read(&self, token: &mut Token) -> Result<U, ()> {
+        channel::read(&self.receiver, token).map(&self.f)
+    }
+
+    fn len(&self) -> usize {
+        self.receiver.len()
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        self.receiver.capacity()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.receiver.is_empty()
+    }
+
+    fn is_full(&self) -> bool {
+        self.receiver.is_full()
+    }
+
+    fn sender_count(&self) -> usize {
+        self.receiver.sender_count()
+    }
+
+    fn shrink_to_fit(&self) {
+        self.receiver.shrink_to_fit()
+    }
+
+    fn handle(&self) -> &dyn SelectHandle {
+        &self.receiver
+    }
+}
+
+/// A receiver that drops messages not matching a predicate.
+pub(crate) struct Filter<T, P> {
+    /// The underlying receiver.
+    receiver: Receiver<T>,
+
+    /// The predicate messages must match.
+    predicate: P,
+
+    /// Messages that have been accepted by selection, but not read yet.
+    pending: Spinlock<VecDeque<T>>,
+}
+
+impl<T, P> Filter<T, P>
+where
+    P: Fn(&T) -> bool,
+{
+    /// Creates a new filtering adapter.
+    pub(crate) fn new(receiver: Receiver<T>, predicate: P) -> Self {
+        Filter {
+            receiver,
+            predicate,
+            pending: Spinlock::new(VecDeque::new()),
+        }
+    }
+
+    /// Stashes a received message for selection if it matches the predicate.
+    ///
+    /// Returns `true` if the selected operation can be completed.
+    fn accept_msg(&self, token: &mut Token, msg: Result<T, ()>) -> bool {
+        match msg {
+            Ok(msg) => {
+                if (self.predicate)(&msg) {
+                    self.pending.lock().push_back(msg);
+                    token.adapter = true;
+                    true
+                } else {
+                    false
+                }
+            }
+            Err(()) => {
+                token.adapter = false;
+                true
+            }
+        }
+    }
+}
+
+impl<T, P> RecvAdapter<T> for Filter<T, P>
+where
+    T: Send,
+    P: Fn(&T) -> bool + Send + Sync,
+{
+    fn try_recv(&self) -> Result<T, TryRecvError> {
+        loop {
+            let msg = self.receiver.try_recv()?;
+            if (self.predicate)(&msg) {
+                return Ok(msg);
+            }
+        }
+    }
+
+    fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        loop {
+            let msg = match deadline {
+                None => self
+                    .receiver
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected)?,
+                Some(d) => self.receiver.recv_deadline(d)?,
+            };
+            if (self.predicate)(&msg) {
+                return Ok(msg);
+            }
+        }
+    }
+
+    unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        if !token.adapter {
+            return Err(());
+        }
+
+        // Every successful selection stashes exactly one message, so there must be one.
+        Ok(self.pending.lock().pop_front().unwrap())
+    }
+
+    fn len(&self) -> usize {
+        self.receiver.len()
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        self.receiver.capacity()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.receiver.is_empty()
+    }
+
+    fn is_full(&self) -> bool {
+        self.receiver.is_full()
+    }
+
+    fn sender_count(&self) -> usize {
+        self.receiver.sender_count()
+    }
+
+    fn shrink_to_fit(&self) {
+        self.receiver.shrink_to_fit()
+    }
+
+    fn handle(&self) -> &dyn SelectHandle {
+        self
+    }
+}
+
+impl<T, P> SelectHandle for Filter<T, P>
+where
+    P: Fn(&T) -> bool,
+{
+    fn try_select(&self, token: &mut Token) -> bool {
+        // Keep receiving until a message matches or the channel becomes empty.
+        while self.receiver.try_select(token) {
+            let msg = unsafe { channel::read(&self.receiver, token) };
+            if self.accept_msg(token, msg) {
+                return true;
+            }
+        }
+        false
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        self.receiver.deadline()
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.receiver.register(oper, cx)
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.receiver.unregister(oper);
+    }
+
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool {
+        if !self.receiver.accept(token, cx) {
+            return false;
+        }
+
+        // If the message doesn't match, selection will start over.
+        let msg = unsafe { channel::read(&self.receiver, token) };
+        self.accept_msg(token, msg)
+    }
+
+    fn is_ready(&self) -> bool {
+        self.receiver.is_ready()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.receiver.watch(oper, cx)
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.receiver.unwatch(oper);
+    }
+}
+
+/// A sender that applies a function to every message before sending it.
+pub(crate) struct With<T, F> {
+    /// The underlying sender.
+    sender: Sender<T>,
+
+    /// The function applied to messages.
+    f: F,
+}
+
+impl<T, F> With<T, F> {
+    /// Creates a new adapter that transforms messages before sending them.
+    pub(crate) fn new(sender: Sender<T>, f: F) -> Self {
+        With { sender, f }
+    }
+}
+
+impl<T, U, F> SendAdapter<U> for With<T, F>
+where
+    T: Send,
+    F: Fn(U) -> T + Send + Sync,
+{
+    fn try_send(&self, msg: U) -> Result<(), TrySendError<U>> {
+        let token = &mut Token::default();
+
+        // Reserve space first so that the original message can be returned on failure.
+        if self.sender.try_select(token) {
+            unsafe { self.write(token, msg).map_err(TrySendError::Disconnected) }
+        } else {
+            Err(TrySendError::Full(msg))
+        }
+    }
+
+    fn send(&self, msg: U, deadline: Option<Instant>) -> Result<(), SendTimeoutError<U>> {
+        let ptr = &self.sender as *const Sender<T> as *const u8;
+        let handles = &mut [(&self.sender as &dyn SelectHandle, 0, ptr)];
+
+        let oper = match deadline {
+            None => select::select(handles),
+            Some(d) => match select::select_deadline(handles, d) {
+                Ok(oper) => oper,
+                Err(_) => return Err(SendTimeoutError::Timeout(msg)),
+            },
+        };
+
+        let token = &mut oper.into_token();
+        unsafe {
+            self.write(token, msg)
+                .map_err(SendTimeoutError::Disconnected)
+        }
+    }
+
+    unsafe fn write(&self, token: &mut Token, msg: U) -> Result<(), U> {
+        if !self.can_write(token) {
+            return Err(msg);
+        }
+
+        let res = channel::write(&self.sender, token, (self.f)(msg));
+        debug_assert!(res.is_ok());
+        Ok(())
+    }
+
+    fn can_write(&self, token: &Token) -> bool {
+        channel::can_write(&self.sender, token)
+    }
+
+    fn len(&self) -> usize {
+        self.sender.len()
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        self.sender.capacity()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.sender.is_empty()
+    }
+
+    fn is_full(&self) -> bool {
+        self.sender.is_full()
+    }
+
+    fn receiver_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    fn handle(&self) -> &dyn SelectHandle {
+        &self.sender
+    }
+}
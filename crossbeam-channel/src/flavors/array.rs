@@ -1,6 +1,9 @@
 //! Bounded channel based on a preallocated array.
 //!
-//! This flavor has a fixed, positive capacity.
+//! This flavor has a fixed, positive capacity. Buffers of up to `CHUNK_CAP` slots are allocated up
+//! front. Larger buffers are split into chunks that get allocated the first time the channel
+//! reaches them, so a generous capacity doesn't cost memory until the channel actually fills up to
+//! it.
 //!
 //! The implementation is based on Dmitry Vyukov's bounded MPMC queue.
 //!
@@ -9,10 +12,13 @@
 //!   - <https://docs.google.com/document/d/1yIAYmbvL3JxOKOjuCyon7JhW4cSv1wy5hC0ApeGMV9s/pub>
 
 use std::cell::UnsafeCell;
+use std::cmp;
+use std::collections::VecDeque;
 use std::marker::PhantomData;
 use std::mem::{self, MaybeUninit};
 use std::ptr;
-use std::sync::atomic::{self, AtomicUsize, Ordering};
+use std::sync::atomic::{self, AtomicPtr, AtomicUsize, Ordering};
+use std::thread::{self, Thread};
 use std::time::Instant;
 
 use crossbeam_utils::{Backoff, CachePadded};
@@ -20,8 +26,12 @@ use crossbeam_utils::{Backoff, CachePadded};
 use crate::context::Context;
 use crate::err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
 use crate::select::{Operation, SelectHandle, Selected, Token};
+use crate::utils::Spinlock;
 use crate::waker::SyncWaker;
 
+/// The maximum number of slots in a chunk of the buffer.
+const CHUNK_CAP: usize = 1024;
+
 /// A slot in a channel.
 struct Slot<T> {
     /// The current stamp.
@@ -51,6 +61,104 @@ impl Default for ArrayToken {
     }
 }
 
+impl ArrayToken {
+    /// Returns `true` if the operation found the channel disconnected.
+    #[inline]
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.slot.is_null()
+    }
+}
+
+/// A queue of threads taking turns to send into a fair channel.
+///
+/// Only the thread at the front of the queue may attempt to send a message, so blocked senders are
+/// serviced in the order they arrived.
+struct SenderQueue {
+    /// Waiting threads and their tickets, in the order they arrived.
+    waiting: Spinlock<VecDeque<(usize, Thread)>>,
+
+    /// The next ticket to hand out.
+    next_ticket: AtomicUsize,
+
+    /// The number of threads in the queue, so that it can be checked without locking.
+    len: AtomicUsize,
+}
+
+impl SenderQueue {
+    /// Creates an empty queue.
+    fn new() -> Self {
+        SenderQueue {
+            waiting: Spinlock::new(VecDeque::new()),
+            next_ticket: AtomicUsize::new(0),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Returns `true` if no thread is waiting for its turn.
+    fn is_empty(&self) -> bool {
+        self.len.load(Ordering::SeqCst) == 0
+    }
+
+    /// Joins the back of the queue and returns a ticket.
+    fn join(&self) -> usize {
+        let ticket = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        self.waiting.lock().push_back((ticket, thread::current()));
+        self.len.fetch_add(1, Ordering::SeqCst);
+        ticket
+    }
+
+    /// Returns `true` if the ticket is at the front of the queue.
+    fn is_front(&self, ticket: usize) -> bool {
+        self.waiting.lock().front().map(|w| w.0) == Some(ticket)
+    }
+
+    /// Waits until the ticket reaches the front of the queue.
+    ///
+    /// Returns `false` if the deadline was reached first.
+    fn wait_turn(&self, ticket: usize, deadline: Option<Instant>) -> bool {
+        // Spin for a short time, the thread in front of us might be about to leave.
+        let backoff = Backoff::new();
+        while !backoff.is_completed() {
+            if self.is_front(ticket) {
+                return true;
+            }
+            backoff.snooze();
+        }
+
+        loop {
+            if self.is_front(ticket) {
+                return true;
+            }
+
+            match deadline {
+                Some(d) => {
+                    let now = Instant::now();
+                    if now >= d {
+                        return false;
+                    }
+                    thread::park_timeout(d - now);
+                }
+                None => thread::park(),
+            }
+        }
+    }
+
+    /// Leaves the queue and wakes up the next thread in line.
+    fn leave(&self, ticket: usize) {
+        let mut waiting = self.waiting.lock();
+        if let Some(i) = waiting.iter().position(|w| w.0 == ticket) {
+            waiting.remove(i);
+            self.len.fetch_sub(1, Ordering::SeqCst);
+
+            if i == 0 {
+                if let Some((_, thread)) = waiting.front() {
+                    thread.unpark();
+                }
+            }
+        }
+    }
+}
+
 /// Bounded channel based on a preallocated array.
 pub(crate) struct Channel<T> {
     /// The head of the channel.
@@ -71,9 +179,15 @@ pub(crate) struct Channel<T> {
     /// Messages are pushed into the tail of the channel.
     tail: CachePadded<AtomicUsize>,
 
-    /// The buffer holding slots.
+    /// The buffer holding slots, or null if the buffer is split into chunks.
     buffer: *mut Slot<T>,
 
+    /// The buffer holding slots, split into chunks of `CHUNK_CAP` slots.
+    ///
+    /// This is used only if the capacity is larger than `CHUNK_CAP`, and is empty otherwise. Null
+    /// chunks haven't been allocated yet.
+    chunks: Box<[AtomicPtr<Slot<T>>]>,
+
     /// The channel capacity.
     cap: usize,
 
@@ -89,6 +203,9 @@ pub(crate) struct Channel<T> {
     /// Receivers waiting while the channel is empty and not disconnected.
     receivers: SyncWaker,
 
+    /// Senders taking turns, if blocked senders are serviced in FIFO order.
+    fair: Option<SenderQueue>,
+
     /// Indicates that dropping a `Channel<T>` may drop values of type `T`.
     _marker: PhantomData<T>,
 }
@@ -107,25 +224,27 @@ impl<T> Channel<T> {
         // Tail is initialized to `{ lap: 0, mark: 0, index: 0 }`.
         let tail = 0;
 
-        // Allocate a buffer of `cap` slots initialized
-        // with stamps.
-        let buffer = {
-            let mut boxed: Box<[Slot<T>]> = (0..cap)
+        // Small buffers are allocated in one piece. Otherwise, only the first chunk is allocated up
+        // front and the rest are allocated on demand.
+        let (buffer, chunks) = if cap <= CHUNK_CAP {
+            (Self::new_chunk(0, cap), Box::new([]) as Box<[_]>)
+        } else {
+            let chunks = (0..(cap + CHUNK_CAP - 1) / CHUNK_CAP)
                 .map(|i| {
-                    // Set the stamp to `{ lap: 0, mark: 0, index: i }`.
-                    Slot {
-                        stamp: AtomicUsize::new(i),
-                        msg: UnsafeCell::new(MaybeUninit::uninit()),
-                    }
+                    let chunk = if i == 0 {
+                        Self::new_chunk(0, cap)
+                    } else {
+                        ptr::null_mut()
+                    };
+                    AtomicPtr::new(chunk)
                 })
                 .collect();
-            let ptr = boxed.as_mut_ptr();
-            mem::forget(boxed);
-            ptr
+            (ptr::null_mut(), chunks)
         };
 
         Channel {
             buffer,
+            chunks,
             cap,
             one_lap,
             mark_bit,
@@ -133,10 +252,77 @@ impl<T> Channel<T> {
             tail: CachePadded::new(AtomicUsize::new(tail)),
             senders: SyncWaker::new(),
             receivers: SyncWaker::new(),
+            fair: None,
             _marker: PhantomData,
         }
     }
 
+    /// Creates a bounded channel of capacity `cap` that services blocked senders in FIFO order.
+    pub(crate) fn with_capacity_fair(cap: usize) -> Self {
+        let mut chan = Self::with_capacity(cap);
+        chan.fair = Some(SenderQueue::new());
+        chan
+    }
+
+    /// Allocates the chunk with the given index.
+    fn new_chunk(index: usize, cap: usize) -> *mut Slot<T> {
+        let start = index * CHUNK_CAP;
+        let end = cmp::min(start + CHUNK_CAP, cap);
+
+        let mut boxed: Box<[Slot<T>]> = (start..end)
+            .map(|i| {
+                // Chunks are reached for the first time during lap 0, so set the stamp to
+                // `{ lap: 0, mark: 0, index: i }`.
+                Slot {
+                    stamp: AtomicUsize::new(i),
+                    msg: UnsafeCell::new(MaybeUninit::uninit()),
+                }
+            })
+            .collect();
+        let ptr = boxed.as_mut_ptr();
+        mem::forget(boxed);
+        ptr
+    }
+
+    /// Returns the slot at `index`, allocating its chunk if needed.
+    #[inline]
+    fn slot(&self, index: usize) -> &Slot<T> {
+        if !self.buffer.is_null() {
+            return unsafe { &*self.buffer.add(index) };
+        }
+        self.chunked_slot(index)
+    }
+
+    /// Returns the slot at `index` in a buffer split into chunks, allocating its chunk if needed.
+    fn chunked_slot(&self, index: usize) -> &Slot<T> {
+        let chunk = &self.chunks[index / CHUNK_CAP];
+        let mut ptr = chunk.load(Ordering::Acquire);
+
+        if ptr.is_null() {
+            let new = Self::new_chunk(index / CHUNK_CAP, self.cap);
+
+            // Install the new chunk, unless another thread has beaten us to it.
+            match chunk.compare_exchange(ptr, new, Ordering::AcqRel, Ordering::Acquire) {
+                Ok(_) => ptr = new,
+                Err(p) => {
+                    unsafe { Self::free_chunk(index / CHUNK_CAP, self.cap, new) };
+                    ptr = p;
+                }
+            }
+        }
+
+        unsafe { &*ptr.add(index % CHUNK_CAP) }
+    }
+
+    /// Deallocates the chunk with the given index, but doesn't run any destructors.
+    unsafe fn free_chunk(index: usize, cap: usize, chunk: *mut Slot<T>) {
+        // Create a slice from the chunk to make a fat pointer. Then, use Box::from_raw to
+        // deallocate it.
+        let len = cmp::min(CHUNK_CAP, cap - index * CHUNK_CAP);
+        let ptr = std::slice::from_raw_parts_mut(chunk, len) as *mut [Slot<T>];
+        drop(Box::from_raw(ptr));
+    }
+
     /// Returns a receiver handle to the channel.
     pub(crate) fn receiver(&self) -> Receiver<'_, T> {
         Receiver(self)
@@ -165,7 +351,7 @@ impl<T> Channel<T> {
             let lap = tail & !(self.one_lap - 1);
 
             // Inspect the corresponding slot.
-            let slot = unsafe { &*self.buffer.add(index) };
+            let slot = self.slot(index);
             let stamp = slot.stamp.load(Ordering::Acquire);
 
             // If the tail and the stamp match, we may attempt to push.
@@ -247,7 +433,7 @@ impl<T> Channel<T> {
             let lap = head & !(self.one_lap - 1);
 
             // Inspect the corresponding slot.
-            let slot = unsafe { &*self.buffer.add(index) };
+            let slot = self.slot(index);
             let stamp = slot.stamp.load(Ordering::Acquire);
 
             // If the the stamp is ahead of the head by 1, we may attempt to pop.
@@ -328,6 +514,16 @@ impl<T> Channel<T> {
 
     /// Attempts to send a message into the channel.
     pub(crate) fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        // Don't overtake senders waiting for their turn.
+        if let Some(queue) = &self.fair {
+            if !queue.is_empty() {
+                if self.is_disconnected() {
+                    return Err(TrySendError::Disconnected(msg));
+                }
+                return Err(TrySendError::Full(msg));
+            }
+        }
+
         let token = &mut Token::default();
         if self.start_send(token) {
             unsafe { self.write(token, msg).map_err(TrySendError::Disconnected) }
@@ -342,6 +538,34 @@ impl<T> Channel<T> {
         msg: T,
         deadline: Option<Instant>,
     ) -> Result<(), SendTimeoutError<T>> {
+        match &self.fair {
+            None => self.send_unfair(msg, deadline),
+            Some(queue) => {
+                // If nobody is waiting and there's room, send right away. Only senders that find
+                // the channel full have to get in line.
+                if queue.is_empty() {
+                    let token = &mut Token::default();
+                    if self.start_send(token) {
+                        let res = unsafe { self.write(token, msg) };
+                        return res.map_err(SendTimeoutError::Disconnected);
+                    }
+                }
+
+                // Wait in line until it's our turn to send.
+                let ticket = queue.join();
+                let res = if queue.wait_turn(ticket, deadline) {
+                    self.send_unfair(msg, deadline)
+                } else {
+                    Err(SendTimeoutError::Timeout(msg))
+                };
+                queue.leave(ticket);
+                res
+            }
+        }
+    }
+
+    /// Sends a message into the channel, competing with all other senders.
+    fn send_unfair(&self, msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
         let token = &mut Token::default();
         loop {
             // Try sending a message several times.
@@ -543,7 +767,7 @@ impl<T> Drop for Channel<T> {
 
             unsafe {
                 let p = {
-                    let slot = &mut *self.buffer.add(index);
+                    let slot = self.slot(index);
                     let msg = &mut *slot.msg.get();
                     msg.as_mut_ptr()
                 };
@@ -551,13 +775,15 @@ impl<T> Drop for Channel<T> {
             }
         }
 
-        // Finally, deallocate the buffer, but don't run any destructors.
-        unsafe {
-            // Create a slice from the buffer to make
-            // a fat pointer. Then, use Box::from_raw
-            // to deallocate it.
-            let ptr = std::slice::from_raw_parts_mut(self.buffer, self.cap) as *mut [Slot<T>];
-            Box::from_raw(ptr);
+        // Finally, deallocate the buffer or its chunks, but don't run any destructors.
+        if !self.buffer.is_null() {
+            unsafe { Self::free_chunk(0, self.cap, self.buffer) };
+        }
+        for (i, chunk) in self.chunks.iter_mut().enumerate() {
+            let chunk = *chunk.get_mut();
+            if !chunk.is_null() {
+                unsafe { Self::free_chunk(i, self.cap, chunk) };
+            }
         }
     }
 }
@@ -0,0 +1,95 @@
+//! Bounded channel split into multiple lanes.
+//!
+//! This flavor has a fixed, positive capacity that is divided among several array channels called
+//! lanes. Each sending thread always sends into the same lane, while receivers take messages from
+//! any lane. Spreading senders across lanes reduces contention on the head and tail indices when
+//! many threads send into the same channel at the same time.
+
+use std::time::Instant;
+
+use crate::err::{SendTimeoutError, TrySendError};
+use crate::flavors::array;
+use crate::flavors::split::{self, Part};
+use crate::select::{SelectHandle, Token};
+
+/// Bounded channel split into multiple lanes.
+pub(crate) type Channel<T> = split::Channel<T, array::Channel<T>>;
+
+impl<T> Channel<T> {
+    /// Creates a bounded channel of capacity `cap` split into `lanes` lanes.
+    ///
+    /// If `cap` is smaller than `lanes`, the channel will have only `cap` lanes.
+    pub(crate) fn with_capacity(cap: usize, lanes: usize) -> Self {
+        assert!(cap > 0, "capacity must be positive");
+        assert!(lanes > 0, "number of lanes must be positive");
+
+        // Divide the capacity as evenly as possible.
+        let lanes = lanes.min(cap);
+        let lanes = (0..lanes)
+            .map(|i| {
+                let extra = if i < cap % lanes { 1 } else { 0 };
+                array::Channel::with_capacity(cap / lanes + extra)
+            })
+            .collect();
+
+        Channel::from_parts(lanes)
+    }
+}
+
+impl<T> Part<T> for array::Channel<T> {
+    fn with_sender<R>(&self, f: impl FnOnce(&dyn SelectHandle) -> R) -> R {
+        f(&self.sender())
+    }
+
+    fn with_receiver<R>(&self, f: impl FnOnce(&dyn SelectHandle) -> R) -> R {
+        f(&self.receiver())
+    }
+
+    fn found_disconnected(token: &Token) -> bool {
+        token.array.is_disconnected()
+    }
+
+    unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
+        self.write(token, msg)
+    }
+
+    unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        self.read(token)
+    }
+
+    fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.try_send(msg)
+    }
+
+    fn send(&self, msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        self.send(msg, deadline)
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        self.capacity()
+    }
+
+    fn shrink_to_fit(&self) {
+        // Arrays are allocated up front, so there is nothing to release.
+    }
+
+    fn disconnect(&self) -> bool {
+        self.disconnect()
+    }
+
+    fn is_disconnected(&self) -> bool {
+        self.is_disconnected()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn is_full(&self) -> bool {
+        self.is_full()
+    }
+}
@@ -1,8 +1,9 @@
 //! Unbounded channel implemented as a linked list.
 
+use std::alloc::{self, Layout};
 use std::cell::UnsafeCell;
 use std::marker::PhantomData;
-use std::mem::MaybeUninit;
+use std::mem::{self, MaybeUninit};
 use std::ptr;
 use std::sync::atomic::{self, AtomicPtr, AtomicUsize, Ordering};
 use std::time::Instant;
@@ -12,6 +13,7 @@ use crossbeam_utils::{Backoff, CachePadded};
 use crate::context::Context;
 use crate::err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
 use crate::select::{Operation, SelectHandle, Selected, Token};
+use crate::utils::Spinlock;
 use crate::waker::SyncWaker;
 
 // TODO(stjepang): Once we bump the minimum required Rust version to 1.28 or newer, re-apply the
@@ -28,10 +30,12 @@ const WRITE: usize = 1;
 const READ: usize = 2;
 const DESTROY: usize = 4;
 
-// Each block covers one "lap" of indices.
+// The default number of indices covered by one block, i.e. one "lap" of indices.
 const LAP: usize = 32;
-// The maximum number of messages a block can hold.
+// The default maximum number of messages a block can hold.
 const BLOCK_CAP: usize = LAP - 1;
+// The maximum number of freed blocks kept around for reuse.
+const MAX_CACHED_BLOCKS: usize = 4;
 // How many lower bits are reserved for metadata.
 const SHIFT: usize = 1;
 // Has two different purposes:
@@ -60,25 +64,61 @@ impl<T> Slot<T> {
 
 /// A block in a linked list.
 ///
-/// Each block in the list can hold up to `BLOCK_CAP` messages.
+/// Each block in the list can hold up to `block_cap` messages, where `block_cap` is chosen when
+/// the channel is created. The slots for messages are allocated right after the block header.
+#[repr(C)]
 struct Block<T> {
     /// The next block in the linked list.
     next: AtomicPtr<Block<T>>,
 
-    /// Slots for messages.
-    slots: [Slot<T>; BLOCK_CAP],
+    /// Makes sure the header is aligned for the slots that follow it.
+    _slots: [Slot<T>; 0],
 }
 
 impl<T> Block<T> {
-    /// Creates an empty block.
-    fn new() -> Block<T> {
+    /// Returns the memory layout of a block with `cap` slots.
+    fn layout(cap: usize) -> Layout {
+        let size = mem::size_of::<Slot<T>>()
+            .checked_mul(cap)
+            .and_then(|size| size.checked_add(mem::size_of::<Block<T>>()))
+            .expect("block size overflow");
+        Layout::from_size_align(size, mem::align_of::<Block<T>>()).expect("block size overflow")
+    }
+
+    /// Allocates an empty block with `cap` slots.
+    fn alloc(cap: usize) -> *mut Block<T> {
+        let layout = Self::layout(cap);
+
         // SAFETY: This is safe because:
         //  [1] `Block::next` (AtomicPtr) may be safely zero initialized.
-        //  [2] `Block::slots` (Array) may be safely zero initialized because of [3, 4].
+        //  [2] The slots may be safely zero initialized because of [3, 4].
         //  [3] `Slot::msg` (UnsafeCell) may be safely zero initialized because it
         //       holds a MaybeUninit.
         //  [4] `Slot::state` (AtomicUsize) may be safely zero initialized.
-        unsafe { MaybeUninit::zeroed().assume_init() }
+        let block = unsafe { alloc::alloc_zeroed(layout) } as *mut Block<T>;
+        if block.is_null() {
+            alloc::handle_alloc_error(layout);
+        }
+        block
+    }
+
+    /// Deallocates a block with `cap` slots.
+    unsafe fn dealloc(this: *mut Block<T>, cap: usize) {
+        alloc::dealloc(this as *mut u8, Self::layout(cap));
+    }
+
+    /// Resets a block with `cap` slots so that it can be reused as an empty block.
+    unsafe fn reset(this: *mut Block<T>, cap: usize) {
+        (*this).next.store(ptr::null_mut(), Ordering::Relaxed);
+        for i in 0..cap {
+            Block::slot(this, i).state.store(0, Ordering::Relaxed);
+        }
+    }
+
+    /// Returns the slot at index `i`.
+    unsafe fn slot<'a>(this: *const Block<T>, i: usize) -> &'a Slot<T> {
+        let slots = (this as *const u8).add(mem::size_of::<Block<T>>()) as *const Slot<T>;
+        &*slots.add(i)
     }
 
     /// Waits until the next pointer is set.
@@ -92,25 +132,40 @@ impl<T> Block<T> {
             backoff.snooze();
         }
     }
+}
 
-    /// Sets the `DESTROY` bit in slots starting from `start` and destroys the block.
-    unsafe fn destroy(this: *mut Block<T>, start: usize) {
-        // It is not necessary to set the `DESTROY` bit in the last slot because that slot has
-        // begun destruction of the block.
-        for i in start..BLOCK_CAP - 1 {
-            let slot = (*this).slots.get_unchecked(i);
+/// A cache of freed blocks that can be reused.
+struct BlockCache<T> {
+    /// The cached blocks.
+    blocks: [*mut Block<T>; MAX_CACHED_BLOCKS],
 
-            // Mark the `DESTROY` bit if a thread is still using the slot.
-            if slot.state.load(Ordering::Acquire) & READ == 0
-                && slot.state.fetch_or(DESTROY, Ordering::AcqRel) & READ == 0
-            {
-                // If a thread is still using the slot, it will continue destruction of the block.
-                return;
-            }
-        }
+    /// The number of cached blocks.
+    len: usize,
+}
 
-        // No thread is using the block, now it is safe to destroy it.
-        drop(Box::from_raw(this));
+/// A block that has been allocated but not yet installed into the linked list.
+///
+/// If dropped, the block is given back to the channel.
+struct OwnedBlock<'a, T> {
+    /// The channel the block belongs to.
+    chan: &'a Channel<T>,
+
+    /// The block.
+    block: *mut Block<T>,
+}
+
+impl<T> OwnedBlock<'_, T> {
+    /// Returns the raw pointer to the block, keeping it allocated.
+    fn into_raw(self) -> *mut Block<T> {
+        let block = self.block;
+        mem::forget(self);
+        block
+    }
+}
+
+impl<T> Drop for OwnedBlock<'_, T> {
+    fn drop(&mut self) {
+        unsafe { self.chan.release_block(self.block) }
     }
 }
 
@@ -144,13 +199,22 @@ impl Default for ListToken {
     }
 }
 
+impl ListToken {
+    /// Returns `true` if the operation found the channel disconnected.
+    #[inline]
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.block.is_null()
+    }
+}
+
 /// Unbounded channel implemented as a linked list.
 ///
 /// Each message sent into the channel is assigned a sequence number, i.e. an index. Indices are
 /// represented as numbers of type `usize` and wrap on overflow.
 ///
 /// Consecutive messages are grouped into blocks in order to put less pressure on the allocator and
-/// improve cache efficiency.
+/// improve cache efficiency. Freed blocks are kept in a small cache and reused, so that repeatedly
+/// filling and draining the channel doesn't allocate a new block every time.
 pub(crate) struct Channel<T> {
     /// The head of the channel.
     head: CachePadded<Position<T>>,
@@ -158,9 +222,21 @@ pub(crate) struct Channel<T> {
     /// The tail of the channel.
     tail: CachePadded<Position<T>>,
 
+    /// The number of indices covered by one block. Always a power of two.
+    lap: usize,
+
+    /// The base-2 logarithm of `lap`.
+    lap_shift: u32,
+
+    /// Freed blocks kept around for reuse.
+    cache: Spinlock<BlockCache<T>>,
+
     /// Receivers waiting while the channel is empty and not disconnected.
     receivers: SyncWaker,
 
+    /// Senders watching for the channel to become disconnected.
+    senders: SyncWaker,
+
     /// Indicates that dropping a `Channel<T>` may drop messages of type `T`.
     _marker: PhantomData<T>,
 }
@@ -168,6 +244,21 @@ pub(crate) struct Channel<T> {
 impl<T> Channel<T> {
     /// Creates a new unbounded channel.
     pub(crate) fn new() -> Self {
+        Self::with_block_cap(BLOCK_CAP)
+    }
+
+    /// Creates a new unbounded channel with blocks that hold at least `block_cap` messages.
+    ///
+    /// The block capacity is rounded up to one less than a power of two.
+    pub(crate) fn with_block_cap(block_cap: usize) -> Self {
+        assert!(block_cap > 0, "block size must be positive");
+        let lap = block_cap
+            .checked_add(1)
+            .and_then(|lap| lap.checked_next_power_of_two())
+            .expect("block size overflow");
+        // Indices are shifted by `SHIFT`, so make sure a lap fits into the index space.
+        assert!(lap <= usize::max_value() >> SHIFT, "block size overflow");
+
         Channel {
             head: CachePadded::new(Position {
                 block: AtomicPtr::new(ptr::null_mut()),
@@ -177,7 +268,14 @@ impl<T> Channel<T> {
                 block: AtomicPtr::new(ptr::null_mut()),
                 index: AtomicUsize::new(0),
             }),
+            lap,
+            lap_shift: lap.trailing_zeros(),
+            cache: Spinlock::new(BlockCache {
+                blocks: [ptr::null_mut(); MAX_CACHED_BLOCKS],
+                len: 0,
+            }),
             receivers: SyncWaker::new(),
+            senders: SyncWaker::new(),
             _marker: PhantomData,
         }
     }
@@ -192,6 +290,102 @@ impl<T> Channel<T> {
         Sender(self)
     }
 
+    /// Returns the maximum number of messages a block can hold.
+    #[inline]
+    fn block_cap(&self) -> usize {
+        self.lap - 1
+    }
+
+    /// Returns the offset of an index into its block.
+    #[inline]
+    fn offset(&self, index: usize) -> usize {
+        (index >> SHIFT) & (self.lap - 1)
+    }
+
+    /// Returns the lap an index belongs to.
+    #[inline]
+    fn lap_of(&self, index: usize) -> usize {
+        (index >> SHIFT) >> self.lap_shift
+    }
+
+    /// Returns an empty block, reusing a cached one if possible.
+    fn new_block(&self) -> OwnedBlock<'_, T> {
+        let cached = {
+            let mut cache = self.cache.lock();
+            if cache.len > 0 {
+                cache.len -= 1;
+                let len = cache.len;
+                Some(cache.blocks[len])
+            } else {
+                None
+            }
+        };
+
+        OwnedBlock {
+            chan: self,
+            block: cached.unwrap_or_else(|| Block::alloc(self.block_cap())),
+        }
+    }
+
+    /// Gives a block that is no longer in use back to the channel.
+    ///
+    /// The block is kept in the cache for reuse, or deallocated if the cache is full.
+    unsafe fn release_block(&self, block: *mut Block<T>) {
+        Block::reset(block, self.block_cap());
+
+        let cached = {
+            let mut cache = self.cache.lock();
+            if cache.len < MAX_CACHED_BLOCKS {
+                let len = cache.len;
+                cache.blocks[len] = block;
+                cache.len += 1;
+                true
+            } else {
+                false
+            }
+        };
+
+        if !cached {
+            Block::dealloc(block, self.block_cap());
+        }
+    }
+
+    /// Deallocates the blocks kept in the cache.
+    pub(crate) fn shrink_to_fit(&self) {
+        let mut blocks = [ptr::null_mut(); MAX_CACHED_BLOCKS];
+        let len = {
+            let mut cache = self.cache.lock();
+            let len = cache.len;
+            blocks[..len].copy_from_slice(&cache.blocks[..len]);
+            cache.len = 0;
+            len
+        };
+
+        for &block in &blocks[..len] {
+            unsafe { Block::dealloc(block, self.block_cap()) };
+        }
+    }
+
+    /// Sets the `DESTROY` bit in slots starting from `start` and destroys the block.
+    unsafe fn destroy_block(&self, block: *mut Block<T>, start: usize) {
+        // It is not necessary to set the `DESTROY` bit in the last slot because that slot has
+        // begun destruction of the block.
+        for i in start..self.block_cap() - 1 {
+            let slot = Block::slot(block, i);
+
+            // Mark the `DESTROY` bit if a thread is still using the slot.
+            if slot.state.load(Ordering::Acquire) & READ == 0
+                && slot.state.fetch_or(DESTROY, Ordering::AcqRel) & READ == 0
+            {
+                // If a thread is still using the slot, it will continue destruction of the block.
+                return;
+            }
+        }
+
+        // No thread is using the block, now it is safe to destroy it.
+        self.release_block(block);
+    }
+
     /// Attempts to reserve a slot for sending a message.
     fn start_send(&self, token: &mut Token) -> bool {
         let backoff = Backoff::new();
@@ -207,10 +401,10 @@ impl<T> Channel<T> {
             }
 
             // Calculate the offset of the index into the block.
-            let offset = (tail >> SHIFT) % LAP;
+            let offset = self.offset(tail);
 
             // If we reached the end of the block, wait until the next one is installed.
-            if offset == BLOCK_CAP {
+            if offset == self.block_cap() {
                 backoff.snooze();
                 tail = self.tail.index.load(Ordering::Acquire);
                 block = self.tail.block.load(Ordering::Acquire);
@@ -219,25 +413,26 @@ impl<T> Channel<T> {
 
             // If we're going to have to install the next block, allocate it in advance in order to
             // make the wait for other threads as short as possible.
-            if offset + 1 == BLOCK_CAP && next_block.is_none() {
-                next_block = Some(Box::new(Block::<T>::new()));
+            if offset + 1 == self.block_cap() && next_block.is_none() {
+                next_block = Some(self.new_block());
             }
 
             // If this is the first message to be sent into the channel, we need to allocate the
             // first block and install it.
             if block.is_null() {
-                let new = Box::into_raw(Box::new(Block::<T>::new()));
+                let new = self.new_block();
 
                 if self
                     .tail
                     .block
-                    .compare_exchange(block, new, Ordering::Release, Ordering::Relaxed)
+                    .compare_exchange(block, new.block, Ordering::Release, Ordering::Relaxed)
                     .is_ok()
                 {
+                    let new = new.into_raw();
                     self.head.block.store(new, Ordering::Release);
                     block = new;
                 } else {
-                    next_block = unsafe { Some(Box::from_raw(new)) };
+                    next_block = Some(new);
                     tail = self.tail.index.load(Ordering::Acquire);
                     block = self.tail.block.load(Ordering::Acquire);
                     continue;
@@ -255,8 +450,8 @@ impl<T> Channel<T> {
             ) {
                 Ok(_) => unsafe {
                     // If we've reached the end of the block, install the next one.
-                    if offset + 1 == BLOCK_CAP {
-                        let next_block = Box::into_raw(next_block.unwrap());
+                    if offset + 1 == self.block_cap() {
+                        let next_block = next_block.take().unwrap().into_raw();
                         self.tail.block.store(next_block, Ordering::Release);
                         self.tail.index.fetch_add(1 << SHIFT, Ordering::Release);
                         (*block).next.store(next_block, Ordering::Release);
@@ -285,7 +480,7 @@ impl<T> Channel<T> {
         // Write the message into the slot.
         let block = token.list.block as *mut Block<T>;
         let offset = token.list.offset;
-        let slot = (*block).slots.get_unchecked(offset);
+        let slot = Block::slot(block, offset);
         slot.msg.get().write(MaybeUninit::new(msg));
         slot.state.fetch_or(WRITE, Ordering::Release);
 
@@ -302,10 +497,10 @@ impl<T> Channel<T> {
 
         loop {
             // Calculate the offset of the index into the block.
-            let offset = (head >> SHIFT) % LAP;
+            let offset = self.offset(head);
 
             // If we reached the end of the block, wait until the next one is installed.
-            if offset == BLOCK_CAP {
+            if offset == self.block_cap() {
                 backoff.snooze();
                 head = self.head.index.load(Ordering::Acquire);
                 block = self.head.block.load(Ordering::Acquire);
@@ -332,7 +527,7 @@ impl<T> Channel<T> {
                 }
 
                 // If head and tail are not in the same block, set `MARK_BIT` in head.
-                if (head >> SHIFT) / LAP != (tail >> SHIFT) / LAP {
+                if self.lap_of(head) != self.lap_of(tail) {
                     new_head |= MARK_BIT;
                 }
             }
@@ -355,7 +550,7 @@ impl<T> Channel<T> {
             ) {
                 Ok(_) => unsafe {
                     // If we've reached the end of the block, move to the next one.
-                    if offset + 1 == BLOCK_CAP {
+                    if offset + 1 == self.block_cap() {
                         let next = (*block).wait_next();
                         let mut next_index = (new_head & !MARK_BIT).wrapping_add(1 << SHIFT);
                         if !(*next).next.load(Ordering::Relaxed).is_null() {
@@ -389,16 +584,16 @@ impl<T> Channel<T> {
         // Read the message.
         let block = token.list.block as *mut Block<T>;
         let offset = token.list.offset;
-        let slot = (*block).slots.get_unchecked(offset);
+        let slot = Block::slot(block, offset);
         slot.wait_write();
         let msg = slot.msg.get().read().assume_init();
 
         // Destroy the block if we've reached the end, or if another thread wanted to destroy but
         // couldn't because we were busy reading from the slot.
-        if offset + 1 == BLOCK_CAP {
-            Block::destroy(block, 0);
+        if offset + 1 == self.block_cap() {
+            self.destroy_block(block, 0);
         } else if slot.state.fetch_or(READ, Ordering::AcqRel) & DESTROY != 0 {
-            Block::destroy(block, offset + 1);
+            self.destroy_block(block, offset + 1);
         }
 
         Ok(msg)
@@ -503,24 +698,24 @@ impl<T> Channel<T> {
                 head &= !((1 << SHIFT) - 1);
 
                 // Fix up indices if they fall onto block ends.
-                if (tail >> SHIFT) & (LAP - 1) == LAP - 1 {
+                if self.offset(tail) == self.block_cap() {
                     tail = tail.wrapping_add(1 << SHIFT);
                 }
-                if (head >> SHIFT) & (LAP - 1) == LAP - 1 {
+                if self.offset(head) == self.block_cap() {
                     head = head.wrapping_add(1 << SHIFT);
                 }
 
                 // Rotate indices so that head falls into the first block.
-                let lap = (head >> SHIFT) / LAP;
-                tail = tail.wrapping_sub((lap * LAP) << SHIFT);
-                head = head.wrapping_sub((lap * LAP) << SHIFT);
+                let lap = self.lap_of(head);
+                tail = tail.wrapping_sub((lap * self.lap) << SHIFT);
+                head = head.wrapping_sub((lap * self.lap) << SHIFT);
 
                 // Remove the lower bits.
                 tail >>= SHIFT;
                 head >>= SHIFT;
 
                 // Return the difference minus the number of blocks between tail and head.
-                return tail - head - tail / LAP;
+                return tail - head - (tail >> self.lap_shift);
             }
         }
     }
@@ -538,6 +733,7 @@ impl<T> Channel<T> {
 
         if tail & MARK_BIT == 0 {
             self.receivers.disconnect();
+            self.senders.disconnect();
             true
         } else {
             false
@@ -575,17 +771,17 @@ impl<T> Drop for Channel<T> {
         unsafe {
             // Drop all messages between head and tail and deallocate the heap-allocated blocks.
             while head != tail {
-                let offset = (head >> SHIFT) % LAP;
+                let offset = self.offset(head);
 
-                if offset < BLOCK_CAP {
+                if offset < self.block_cap() {
                     // Drop the message in the slot.
-                    let slot = (*block).slots.get_unchecked(offset);
+                    let slot = Block::slot(block, offset);
                     let p = &mut *slot.msg.get();
                     p.as_mut_ptr().drop_in_place();
                 } else {
                     // Deallocate the block and move to the next one.
                     let next = (*block).next.load(Ordering::Relaxed);
-                    drop(Box::from_raw(block));
+                    Block::dealloc(block, self.block_cap());
                     block = next;
                 }
 
@@ -594,7 +790,13 @@ impl<T> Drop for Channel<T> {
 
             // Deallocate the last remaining block.
             if !block.is_null() {
-                drop(Box::from_raw(block));
+                Block::dealloc(block, self.block_cap());
+            }
+
+            // Deallocate the cached blocks.
+            let cache = self.cache.lock();
+            for &block in &cache.blocks[..cache.len] {
+                Block::dealloc(block, self.block_cap());
             }
         }
     }
@@ -665,9 +867,13 @@ impl<T> SelectHandle for Sender<'_, T> {
         true
     }
 
-    fn watch(&self, _oper: Operation, _cx: &Context) -> bool {
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        // Sending never blocks, but a watcher may still be waiting for disconnection.
+        self.0.senders.watch(oper, cx);
         self.is_ready()
     }
 
-    fn unwatch(&self, _oper: Operation) {}
+    fn unwatch(&self, oper: Operation) {
+        self.0.senders.unwatch(oper);
+    }
 }
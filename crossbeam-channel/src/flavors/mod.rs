@@ -1,17 +1,27 @@
 //! Channel flavors.
 //!
-//! There are six flavors:
+//! There are nine flavors:
 //!
 //! 1. `at` - Channel that delivers a message after a certain amount of time.
 //! 2. `array` - Bounded channel based on a preallocated array.
-//! 3. `list` - Unbounded channel implemented as a linked list.
-//! 4. `never` - Channel that never delivers messages.
-//! 5. `tick` - Channel that delivers messages periodically.
-//! 6. `zero` - Zero-capacity channel.
+//! 3. `lanes` - Bounded channel split into multiple arrays.
+//! 4. `list` - Unbounded channel implemented as a linked list.
+//! 5. `never` - Channel that never delivers messages.
+//! 6. `sharded` - Unbounded channel split into multiple linked lists.
+//! 7. `tick` - Channel that delivers messages periodically.
+//! 8. `zero` - Zero-capacity channel.
+//! 9. `adapter` - Channel that transforms messages passing through another channel.
+//!
+//! The `lanes` and `sharded` flavors are both built on `split`, which spreads a channel across
+//! several smaller channels of another flavor.
 
+pub(crate) mod adapter;
 pub(crate) mod array;
 pub(crate) mod at;
+pub(crate) mod lanes;
 pub(crate) mod list;
 pub(crate) mod never;
+pub(crate) mod sharded;
+pub(crate) mod split;
 pub(crate) mod tick;
 pub(crate) mod zero;
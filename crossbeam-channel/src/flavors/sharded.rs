@@ -0,0 +1,84 @@
+//! Unbounded channel split into multiple shards.
+//!
+//! This flavor is a set of linked-list channels called shards. Each sending thread always sends
+//! into the same shard, while receivers merge messages from all shards. Producers sending into
+//! different shards never touch the same tail index, which greatly reduces contention when many
+//! threads send into the same channel at the same time. Messages are delivered in the order they
+//! were sent by each thread, but there is no ordering between messages sent by different threads.
+
+use std::time::Instant;
+
+use crate::err::{SendTimeoutError, TrySendError};
+use crate::flavors::list;
+use crate::flavors::split::{self, Part};
+use crate::select::{SelectHandle, Token};
+
+/// Unbounded channel split into multiple shards.
+pub(crate) type Channel<T> = split::Channel<T, list::Channel<T>>;
+
+impl<T> Channel<T> {
+    /// Creates an unbounded channel split into `shards` shards.
+    pub(crate) fn new(shards: usize) -> Self {
+        assert!(shards > 0, "number of shards must be positive");
+
+        Channel::from_parts((0..shards).map(|_| list::Channel::new()).collect())
+    }
+}
+
+impl<T> Part<T> for list::Channel<T> {
+    fn with_sender<R>(&self, f: impl FnOnce(&dyn SelectHandle) -> R) -> R {
+        f(&self.sender())
+    }
+
+    fn with_receiver<R>(&self, f: impl FnOnce(&dyn SelectHandle) -> R) -> R {
+        f(&self.receiver())
+    }
+
+    fn found_disconnected(token: &Token) -> bool {
+        token.list.is_disconnected()
+    }
+
+    unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
+        self.write(token, msg)
+    }
+
+    unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        self.read(token)
+    }
+
+    fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.try_send(msg)
+    }
+
+    fn send(&self, msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        self.send(msg, deadline)
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        self.capacity()
+    }
+
+    fn shrink_to_fit(&self) {
+        self.shrink_to_fit()
+    }
+
+    fn disconnect(&self) -> bool {
+        self.disconnect()
+    }
+
+    fn is_disconnected(&self) -> bool {
+        self.is_disconnected()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.is_empty()
+    }
+
+    fn is_full(&self) -> bool {
+        self.is_full()
+    }
+}
@@ -0,0 +1,369 @@
+//! Channel split into multiple smaller channels.
+//!
+//! This is the implementation shared by the `lanes` and `sharded` flavors. The channel is a set of
+//! smaller channels called parts. Each sending thread always sends into the same part, while
+//! receivers take messages from any part. Senders spread across parts don't contend on the same
+//! head and tail indices when many threads send into the channel at the same time.
+
+use std::cell::Cell;
+use std::marker::PhantomData;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::Instant;
+
+use crossbeam_utils::Backoff;
+
+use crate::context::Context;
+use crate::err::{RecvTimeoutError, SendTimeoutError, TryRecvError, TrySendError};
+use crate::select::{Operation, SelectHandle, Selected, Token};
+
+/// The token type for split channels.
+///
+/// Holds the index of the part the operation was started on. The rest of the operation is
+/// described by the token of the part.
+pub(crate) type SplitToken = usize;
+
+/// Returns the part index preferred by the current thread, which is assigned on first use.
+fn thread_part() -> usize {
+    static NEXT: AtomicUsize = AtomicUsize::new(0);
+
+    thread_local! {
+        static PART: usize = NEXT.fetch_add(1, Ordering::Relaxed);
+    }
+
+    PART.try_with(|part| *part)
+        .unwrap_or_else(|_| NEXT.fetch_add(1, Ordering::Relaxed))
+}
+
+/// Returns the part index at which the current thread should start looking for messages.
+fn thread_recv_start() -> usize {
+    thread_local! {
+        static START: Cell<usize> = Cell::new(thread_part());
+    }
+
+    START
+        .try_with(|start| {
+            let s = start.get();
+            start.set(s.wrapping_add(1));
+            s
+        })
+        .unwrap_or(0)
+}
+
+/// A channel flavor that can be used as a part of a split channel.
+pub(crate) trait Part<T> {
+    /// Calls `f` with the sender handle of the part.
+    fn with_sender<R>(&self, f: impl FnOnce(&dyn SelectHandle) -> R) -> R;
+
+    /// Calls `f` with the receiver handle of the part.
+    fn with_receiver<R>(&self, f: impl FnOnce(&dyn SelectHandle) -> R) -> R;
+
+    /// Returns `true` if a selected receive operation found the part empty and disconnected.
+    fn found_disconnected(token: &Token) -> bool;
+
+    /// Writes a message into the part.
+    unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T>;
+
+    /// Reads a message from the part.
+    unsafe fn read(&self, token: &mut Token) -> Result<T, ()>;
+
+    /// Attempts to send a message into the part.
+    fn try_send(&self, msg: T) -> Result<(), TrySendError<T>>;
+
+    /// Sends a message into the part.
+    fn send(&self, msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>>;
+
+    /// Returns the current number of messages inside the part.
+    fn len(&self) -> usize;
+
+    /// Returns the capacity of the part.
+    fn capacity(&self) -> Option<usize>;
+
+    /// Releases memory the part keeps around for reuse.
+    fn shrink_to_fit(&self);
+
+    /// Disconnects the part.
+    fn disconnect(&self) -> bool;
+
+    /// Returns `true` if the part is disconnected.
+    fn is_disconnected(&self) -> bool;
+
+    /// Returns `true` if the part is empty.
+    fn is_empty(&self) -> bool;
+
+    /// Returns `true` if the part is full.
+    fn is_full(&self) -> bool;
+}
+
+/// Channel split into multiple smaller channels.
+pub(crate) struct Channel<T, P> {
+    /// The parts.
+    parts: Box<[P]>,
+
+    /// Indicates that the parts hold messages of type `T`.
+    _marker: PhantomData<T>,
+}
+
+impl<T, P: Part<T>> Channel<T, P> {
+    /// Creates a channel out of the given parts.
+    pub(crate) fn from_parts(parts: Box<[P]>) -> Self {
+        debug_assert!(!parts.is_empty());
+        Channel {
+            parts,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a receiver handle to the channel.
+    pub(crate) fn receiver(&self) -> Receiver<'_, T, P> {
+        Receiver(self)
+    }
+
+    /// Returns a sender handle to the channel.
+    pub(crate) fn sender(&self) -> Sender<'_, T, P> {
+        Sender(self)
+    }
+
+    /// Returns the part the current thread sends into.
+    fn send_part(&self) -> &P {
+        &self.parts[thread_part() % self.parts.len()]
+    }
+
+    /// Attempts to reserve a slot for sending a message.
+    fn start_send(&self, token: &mut Token) -> bool {
+        let part = thread_part() % self.parts.len();
+        token.split = part;
+        self.parts[part].with_sender(|s| s.try_select(token))
+    }
+
+    /// Writes a message into the channel.
+    pub(crate) unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
+        self.parts[token.split].write(token, msg)
+    }
+
+    /// Attempts to reserve a slot for receiving a message.
+    fn start_recv(&self, token: &mut Token) -> bool {
+        let len = self.parts.len();
+        let start = thread_recv_start();
+        let mut disconnected = 0;
+
+        for i in 0..len {
+            let part = (start + i) % len;
+
+            if self.parts[part].with_receiver(|r| r.try_select(token)) {
+                if !P::found_disconnected(token) {
+                    token.split = part;
+                    return true;
+                }
+
+                // This part is empty and disconnected, but other parts might still have messages.
+                disconnected += 1;
+            }
+        }
+
+        // The channel is disconnected only if all parts are empty and disconnected.
+        disconnected == len
+    }
+
+    /// Reads a message from the channel.
+    pub(crate) unsafe fn read(&self, token: &mut Token) -> Result<T, ()> {
+        self.parts[token.split].read(token)
+    }
+
+    /// Attempts to send a message into the channel.
+    pub(crate) fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.send_part().try_send(msg)
+    }
+
+    /// Sends a message into the channel.
+    pub(crate) fn send(
+        &self,
+        msg: T,
+        deadline: Option<Instant>,
+    ) -> Result<(), SendTimeoutError<T>> {
+        self.send_part().send(msg, deadline)
+    }
+
+    /// Attempts to receive a message without blocking.
+    pub(crate) fn try_recv(&self) -> Result<T, TryRecvError> {
+        let token = &mut Token::default();
+
+        if self.start_recv(token) {
+            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
+        } else {
+            Err(TryRecvError::Empty)
+        }
+    }
+
+    /// Receives a message from the channel.
+    pub(crate) fn recv(&self, deadline: Option<Instant>) -> Result<T, RecvTimeoutError> {
+        let token = &mut Token::default();
+        loop {
+            // Try receiving a message several times.
+            let backoff = Backoff::new();
+            loop {
+                if self.start_recv(token) {
+                    let res = unsafe { self.read(token) };
+                    return res.map_err(|_| RecvTimeoutError::Disconnected);
+                }
+
+                if backoff.is_completed() {
+                    break;
+                } else {
+                    backoff.snooze();
+                }
+            }
+
+            if let Some(d) = deadline {
+                if Instant::now() >= d {
+                    return Err(RecvTimeoutError::Timeout);
+                }
+            }
+
+            Context::with(|cx| {
+                // Prepare for blocking until a sender wakes us up.
+                let oper = Operation::hook(token);
+                let receiver = self.receiver();
+
+                // Has the channel become ready just now?
+                if receiver.register(oper, cx) {
+                    let _ = cx.try_select(Selected::Aborted);
+                }
+
+                // Block the current thread.
+                cx.wait_until(deadline);
+
+                // The operation might be registered with parts that didn't wake us up.
+                receiver.unregister(oper);
+            });
+        }
+    }
+
+    /// Returns the current number of messages inside the channel.
+    pub(crate) fn len(&self) -> usize {
+        self.parts.iter().map(|part| part.len()).sum()
+    }
+
+    /// Returns the capacity of the channel.
+    pub(crate) fn capacity(&self) -> Option<usize> {
+        self.parts
+            .iter()
+            .try_fold(0, |cap, part| Some(cap + part.capacity()?))
+    }
+
+    /// Releases memory the parts keep around for reuse.
+    pub(crate) fn shrink_to_fit(&self) {
+        for part in self.parts.iter() {
+            part.shrink_to_fit();
+        }
+    }
+
+    /// Disconnects the channel and wakes up all blocked senders and receivers.
+    ///
+    /// Returns `true` if this call disconnected the channel.
+    pub(crate) fn disconnect(&self) -> bool {
+        let mut disconnected = false;
+        for part in self.parts.iter() {
+            disconnected |= part.disconnect();
+        }
+        disconnected
+    }
+
+    /// Returns `true` if the channel is disconnected.
+    pub(crate) fn is_disconnected(&self) -> bool {
+        self.parts.iter().all(|part| part.is_disconnected())
+    }
+
+    /// Returns `true` if the channel is empty.
+    pub(crate) fn is_empty(&self) -> bool {
+        self.parts.iter().all(|part| part.is_empty())
+    }
+
+    /// Returns `true` if the channel is full.
+    pub(crate) fn is_full(&self) -> bool {
+        self.parts.iter().all(|part| part.is_full())
+    }
+}
+
+/// Receiver handle to a channel.
+pub(crate) struct Receiver<'a, T, P>(&'a Channel<T, P>);
+
+/// Sender handle to a channel.
+pub(crate) struct Sender<'a, T, P>(&'a Channel<T, P>);
+
+impl<T, P: Part<T>> SelectHandle for Receiver<'_, T, P> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_recv(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        for part in self.0.parts.iter() {
+            part.with_receiver(|r| r.register(oper, cx));
+        }
+        self.is_ready()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        for part in self.0.parts.iter() {
+            part.with_receiver(|r| r.unregister(oper));
+        }
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        !self.0.is_empty() || self.0.is_disconnected()
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        for part in self.0.parts.iter() {
+            part.with_receiver(|r| r.watch(oper, cx));
+        }
+        self.is_ready()
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        for part in self.0.parts.iter() {
+            part.with_receiver(|r| r.unwatch(oper));
+        }
+    }
+}
+
+impl<T, P: Part<T>> SelectHandle for Sender<'_, T, P> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        self.0.start_send(token)
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        None
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.send_part().with_sender(|s| s.register(oper, cx))
+    }
+
+    fn unregister(&self, oper: Operation) {
+        self.0.send_part().with_sender(|s| s.unregister(oper));
+    }
+
+    fn accept(&self, token: &mut Token, _cx: &Context) -> bool {
+        self.try_select(token)
+    }
+
+    fn is_ready(&self) -> bool {
+        self.0.send_part().with_sender(|s| s.is_ready())
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.0.send_part().with_sender(|s| s.watch(oper, cx))
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.0.send_part().with_sender(|s| s.unwatch(oper));
+    }
+}
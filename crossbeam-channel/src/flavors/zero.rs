@@ -4,7 +4,7 @@
 
 use std::cell::UnsafeCell;
 use std::marker::PhantomData;
-use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::time::Instant;
 
 use crossbeam_utils::Backoff;
@@ -18,6 +18,9 @@ use crate::waker::Waker;
 /// A pointer to a packet.
 pub(crate) type ZeroToken = usize;
 
+/// How many heap-allocated packets are kept around for reuse.
+const MAX_CACHED_PACKETS: usize = 4;
+
 /// A slot for passing one message from a sender to a receiver.
 struct Packet<T> {
     /// Equals `true` if the packet is allocated on the stack.
@@ -58,6 +61,13 @@ impl<T> Packet<T> {
         }
     }
 
+    /// Resets a heap-allocated packet so that it can be reused.
+    fn reset(&mut self) {
+        debug_assert!(!self.on_stack);
+        *self.ready.get_mut() = false;
+        self.msg = UnsafeCell::new(None);
+    }
+
     /// Waits until the packet becomes ready for reading or writing.
     fn wait_ready(&self) {
         let backoff = Backoff::new();
@@ -77,6 +87,12 @@ struct Inner {
 
     /// Equals `true` when the channel is disconnected.
     is_disconnected: bool,
+
+    /// Empty heap-allocated packets that can be reused by selection.
+    packets: [usize; MAX_CACHED_PACKETS],
+
+    /// The number of cached packets.
+    cached: usize,
 }
 
 /// Zero-capacity channel.
@@ -84,6 +100,12 @@ pub(crate) struct Channel<T> {
     /// Inner representation of the channel.
     inner: Spinlock<Inner>,
 
+    /// A packet offered by a sender that is spinning before it blocks, or zero.
+    ///
+    /// A receiver that arrives in the meantime takes the packet and reads the message without
+    /// registering or waking anyone up.
+    offer: AtomicUsize,
+
     /// Indicates that dropping a `Channel<T>` may drop values of type `T`.
     _marker: PhantomData<T>,
 }
@@ -96,7 +118,10 @@ impl<T> Channel<T> {
                 senders: Waker::new(),
                 receivers: Waker::new(),
                 is_disconnected: false,
+                packets: [0; MAX_CACHED_PACKETS],
+                cached: 0,
             }),
+            offer: AtomicUsize::new(0),
             _marker: PhantomData,
         }
     }
@@ -111,6 +136,107 @@ impl<T> Channel<T> {
         Sender(self)
     }
 
+    /// Returns an empty heap-allocated packet, reusing a cached one if possible.
+    fn alloc_packet(inner: &mut Inner) -> usize {
+        if inner.cached > 0 {
+            inner.cached -= 1;
+            inner.packets[inner.cached]
+        } else {
+            Box::into_raw(Packet::<T>::empty_on_heap()) as usize
+        }
+    }
+
+    /// Gives back a heap-allocated packet that is no longer in use.
+    ///
+    /// The packet is cached for reuse, or deallocated if the cache is full.
+    unsafe fn release_packet(&self, packet: usize) {
+        let mut packet = Box::from_raw(packet as *mut Packet<T>);
+        packet.reset();
+
+        let mut inner = self.inner.lock();
+        if inner.cached < MAX_CACHED_PACKETS {
+            let i = inner.cached;
+            inner.packets[i] = Box::into_raw(packet) as usize;
+            inner.cached += 1;
+        } else {
+            drop(inner);
+            drop(packet);
+        }
+    }
+
+    /// Deallocates the packets kept in the cache.
+    pub(crate) fn shrink_to_fit(&self) {
+        let mut packets = [0; MAX_CACHED_PACKETS];
+        let cached = {
+            let mut inner = self.inner.lock();
+            let cached = inner.cached;
+            packets[..cached].copy_from_slice(&inner.packets[..cached]);
+            inner.cached = 0;
+            cached
+        };
+
+        for &packet in &packets[..cached] {
+            unsafe {
+                drop(Box::from_raw(packet as *mut Packet<T>));
+            }
+        }
+    }
+
+    /// Takes the packet offered by a spinning sender, if there is one.
+    fn take_offer(&self) -> Option<usize> {
+        let packet = self.offer.load(Ordering::Acquire);
+        if packet != 0
+            && self
+                .offer
+                .compare_exchange(packet, 0, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+        {
+            Some(packet)
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if a spinning sender is offering a message.
+    fn has_offer(&self) -> bool {
+        self.offer.load(Ordering::SeqCst) != 0
+    }
+
+    /// Offers a message to receivers for a short while before the sender blocks.
+    ///
+    /// Returns the message back if no receiver has taken it.
+    fn offer(&self, msg: T) -> Result<(), T> {
+        let packet = Packet::<T>::message_on_stack(msg);
+        let ptr = &packet as *const Packet<T> as usize;
+
+        if self
+            .offer
+            .compare_exchange(0, ptr, Ordering::AcqRel, Ordering::Relaxed)
+            .is_ok()
+        {
+            let backoff = Backoff::new();
+            while !backoff.is_completed() {
+                if self.offer.load(Ordering::Acquire) != ptr {
+                    break;
+                }
+                backoff.snooze();
+            }
+
+            // Withdraw the offer, unless a receiver has already taken it.
+            if self
+                .offer
+                .compare_exchange(ptr, 0, Ordering::AcqRel, Ordering::Relaxed)
+                .is_err()
+            {
+                // Wait until the message is read, then drop the packet.
+                packet.wait_ready();
+                return Ok(());
+            }
+        }
+
+        Err(unsafe { packet.msg.get().replace(None).unwrap() })
+    }
+
     /// Attempts to reserve a slot for sending a message.
     fn start_send(&self, token: &mut Token) -> bool {
         let mut inner = self.inner.lock();
@@ -148,6 +274,9 @@ impl<T> Channel<T> {
         if let Some(operation) = inner.senders.try_select() {
             token.zero = operation.packet;
             true
+        } else if let Some(packet) = self.take_offer() {
+            token.zero = packet;
+            true
         } else if inner.is_disconnected {
             token.zero = 0;
             true
@@ -173,11 +302,11 @@ impl<T> Channel<T> {
             packet.ready.store(true, Ordering::Release);
             Ok(msg)
         } else {
-            // Wait until the message becomes available, then read it and destroy the
+            // Wait until the message becomes available, then read it and give back the
             // heap-allocated packet.
             packet.wait_ready();
             let msg = packet.msg.get().replace(None).unwrap();
-            drop(Box::from_raw(packet as *const Packet<T> as *mut Packet<T>));
+            self.release_packet(token.zero);
             Ok(msg)
         }
     }
@@ -205,24 +334,41 @@ impl<T> Channel<T> {
     /// Sends a message into the channel.
     pub(crate) fn send(
         &self,
-        msg: T,
+        mut msg: T,
         deadline: Option<Instant>,
     ) -> Result<(), SendTimeoutError<T>> {
         let token = &mut Token::default();
         let mut inner = self.inner.lock();
+        let mut offered = false;
+
+        loop {
+            // If there's a waiting receiver, pair up with it.
+            if let Some(operation) = inner.receivers.try_select() {
+                token.zero = operation.packet;
+                drop(inner);
+                unsafe {
+                    self.write(token, msg).ok().unwrap();
+                }
+                return Ok(());
+            }
 
-        // If there's a waiting receiver, pair up with it.
-        if let Some(operation) = inner.receivers.try_select() {
-            token.zero = operation.packet;
-            drop(inner);
-            unsafe {
-                self.write(token, msg).ok().unwrap();
+            if inner.is_disconnected {
+                return Err(SendTimeoutError::Disconnected(msg));
             }
-            return Ok(());
-        }
 
-        if inner.is_disconnected {
-            return Err(SendTimeoutError::Disconnected(msg));
+            if offered {
+                break;
+            }
+
+            // A receiver may be about to arrive, so offer the message before blocking.
+            inner.receivers.notify();
+            drop(inner);
+            msg = match self.offer(msg) {
+                Ok(()) => return Ok(()),
+                Err(msg) => msg,
+            };
+            offered = true;
+            inner = self.inner.lock();
         }
 
         Context::with(|cx| {
@@ -269,6 +415,10 @@ impl<T> Channel<T> {
             token.zero = operation.packet;
             drop(inner);
             unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
+        } else if let Some(packet) = self.take_offer() {
+            token.zero = packet;
+            drop(inner);
+            unsafe { self.read(token).map_err(|_| TryRecvError::Disconnected) }
         } else if inner.is_disconnected {
             Err(TryRecvError::Disconnected)
         } else {
@@ -290,6 +440,15 @@ impl<T> Channel<T> {
             }
         }
 
+        // If a sender is spinning with a message, take it.
+        if let Some(packet) = self.take_offer() {
+            token.zero = packet;
+            drop(inner);
+            unsafe {
+                return self.read(token).map_err(|_| RecvTimeoutError::Disconnected);
+            }
+        }
+
         if inner.is_disconnected {
             return Err(RecvTimeoutError::Disconnected);
         }
@@ -364,6 +523,17 @@ impl<T> Channel<T> {
     }
 }
 
+impl<T> Drop for Channel<T> {
+    fn drop(&mut self) {
+        let inner = self.inner.lock();
+        for &packet in &inner.packets[..inner.cached] {
+            unsafe {
+                drop(Box::from_raw(packet as *mut Packet<T>));
+            }
+        }
+    }
+}
+
 /// Receiver handle to a channel.
 pub(crate) struct Receiver<'a, T>(&'a Channel<T>);
 
@@ -380,20 +550,18 @@ impl<T> SelectHandle for Receiver<'_, T> {
     }
 
     fn register(&self, oper: Operation, cx: &Context) -> bool {
-        let packet = Box::into_raw(Packet::<T>::empty_on_heap());
-
         let mut inner = self.0.inner.lock();
-        inner
-            .receivers
-            .register_with_packet(oper, packet as usize, cx);
+        let packet = Channel::<T>::alloc_packet(&mut inner);
+        inner.receivers.register_with_packet(oper, packet, cx);
         inner.senders.notify();
-        inner.senders.can_select() || inner.is_disconnected
+        inner.senders.can_select() || self.0.has_offer() || inner.is_disconnected
     }
 
     fn unregister(&self, oper: Operation) {
-        if let Some(operation) = self.0.inner.lock().receivers.unregister(oper) {
+        let operation = self.0.inner.lock().receivers.unregister(oper);
+        if let Some(operation) = operation {
             unsafe {
-                drop(Box::from_raw(operation.packet as *mut Packet<T>));
+                self.0.release_packet(operation.packet);
             }
         }
     }
@@ -405,13 +573,13 @@ impl<T> SelectHandle for Receiver<'_, T> {
 
     fn is_ready(&self) -> bool {
         let inner = self.0.inner.lock();
-        inner.senders.can_select() || inner.is_disconnected
+        inner.senders.can_select() || self.0.has_offer() || inner.is_disconnected
     }
 
     fn watch(&self, oper: Operation, cx: &Context) -> bool {
         let mut inner = self.0.inner.lock();
         inner.receivers.watch(oper, cx);
-        inner.senders.can_select() || inner.is_disconnected
+        inner.senders.can_select() || self.0.has_offer() || inner.is_disconnected
     }
 
     fn unwatch(&self, oper: Operation) {
@@ -430,20 +598,18 @@ impl<T> SelectHandle for Sender<'_, T> {
     }
 
     fn register(&self, oper: Operation, cx: &Context) -> bool {
-        let packet = Box::into_raw(Packet::<T>::empty_on_heap());
-
         let mut inner = self.0.inner.lock();
-        inner
-            .senders
-            .register_with_packet(oper, packet as usize, cx);
+        let packet = Channel::<T>::alloc_packet(&mut inner);
+        inner.senders.register_with_packet(oper, packet, cx);
         inner.receivers.notify();
         inner.receivers.can_select() || inner.is_disconnected
     }
 
     fn unregister(&self, oper: Operation) {
-        if let Some(operation) = self.0.inner.lock().senders.unregister(oper) {
+        let operation = self.0.inner.lock().senders.unregister(oper);
+        if let Some(operation) = operation {
             unsafe {
-                drop(Box::from_raw(operation.packet as *mut Packet<T>));
+                self.0.release_packet(operation.packet);
             }
         }
     }
@@ -0,0 +1,227 @@
+//! Groups of senders that receive the same messages.
+
+use std::fmt;
+
+use crate::channel::Sender;
+use crate::err::{GroupSendError, TrySendError};
+use crate::select::Select;
+
+/// What a [`Group`] does with members that can't accept a message right away.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SendPolicy {
+    /// Members that are full or disconnected are skipped.
+    Skip,
+
+    /// Blocks until every member that is not disconnected accepts the message.
+    ///
+    /// Disconnected members are skipped.
+    Block,
+
+    /// Stops at the first member that is full or disconnected and returns an error.
+    ///
+    /// Members before the failing one have already received the message.
+    Error,
+}
+
+/// A set of senders that messages are sent to all at once.
+///
+/// [`send_to_all`] sends a clone of a message into every member of the group, and handles members
+/// that are full or disconnected according to the group's [`SendPolicy`].
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_channel::{bounded, unbounded, Group, SendPolicy};
+///
+/// let (s1, r1) = unbounded();
+/// let (s2, r2) = bounded(1);
+///
+/// let mut group = Group::new(SendPolicy::Skip);
+/// group.push(s1);
+/// group.push(s2);
+///
+/// // Both members receive the first message, but the second one is full afterwards.
+/// assert_eq!(group.send_to_all("a"), Ok(2));
+/// assert_eq!(group.send_to_all("b"), Ok(1));
+///
+/// assert_eq!(r1.try_iter().collect::<Vec<_>>(), ["a", "b"]);
+/// assert_eq!(r2.try_iter().collect::<Vec<_>>(), ["a"]);
+/// ```
+///
+/// [`send_to_all`]: Group::send_to_all
+pub struct Group<T> {
+    /// The members of the group.
+    senders: Vec<Sender<T>>,
+
+    /// What to do with members that can't accept a message.
+    policy: SendPolicy,
+}
+
+impl<T> Group<T> {
+    /// Creates an empty group with the given policy.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{Group, SendPolicy};
+    ///
+    /// let group = Group::<i32>::new(SendPolicy::Block);
+    /// assert!(group.is_empty());
+    /// assert_eq!(group.policy(), SendPolicy::Block);
+    /// ```
+    pub fn new(policy: SendPolicy) -> Group<T> {
+        Group {
+            senders: Vec::new(),
+            policy,
+        }
+    }
+
+    /// Adds a sender to the group.
+    pub fn push(&mut self, sender: Sender<T>) {
+        self.senders.push(sender);
+    }
+
+    /// Returns the policy of the group.
+    pub fn policy(&self) -> SendPolicy {
+        self.policy
+    }
+
+    /// Returns the members of the group, in the order they were added.
+    pub fn senders(&self) -> &[Sender<T>] {
+        &self.senders
+    }
+
+    /// Returns the number of members in the group.
+    pub fn len(&self) -> usize {
+        self.senders.len()
+    }
+
+    /// Returns `true` if the group has no members.
+    pub fn is_empty(&self) -> bool {
+        self.senders.is_empty()
+    }
+
+    /// Removes and returns the member at `index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> Sender<T> {
+        self.senders.remove(index)
+    }
+}
+
+impl<T: Clone> Group<T> {
+    /// Sends a clone of a message into every member of the group.
+    ///
+    /// Members that are full or disconnected are handled according to the group's
+    /// [`SendPolicy`]. On success, returns the number of members that received the message.
+    ///
+    /// # Errors
+    ///
+    /// Only groups with the [`SendPolicy::Error`] policy return errors. The error contains the
+    /// index of the first member that could not accept the message, and the message so it can be
+    /// recovered.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{bounded, Group, SendPolicy};
+    ///
+    /// let (s1, r1) = bounded(0);
+    /// let (s2, r2) = bounded(0);
+    ///
+    /// let mut group = Group::new(SendPolicy::Block);
+    /// group.push(s1);
+    /// group.push(s2);
+    ///
+    /// thread::spawn(move || {
+    ///     thread::sleep(Duration::from_millis(100));
+    ///     assert_eq!(r2.recv(), Ok(7));
+    ///     assert_eq!(r1.recv(), Ok(7));
+    /// });
+    ///
+    /// // Blocks until both receivers have taken the message.
+    /// assert_eq!(group.send_to_all(7), Ok(2));
+    /// ```
+    pub fn send_to_all(&self, msg: T) -> Result<usize, GroupSendError<T>> {
+        match self.policy {
+            SendPolicy::Skip => Ok(self
+                .senders
+                .iter()
+                .filter(|s| s.try_send(msg.clone()).is_ok())
+                .count()),
+            SendPolicy::Block => Ok(self.send_blocking(msg)),
+            SendPolicy::Error => {
+                for (index, s) in self.senders.iter().enumerate() {
+                    if let Err(err) = s.try_send(msg.clone()) {
+                        let error = match err {
+                            TrySendError::Full(_) => TrySendError::Full(msg),
+                            TrySendError::Disconnected(_) => TrySendError::Disconnected(msg),
+                        };
+                        return Err(GroupSendError { index, error });
+                    }
+                }
+                Ok(self.senders.len())
+            }
+        }
+    }
+
+    /// Sends a message into all members, blocking until every connected member has accepted it.
+    fn send_blocking(&self, msg: T) -> usize {
+        let mut sent = 0;
+        let mut pending = Vec::new();
+
+        // Send into members that have room right away.
+        for s in &self.senders {
+            match s.try_send(msg.clone()) {
+                Ok(()) => sent += 1,
+                Err(TrySendError::Full(_)) => pending.push(s),
+                Err(TrySendError::Disconnected(_)) => {}
+            }
+        }
+
+        // Wait for the remaining members in whatever order they become ready.
+        while !pending.is_empty() {
+            let mut sel = Select::new();
+            for s in &pending {
+                sel.send(*s);
+            }
+
+            let oper = sel.select();
+            let index = oper.index();
+            if oper.send(pending[index], msg.clone()).is_ok() {
+                sent += 1;
+            }
+            pending.swap_remove(index);
+        }
+
+        sent
+    }
+}
+
+impl<T> Clone for Group<T> {
+    fn clone(&self) -> Group<T> {
+        Group {
+            senders: self.senders.clone(),
+            policy: self.policy,
+        }
+    }
+}
+
+impl<T> Extend<Sender<T>> for Group<T> {
+    fn extend<I: IntoIterator<Item = Sender<T>>>(&mut self, iter: I) {
+        self.senders.extend(iter);
+    }
+}
+
+impl<T> fmt::Debug for Group<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Group")
+            .field("len", &self.senders.len())
+            .field("policy", &self.policy)
+            .finish()
+    }
+}
@@ -316,6 +316,13 @@
 //! }
 //! ```
 //!
+//! To manage many timeouts at once, [`timer_wheel`] creates a [`TimerWheel`] that delivers all of
+//! its expired timers into a single channel, instead of creating an [`after`] channel for each.
+//!
+//! Finally, [`stdin_lines`] creates a channel fed by a background thread that reads lines from the
+//! standard input, which makes it easy to select over user input alongside other channels. To read
+//! lines from another source, use [`lines`].
+//!
 //! [`send`]: Sender::send
 //! [`recv`]: Receiver::recv
 //! [`iter`]: Receiver::iter
@@ -340,13 +347,19 @@ use cfg_if::cfg_if;
 
 cfg_if! {
     if #[cfg(feature = "std")] {
+        mod bytes;
+        mod cancel;
         mod channel;
         mod context;
         mod counter;
         mod err;
         mod flavors;
+        mod group;
+        mod pool;
         mod select;
         mod select_macro;
+        mod throttle;
+        mod timer;
         mod utils;
         mod waker;
 
@@ -357,15 +370,26 @@ cfg_if! {
             pub use crate::select::{select, select_timeout, try_select};
         }
 
-        pub use crate::channel::{after, at, never, tick};
-        pub use crate::channel::{bounded, unbounded};
+        pub use crate::channel::{after, at, lines, never, stdin_lines, tick};
+        pub use crate::channel::{
+            bounded, bounded_fair, bounded_with_lanes, unbounded, unbounded_sharded,
+            unbounded_with_block_size,
+        };
         pub use crate::channel::{IntoIter, Iter, TryIter};
         pub use crate::channel::{Receiver, Sender};
 
-        pub use crate::select::{Select, SelectedOperation};
+        pub use crate::bytes::{byte_channel, ByteReceiver, ByteSender};
+        pub use crate::cancel::CancelToken;
+        pub use crate::group::{Group, SendPolicy};
+        pub use crate::pool::{pool, PoolReceiver, PoolSender};
+        pub use crate::throttle::Throttled;
+        pub use crate::timer::{timer_wheel, TimerToken, TimerWheel};
+
+        pub use crate::select::{Completed, ScopedOperation, Select, SelectedOperation};
 
         pub use crate::err::{ReadyTimeoutError, SelectTimeoutError, TryReadyError, TrySelectError};
+        pub use crate::err::{RecvCancelError, SendCancelError};
         pub use crate::err::{RecvError, RecvTimeoutError, TryRecvError};
-        pub use crate::err::{SendError, SendTimeoutError, TrySendError};
+        pub use crate::err::{GroupSendError, SendError, SendTimeoutError, TrySendError};
     }
 }
@@ -0,0 +1,211 @@
+//! Channels that recycle message buffers.
+
+use std::fmt;
+use std::ops::Deref;
+
+use crate::channel::{bounded, Receiver, Sender};
+
+/// Creates a channel of bounded capacity that recycles consumed messages.
+///
+/// This is a pair of channels: the data channel of capacity `cap`, which carries messages from
+/// senders to receivers, and a free list, which carries consumed messages back to senders so that
+/// they can be reused. This avoids allocating a new buffer for every message in pipelines that
+/// send many large buffers, like `Vec<u8>`.
+///
+/// Receivers return consumed buffers with [`PoolReceiver::recycle`], and senders get recycled
+/// buffers with [`PoolSender::alloc`]. The free list holds at most `cap + 1` buffers, and any extra
+/// recycled buffers are simply dropped.
+///
+/// [`PoolSender`] and [`PoolReceiver`] dereference to [`Sender`] and [`Receiver`], so all the
+/// usual channel operations, including selection, are available.
+///
+/// # Examples
+///
+/// ```
+/// use std::thread;
+/// use crossbeam_channel::pool;
+///
+/// let (s, r) = pool::<Vec<u8>>(4);
+///
+/// thread::spawn(move || {
+///     for i in 0..100 {
+///         // Reuse a buffer that has already been consumed, if there is one.
+///         let mut buf = s.alloc();
+///         buf.clear();
+///         buf.extend_from_slice(&[i; 1024]);
+///         s.send(buf).unwrap();
+///     }
+/// });
+///
+/// for buf in r.iter() {
+///     assert_eq!(buf.len(), 1024);
+///
+///     // Hand the buffer back to the sender.
+///     r.recycle(buf);
+/// }
+/// ```
+pub fn pool<T>(cap: usize) -> (PoolSender<T>, PoolReceiver<T>) {
+    let (s, r) = bounded(cap);
+    let (free_s, free_r) = bounded(cap.saturating_add(1));
+
+    let s = PoolSender {
+        sender: s,
+        free: free_r,
+    };
+    let r = PoolReceiver {
+        receiver: r,
+        free: free_s,
+    };
+    (s, r)
+}
+
+/// The sending side of a recycling channel.
+///
+/// Created by the [`pool`] function.
+pub struct PoolSender<T> {
+    /// The sending side of the data channel.
+    sender: Sender<T>,
+
+    /// The receiving side of the free list.
+    free: Receiver<T>,
+}
+
+impl<T> PoolSender<T> {
+    /// Returns a recycled buffer, or a new default buffer if there are none.
+    ///
+    /// Recycled buffers are returned as they were passed to [`PoolReceiver::recycle`], so they
+    /// may need to be cleared before use.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::pool;
+    ///
+    /// let (s, r) = pool::<Vec<u8>>(1);
+    /// assert!(s.alloc().is_empty());
+    ///
+    /// s.send(vec![1, 2, 3]).unwrap();
+    /// r.recycle(r.recv().unwrap());
+    /// assert_eq!(s.alloc(), [1, 2, 3]);
+    /// ```
+    pub fn alloc(&self) -> T
+    where
+        T: Default,
+    {
+        self.alloc_with(T::default)
+    }
+
+    /// Returns a recycled buffer, or creates a new one with `f` if there are none.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::pool;
+    ///
+    /// let (s, r) = pool::<Vec<u8>>(1);
+    /// let buf = s.alloc_with(|| Vec::with_capacity(4096));
+    /// assert!(buf.capacity() >= 4096);
+    /// ```
+    pub fn alloc_with<F>(&self, f: F) -> T
+    where
+        F: FnOnce() -> T,
+    {
+        self.free.try_recv().unwrap_or_else(|_| f())
+    }
+
+    /// Returns the number of recycled buffers that are ready to be reused.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::pool;
+    ///
+    /// let (s, r) = pool::<Vec<u8>>(1);
+    /// assert_eq!(s.free_len(), 0);
+    ///
+    /// r.recycle(Vec::new());
+    /// assert_eq!(s.free_len(), 1);
+    /// ```
+    pub fn free_len(&self) -> usize {
+        self.free.len()
+    }
+}
+
+impl<T> Deref for PoolSender<T> {
+    type Target = Sender<T>;
+
+    fn deref(&self) -> &Sender<T> {
+        &self.sender
+    }
+}
+
+impl<T> Clone for PoolSender<T> {
+    fn clone(&self) -> Self {
+        PoolSender {
+            sender: self.sender.clone(),
+            free: self.free.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for PoolSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("PoolSender { .. }")
+    }
+}
+
+/// The receiving side of a recycling channel.
+///
+/// Created by the [`pool`] function.
+pub struct PoolReceiver<T> {
+    /// The receiving side of the data channel.
+    receiver: Receiver<T>,
+
+    /// The sending side of the free list.
+    free: Sender<T>,
+}
+
+impl<T> PoolReceiver<T> {
+    /// Returns a consumed buffer to the free list so that senders can reuse it.
+    ///
+    /// If the free list is full or all senders have been dropped, the buffer is dropped instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::pool;
+    ///
+    /// let (s, r) = pool(1);
+    /// s.send(String::from("hello")).unwrap();
+    ///
+    /// let msg = r.recv().unwrap();
+    /// r.recycle(msg);
+    /// assert_eq!(s.alloc(), "hello");
+    /// ```
+    pub fn recycle(&self, buf: T) {
+        let _ = self.free.try_send(buf);
+    }
+}
+
+impl<T> Deref for PoolReceiver<T> {
+    type Target = Receiver<T>;
+
+    fn deref(&self) -> &Receiver<T> {
+        &self.receiver
+    }
+}
+
+impl<T> Clone for PoolReceiver<T> {
+    fn clone(&self) -> Self {
+        PoolReceiver {
+            receiver: self.receiver.clone(),
+            free: self.free.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for PoolReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("PoolReceiver { .. }")
+    }
+}
@@ -3,6 +3,7 @@
 use std::fmt;
 use std::marker::PhantomData;
 use std::mem;
+use std::thread;
 use std::time::{Duration, Instant};
 
 use crossbeam_utils::Backoff;
@@ -21,10 +22,12 @@ use crate::utils;
 /// Each field contains data associated with a specific channel flavor.
 #[derive(Debug, Default)]
 pub struct Token {
+    pub adapter: flavors::adapter::AdapterToken,
     pub at: flavors::at::AtToken,
     pub array: flavors::array::ArrayToken,
     pub list: flavors::list::ListToken,
     pub never: flavors::never::NeverToken,
+    pub split: flavors::split::SplitToken,
     pub tick: flavors::tick::TickToken,
     pub zero: flavors::zero::ZeroToken,
 }
@@ -618,6 +621,9 @@ impl<'a> Select<'a> {
     ///
     /// Returns the index of the added operation.
     ///
+    /// The same sender may be added multiple times. Each addition gets its own index, and any one
+    /// of them can be selected when the channel is ready.
+    ///
     /// # Examples
     ///
     /// ```
@@ -640,6 +646,9 @@ impl<'a> Select<'a> {
     ///
     /// Returns the index of the added operation.
     ///
+    /// The same receiver may be added multiple times. Each addition gets its own index, and any one
+    /// of them can be selected when the channel is ready.
+    ///
     /// # Examples
     ///
     /// ```
@@ -904,6 +913,111 @@ impl<'a> Select<'a> {
         select_deadline(&mut self.handles, deadline)
     }
 
+    /// Blocks until one of the operations becomes ready, then completes it inside a closure.
+    ///
+    /// This works like [`select`], but the selected operation is passed to `f` as a
+    /// [`ScopedOperation`], which must be completed with [`ScopedOperation::send`] or
+    /// [`ScopedOperation::recv`] to produce the [`Completed`] value that `f` returns. Forgetting
+    /// to complete the operation is a compile error rather than a panic.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::thread;
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s1, r1) = unbounded::<i32>();
+    /// let (s2, r2) = unbounded::<i32>();
+    ///
+    /// thread::spawn(move || s2.send(20).unwrap());
+    ///
+    /// let mut sel = Select::new();
+    /// let oper1 = sel.recv(&r1);
+    /// let oper2 = sel.recv(&r2);
+    ///
+    /// let msg = sel.select_with(|oper| match oper.index() {
+    ///     i if i == oper1 => oper.recv(&r1),
+    ///     i if i == oper2 => oper.recv(&r2),
+    ///     _ => unreachable!(),
+    /// });
+    /// assert_eq!(msg, Ok(20));
+    /// # drop(s1);
+    /// ```
+    ///
+    /// [`select`]: Select::select
+    pub fn select_with<F, R>(&mut self, f: F) -> R
+    where
+        F: for<'s> FnOnce(ScopedOperation<'s, 'a>) -> Completed<'s, R>,
+    {
+        ScopedOperation::run(self.select(), f)
+    }
+
+    /// Attempts to select one of the operations without blocking, then completes it inside a
+    /// closure.
+    ///
+    /// This works like [`try_select`], but the selected operation is completed inside `f`. See
+    /// [`select_with`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv(&r);
+    /// assert!(sel.try_select_with(|oper| oper.recv(&r)).is_err());
+    ///
+    /// s.send(1).unwrap();
+    /// assert_eq!(sel.try_select_with(|oper| oper.recv(&r)), Ok(Ok(1)));
+    /// ```
+    ///
+    /// [`try_select`]: Select::try_select
+    /// [`select_with`]: Select::select_with
+    pub fn try_select_with<F, R>(&mut self, f: F) -> Result<R, TrySelectError>
+    where
+        F: for<'s> FnOnce(ScopedOperation<'s, 'a>) -> Completed<'s, R>,
+    {
+        self.try_select().map(|oper| ScopedOperation::run(oper, f))
+    }
+
+    /// Blocks for a limited time until one of the operations becomes ready, then completes it
+    /// inside a closure.
+    ///
+    /// This works like [`select_timeout`], but the selected operation is completed inside `f`.
+    /// See [`select_with`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.send(&s);
+    ///
+    /// let res = sel.select_timeout_with(Duration::from_millis(100), |oper| oper.send(&s, 1));
+    /// assert_eq!(res, Ok(Ok(())));
+    /// assert_eq!(r.try_recv(), Ok(1));
+    /// ```
+    ///
+    /// [`select_timeout`]: Select::select_timeout
+    /// [`select_with`]: Select::select_with
+    pub fn select_timeout_with<F, R>(
+        &mut self,
+        timeout: Duration,
+        f: F,
+    ) -> Result<R, SelectTimeoutError>
+    where
+        F: for<'s> FnOnce(ScopedOperation<'s, 'a>) -> Completed<'s, R>,
+    {
+        self.select_timeout(timeout)
+            .map(|oper| ScopedOperation::run(oper, f))
+    }
+
     /// Attempts to find a ready operation without blocking.
     ///
     /// If an operation is ready, its index is returned. If multiple operations are ready at the
@@ -1118,7 +1232,9 @@ impl fmt::Debug for Select<'_> {
 /// # Panics
 ///
 /// Forgetting to complete the operation is an error and might lead to deadlocks. If a
-/// `SelectedOperation` is dropped without completion, a panic occurs.
+/// `SelectedOperation` is dropped without completion, a panic occurs, unless the thread is
+/// already panicking. Use [`Select::select_with`] to have completion checked at compile time
+/// instead.
 ///
 /// [`send`]: SelectedOperation::send
 /// [`recv`]: SelectedOperation::recv
@@ -1233,6 +1349,18 @@ impl SelectedOperation<'_> {
         mem::forget(self);
         res.map_err(|_| RecvError)
     }
+
+    /// Completes an operation that doesn't transfer a message, like a cancellation.
+    pub(crate) fn complete(self) {
+        mem::forget(self);
+    }
+
+    /// Completes the selection and returns the token, leaving the transfer to the caller.
+    pub(crate) fn into_token(mut self) -> Token {
+        let token = mem::replace(&mut self.token, Token::default());
+        mem::forget(self);
+        token
+    }
 }
 
 impl fmt::Debug for SelectedOperation<'_> {
@@ -1243,6 +1371,135 @@ impl fmt::Debug for SelectedOperation<'_> {
 
 impl Drop for SelectedOperation<'_> {
     fn drop(&mut self) {
-        panic!("dropped `SelectedOperation` without completing the operation");
+        // Panicking while already unwinding would abort the process.
+        if !thread::panicking() {
+            panic!("dropped `SelectedOperation` without completing the operation");
+        }
+    }
+}
+
+/// A selected operation that must be completed inside the closure passed to
+/// [`Select::select_with`] and similar methods.
+///
+/// To complete the operation, call [`send`] or [`recv`]. They return a [`Completed`] value, which
+/// is the only way for the closure to return. A `Completed` value is tied to the operation it was
+/// created from, so it can't be taken from a different selection.
+///
+/// # Examples
+///
+/// A closure that doesn't complete the operation doesn't compile:
+///
+/// ```compile_fail
+/// use crossbeam_channel::{unbounded, Select};
+///
+/// let (s, r) = unbounded::<i32>();
+///
+/// let mut sel = Select::new();
+/// sel.recv(&r);
+/// sel.select_with(|oper| oper.index());
+/// ```
+///
+/// [`send`]: ScopedOperation::send
+/// [`recv`]: ScopedOperation::recv
+pub struct ScopedOperation<'s, 'a> {
+    /// The selected operation.
+    oper: SelectedOperation<'a>,
+
+    /// Ties the operation to its `Completed` value.
+    _brand: PhantomData<fn(&'s ()) -> &'s ()>,
+}
+
+impl<'s, 'a> ScopedOperation<'s, 'a> {
+    /// Completes `oper` inside `f`.
+    fn run<F, R>(oper: SelectedOperation<'a>, f: F) -> R
+    where
+        F: for<'t> FnOnce(ScopedOperation<'t, 'a>) -> Completed<'t, R>,
+    {
+        let oper = ScopedOperation {
+            oper,
+            _brand: PhantomData,
+        };
+        f(oper).value
+    }
+
+    /// Returns the index of the selected operation.
+    pub fn index(&self) -> usize {
+        self.oper.index()
+    }
+
+    /// Completes the send operation.
+    ///
+    /// The passed [`Sender`] reference must be the same one that was used in [`Select::send`]
+    /// when the operation was added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an incorrect [`Sender`] reference is passed.
+    pub fn send<T>(self, s: &Sender<T>, msg: T) -> Completed<'s, Result<(), SendError<T>>> {
+        Completed::new(self.oper.send(s, msg))
+    }
+
+    /// Completes the receive operation.
+    ///
+    /// The passed [`Receiver`] reference must be the same one that was used in [`Select::recv`]
+    /// when the operation was added.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an incorrect [`Receiver`] reference is passed.
+    pub fn recv<T>(self, r: &Receiver<T>) -> Completed<'s, Result<T, RecvError>> {
+        Completed::new(self.oper.recv(r))
+    }
+}
+
+impl fmt::Debug for ScopedOperation<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("ScopedOperation { .. }")
+    }
+}
+
+/// The result of a completed [`ScopedOperation`].
+#[must_use]
+pub struct Completed<'s, R> {
+    /// The value returned from the closure.
+    value: R,
+
+    /// Ties the value to the operation it was created from.
+    _brand: PhantomData<fn(&'s ()) -> &'s ()>,
+}
+
+impl<'s, R> Completed<'s, R> {
+    /// Wraps the result of an operation.
+    fn new(value: R) -> Completed<'s, R> {
+        Completed {
+            value,
+            _brand: PhantomData,
+        }
+    }
+
+    /// Transforms the result of the completed operation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_channel::{unbounded, Select};
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// s.send(1).unwrap();
+    ///
+    /// let mut sel = Select::new();
+    /// sel.recv(&r);
+    ///
+    /// let msg = sel.select_with(|oper| oper.recv(&r).map(|res| res.unwrap() * 10));
+    /// assert_eq!(msg, 10);
+    /// ```
+    pub fn map<U, F: FnOnce(R) -> U>(self, f: F) -> Completed<'s, U> {
+        Completed::new(f(self.value))
+    }
+}
+
+impl<R: fmt::Debug> fmt::Debug for Completed<'_, R> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Completed").field(&self.value).finish()
     }
 }
@@ -0,0 +1,521 @@
+//! Rate-limited senders.
+
+use std::fmt;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+use crate::channel::{self, Sender};
+use crate::context::Context;
+use crate::err::{SendError, SendTimeoutError, TrySendError};
+use crate::flavors::adapter::SendAdapter;
+use crate::select::{self, Operation, SelectHandle, Selected, Token};
+use crate::utils::Spinlock;
+
+/// A sender that limits the rate at which messages are sent.
+///
+/// The rate is limited with a token bucket. The bucket holds at most `burst` tokens and starts
+/// full. Sending a message takes one token from the bucket, and a new token is added every
+/// `interval`. When the bucket is empty, send operations wait until a token becomes available.
+///
+/// Clones of a `Throttled` share the same bucket, so the rate limit applies to all of them
+/// together.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, Instant};
+/// use crossbeam_channel::{unbounded, Throttled};
+///
+/// let (s, r) = unbounded();
+///
+/// // Allow a burst of 2 messages, then one message every 100 milliseconds.
+/// let s = Throttled::new(s, 2, Duration::from_millis(100));
+///
+/// let start = Instant::now();
+/// for i in 0..4 {
+///     s.send(i).unwrap();
+/// }
+///
+/// // The first two messages were sent immediately, and the other two had to wait.
+/// assert!(start.elapsed() >= Duration::from_millis(200));
+/// assert_eq!(r.try_iter().collect::<Vec<_>>(), [0, 1, 2, 3]);
+/// ```
+pub struct Throttled<T> {
+    /// A sender that goes through the rate limit.
+    sender: Sender<T>,
+
+    /// The rate limit shared by all clones.
+    limit: Arc<Limit<T>>,
+}
+
+/// A token bucket.
+struct Bucket {
+    /// The maximum number of tokens in the bucket.
+    burst: u32,
+
+    /// The time it takes to add one token to the bucket.
+    interval: Duration,
+
+    /// The number of tokens in the bucket.
+    tokens: u32,
+
+    /// The instant at which the last token was added, or the bucket was last seen full.
+    ///
+    /// This may be in the future if a token had to be taken from an empty bucket.
+    last_refill: Instant,
+}
+
+impl Bucket {
+    /// Adds the tokens that have accumulated since the last refill.
+    fn refill(&mut self, now: Instant) {
+        if now <= self.last_refill {
+            return;
+        }
+        if self.tokens >= self.burst {
+            self.last_refill = now;
+            return;
+        }
+
+        let elapsed = now - self.last_refill;
+        let n = elapsed.as_nanos() / self.interval.as_nanos();
+        let n = n.min(u128::from(self.burst - self.tokens)) as u32;
+
+        self.tokens += n;
+        if self.tokens == self.burst {
+            self.last_refill = now;
+        } else {
+            self.last_refill += self.interval * n;
+        }
+    }
+
+    /// Returns the instant at which the next token will be added, or `None` if the bucket isn't
+    /// empty.
+    fn next_token(&mut self, now: Instant) -> Option<Instant> {
+        self.refill(now);
+
+        if self.tokens > 0 {
+            None
+        } else {
+            Some(self.last_refill + self.interval)
+        }
+    }
+
+    /// Attempts to take a token from the bucket.
+    fn acquire(&mut self, now: Instant) -> bool {
+        if self.next_token(now).is_some() {
+            return false;
+        }
+        self.tokens -= 1;
+        true
+    }
+
+    /// Takes a token from the bucket, borrowing the next one if the bucket is empty.
+    fn force_acquire(&mut self, now: Instant) {
+        if !self.acquire(now) {
+            self.last_refill += self.interval;
+        }
+    }
+
+    /// Returns a token that was taken but not used.
+    fn release(&mut self) {
+        if self.tokens < self.burst {
+            self.tokens += 1;
+        }
+    }
+}
+
+/// The rate limit of a `Throttled`, used as the adapter behind its sender.
+struct Limit<T> {
+    /// The underlying sender.
+    sender: Sender<T>,
+
+    /// The token bucket.
+    bucket: Mutex<Bucket>,
+
+    /// Operations that are only watching the underlying channel because the bucket was empty.
+    watching: Spinlock<Vec<(Operation, Context)>>,
+}
+
+impl<T> Limit<T> {
+    /// Locks the token bucket.
+    fn lock(&self) -> MutexGuard<'_, Bucket> {
+        self.bucket.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Returns `true` if the bucket isn't empty.
+    fn has_token(&self) -> bool {
+        self.lock().next_token(Instant::now()).is_none()
+    }
+
+    /// Returns `true` if the underlying channel is disconnected.
+    fn is_disconnected(&self) -> bool {
+        self.sender.receiver_count() == 0
+    }
+}
+
+impl<T: Send> SendAdapter<T> for Limit<T> {
+    fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        let token = &mut Token::default();
+
+        if self.try_select(token) {
+            unsafe { self.write(token, msg).map_err(TrySendError::Disconnected) }
+        } else {
+            Err(TrySendError::Full(msg))
+        }
+    }
+
+    fn send(&self, msg: T, deadline: Option<Instant>) -> Result<(), SendTimeoutError<T>> {
+        if let Some(d) = deadline {
+            // Don't wait for a token that won't be added before the deadline.
+            let next = self.lock().next_token(Instant::now());
+            if next.map_or(false, |next| next > d) && !self.is_disconnected() {
+                return Err(SendTimeoutError::Timeout(msg));
+            }
+        }
+
+        let ptr = self as *const Limit<T> as *const u8;
+        let handles = &mut [(self as &dyn SelectHandle, 0, ptr)];
+
+        let oper = match deadline {
+            None => select::select(handles),
+            Some(d) => match select::select_deadline(handles, d) {
+                Ok(oper) => oper,
+                Err(_) => return Err(SendTimeoutError::Timeout(msg)),
+            },
+        };
+
+        let token = &mut oper.into_token();
+        unsafe {
+            self.write(token, msg)
+                .map_err(SendTimeoutError::Disconnected)
+        }
+    }
+
+    unsafe fn write(&self, token: &mut Token, msg: T) -> Result<(), T> {
+        channel::write(&self.sender, token, msg)
+    }
+
+    fn can_write(&self, token: &Token) -> bool {
+        channel::can_write(&self.sender, token)
+    }
+
+    fn len(&self) -> usize {
+        self.sender.len()
+    }
+
+    fn capacity(&self) -> Option<usize> {
+        self.sender.capacity()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.sender.is_empty()
+    }
+
+    fn is_full(&self) -> bool {
+        self.sender.is_full()
+    }
+
+    fn receiver_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    fn handle(&self) -> &dyn SelectHandle {
+        self
+    }
+}
+
+impl<T> SelectHandle for Limit<T> {
+    fn try_select(&self, token: &mut Token) -> bool {
+        // A disconnected channel is selected without a token so that the error is reported
+        // right away.
+        let acquired = self.lock().acquire(Instant::now());
+        if !acquired && !self.is_disconnected() {
+            return false;
+        }
+
+        if !self.sender.try_select(token) {
+            if acquired {
+                self.lock().release();
+            }
+            return false;
+        }
+
+        if acquired && !channel::can_write(&self.sender, token) {
+            self.lock().release();
+        }
+        true
+    }
+
+    fn deadline(&self) -> Option<Instant> {
+        let next = self.lock().next_token(Instant::now());
+        match (next, self.sender.deadline()) {
+            (Some(a), Some(b)) => Some(a.min(b)),
+            (a, b) => a.or(b),
+        }
+    }
+
+    fn register(&self, oper: Operation, cx: &Context) -> bool {
+        if self.has_token() {
+            return self.sender.register(oper, cx);
+        }
+
+        // Registering would let a receiver of a zero-capacity channel pair up with this
+        // operation before a token is added, so only watch for disconnection until then.
+        self.watching.lock().push((oper, cx.clone()));
+        self.sender.watch(oper, cx) && self.is_disconnected()
+    }
+
+    fn unregister(&self, oper: Operation) {
+        let mut watching = self.watching.lock();
+        match watching.iter().position(|(o, _)| *o == oper) {
+            Some(i) => {
+                // If the watch has woken the thread, keep the entry so that `accept` can tell.
+                if watching[i].1.selected() != Selected::Operation(oper) {
+                    watching.swap_remove(i);
+                }
+                drop(watching);
+                self.sender.unwatch(oper);
+            }
+            None => {
+                drop(watching);
+                self.sender.unregister(oper);
+            }
+        }
+    }
+
+    fn accept(&self, token: &mut Token, cx: &Context) -> bool {
+        if let Selected::Operation(oper) = cx.selected() {
+            let mut watching = self.watching.lock();
+            if let Some(i) = watching.iter().position(|(o, _)| *o == oper) {
+                // The channel has only become ready, so selection has to start over.
+                watching.swap_remove(i);
+                return false;
+            }
+        }
+
+        if !self.sender.accept(token, cx) {
+            return false;
+        }
+
+        if channel::can_write(&self.sender, token) {
+            // The operation may already be paired with a receiver, so it has to go through even
+            // if another clone has taken the token since registration.
+            self.lock().force_acquire(Instant::now());
+        }
+        true
+    }
+
+    fn is_ready(&self) -> bool {
+        self.sender.is_ready() && (self.has_token() || self.is_disconnected())
+    }
+
+    fn watch(&self, oper: Operation, cx: &Context) -> bool {
+        self.sender.watch(oper, cx) && (self.has_token() || self.is_disconnected())
+    }
+
+    fn unwatch(&self, oper: Operation) {
+        self.sender.unwatch(oper);
+    }
+}
+
+impl<T> Throttled<T> {
+    /// Wraps a sender so that it sends at most `burst` messages at once, and then one message
+    /// every `interval`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `burst` is zero or `interval` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, Throttled};
+    ///
+    /// let (s, r) = unbounded::<i32>();
+    /// let s = Throttled::new(s, 10, Duration::from_millis(100));
+    /// ```
+    pub fn new(sender: Sender<T>, burst: u32, interval: Duration) -> Throttled<T>
+    where
+        T: Send + 'static,
+    {
+        assert!(burst > 0, "burst must be positive");
+        assert!(
+            interval > Duration::from_secs(0),
+            "interval must be positive"
+        );
+
+        let limit = Arc::new(Limit {
+            sender,
+            bucket: Mutex::new(Bucket {
+                burst,
+                interval,
+                tokens: burst,
+                last_refill: Instant::now(),
+            }),
+            watching: Spinlock::new(Vec::new()),
+        });
+
+        Throttled {
+            sender: Sender::from_adapter(limit.clone()),
+            limit,
+        }
+    }
+
+    /// Attempts to send a message into the channel without blocking.
+    ///
+    /// If the rate limit doesn't allow sending a message right now, this method returns
+    /// [`TrySendError::Full`], just like when the channel itself is full. The returned error
+    /// contains the original message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, Throttled, TrySendError};
+    ///
+    /// let (s, r) = unbounded();
+    /// let s = Throttled::new(s, 1, Duration::from_secs(1));
+    ///
+    /// assert_eq!(s.try_send(1), Ok(()));
+    /// assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    /// ```
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        self.sender.try_send(msg)
+    }
+
+    /// Blocks the current thread until a message is sent or the channel is disconnected.
+    ///
+    /// This call waits for the rate limit to allow sending a message and for the channel to
+    /// accept it. If the channel is or becomes disconnected, this call will wake up and return an
+    /// error without waiting for the rate limit. The returned error contains the original message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, SendError, Throttled};
+    ///
+    /// let (s, r) = unbounded();
+    /// let s = Throttled::new(s, 1, Duration::from_millis(100));
+    ///
+    /// assert_eq!(s.send(1), Ok(()));
+    /// assert_eq!(s.send(2), Ok(()));
+    ///
+    /// drop(r);
+    /// assert_eq!(s.send(3), Err(SendError(3)));
+    /// ```
+    pub fn send(&self, msg: T) -> Result<(), SendError<T>> {
+        self.sender.send(msg)
+    }
+
+    /// Waits for a message to be sent into the channel, but only for a limited time.
+    ///
+    /// The timeout covers both waiting for the rate limit and waiting for the channel to accept
+    /// the message. If the rate limit won't allow sending a message before the timeout, this call
+    /// returns an error right away. The returned error contains the original message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{unbounded, SendTimeoutError, Throttled};
+    ///
+    /// let (s, r) = unbounded();
+    /// let s = Throttled::new(s, 1, Duration::from_secs(1));
+    ///
+    /// assert_eq!(s.send_timeout(1, Duration::from_millis(100)), Ok(()));
+    /// assert_eq!(
+    ///     s.send_timeout(2, Duration::from_millis(100)),
+    ///     Err(SendTimeoutError::Timeout(2)),
+    /// );
+    /// ```
+    pub fn send_timeout(&self, msg: T, timeout: Duration) -> Result<(), SendTimeoutError<T>> {
+        self.send_deadline(msg, Instant::now() + timeout)
+    }
+
+    /// Waits for a message to be sent into the channel, but only until a given deadline.
+    ///
+    /// The deadline covers both waiting for the rate limit and waiting for the channel to accept
+    /// the message. If the rate limit won't allow sending a message before the deadline, this call
+    /// returns an error right away. The returned error contains the original message.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::{unbounded, SendTimeoutError, Throttled};
+    ///
+    /// let (s, r) = unbounded();
+    /// let s = Throttled::new(s, 1, Duration::from_millis(500));
+    ///
+    /// let now = Instant::now();
+    /// assert_eq!(s.send_deadline(1, now + Duration::from_millis(100)), Ok(()));
+    /// assert_eq!(
+    ///     s.send_deadline(2, now + Duration::from_millis(100)),
+    ///     Err(SendTimeoutError::Timeout(2)),
+    /// );
+    /// assert_eq!(s.send_deadline(3, now + Duration::from_secs(1)), Ok(()));
+    /// ```
+    pub fn send_deadline(&self, msg: T, deadline: Instant) -> Result<(), SendTimeoutError<T>> {
+        self.sender.send_deadline(msg, deadline)
+    }
+
+    /// Returns a sender that goes through the rate limit.
+    ///
+    /// Messages sent with the returned sender take tokens from the same bucket as this
+    /// `Throttled`. The sender can be used in [`select!`] and [`Select`].
+    ///
+    /// [`select!`]: crate::select!
+    /// [`Select`]: crate::Select
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::{select, unbounded, Throttled};
+    ///
+    /// let (s1, r1) = unbounded();
+    /// let (s2, r2) = unbounded();
+    /// let s1 = Throttled::new(s1, 1, Duration::from_secs(1));
+    ///
+    /// s1.send(1).unwrap();
+    ///
+    /// // The rate limit doesn't allow sending into the first channel yet.
+    /// select! {
+    ///     send(s1.as_sender(), 2) -> _ => panic!(),
+    ///     send(s2, 3) -> res => assert_eq!(res, Ok(())),
+    /// }
+    ///
+    /// assert_eq!(r1.try_iter().collect::<Vec<_>>(), [1]);
+    /// assert_eq!(r2.try_iter().collect::<Vec<_>>(), [3]);
+    /// ```
+    pub fn as_sender(&self) -> &Sender<T> {
+        &self.sender
+    }
+
+    /// Returns a reference to the underlying sender.
+    pub fn get_ref(&self) -> &Sender<T> {
+        &self.limit.sender
+    }
+
+    /// Unwraps the underlying sender.
+    pub fn into_inner(self) -> Sender<T> {
+        self.limit.sender.clone()
+    }
+}
+
+impl<T> Clone for Throttled<T> {
+    fn clone(&self) -> Self {
+        Throttled {
+            sender: self.sender.clone(),
+            limit: self.limit.clone(),
+        }
+    }
+}
+
+impl<T> fmt::Debug for Throttled<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Throttled { .. }")
+    }
+}
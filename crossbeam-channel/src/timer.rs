@@ -0,0 +1,629 @@
+//! A hierarchical timer wheel that delivers expirations into a channel.
+
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::channel::{unbounded, Receiver, Sender};
+
+/// The number of slots in each level of the wheel.
+const SLOTS: usize = 64;
+
+/// The number of bits needed to index a slot in a level.
+const SLOT_BITS: u32 = 6;
+
+/// The number of levels in the wheel.
+const LEVELS: usize = 6;
+
+/// The longest timeout the wheel can represent, in ticks.
+const MAX_TICKS: u64 = 1 << (SLOT_BITS as u64 * LEVELS as u64);
+
+/// Marks the end of a list of entries.
+const NIL: usize = usize::max_value();
+
+/// Creates a timer wheel that delivers expired timers into a channel.
+///
+/// Timers are scheduled with [`TimerWheel::schedule`], which returns a [`TimerToken`] identifying
+/// the timer. Once the timer expires, its token is sent into the returned channel. This makes it
+/// possible to manage a large number of timeouts, like one for every connection of a server, with
+/// a single channel instead of creating an [`after`] channel for each of them.
+///
+/// Time is measured in ticks of length `tick`, and timers never expire before their deadline, but
+/// may expire up to one tick late. Scheduling and cancelling a timer take constant time. The wheel
+/// is driven by a background thread, which stops once all [`TimerWheel`] handles have been
+/// dropped. Pending timers are then discarded, and the channel becomes disconnected.
+///
+/// # Panics
+///
+/// Panics if `tick` is zero.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::Duration;
+/// use crossbeam_channel::timer_wheel;
+///
+/// let (timers, r) = timer_wheel(Duration::from_millis(1));
+///
+/// let slow = timers.schedule(Duration::from_millis(200));
+/// let fast = timers.schedule(Duration::from_millis(100));
+/// let cancelled = timers.schedule(Duration::from_millis(50));
+/// assert!(timers.cancel(cancelled));
+///
+/// // Timers are delivered in the order in which they expire.
+/// assert_eq!(r.recv(), Ok(fast));
+/// assert_eq!(r.recv(), Ok(slow));
+/// ```
+///
+/// [`after`]: super::after
+pub fn timer_wheel(tick: Duration) -> (TimerWheel, Receiver<TimerToken>) {
+    assert!(tick > Duration::from_secs(0), "tick must be positive");
+
+    let inner = Arc::new(Inner {
+        wheel: Mutex::new(Wheel::new()),
+        wakeup: Condvar::new(),
+        start: Instant::now(),
+        tick,
+    });
+    let (s, r) = unbounded();
+
+    {
+        let inner = inner.clone();
+        thread::Builder::new()
+            .name("crossbeam-timer".to_string())
+            .spawn(move || run(inner, s))
+            .expect("failed to spawn the timer thread");
+    }
+
+    (TimerWheel { inner }, r)
+}
+
+/// Identifies a timer scheduled on a [`TimerWheel`].
+///
+/// Tokens are unique among the timers of a wheel: a token is never handed out again, even after
+/// its timer has expired or has been cancelled.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TimerToken {
+    /// The index of the timer entry in the wheel.
+    index: usize,
+
+    /// The generation of the timer entry, which distinguishes timers reusing the same entry.
+    generation: u64,
+}
+
+/// A handle for scheduling timers on a timer wheel.
+///
+/// Created by the [`timer_wheel`] function. Clones of a `TimerWheel` share the same wheel and
+/// deliver expired timers into the same channel.
+pub struct TimerWheel {
+    inner: Arc<Inner>,
+}
+
+/// State shared by the handles and the background thread.
+struct Inner {
+    /// The wheel holding the pending timers.
+    wheel: Mutex<Wheel>,
+
+    /// Wakes up the background thread when a timer expires sooner than it expected.
+    wakeup: Condvar,
+
+    /// The instant at which tick 0 started.
+    start: Instant,
+
+    /// The length of a tick.
+    tick: Duration,
+}
+
+impl Inner {
+    /// Locks the wheel.
+    fn lock(&self) -> MutexGuard<'_, Wheel> {
+        self.wheel.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Returns the number of whole ticks elapsed at `instant`.
+    fn ticks_at(&self, instant: Instant) -> u64 {
+        if instant <= self.start {
+            return 0;
+        }
+        let elapsed = (instant - self.start).as_nanos() / self.tick.as_nanos();
+        elapsed.min(u128::from(u64::max_value())) as u64
+    }
+
+    /// Returns the first tick that starts at or after `instant`.
+    fn deadline_at(&self, instant: Instant) -> u64 {
+        if instant <= self.start {
+            return 0;
+        }
+        let tick = self.tick.as_nanos();
+        let elapsed = (instant - self.start).as_nanos();
+        let deadline = (elapsed + tick - 1) / tick;
+        deadline.min(u128::from(u64::max_value())) as u64
+    }
+
+    /// Returns the time from `now` until tick `deadline` starts, or `None` if that instant
+    /// cannot be represented.
+    fn time_until(&self, deadline: u64, now: Instant) -> Option<Duration> {
+        let nanos = self.tick.as_nanos() * u128::from(deadline);
+        let nanos = nanos.min(u128::from(u64::max_value())) as u64;
+        let instant = self.start.checked_add(Duration::from_nanos(nanos))?;
+
+        if instant > now {
+            Some(instant - now)
+        } else {
+            Some(Duration::from_secs(0))
+        }
+    }
+}
+
+impl TimerWheel {
+    /// Schedules a timer that expires after `timeout`.
+    ///
+    /// Timeouts longer than 2<sup>36</sup> ticks, which is about two years with 1 millisecond
+    /// ticks, are shortened to that length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::timer_wheel;
+    ///
+    /// let (timers, r) = timer_wheel(Duration::from_millis(1));
+    /// let token = timers.schedule(Duration::from_millis(10));
+    /// assert_eq!(r.recv(), Ok(token));
+    /// ```
+    pub fn schedule(&self, timeout: Duration) -> TimerToken {
+        match Instant::now().checked_add(timeout) {
+            Some(deadline) => self.schedule_at(deadline),
+            None => self.schedule_ticks(u64::max_value()),
+        }
+    }
+
+    /// Schedules a timer that expires at `deadline`.
+    ///
+    /// If `deadline` has already passed, the timer expires right away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, Instant};
+    /// use crossbeam_channel::timer_wheel;
+    ///
+    /// let (timers, r) = timer_wheel(Duration::from_millis(1));
+    /// let deadline = Instant::now() + Duration::from_millis(10);
+    /// let token = timers.schedule_at(deadline);
+    ///
+    /// assert_eq!(r.recv(), Ok(token));
+    /// assert!(Instant::now() >= deadline);
+    /// ```
+    pub fn schedule_at(&self, deadline: Instant) -> TimerToken {
+        self.schedule_ticks(self.inner.deadline_at(deadline))
+    }
+
+    /// Schedules a timer that expires at tick `when`.
+    fn schedule_ticks(&self, when: u64) -> TimerToken {
+        let mut wheel = self.inner.lock();
+        let (token, when) = wheel.insert(when);
+
+        // Wake up the background thread if the new timer expires before it would wake up anyway.
+        if wheel.wake_at.map_or(true, |wake_at| when < wake_at) {
+            wheel.wake_at = Some(when);
+            self.inner.wakeup.notify_one();
+        }
+        token
+    }
+
+    /// Cancels a pending timer.
+    ///
+    /// Returns `true` if the timer was cancelled, or `false` if it has already expired or has
+    /// already been cancelled. A timer that has expired but whose token hasn't been received from
+    /// the channel yet cannot be cancelled anymore.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::timer_wheel;
+    ///
+    /// let (timers, r) = timer_wheel(Duration::from_millis(1));
+    /// let token = timers.schedule(Duration::from_secs(60));
+    ///
+    /// assert!(timers.cancel(token));
+    /// assert!(!timers.cancel(token));
+    /// assert!(timers.is_empty());
+    /// ```
+    pub fn cancel(&self, token: TimerToken) -> bool {
+        self.inner.lock().remove(token)
+    }
+
+    /// Returns `true` if there are no pending timers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::timer_wheel;
+    ///
+    /// let (timers, r) = timer_wheel(Duration::from_millis(1));
+    /// assert!(timers.is_empty());
+    ///
+    /// timers.schedule(Duration::from_secs(60));
+    /// assert!(!timers.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of pending timers.
+    ///
+    /// Timers that have expired are not pending anymore, even if their tokens haven't been
+    /// received from the channel yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::timer_wheel;
+    ///
+    /// let (timers, r) = timer_wheel(Duration::from_millis(1));
+    /// for i in 1..=10 {
+    ///     timers.schedule(Duration::from_secs(i));
+    /// }
+    /// assert_eq!(timers.len(), 10);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.inner.lock().len
+    }
+
+    /// Returns the length of a tick.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use crossbeam_channel::timer_wheel;
+    ///
+    /// let (timers, r) = timer_wheel(Duration::from_millis(5));
+    /// assert_eq!(timers.tick(), Duration::from_millis(5));
+    /// ```
+    pub fn tick(&self) -> Duration {
+        self.inner.tick
+    }
+}
+
+impl Clone for TimerWheel {
+    fn clone(&self) -> TimerWheel {
+        self.inner.lock().handles += 1;
+        TimerWheel {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl Drop for TimerWheel {
+    fn drop(&mut self) {
+        let mut wheel = self.inner.lock();
+        wheel.handles -= 1;
+        if wheel.handles == 0 {
+            self.inner.wakeup.notify_one();
+        }
+    }
+}
+
+impl fmt::Debug for TimerWheel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("TimerWheel { .. }")
+    }
+}
+
+/// Drives the wheel and sends expired timers into the channel.
+fn run(inner: Arc<Inner>, sender: Sender<TimerToken>) {
+    let mut expired = Vec::new();
+    let mut wheel = inner.lock();
+
+    while wheel.handles > 0 {
+        let now = Instant::now();
+        wheel.poll(inner.ticks_at(now), &mut expired);
+
+        if !expired.is_empty() {
+            // Send the tokens without holding the lock. If the receiver is gone, the tokens are
+            // simply discarded.
+            drop(wheel);
+            for token in expired.drain(..) {
+                let _ = sender.send(token);
+            }
+            wheel = inner.lock();
+            continue;
+        }
+
+        let exp = wheel.next_expiration();
+        wheel.wake_at = exp.as_ref().map(|exp| exp.deadline);
+
+        wheel = match exp.and_then(|exp| inner.time_until(exp.deadline, now)) {
+            Some(timeout) => {
+                inner
+                    .wakeup
+                    .wait_timeout(wheel, timeout)
+                    .unwrap_or_else(|err| err.into_inner())
+                    .0
+            }
+            None => inner
+                .wakeup
+                .wait(wheel)
+                .unwrap_or_else(|err| err.into_inner()),
+        };
+    }
+}
+
+/// A timer in the wheel.
+struct Entry {
+    /// The tick at which the timer expires.
+    when: u64,
+
+    /// Incremented every time the entry is freed, so that stale tokens can be detected.
+    generation: u64,
+
+    /// The level holding the entry, or `None` if the entry is free.
+    level: Option<usize>,
+
+    /// The previous entry in the same slot.
+    prev: usize,
+
+    /// The next entry in the same slot, or the next free entry.
+    next: usize,
+}
+
+/// A level of the wheel.
+///
+/// Each slot of level `n` covers 64<sup>n</sup> ticks.
+struct Level {
+    /// A bit mask of the slots holding at least one entry.
+    occupied: u64,
+
+    /// The first entry in every slot.
+    heads: [usize; SLOTS],
+}
+
+/// The next slot that needs to be processed.
+struct Expiration {
+    /// The level of the slot.
+    level: usize,
+
+    /// The index of the slot in the level.
+    slot: usize,
+
+    /// The tick at which the slot starts.
+    deadline: u64,
+}
+
+/// A hierarchical hashed timer wheel.
+///
+/// A timer is placed in the lowest level whose slots still tell its tick apart from the current
+/// one. When the wheel reaches a slot of a higher level, the timers in it are moved down to lower
+/// levels, until they end up in the lowest level and expire.
+struct Wheel {
+    /// The tick up to which all expired timers have been processed.
+    elapsed: u64,
+
+    /// The levels, from the finest to the coarsest.
+    levels: Vec<Level>,
+
+    /// The entries of all timers, including free ones.
+    entries: Vec<Entry>,
+
+    /// The first free entry.
+    free: usize,
+
+    /// The number of pending timers.
+    len: usize,
+
+    /// The number of `TimerWheel` handles.
+    handles: usize,
+
+    /// The tick at which the background thread is going to wake up, or `None` if it is waiting
+    /// for a timer to get scheduled.
+    wake_at: Option<u64>,
+}
+
+impl Wheel {
+    /// Creates an empty wheel.
+    fn new() -> Wheel {
+        Wheel {
+            elapsed: 0,
+            levels: (0..LEVELS)
+                .map(|_| Level {
+                    occupied: 0,
+                    heads: [NIL; SLOTS],
+                })
+                .collect(),
+            entries: Vec::new(),
+            free: NIL,
+            len: 0,
+            handles: 1,
+            wake_at: None,
+        }
+    }
+
+    /// Returns the level for a timer expiring at tick `when`.
+    fn level_for(&self, when: u64) -> usize {
+        let mut masked = (self.elapsed ^ when) | (SLOTS as u64 - 1);
+        if masked >= MAX_TICKS {
+            masked = MAX_TICKS - 1;
+        }
+        let significant = 63 - masked.leading_zeros();
+        (significant / SLOT_BITS) as usize
+    }
+
+    /// Returns the slot for a timer expiring at tick `when` in the given level.
+    fn slot_for(when: u64, level: usize) -> usize {
+        ((when >> (SLOT_BITS as usize * level)) as usize) & (SLOTS - 1)
+    }
+
+    /// Inserts a timer expiring at tick `when`.
+    ///
+    /// Returns the token of the timer and the tick at which it actually expires.
+    fn insert(&mut self, when: u64) -> (TimerToken, u64) {
+        let when = when
+            .max(self.elapsed)
+            .min(self.elapsed.saturating_add(MAX_TICKS - 1));
+
+        let index = if self.free != NIL {
+            let index = self.free;
+            self.free = self.entries[index].next;
+            index
+        } else {
+            self.entries.push(Entry {
+                when: 0,
+                generation: 0,
+                level: None,
+                prev: NIL,
+                next: NIL,
+            });
+            self.entries.len() - 1
+        };
+
+        self.entries[index].when = when;
+        self.link(index);
+        self.len += 1;
+
+        let token = TimerToken {
+            index,
+            generation: self.entries[index].generation,
+        };
+        (token, when)
+    }
+
+    /// Removes the timer identified by `token`.
+    ///
+    /// Returns `false` if the timer is not pending.
+    fn remove(&mut self, token: TimerToken) -> bool {
+        match self.entries.get(token.index) {
+            Some(entry) if entry.generation == token.generation && entry.level.is_some() => {}
+            _ => return false,
+        }
+
+        self.unlink(token.index);
+        self.release(token.index);
+        true
+    }
+
+    /// Adds an entry to the slot matching its tick.
+    fn link(&mut self, index: usize) {
+        let when = self.entries[index].when;
+        let level = self.level_for(when);
+        let slot = Wheel::slot_for(when, level);
+
+        let head = self.levels[level].heads[slot];
+        if head != NIL {
+            self.entries[head].prev = index;
+        }
+        let entry = &mut self.entries[index];
+        entry.level = Some(level);
+        entry.prev = NIL;
+        entry.next = head;
+
+        self.levels[level].heads[slot] = index;
+        self.levels[level].occupied |= 1 << slot;
+    }
+
+    /// Removes an entry from its slot.
+    fn unlink(&mut self, index: usize) {
+        let entry = &mut self.entries[index];
+        let level = entry.level.take().unwrap();
+        let (prev, next) = (entry.prev, entry.next);
+        let slot = Wheel::slot_for(entry.when, level);
+
+        if prev != NIL {
+            self.entries[prev].next = next;
+        } else {
+            self.levels[level].heads[slot] = next;
+            if next == NIL {
+                self.levels[level].occupied &= !(1 << slot);
+            }
+        }
+        if next != NIL {
+            self.entries[next].prev = prev;
+        }
+    }
+
+    /// Puts an unlinked entry on the free list.
+    fn release(&mut self, index: usize) {
+        let entry = &mut self.entries[index];
+        entry.generation += 1;
+        entry.next = self.free;
+        self.free = index;
+        self.len -= 1;
+    }
+
+    /// Returns the next slot that needs to be processed.
+    fn next_expiration(&self) -> Option<Expiration> {
+        // Timers in lower levels always expire before the slots of higher levels start, so the
+        // first occupied level is the one to look at.
+        let (level, lvl) = self
+            .levels
+            .iter()
+            .enumerate()
+            .find(|(_, lvl)| lvl.occupied != 0)?;
+
+        let slot_range = 1u64 << (SLOT_BITS as usize * level);
+        let level_range = slot_range << SLOT_BITS;
+
+        let now_slot = ((self.elapsed / slot_range) as usize) & (SLOTS - 1);
+        let level_start = self.elapsed & !(level_range - 1);
+
+        // Find the first occupied slot at or after the current one, wrapping around. If the
+        // current slot has already started, its timers belong to the next rotation of the level,
+        // so they expire after the timers in all other slots.
+        let mut occupied = lvl.occupied.rotate_right(now_slot as u32);
+        if level_start + now_slot as u64 * slot_range < self.elapsed && occupied & !1 != 0 {
+            occupied &= !1;
+        }
+        let slot = (occupied.trailing_zeros() as usize + now_slot) & (SLOTS - 1);
+
+        let mut deadline = level_start + slot as u64 * slot_range;
+        if deadline < self.elapsed {
+            deadline += level_range;
+        }
+
+        Some(Expiration {
+            level,
+            slot,
+            deadline,
+        })
+    }
+
+    /// Processes all slots up to tick `now`, and collects the tokens of expired timers.
+    fn poll(&mut self, now: u64, expired: &mut Vec<TimerToken>) {
+        while let Some(exp) = self.next_expiration() {
+            if exp.deadline > now {
+                break;
+            }
+            self.elapsed = exp.deadline;
+
+            let lvl = &mut self.levels[exp.level];
+            let mut index = lvl.heads[exp.slot];
+            lvl.heads[exp.slot] = NIL;
+            lvl.occupied &= !(1 << exp.slot);
+
+            while index != NIL {
+                let next = self.entries[index].next;
+                if self.entries[index].when <= self.elapsed {
+                    self.entries[index].level = None;
+                    expired.push(TimerToken {
+                        index,
+                        generation: self.entries[index].generation,
+                    });
+                    self.release(index);
+                } else {
+                    // Move the timer down to a lower level.
+                    self.link(index);
+                }
+                index = next;
+            }
+        }
+
+        if now > self.elapsed {
+            self.elapsed = now;
+        }
+    }
+}
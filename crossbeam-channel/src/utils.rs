@@ -67,6 +67,9 @@ pub(crate) struct Spinlock<T> {
     value: UnsafeCell<T>,
 }
 
+unsafe impl<T: Send> Send for Spinlock<T> {}
+unsafe impl<T: Send> Sync for Spinlock<T> {}
+
 impl<T> Spinlock<T> {
     /// Returns a new spinlock initialized with `value`.
     pub(crate) fn new(value: T) -> Spinlock<T> {
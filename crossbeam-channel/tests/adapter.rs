@@ -0,0 +1,277 @@
+//! Tests for channel adapters.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, select, tick, unbounded, Select};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn map() {
+    let (s, r) = unbounded();
+    let r = r.map(|x: i32| x * 2);
+
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r.len(), 2);
+    assert_eq!(r.try_recv(), Ok(2));
+    assert_eq!(r.recv(), Ok(4));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+
+    drop(s);
+    assert_eq!(r.recv(), Err(RecvError));
+}
+
+#[test]
+fn map_twice() {
+    let (s, r) = bounded(2);
+    let r = r.map(|x: i32| x + 1).map(|x| x.to_string());
+
+    s.send(1).unwrap();
+    assert_eq!(r.capacity(), Some(2));
+    assert_eq!(r.recv(), Ok("2".to_string()));
+}
+
+#[test]
+fn filter() {
+    let (s, r) = unbounded();
+    let r = r.filter(|x: &i32| x % 3 == 0);
+
+    for i in 0..10 {
+        s.send(i).unwrap();
+    }
+    assert_eq!(r.try_recv(), Ok(0));
+    assert_eq!(r.recv(), Ok(3));
+    assert_eq!(r.recv_timeout(ms(100)), Ok(6));
+    assert_eq!(r.try_recv(), Ok(9));
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    s.send(10).unwrap();
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+
+    drop(s);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn with() {
+    let (s, r) = bounded(1);
+    let s = s.with(|x: i32| x.to_string());
+
+    assert_eq!(s.capacity(), Some(1));
+    assert_eq!(s.try_send(1), Ok(()));
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    assert_eq!(
+        s.send_timeout(3, ms(100)),
+        Err(SendTimeoutError::Timeout(3))
+    );
+    assert_eq!(r.recv(), Ok("1".to_string()));
+
+    s.send(4).unwrap();
+    assert_eq!(r.recv(), Ok("4".to_string()));
+
+    drop(r);
+    assert_eq!(s.send(5), Err(SendError(5)));
+    assert_eq!(s.try_send(6), Err(TrySendError::Disconnected(6)));
+}
+
+#[test]
+fn with_zero() {
+    let (s, r) = bounded(0);
+    let s = s.with(|x: i32| x * 10);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            assert_eq!(r.recv(), Ok(10));
+        });
+        s.send(1).unwrap();
+    })
+    .unwrap();
+
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+}
+
+#[test]
+fn with_calls_once() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+    let (s, r) = bounded(1);
+    let s = s.with(|x: i32| {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        x
+    });
+
+    s.send(1).unwrap();
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    assert_eq!(
+        s.send_timeout(3, ms(100)),
+        Err(SendTimeoutError::Timeout(3))
+    );
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+    assert_eq!(r.recv(), Ok(1));
+}
+
+#[test]
+fn disconnect() {
+    let (s, r) = unbounded::<i32>();
+    let r = r.map(|x| x + 1);
+    let s = s.with(|x: i32| x - 1);
+
+    scope(|scope| {
+        scope.spawn(|_| assert_eq!(r.recv(), Err(RecvError)));
+        thread::sleep(ms(500));
+        drop(s);
+    })
+    .unwrap();
+}
+
+#[test]
+fn select_map() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = unbounded::<&str>();
+    let r1 = r1.map(|x| x.to_string());
+    let r2 = r2.map(|x| x.to_string());
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s2.send("hello").unwrap();
+        });
+
+        select! {
+            recv(r1) -> _ => panic!(),
+            recv(r2) -> msg => assert_eq!(msg, Ok("hello".to_string())),
+        }
+
+        s1.send(7).unwrap();
+        select! {
+            recv(r1) -> msg => assert_eq!(msg, Ok("7".to_string())),
+            recv(r2) -> _ => panic!(),
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn select_filter() {
+    let (s, r) = bounded(0);
+    let r = r.filter(|x: &i32| *x > 5);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..10 {
+                s.send(i).unwrap();
+            }
+        });
+
+        for i in 6..10 {
+            select! {
+                recv(r) -> msg => assert_eq!(msg, Ok(i)),
+                default(ms(1000)) => panic!(),
+            }
+        }
+    })
+    .unwrap();
+
+    drop(s);
+    select! {
+        recv(r) -> msg => assert_eq!(msg, Err(RecvError)),
+        default(ms(1000)) => panic!(),
+    }
+}
+
+#[test]
+fn select_with() {
+    let (s1, r1) = bounded(0);
+    let (s2, r2) = bounded(0);
+    let s1 = s1.with(|x: i32| x + 1);
+    let s2 = s2.with(|x: i32| x + 2);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            assert_eq!(r2.recv(), Ok(12));
+        });
+
+        select! {
+            send(s1, 10) -> _ => panic!(),
+            send(s2, 10) -> res => res.unwrap(),
+        }
+    })
+    .unwrap();
+
+    drop(r1);
+    let mut sel = Select::new();
+    let oper1 = sel.send(&s1);
+    let oper = sel.select();
+    assert_eq!(oper.index(), oper1);
+    assert_eq!(oper.send(&s1, 20), Err(SendError(20)));
+}
+
+#[test]
+fn tick_map() {
+    let r = tick(ms(50)).map(|_| ());
+
+    select! {
+        recv(r) -> msg => assert_eq!(msg, Ok(())),
+        default(ms(1000)) => panic!(),
+    }
+}
+
+#[test]
+fn same_channel() {
+    let (_, r) = unbounded::<i32>();
+    let r1 = r.map(|x| x);
+    let r2 = r1.clone();
+    assert!(r1.same_channel(&r2));
+
+    let (_, r) = unbounded::<i32>();
+    let r3 = r.map(|x| x);
+    assert!(!r1.same_channel(&r3));
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = bounded::<usize>(3);
+    let s = s.with(|x: usize| x * 2);
+    let r = r.filter(|x| x % 4 == 0).map(|x| x / 2);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                while let Ok(n) = r.recv() {
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            let s = s.clone();
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+        drop(s);
+    })
+    .unwrap();
+
+    for (i, c) in v.iter().enumerate() {
+        let expected = if i % 2 == 0 { THREADS } else { 0 };
+        assert_eq!(c.load(Ordering::SeqCst), expected);
+    }
+}
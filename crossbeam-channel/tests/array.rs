@@ -6,7 +6,7 @@ use std::sync::atomic::Ordering;
 use std::thread;
 use std::time::Duration;
 
-use crossbeam_channel::{bounded, select, Receiver};
+use crossbeam_channel::{bounded, bounded_fair, select, Receiver};
 use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
 use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
 use crossbeam_utils::thread::scope;
@@ -529,6 +529,105 @@ fn drops() {
     }
 }
 
+#[test]
+fn large_capacity() {
+    // The buffer is split into chunks, and the last one is only partially used.
+    const CAP: usize = 2500;
+
+    let (s, r) = bounded(CAP);
+    assert_eq!(s.capacity(), Some(CAP));
+
+    for lap in 0..3 {
+        for i in 0..CAP {
+            s.try_send(lap * CAP + i).unwrap();
+        }
+        assert!(s.is_full());
+        assert_eq!(s.try_send(0), Err(TrySendError::Full(0)));
+
+        for i in 0..CAP {
+            assert_eq!(r.try_recv(), Ok(lap * CAP + i));
+        }
+        assert!(r.is_empty());
+    }
+
+    // Move the head and the tail into the middle of a chunk before wrapping around.
+    for i in 0..1500 {
+        s.send(i).unwrap();
+        assert_eq!(r.recv(), Ok(i));
+    }
+    for i in 0..CAP {
+        s.try_send(i).unwrap();
+    }
+    assert_eq!(r.len(), CAP);
+    assert_eq!(
+        r.try_iter().collect::<Vec<_>>(),
+        (0..CAP).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn large_capacity_mpmc() {
+    #[cfg(miri)]
+    const COUNT: usize = 50;
+    #[cfg(not(miri))]
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = bounded::<usize>(3000);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    let n = r.recv().unwrap();
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
+
+#[test]
+fn large_capacity_drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = bounded::<DropCounter>(10_000);
+    for _ in 0..3000 {
+        s.send(DropCounter).unwrap();
+    }
+    for _ in 0..1000 {
+        r.recv().unwrap();
+    }
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1000);
+
+    // Messages left in the channel span several chunks.
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 3000);
+}
+
 #[test]
 fn linearizable() {
     const COUNT: usize = 25_000;
@@ -594,6 +693,104 @@ fn fairness_duplicates() {
     assert!(hits.iter().all(|x| *x >= COUNT / hits.len() / 2));
 }
 
+#[test]
+fn fair_senders() {
+    let (s, r) = bounded_fair(1);
+    s.send(0).unwrap();
+
+    scope(|scope| {
+        for i in 1..=5 {
+            let s = &s;
+            scope.spawn(move |_| s.send(i).unwrap());
+            thread::sleep(ms(100));
+        }
+
+        // Later senders can't overtake the blocked ones.
+        assert_eq!(s.try_send(6), Err(TrySendError::Full(6)));
+
+        for i in 0..=5 {
+            assert_eq!(r.recv(), Ok(i));
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn fair_send_timeout() {
+    let (s, r) = bounded_fair(1);
+    s.send(0).unwrap();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            assert_eq!(
+                s.send_timeout(1, ms(100)),
+                Err(SendTimeoutError::Timeout(1))
+            );
+            s.send(2).unwrap();
+        });
+        scope.spawn(|_| {
+            thread::sleep(ms(50));
+            s.send(3).unwrap();
+        });
+
+        thread::sleep(ms(1000));
+        assert_eq!(r.recv(), Ok(0));
+        assert_eq!(r.recv(), Ok(3));
+        assert_eq!(r.recv(), Ok(2));
+    })
+    .unwrap();
+}
+
+#[test]
+fn fair_disconnect() {
+    let (s, r) = bounded_fair(1);
+    s.send(0).unwrap();
+
+    scope(|scope| {
+        for _ in 0..3 {
+            scope.spawn(|_| assert_eq!(s.send(1), Err(SendError(1))));
+        }
+
+        thread::sleep(ms(500));
+        drop(r);
+    })
+    .unwrap();
+
+    assert_eq!(s.try_send(2), Err(TrySendError::Disconnected(2)));
+}
+
+#[test]
+fn fair_mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = bounded_fair::<usize>(3);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    let n = r.recv().unwrap();
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
+
 #[test]
 fn recv_in_send() {
     let (s, _r) = bounded(1);
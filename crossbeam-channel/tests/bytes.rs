@@ -0,0 +1,233 @@
+//! Tests for byte channels.
+
+use std::io::{ErrorKind, Read, Write};
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{byte_channel, select, unbounded};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (mut s, mut r) = byte_channel(8);
+    assert_eq!(s.write(b"hello").unwrap(), 5);
+
+    let mut buf = [0; 8];
+    assert_eq!(r.read(&mut buf).unwrap(), 5);
+    assert_eq!(&buf[..5], b"hello");
+}
+
+#[test]
+fn capacity() {
+    for cap in 1..10 {
+        let (s, r) = byte_channel(cap);
+        assert_eq!(s.capacity(), cap);
+        assert_eq!(r.capacity(), cap);
+    }
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    byte_channel(0);
+}
+
+#[test]
+fn len_empty_full() {
+    let (mut s, mut r) = byte_channel(4);
+
+    assert_eq!(s.len(), 0);
+    assert_eq!(s.is_empty(), true);
+    assert_eq!(s.is_full(), false);
+
+    // Writes accept only as many bytes as fit.
+    assert_eq!(s.write(b"abcdef").unwrap(), 4);
+
+    assert_eq!(r.len(), 4);
+    assert_eq!(r.is_empty(), false);
+    assert_eq!(r.is_full(), true);
+
+    let mut buf = [0; 3];
+    assert_eq!(r.read(&mut buf).unwrap(), 3);
+    assert_eq!(&buf, b"abc");
+
+    assert_eq!(r.len(), 1);
+    assert_eq!(r.is_empty(), false);
+    assert_eq!(r.is_full(), false);
+}
+
+#[test]
+fn wrap_around() {
+    let (mut s, mut r) = byte_channel(5);
+    let mut buf = [0; 5];
+
+    for i in 0..20u8 {
+        s.write_all(&[i, i + 1, i + 2]).unwrap();
+        r.read_exact(&mut buf[..3]).unwrap();
+        assert_eq!(&buf[..3], &[i, i + 1, i + 2]);
+    }
+}
+
+#[test]
+fn empty_buffers() {
+    let (mut s, mut r) = byte_channel(4);
+    assert_eq!(s.write(&[]).unwrap(), 0);
+    assert_eq!(r.read(&mut []).unwrap(), 0);
+}
+
+#[test]
+fn eof() {
+    let (mut s, mut r) = byte_channel(8);
+    s.write_all(b"abc").unwrap();
+    drop(s);
+
+    let mut v = Vec::new();
+    assert_eq!(r.read_to_end(&mut v).unwrap(), 3);
+    assert_eq!(v, b"abc");
+    assert_eq!(r.read(&mut [0; 4]).unwrap(), 0);
+}
+
+#[test]
+fn broken_pipe() {
+    let (mut s, r) = byte_channel(8);
+    drop(r);
+    assert_eq!(s.write(b"abc").unwrap_err().kind(), ErrorKind::BrokenPipe);
+}
+
+#[test]
+fn blocking_write() {
+    let (mut s, mut r) = byte_channel(2);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            s.write_all(b"abcd").unwrap();
+        });
+
+        thread::sleep(ms(500));
+        assert_eq!(r.len(), 2);
+
+        let mut buf = [0; 4];
+        r.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"abcd");
+    })
+    .unwrap();
+}
+
+#[test]
+fn disconnect_wakes_writer() {
+    let (mut s, r) = byte_channel(2);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            let err = s.write_all(b"abcd").unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::BrokenPipe);
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            drop(r);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn disconnect_wakes_reader() {
+    let (s, mut r) = byte_channel(2);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.read(&mut [0; 4]).unwrap(), 0);
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            drop(s);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn readable() {
+    let (mut s, mut r) = byte_channel(4);
+    let readable = r.readable();
+
+    assert_eq!(readable.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(
+        readable.recv_timeout(ms(100)),
+        Err(RecvTimeoutError::Timeout)
+    );
+
+    s.write_all(b"ab").unwrap();
+    assert_eq!(readable.len(), 2);
+
+    // Readiness doesn't consume bytes.
+    assert_eq!(readable.try_recv(), Ok(()));
+    assert_eq!(readable.recv(), Ok(()));
+    assert_eq!(r.read(&mut [0; 4]).unwrap(), 2);
+    assert_eq!(readable.try_recv(), Err(TryRecvError::Empty));
+
+    s.write_all(b"c").unwrap();
+    drop(s);
+    assert_eq!(readable.recv(), Ok(()));
+    assert_eq!(r.read(&mut [0; 4]).unwrap(), 1);
+    assert_eq!(readable.recv(), Err(RecvError));
+}
+
+#[test]
+fn select_readable() {
+    let (mut s, mut r) = byte_channel(4);
+    let (s2, r2) = unbounded::<()>();
+    let readable = r.readable();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            s.write_all(b"abc").unwrap();
+        });
+
+        select! {
+            recv(r2) -> _ => panic!(),
+            recv(readable) -> msg => assert_eq!(msg, Ok(())),
+        }
+        let mut buf = [0; 3];
+        r.read_exact(&mut buf).unwrap();
+        assert_eq!(&buf, b"abc");
+
+        select! {
+            recv(r2) -> _ => panic!(),
+            recv(readable) -> msg => assert_eq!(msg, Err(RecvError)),
+        }
+    })
+    .unwrap();
+
+    drop(s2);
+}
+
+#[test]
+fn stream() {
+    const COUNT: usize = 100_000;
+
+    let (mut s, mut r) = byte_channel(7);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            let data = (0..COUNT).map(|i| i as u8).collect::<Vec<_>>();
+            for chunk in data.chunks(13) {
+                s.write_all(chunk).unwrap();
+            }
+        });
+
+        let mut v = Vec::new();
+        r.read_to_end(&mut v).unwrap();
+        assert_eq!(v.len(), COUNT);
+        for (i, &b) in v.iter().enumerate() {
+            assert_eq!(b, i as u8);
+        }
+    })
+    .unwrap();
+}
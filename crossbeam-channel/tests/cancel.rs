@@ -0,0 +1,137 @@
+//! Tests for cancellable operations.
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded, CancelToken};
+use crossbeam_channel::{RecvCancelError, SendCancelError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let token = CancelToken::new();
+    assert!(!token.is_cancelled());
+
+    let (s, r) = unbounded();
+    s.send_cancellable(7, &token).unwrap();
+    assert_eq!(r.recv_cancellable(&token), Ok(7));
+
+    token.cancel();
+    assert!(token.is_cancelled());
+    assert_eq!(
+        s.send_cancellable(8, &token),
+        Err(SendCancelError::Cancelled(8))
+    );
+    assert_eq!(r.recv_cancellable(&token), Err(RecvCancelError::Cancelled));
+    assert!(r.is_empty());
+}
+
+#[test]
+fn clone_shares_state() {
+    let token = CancelToken::new();
+    let t = token.clone();
+
+    t.cancel();
+    assert!(token.is_cancelled());
+
+    // Cancelling again is a no-op.
+    token.cancel();
+    assert!(t.is_cancelled());
+}
+
+#[test]
+fn disconnected() {
+    let token = CancelToken::new();
+
+    let (s, r) = bounded::<i32>(1);
+    drop(r);
+    assert_eq!(
+        s.send_cancellable(1, &token),
+        Err(SendCancelError::Disconnected(1))
+    );
+
+    let (s, r) = bounded::<i32>(1);
+    drop(s);
+    assert_eq!(
+        r.recv_cancellable(&token),
+        Err(RecvCancelError::Disconnected)
+    );
+}
+
+#[test]
+fn recv() {
+    let (s, r) = bounded(0);
+    let token = CancelToken::new();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            assert_eq!(r.recv_cancellable(&token), Ok(7));
+            assert_eq!(r.recv_cancellable(&token), Err(RecvCancelError::Cancelled));
+        });
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            s.send(7).unwrap();
+            thread::sleep(ms(500));
+            token.cancel();
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn send() {
+    let (s, r) = bounded(1);
+    let token = CancelToken::new();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            assert_eq!(s.send_cancellable(7, &token), Ok(()));
+            assert_eq!(s.send_cancellable(8, &token), Ok(()));
+            assert_eq!(
+                s.send_cancellable(9, &token),
+                Err(SendCancelError::Cancelled(9))
+            );
+        });
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            assert_eq!(r.recv(), Ok(7));
+            thread::sleep(ms(500));
+            token.cancel();
+        });
+    })
+    .unwrap();
+
+    assert_eq!(r.try_recv(), Ok(8));
+    assert!(r.is_empty());
+}
+
+#[test]
+fn cancel_many() {
+    const THREADS: usize = 10;
+
+    let (s, r) = bounded::<i32>(0);
+    let token = CancelToken::new();
+
+    scope(|scope| {
+        for _ in 0..THREADS / 2 {
+            scope.spawn(|_| {
+                assert_eq!(r.recv_cancellable(&token), Err(RecvCancelError::Cancelled));
+            });
+        }
+        for _ in 0..THREADS / 2 {
+            scope.spawn(|_| {
+                let t = CancelToken::new();
+                assert_eq!(r.recv_cancellable(&t), Err(RecvCancelError::Disconnected));
+            });
+        }
+        thread::sleep(ms(500));
+        token.cancel();
+        thread::sleep(ms(500));
+        drop(s);
+    })
+    .unwrap();
+}
@@ -0,0 +1,95 @@
+//! Tests for error types.
+
+use std::io;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded, CancelToken};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn send_into_io_error() {
+    let (s, r) = bounded(1);
+    s.send(1).unwrap();
+
+    let err = io::Error::from(s.try_send(2).unwrap_err());
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+    assert_eq!(err.to_string(), "sending on a full channel");
+
+    let err = io::Error::from(s.send_timeout(2, ms(10)).unwrap_err());
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+
+    drop(r);
+    let err = io::Error::from(s.send(2).unwrap_err());
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    assert!(err.get_ref().unwrap().is::<SendError<()>>());
+
+    let err = io::Error::from(s.try_send(2).unwrap_err());
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    assert!(err.get_ref().unwrap().is::<TrySendError<()>>());
+
+    let err = io::Error::from(s.send_timeout(2, ms(10)).unwrap_err());
+    assert_eq!(err.kind(), io::ErrorKind::BrokenPipe);
+    assert!(err.get_ref().unwrap().is::<SendTimeoutError<()>>());
+}
+
+#[test]
+fn recv_into_io_error() {
+    let (s, r) = unbounded::<i32>();
+
+    let err = io::Error::from(r.try_recv().unwrap_err());
+    assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+    let err = io::Error::from(r.recv_timeout(ms(10)).unwrap_err());
+    assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+    assert_eq!(
+        err.into_inner()
+            .unwrap()
+            .downcast::<RecvTimeoutError>()
+            .ok(),
+        Some(Box::new(RecvTimeoutError::Timeout))
+    );
+
+    drop(s);
+    let err = io::Error::from(r.recv().unwrap_err());
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert!(err.get_ref().unwrap().is::<RecvError>());
+
+    let err = io::Error::from(r.try_recv().unwrap_err());
+    assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    assert!(err.get_ref().unwrap().is::<TryRecvError>());
+}
+
+#[test]
+fn cancel_into_io_error() {
+    let (s, r) = bounded(0);
+    let token = CancelToken::new();
+    token.cancel();
+
+    let err = io::Error::from(s.send_cancellable(1, &token).unwrap_err());
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+
+    let err = io::Error::from(r.recv_cancellable(&token).unwrap_err());
+    assert_eq!(err.kind(), io::ErrorKind::Other);
+}
+
+#[test]
+fn question_mark() {
+    fn forward(r: &crossbeam_channel::Receiver<i32>) -> io::Result<i32> {
+        Ok(r.recv()?)
+    }
+
+    let (s, r) = unbounded();
+    s.send(7).unwrap();
+    assert_eq!(forward(&r).unwrap(), 7);
+
+    drop(s);
+    assert_eq!(
+        forward(&r).unwrap_err().kind(),
+        io::ErrorKind::UnexpectedEof
+    );
+}
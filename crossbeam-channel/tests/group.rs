@@ -0,0 +1,130 @@
+//! Tests for sender groups.
+
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, unbounded, Group, SendPolicy, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+
+    let mut group = Group::new(SendPolicy::Error);
+    assert_eq!(group.send_to_all(0), Ok(0));
+
+    group.extend(vec![s1, s2]);
+    assert_eq!(group.len(), 2);
+    assert_eq!(group.send_to_all(1), Ok(2));
+    assert_eq!(r1.try_recv(), Ok(1));
+    assert_eq!(r2.try_recv(), Ok(1));
+}
+
+#[test]
+fn skip() {
+    let (s1, r1) = bounded(1);
+    let (s2, r2) = unbounded();
+    let (s3, r3) = unbounded();
+    drop(r3);
+
+    let mut group = Group::new(SendPolicy::Skip);
+    group.extend(vec![s1, s2, s3]);
+
+    assert_eq!(group.send_to_all(1), Ok(2));
+    assert_eq!(group.send_to_all(2), Ok(1));
+    assert_eq!(r1.try_iter().collect::<Vec<_>>(), [1]);
+    assert_eq!(r2.try_iter().collect::<Vec<_>>(), [1, 2]);
+}
+
+#[test]
+fn block() {
+    let (s1, r1) = bounded(1);
+    let (s2, r2) = bounded(0);
+    let (s3, r3) = unbounded();
+    drop(r3);
+
+    let mut group = Group::new(SendPolicy::Block);
+    group.extend(vec![s1, s2, s3]);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            assert_eq!(r2.recv(), Ok(1));
+            thread::sleep(ms(500));
+            assert_eq!(r1.recv(), Ok(1));
+            assert_eq!(r1.recv(), Ok(2));
+            assert_eq!(r2.recv(), Ok(2));
+        });
+
+        assert_eq!(group.send_to_all(1), Ok(2));
+        assert_eq!(group.send_to_all(2), Ok(2));
+    })
+    .unwrap();
+}
+
+#[test]
+fn block_disconnect() {
+    let (s, r) = bounded(1);
+
+    let mut group = Group::new(SendPolicy::Block);
+    group.push(s);
+    assert_eq!(group.send_to_all(1), Ok(1));
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            thread::sleep(ms(500));
+            drop(r);
+        });
+
+        // The blocked send completes once the receiver is gone.
+        assert_eq!(group.send_to_all(2), Ok(0));
+    })
+    .unwrap();
+}
+
+#[test]
+fn error() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = bounded(1);
+    let (s3, r3) = unbounded();
+
+    let mut group = Group::new(SendPolicy::Error);
+    group.extend(vec![s1, s2, s3]);
+    assert_eq!(group.send_to_all(1), Ok(3));
+
+    let err = group.send_to_all(2).unwrap_err();
+    assert_eq!(err.index, 1);
+    assert_eq!(err.error, TrySendError::Full(2));
+    assert_eq!(err.into_inner(), 2);
+
+    assert_eq!(r1.try_iter().collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(r2.try_iter().collect::<Vec<_>>(), [1]);
+    assert_eq!(r3.try_iter().collect::<Vec<_>>(), [1]);
+
+    drop(r1);
+    let err = group.send_to_all(3).unwrap_err();
+    assert_eq!(err.index, 0);
+    assert_eq!(err.error, TrySendError::Disconnected(3));
+}
+
+#[test]
+fn remove() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = unbounded();
+
+    let mut group = Group::new(SendPolicy::Skip);
+    group.push(s1.clone());
+    group.push(s2);
+
+    assert!(group.remove(0).same_channel(&s1));
+    assert_eq!(group.len(), 1);
+
+    assert_eq!(group.send_to_all(1), Ok(1));
+    assert_eq!(r1.try_recv().is_err(), true);
+    assert_eq!(r2.try_recv(), Ok(1));
+}
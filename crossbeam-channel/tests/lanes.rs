@@ -0,0 +1,252 @@
+//! Tests for the lanes channel flavor.
+
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded_with_lanes, select};
+use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
+use crossbeam_channel::{SendError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = bounded_with_lanes(4, 2);
+    s.send(7).unwrap();
+    assert_eq!(r.try_recv(), Ok(7));
+
+    s.send(8).unwrap();
+    assert_eq!(r.recv(), Ok(8));
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(r.recv_timeout(ms(1000)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+fn capacity() {
+    for cap in 1..10 {
+        for lanes in 1..10 {
+            let (s, r) = bounded_with_lanes::<()>(cap, lanes);
+            assert_eq!(s.capacity(), Some(cap));
+            assert_eq!(r.capacity(), Some(cap));
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    bounded_with_lanes::<()>(0, 4);
+}
+
+#[test]
+#[should_panic(expected = "number of lanes must be positive")]
+fn zero_lanes() {
+    bounded_with_lanes::<()>(4, 0);
+}
+
+#[test]
+fn len_empty_full() {
+    let (s, r) = bounded_with_lanes(2, 1);
+
+    assert_eq!(s.len(), 0);
+    assert_eq!(s.is_empty(), true);
+    assert_eq!(s.is_full(), false);
+
+    s.send(()).unwrap();
+    s.send(()).unwrap();
+
+    assert_eq!(r.len(), 2);
+    assert_eq!(r.is_empty(), false);
+    assert_eq!(r.is_full(), true);
+    assert_eq!(s.try_send(()), Err(TrySendError::Full(())));
+
+    r.recv().unwrap();
+
+    assert_eq!(r.len(), 1);
+    assert_eq!(r.is_empty(), false);
+    assert_eq!(r.is_full(), false);
+}
+
+#[test]
+fn fifo_per_sender() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 8;
+
+    let (s, r) = bounded_with_lanes(16, 4);
+
+    scope(|scope| {
+        for t in 0..THREADS {
+            let s = s.clone();
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.send((t, i)).unwrap();
+                }
+            });
+        }
+        drop(s);
+
+        let mut next = [0; THREADS];
+        for (t, i) in r.iter() {
+            assert_eq!(next[t], i);
+            next[t] += 1;
+        }
+        assert_eq!(next, [COUNT; THREADS]);
+    })
+    .unwrap();
+}
+
+#[test]
+fn disconnect_wakes_receiver() {
+    let (s, r) = bounded_with_lanes::<()>(4, 4);
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            assert_eq!(r.recv(), Err(RecvError));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(1000));
+            drop(s);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn recv_after_disconnect() {
+    let (s, r) = bounded_with_lanes(16, 4);
+
+    scope(|scope| {
+        for i in 0..4 {
+            let s = s.clone();
+            scope.spawn(move |_| s.send(i).unwrap());
+        }
+    })
+    .unwrap();
+    drop(s);
+
+    let mut v = r.iter().collect::<Vec<_>>();
+    v.sort();
+    assert_eq!(v, [0, 1, 2, 3]);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn send_after_disconnect() {
+    let (s, r) = bounded_with_lanes(4, 2);
+    drop(r);
+
+    assert_eq!(s.send(1), Err(SendError(1)));
+    assert_eq!(s.try_send(2), Err(TrySendError::Disconnected(2)));
+}
+
+#[test]
+fn mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = bounded_with_lanes::<usize>(3, 8);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    let n = r.recv().unwrap();
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
+
+#[test]
+fn select() {
+    const COUNT: usize = 10_000;
+
+    let (s1, r1) = bounded_with_lanes(4, 4);
+    let (s2, r2) = bounded_with_lanes(4, 4);
+
+    scope(|scope| {
+        for _ in 0..2 {
+            let (s1, s2) = (s1.clone(), s2.clone());
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    select! {
+                        send(s1, i) -> res => res.unwrap(),
+                        send(s2, i) -> res => res.unwrap(),
+                    }
+                }
+            });
+        }
+        drop((s1, s2));
+
+        let mut hits = 0;
+        loop {
+            select! {
+                recv(r1) -> msg => match msg {
+                    Ok(_) => hits += 1,
+                    Err(_) => break,
+                },
+                recv(r2) -> msg => match msg {
+                    Ok(_) => hits += 1,
+                    Err(_) => break,
+                },
+            }
+        }
+        hits += r1.iter().count() + r2.iter().count();
+        assert_eq!(hits, 2 * COUNT);
+    })
+    .unwrap();
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let (s, r) = bounded_with_lanes(30, 3);
+    scope(|scope| {
+        for _ in 0..3 {
+            let s = s.clone();
+            scope.spawn(move |_| {
+                for _ in 0..3 {
+                    s.send(DropCounter).unwrap();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    r.recv().unwrap();
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    drop(s);
+    drop(r);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 9);
+}
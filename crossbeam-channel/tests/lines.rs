@@ -0,0 +1,143 @@
+//! Tests for the channel that reads lines.
+
+use std::io::{self, BufRead, Cursor, Read};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam_channel::{bounded, lines, select, RecvTimeoutError, Sender};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+/// A reader that counts the lines read from it and reports when it gets dropped.
+struct Counted {
+    inner: Cursor<Vec<u8>>,
+    reads: Arc<AtomicUsize>,
+    dropped: Sender<()>,
+}
+
+impl Read for Counted {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.inner.read(buf)
+    }
+}
+
+impl BufRead for Counted {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        self.inner.fill_buf()
+    }
+
+    fn consume(&mut self, amt: usize) {
+        self.inner.consume(amt)
+    }
+
+    fn read_line(&mut self, buf: &mut String) -> io::Result<usize> {
+        self.reads.fetch_add(1, Ordering::SeqCst);
+        self.inner.read_line(buf)
+    }
+}
+
+impl Drop for Counted {
+    fn drop(&mut self) {
+        let _ = self.dropped.send(());
+    }
+}
+
+/// A reader that fails after yielding a line.
+struct Failing(bool);
+
+impl Read for Failing {
+    fn read(&mut self, _: &mut [u8]) -> io::Result<usize> {
+        unreachable!()
+    }
+}
+
+impl BufRead for Failing {
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        if self.0 {
+            Err(io::Error::new(io::ErrorKind::Other, "failure"))
+        } else {
+            Ok(b"line\n")
+        }
+    }
+
+    fn consume(&mut self, amt: usize) {
+        assert_eq!(amt, 5);
+        self.0 = true;
+    }
+}
+
+#[test]
+fn smoke() {
+    let r = lines(Cursor::new("foo\nbar\n"));
+    assert_eq!(r.recv(), Ok("foo".to_string()));
+    assert_eq!(r.recv(), Ok("bar".to_string()));
+    assert!(r.recv().is_err());
+}
+
+#[test]
+fn line_endings() {
+    let r = lines(Cursor::new(
+        "unix\nwindows\r\n\n\r\nlone\rcarriage\rreturn\r\nlast",
+    ));
+    let received: Vec<_> = r.iter().collect();
+    assert_eq!(
+        received,
+        ["unix", "windows", "", "", "lone\rcarriage\rreturn", "last"]
+    );
+}
+
+#[test]
+fn empty() {
+    let r = lines(Cursor::new(""));
+    assert!(r.recv().is_err());
+}
+
+#[test]
+fn error_disconnects() {
+    let r = lines(Failing(false));
+    assert_eq!(r.recv(), Ok("line".to_string()));
+    assert!(r.recv().is_err());
+}
+
+#[test]
+fn reads_on_demand() {
+    let reads = Arc::new(AtomicUsize::new(0));
+    let (ds, dr) = bounded(1);
+    let r = lines(Counted {
+        inner: Cursor::new(b"a\nb\nc\nd\n".to_vec()),
+        reads: reads.clone(),
+        dropped: ds,
+    });
+
+    // The background thread reads one line and blocks until it's received.
+    thread::sleep(ms(100));
+    assert_eq!(reads.load(Ordering::SeqCst), 1);
+
+    assert_eq!(r.recv(), Ok("a".to_string()));
+    thread::sleep(ms(100));
+    assert_eq!(reads.load(Ordering::SeqCst), 2);
+
+    // Once the receiver is dropped, the thread exits after failing to send the pending line.
+    drop(r);
+    assert_eq!(dr.recv_timeout(ms(1000)), Ok(()));
+    assert_eq!(reads.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn select() {
+    let r = lines(Cursor::new("foo\n"));
+    let (_s, other) = bounded::<()>(0);
+
+    select! {
+        recv(other) -> _ => panic!(),
+        recv(r) -> line => assert_eq!(line, Ok("foo".to_string())),
+    }
+    assert_eq!(
+        r.recv_timeout(ms(1000)),
+        Err(RecvTimeoutError::Disconnected)
+    );
+}
@@ -6,7 +6,7 @@ use std::sync::atomic::Ordering;
 use std::thread;
 use std::time::Duration;
 
-use crossbeam_channel::{select, unbounded, Receiver};
+use crossbeam_channel::{select, unbounded, unbounded_with_block_size, Receiver};
 use crossbeam_channel::{RecvError, RecvTimeoutError, TryRecvError};
 use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
 use crossbeam_utils::thread::scope;
@@ -419,6 +419,167 @@ fn drops() {
     }
 }
 
+#[test]
+fn block_size() {
+    for &block_size in &[1, 2, 3, 7, 31, 32, 100, 1000] {
+        let (s, r) = unbounded_with_block_size(block_size);
+
+        for round in 0..3 {
+            for i in 0..1000 {
+                s.send(i).unwrap();
+                assert_eq!(r.len(), i + 1);
+            }
+            for i in 0..1000 {
+                assert_eq!(r.recv(), Ok(i));
+                assert_eq!(r.len(), 999 - i);
+            }
+            assert!(r.is_empty(), "round {}", round);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "block size must be positive")]
+fn block_size_zero() {
+    let _ = unbounded_with_block_size::<i32>(0);
+}
+
+#[test]
+fn block_size_mpmc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let (s, r) = unbounded_with_block_size::<usize>(1);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    let n = r.recv().unwrap();
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
+
+#[test]
+fn block_size_drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let mut rng = thread_rng();
+
+    for _ in 0..100 {
+        let block_size = rng.gen_range(1..100);
+        let steps = rng.gen_range(0..10_000);
+        let additional = rng.gen_range(0..1000);
+
+        DROPS.store(0, Ordering::SeqCst);
+        let (s, r) = unbounded_with_block_size::<DropCounter>(block_size);
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                for _ in 0..steps {
+                    r.recv().unwrap();
+                }
+            });
+
+            scope.spawn(|_| {
+                for _ in 0..steps {
+                    s.send(DropCounter).unwrap();
+                }
+            });
+        })
+        .unwrap();
+
+        for _ in 0..additional {
+            s.try_send(DropCounter).unwrap();
+        }
+
+        assert_eq!(DROPS.load(Ordering::SeqCst), steps);
+        drop(s);
+        drop(r);
+        assert_eq!(DROPS.load(Ordering::SeqCst), steps + additional);
+    }
+}
+
+#[test]
+fn shrink_to_fit() {
+    let (s, r) = unbounded_with_block_size(4);
+
+    for _ in 0..3 {
+        // A burst frees many blocks once drained.
+        for i in 0..1000 {
+            s.send(i).unwrap();
+        }
+        for i in 0..990 {
+            assert_eq!(r.recv(), Ok(i));
+        }
+        r.shrink_to_fit();
+
+        // Messages still in the channel are kept.
+        assert_eq!(r.len(), 10);
+        assert_eq!(
+            r.try_iter().collect::<Vec<_>>(),
+            (990..1000).collect::<Vec<_>>()
+        );
+        r.shrink_to_fit();
+    }
+
+    let r = r.map(|x| x * 2);
+    s.send(1).unwrap();
+    r.shrink_to_fit();
+    assert_eq!(r.recv(), Ok(2));
+}
+
+#[test]
+fn shrink_to_fit_concurrent() {
+    #[cfg(miri)]
+    const COUNT: usize = 50;
+    #[cfg(not(miri))]
+    const COUNT: usize = 100_000;
+
+    let (s, r) = unbounded_with_block_size(4);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                s.send(i).unwrap();
+            }
+        });
+
+        for i in 0..COUNT {
+            assert_eq!(r.recv(), Ok(i));
+            if i % 100 == 0 {
+                r.shrink_to_fit();
+            }
+        }
+    })
+    .unwrap();
+}
+
 #[test]
 fn linearizable() {
     const COUNT: usize = 25_000;
@@ -0,0 +1,72 @@
+//! Tests for sender and receiver counts.
+
+use std::time::Duration;
+
+use crossbeam_channel::{after, bounded, bounded_with_lanes, never, tick, unbounded};
+use crossbeam_channel::{unbounded_sharded, Receiver, Sender};
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+fn check<T>(s: Sender<T>, r: Receiver<T>) {
+    assert_eq!(s.receiver_count(), 1);
+    assert_eq!(r.sender_count(), 1);
+
+    let s2 = s.clone();
+    let r2 = r.clone();
+    let r3 = r.clone();
+    assert_eq!(s.receiver_count(), 3);
+    assert_eq!(r.sender_count(), 2);
+
+    drop(s2);
+    assert_eq!(r.sender_count(), 1);
+
+    drop(r);
+    drop(r2);
+    assert_eq!(s.receiver_count(), 1);
+
+    drop(s);
+    assert_eq!(r3.sender_count(), 0);
+}
+
+#[test]
+fn flavors() {
+    let (s, r) = bounded::<i32>(0);
+    check(s, r);
+    let (s, r) = bounded::<i32>(1);
+    check(s, r);
+    let (s, r) = bounded_with_lanes::<i32>(4, 2);
+    check(s, r);
+    let (s, r) = unbounded::<i32>();
+    check(s, r);
+    let (s, r) = unbounded_sharded::<i32>(2);
+    check(s, r);
+}
+
+#[test]
+fn adapters() {
+    let (s, r) = unbounded::<i32>();
+    let s = s.with(|x: i32| x);
+    let r = r.map(|x| x);
+
+    assert_eq!(s.receiver_count(), 1);
+    assert_eq!(r.sender_count(), 1);
+
+    // Clones of an adapter share the underlying endpoint.
+    let s2 = s.clone();
+    let r2 = r.clone();
+    assert_eq!(s.receiver_count(), 1);
+    assert_eq!(r.sender_count(), 1);
+
+    drop((s, s2));
+    assert_eq!(r2.sender_count(), 0);
+    drop((r, r2));
+}
+
+#[test]
+fn no_senders() {
+    assert_eq!(after(ms(100)).sender_count(), 0);
+    assert_eq!(tick(ms(100)).sender_count(), 0);
+    assert_eq!(never::<i32>().sender_count(), 0);
+}
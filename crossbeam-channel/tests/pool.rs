@@ -0,0 +1,104 @@
+//! Tests for recycling channels.
+
+use crossbeam_channel::{pool, select, TryRecvError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+#[test]
+fn smoke() {
+    let (s, r) = pool::<Vec<u8>>(1);
+
+    let mut buf = s.alloc();
+    buf.push(7);
+    s.send(buf).unwrap();
+    assert_eq!(s.try_send(vec![8]), Err(TrySendError::Full(vec![8])));
+
+    let buf = r.recv().unwrap();
+    assert_eq!(buf, [7]);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    r.recycle(buf);
+    assert_eq!(s.free_len(), 1);
+    assert_eq!(s.alloc(), [7]);
+    assert_eq!(s.free_len(), 0);
+}
+
+#[test]
+fn alloc_with() {
+    let (s, r) = pool::<Vec<u8>>(1);
+
+    assert_eq!(s.alloc_with(|| vec![1]), [1]);
+
+    r.recycle(vec![2]);
+    assert_eq!(s.alloc_with(|| vec![1]), [2]);
+}
+
+#[test]
+fn free_list_overflow() {
+    let (s, r) = pool::<i32>(2);
+
+    for i in 0..10 {
+        r.recycle(i);
+    }
+    assert_eq!(s.free_len(), 3);
+    assert_eq!(s.alloc(), 0);
+    assert_eq!(s.alloc(), 1);
+    assert_eq!(s.alloc(), 2);
+    assert_eq!(s.alloc(), 0);
+}
+
+#[test]
+fn zero_capacity() {
+    let (s, r) = pool::<i32>(0);
+    assert_eq!(s.capacity(), Some(0));
+
+    r.recycle(5);
+    assert_eq!(s.alloc(), 5);
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = pool::<i32>(1);
+    drop(s);
+
+    assert_eq!(r.try_recv(), Err(TryRecvError::Disconnected));
+    r.recycle(1);
+}
+
+#[test]
+fn select() {
+    let (s, r) = pool::<i32>(1);
+    s.send(1).unwrap();
+
+    select! {
+        recv(r) -> msg => r.recycle(msg.unwrap()),
+        default => panic!(),
+    }
+    assert_eq!(s.alloc(), 1);
+}
+
+#[test]
+fn recycles() {
+    const COUNT: usize = 10_000;
+
+    let (s, r) = pool::<Vec<usize>>(4);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                let mut buf = s.alloc_with(|| Vec::with_capacity(16));
+                buf.clear();
+                buf.push(i);
+                s.send(buf).unwrap();
+            }
+        });
+
+        for i in 0..COUNT {
+            let buf = r.recv().unwrap();
+            assert_eq!(buf, [i]);
+            r.recycle(buf);
+        }
+    })
+    .unwrap();
+
+    assert!(s.free_len() > 0);
+}
@@ -1,4 +1,4 @@
-use std::time::Duration;
+use std::time::{Duration, Instant};
 
 use crossbeam_channel::{after, bounded, never, tick, unbounded};
 
@@ -110,3 +110,36 @@ fn different_flavors_same_channel() {
     assert!(!s1.same_channel(&s2));
     assert!(!r1.same_channel(&r2));
 }
+
+#[test]
+fn sender_receiver_same_channel() {
+    for (s, r) in vec![bounded::<usize>(0), bounded(1), unbounded()] {
+        let s2 = s.clone();
+        let r2 = r.clone();
+        assert!(s.same_channel_as_receiver(&r));
+        assert!(s2.same_channel_as_receiver(&r2));
+        assert!(r.same_channel_as_sender(&s2));
+
+        let (s3, r3) = unbounded::<usize>();
+        assert!(!s.same_channel_as_receiver(&r3));
+        assert!(!r.same_channel_as_sender(&s3));
+    }
+
+    let (s, _) = unbounded::<Instant>();
+    assert!(!s.same_channel_as_receiver(&after(ms(50))));
+    assert!(!s.same_channel_as_receiver(&tick(ms(50))));
+    assert!(!never().same_channel_as_sender(&s));
+}
+
+#[test]
+fn adapters_same_channel() {
+    let (s, r) = unbounded::<usize>();
+    let s2 = s.clone().with(|x: usize| x + 1);
+    let r2 = r.clone().map(|x| x + 1);
+
+    assert!(s2.same_channel(&s2.clone()));
+    assert!(!s2.same_channel(&s));
+    assert!(!s2.same_channel_as_receiver(&r));
+    assert!(!r2.same_channel_as_sender(&s));
+    assert!(s.same_channel_as_receiver(&r));
+}
@@ -2,10 +2,12 @@
 
 use std::any::Any;
 use std::cell::Cell;
+use std::panic::{self, AssertUnwindSafe};
 use std::thread;
 use std::time::{Duration, Instant};
 
 use crossbeam_channel::{after, bounded, tick, unbounded, Receiver, Select, TryRecvError};
+use crossbeam_channel::{Completed, RecvError};
 use crossbeam_utils::thread::scope;
 
 fn ms(ms: u64) -> Duration {
@@ -643,6 +645,81 @@ fn duplicate_operations() {
     }
 }
 
+#[test]
+fn duplicate_operations_blocking() {
+    for cap in 0..3 {
+        let (s, r) = bounded::<usize>(cap);
+        let hit = vec![Cell::new(false); 2];
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                for i in 0.. {
+                    thread::sleep(ms(1));
+                    if s.send(i).is_err() {
+                        break;
+                    }
+                }
+            });
+
+            while hit.iter().map(|h| h.get()).any(|hit| !hit) {
+                let mut sel = Select::new();
+                let oper0 = sel.recv(&r);
+                let oper1 = sel.recv(&r);
+                let oper = sel.select();
+                match oper.index() {
+                    i if i == oper0 => {
+                        assert!(oper.recv(&r).is_ok());
+                        hit[0].set(true);
+                    }
+                    i if i == oper1 => {
+                        assert!(oper.recv(&r).is_ok());
+                        hit[1].set(true);
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            drop(r);
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn duplicate_send_operations_blocking() {
+    for cap in 0..3 {
+        let (s, r) = bounded::<usize>(cap);
+        let hit = vec![Cell::new(false); 2];
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                while r.recv().is_ok() {
+                    thread::sleep(ms(1));
+                }
+            });
+
+            while hit.iter().map(|h| h.get()).any(|hit| !hit) {
+                let mut sel = Select::new();
+                let oper0 = sel.send(&s);
+                let oper1 = sel.send(&s);
+                let oper = sel.select();
+                match oper.index() {
+                    i if i == oper0 => {
+                        assert!(oper.send(&s, 0).is_ok());
+                        hit[0].set(true);
+                    }
+                    i if i == oper1 => {
+                        assert!(oper.send(&s, 1).is_ok());
+                        hit[1].set(true);
+                    }
+                    _ => unreachable!(),
+                }
+            }
+            drop(s);
+        })
+        .unwrap();
+    }
+}
+
 #[test]
 fn nesting() {
     let (s, r) = unbounded::<i32>();
@@ -1297,3 +1374,79 @@ fn reuse() {
     })
     .unwrap();
 }
+
+#[test]
+fn select_with() {
+    let (s1, r1) = unbounded::<i32>();
+    let (s2, r2) = bounded::<i32>(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            thread::sleep(ms(500));
+            assert_eq!(r2.recv(), Ok(2));
+        });
+
+        let mut sel = Select::new();
+        let oper1 = sel.recv(&r1);
+        let oper2 = sel.send(&s2);
+
+        let res = sel.select_with(|oper| match oper.index() {
+            i if i == oper1 => oper.recv(&r1).map(|_| panic!()),
+            i if i == oper2 => oper.send(&s2, 2),
+            _ => unreachable!(),
+        });
+        assert_eq!(res, Ok(()));
+    })
+    .unwrap();
+
+    drop(s1);
+}
+
+#[test]
+fn try_select_with() {
+    let (s, r) = unbounded::<i32>();
+
+    let mut sel = Select::new();
+    sel.recv(&r);
+    assert!(sel.try_select_with(|oper| oper.recv(&r)).is_err());
+
+    s.send(1).unwrap();
+    assert_eq!(sel.try_select_with(|oper| oper.recv(&r)), Ok(Ok(1)));
+
+    drop(s);
+    assert_eq!(
+        sel.try_select_with(|oper| oper.recv(&r)),
+        Ok(Err(RecvError))
+    );
+}
+
+#[test]
+fn select_timeout_with() {
+    let (_s, r) = unbounded::<i32>();
+
+    let mut sel = Select::new();
+    sel.recv(&r);
+
+    let start = Instant::now();
+    assert!(sel
+        .select_timeout_with(ms(100), |oper| oper.recv(&r))
+        .is_err());
+    assert!(start.elapsed() >= ms(100));
+}
+
+#[test]
+fn panic_inside_select_with() {
+    let (s, r) = unbounded::<i32>();
+    s.send(1).unwrap();
+
+    // Unwinding out of the closure drops the operation without aborting the process.
+    let res = panic::catch_unwind(AssertUnwindSafe(|| {
+        let mut sel = Select::new();
+        sel.recv(&r);
+        sel.select_with(|oper| -> Completed<'_, ()> {
+            let _ = oper.index();
+            panic!("oops");
+        })
+    }));
+    assert!(res.is_err());
+}
@@ -0,0 +1,242 @@
+//! Tests for rate-limited senders.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{bounded, select, unbounded, Throttled};
+use crossbeam_channel::{SendError, SendTimeoutError, TrySendError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (s, r) = unbounded();
+    let s = Throttled::new(s, 1, ms(1000));
+
+    assert_eq!(s.try_send(7), Ok(()));
+    assert_eq!(r.try_recv(), Ok(7));
+    assert_eq!(s.try_send(8), Err(TrySendError::Full(8)));
+}
+
+#[test]
+#[should_panic(expected = "burst must be positive")]
+fn zero_burst() {
+    let (s, _r) = unbounded::<i32>();
+    let _ = Throttled::new(s, 0, ms(100));
+}
+
+#[test]
+#[should_panic(expected = "interval must be positive")]
+fn zero_interval() {
+    let (s, _r) = unbounded::<i32>();
+    let _ = Throttled::new(s, 1, ms(0));
+}
+
+#[test]
+fn burst() {
+    let (s, r) = unbounded();
+    let s = Throttled::new(s, 5, ms(1000));
+
+    for i in 0..5 {
+        assert_eq!(s.try_send(i), Ok(()));
+    }
+    assert_eq!(s.try_send(5), Err(TrySendError::Full(5)));
+    assert_eq!(r.len(), 5);
+}
+
+#[test]
+fn refill() {
+    let (s, r) = unbounded();
+    let s = Throttled::new(s, 2, ms(200));
+
+    let start = Instant::now();
+    for i in 0..6 {
+        s.send(i).unwrap();
+    }
+    let elapsed = start.elapsed();
+
+    assert!(elapsed >= ms(800));
+    assert!(elapsed < ms(1200));
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [0, 1, 2, 3, 4, 5]);
+}
+
+#[test]
+fn send_timeout() {
+    let (s, r) = unbounded();
+    let s = Throttled::new(s, 1, ms(1000));
+
+    assert_eq!(s.send_timeout(1, ms(100)), Ok(()));
+
+    // The next token won't be added before the deadline, so there's no point in waiting.
+    let start = Instant::now();
+    assert_eq!(
+        s.send_timeout(2, ms(300)),
+        Err(SendTimeoutError::Timeout(2))
+    );
+    assert!(start.elapsed() < ms(100));
+
+    assert_eq!(s.send_timeout(3, ms(1500)), Ok(()));
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [1, 3]);
+}
+
+#[test]
+fn send_timeout_full() {
+    let (s, r) = bounded(1);
+    let s = Throttled::new(s, 2, ms(100));
+
+    assert_eq!(s.send(1), Ok(()));
+
+    // A token is available, but the channel stays full until the deadline.
+    let start = Instant::now();
+    assert_eq!(
+        s.send_timeout(2, ms(300)),
+        Err(SendTimeoutError::Timeout(2))
+    );
+    assert!(start.elapsed() >= ms(300));
+    assert_eq!(r.try_iter().collect::<Vec<_>>(), [1]);
+}
+
+#[test]
+fn full_channel_returns_token() {
+    let (s, r) = bounded(1);
+    let s = Throttled::new(s, 2, ms(1000));
+
+    assert_eq!(s.try_send(1), Ok(()));
+    assert_eq!(s.try_send(2), Err(TrySendError::Full(2)));
+    assert_eq!(
+        s.send_timeout(3, ms(100)),
+        Err(SendTimeoutError::Timeout(3))
+    );
+
+    // The failed sends didn't use up the remaining token.
+    assert_eq!(r.recv(), Ok(1));
+    assert_eq!(s.try_send(4), Ok(()));
+    assert_eq!(r.recv(), Ok(4));
+}
+
+#[test]
+fn disconnected() {
+    let (s, r) = unbounded();
+    let s = Throttled::new(s, 1, ms(1000));
+
+    drop(r);
+    assert_eq!(s.send(1), Err(SendError(1)));
+    assert_eq!(s.try_send(2), Err(TrySendError::Disconnected(2)));
+}
+
+#[test]
+fn disconnected_without_tokens() {
+    let (s, r) = unbounded();
+    let s = Throttled::new(s, 1, ms(1000));
+
+    assert_eq!(s.send(1), Ok(()));
+    drop(r);
+
+    let start = Instant::now();
+    assert_eq!(s.send(2), Err(SendError(2)));
+    assert_eq!(
+        s.send_timeout(3, ms(100)),
+        Err(SendTimeoutError::Disconnected(3))
+    );
+    assert!(start.elapsed() < ms(500));
+}
+
+#[test]
+fn disconnect_wakes_up() {
+    let (s, r) = unbounded();
+    let s = Throttled::new(s, 1, ms(2000));
+
+    assert_eq!(s.send(1), Ok(()));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let start = Instant::now();
+            assert_eq!(s.send(2), Err(SendError(2)));
+            assert!(start.elapsed() < ms(1000));
+        });
+        scope.spawn(move |_| {
+            thread::sleep(ms(300));
+            drop(r);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn select() {
+    let (s1, r1) = unbounded();
+    let (s2, r2) = bounded(1);
+    let s1 = Throttled::new(s1, 1, ms(300));
+
+    assert_eq!(s1.send(1), Ok(()));
+    assert_eq!(s2.send(()), Ok(()));
+
+    // Neither operation is ready until the next token is added.
+    let start = Instant::now();
+    select! {
+        send(s1.as_sender(), 2) -> res => assert_eq!(res, Ok(())),
+        send(s2, ()) -> _ => panic!(),
+    }
+    assert!(start.elapsed() >= ms(200));
+    assert_eq!(r1.try_iter().collect::<Vec<_>>(), [1, 2]);
+    assert_eq!(r2.len(), 1);
+}
+
+#[test]
+fn zero_capacity() {
+    let (s, r) = bounded(0);
+    let s = Throttled::new(s, 1, ms(300));
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            // A waiting receiver must not take a message before a token is added.
+            let start = Instant::now();
+            s.send(1).unwrap();
+            s.send(2).unwrap();
+            assert!(start.elapsed() >= ms(200));
+        });
+
+        assert_eq!(r.recv(), Ok(1));
+        assert_eq!(r.recv(), Ok(2));
+    })
+    .unwrap();
+}
+
+#[test]
+fn clones_share_bucket() {
+    let (s, r) = unbounded();
+    let s1 = Throttled::new(s, 1, ms(1000));
+    let s2 = s1.clone();
+
+    assert_eq!(s1.try_send(1), Ok(()));
+    assert_eq!(s2.try_send(2), Err(TrySendError::Full(2)));
+    assert_eq!(r.len(), 1);
+}
+
+#[test]
+fn mpsc() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 10;
+
+    let (s, r) = unbounded();
+    let s = Throttled::new(s, 1, ms(10));
+
+    let start = Instant::now();
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    s.send(i).unwrap();
+                    thread::yield_now();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert!(start.elapsed() >= ms(10 * (THREADS * COUNT - 1) as u64));
+    assert_eq!(r.len(), THREADS * COUNT);
+}
@@ -0,0 +1,165 @@
+//! Tests for the timer wheel.
+
+use std::collections::HashSet;
+use std::time::{Duration, Instant};
+
+use crossbeam_channel::{timer_wheel, RecvTimeoutError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+fn ms(ms: u64) -> Duration {
+    Duration::from_millis(ms)
+}
+
+#[test]
+fn smoke() {
+    let (timers, r) = timer_wheel(ms(1));
+    let start = Instant::now();
+
+    let token = timers.schedule(ms(50));
+    assert_eq!(timers.len(), 1);
+    assert_eq!(r.try_recv(), Err(TryRecvError::Empty));
+
+    assert_eq!(r.recv(), Ok(token));
+    assert!(start.elapsed() >= ms(50));
+    assert!(timers.is_empty());
+}
+
+#[test]
+#[should_panic(expected = "tick must be positive")]
+fn zero_tick() {
+    timer_wheel(Duration::from_secs(0));
+}
+
+#[test]
+fn order() {
+    let (timers, r) = timer_wheel(ms(1));
+
+    let tokens: Vec<_> = [50, 10, 40, 20, 30]
+        .iter()
+        .map(|&t| (t, timers.schedule(ms(t))))
+        .collect();
+
+    let mut expected = tokens.clone();
+    expected.sort_by_key(|&(t, _)| t);
+    for (_, token) in expected {
+        assert_eq!(r.recv(), Ok(token));
+    }
+}
+
+#[test]
+fn past_deadline() {
+    let (timers, r) = timer_wheel(ms(10));
+    let token = timers.schedule_at(Instant::now() - ms(100));
+    assert_eq!(r.recv_timeout(ms(1000)), Ok(token));
+}
+
+#[test]
+fn cancel() {
+    let (timers, r) = timer_wheel(ms(1));
+
+    let a = timers.schedule(ms(50));
+    let b = timers.schedule(ms(100));
+    assert!(timers.cancel(a));
+    assert!(!timers.cancel(a));
+    assert_eq!(timers.len(), 1);
+
+    assert_eq!(r.recv(), Ok(b));
+    assert!(!timers.cancel(b));
+    assert_eq!(r.recv_timeout(ms(100)), Err(RecvTimeoutError::Timeout));
+}
+
+#[test]
+fn unique_tokens() {
+    let (timers, _r) = timer_wheel(ms(1));
+    let mut seen = HashSet::new();
+
+    // Cancelled entries are reused, but their tokens aren't.
+    for _ in 0..100 {
+        let token = timers.schedule(Duration::from_secs(60));
+        assert!(seen.insert(token));
+        assert!(timers.cancel(token));
+    }
+}
+
+#[test]
+fn long_timeouts() {
+    let (timers, r) = timer_wheel(ms(1));
+
+    // These land in higher levels of the wheel and have to move down before they expire.
+    let far = timers.schedule(Duration::from_secs(3600));
+    let farther = timers.schedule(Duration::from_secs(1_000_000_000));
+    let near = timers.schedule(ms(200));
+
+    assert_eq!(r.recv(), Ok(near));
+    assert_eq!(timers.len(), 2);
+    assert!(timers.cancel(far));
+    assert!(timers.cancel(farther));
+}
+
+#[test]
+fn many() {
+    #[cfg(miri)]
+    const COUNT: usize = 50;
+    #[cfg(not(miri))]
+    const COUNT: usize = 100_000;
+
+    let (timers, r) = timer_wheel(ms(1));
+    let start = Instant::now();
+
+    let mut pending = HashSet::new();
+    for i in 0..COUNT {
+        let token = timers.schedule(ms(100 + (i % 300) as u64));
+        if i % 2 == 0 {
+            pending.insert(token);
+        } else {
+            assert!(timers.cancel(token));
+        }
+    }
+
+    while !pending.is_empty() {
+        let token = r.recv().unwrap();
+        assert!(pending.remove(&token));
+    }
+    assert!(start.elapsed() >= ms(100));
+    assert!(timers.is_empty());
+}
+
+#[test]
+fn concurrent() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 1000;
+
+    let (timers, r) = timer_wheel(ms(1));
+
+    scope(|scope| {
+        for t in 0..THREADS {
+            let timers = timers.clone();
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    timers.schedule(ms(((t * COUNT + i) % 50) as u64));
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    let received: HashSet<_> = r.iter().take(THREADS * COUNT).collect();
+    assert_eq!(received.len(), THREADS * COUNT);
+}
+
+#[test]
+fn disconnect() {
+    let (timers, r) = timer_wheel(ms(1));
+    let timers2 = timers.clone();
+
+    let token = timers.schedule(ms(10));
+    timers.schedule(Duration::from_secs(60));
+    drop(timers);
+
+    // The wheel keeps running while a handle is alive.
+    assert_eq!(r.recv(), Ok(token));
+
+    // Pending timers are discarded along with the last handle.
+    drop(timers2);
+    assert!(r.recv().is_err());
+}
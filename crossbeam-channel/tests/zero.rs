@@ -426,6 +426,85 @@ fn drops() {
     }
 }
 
+#[test]
+fn select_drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let mut rng = thread_rng();
+
+    for _ in 0..100 {
+        let steps = rng.gen_range(0..3_000);
+
+        DROPS.store(0, Ordering::SeqCst);
+        let (s, r) = bounded::<DropCounter>(0);
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                for _ in 0..steps {
+                    select! {
+                        recv(r) -> msg => drop(msg.unwrap()),
+                    }
+                }
+            });
+
+            scope.spawn(|_| {
+                for _ in 0..steps {
+                    select! {
+                        send(s, DropCounter) -> res => res.unwrap(),
+                    }
+                }
+            });
+        })
+        .unwrap();
+
+        assert_eq!(DROPS.load(Ordering::SeqCst), steps);
+        drop(s);
+        drop(r);
+        assert_eq!(DROPS.load(Ordering::SeqCst), steps);
+    }
+}
+
+#[test]
+fn shrink_to_fit() {
+    #[cfg(miri)]
+    const COUNT: usize = 50;
+    #[cfg(not(miri))]
+    const COUNT: usize = 10_000;
+
+    let (s, r) = bounded::<usize>(0);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                select! {
+                    send(s, i) -> res => res.unwrap(),
+                }
+            }
+        });
+
+        for i in 0..COUNT {
+            select! {
+                recv(r) -> msg => assert_eq!(msg, Ok(i)),
+            }
+            if i % 100 == 0 {
+                r.shrink_to_fit();
+            }
+        }
+    })
+    .unwrap();
+
+    r.shrink_to_fit();
+}
+
 #[test]
 fn fairness() {
     const COUNT: usize = 10_000;
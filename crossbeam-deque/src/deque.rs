@@ -114,6 +114,76 @@ struct Inner<T> {
 
     /// The underlying buffer.
     buffer: CachePadded<Atomic<Buffer<T>>>,
+
+    /// Counters for operations on the queue.
+    #[cfg(feature = "stats")]
+    counters: CachePadded<Counters>,
+}
+
+/// Counters for operations on a worker queue, kept when the `stats` feature is enabled.
+#[cfg(feature = "stats")]
+#[derive(Default)]
+struct Counters {
+    pushes: AtomicUsize,
+    pops: AtomicUsize,
+    steals: AtomicUsize,
+    failed_steals: AtomicUsize,
+    resizes: AtomicUsize,
+}
+
+// Increments a counter in `Inner::counters` if the `stats` feature is enabled.
+#[cfg(feature = "stats")]
+macro_rules! count {
+    ($inner:expr, $counter:ident) => {
+        $inner.counters.$counter.fetch_add(1, Ordering::Relaxed)
+    };
+}
+
+#[cfg(not(feature = "stats"))]
+macro_rules! count {
+    ($inner:expr, $counter:ident) => {
+        ()
+    };
+}
+
+/// Statistics of operations on a worker queue, returned by [`Worker::stats`] and
+/// [`Stealer::stats`].
+///
+/// Counters are updated with relaxed atomic operations, so a snapshot taken while other threads
+/// use the queue may be slightly out of date.
+#[cfg(feature = "stats")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct WorkerStats {
+    /// The number of tasks pushed by the worker.
+    pub pushes: usize,
+
+    /// The number of tasks popped by the worker.
+    pub pops: usize,
+
+    /// The number of successful steal operations by stealers of the queue.
+    ///
+    /// A batch steal counts as a single steal.
+    pub steals: usize,
+
+    /// The number of steal operations that found the queue empty or had to be retried.
+    pub failed_steals: usize,
+
+    /// The number of times the buffer was grown or shrunk.
+    pub resizes: usize,
+}
+
+#[cfg(feature = "stats")]
+impl<T> Inner<T> {
+    /// Takes a snapshot of the counters.
+    fn stats(&self) -> WorkerStats {
+        WorkerStats {
+            pushes: self.counters.pushes.load(Ordering::Relaxed),
+            pops: self.counters.pops.load(Ordering::Relaxed),
+            steals: self.counters.steals.load(Ordering::Relaxed),
+            failed_steals: self.counters.failed_steals.load(Ordering::Relaxed),
+            resizes: self.counters.resizes.load(Ordering::Relaxed),
+        }
+    }
 }
 
 impl<T> Drop for Inner<T> {
@@ -198,6 +268,9 @@ pub struct Worker<T> {
     /// The flavor of the queue.
     flavor: Flavor,
 
+    /// The maximum number of tasks in a bounded queue, or `None` if the queue is unbounded.
+    bound: Option<usize>,
+
     /// Indicates that the worker cannot be shared among threads.
     _marker: PhantomData<*mut ()>, // !Send + !Sync
 }
@@ -223,12 +296,15 @@ impl<T> Worker<T> {
             front: AtomicIsize::new(0),
             back: AtomicIsize::new(0),
             buffer: CachePadded::new(Atomic::new(buffer)),
+            #[cfg(feature = "stats")]
+            counters: CachePadded::new(Counters::default()),
         }));
 
         Worker {
             inner,
             buffer: Cell::new(buffer),
             flavor: Flavor::Fifo,
+            bound: None,
             _marker: PhantomData,
         }
     }
@@ -251,16 +327,118 @@ impl<T> Worker<T> {
             front: AtomicIsize::new(0),
             back: AtomicIsize::new(0),
             buffer: CachePadded::new(Atomic::new(buffer)),
+            #[cfg(feature = "stats")]
+            counters: CachePadded::new(Counters::default()),
         }));
 
         Worker {
             inner,
             buffer: Cell::new(buffer),
             flavor: Flavor::Lifo,
+            bound: None,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates a bounded FIFO worker queue that holds at most `cap` tasks.
+    ///
+    /// The buffer of a bounded queue is allocated up front and never shrinks. Use `try_push` or
+    /// `push_or_overflow` to push tasks into it without exceeding the capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cap` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::Worker;
+    ///
+    /// let w = Worker::<i32>::new_fifo_bounded(256);
+    /// ```
+    pub fn new_fifo_bounded(cap: usize) -> Worker<T> {
+        Worker::bounded(Flavor::Fifo, cap)
+    }
+
+    /// Creates a bounded LIFO worker queue that holds at most `cap` tasks.
+    ///
+    /// The buffer of a bounded queue is allocated up front and never shrinks. Use `try_push` or
+    /// `push_or_overflow` to push tasks into it without exceeding the capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `cap` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::Worker;
+    ///
+    /// let w = Worker::<i32>::new_lifo_bounded(256);
+    /// ```
+    pub fn new_lifo_bounded(cap: usize) -> Worker<T> {
+        Worker::bounded(Flavor::Lifo, cap)
+    }
+
+    /// Creates a bounded worker queue of the given flavor.
+    fn bounded(flavor: Flavor, cap: usize) -> Worker<T> {
+        assert!(cap > 0, "capacity must be positive");
+
+        let buffer = Buffer::alloc(cap.next_power_of_two());
+
+        let inner = Arc::new(CachePadded::new(Inner {
+            front: AtomicIsize::new(0),
+            back: AtomicIsize::new(0),
+            buffer: CachePadded::new(Atomic::new(buffer)),
+            #[cfg(feature = "stats")]
+            counters: CachePadded::new(Counters::default()),
+        }));
+
+        Worker {
+            inner,
+            buffer: Cell::new(buffer),
+            flavor,
+            bound: Some(cap),
             _marker: PhantomData,
         }
     }
 
+    /// Returns the capacity of the queue if it is bounded, or `None` if it is unbounded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::Worker;
+    ///
+    /// assert_eq!(Worker::<i32>::new_fifo().capacity(), None);
+    /// assert_eq!(Worker::<i32>::new_fifo_bounded(100).capacity(), Some(100));
+    /// ```
+    pub fn capacity(&self) -> Option<usize> {
+        self.bound
+    }
+
+    /// Returns statistics of operations on this queue.
+    ///
+    /// This method is only available with the `stats` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::Worker;
+    ///
+    /// let w = Worker::new_lifo();
+    /// w.push(1);
+    /// w.pop();
+    ///
+    /// let stats = w.stats();
+    /// assert_eq!(stats.pushes, 1);
+    /// assert_eq!(stats.pops, 1);
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> WorkerStats {
+        self.inner.stats()
+    }
+
     /// Creates a stealer for this queue.
     ///
     /// The returned stealer can be shared among threads and cloned.
@@ -283,6 +461,8 @@ impl<T> Worker<T> {
     /// Resizes the internal buffer to the new capacity of `new_cap`.
     #[cold]
     unsafe fn resize(&self, new_cap: usize) {
+        count!(self.inner, resizes);
+
         // Load the back index, front index, and buffer.
         let b = self.inner.back.load(Ordering::Relaxed);
         let f = self.inner.front.load(Ordering::Relaxed);
@@ -343,8 +523,25 @@ impl<T> Worker<T> {
         }
     }
 
+    /// Caps `batch_size` so that a batch of that size still fits into a bounded queue.
+    fn clamp_to_bound(&self, batch_size: usize) -> usize {
+        match self.bound {
+            None => batch_size,
+            Some(bound) => {
+                // Stealers can only make the queue shorter, so this length is an upper bound.
+                let b = self.inner.back.load(Ordering::Relaxed);
+                let f = self.inner.front.load(Ordering::SeqCst);
+                let len = b.wrapping_sub(f).max(0) as usize;
+
+                cmp::min(batch_size, bound.saturating_sub(len))
+            }
+        }
+    }
+
     /// Returns `true` if the queue is empty.
     ///
+    /// Stealers may take tasks concurrently, so the result is only a snapshot.
+    ///
     /// ```
     /// use crossbeam_deque::Worker;
     ///
@@ -362,6 +559,8 @@ impl<T> Worker<T> {
 
     /// Returns the number of tasks in the deque.
     ///
+    /// Stealers may take tasks concurrently, so the result is only a snapshot.
+    ///
     /// ```
     /// use crossbeam_deque::Worker;
     ///
@@ -381,6 +580,13 @@ impl<T> Worker<T> {
 
     /// Pushes a task into the queue.
     ///
+    /// This method never fails. If the queue is bounded and full, its buffer grows and the queue
+    /// holds more tasks than its capacity until enough of them are popped or stolen. Use
+    /// [`try_push`] or [`push_or_overflow`] to respect the bound instead.
+    ///
+    /// [`try_push`]: Worker::try_push
+    /// [`push_or_overflow`]: Worker::push_or_overflow
+    ///
     /// # Examples
     ///
     /// ```
@@ -391,15 +597,50 @@ impl<T> Worker<T> {
     /// w.push(2);
     /// ```
     pub fn push(&self, task: T) {
-        // Load the back index, front index, and buffer.
         let b = self.inner.back.load(Ordering::Relaxed);
         let f = self.inner.front.load(Ordering::Acquire);
+        self.push_at(task, b, f);
+    }
+
+    /// Attempts to push a task into the queue.
+    ///
+    /// If the queue is bounded and full, the task is returned back as an error. Unbounded queues
+    /// always accept the task.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::Worker;
+    ///
+    /// let w = Worker::new_fifo_bounded(1);
+    ///
+    /// assert_eq!(w.try_push(1), Ok(()));
+    /// assert_eq!(w.try_push(2), Err(2));
+    /// ```
+    pub fn try_push(&self, task: T) -> Result<(), T> {
+        // Load the back index and front index.
+        let b = self.inner.back.load(Ordering::Relaxed);
+        let f = self.inner.front.load(Ordering::Acquire);
+
+        // Is a bounded queue full?
+        if let Some(bound) = self.bound {
+            if b.wrapping_sub(f) >= bound as isize {
+                return Err(task);
+            }
+        }
+
+        self.push_at(task, b, f);
+        Ok(())
+    }
+
+    /// Pushes a task at back index `b`, given front index `f` loaded after it.
+    fn push_at(&self, task: T, b: isize, f: isize) {
         let mut buffer = self.buffer.get();
 
         // Calculate the length of the queue.
         let len = b.wrapping_sub(f);
 
-        // Is the queue full?
+        // Is the buffer full?
         if len >= buffer.cap as isize {
             // Yes. Grow the underlying buffer.
             unsafe {
@@ -420,6 +661,61 @@ impl<T> Worker<T> {
         // This ordering could be `Relaxed`, but then thread sanitizer would falsely report data
         // races because it doesn't understand fences.
         self.inner.back.store(b.wrapping_add(1), Ordering::Release);
+        count!(self.inner, pushes);
+    }
+
+    /// Pushes a task into the queue, overflowing into `injector` if the queue is bounded and full.
+    ///
+    /// On overflow, the older half of the tasks is moved into `injector` to make room for the new
+    /// task, so that the cost of overflowing is amortized over many pushes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{Injector, Steal, Worker};
+    ///
+    /// let q = Injector::new();
+    /// let w = Worker::new_fifo_bounded(4);
+    ///
+    /// for i in 1..=5 {
+    ///     w.push_or_overflow(i, &q);
+    /// }
+    ///
+    /// assert_eq!(w.len(), 3);
+    /// assert_eq!(q.len(), 2);
+    /// assert_eq!(q.steal(), Steal::Success(1));
+    /// assert_eq!(w.pop(), Some(3));
+    /// ```
+    pub fn push_or_overflow(&self, task: T, injector: &Injector<T>) {
+        if let Err(task) = self.try_push(task) {
+            self.overflow(task, injector);
+        }
+    }
+
+    /// Moves the older half of the tasks and then `task` into `injector`, or pushes `task` locally
+    /// if stealers have made room in the meantime.
+    #[cold]
+    fn overflow(&self, task: T, injector: &Injector<T>) {
+        let bound = self.bound.unwrap_or(0);
+
+        // Take tasks from the front the same way stealers do, so that tasks are moved in the
+        // order they would have been stolen.
+        let stealer = self.stealer();
+        let mut moved = 0;
+        while moved < (bound + 1) / 2 {
+            match stealer.steal_task() {
+                Steal::Success(t) => {
+                    injector.push(t);
+                    moved += 1;
+                }
+                Steal::Retry => {}
+                Steal::Empty => break,
+            }
+        }
+
+        if let Err(task) = self.try_push(task) {
+            injector.push(task);
+        }
     }
 
     /// Pops a task from the queue.
@@ -467,8 +763,13 @@ impl<T> Worker<T> {
                     let buffer = self.buffer.get();
                     let task = buffer.read(f);
 
+                    count!(self.inner, pops);
+
                     // Shrink the buffer if `len - 1` is less than one fourth of the capacity.
-                    if buffer.cap > MIN_CAP && len <= buffer.cap as isize / 4 {
+                    if self.bound.is_none()
+                        && buffer.cap > MIN_CAP
+                        && len <= buffer.cap as isize / 4
+                    {
                         self.resize(buffer.cap / 2);
                     }
 
@@ -515,13 +816,20 @@ impl<T> Worker<T> {
                         {
                             // Failed. We didn't pop anything.
                             mem::forget(task.take());
+                        } else {
+                            count!(self.inner, pops);
                         }
 
                         // Restore the back index to the original task.
                         self.inner.back.store(b.wrapping_add(1), Ordering::Relaxed);
                     } else {
+                        count!(self.inner, pops);
+
                         // Shrink the buffer if `len` is less than one fourth of the capacity.
-                        if buffer.cap > MIN_CAP && len < buffer.cap as isize / 4 {
+                        if self.bound.is_none()
+                            && buffer.cap > MIN_CAP
+                            && len < buffer.cap as isize / 4
+                        {
                             unsafe {
                                 self.resize(buffer.cap / 2);
                             }
@@ -575,6 +883,9 @@ unsafe impl<T: Send> Sync for Stealer<T> {}
 impl<T> Stealer<T> {
     /// Returns `true` if the queue is empty.
     ///
+    /// The worker and other stealers may change the queue concurrently, so the result is only an
+    /// approximation that is suitable as a load signal.
+    ///
     /// ```
     /// use crossbeam_deque::Worker;
     ///
@@ -592,6 +903,54 @@ impl<T> Stealer<T> {
         b.wrapping_sub(f) <= 0
     }
 
+    /// Returns the number of tasks in the queue.
+    ///
+    /// The worker and other stealers may change the queue concurrently, so the result is only an
+    /// approximation that is suitable as a load signal.
+    ///
+    /// ```
+    /// use crossbeam_deque::Worker;
+    ///
+    /// let w = Worker::new_lifo();
+    /// let s = w.stealer();
+    ///
+    /// assert_eq!(s.len(), 0);
+    /// w.push(1);
+    /// w.push(2);
+    /// assert_eq!(s.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        let f = self.inner.front.load(Ordering::Acquire);
+        atomic::fence(Ordering::SeqCst);
+        let b = self.inner.back.load(Ordering::Acquire);
+        b.wrapping_sub(f).max(0) as usize
+    }
+
+    /// Returns statistics of operations on the queue.
+    ///
+    /// This method is only available with the `stats` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::Worker;
+    ///
+    /// let w = Worker::new_lifo();
+    /// let s = w.stealer();
+    ///
+    /// w.push(1);
+    /// let _ = s.steal();
+    /// let _ = s.steal();
+    ///
+    /// let stats = s.stats();
+    /// assert_eq!(stats.steals, 1);
+    /// assert_eq!(stats.failed_steals, 1);
+    /// ```
+    #[cfg(feature = "stats")]
+    pub fn stats(&self) -> WorkerStats {
+        self.inner.stats()
+    }
+
     /// Steals a task from the queue.
     ///
     /// # Examples
@@ -608,6 +967,11 @@ impl<T> Stealer<T> {
     /// assert_eq!(s.steal(), Steal::Success(2));
     /// ```
     pub fn steal(&self) -> Steal<T> {
+        self.record(self.steal_task())
+    }
+
+    /// Steals a task from the queue without recording it in the statistics.
+    fn steal_task(&self) -> Steal<T> {
         // Load the front index.
         let f = self.inner.front.load(Ordering::Acquire);
 
@@ -674,6 +1038,48 @@ impl<T> Stealer<T> {
     /// assert_eq!(w2.pop(), Some(2));
     /// ```
     pub fn steal_batch(&self, dest: &Worker<T>) -> Steal<()> {
+        self.record(self.steal_into(dest, |len| cmp::min((len + 1) / 2, MAX_BATCH)))
+    }
+
+    /// Steals up to `limit` tasks and pushes them into another worker.
+    ///
+    /// Unlike `steal_batch`, this method does not stop at around half of the tasks in the
+    /// queue, which lets a thief size the steal to its own remaining capacity. Fewer than `limit`
+    /// tasks may be stolen if the queue is shorter or if the steal races with other threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{Steal, Worker};
+    ///
+    /// let w1 = Worker::new_fifo();
+    /// for i in 1..=4 {
+    ///     w1.push(i);
+    /// }
+    ///
+    /// let s = w1.stealer();
+    /// let w2 = Worker::new_fifo();
+    ///
+    /// assert_eq!(s.steal_many(&w2, 3), Steal::Success(()));
+    /// assert_eq!(w2.len(), 3);
+    /// assert_eq!(w2.pop(), Some(1));
+    /// assert_eq!(s.steal(), Steal::Success(4));
+    /// ```
+    pub fn steal_many(&self, dest: &Worker<T>, limit: usize) -> Steal<()> {
+        assert!(limit > 0, "limit must be greater than zero");
+        self.record(self.steal_into(dest, |len| cmp::min(len, limit)))
+    }
+
+    /// Steals a batch of tasks into `dest`, where `batch_size` picks how many tasks to steal given
+    /// the current length of the queue.
+    fn steal_into<F>(&self, dest: &Worker<T>, batch_size: F) -> Steal<()>
+    where
+        F: FnOnce(usize) -> usize,
+    {
         if Arc::ptr_eq(&self.inner, &dest.inner) {
             if dest.is_empty() {
                 return Steal::Empty;
@@ -706,7 +1112,11 @@ impl<T> Stealer<T> {
         }
 
         // Reserve capacity for the stolen batch.
-        let batch_size = cmp::min((len as usize + 1) / 2, MAX_BATCH);
+        let batch_size = dest.clamp_to_bound(batch_size(len as usize));
+        if batch_size == 0 {
+            // The destination queue is bounded and full, so nothing can be stolen into it.
+            return Steal::Empty;
+        }
         dest.reserve(batch_size);
         let mut batch_size = batch_size as isize;
 
@@ -859,6 +1269,12 @@ impl<T> Stealer<T> {
     /// assert_eq!(w2.pop(), Some(2));
     /// ```
     pub fn steal_batch_and_pop(&self, dest: &Worker<T>) -> Steal<T> {
+        self.record(self.steal_into_and_pop(dest))
+    }
+
+    /// Steals a batch of tasks into `dest` and pops a task from it, without recording it in the
+    /// statistics.
+    fn steal_into_and_pop(&self, dest: &Worker<T>) -> Steal<T> {
         if Arc::ptr_eq(&self.inner, &dest.inner) {
             match dest.pop() {
                 None => return Steal::Empty,
@@ -890,7 +1306,7 @@ impl<T> Stealer<T> {
         }
 
         // Reserve capacity for the stolen batch.
-        let batch_size = cmp::min((len as usize - 1) / 2, MAX_BATCH - 1);
+        let batch_size = dest.clamp_to_bound(cmp::min((len as usize - 1) / 2, MAX_BATCH - 1));
         dest.reserve(batch_size);
         let mut batch_size = batch_size as isize;
 
@@ -1032,6 +1448,15 @@ impl<T> Stealer<T> {
         // Return with success.
         Steal::Success(task)
     }
+
+    /// Records the outcome of a steal operation in the statistics.
+    fn record<R>(&self, steal: Steal<R>) -> Steal<R> {
+        match steal {
+            Steal::Success(_) => count!(self.inner, steals),
+            Steal::Empty | Steal::Retry => count!(self.inner, failed_steals),
+        };
+        steal
+    }
 }
 
 impl<T> Clone for Stealer<T> {
@@ -1367,7 +1792,9 @@ impl<T> Injector<T> {
 
             // Destroy the block if we've reached the end, or if another thread wanted to destroy
             // but couldn't because we were busy reading from the slot.
-            if (offset + 1 == BLOCK_CAP) || (slot.state.fetch_or(READ, Ordering::AcqRel) & DESTROY != 0) {
+            if (offset + 1 == BLOCK_CAP)
+                || (slot.state.fetch_or(READ, Ordering::AcqRel) & DESTROY != 0)
+            {
                 Block::destroy(block, offset);
             }
 
@@ -1445,6 +1872,13 @@ impl<T> Injector<T> {
             advance = (BLOCK_CAP - offset).min(MAX_BATCH);
         }
 
+        // Don't steal more tasks than a bounded destination queue can hold.
+        let advance = dest.clamp_to_bound(advance);
+        if advance == 0 {
+            // The destination queue is bounded and full, so nothing can be stolen into it.
+            return Steal::Empty;
+        }
+
         new_head += advance << SHIFT;
         let new_offset = offset + advance;
 
@@ -1603,6 +2037,9 @@ impl<T> Injector<T> {
             advance = (BLOCK_CAP - offset).min(MAX_BATCH + 1);
         }
 
+        // Don't steal more tasks than a bounded destination queue can hold, plus the popped one.
+        let advance = dest.clamp_to_bound(advance - 1) + 1;
+
         new_head += advance << SHIFT;
         let new_offset = offset + advance;
 
@@ -26,6 +26,9 @@
 //! Method [`stealer()`] creates a [`Stealer`] that may be shared among threads and can only steal
 //! tasks from its [`Worker`]. Tasks are stolen from the end opposite to where they get pushed.
 //!
+//! [`PriorityWorker`] combines a high-priority and a normal worker queue, so that urgent tasks are
+//! popped first without having to be stolen from a separate queue.
+//!
 //! # Stealing
 //!
 //! Steal operations come in three flavors:
@@ -38,6 +41,9 @@
 //! In contrast to push and pop operations, stealing can spuriously fail with [`Steal::Retry`], in
 //! which case the steal operation needs to be retried.
 //!
+//! [`Stealers`] is a set of victims that handles the retrying, and picks the victim to steal from
+//! with one of several [strategies].
+//!
 //! # Examples
 //!
 //! Suppose a thread in a work-stealing scheduler is idle and looking for the next task to run. To
@@ -81,6 +87,7 @@
 //! [`steal()`]: Stealer::steal
 //! [`steal_batch()`]: Stealer::steal_batch
 //! [`steal_batch_and_pop()`]: Stealer::steal_batch_and_pop
+//! [strategies]: StealStrategy
 
 #![doc(test(
     no_crate_inject,
@@ -105,6 +112,12 @@ cfg_if! {
         use crossbeam_utils as utils;
 
         mod deque;
+        mod priority;
+        mod stealers;
         pub use crate::deque::{Injector, Steal, Stealer, Worker};
+        pub use crate::priority::{PriorityStealer, PriorityWorker, StealPolicy};
+        pub use crate::stealers::{StealStrategy, Stealers};
+        #[cfg(feature = "stats")]
+        pub use crate::deque::WorkerStats;
     }
 }
@@ -0,0 +1,386 @@
+use std::fmt;
+
+use crate::deque::{Steal, Stealer, Worker};
+
+/// Which lanes of a [`PriorityWorker`] stealers take tasks from, and in what order.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StealPolicy {
+    /// Steal from the high-priority lane first, and then from the normal lane.
+    HighFirst,
+
+    /// Steal from the normal lane first, and then from the high-priority lane.
+    NormalFirst,
+
+    /// Steal only from the normal lane, leaving high-priority tasks to the owner.
+    NormalOnly,
+}
+
+/// A worker queue with a high-priority lane and a normal lane.
+///
+/// Pops always take tasks from the high-priority lane before the normal lane, so latency-critical
+/// tasks can jump ahead of the backlog. Each lane is a FIFO or LIFO [`Worker`], and stealers take
+/// tasks from the lanes according to a [`StealPolicy`].
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_deque::{PriorityWorker, Steal, StealPolicy};
+///
+/// let w = PriorityWorker::new_fifo(StealPolicy::NormalOnly);
+/// let s = w.stealer();
+///
+/// w.push(1);
+/// w.push(2);
+/// w.push_high(3);
+///
+/// assert_eq!(s.steal(), Steal::Success(1));
+/// assert_eq!(w.pop(), Some(3));
+/// assert_eq!(w.pop(), Some(2));
+/// ```
+pub struct PriorityWorker<T> {
+    /// The high-priority lane.
+    high: Worker<T>,
+
+    /// The normal lane.
+    normal: Worker<T>,
+
+    /// The steal policy handed to stealers.
+    policy: StealPolicy,
+}
+
+impl<T> PriorityWorker<T> {
+    /// Creates a priority worker queue whose lanes are FIFO queues.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityWorker, StealPolicy};
+    ///
+    /// let w = PriorityWorker::<i32>::new_fifo(StealPolicy::HighFirst);
+    /// ```
+    pub fn new_fifo(policy: StealPolicy) -> PriorityWorker<T> {
+        PriorityWorker {
+            high: Worker::new_fifo(),
+            normal: Worker::new_fifo(),
+            policy,
+        }
+    }
+
+    /// Creates a priority worker queue whose lanes are LIFO queues.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityWorker, StealPolicy};
+    ///
+    /// let w = PriorityWorker::<i32>::new_lifo(StealPolicy::HighFirst);
+    /// ```
+    pub fn new_lifo(policy: StealPolicy) -> PriorityWorker<T> {
+        PriorityWorker {
+            high: Worker::new_lifo(),
+            normal: Worker::new_lifo(),
+            policy,
+        }
+    }
+
+    /// Creates a stealer for this queue.
+    ///
+    /// The returned stealer can be shared among threads and cloned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityWorker, StealPolicy};
+    ///
+    /// let w = PriorityWorker::<i32>::new_lifo(StealPolicy::HighFirst);
+    /// let s = w.stealer();
+    /// ```
+    pub fn stealer(&self) -> PriorityStealer<T> {
+        PriorityStealer {
+            high: self.high.stealer(),
+            normal: self.normal.stealer(),
+            policy: self.policy,
+        }
+    }
+
+    /// Returns the steal policy of this queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityWorker, StealPolicy};
+    ///
+    /// let w = PriorityWorker::<i32>::new_lifo(StealPolicy::NormalFirst);
+    /// assert_eq!(w.steal_policy(), StealPolicy::NormalFirst);
+    /// ```
+    pub fn steal_policy(&self) -> StealPolicy {
+        self.policy
+    }
+
+    /// Returns `true` if both lanes are empty.
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityWorker, StealPolicy};
+    ///
+    /// let w = PriorityWorker::new_lifo(StealPolicy::HighFirst);
+    ///
+    /// assert!(w.is_empty());
+    /// w.push_high(1);
+    /// assert!(!w.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.high.is_empty() && self.normal.is_empty()
+    }
+
+    /// Returns the number of tasks in both lanes.
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityWorker, StealPolicy};
+    ///
+    /// let w = PriorityWorker::new_lifo(StealPolicy::HighFirst);
+    ///
+    /// w.push(1);
+    /// w.push_high(2);
+    /// assert_eq!(w.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.high.len() + self.normal.len()
+    }
+
+    /// Pushes a task into the normal lane.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityWorker, StealPolicy};
+    ///
+    /// let w = PriorityWorker::new_lifo(StealPolicy::HighFirst);
+    /// w.push(1);
+    /// ```
+    pub fn push(&self, task: T) {
+        self.normal.push(task);
+    }
+
+    /// Pushes a task into the high-priority lane.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityWorker, StealPolicy};
+    ///
+    /// let w = PriorityWorker::new_lifo(StealPolicy::HighFirst);
+    /// w.push_high(1);
+    /// ```
+    pub fn push_high(&self, task: T) {
+        self.high.push(task);
+    }
+
+    /// Pops a task from the high-priority lane, or from the normal lane if that one is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityWorker, StealPolicy};
+    ///
+    /// let w = PriorityWorker::new_fifo(StealPolicy::HighFirst);
+    /// w.push(1);
+    /// w.push_high(2);
+    ///
+    /// assert_eq!(w.pop(), Some(2));
+    /// assert_eq!(w.pop(), Some(1));
+    /// assert_eq!(w.pop(), None);
+    /// ```
+    pub fn pop(&self) -> Option<T> {
+        self.high.pop().or_else(|| self.normal.pop())
+    }
+
+    /// Returns the high-priority lane if `high` is `true`, and the normal lane otherwise.
+    fn lane(&self, high: bool) -> &Worker<T> {
+        if high {
+            &self.high
+        } else {
+            &self.normal
+        }
+    }
+}
+
+impl<T> fmt::Debug for PriorityWorker<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("PriorityWorker { .. }")
+    }
+}
+
+/// A stealer handle of a priority worker queue.
+///
+/// Stealers can be shared among threads. Tasks are stolen from the lanes in the order given by
+/// the [`StealPolicy`] of the queue, and batches are moved into the matching lane of the
+/// destination.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_deque::{PriorityWorker, Steal, StealPolicy};
+///
+/// let w = PriorityWorker::new_lifo(StealPolicy::HighFirst);
+/// w.push(1);
+/// w.push_high(2);
+///
+/// let s = w.stealer();
+/// assert_eq!(s.steal(), Steal::Success(2));
+/// assert_eq!(s.steal(), Steal::Success(1));
+/// assert_eq!(s.steal(), Steal::Empty);
+/// ```
+pub struct PriorityStealer<T> {
+    /// A stealer of the high-priority lane.
+    high: Stealer<T>,
+
+    /// A stealer of the normal lane.
+    normal: Stealer<T>,
+
+    /// The order in which lanes are stolen from.
+    policy: StealPolicy,
+}
+
+impl<T> PriorityStealer<T> {
+    /// Returns `true` if all lanes this stealer takes tasks from are empty.
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityWorker, StealPolicy};
+    ///
+    /// let w = PriorityWorker::new_lifo(StealPolicy::NormalOnly);
+    /// let s = w.stealer();
+    ///
+    /// w.push_high(1);
+    /// assert!(s.is_empty());
+    /// w.push(2);
+    /// assert!(!s.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        match self.policy {
+            StealPolicy::HighFirst | StealPolicy::NormalFirst => {
+                self.high.is_empty() && self.normal.is_empty()
+            }
+            StealPolicy::NormalOnly => self.normal.is_empty(),
+        }
+    }
+
+    /// Returns the number of tasks in all lanes this stealer takes tasks from.
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityWorker, StealPolicy};
+    ///
+    /// let w = PriorityWorker::new_lifo(StealPolicy::HighFirst);
+    /// let s = w.stealer();
+    ///
+    /// w.push(1);
+    /// w.push_high(2);
+    /// assert_eq!(s.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        match self.policy {
+            StealPolicy::HighFirst | StealPolicy::NormalFirst => {
+                self.high.len() + self.normal.len()
+            }
+            StealPolicy::NormalOnly => self.normal.len(),
+        }
+    }
+
+    /// Steals a task from the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityWorker, Steal, StealPolicy};
+    ///
+    /// let w = PriorityWorker::new_lifo(StealPolicy::NormalFirst);
+    /// w.push(1);
+    /// w.push_high(2);
+    ///
+    /// let s = w.stealer();
+    /// assert_eq!(s.steal(), Steal::Success(1));
+    /// assert_eq!(s.steal(), Steal::Success(2));
+    /// ```
+    pub fn steal(&self) -> Steal<T> {
+        self.steal_lanes(|s, _| s.steal())
+    }
+
+    /// Steals a batch of tasks and pushes them into the matching lane of another priority worker.
+    ///
+    /// Only one lane is stolen from at a time. How many tasks exactly will be stolen is not
+    /// specified, as with [`Stealer::steal_batch`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityWorker, StealPolicy};
+    ///
+    /// let w1 = PriorityWorker::new_fifo(StealPolicy::HighFirst);
+    /// w1.push(1);
+    /// w1.push_high(2);
+    /// w1.push_high(3);
+    ///
+    /// let s = w1.stealer();
+    /// let w2 = PriorityWorker::new_fifo(StealPolicy::HighFirst);
+    ///
+    /// let _ = s.steal_batch(&w2);
+    /// assert_eq!(w2.pop(), Some(2));
+    /// ```
+    pub fn steal_batch(&self, dest: &PriorityWorker<T>) -> Steal<()> {
+        self.steal_lanes(|s, high| s.steal_batch(dest.lane(high)))
+    }
+
+    /// Steals a batch of tasks, pushes them into the matching lane of another priority worker, and
+    /// pops a task from that lane.
+    ///
+    /// Only one lane is stolen from at a time. How many tasks exactly will be stolen is not
+    /// specified, as with [`Stealer::steal_batch_and_pop`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{PriorityWorker, Steal, StealPolicy};
+    ///
+    /// let w1 = PriorityWorker::new_fifo(StealPolicy::NormalOnly);
+    /// w1.push_high(1);
+    /// w1.push(2);
+    /// w1.push(3);
+    ///
+    /// let s = w1.stealer();
+    /// let w2 = PriorityWorker::new_fifo(StealPolicy::NormalOnly);
+    ///
+    /// assert_eq!(s.steal_batch_and_pop(&w2), Steal::Success(2));
+    /// ```
+    pub fn steal_batch_and_pop(&self, dest: &PriorityWorker<T>) -> Steal<T> {
+        self.steal_lanes(|s, high| s.steal_batch_and_pop(dest.lane(high)))
+    }
+
+    /// Runs a steal operation on the lanes in the order given by the steal policy.
+    ///
+    /// The closure receives a stealer of a lane and whether that lane is the high-priority one.
+    fn steal_lanes<R, F>(&self, mut f: F) -> Steal<R>
+    where
+        F: FnMut(&Stealer<T>, bool) -> Steal<R>,
+    {
+        match self.policy {
+            StealPolicy::HighFirst => f(&self.high, true).or_else(|| f(&self.normal, false)),
+            StealPolicy::NormalFirst => f(&self.normal, false).or_else(|| f(&self.high, true)),
+            StealPolicy::NormalOnly => f(&self.normal, false),
+        }
+    }
+}
+
+impl<T> Clone for PriorityStealer<T> {
+    fn clone(&self) -> PriorityStealer<T> {
+        PriorityStealer {
+            high: self.high.clone(),
+            normal: self.normal.clone(),
+            policy: self.policy,
+        }
+    }
+}
+
+impl<T> fmt::Debug for PriorityStealer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("PriorityStealer { .. }")
+    }
+}
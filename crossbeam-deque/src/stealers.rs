@@ -0,0 +1,215 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::deque::{Steal, Stealer, Worker};
+use crate::utils::Backoff;
+
+/// The order in which [`Stealers`] visits its victims.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StealStrategy {
+    /// Start from a randomly chosen victim.
+    Random,
+
+    /// Start from the victim of the last successful steal.
+    RoundRobin,
+
+    /// Start from the victim with the most tasks.
+    LoadAware,
+}
+
+/// A set of victims to steal tasks from.
+///
+/// Each worker thread in a scheduler typically owns a `Stealers` for the queues of the other
+/// threads. [`steal_into`] visits the victims in an order picked by the [`StealStrategy`] and
+/// takes care of retrying steal operations that fail with [`Steal::Retry`].
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_deque::{StealStrategy, Stealers, Worker};
+///
+/// let w1 = Worker::new_fifo();
+/// let w2 = Worker::new_fifo();
+/// w2.push(1);
+/// w2.push(2);
+///
+/// let victims = Stealers::new(vec![w1.stealer(), w2.stealer()], StealStrategy::RoundRobin);
+///
+/// let local = Worker::new_fifo();
+/// assert_eq!(victims.steal_into(&local), Some(1));
+/// ```
+///
+/// [`steal_into`]: Stealers::steal_into
+pub struct Stealers<T> {
+    /// The victims.
+    stealers: Vec<Stealer<T>>,
+
+    /// The order in which victims are visited.
+    strategy: StealStrategy,
+
+    /// The index of the last successful victim, or the state of the random number generator.
+    state: AtomicUsize,
+}
+
+impl<T> Stealers<T> {
+    /// Creates a victim set from a list of stealers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{StealStrategy, Stealers, Worker};
+    ///
+    /// let workers = vec![Worker::<i32>::new_fifo(), Worker::new_fifo()];
+    /// let victims = Stealers::new(
+    ///     workers.iter().map(|w| w.stealer()).collect(),
+    ///     StealStrategy::Random,
+    /// );
+    /// ```
+    pub fn new(stealers: Vec<Stealer<T>>, strategy: StealStrategy) -> Stealers<T> {
+        let state = match strategy {
+            StealStrategy::Random => seed(),
+            StealStrategy::RoundRobin | StealStrategy::LoadAware => 0,
+        };
+
+        Stealers {
+            stealers,
+            strategy,
+            state: AtomicUsize::new(state),
+        }
+    }
+
+    /// Returns the strategy used to pick victims.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{StealStrategy, Stealers};
+    ///
+    /// let victims = Stealers::<i32>::new(vec![], StealStrategy::LoadAware);
+    /// assert_eq!(victims.strategy(), StealStrategy::LoadAware);
+    /// ```
+    pub fn strategy(&self) -> StealStrategy {
+        self.strategy
+    }
+
+    /// Returns the stealers in this set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{StealStrategy, Stealers, Worker};
+    ///
+    /// let w = Worker::<i32>::new_fifo();
+    /// let victims = Stealers::new(vec![w.stealer()], StealStrategy::RoundRobin);
+    /// assert_eq!(victims.stealers().len(), 1);
+    /// ```
+    pub fn stealers(&self) -> &[Stealer<T>] {
+        &self.stealers
+    }
+
+    /// Steals a batch of tasks from one of the victims, pushes them into `dest`, and pops a task
+    /// from `dest`.
+    ///
+    /// Victims are visited in the order given by the strategy, and steal operations are retried
+    /// until a task is stolen or all victims are found empty. Returns `None` in the latter case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_deque::{StealStrategy, Stealers, Worker};
+    ///
+    /// let w = Worker::new_fifo();
+    /// w.push(1);
+    /// w.push(2);
+    /// w.push(3);
+    ///
+    /// let victims = Stealers::new(vec![w.stealer()], StealStrategy::LoadAware);
+    /// let local = Worker::new_fifo();
+    ///
+    /// assert_eq!(victims.steal_into(&local), Some(1));
+    /// assert_eq!(local.pop(), Some(2));
+    /// assert_eq!(victims.steal_into(&local), Some(3));
+    /// assert_eq!(victims.steal_into(&local), None);
+    /// ```
+    pub fn steal_into(&self, dest: &Worker<T>) -> Option<T> {
+        let len = self.stealers.len();
+        if len == 0 {
+            return None;
+        }
+
+        let backoff = Backoff::new();
+        loop {
+            let start = self.start();
+            let mut retry = false;
+
+            for i in 0..len {
+                let index = (start + i) % len;
+
+                match self.stealers[index].steal_batch_and_pop(dest) {
+                    Steal::Success(task) => {
+                        if self.strategy == StealStrategy::RoundRobin {
+                            self.state.store(index, Ordering::Relaxed);
+                        }
+                        return Some(task);
+                    }
+                    Steal::Retry => retry = true,
+                    Steal::Empty => {}
+                }
+            }
+
+            if !retry {
+                return None;
+            }
+            backoff.spin();
+        }
+    }
+
+    /// Returns the index of the first victim to visit.
+    fn start(&self) -> usize {
+        match self.strategy {
+            StealStrategy::Random => {
+                // Advance the xorshift generator. Races between threads only make it more random.
+                let mut x = self.state.load(Ordering::Relaxed) as u32;
+                x ^= x << 13;
+                x ^= x >> 17;
+                x ^= x << 5;
+                self.state.store(x as usize, Ordering::Relaxed);
+                x as usize % self.stealers.len()
+            }
+            StealStrategy::RoundRobin => self.state.load(Ordering::Relaxed),
+            StealStrategy::LoadAware => {
+                let mut start = 0;
+                let mut max = 0;
+                for (i, s) in self.stealers.iter().enumerate() {
+                    let len = s.len();
+                    if len > max {
+                        start = i;
+                        max = len;
+                    }
+                }
+                start
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Stealers<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Stealers")
+            .field("len", &self.stealers.len())
+            .field("strategy", &self.strategy)
+            .finish()
+    }
+}
+
+/// Returns a nonzero seed for the random number generator of a new victim set.
+fn seed() -> usize {
+    static SEED: AtomicUsize = AtomicUsize::new(0x9e37_79b9);
+
+    let seed = SEED.fetch_add(0x9e37_79b9, Ordering::Relaxed) as u32;
+    if seed == 0 {
+        1
+    } else {
+        seed as usize
+    }
+}
@@ -0,0 +1,169 @@
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::SeqCst;
+
+use crossbeam_deque::Steal::{Empty, Success};
+use crossbeam_deque::{Injector, Worker};
+use crossbeam_utils::thread::scope;
+
+#[test]
+fn smoke() {
+    let w = Worker::new_fifo_bounded(3);
+    assert_eq!(w.capacity(), Some(3));
+
+    assert_eq!(w.try_push(1), Ok(()));
+    assert_eq!(w.try_push(2), Ok(()));
+    assert_eq!(w.try_push(3), Ok(()));
+    assert_eq!(w.try_push(4), Err(4));
+    assert_eq!(w.len(), 3);
+
+    assert_eq!(w.pop(), Some(1));
+    assert_eq!(w.try_push(4), Ok(()));
+    assert_eq!(w.pop(), Some(2));
+    assert_eq!(w.pop(), Some(3));
+    assert_eq!(w.pop(), Some(4));
+    assert_eq!(w.pop(), None);
+}
+
+#[test]
+fn unbounded_capacity() {
+    let w = Worker::new_lifo();
+    assert_eq!(w.capacity(), None);
+
+    for i in 0..1000 {
+        assert_eq!(w.try_push(i), Ok(()));
+    }
+    assert_eq!(w.len(), 1000);
+}
+
+#[test]
+fn push_full() {
+    let w = Worker::new_lifo_bounded(1);
+    w.push(1);
+    w.push(2);
+    assert_eq!(w.len(), 2);
+    assert_eq!(w.try_push(3), Err(3));
+
+    assert_eq!(w.pop(), Some(2));
+    assert_eq!(w.try_push(3), Err(3));
+    assert_eq!(w.pop(), Some(1));
+    assert_eq!(w.try_push(3), Ok(()));
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    let _ = Worker::<i32>::new_fifo_bounded(0);
+}
+
+#[test]
+fn overflow_fifo() {
+    let q = Injector::new();
+    let w = Worker::new_fifo_bounded(4);
+
+    for i in 1..=5 {
+        w.push_or_overflow(i, &q);
+    }
+
+    assert_eq!(q.steal(), Success(1));
+    assert_eq!(q.steal(), Success(2));
+    assert_eq!(q.steal(), Empty);
+    assert_eq!(w.pop(), Some(3));
+    assert_eq!(w.pop(), Some(4));
+    assert_eq!(w.pop(), Some(5));
+    assert_eq!(w.pop(), None);
+}
+
+#[test]
+fn overflow_lifo() {
+    let q = Injector::new();
+    let w = Worker::new_lifo_bounded(4);
+
+    for i in 1..=5 {
+        w.push_or_overflow(i, &q);
+    }
+
+    assert_eq!(q.steal(), Success(1));
+    assert_eq!(q.steal(), Success(2));
+    assert_eq!(q.steal(), Empty);
+    assert_eq!(w.pop(), Some(5));
+    assert_eq!(w.pop(), Some(4));
+    assert_eq!(w.pop(), Some(3));
+    assert_eq!(w.pop(), None);
+}
+
+#[test]
+fn steal_into_bounded() {
+    let w = Worker::new_fifo();
+    for i in 1..=10 {
+        w.push(i);
+    }
+    let s = w.stealer();
+
+    let w2 = Worker::new_fifo_bounded(2);
+    assert_eq!(s.steal_many(&w2, 10), Success(()));
+    assert_eq!(w2.len(), 2);
+    assert_eq!(s.steal_batch(&w2), Empty);
+    assert_eq!(s.steal_many(&w2, 10), Empty);
+    assert_eq!(w2.len(), 2);
+    assert_eq!(s.steal_batch_and_pop(&w2), Success(3));
+    assert_eq!(w2.len(), 2);
+    assert_eq!(w.len(), 7);
+
+    let q = Injector::new();
+    for i in 1..=10 {
+        q.push(i);
+    }
+
+    let w3 = Worker::new_lifo_bounded(3);
+    assert_eq!(q.steal_batch(&w3), Success(()));
+    assert_eq!(w3.len(), 3);
+    assert_eq!(q.steal_batch(&w3), Empty);
+    assert_eq!(w3.len(), 3);
+    assert_eq!(q.steal_batch_and_pop(&w3), Success(4));
+    assert_eq!(w3.len(), 3);
+    assert_eq!(q.len(), 6);
+}
+
+#[test]
+fn stress() {
+    const THREADS: usize = 8;
+    const COUNT: usize = 50_000;
+
+    let q = Injector::new();
+    let w = Worker::new_fifo_bounded(64);
+    let remaining = AtomicUsize::new(COUNT);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            let s = w.stealer();
+            let q = &q;
+            let remaining = &remaining;
+
+            scope.spawn(move |_| {
+                let w2 = Worker::new_fifo_bounded(16);
+
+                while remaining.load(SeqCst) > 0 {
+                    let _ = s.steal_batch(&w2);
+                    let _ = q.steal_batch(&w2);
+
+                    while w2.pop().is_some() {
+                        remaining.fetch_sub(1, SeqCst);
+                    }
+                    assert!(w2.len() <= 16);
+                }
+            });
+        }
+
+        for i in 0..COUNT {
+            w.push_or_overflow(i, &q);
+            assert!(w.len() <= 64);
+        }
+
+        while remaining.load(SeqCst) > 0 {
+            if w.pop().is_some() {
+                remaining.fetch_sub(1, SeqCst);
+            }
+        }
+    })
+    .unwrap();
+}
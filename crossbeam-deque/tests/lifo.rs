@@ -69,6 +69,25 @@ fn is_empty() {
     assert!(s.is_empty());
 }
 
+#[test]
+fn len() {
+    let w = Worker::new_lifo();
+    let s = w.stealer();
+
+    assert_eq!(w.len(), 0);
+    assert_eq!(s.len(), 0);
+    w.push(1);
+    w.push(2);
+    assert_eq!(w.len(), 2);
+    assert_eq!(s.len(), 2);
+    let _ = w.pop();
+    assert_eq!(w.len(), 1);
+    assert_eq!(s.len(), 1);
+    let _ = s.steal();
+    assert_eq!(w.len(), 0);
+    assert_eq!(s.len(), 0);
+}
+
 #[test]
 fn spsc() {
     const STEPS: usize = 50_000;
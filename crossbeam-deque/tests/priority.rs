@@ -0,0 +1,125 @@
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::SeqCst;
+
+use crossbeam_deque::Steal::{Empty, Success};
+use crossbeam_deque::{PriorityWorker, StealPolicy};
+use crossbeam_utils::thread::scope;
+
+#[test]
+fn pop_high_first() {
+    let w = PriorityWorker::new_lifo(StealPolicy::HighFirst);
+
+    w.push(1);
+    w.push(2);
+    w.push_high(3);
+    w.push_high(4);
+    assert_eq!(w.len(), 4);
+
+    assert_eq!(w.pop(), Some(4));
+    assert_eq!(w.pop(), Some(3));
+    w.push_high(5);
+    assert_eq!(w.pop(), Some(5));
+    assert_eq!(w.pop(), Some(2));
+    assert_eq!(w.pop(), Some(1));
+    assert_eq!(w.pop(), None);
+    assert!(w.is_empty());
+}
+
+#[test]
+fn steal_policies() {
+    let w = PriorityWorker::new_fifo(StealPolicy::HighFirst);
+    let s = w.stealer();
+    w.push(1);
+    w.push_high(2);
+    assert_eq!(s.steal(), Success(2));
+    assert_eq!(s.steal(), Success(1));
+    assert_eq!(s.steal(), Empty);
+
+    let w = PriorityWorker::new_fifo(StealPolicy::NormalFirst);
+    let s = w.stealer();
+    w.push(1);
+    w.push_high(2);
+    assert_eq!(s.steal(), Success(1));
+    assert_eq!(s.steal(), Success(2));
+    assert_eq!(s.steal(), Empty);
+
+    let w = PriorityWorker::new_fifo(StealPolicy::NormalOnly);
+    let s = w.stealer();
+    w.push(1);
+    w.push_high(2);
+    assert_eq!(s.len(), 1);
+    assert_eq!(s.steal(), Success(1));
+    assert_eq!(s.steal(), Empty);
+    assert!(s.is_empty());
+    assert_eq!(w.pop(), Some(2));
+}
+
+#[test]
+fn steal_batch_into_matching_lane() {
+    let w1 = PriorityWorker::new_fifo(StealPolicy::HighFirst);
+    for i in 0..4 {
+        w1.push(i);
+    }
+    w1.push_high(10);
+    w1.push_high(11);
+
+    let s = w1.stealer();
+    let w2 = PriorityWorker::new_fifo(StealPolicy::HighFirst);
+
+    assert_eq!(s.steal_batch(&w2), Success(()));
+    assert_eq!(w2.pop(), Some(10));
+    assert_eq!(w2.pop(), None);
+
+    assert_eq!(s.steal_batch_and_pop(&w2), Success(11));
+    assert_eq!(s.steal_batch(&w2), Success(()));
+    w2.push_high(20);
+    assert_eq!(w2.pop(), Some(20));
+    assert_eq!(w2.pop(), Some(0));
+}
+
+#[test]
+fn stress() {
+    const THREADS: usize = 8;
+    const COUNT: usize = 50_000;
+
+    let w = PriorityWorker::new_lifo(StealPolicy::HighFirst);
+    let remaining = AtomicUsize::new(COUNT);
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            let s = w.stealer();
+            let remaining = &remaining;
+
+            scope.spawn(move |_| {
+                let w2 = PriorityWorker::new_lifo(StealPolicy::HighFirst);
+
+                while remaining.load(SeqCst) > 0 {
+                    if let Success(_) = s.steal_batch_and_pop(&w2) {
+                        remaining.fetch_sub(1, SeqCst);
+                    }
+                    while w2.pop().is_some() {
+                        remaining.fetch_sub(1, SeqCst);
+                    }
+                }
+            });
+        }
+
+        for i in 0..COUNT {
+            if i % 3 == 0 {
+                w.push_high(i);
+            } else {
+                w.push(i);
+            }
+            if i % 2 == 0 && w.pop().is_some() {
+                remaining.fetch_sub(1, SeqCst);
+            }
+        }
+
+        while remaining.load(SeqCst) > 0 {
+            if w.pop().is_some() {
+                remaining.fetch_sub(1, SeqCst);
+            }
+        }
+    })
+    .unwrap();
+}
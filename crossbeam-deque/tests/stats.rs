@@ -0,0 +1,46 @@
+#![cfg(feature = "stats")]
+
+use crossbeam_deque::Steal::{Empty, Success};
+use crossbeam_deque::{Worker, WorkerStats};
+
+#[test]
+fn smoke() {
+    let w = Worker::new_fifo();
+    let s = w.stealer();
+    assert_eq!(w.stats(), WorkerStats::default());
+
+    for i in 0..4 {
+        w.push(i);
+    }
+    assert_eq!(w.pop(), Some(0));
+    assert_eq!(s.steal(), Success(1));
+
+    let w2 = Worker::new_fifo();
+    assert_eq!(s.steal_batch(&w2), Success(()));
+    assert_eq!(w.pop(), Some(3));
+    assert_eq!(s.steal(), Empty);
+
+    let stats = s.stats();
+    assert_eq!(w.stats(), stats);
+    assert_eq!(stats.pushes, 4);
+    assert_eq!(stats.pops, 2);
+    assert_eq!(stats.steals, 2);
+    assert_eq!(stats.failed_steals, 1);
+    assert_eq!(stats.resizes, 0);
+    assert_eq!(w2.stats().pushes, 0);
+}
+
+#[test]
+fn resizes() {
+    let w = Worker::new_lifo();
+
+    for i in 0..1000 {
+        w.push(i);
+    }
+    let grown = w.stats().resizes;
+    assert!(grown > 0);
+
+    while w.pop().is_some() {}
+    assert!(w.stats().resizes > grown);
+    assert_eq!(w.stats().pops, 1000);
+}
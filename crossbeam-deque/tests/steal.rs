@@ -210,3 +210,81 @@ fn steal_batch_and_pop_injector_lifo() {
     assert_eq!(w2.pop(), Some(2));
     assert_eq!(w2.pop(), Some(3));
 }
+
+#[test]
+fn steal_many_fifo_fifo() {
+    let w = Worker::new_fifo();
+    for i in 1..=4 {
+        w.push(i);
+    }
+
+    let s = w.stealer();
+    let w2 = Worker::new_fifo();
+
+    assert_eq!(s.steal_many(&w2, 3), Success(()));
+    assert_eq!(w2.pop(), Some(1));
+    assert_eq!(w2.pop(), Some(2));
+    assert_eq!(w2.pop(), Some(3));
+    assert_eq!(w2.pop(), None);
+    assert_eq!(s.steal_many(&w2, 3), Success(()));
+    assert_eq!(w2.pop(), Some(4));
+    assert!(s.steal_many(&w2, 3).is_empty());
+}
+
+#[test]
+fn steal_many_lifo_lifo() {
+    let w = Worker::new_lifo();
+    for i in 1..=4 {
+        w.push(i);
+    }
+
+    let s = w.stealer();
+    let w2 = Worker::new_lifo();
+
+    assert_eq!(s.steal_many(&w2, 3), Success(()));
+    assert_eq!(w2.pop(), Some(3));
+    assert_eq!(w2.pop(), Some(2));
+    assert_eq!(w2.pop(), Some(1));
+    assert_eq!(w2.pop(), None);
+    assert_eq!(w.pop(), Some(4));
+}
+
+#[test]
+fn steal_many_fifo_lifo() {
+    let w = Worker::new_fifo();
+    for i in 1..=4 {
+        w.push(i);
+    }
+
+    let s = w.stealer();
+    let w2 = Worker::new_lifo();
+
+    assert_eq!(s.steal_many(&w2, 10), Success(()));
+    assert_eq!(w2.pop(), Some(1));
+    assert_eq!(w2.pop(), Some(2));
+    assert_eq!(w2.pop(), Some(3));
+    assert_eq!(w2.pop(), Some(4));
+}
+
+#[test]
+fn steal_many_lifo_fifo() {
+    let w = Worker::new_lifo();
+    for i in 1..=4 {
+        w.push(i);
+    }
+
+    let s = w.stealer();
+    let w2 = Worker::new_fifo();
+
+    assert_eq!(s.steal_many(&w2, 2), Success(()));
+    assert_eq!(w2.pop(), Some(2));
+    assert_eq!(w2.pop(), Some(1));
+    assert_eq!(w2.pop(), None);
+}
+
+#[test]
+#[should_panic(expected = "limit must be greater than zero")]
+fn steal_many_zero() {
+    let w = Worker::<i32>::new_fifo();
+    let _ = w.stealer().steal_many(&Worker::new_fifo(), 0);
+}
@@ -0,0 +1,142 @@
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering::SeqCst;
+
+use crossbeam_deque::{StealStrategy, Stealers, Worker};
+use crossbeam_utils::thread::scope;
+
+#[test]
+fn empty() {
+    let local = Worker::<i32>::new_fifo();
+
+    for &strategy in &[
+        StealStrategy::Random,
+        StealStrategy::RoundRobin,
+        StealStrategy::LoadAware,
+    ] {
+        let victims = Stealers::new(vec![], strategy);
+        assert_eq!(victims.steal_into(&local), None);
+
+        let w = Worker::new_fifo();
+        let victims = Stealers::new(vec![w.stealer(), w.stealer()], strategy);
+        assert_eq!(victims.steal_into(&local), None);
+    }
+}
+
+#[test]
+fn round_robin() {
+    let w1 = Worker::new_fifo();
+    let w2 = Worker::new_fifo();
+    for i in 0..4 {
+        w1.push(i);
+        w2.push(10 + i);
+    }
+
+    let victims = Stealers::new(vec![w1.stealer(), w2.stealer()], StealStrategy::RoundRobin);
+    let local = Worker::new_fifo();
+
+    // The first victim is visited first, and then remembered.
+    assert_eq!(victims.steal_into(&local), Some(0));
+    while local.pop().is_some() {}
+    assert_eq!(victims.steal_into(&local), Some(2));
+    while local.pop().is_some() {}
+    assert_eq!(victims.steal_into(&local), Some(3));
+
+    // Once the first victim is empty, the second one is found and then remembered.
+    assert!(w1.is_empty());
+    assert_eq!(victims.steal_into(&local), Some(10));
+    while local.pop().is_some() {}
+    w1.push(4);
+    assert_eq!(victims.steal_into(&local), Some(12));
+}
+
+#[test]
+fn load_aware() {
+    let w1 = Worker::new_fifo();
+    let w2 = Worker::new_fifo();
+    let w3 = Worker::new_fifo();
+    w1.push(1);
+    for i in 0..3 {
+        w2.push(20 + i);
+    }
+    for i in 0..2 {
+        w3.push(30 + i);
+    }
+
+    let victims = Stealers::new(
+        vec![w1.stealer(), w2.stealer(), w3.stealer()],
+        StealStrategy::LoadAware,
+    );
+    let local = Worker::new_fifo();
+
+    assert_eq!(victims.steal_into(&local), Some(20));
+    while local.pop().is_some() {}
+    assert_eq!(victims.steal_into(&local), Some(30));
+}
+
+#[test]
+fn random() {
+    const COUNT: usize = 100;
+
+    let workers = (0..4).map(|_| Worker::new_fifo()).collect::<Vec<_>>();
+    for (i, w) in workers.iter().enumerate() {
+        for j in 0..COUNT {
+            w.push(i * COUNT + j);
+        }
+    }
+
+    let victims = Stealers::new(
+        workers.iter().map(|w| w.stealer()).collect(),
+        StealStrategy::Random,
+    );
+    let local = Worker::new_fifo();
+
+    let mut seen = vec![false; workers.len() * COUNT];
+    while let Some(x) = victims.steal_into(&local) {
+        assert!(!seen[x]);
+        seen[x] = true;
+        while let Some(x) = local.pop() {
+            assert!(!seen[x]);
+            seen[x] = true;
+        }
+    }
+    assert!(seen.iter().all(|&s| s));
+}
+
+#[test]
+fn stress() {
+    const THREADS: usize = 8;
+    const COUNT: usize = 50_000;
+
+    let workers = (0..4).map(|_| Worker::new_fifo()).collect::<Vec<_>>();
+    for (i, w) in workers.iter().enumerate() {
+        for j in 0..COUNT {
+            w.push(i * COUNT + j);
+        }
+    }
+    let remaining = AtomicUsize::new(workers.len() * COUNT);
+
+    scope(|scope| {
+        for t in 0..THREADS {
+            let strategy = match t % 3 {
+                0 => StealStrategy::Random,
+                1 => StealStrategy::RoundRobin,
+                _ => StealStrategy::LoadAware,
+            };
+            let victims = Stealers::new(workers.iter().map(|w| w.stealer()).collect(), strategy);
+            let remaining = &remaining;
+
+            scope.spawn(move |_| {
+                let local = Worker::new_fifo();
+                while victims.steal_into(&local).is_some() {
+                    remaining.fetch_sub(1, SeqCst);
+                    while local.pop().is_some() {
+                        remaining.fetch_sub(1, SeqCst);
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(remaining.load(SeqCst), 0);
+}
@@ -11,6 +11,12 @@ fn single_pin(b: &mut Bencher) {
     b.iter(|| epoch::pin());
 }
 
+#[bench]
+fn nested_pin(b: &mut Bencher) {
+    let _guard = epoch::pin();
+    b.iter(|| epoch::pin());
+}
+
 #[bench]
 fn multi_pin(b: &mut Bencher) {
     const THREADS: usize = 16;
@@ -0,0 +1,21 @@
+use autocfg::AutoCfg;
+
+// The rustc-cfg strings below are *not* public API. Please let us know by
+// opening a GitHub issue if your build environment requires some way to enable
+// these cfgs other than by executing our build script.
+fn main() {
+    let cfg = match AutoCfg::new() {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            println!(
+                "cargo:warning=crossbeam-epoch: unable to determine rustc version: {}",
+                e
+            );
+            return;
+        }
+    };
+
+    if cfg.probe_rustc_version(1, 51) {
+        autocfg::emit("has_min_const_generics");
+    }
+}
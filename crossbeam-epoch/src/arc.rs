@@ -0,0 +1,385 @@
+use alloc::sync::Arc;
+use core::fmt;
+use core::marker::PhantomData;
+use core::mem::{self, ManuallyDrop};
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use crate::collector::Collector;
+use crate::default;
+use crate::guard::Guard;
+
+/// An atomic cell holding an [`Arc`].
+///
+/// This is the usual building block for read-mostly data, such as configuration that is read on
+/// every request and replaced once in a while: readers [`load`] the current value without ever
+/// blocking, while writers [`store`] a new one or update it with [`compare_exchange`].
+///
+/// A reader pins the current thread, loads the pointer, and increments the reference count. A
+/// writer that replaces the value doesn't release the old reference right away, but defers it
+/// until no thread that may be in the middle of a load is pinned anymore. Readers therefore never
+/// wait for writers or for each other.
+///
+/// [`load`]: AtomicArc::load
+/// [`store`]: AtomicArc::store
+/// [`compare_exchange`]: AtomicArc::compare_exchange
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_epoch::AtomicArc;
+/// use std::sync::Arc;
+///
+/// struct Config {
+///     verbose: bool,
+/// }
+///
+/// let config = AtomicArc::new(Arc::new(Config { verbose: false }));
+///
+/// let old = config.load();
+/// config.store(Arc::new(Config { verbose: true }));
+///
+/// // Readers that loaded the old value can keep using it.
+/// assert!(!old.verbose);
+/// assert!(config.load().verbose);
+/// ```
+pub struct AtomicArc<T> {
+    /// A pointer obtained from `Arc::into_raw`, which owns one reference.
+    ptr: AtomicPtr<T>,
+
+    /// The collector that releases replaced references.
+    collector: Collector,
+
+    /// Indicates that the cell owns an `Arc<T>`.
+    _marker: PhantomData<Arc<T>>,
+}
+
+impl<T> AtomicArc<T> {
+    /// Creates a new cell holding `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::AtomicArc;
+    /// use std::sync::Arc;
+    ///
+    /// let a = AtomicArc::new(Arc::new(7));
+    /// ```
+    pub fn new(value: Arc<T>) -> AtomicArc<T> {
+        AtomicArc {
+            ptr: AtomicPtr::new(Arc::into_raw(value) as *mut T),
+            collector: default::default_collector().clone(),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Loads the value from the cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::AtomicArc;
+    /// use std::sync::Arc;
+    ///
+    /// let a = AtomicArc::new(Arc::new(7));
+    /// assert_eq!(*a.load(), 7);
+    /// ```
+    pub fn load(&self) -> Arc<T> {
+        let _guard = self.pin();
+        let ptr = self.ptr.load(Ordering::Acquire);
+        // The reference owned by the cell is only released once we are unpinned.
+        unsafe { clone_raw(ptr) }
+    }
+
+    /// Returns a reference to the value in the cell, without touching the reference count.
+    ///
+    /// The reference stays valid for as long as `guard` is alive, even if the value gets replaced
+    /// in the meantime. This is cheaper than [`load`] when many values are read under the same
+    /// guard.
+    ///
+    /// [`load`]: AtomicArc::load
+    ///
+    /// # Panics
+    ///
+    /// Panics if `guard` doesn't belong to the collector that was the default one when the cell
+    /// was created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::{self as epoch, AtomicArc};
+    /// use std::sync::Arc;
+    ///
+    /// let a = AtomicArc::new(Arc::new(7));
+    /// let guard = &epoch::pin();
+    ///
+    /// let v = a.peek(guard);
+    /// a.store(Arc::new(8));
+    /// assert_eq!(*v, 7);
+    /// ```
+    pub fn peek<'g>(&'g self, guard: &'g Guard) -> &'g T {
+        if let Some(c) = guard.collector() {
+            assert!(c == &self.collector);
+        }
+        unsafe { &*self.ptr.load(Ordering::Acquire) }
+    }
+
+    /// Consumes the cell, returning the value it holds.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::AtomicArc;
+    /// use std::sync::Arc;
+    ///
+    /// let a = AtomicArc::new(Arc::new(7));
+    /// assert_eq!(*a.into_inner(), 7);
+    /// ```
+    pub fn into_inner(mut self) -> Arc<T> {
+        let ptr = mem::replace(self.ptr.get_mut(), ptr::null_mut());
+        unsafe { Arc::from_raw(ptr) }
+    }
+
+    /// Pins the current thread with the collector of the cell.
+    fn pin(&self) -> Guard {
+        let guard = default::pin();
+        if guard.collector() == Some(&self.collector) {
+            guard
+        } else {
+            self.collector.register().pin()
+        }
+    }
+}
+
+impl<T: Send + Sync + 'static> AtomicArc<T> {
+    /// Stores `value` into the cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::AtomicArc;
+    /// use std::sync::Arc;
+    ///
+    /// let a = AtomicArc::new(Arc::new(7));
+    /// a.store(Arc::new(8));
+    /// assert_eq!(*a.load(), 8);
+    /// ```
+    pub fn store(&self, value: Arc<T>) {
+        let guard = &self.pin();
+        let old = self
+            .ptr
+            .swap(Arc::into_raw(value) as *mut T, Ordering::AcqRel);
+        unsafe { retire(old, guard) };
+    }
+
+    /// Stores `value` into the cell, returning the previous value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::AtomicArc;
+    /// use std::sync::Arc;
+    ///
+    /// let a = AtomicArc::new(Arc::new(7));
+    /// assert_eq!(*a.swap(Arc::new(8)), 7);
+    /// assert_eq!(*a.load(), 8);
+    /// ```
+    pub fn swap(&self, value: Arc<T>) -> Arc<T> {
+        let guard = &self.pin();
+        let old = self
+            .ptr
+            .swap(Arc::into_raw(value) as *mut T, Ordering::AcqRel);
+        unsafe {
+            // The reference owned by the cell can't be handed out, since readers that loaded the
+            // pointer before the swap may still be about to increment the reference count.
+            let prev = clone_raw(old);
+            retire(old, guard);
+            prev
+        }
+    }
+
+    /// If the cell holds the same `Arc` as `current`, stores `new` into it.
+    ///
+    /// The return value is a result indicating whether the new value was written and containing
+    /// the previous value. On success this value is guaranteed to be the same `Arc` as `current`.
+    /// On failure, `new` is dropped.
+    ///
+    /// Values are compared by pointer, not with [`PartialEq`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::AtomicArc;
+    /// use std::sync::Arc;
+    ///
+    /// let a = AtomicArc::new(Arc::new(1));
+    /// let current = a.load();
+    ///
+    /// assert!(a.compare_exchange(&Arc::new(1), Arc::new(2)).is_err());
+    /// assert!(a.compare_exchange(&current, Arc::new(3)).is_ok());
+    /// assert_eq!(*a.load(), 3);
+    /// ```
+    pub fn compare_exchange(&self, current: &Arc<T>, new: Arc<T>) -> Result<Arc<T>, Arc<T>> {
+        let guard = &self.pin();
+        let current = &**current as *const T as *mut T;
+        let new = Arc::into_raw(new) as *mut T;
+
+        // `current` holds a reference, so its address can't be reused by another value.
+        match self
+            .ptr
+            .compare_exchange(current, new, Ordering::AcqRel, Ordering::Acquire)
+        {
+            Ok(old) => unsafe {
+                let prev = clone_raw(old);
+                retire(old, guard);
+                Ok(prev)
+            },
+            Err(actual) => unsafe {
+                drop(Arc::from_raw(new));
+                Err(clone_raw(actual))
+            },
+        }
+    }
+}
+
+impl<T> Drop for AtomicArc<T> {
+    fn drop(&mut self) {
+        let ptr = *self.ptr.get_mut();
+        if !ptr.is_null() {
+            unsafe { drop(Arc::from_raw(ptr)) };
+        }
+    }
+}
+
+impl<T: Default> Default for AtomicArc<T> {
+    fn default() -> AtomicArc<T> {
+        AtomicArc::new(Arc::default())
+    }
+}
+
+impl<T> From<Arc<T>> for AtomicArc<T> {
+    fn from(value: Arc<T>) -> AtomicArc<T> {
+        AtomicArc::new(value)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for AtomicArc<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("AtomicArc")
+            .field("value", &self.load())
+            .finish()
+    }
+}
+
+/// Returns a new reference to the `Arc` that `ptr` was obtained from.
+///
+/// The `Arc` must have a reference that stays alive while this function runs.
+unsafe fn clone_raw<T>(ptr: *const T) -> Arc<T> {
+    let arc = ManuallyDrop::new(Arc::from_raw(ptr));
+    Arc::clone(&arc)
+}
+
+/// Releases the reference owned by `ptr` once no thread pinned right now can be loading it.
+unsafe fn retire<T: Send + Sync + 'static>(ptr: *mut T, guard: &Guard) {
+    guard.defer_unchecked(move || drop(Arc::from_raw(ptr)));
+}
+
+#[cfg(all(test, not(crossbeam_loom)))]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crossbeam_utils::thread;
+
+    use super::AtomicArc;
+    use crate::Collector;
+
+    #[test]
+    fn drops_replaced_values() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Elem;
+
+        impl Drop for Elem {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let a = AtomicArc::new(Arc::new(Elem));
+        for _ in 0..100 {
+            a.store(Arc::new(Elem));
+        }
+        drop(a.swap(Arc::new(Elem)));
+        drop(a);
+
+        while DROPS.load(Ordering::Relaxed) < 102 {
+            crate::pin().flush();
+        }
+        assert_eq!(DROPS.load(Ordering::Relaxed), 102);
+    }
+
+    #[test]
+    fn compare_exchange() {
+        let a = AtomicArc::new(Arc::new(1));
+        let one = a.load();
+        let two = Arc::new(2);
+
+        assert!(Arc::ptr_eq(
+            &a.compare_exchange(&one, two.clone()).unwrap(),
+            &one
+        ));
+        let actual = a.compare_exchange(&one, Arc::new(3)).unwrap_err();
+        assert!(Arc::ptr_eq(&actual, &two));
+        assert_eq!(*a.into_inner(), 2);
+    }
+
+    #[test]
+    fn ambient_collector() {
+        let a = AtomicArc::new(Arc::new(0));
+
+        // Garbage goes to the collector the cell was created with, even inside another one.
+        let collector = Collector::new();
+        collector.with(|| {
+            a.store(Arc::new(1));
+            assert_eq!(*a.load(), 1);
+        });
+        assert_eq!(*a.load(), 1);
+    }
+
+    #[test]
+    fn concurrent_updates() {
+        const THREADS: usize = 4;
+        const STEPS: usize = 1000;
+
+        let a = AtomicArc::new(Arc::new(0));
+
+        thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|_| {
+                    for _ in 0..STEPS {
+                        let mut current = a.load();
+                        loop {
+                            match a.compare_exchange(&current, Arc::new(*current + 1)) {
+                                Ok(_) => break,
+                                Err(actual) => current = actual,
+                            }
+                        }
+                    }
+                });
+            }
+
+            scope.spawn(|_| {
+                let mut last = 0;
+                for _ in 0..STEPS {
+                    let v = *a.load();
+                    assert!(v >= last);
+                    last = v;
+                }
+            });
+        })
+        .unwrap();
+
+        assert_eq!(*a.load(), THREADS * STEPS);
+    }
+}
@@ -4,11 +4,13 @@ use core::fmt;
 use core::marker::PhantomData;
 use core::mem::{self, MaybeUninit};
 use core::ops::{Deref, DerefMut};
-use core::slice;
 use core::sync::atomic::Ordering;
+use core::{ptr, slice, str};
 
 use crate::alloc::alloc;
 use crate::alloc::boxed::Box;
+use crate::alloc::string::String;
+use crate::alloc::vec::Vec;
 use crate::guard::Guard;
 use crate::primitive::sync::atomic::AtomicUsize;
 use crossbeam_utils::atomic::AtomicConsume;
@@ -107,10 +109,65 @@ fn low_bits<T: ?Sized + Pointable>() -> usize {
     (1 << T::ALIGN.trailing_zeros()) - 1
 }
 
+/// Number of unused most significant bits in a pointer that can hold a tag.
+///
+/// These bits are used only if the `high-tag` feature is enabled, and only on targets where
+/// user-space pointers are known to leave them unused:
+///
+/// - On x86-64, bits 57 to 63 are clear in user-space pointers even with 5-level paging.
+/// - On AArch64, the top byte is left alone because Top Byte Ignore and memory tagging (MTE) can
+///   store their own tags in it, so only bits 48 to 55 are used. These are clear unless the
+///   process asks for 52-bit virtual addresses.
+#[cfg(all(
+    feature = "high-tag",
+    target_pointer_width = "64",
+    target_arch = "x86_64"
+))]
+const HIGH_TAG_BITS: usize = 7;
+#[cfg(all(
+    feature = "high-tag",
+    target_pointer_width = "64",
+    target_arch = "aarch64"
+))]
+const HIGH_TAG_BITS: usize = 8;
+#[cfg(not(all(
+    feature = "high-tag",
+    target_pointer_width = "64",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+const HIGH_TAG_BITS: usize = 0;
+
+/// Position of the least significant bit of the high tag.
+#[cfg(all(
+    feature = "high-tag",
+    target_pointer_width = "64",
+    target_arch = "x86_64"
+))]
+const HIGH_TAG_SHIFT: usize = 57;
+#[cfg(all(
+    feature = "high-tag",
+    target_pointer_width = "64",
+    target_arch = "aarch64"
+))]
+const HIGH_TAG_SHIFT: usize = 48;
+#[cfg(not(all(
+    feature = "high-tag",
+    target_pointer_width = "64",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+)))]
+const HIGH_TAG_SHIFT: usize = 0;
+
+/// Returns a bitmask containing the most significant bits of a pointer that can hold a tag.
+#[inline]
+fn high_bits() -> usize {
+    ((1 << HIGH_TAG_BITS) - 1) << HIGH_TAG_SHIFT
+}
+
 /// Panics if the pointer is not properly unaligned.
 #[inline]
 fn ensure_aligned<T: ?Sized + Pointable>(raw: usize) {
     assert_eq!(raw & low_bits::<T>(), 0, "unaligned pointer");
+    assert_eq!(raw & high_bits(), 0, "pointer uses the high tag bits");
 }
 
 /// Given a tagged pointer `data`, returns the same pointer, but tagged with `tag`.
@@ -122,9 +179,38 @@ fn compose_tag<T: ?Sized + Pointable>(data: usize, tag: usize) -> usize {
 }
 
 /// Decomposes a tagged pointer `data` into the pointer and the tag.
+///
+/// The returned pointer has both the low and the high tag bits cleared.
 #[inline]
 fn decompose_tag<T: ?Sized + Pointable>(data: usize) -> (usize, usize) {
-    (data & !low_bits::<T>(), data & low_bits::<T>())
+    (
+        data & !low_bits::<T>() & !high_bits(),
+        data & low_bits::<T>(),
+    )
+}
+
+/// Given a tagged pointer `data`, returns the same pointer, but with the high tag set to `tag`.
+///
+/// `tag` is truncated to fit into the high tag bits.
+#[inline]
+#[cfg(all(
+    feature = "high-tag",
+    target_pointer_width = "64",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+fn compose_high_tag(data: usize, tag: usize) -> usize {
+    (data & !high_bits()) | ((tag << HIGH_TAG_SHIFT) & high_bits())
+}
+
+/// Returns the high tag stored in a tagged pointer `data`.
+#[inline]
+#[cfg(all(
+    feature = "high-tag",
+    target_pointer_width = "64",
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+fn decompose_high_tag(data: usize) -> usize {
+    (data & high_bits()) >> HIGH_TAG_SHIFT
 }
 
 /// Types that are pointed to by a single word.
@@ -135,17 +221,19 @@ fn decompose_tag<T: ?Sized + Pointable>(data: usize) -> (usize, usize) {
 ///
 /// The trait generalizes `Box<T>` for a sized type `T`.  In a box, an object of type `T` is
 /// allocated in heap and it is owned by a single-word pointer.  This trait is also implemented for
-/// `[MaybeUninit<T>]` by storing its size along with its elements and pointing to the pair of array
-/// size and elements.
+/// `[MaybeUninit<T>]`, [`Slice<T>`] and `str` by storing their length along with their elements
+/// and pointing to the pair of length and elements.
 ///
 /// Pointers to `Pointable` types can be stored in [`Atomic`], [`Owned`], and [`Shared`].  In
-/// particular, Crossbeam supports dynamically sized slices as follows.
+/// particular, Crossbeam supports dynamically sized slices and strings as follows.
 ///
 /// ```
 /// use std::mem::MaybeUninit;
-/// use crossbeam_epoch::Owned;
+/// use crossbeam_epoch::{Owned, Slice};
 ///
 /// let o = Owned::<[MaybeUninit<i32>]>::init(10); // allocating [i32; 10]
+/// let o = Owned::<Slice<i32>>::from(vec![1, 2, 3]);
+/// let o = Owned::<str>::from("hello");
 /// ```
 pub trait Pointable {
     /// The alignment of pointer.
@@ -238,22 +326,38 @@ impl<T> Pointable for T {
 // [`alloc::alloc::Layout::extend`] instead.
 #[repr(C)]
 struct Array<T> {
+    /// The number of elements (not the number of bytes).
     size: usize,
     elements: [MaybeUninit<T>; 0],
 }
 
+impl<T> Array<T> {
+    /// Returns the layout of an array with `size` elements.
+    fn layout(size: usize) -> alloc::Layout {
+        let size = mem::size_of::<Array<T>>() + mem::size_of::<MaybeUninit<T>>() * size;
+        let align = mem::align_of::<Array<T>>();
+        alloc::Layout::from_size_align(size, align).unwrap()
+    }
+
+    /// Allocates an array with `size` uninitialized elements.
+    unsafe fn alloc(size: usize) -> *mut Array<T> {
+        let layout = Self::layout(size);
+        let ptr = alloc::alloc(layout) as *mut Array<T>;
+        if ptr.is_null() {
+            alloc::handle_alloc_error(layout);
+        }
+        (*ptr).size = size;
+        ptr
+    }
+}
+
 impl<T> Pointable for [MaybeUninit<T>] {
     const ALIGN: usize = mem::align_of::<Array<T>>();
 
     type Init = usize;
 
     unsafe fn init(size: Self::Init) -> usize {
-        let size = mem::size_of::<Array<T>>() + mem::size_of::<MaybeUninit<T>>() * size;
-        let align = mem::align_of::<Array<T>>();
-        let layout = alloc::Layout::from_size_align(size, align).unwrap();
-        let ptr = alloc::alloc(layout) as *mut Array<T>;
-        (*ptr).size = size;
-        ptr as usize
+        Array::<T>::alloc(size) as usize
     }
 
     unsafe fn deref<'a>(ptr: usize) -> &'a Self {
@@ -262,19 +366,121 @@ impl<T> Pointable for [MaybeUninit<T>] {
     }
 
     unsafe fn deref_mut<'a>(ptr: usize) -> &'a mut Self {
-        let array = &*(ptr as *mut Array<T>);
-        slice::from_raw_parts_mut(array.elements.as_ptr() as *mut _, array.size)
+        let array = &mut *(ptr as *mut Array<T>);
+        slice::from_raw_parts_mut(array.elements.as_mut_ptr() as *mut _, array.size)
     }
 
     unsafe fn drop(ptr: usize) {
         let array = &*(ptr as *mut Array<T>);
-        let size = mem::size_of::<Array<T>>() + mem::size_of::<MaybeUninit<T>>() * array.size;
-        let align = mem::align_of::<Array<T>>();
-        let layout = alloc::Layout::from_size_align(size, align).unwrap();
+        let layout = Array::<T>::layout(array.size);
         alloc::dealloc(ptr as *mut u8, layout);
     }
 }
 
+/// A slice of initialized elements that can be stored behind [`Owned`] or [`Atomic`].
+///
+/// `[T]` itself can't implement [`Pointable`], because `[MaybeUninit<T>]` already does so with
+/// uninitialized elements. `Slice<T>` is a `[T]` whose elements are all initialized, and which
+/// drops them along with the allocation. It dereferences to `[T]`.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_epoch::{Owned, Slice};
+///
+/// let mut o = Owned::<Slice<String>>::from(vec!["a".to_string(), "b".to_string()]);
+/// o[1].push('c');
+/// assert_eq!(&o[..], ["a", "bc"]);
+/// ```
+#[repr(transparent)]
+pub struct Slice<T> {
+    elements: [T],
+}
+
+impl<T> Deref for Slice<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.elements
+    }
+}
+
+impl<T> DerefMut for Slice<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        &mut self.elements
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Slice<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.elements.fmt(f)
+    }
+}
+
+impl<T> Pointable for Slice<T> {
+    const ALIGN: usize = <[MaybeUninit<T>] as Pointable>::ALIGN;
+
+    type Init = Box<[T]>;
+
+    unsafe fn init(init: Self::Init) -> usize {
+        let mut values = init.into_vec();
+        let ptr = Array::<T>::alloc(values.len());
+        // Move the elements into the array and free the vector without dropping them.
+        ptr::copy_nonoverlapping(
+            values.as_ptr(),
+            (*ptr).elements.as_mut_ptr() as *mut T,
+            values.len(),
+        );
+        values.set_len(0);
+        ptr as usize
+    }
+
+    unsafe fn deref<'a>(ptr: usize) -> &'a Self {
+        let elements = <[MaybeUninit<T>] as Pointable>::deref(ptr);
+        &*(elements as *const [MaybeUninit<T>] as *const Slice<T>)
+    }
+
+    unsafe fn deref_mut<'a>(ptr: usize) -> &'a mut Self {
+        let elements = <[MaybeUninit<T>] as Pointable>::deref_mut(ptr);
+        &mut *(elements as *mut [MaybeUninit<T>] as *mut Slice<T>)
+    }
+
+    unsafe fn drop(ptr: usize) {
+        ptr::drop_in_place(&mut <Self as Pointable>::deref_mut(ptr).elements);
+        <[MaybeUninit<T>] as Pointable>::drop(ptr)
+    }
+}
+
+impl Pointable for str {
+    const ALIGN: usize = <[MaybeUninit<u8>] as Pointable>::ALIGN;
+
+    type Init = Box<str>;
+
+    unsafe fn init(init: Self::Init) -> usize {
+        let ptr = Array::<u8>::alloc(init.len());
+        ptr::copy_nonoverlapping(
+            init.as_ptr(),
+            (*ptr).elements.as_mut_ptr() as *mut u8,
+            init.len(),
+        );
+        ptr as usize
+    }
+
+    unsafe fn deref<'a>(ptr: usize) -> &'a Self {
+        let bytes = <[MaybeUninit<u8>] as Pointable>::deref(ptr);
+        str::from_utf8_unchecked(&*(bytes as *const [MaybeUninit<u8>] as *const [u8]))
+    }
+
+    unsafe fn deref_mut<'a>(ptr: usize) -> &'a mut Self {
+        let bytes = <[MaybeUninit<u8>] as Pointable>::deref_mut(ptr);
+        str::from_utf8_unchecked_mut(&mut *(bytes as *mut [MaybeUninit<u8>] as *mut [u8]))
+    }
+
+    unsafe fn drop(ptr: usize) {
+        <[MaybeUninit<u8>] as Pointable>::drop(ptr)
+    }
+}
+
 /// An atomic pointer that can be safely shared between threads.
 ///
 /// The pointer must be properly aligned. Since it is aligned, a tag can be stored into the unused
@@ -558,6 +764,72 @@ impl<T: ?Sized + Pointable> Atomic<T> {
             })
     }
 
+    /// Fetches the pointer, and then applies a function to it that returns a new pointer.
+    ///
+    /// Returns `Ok(previous)` if the function returned `Some(_)` and the new pointer was stored,
+    /// and `Err(previous)` if the function returned `None`.
+    ///
+    /// The function may be called multiple times if the pointer is changed by other threads in the
+    /// meantime, as long as it returns `Some(_)`, but only one of the returned pointers gets
+    /// stored. Since the function may run more than once, it returns `Shared` pointers rather than
+    /// `Owned` ones.
+    ///
+    /// This method takes two `Ordering` arguments to describe the memory ordering of this
+    /// operation. `set_order` describes the required ordering for when the operation finally
+    /// succeeds, and `fetch_order` the required ordering for loads. These correspond to the
+    /// success and failure orderings of [`compare_exchange`] respectively.
+    ///
+    /// [`compare_exchange`]: Atomic::compare_exchange
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::{self as epoch, Atomic};
+    /// use std::sync::atomic::Ordering::SeqCst;
+    ///
+    /// let a = Atomic::new(1234);
+    /// let guard = &epoch::pin();
+    ///
+    /// // Set the tag, unless the pointer is already tagged.
+    /// let res1 = a.fetch_update(SeqCst, SeqCst, guard, |p| {
+    ///     if p.tag() == 0 {
+    ///         Some(p.with_tag(1))
+    ///     } else {
+    ///         None
+    ///     }
+    /// });
+    /// assert_eq!(res1.map(|p| p.tag()), Ok(0));
+    ///
+    /// let res2 = a.fetch_update(SeqCst, SeqCst, guard, |p| {
+    ///     if p.tag() == 0 {
+    ///         Some(p.with_tag(1))
+    ///     } else {
+    ///         None
+    ///     }
+    /// });
+    /// assert_eq!(res2.map_err(|p| p.tag()), Err(1));
+    /// # unsafe { drop(a.into_owned()); }
+    /// ```
+    pub fn fetch_update<'g, F>(
+        &self,
+        set_order: Ordering,
+        fetch_order: Ordering,
+        guard: &'g Guard,
+        mut func: F,
+    ) -> Result<Shared<'g, T>, Shared<'g, T>>
+    where
+        F: FnMut(Shared<'g, T>) -> Option<Shared<'g, T>>,
+    {
+        let mut prev = self.load(fetch_order, guard);
+        while let Some(next) = func(prev) {
+            match self.compare_exchange_weak(prev, next, set_order, fetch_order, guard) {
+                Ok(_) => return Ok(prev),
+                Err(err) => prev = err.current,
+            }
+        }
+        Err(prev)
+    }
+
     /// Stores the pointer `new` (either `Shared` or `Owned`) into the atomic pointer if the current
     /// value is the same as `current`. The tag is also taken into account, so two pointers to the
     /// same object, but with different tags, will not be considered equal.
@@ -1074,6 +1346,55 @@ impl<T: ?Sized + Pointable> Owned<T> {
         let data = self.into_usize();
         unsafe { Self::from_usize(compose_tag::<T>(data, tag)) }
     }
+
+    /// Returns the high tag stored within the pointer.
+    ///
+    /// The high tag lives in upper bits of the pointer that are unused by user-space pointers:
+    /// bits 57 to 63 on x86-64, and bits 48 to 55 on AArch64. It is available only if the
+    /// `high-tag` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::Owned;
+    ///
+    /// assert_eq!(Owned::new(1234).high_tag(), 0);
+    /// ```
+    #[cfg(all(
+        feature = "high-tag",
+        target_pointer_width = "64",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    pub fn high_tag(&self) -> usize {
+        decompose_high_tag(self.data)
+    }
+
+    /// Returns the same pointer, but with the high tag set to `tag`. `tag` is truncated to 7 bits
+    /// on x86-64 and to 8 bits on AArch64.
+    ///
+    /// The high tag is independent of the tag stored in the alignment bits, see [`tag`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::Owned;
+    ///
+    /// let o = Owned::new(0u64).with_tag(2).with_high_tag(0x55);
+    /// assert_eq!(o.tag(), 2);
+    /// assert_eq!(o.high_tag(), 0x55);
+    /// assert_eq!(*o, 0);
+    /// ```
+    ///
+    /// [`tag`]: Owned::tag
+    #[cfg(all(
+        feature = "high-tag",
+        target_pointer_width = "64",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    pub fn with_high_tag(self, tag: usize) -> Owned<T> {
+        let data = self.into_usize();
+        unsafe { Self::from_usize(compose_high_tag(data, tag)) }
+    }
 }
 
 impl<T: ?Sized + Pointable> Drop for Owned<T> {
@@ -1143,6 +1464,86 @@ impl<T> From<Box<T>> for Owned<T> {
     }
 }
 
+impl<T> From<Box<[T]>> for Owned<Slice<T>> {
+    /// Returns a new owned pointer to a slice holding the elements of `b`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::{Owned, Slice};
+    ///
+    /// let o = Owned::<Slice<i32>>::from(vec![1, 2, 3].into_boxed_slice());
+    /// assert_eq!(&o[..], [1, 2, 3]);
+    /// ```
+    fn from(b: Box<[T]>) -> Self {
+        Owned::init(b)
+    }
+}
+
+impl<T> From<Vec<T>> for Owned<Slice<T>> {
+    /// Returns a new owned pointer to a slice holding the elements of `v`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::{Owned, Slice};
+    ///
+    /// let o = Owned::<Slice<i32>>::from(vec![1, 2, 3]);
+    /// assert_eq!(&o[..], [1, 2, 3]);
+    /// ```
+    fn from(v: Vec<T>) -> Self {
+        Owned::init(v.into_boxed_slice())
+    }
+}
+
+impl From<Box<str>> for Owned<str> {
+    /// Returns a new owned pointer to a copy of the string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::Owned;
+    ///
+    /// let o = Owned::<str>::from(String::from("hello").into_boxed_str());
+    /// assert_eq!(&*o, "hello");
+    /// ```
+    fn from(s: Box<str>) -> Self {
+        Owned::init(s)
+    }
+}
+
+impl From<String> for Owned<str> {
+    /// Returns a new owned pointer to a copy of the string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::Owned;
+    ///
+    /// let o = Owned::<str>::from(String::from("hello"));
+    /// assert_eq!(&*o, "hello");
+    /// ```
+    fn from(s: String) -> Self {
+        Owned::init(s.into_boxed_str())
+    }
+}
+
+impl From<&str> for Owned<str> {
+    /// Returns a new owned pointer to a copy of the string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::Owned;
+    ///
+    /// let o = Owned::<str>::from("hello");
+    /// assert_eq!(&*o, "hello");
+    /// ```
+    fn from(s: &str) -> Self {
+        Owned::init(s.into())
+    }
+}
+
 impl<T: ?Sized + Pointable> Borrow<T> for Owned<T> {
     fn borrow(&self) -> &T {
         self.deref()
@@ -1416,6 +1817,54 @@ impl<'g, T: ?Sized + Pointable> Shared<'g, T> {
         Owned::from_usize(self.data)
     }
 
+    /// Claims that the object was just unlinked from a data structure, so that it can later be
+    /// destroyed safely with [`Unlinked::defer_drop`].
+    ///
+    /// This is the only unsafe step of reclaiming an object. The claim is usually made right
+    /// after the successful [`compare_exchange`] or [`swap`] that unlinked the object, where it
+    /// is easiest to check.
+    ///
+    /// # Safety
+    ///
+    /// The following must hold:
+    ///
+    /// - The pointer must not be null, and must have been obtained with `guard` pinned.
+    /// - The object must not be reachable from the data structure anymore, so that threads
+    ///   pinning after this point can't get a reference to it.
+    /// - No other `Unlinked` may be created for the same object. This holds if the pointer was
+    ///   replaced by an operation that can only succeed once, such as a successful CAS on the
+    ///   only `Atomic` pointing to the object.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::{self as epoch, Atomic, Owned};
+    /// use std::sync::atomic::Ordering::SeqCst;
+    ///
+    /// let a = Atomic::new(1234);
+    /// let guard = &epoch::pin();
+    ///
+    /// let p = a.swap(Owned::new(5678), SeqCst, guard);
+    /// // `a` was the only pointer to the old object, and `swap` unlinked it.
+    /// let unlinked = unsafe { p.into_unlinked(guard) };
+    /// assert_eq!(*unlinked, 1234);
+    /// unlinked.defer_drop();
+    /// # unsafe { drop(a.into_owned()); }
+    /// ```
+    ///
+    /// [`compare_exchange`]: Atomic::compare_exchange
+    /// [`swap`]: Atomic::swap
+    pub unsafe fn into_unlinked(self, guard: &'g Guard) -> Unlinked<'g, T> {
+        debug_assert!(
+            !self.is_null(),
+            "converting a null `Shared` into `Unlinked`"
+        );
+        Unlinked {
+            shared: self,
+            guard,
+        }
+    }
+
     /// Returns the tag stored within the pointer.
     ///
     /// # Examples
@@ -1457,6 +1906,66 @@ impl<'g, T: ?Sized + Pointable> Shared<'g, T> {
     pub fn with_tag(&self, tag: usize) -> Shared<'g, T> {
         unsafe { Self::from_usize(compose_tag::<T>(self.data, tag)) }
     }
+
+    /// Returns the high tag stored within the pointer.
+    ///
+    /// The high tag lives in upper bits of the pointer that are unused by user-space pointers:
+    /// bits 57 to 63 on x86-64, and bits 48 to 55 on AArch64. Together with the tag in the
+    /// alignment bits, this is enough room for small version counters or multi-bit state in a
+    /// single atomic word. It is available only if the `high-tag` feature is enabled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::{self as epoch, Atomic, Owned};
+    /// use std::sync::atomic::Ordering::SeqCst;
+    ///
+    /// let a = Atomic::<u64>::from(Owned::new(0u64).with_high_tag(7));
+    /// let guard = &epoch::pin();
+    /// let p = a.load(SeqCst, guard);
+    /// assert_eq!(p.high_tag(), 7);
+    /// # unsafe { drop(a.into_owned()); }
+    /// ```
+    #[cfg(all(
+        feature = "high-tag",
+        target_pointer_width = "64",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn high_tag(&self) -> usize {
+        decompose_high_tag(self.data)
+    }
+
+    /// Returns the same pointer, but with the high tag set to `tag`. `tag` is truncated to 7 bits
+    /// on x86-64 and to 8 bits on AArch64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::{self as epoch, Atomic, Owned};
+    /// use std::sync::atomic::Ordering::SeqCst;
+    ///
+    /// let a = Atomic::new(1234);
+    /// let guard = &epoch::pin();
+    ///
+    /// // Bump a version counter in the high bits on every update.
+    /// let p = a.load(SeqCst, guard);
+    /// let new = Owned::new(5678).with_high_tag(p.high_tag() + 1);
+    /// let p = a.compare_exchange(p, new, SeqCst, SeqCst, guard).unwrap();
+    ///
+    /// assert_eq!(p.high_tag(), 1);
+    /// assert_eq!(unsafe { *p.deref() }, 5678);
+    /// # unsafe { drop(a.into_owned()); }
+    /// ```
+    #[cfg(all(
+        feature = "high-tag",
+        target_pointer_width = "64",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    #[allow(clippy::trivially_copy_pass_by_ref)]
+    pub fn with_high_tag(&self, tag: usize) -> Shared<'g, T> {
+        unsafe { Self::from_usize(compose_high_tag(self.data, tag)) }
+    }
 }
 
 impl<T> From<*const T> for Shared<'_, T> {
@@ -1524,9 +2033,281 @@ impl<T: ?Sized + Pointable> Default for Shared<'_, T> {
     }
 }
 
+/// An object that was unlinked from a data structure and is waiting to be destroyed.
+///
+/// The object stays valid for as long as the guard it was unlinked under, so it can still be
+/// read. Once done with it, call [`defer_drop`] to destroy it after all threads currently pinned
+/// get unpinned. Unlike [`Guard::defer_destroy`], this is safe: the preconditions were checked when
+/// the object was unlinked with [`Shared::into_unlinked`], and the object must be safe to drop on
+/// another thread.
+///
+/// Dropping an `Unlinked` without calling [`defer_drop`] leaks the object.
+///
+/// # Examples
+///
+/// Objects that can't be sent to other threads can't be destroyed this way:
+///
+/// ```compile_fail
+/// use crossbeam_epoch::{self as epoch, Atomic, Owned};
+/// use std::rc::Rc;
+/// use std::sync::atomic::Ordering::SeqCst;
+///
+/// let a = Atomic::new(Rc::new(1));
+/// let guard = &epoch::pin();
+///
+/// let p = a.swap(Owned::new(Rc::new(2)), SeqCst, guard);
+/// unsafe { p.into_unlinked(guard) }.defer_drop(); // `Rc` is not `Send`
+/// ```
+///
+/// [`defer_drop`]: Unlinked::defer_drop
+pub struct Unlinked<'g, T: ?Sized + Pointable> {
+    shared: Shared<'g, T>,
+    guard: &'g Guard,
+}
+
+impl<'g, T: ?Sized + Pointable> Unlinked<'g, T> {
+    /// Returns the pointer to the object.
+    pub fn as_shared(&self) -> Shared<'g, T> {
+        self.shared
+    }
+
+    /// Destroys the object after all currently pinned threads get unpinned.
+    ///
+    /// See [`Guard::defer_destroy`] for details.
+    pub fn defer_drop(self)
+    where
+        T: Send + 'static,
+    {
+        let shared = self.shared;
+        unsafe {
+            self.guard
+                .defer_sized_unchecked(mem::size_of_val(shared.deref()), move || {
+                    shared.into_owned()
+                });
+        }
+    }
+}
+
+impl<T: ?Sized + Pointable> Deref for Unlinked<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { self.shared.deref() }
+    }
+}
+
+impl<T: ?Sized + Pointable> fmt::Debug for Unlinked<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Unlinked")
+            .field("shared", &self.shared)
+            .finish()
+    }
+}
+
+/// An atomic pointer whose tag width is checked at compile time.
+///
+/// How many low bits of a pointer can hold a tag depends on the alignment of `T`, which differs
+/// between platforms: `u64` is 8-byte aligned on 64-bit targets, leaving 3 bits for a tag, but
+/// only 4-byte aligned on some 32-bit ones. Since [`Shared::with_tag`] silently drops tag bits
+/// that don't fit, code that assumes a wider tag than the platform supports corrupts its tags.
+///
+/// `Tagged<T, BITS>` is an [`Atomic`] that requires `BITS` tag bits. Creating one fails to compile
+/// if pointers to `T` can't hold that many, and [`Tagged::with_tag`] panics on tags that don't fit
+/// in `BITS` bits. Any other operation is available through [`Tagged::as_atomic`].
+///
+/// This type requires Rust 1.51 or newer.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_epoch::{self as epoch, Tagged};
+/// use std::sync::atomic::Ordering::SeqCst;
+///
+/// // `u32` is 4-byte aligned on all supported platforms, so 2 tag bits are always available.
+/// type Ptr = Tagged<u32, 2>;
+///
+/// let a = Ptr::new(7);
+/// let guard = &epoch::pin();
+///
+/// let p = a.load(SeqCst, guard);
+/// a.store(Ptr::with_tag(p, 3), SeqCst);
+/// assert_eq!(a.load(SeqCst, guard).tag(), 3);
+/// # unsafe { drop(a.into_atomic().into_owned()) };
+/// ```
+///
+/// Requiring more tag bits than the alignment of `T` provides doesn't compile:
+///
+/// ```compile_fail
+/// use crossbeam_epoch::Tagged;
+///
+/// let a = Tagged::<u8, 1>::null();
+/// ```
+#[cfg(has_min_const_generics)]
+pub struct Tagged<T: ?Sized + Pointable, const BITS: usize> {
+    atomic: Atomic<T>,
+}
+
+#[cfg(has_min_const_generics)]
+impl<T: ?Sized + Pointable, const BITS: usize> Tagged<T, BITS> {
+    /// The bits of a tag.
+    pub const TAG_MASK: usize = (1 << BITS) - 1;
+
+    /// Overflows, and thus fails to compile when used, if pointers to `T` have fewer than `BITS`
+    /// unused low bits.
+    const CHECK: usize = T::ALIGN.trailing_zeros() as usize - BITS;
+
+    /// Returns a new null atomic pointer.
+    pub fn null() -> Self {
+        let _ = Self::CHECK;
+        Tagged {
+            atomic: Atomic::null(),
+        }
+    }
+
+    /// Converts an atomic pointer into a `Tagged`.
+    pub fn from_atomic(atomic: Atomic<T>) -> Self {
+        let _ = Self::CHECK;
+        Tagged { atomic }
+    }
+
+    /// Returns the underlying atomic pointer.
+    pub fn as_atomic(&self) -> &Atomic<T> {
+        &self.atomic
+    }
+
+    /// Converts into the underlying atomic pointer.
+    pub fn into_atomic(self) -> Atomic<T> {
+        self.atomic
+    }
+
+    /// Returns the same pointer, but tagged with `tag`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `tag` doesn't fit in `BITS` bits.
+    pub fn with_tag(ptr: Shared<'_, T>, tag: usize) -> Shared<'_, T> {
+        assert!(
+            tag <= Self::TAG_MASK,
+            "tag {} does not fit in {} bits",
+            tag,
+            BITS
+        );
+        ptr.with_tag(tag)
+    }
+
+    /// Loads a `Shared` from the atomic pointer.
+    ///
+    /// See [`Atomic::load`] for details.
+    pub fn load<'g>(&self, ord: Ordering, guard: &'g Guard) -> Shared<'g, T> {
+        self.atomic.load(ord, guard)
+    }
+
+    /// Stores a `Shared` or `Owned` pointer into the atomic pointer.
+    ///
+    /// See [`Atomic::store`] for details.
+    pub fn store<P: Pointer<T>>(&self, new: P, ord: Ordering) {
+        self.atomic.store(new, ord)
+    }
+
+    /// Stores a `Shared` or `Owned` pointer into the atomic pointer, returning the previous
+    /// `Shared`.
+    ///
+    /// See [`Atomic::swap`] for details.
+    pub fn swap<'g, P: Pointer<T>>(
+        &self,
+        new: P,
+        ord: Ordering,
+        guard: &'g Guard,
+    ) -> Shared<'g, T> {
+        self.atomic.swap(new, ord, guard)
+    }
+
+    /// Stores the pointer `new` into the atomic pointer if the current value is the same as
+    /// `current`.
+    ///
+    /// See [`Atomic::compare_exchange`] for details.
+    pub fn compare_exchange<'g, P>(
+        &self,
+        current: Shared<'_, T>,
+        new: P,
+        success: Ordering,
+        failure: Ordering,
+        guard: &'g Guard,
+    ) -> Result<Shared<'g, T>, CompareExchangeError<'g, T, P>>
+    where
+        P: Pointer<T>,
+    {
+        self.atomic
+            .compare_exchange(current, new, success, failure, guard)
+    }
+
+    /// Stores the pointer `new` into the atomic pointer if the current value is the same as
+    /// `current`, and may spuriously fail.
+    ///
+    /// See [`Atomic::compare_exchange_weak`] for details.
+    pub fn compare_exchange_weak<'g, P>(
+        &self,
+        current: Shared<'_, T>,
+        new: P,
+        success: Ordering,
+        failure: Ordering,
+        guard: &'g Guard,
+    ) -> Result<Shared<'g, T>, CompareExchangeError<'g, T, P>>
+    where
+        P: Pointer<T>,
+    {
+        self.atomic
+            .compare_exchange_weak(current, new, success, failure, guard)
+    }
+}
+
+#[cfg(has_min_const_generics)]
+impl<T, const BITS: usize> Tagged<T, BITS> {
+    /// Allocates `value` on the heap and returns a new atomic pointer pointing to it.
+    pub fn new(value: T) -> Self {
+        Self::from_atomic(Atomic::new(value))
+    }
+}
+
+#[cfg(has_min_const_generics)]
+impl<T: ?Sized + Pointable, const BITS: usize> fmt::Debug for Tagged<T, BITS> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.atomic.fmt(f)
+    }
+}
+
 #[cfg(all(test, not(crossbeam_loom)))]
 mod tests {
-    use super::Shared;
+    use std::mem::MaybeUninit;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use super::{Atomic, Owned, Shared, Slice};
+    use crate::Collector;
+
+    #[cfg(has_min_const_generics)]
+    #[test]
+    fn tagged() {
+        use crate::Tagged;
+
+        type Ptr = Tagged<u64, 2>;
+        assert_eq!(Ptr::TAG_MASK, 0b11);
+
+        let a = Ptr::new(1);
+        let guard = &crate::pin();
+        let p = a.load(Ordering::SeqCst, guard);
+        a.store(Ptr::with_tag(p, 2), Ordering::SeqCst);
+        assert_eq!(a.load(Ordering::SeqCst, guard).tag(), 2);
+        assert_eq!(unsafe { *a.load(Ordering::SeqCst, guard).deref() }, 1);
+
+        unsafe { drop(a.into_atomic().into_owned()) };
+    }
+
+    #[cfg(has_min_const_generics)]
+    #[test]
+    #[should_panic(expected = "tag 4 does not fit in 2 bits")]
+    fn tagged_tag_too_wide() {
+        crate::Tagged::<u64, 2>::with_tag(Shared::null(), 4);
+    }
 
     #[test]
     fn valid_tag_i8() {
@@ -1538,10 +2319,160 @@ mod tests {
         Shared::<i64>::null().with_tag(7);
     }
 
+    #[cfg(all(
+        feature = "high-tag",
+        target_pointer_width = "64",
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    #[test]
+    fn high_tag() {
+        let max = (1 << super::HIGH_TAG_BITS) - 1;
+
+        let o = Owned::new(7u8).with_tag(1).with_high_tag(max + 1 + 0x25);
+        assert_eq!(o.tag(), 0);
+        assert_eq!(o.high_tag(), 0x25);
+        assert_eq!(*o, 7);
+
+        let guard = &crate::pin();
+        let p = o.into_shared(guard).with_high_tag(max);
+        assert_eq!(p.high_tag(), max);
+        assert_eq!(unsafe { *p.deref() }, 7);
+        assert!(!p.is_null());
+
+        assert!(Shared::<u8>::null().with_high_tag(1).is_null());
+        unsafe { drop(p.into_owned()) };
+    }
+
+    #[cfg(all(feature = "high-tag", target_arch = "aarch64"))]
+    #[test]
+    fn high_tag_keeps_top_byte() {
+        // The top byte may hold a tag of the hardware, which must survive untouched.
+        let data = 0xab00_0000_0000_1000 | (0x12 << 48) | 3;
+        assert_eq!(
+            super::decompose_tag::<u64>(data),
+            (0xab00_0000_0000_1000, 3)
+        );
+        assert_eq!(super::decompose_high_tag(data), 0x12);
+        assert_eq!(super::compose_high_tag(data, 0x34), 0xab34_0000_0000_1003);
+    }
+
     #[cfg(feature = "nightly")]
     #[test]
     fn const_atomic_null() {
-        use super::Atomic;
         const _: Atomic<u8> = Atomic::<u8>::null();
     }
+
+    #[test]
+    fn uninit_slice() {
+        let o = Owned::<[MaybeUninit<u64>]>::init(10);
+        assert_eq!(o.len(), 10);
+    }
+
+    #[test]
+    fn slice() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Elem(usize);
+
+        impl Drop for Elem {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let a = Atomic::<Slice<Elem>>::from(Owned::from((0..5).map(Elem).collect::<Vec<_>>()));
+        assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+
+        let guard = &crate::pin();
+        let p = a.load(Ordering::SeqCst, guard);
+        let values = unsafe { p.deref() }.iter().map(|e| e.0).collect::<Vec<_>>();
+        assert_eq!(values, [0, 1, 2, 3, 4]);
+
+        unsafe { drop(a.into_owned()) };
+        assert_eq!(DROPS.load(Ordering::Relaxed), 5);
+    }
+
+    #[test]
+    fn empty_slice() {
+        let o = Owned::<Slice<String>>::from(Vec::new());
+        assert!(o.is_empty());
+    }
+
+    #[test]
+    fn str() {
+        let mut o = Owned::<str>::from("hello");
+        assert_eq!(&*o, "hello");
+        o.make_ascii_uppercase();
+        assert_eq!(&*o, "HELLO");
+
+        let a = Atomic::<str>::from(Owned::from(String::from("world")));
+        let guard = &crate::pin();
+        assert_eq!(unsafe { a.load(Ordering::SeqCst, guard).deref() }, "world");
+        unsafe { drop(a.into_owned()) };
+
+        assert!(Owned::<str>::init("".into()).is_empty());
+    }
+
+    #[test]
+    fn fetch_update_concurrent() {
+        const THREADS: usize = 4;
+        const COUNT: usize = 999;
+
+        let a = Atomic::<u64>::null();
+
+        crossbeam_utils::thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|_| {
+                    let guard = &crate::pin();
+                    for _ in 0..COUNT {
+                        // Count updates in the tag bits of a null pointer.
+                        a.fetch_update(Ordering::SeqCst, Ordering::SeqCst, guard, |p| {
+                            Some(p.with_tag((p.tag() + 1) % 8))
+                        })
+                        .unwrap();
+                    }
+                });
+            }
+        })
+        .unwrap();
+
+        let guard = &crate::pin();
+        assert_eq!(a.load(Ordering::SeqCst, guard).tag(), THREADS * COUNT % 8);
+    }
+
+    #[test]
+    fn unlinked_defer_drop() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Elem(i32);
+
+        impl Drop for Elem {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let collector = Collector::new();
+        let handle = collector.register();
+        // Another participant keeps the garbage from being executed as soon as `handle` unpins.
+        let other = collector.register();
+        let a = Atomic::new(Elem(1));
+
+        {
+            let guard = &handle.pin();
+            let p = a.swap(Owned::new(Elem(2)), Ordering::SeqCst, guard);
+            let unlinked = unsafe { p.into_unlinked(guard) };
+            assert_eq!(unlinked.0, 1);
+            unlinked.defer_drop();
+        }
+        assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+
+        drop(handle);
+        drop(other);
+        drop(collector);
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+        unsafe { drop(a.into_owned()) };
+        assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+    }
 }
@@ -15,7 +15,7 @@
 use core::fmt;
 
 use crate::guard::Guard;
-use crate::internal::{Global, Local};
+use crate::internal::{Global, Local, MAX_OBJECTS};
 use crate::primitive::sync::Arc;
 
 /// An epoch-based garbage collector.
@@ -28,9 +28,7 @@ unsafe impl Sync for Collector {}
 
 impl Default for Collector {
     fn default() -> Self {
-        Self {
-            global: Arc::new(Global::new()),
-        }
+        CollectorBuilder::new().build()
     }
 }
 
@@ -44,6 +42,284 @@ impl Collector {
     pub fn register(&self) -> LocalHandle {
         Local::register(self)
     }
+
+    /// Installs this collector as the ambient collector of the current thread while `f` runs.
+    ///
+    /// Inside `f`, [`pin`] and [`is_pinned`] use a participant registered with this collector
+    /// instead of the default one, so code that pins through them can keep its garbage in this
+    /// collector without having to pass a [`LocalHandle`] around. [`default_collector`] is not
+    /// affected. Calls can be nested, in which case the innermost collector is used.
+    ///
+    /// [`pin`]: crate::pin
+    /// [`is_pinned`]: crate::is_pinned
+    /// [`default_collector`]: crate::default_collector
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::{self as epoch, Collector};
+    ///
+    /// let collector = Collector::new();
+    ///
+    /// collector.with(|| {
+    ///     let guard = epoch::pin();
+    ///     assert!(guard.collector() == Some(&collector));
+    /// });
+    ///
+    /// let guard = epoch::pin();
+    /// assert!(guard.collector() == Some(epoch::default_collector()));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn with<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce() -> R,
+    {
+        crate::default::with_scoped(&self.register(), f)
+    }
+
+    /// Attempts to advance the global epoch and executes all deferred functions that are ready.
+    ///
+    /// Unlike [`Guard::flush`], which executes only a few bags of garbage, this method destroys
+    /// every bag that has expired and returns the number of deferred functions that were
+    /// executed. Garbage that is not ready yet is left for later, so this is a good fit for
+    /// driving reclamation at points where the application knows it can afford the work, such as
+    /// between frames.
+    ///
+    /// The thread-local bag of `guard` is moved into the global queue first, but its functions
+    /// can't run yet: at least two epoch advancements are needed before they become ready.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `guard` was not created by a handle registered with this collector.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::Collector;
+    ///
+    /// let collector = Collector::new();
+    /// let handle = collector.register();
+    ///
+    /// let guard = handle.pin();
+    /// for _ in 0..10 {
+    ///     guard.defer(|| ());
+    /// }
+    ///
+    /// // The first call seals the garbage and the following ones execute it.
+    /// assert_eq!(collector.try_collect(&guard), 0);
+    /// drop(guard);
+    ///
+    /// let mut executed = 0;
+    /// while executed < 10 {
+    ///     executed += collector.try_collect(&handle.pin());
+    /// }
+    /// assert_eq!(executed, 10);
+    /// ```
+    pub fn try_collect(&self, guard: &Guard) -> usize {
+        match unsafe { guard.local.as_ref() } {
+            Some(local) if local.collector() == self => local.try_collect(guard),
+            _ => panic!("guard must belong to this collector"),
+        }
+    }
+
+    /// Returns a snapshot of the garbage waiting to be collected.
+    ///
+    /// Only garbage that has been moved into the global queue is counted. Deferred functions
+    /// still sitting in thread-local bags are not visible until a bag fills up or gets flushed
+    /// with [`Guard::flush`].
+    ///
+    /// This only reads a few counters, so it neither registers nor pins a participant and can be
+    /// called from any thread. The counters are updated concurrently with other threads, so the
+    /// snapshot is only approximate while the collector is in use.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::Collector;
+    ///
+    /// let collector = Collector::new();
+    /// let handle = collector.register();
+    ///
+    /// let guard = handle.pin();
+    /// guard.defer_sized(1024, || ());
+    /// guard.flush();
+    ///
+    /// let stats = collector.stats();
+    /// assert_eq!(stats.deferred, 1);
+    /// assert_eq!(stats.deferred_bytes, 1024);
+    /// ```
+    pub fn stats(&self) -> CollectorStats {
+        self.global.stats()
+    }
+
+    /// Counts the bags of deferred functions in the global queue by their age.
+    ///
+    /// Index 0 counts bags sealed in the current global epoch, index 1 bags sealed in the previous
+    /// epoch, and index 2 older bags, which are ready to be collected. Like [`stats`], this
+    /// doesn't count garbage in thread-local bags.
+    ///
+    /// Unlike [`stats`], this has to walk the global queue, which is why it needs a guard.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `guard` is not pinned with this collector.
+    ///
+    /// [`stats`]: Collector::stats
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::Collector;
+    ///
+    /// let collector = Collector::new();
+    /// let handle = collector.register();
+    ///
+    /// let guard = &handle.pin();
+    /// guard.defer(|| ());
+    /// guard.flush();
+    ///
+    /// let bags = collector.bags(guard);
+    /// assert_eq!(bags.iter().sum::<usize>(), 1);
+    /// ```
+    pub fn bags(&self, guard: &Guard) -> [usize; 3] {
+        match unsafe { guard.local.as_ref() } {
+            Some(local) if local.collector() == self => self.global.bags(guard),
+            _ => panic!("guard must belong to this collector"),
+        }
+    }
+
+    /// Collects all garbage that can be collected, and panics if any deferred function still
+    /// hasn't been executed.
+    ///
+    /// This is meant for tests of data structures built on top of this crate: once a data
+    /// structure is dropped, all of its garbage should be reclaimable. Garbage in the local caches
+    /// of other participants can't be collected from here, so they have to be unregistered (or
+    /// flush their caches) first, and no other participant may stay pinned.
+    ///
+    /// The garbage of a collector is also checked when it gets dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::{Collector, Owned};
+    ///
+    /// let collector = Collector::new();
+    /// let handle = collector.register();
+    ///
+    /// unsafe {
+    ///     let guard = &handle.pin();
+    ///     guard.defer_destroy(Owned::new(7).into_shared(guard));
+    /// }
+    /// drop(handle);
+    ///
+    /// collector.assert_clean();
+    /// ```
+    #[cfg(feature = "leak-check")]
+    pub fn assert_clean(&self) {
+        let handle = self.register();
+
+        // Garbage becomes collectable after the global epoch advances twice.
+        for _ in 0..3 {
+            let guard = &handle.pin();
+            guard.flush();
+            self.global.collect_bags(usize::max_value(), guard);
+        }
+
+        let outstanding = self.global.outstanding();
+        if outstanding != 0 {
+            panic!("{} deferred functions were never executed", outstanding);
+        }
+    }
+}
+
+/// A snapshot of the garbage in a [`Collector`], returned by [`Collector::stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CollectorStats {
+    /// The number of deferred functions in the global queue that haven't been executed yet.
+    pub deferred: usize,
+
+    /// The estimated number of bytes that will be freed by the pending deferred functions.
+    ///
+    /// Only sizes supplied through [`Guard::defer_sized`] and friends, or implied by
+    /// [`Guard::defer_destroy`], are counted.
+    pub deferred_bytes: usize,
+}
+
+/// Callbacks for observing garbage collection in a [`Collector`].
+///
+/// An observer is installed with [`CollectorBuilder::observer`]. Garbage is reported a bag at a
+/// time, as bags of deferred functions move through the global queue, so the callbacks are not
+/// on the path of every [`Guard::defer`]. They may be called from any thread that uses the
+/// collector, so they should be cheap and must not pin the collector themselves.
+///
+/// All methods do nothing by default.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_epoch::{CollectorBuilder, Observer};
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+///
+/// #[derive(Default)]
+/// struct Reclaimed(AtomicUsize);
+///
+/// impl Observer for Reclaimed {
+///     fn reclaimed(&self, deferred: usize, _bytes: usize, _epochs: usize) {
+///         self.0.fetch_add(deferred, Ordering::Relaxed);
+///     }
+/// }
+///
+/// let observer = Arc::new(Reclaimed::default());
+/// let collector = CollectorBuilder::new().observer(observer.clone()).build();
+/// let handle = collector.register();
+///
+/// let guard = handle.pin();
+/// guard.defer(|| ());
+/// guard.flush();
+/// drop(guard);
+///
+/// while observer.0.load(Ordering::Relaxed) == 0 {
+///     collector.try_collect(&handle.pin());
+/// }
+/// ```
+pub trait Observer: Send + Sync {
+    /// Called when a bag of `deferred` functions, estimated to free `bytes` bytes, is moved into
+    /// the global queue.
+    fn deferred(&self, deferred: usize, bytes: usize) {
+        let _ = (deferred, bytes);
+    }
+
+    /// Called when a bag of `deferred` functions, estimated to free `bytes` bytes, has been
+    /// executed.
+    ///
+    /// `epochs` is the number of times the global epoch advanced since the bag was moved into the
+    /// global queue, which is at least 2. Bags that a participant executes directly because no
+    /// other participant is registered are reported with `epochs` set to 0.
+    fn reclaimed(&self, deferred: usize, bytes: usize, epochs: usize) {
+        let _ = (deferred, bytes, epochs);
+    }
+
+    /// Called when the global epoch can't be advanced because a participant is still pinned in
+    /// the previous epoch.
+    ///
+    /// This happens routinely while other threads are briefly pinned. Repeated calls without any
+    /// garbage getting reclaimed in between indicate that some thread stays pinned for too long.
+    fn advance_blocked(&self) {}
+}
+
+impl<O: Observer + ?Sized> Observer for alloc::sync::Arc<O> {
+    fn deferred(&self, deferred: usize, bytes: usize) {
+        (**self).deferred(deferred, bytes)
+    }
+
+    fn reclaimed(&self, deferred: usize, bytes: usize, epochs: usize) {
+        (**self).reclaimed(deferred, bytes, epochs)
+    }
+
+    fn advance_blocked(&self) {
+        (**self).advance_blocked()
+    }
 }
 
 impl Clone for Collector {
@@ -69,6 +345,151 @@ impl PartialEq for Collector {
 }
 impl Eq for Collector {}
 
+/// Configures and creates a [`Collector`].
+///
+/// Participants pinned with a collector periodically try to advance the epoch and execute some
+/// garbage, and move their thread-local garbage into the global queue whenever it fills up. Both
+/// of these happen on the pinning path, so their frequency trades reclamation throughput against
+/// latency spikes when pinning.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_epoch::CollectorBuilder;
+///
+/// let collector = CollectorBuilder::new()
+///     .pinnings_between_collect(512)
+///     .bag_capacity(16)
+///     .build();
+///
+/// let handle = collector.register();
+/// handle.pin().flush();
+/// ```
+#[derive(Clone)]
+pub struct CollectorBuilder {
+    pinnings_between_collect: usize,
+    bag_capacity: usize,
+    max_garbage: Option<usize>,
+    observer: Option<alloc::sync::Arc<dyn Observer>>,
+}
+
+impl CollectorBuilder {
+    /// Creates a builder with the default configuration.
+    pub fn new() -> CollectorBuilder {
+        CollectorBuilder {
+            pinnings_between_collect: Local::PINNINGS_BETWEEN_COLLECT,
+            bag_capacity: MAX_OBJECTS,
+            max_garbage: None,
+            observer: None,
+        }
+    }
+
+    /// Sets how many times a participant is pinned between attempts to collect garbage.
+    ///
+    /// Lower values reclaim memory sooner, while higher values make pinning cheaper on average
+    /// but let more garbage pile up between collections. The default is 128.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `pinnings` is not a power of two.
+    pub fn pinnings_between_collect(mut self, pinnings: usize) -> CollectorBuilder {
+        assert!(
+            pinnings.is_power_of_two(),
+            "pinnings between collections must be a power of two"
+        );
+        self.pinnings_between_collect = pinnings;
+        self
+    }
+
+    /// Sets how many deferred functions a participant buffers before moving them into the global
+    /// queue.
+    ///
+    /// Smaller bags make garbage visible to other threads sooner, at the cost of more frequent
+    /// synchronization. The default is also the maximum capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero or greater than the default capacity.
+    pub fn bag_capacity(mut self, capacity: usize) -> CollectorBuilder {
+        check_bag_capacity(capacity);
+        self.bag_capacity = capacity;
+        self
+    }
+
+    /// Sets a limit on the number of deferred functions waiting in the global queue.
+    ///
+    /// Without a limit, garbage can pile up without bound if some thread stays pinned for a long
+    /// time. When the limit is exceeded, every participant that gets pinned tries to collect all
+    /// expired garbage right away instead of a few bags at a time, and [`Guard::try_defer`]
+    /// refuses new garbage that can't be made room for.
+    ///
+    /// The limit only counts garbage in the global queue. Each participant may additionally hold
+    /// up to one bag of garbage locally. Collection can't make progress while a thread stays
+    /// pinned, so [`Guard::defer`] still accepts garbage above the limit.
+    ///
+    /// By default there is no limit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::CollectorBuilder;
+    ///
+    /// let collector = CollectorBuilder::new().max_garbage(10_000).build();
+    /// # drop(collector);
+    /// ```
+    pub fn max_garbage(mut self, limit: usize) -> CollectorBuilder {
+        self.max_garbage = Some(limit);
+        self
+    }
+
+    /// Sets an observer that gets notified as garbage is deferred and reclaimed.
+    ///
+    /// See [`Observer`] for details. By default there is no observer.
+    pub fn observer<O: Observer + 'static>(mut self, observer: O) -> CollectorBuilder {
+        self.observer = Some(alloc::sync::Arc::new(observer));
+        self
+    }
+
+    /// Creates a collector with the configured settings.
+    pub fn build(self) -> Collector {
+        Collector {
+            global: Arc::new(Global::new(
+                self.pinnings_between_collect,
+                self.bag_capacity,
+                self.max_garbage,
+                self.observer,
+            )),
+        }
+    }
+}
+
+impl fmt::Debug for CollectorBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CollectorBuilder")
+            .field("pinnings_between_collect", &self.pinnings_between_collect)
+            .field("bag_capacity", &self.bag_capacity)
+            .field("max_garbage", &self.max_garbage)
+            .field("observer", &self.observer.is_some())
+            .finish()
+    }
+}
+
+impl Default for CollectorBuilder {
+    fn default() -> CollectorBuilder {
+        CollectorBuilder::new()
+    }
+}
+
+/// Panics if `capacity` is not a valid bag capacity.
+fn check_bag_capacity(capacity: usize) {
+    assert!(capacity > 0, "bag capacity must be positive");
+    assert!(
+        capacity <= MAX_OBJECTS,
+        "bag capacity must be at most {}",
+        MAX_OBJECTS
+    );
+}
+
 /// A handle to a garbage collector.
 pub struct LocalHandle {
     pub(crate) local: *const Local,
@@ -92,6 +513,57 @@ impl LocalHandle {
     pub fn collector(&self) -> &Collector {
         unsafe { (*self.local).collector() }
     }
+
+    /// Moves the garbage buffered by this handle into the global queue.
+    ///
+    /// Garbage deferred through a handle is buffered locally until the buffer fills up, and only
+    /// then becomes visible to other threads. A thread that is about to block for a long time,
+    /// for example while waiting on a channel, can call this method first so that other threads
+    /// can destroy its garbage in the meantime.
+    ///
+    /// This is the same as pinning the handle and calling [`Guard::flush`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::Collector;
+    ///
+    /// let collector = Collector::new();
+    /// let handle = collector.register();
+    ///
+    /// handle.pin().defer(|| println!("destroyed"));
+    ///
+    /// // Hand the garbage off before blocking, so that other threads can destroy it.
+    /// handle.flush();
+    /// ```
+    pub fn flush(&self) {
+        self.pin().flush();
+    }
+
+    /// Returns the number of deferred functions this handle buffers before moving them into the
+    /// global queue.
+    ///
+    /// This defaults to the capacity configured with [`CollectorBuilder::bag_capacity`].
+    #[inline]
+    pub fn bag_capacity(&self) -> usize {
+        unsafe { (*self.local).bag_capacity() }
+    }
+
+    /// Sets the number of deferred functions this handle buffers before moving them into the
+    /// global queue.
+    ///
+    /// This overrides the capacity configured with [`CollectorBuilder::bag_capacity`] for this
+    /// handle only. Threads that defer garbage rarely, or that often block, may want a small
+    /// capacity so that their garbage doesn't get stranded. Garbage that is already buffered
+    /// stays buffered until the next time garbage is deferred or the handle is flushed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero or greater than the default capacity.
+    pub fn set_bag_capacity(&self, capacity: usize) {
+        check_bag_capacity(capacity);
+        unsafe { (*self.local).set_bag_capacity(capacity) }
+    }
 }
 
 impl Drop for LocalHandle {
@@ -113,10 +585,11 @@ impl fmt::Debug for LocalHandle {
 mod tests {
     use std::mem;
     use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
 
     use crossbeam_utils::thread;
 
-    use crate::{Collector, Owned};
+    use crate::{Collector, CollectorBuilder, CollectorStats, Observer, Owned, RepinEvery};
 
     const NUM_THREADS: usize = 8;
 
@@ -176,6 +649,119 @@ mod tests {
         }
     }
 
+    #[test]
+    fn builder_bag_capacity() {
+        let collector = CollectorBuilder::new().bag_capacity(2).build();
+        let handle = collector.register();
+
+        let guard = &handle.pin();
+        for _ in 0..5 {
+            guard.defer(|| ());
+        }
+
+        // Two full bags were moved into the global queue.
+        assert_eq!(collector.stats().deferred, 4);
+    }
+
+    #[test]
+    fn builder_pinnings_between_collect() {
+        let collector = CollectorBuilder::new().pinnings_between_collect(2).build();
+        let handle = collector.register();
+
+        let before = collector.global.epoch.load(Ordering::Relaxed);
+        for _ in 0..8 {
+            drop(handle.pin());
+        }
+        let after = collector.global.epoch.load(Ordering::Relaxed);
+        assert_eq!(after.wrapping_sub(before), 4);
+    }
+
+    #[test]
+    fn max_garbage() {
+        let collector = CollectorBuilder::new()
+            .bag_capacity(2)
+            .max_garbage(4)
+            .build();
+        let handle = collector.register();
+        let other = collector.register();
+
+        {
+            // While `other` stays pinned, garbage can't be collected.
+            let _pinned = other.pin();
+            let guard = &handle.pin();
+            for _ in 0..7 {
+                guard.defer(|| ());
+            }
+            assert_eq!(collector.stats().deferred, 6);
+            assert!(guard.try_defer(|| ()).is_err());
+        }
+
+        // Once nothing holds the epoch back, pinning collects all expired garbage.
+        for _ in 0..3 {
+            drop(handle.pin());
+        }
+        assert!(collector.stats().deferred <= 4);
+        assert!(handle.pin().try_defer(|| ()).is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "pinnings between collections must be a power of two")]
+    fn builder_pinnings_between_collect_not_power_of_two() {
+        CollectorBuilder::new().pinnings_between_collect(3);
+    }
+
+    #[test]
+    #[should_panic(expected = "bag capacity must be positive")]
+    fn builder_zero_bag_capacity() {
+        CollectorBuilder::new().bag_capacity(0);
+    }
+
+    #[test]
+    fn handle_bag_capacity() {
+        let collector = CollectorBuilder::new().bag_capacity(4).build();
+        let handle = collector.register();
+        assert_eq!(handle.bag_capacity(), 4);
+
+        handle.set_bag_capacity(1);
+        assert_eq!(handle.bag_capacity(), 1);
+        let other = collector.register();
+        assert_eq!(other.bag_capacity(), 4);
+
+        let guard = &handle.pin();
+        for _ in 0..3 {
+            guard.defer(|| ());
+        }
+
+        // Every deferred function but the last one was moved into the global queue.
+        assert_eq!(collector.stats().deferred, 2);
+    }
+
+    #[test]
+    #[should_panic(expected = "bag capacity must be at most")]
+    fn handle_bag_capacity_too_large() {
+        let collector = Collector::new();
+        collector.register().set_bag_capacity(usize::max_value());
+    }
+
+    #[test]
+    fn handle_flush() {
+        let collector = Collector::new();
+        let handle = collector.register();
+        // Another participant keeps the garbage from being executed as soon as `handle` unpins.
+        let _other = collector.register();
+
+        {
+            let guard = &handle.pin();
+            for _ in 0..3 {
+                guard.defer(|| ());
+            }
+        }
+        assert_eq!(collector.stats().deferred, 0);
+
+        handle.flush();
+        assert_eq!(collector.stats().deferred, 3);
+    }
+
     #[test]
     fn pin_holds_advance() {
         let collector = Collector::new();
@@ -199,6 +785,218 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn repin_every_lets_epoch_advance() {
+        let collector = Collector::new();
+        let handle = collector.register();
+        let other = collector.register();
+
+        let mut guard = RepinEvery::new(handle.pin(), 4);
+        let before = collector.global.epoch.load(Ordering::Relaxed);
+
+        for _ in 0..100 {
+            collector.global.collect(&other.pin());
+            guard.tick();
+        }
+
+        let after = collector.global.epoch.load(Ordering::Relaxed);
+        assert!(after.wrapping_sub(before) > 2);
+    }
+
+    #[test]
+    fn stats() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        let collector = Collector::new();
+        let handle = collector.register();
+        assert_eq!(collector.stats(), CollectorStats::default());
+        assert_eq!(collector.bags(&handle.pin()), [0, 0, 0]);
+
+        {
+            let guard = &handle.pin();
+            unsafe {
+                for _ in 0..10 {
+                    let a = Owned::new(7u64).into_shared(guard);
+                    guard.defer_destroy(a);
+                }
+            }
+            guard.defer_sized(100, || CALLS.fetch_add(1, Ordering::Relaxed));
+
+            // Garbage in the local bag is not counted.
+            assert_eq!(collector.stats().deferred, 0);
+
+            // Flushing seals the bag and advances the epoch once.
+            guard.flush();
+            let stats = collector.stats();
+            assert_eq!(stats.deferred, 11);
+            assert_eq!(stats.deferred_bytes, 10 * mem::size_of::<u64>() + 100);
+            assert_eq!(collector.bags(guard), [0, 1, 0]);
+        }
+
+        for _ in 0..10 {
+            handle.pin().flush();
+        }
+        assert_eq!(CALLS.load(Ordering::Relaxed), 1);
+
+        // Popping a bag from the global queue defers destruction of the queue node.
+        assert!(collector.stats().deferred <= 1);
+    }
+
+    #[test]
+    #[should_panic(expected = "guard must belong to this collector")]
+    fn bags_foreign_guard() {
+        let collector = Collector::new();
+        let handle = Collector::new().register();
+        collector.bags(&handle.pin());
+    }
+
+    #[test]
+    fn try_collect() {
+        static CALLS: AtomicUsize = AtomicUsize::new(0);
+
+        let collector = Collector::new();
+        let handle = collector.register();
+
+        {
+            let guard = &handle.pin();
+            for _ in 0..10 {
+                guard.defer(|| CALLS.fetch_add(1, Ordering::Relaxed));
+            }
+
+            // The garbage was sealed in the epoch `guard` is pinned in.
+            assert_eq!(collector.try_collect(guard), 0);
+            assert_eq!(CALLS.load(Ordering::Relaxed), 0);
+        }
+
+        let mut executed = 0;
+        for _ in 0..3 {
+            executed += collector.try_collect(&handle.pin());
+        }
+        assert_eq!(executed, 10);
+        assert_eq!(CALLS.load(Ordering::Relaxed), 10);
+    }
+
+    #[test]
+    #[should_panic(expected = "guard must belong to this collector")]
+    fn try_collect_foreign_guard() {
+        let collector = Collector::new();
+        let handle = Collector::new().register();
+        collector.try_collect(&handle.pin());
+    }
+
+    #[test]
+    fn with() {
+        let a = Collector::new();
+        let b = Collector::new();
+
+        a.with(|| {
+            assert!(crate::pin().collector() == Some(&a));
+
+            b.with(|| {
+                let guard = &crate::pin();
+                assert!(guard.collector() == Some(&b));
+                assert!(crate::is_pinned());
+
+                guard.defer(|| ());
+                guard.flush();
+                assert_eq!(b.stats().deferred, 1);
+            });
+
+            assert!(crate::pin().collector() == Some(&a));
+        });
+
+        assert!(crate::pin().collector() == Some(crate::default_collector()));
+        assert_eq!(a.stats().deferred, 0);
+    }
+
+    #[test]
+    fn observer() {
+        #[derive(Default)]
+        struct Counts {
+            deferred: AtomicUsize,
+            reclaimed: AtomicUsize,
+            early: AtomicUsize,
+            blocked: AtomicUsize,
+        }
+
+        impl Observer for Counts {
+            fn deferred(&self, deferred: usize, _: usize) {
+                self.deferred.fetch_add(deferred, Ordering::Relaxed);
+            }
+
+            fn reclaimed(&self, deferred: usize, _: usize, epochs: usize) {
+                self.reclaimed.fetch_add(deferred, Ordering::Relaxed);
+                if epochs < 2 {
+                    self.early.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+
+            fn advance_blocked(&self) {
+                self.blocked.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let counts = Arc::new(Counts::default());
+        let collector = CollectorBuilder::new().observer(counts.clone()).build();
+        let handle = collector.register();
+        let other = collector.register();
+
+        {
+            let _pinned = other.pin();
+            let guard = &handle.pin();
+            for _ in 0..10 {
+                guard.defer(|| ());
+            }
+            guard.flush();
+            assert_eq!(counts.deferred.load(Ordering::Relaxed), 10);
+
+            // `other` stays pinned, so the epoch can advance at most once.
+            for _ in 0..3 {
+                collector.try_collect(&handle.pin());
+            }
+            assert_eq!(counts.reclaimed.load(Ordering::Relaxed), 0);
+            assert!(counts.blocked.load(Ordering::Relaxed) > 0);
+        }
+
+        while counts.reclaimed.load(Ordering::Relaxed) < 10 {
+            collector.try_collect(&handle.pin());
+        }
+        assert_eq!(counts.reclaimed.load(Ordering::Relaxed), 10);
+        assert_eq!(counts.early.load(Ordering::Relaxed), 0);
+    }
+
+    #[cfg(feature = "leak-check")]
+    #[test]
+    fn assert_clean() {
+        let collector = Collector::new();
+        let handle = collector.register();
+
+        unsafe {
+            let guard = &handle.pin();
+            for i in 0..100 {
+                guard.defer_destroy(Owned::new(i).into_shared(guard));
+            }
+        }
+        drop(handle);
+
+        collector.assert_clean();
+    }
+
+    #[cfg(feature = "leak-check")]
+    #[test]
+    #[should_panic(expected = "deferred functions were never executed")]
+    fn assert_clean_pinned() {
+        let collector = Collector::new();
+        let handle = collector.register();
+
+        let guard = &handle.pin();
+        guard.defer(|| ());
+        guard.flush();
+
+        // The pinned participant keeps the global epoch from advancing far enough.
+        collector.assert_clean();
+    }
+
     #[cfg(not(crossbeam_sanitize))] // TODO: assertions failed due to `cfg(crossbeam_sanitize)` reduce `internal::MAX_OBJECTS`
     #[test]
     fn incremental() {
@@ -233,6 +1031,33 @@ mod tests {
         assert!(DESTROYS.load(Ordering::Relaxed) == 100_000);
     }
 
+    #[test]
+    fn reclaim_alone() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        let collector = Collector::new();
+        let handle = collector.register();
+
+        {
+            let guard = &handle.pin();
+            guard.defer(|| DROPS.fetch_add(1, Ordering::Relaxed));
+            {
+                let _nested = handle.pin();
+            }
+            // The outer guard still protects the garbage.
+            assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+        }
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+        let other = collector.register();
+        handle.pin().defer(|| DROPS.fetch_add(1, Ordering::Relaxed));
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+        drop(other);
+        drop(handle.pin());
+        assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+    }
+
     #[test]
     fn buffering() {
         const COUNT: usize = 10;
@@ -240,6 +1065,8 @@ mod tests {
 
         let collector = Collector::new();
         let handle = collector.register();
+        // Another participant keeps the garbage from being executed as soon as `handle` unpins.
+        let _other = collector.register();
 
         unsafe {
             let guard = &handle.pin();
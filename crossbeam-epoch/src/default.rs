@@ -1,21 +1,203 @@
 //! The default garbage collector.
 //!
-//! For each thread, a participant is lazily initialized on its first use, when the current thread
-//! is registered in the default collector.  If initialized, the thread's participant will get
-//! destructed on thread exit, which in turn unregisters the thread.
+//! With the `std` feature, for each thread, a participant is lazily initialized on its first use,
+//! when the current thread is registered in the default collector.  If initialized, the thread's
+//! participant will get destructed on thread exit, which in turn unregisters the thread.
+//!
+//! Alternatively, the participants can be provided by a custom [`Registry`] installed with
+//! [`set_registry`]. This is the only way to use the default collector without the `std` feature.
+
+#[cfg(feature = "std")]
+use core::cell::Cell;
+use core::cell::UnsafeCell;
+#[cfg(feature = "std")]
+use core::ptr;
+// Loom's atomics can't be created in a `static`, and this one doesn't need to be modeled anyway.
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crossbeam_utils::Backoff;
+#[cfg(feature = "std")]
+use scopeguard::defer;
 
 use crate::collector::{Collector, LocalHandle};
 use crate::guard::Guard;
+#[cfg(feature = "std")]
 use crate::primitive::{lazy_static, thread_local};
 
+#[cfg(feature = "std")]
 lazy_static! {
     /// The global data for the default garbage collector.
     static ref COLLECTOR: Collector = Collector::new();
 }
 
+#[cfg(feature = "std")]
 thread_local! {
     /// The per-thread participant for the default garbage collector.
-    static HANDLE: LocalHandle = COLLECTOR.register();
+    static HANDLE: ThreadHandle = ThreadHandle(COLLECTOR.register());
+
+    /// The participant the current thread pins with, or null if it hasn't been looked up yet.
+    ///
+    /// This is either the participant installed by `Collector::with`, or the one in `HANDLE` once
+    /// the built-in registry is in use. Checking it first keeps `pin` down to a single
+    /// thread-local access.
+    static CURRENT: Cell<*const LocalHandle> = Cell::new(ptr::null());
+}
+
+/// The participant in `HANDLE`, which stops being the current one when it is destroyed.
+#[cfg(feature = "std")]
+struct ThreadHandle(LocalHandle);
+
+#[cfg(feature = "std")]
+impl Drop for ThreadHandle {
+    fn drop(&mut self) {
+        let _ = CURRENT.try_with(|c| {
+            if ptr::eq(c.get(), &self.0) {
+                c.set(ptr::null());
+            }
+        });
+    }
+}
+
+/// A source of participants for the default collector.
+///
+/// The default collector needs a participant for every thread (or other execution context) that
+/// pins it. With the `std` feature, participants are kept in thread-local storage. Environments
+/// without `std` can provide them by implementing this trait and installing it with
+/// [`set_registry`].
+///
+/// # Examples
+///
+/// ```
+/// use std::cell::RefCell;
+/// use crossbeam_epoch::{self as epoch, Collector, LocalHandle, Registry};
+///
+/// struct MyRegistry {
+///     collector: Collector,
+/// }
+///
+/// thread_local! {
+///     static HANDLE: RefCell<Option<LocalHandle>> = RefCell::new(None);
+/// }
+///
+/// impl Registry for MyRegistry {
+///     fn collector(&self) -> &Collector {
+///         &self.collector
+///     }
+///
+///     fn with_handle(&self, f: &mut dyn FnMut(&LocalHandle)) {
+///         HANDLE.with(|h| {
+///             let mut h = h.borrow_mut();
+///             f(h.get_or_insert_with(|| self.collector.register()))
+///         })
+///     }
+/// }
+///
+/// let registry = Box::leak(Box::new(MyRegistry {
+///     collector: Collector::new(),
+/// }));
+/// assert!(epoch::set_registry(registry).is_ok());
+///
+/// let guard = epoch::pin();
+/// assert!(guard.collector() == Some(&registry.collector));
+/// ```
+pub trait Registry: Sync {
+    /// Returns the collector the participants are registered with.
+    fn collector(&self) -> &Collector;
+
+    /// Calls `f` with the participant of the current thread.
+    ///
+    /// The participant must be registered with [`collector`], and must not be used by another
+    /// thread while `f` runs or while the current thread is pinned.
+    ///
+    /// [`collector`]: Registry::collector
+    fn with_handle(&self, f: &mut dyn FnMut(&LocalHandle));
+}
+
+/// No registry has been chosen yet.
+const UNSET: usize = 0;
+/// A custom registry is being installed.
+const SETTING: usize = 1;
+/// A custom registry is installed.
+const CUSTOM: usize = 2;
+/// The built-in thread-local registry is in use.
+#[cfg(feature = "std")]
+const BUILTIN: usize = 3;
+
+/// The registry used by the default collector.
+struct RegistryCell {
+    state: AtomicUsize,
+    registry: UnsafeCell<Option<&'static dyn Registry>>,
+}
+
+// `registry` is written only once, before `state` is set to `CUSTOM` with `Release` ordering.
+unsafe impl Sync for RegistryCell {}
+
+static REGISTRY: RegistryCell = RegistryCell {
+    state: AtomicUsize::new(UNSET),
+    registry: UnsafeCell::new(None),
+};
+
+/// Installs the registry that provides participants for the default collector.
+///
+/// The registry has to be installed before the default collector is first used. Afterwards, it
+/// can't be changed: if a registry is already installed, or the built-in thread-local one is
+/// already in use, `registry` is returned back as an error.
+///
+/// Without the `std` feature, a registry must be installed before calling [`pin`],
+/// [`is_pinned`], or [`default_collector`].
+pub fn set_registry(registry: &'static dyn Registry) -> Result<(), &'static dyn Registry> {
+    if REGISTRY
+        .state
+        .compare_exchange(UNSET, SETTING, Ordering::Acquire, Ordering::Relaxed)
+        .is_err()
+    {
+        return Err(registry);
+    }
+
+    unsafe {
+        *REGISTRY.registry.get() = Some(registry);
+    }
+    REGISTRY.state.store(CUSTOM, Ordering::Release);
+    Ok(())
+}
+
+/// Returns the installed custom registry, or `None` if the built-in one is used.
+#[inline]
+fn custom_registry() -> Option<&'static dyn Registry> {
+    match REGISTRY.state.load(Ordering::Acquire) {
+        CUSTOM => unsafe { *REGISTRY.registry.get() },
+        #[cfg(feature = "std")]
+        BUILTIN => None,
+        _ => custom_registry_slow(),
+    }
+}
+
+#[cold]
+fn custom_registry_slow() -> Option<&'static dyn Registry> {
+    let backoff = Backoff::new();
+    loop {
+        match REGISTRY.state.load(Ordering::Acquire) {
+            CUSTOM => return unsafe { *REGISTRY.registry.get() },
+            SETTING => backoff.snooze(),
+            #[cfg(feature = "std")]
+            UNSET | BUILTIN => {
+                // Claim the built-in registry, unless a custom one is being installed right now.
+                match REGISTRY.state.compare_exchange(
+                    UNSET,
+                    BUILTIN,
+                    Ordering::Acquire,
+                    Ordering::Acquire,
+                ) {
+                    Ok(_) | Err(BUILTIN) => return None,
+                    Err(_) => {}
+                }
+            }
+            #[cfg(not(feature = "std"))]
+            _ => panic!("the default collector requires a registry without the `std` feature"),
+            #[cfg(feature = "std")]
+            _ => unreachable!(),
+        }
+    }
 }
 
 /// Pins the current thread.
@@ -32,23 +214,78 @@ pub fn is_pinned() -> bool {
 
 /// Returns the default global collector.
 pub fn default_collector() -> &'static Collector {
-    &COLLECTOR
+    match custom_registry() {
+        Some(registry) => registry.collector(),
+        #[cfg(feature = "std")]
+        None => &COLLECTOR,
+        #[cfg(not(feature = "std"))]
+        None => unreachable!(),
+    }
+}
+
+/// Uses `handle` instead of the default collector's participant while `f` runs.
+#[cfg(feature = "std")]
+pub(crate) fn with_scoped<F, R>(handle: &LocalHandle, f: F) -> R
+where
+    F: FnOnce() -> R,
+{
+    let prev = CURRENT.with(|c| c.replace(handle));
+    defer! {
+        CURRENT.with(|c| c.set(prev));
+    }
+    f()
 }
 
 #[inline]
+// `f` is only called directly when the thread-local handle is available.
+#[cfg_attr(not(feature = "std"), allow(unused_mut))]
 fn with_handle<F, R>(mut f: F) -> R
 where
     F: FnMut(&LocalHandle) -> R,
 {
-    HANDLE
-        .try_with(|h| f(h))
-        .unwrap_or_else(|_| f(&COLLECTOR.register()))
+    #[cfg(feature = "std")]
+    {
+        let current = CURRENT.try_with(|c| c.get()).unwrap_or(ptr::null());
+        if !current.is_null() {
+            // `with_scoped` and `ThreadHandle` reset the pointer before the handle goes away.
+            return f(unsafe { &*current });
+        }
+    }
+
+    with_handle_slow(f)
+}
+
+#[cold]
+fn with_handle_slow<F, R>(mut f: F) -> R
+where
+    F: FnMut(&LocalHandle) -> R,
+{
+    match custom_registry() {
+        Some(registry) => {
+            let mut result = None;
+            registry.with_handle(&mut |h| result = Some(f(h)));
+            result.expect("registry didn't provide a participant")
+        }
+        #[cfg(feature = "std")]
+        None => HANDLE
+            .try_with(|h| {
+                // The built-in registry can't be replaced anymore, so the handle can be cached.
+                let _ = CURRENT.try_with(|c| c.set(&h.0));
+                f(&h.0)
+            })
+            .unwrap_or_else(|_| f(&COLLECTOR.register())),
+        #[cfg(not(feature = "std"))]
+        None => unreachable!(),
+    }
 }
 
 #[cfg(all(test, not(crossbeam_loom)))]
 mod tests {
     use crossbeam_utils::thread;
 
+    use super::{Registry, COLLECTOR};
+    use crate::{Collector, LocalHandle};
+
     #[test]
     fn pin_while_exiting() {
         struct Foo;
@@ -74,4 +311,24 @@ mod tests {
         })
         .unwrap();
     }
+
+    #[test]
+    fn set_registry_after_use() {
+        struct Unused;
+
+        impl Registry for Unused {
+            fn collector(&self) -> &Collector {
+                unreachable!()
+            }
+
+            fn with_handle(&self, _: &mut dyn FnMut(&LocalHandle)) {
+                unreachable!()
+            }
+        }
+
+        // Once the built-in registry is in use, it can't be replaced.
+        drop(super::pin());
+        assert!(super::set_registry(&Unused).is_err());
+        assert!(super::default_collector() == &*COLLECTOR);
+    }
 }
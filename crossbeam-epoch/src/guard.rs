@@ -1,5 +1,6 @@
 use core::fmt;
 use core::mem;
+use core::ops::Deref;
 
 use scopeguard::defer;
 
@@ -66,7 +67,33 @@ use crate::internal::Local;
 /// assert!(!epoch::is_pinned());
 /// ```
 ///
+/// # Long-running loops
+///
+/// The global epoch can't advance past the epoch of a pinned thread, so garbage deferred by any
+/// thread can't be reclaimed while a guard is held. A loop that pins once and then runs for a
+/// long time makes garbage pile up. Such loops should periodically call [`repin`] when they hold
+/// no references obtained through the guard, or use [`RepinEvery`] to repin automatically after
+/// a fixed number of iterations:
+///
+/// ```
+/// use crossbeam_epoch::{self as epoch, Atomic, RepinEvery};
+/// use std::sync::atomic::Ordering::SeqCst;
+///
+/// let a = Atomic::new(0);
+/// let mut guard = RepinEvery::new(epoch::pin(), 128);
+///
+/// for _ in 0..1000 {
+///     let p = a.load(SeqCst, &guard);
+///     assert_eq!(unsafe { p.as_ref() }, Some(&0));
+///
+///     // Let the epoch advance every 128 iterations.
+///     guard.tick();
+/// }
+/// # unsafe { drop(a.into_owned()); }
+/// ```
+///
 /// [`pin`]: super::pin
+/// [`repin`]: Guard::repin
 pub struct Guard {
     pub(crate) local: *const Local,
 }
@@ -186,11 +213,102 @@ impl Guard {
     /// }
     /// ```
     pub unsafe fn defer_unchecked<F, R>(&self, f: F)
+    where
+        F: FnOnce() -> R,
+    {
+        self.defer_sized_unchecked(0, f);
+    }
+
+    /// Stores a function so that it can be executed at some point after all currently pinned
+    /// threads get unpinned, unless the collector already holds too much garbage.
+    ///
+    /// This is the same as [`defer`], except that it applies backpressure when the collector was
+    /// built with a garbage limit (see [`CollectorBuilder::max_garbage`]). If the global queue
+    /// holds more deferred functions than the limit, this method first tries to collect all
+    /// expired garbage. If the queue is still over the limit afterwards, `f` is returned back as
+    /// an error and the caller can decide what to do, for example unpin and retry later.
+    ///
+    /// If the collector has no garbage limit, this method always succeeds.
+    ///
+    /// If this method is called from an [`unprotected`] guard, the function will simply be
+    /// executed immediately.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::CollectorBuilder;
+    ///
+    /// let collector = CollectorBuilder::new().max_garbage(1000).build();
+    /// let handle = collector.register();
+    ///
+    /// let guard = handle.pin();
+    /// assert!(guard.try_defer(|| ()).is_ok());
+    /// ```
+    ///
+    /// [`defer`]: Guard::defer
+    /// [`CollectorBuilder::max_garbage`]: crate::CollectorBuilder::max_garbage
+    pub fn try_defer<F, R>(&self, f: F) -> Result<(), F>
+    where
+        F: FnOnce() -> R,
+        F: Send + 'static,
+    {
+        if let Some(local) = unsafe { self.local.as_ref() } {
+            if !local.make_room(self) {
+                return Err(f);
+            }
+        }
+
+        self.defer(f);
+        Ok(())
+    }
+
+    /// Stores a function that frees roughly `bytes` bytes of memory so that it can be executed at
+    /// some point after all currently pinned threads get unpinned.
+    ///
+    /// This is the same as [`defer`], except that `bytes` is added to the estimated amount of
+    /// garbage reported by [`Collector::stats`] until the function is executed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch as epoch;
+    ///
+    /// let buffer = vec![0u8; 1024];
+    ///
+    /// let guard = &epoch::pin();
+    /// guard.defer_sized(buffer.capacity(), move || drop(buffer));
+    /// ```
+    ///
+    /// [`defer`]: Guard::defer
+    pub fn defer_sized<F, R>(&self, bytes: usize, f: F)
+    where
+        F: FnOnce() -> R,
+        F: Send + 'static,
+    {
+        unsafe {
+            self.defer_sized_unchecked(bytes, f);
+        }
+    }
+
+    /// Stores a function that frees roughly `bytes` bytes of memory so that it can be executed at
+    /// some point after all currently pinned threads get unpinned.
+    ///
+    /// This is the same as [`defer_unchecked`], except that `bytes` is added to the estimated
+    /// amount of garbage reported by [`Collector::stats`] until the function is executed.
+    ///
+    /// # Safety
+    ///
+    /// The same rules as for [`defer_unchecked`] apply.
+    ///
+    /// [`defer_unchecked`]: Guard::defer_unchecked
+    pub unsafe fn defer_sized_unchecked<F, R>(&self, bytes: usize, f: F)
     where
         F: FnOnce() -> R,
     {
         if let Some(local) = self.local.as_ref() {
-            local.defer(Deferred::new(move || drop(f())), self);
+            #[cfg(feature = "leak-check")]
+            let f = local.global().track(f);
+            local.defer(Deferred::new(move || drop(f())), bytes, self);
         } else {
             drop(f());
         }
@@ -212,6 +330,8 @@ impl Guard {
     /// If this method is called from an [`unprotected`] guard, the destructor will simply be
     /// executed immediately.
     ///
+    /// The size of `T` is counted as garbage in [`Collector::stats`] until the destructor runs.
+    ///
     /// # Safety
     ///
     /// The object must not be reachable by other threads anymore, otherwise it might be still in
@@ -265,7 +385,23 @@ impl Guard {
     /// }
     /// ```
     pub unsafe fn defer_destroy<T>(&self, ptr: Shared<'_, T>) {
-        self.defer_unchecked(move || ptr.into_owned());
+        self.defer_sized_unchecked(mem::size_of::<T>(), move || ptr.into_owned());
+    }
+
+    /// Like [`defer_destroy`], but for the collector's own data structures, whose garbage isn't
+    /// tracked by the `leak-check` feature.
+    ///
+    /// [`defer_destroy`]: Guard::defer_destroy
+    pub(crate) unsafe fn defer_destroy_internal<T>(&self, ptr: Shared<'_, T>) {
+        if let Some(local) = self.local.as_ref() {
+            local.defer(
+                Deferred::new(move || drop(ptr.into_owned())),
+                mem::size_of::<T>(),
+                self,
+            );
+        } else {
+            drop(ptr.into_owned());
+        }
     }
 
     /// Clears up the thread-local cache of deferred functions by executing them or moving into the
@@ -301,6 +437,10 @@ impl Guard {
     /// the call (the latter is enforced by `&mut self`). The thread will only be repinned if this
     /// is the only active guard for the current thread.
     ///
+    /// Repinning is cheap, especially when the global epoch hasn't advanced, so long-running loops
+    /// can call it every few iterations to let garbage be reclaimed. See [`RepinEvery`] for a
+    /// helper that does this automatically.
+    ///
     /// If this method is called from an [`unprotected`] guard, then the call will be just no-op.
     ///
     /// # Examples
@@ -415,6 +555,115 @@ impl fmt::Debug for Guard {
     }
 }
 
+/// A guard that repins the thread after a fixed number of operations.
+///
+/// Long-running loops that keep a thread pinned prevent the global epoch from advancing, so no
+/// garbage can be reclaimed until they finish. `RepinEvery` wraps a [`Guard`] and counts
+/// operations with [`tick`]; every `period` ticks it calls [`Guard::repin`].
+///
+/// `RepinEvery` dereferences to [`Guard`], so it can be used wherever a guard is expected. Since
+/// [`tick`] takes `&mut self`, no references obtained through the guard can be held across it.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_epoch::{self as epoch, Atomic, Owned, RepinEvery};
+/// use std::sync::atomic::Ordering::SeqCst;
+///
+/// let a = Atomic::new(0);
+/// let mut guard = RepinEvery::new(epoch::pin(), 64);
+///
+/// for i in 1..=1000 {
+///     let old = a.swap(Owned::new(i), SeqCst, &guard);
+///     unsafe { guard.defer_destroy(old) };
+///
+///     // Without this, none of the replaced values could be freed until the loop ends.
+///     guard.tick();
+/// }
+/// # unsafe { drop(a.into_owned()); }
+/// ```
+///
+/// [`tick`]: RepinEvery::tick
+pub struct RepinEvery {
+    /// The wrapped guard.
+    guard: Guard,
+
+    /// The number of operations between two repins.
+    period: usize,
+
+    /// The number of operations since the last repin.
+    count: usize,
+}
+
+impl RepinEvery {
+    /// Wraps `guard` so that it is repinned after every `period` operations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `period` is zero.
+    pub fn new(guard: Guard, period: usize) -> RepinEvery {
+        assert!(period > 0, "period must be positive");
+        RepinEvery {
+            guard,
+            period,
+            count: 0,
+        }
+    }
+
+    /// Counts one operation, repinning the thread if `period` operations have been counted since
+    /// the last repin.
+    ///
+    /// Returns `true` if the guard was repinned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::{self as epoch, RepinEvery};
+    ///
+    /// let mut guard = RepinEvery::new(epoch::pin(), 2);
+    /// assert!(!guard.tick());
+    /// assert!(guard.tick());
+    /// assert!(!guard.tick());
+    /// ```
+    pub fn tick(&mut self) -> bool {
+        self.count += 1;
+        if self.count < self.period {
+            return false;
+        }
+
+        self.count = 0;
+        self.guard.repin();
+        true
+    }
+
+    /// Returns the number of operations between two repins.
+    pub fn period(&self) -> usize {
+        self.period
+    }
+
+    /// Unwraps the guard.
+    pub fn into_inner(self) -> Guard {
+        self.guard
+    }
+}
+
+impl Deref for RepinEvery {
+    type Target = Guard;
+
+    fn deref(&self) -> &Guard {
+        &self.guard
+    }
+}
+
+impl fmt::Debug for RepinEvery {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RepinEvery")
+            .field("period", &self.period)
+            .field("count", &self.count)
+            .finish()
+    }
+}
+
 /// Returns a reference to a dummy guard that allows unprotected access to [`Atomic`]s.
 ///
 /// This guard should be used in special occasions only. Note that it doesn't actually keep any
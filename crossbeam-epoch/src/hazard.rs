@@ -0,0 +1,468 @@
+//! Hazard-pointer-based memory reclamation.
+//!
+//! Epoch-based reclamation protects everything a thread loads while it is pinned, but a single
+//! thread that stays pinned for a long time keeps all garbage from being destroyed. Hazard
+//! pointers protect objects one at a time instead: before dereferencing a pointer, a thread
+//! publishes it in a hazard pointer slot, and retired objects are only destroyed once no slot
+//! holds their address. The number of objects that can't be destroyed is therefore bounded by the
+//! number of slots, no matter how long any thread holds on to them.
+//!
+//! The price is that every protected load needs a full fence and has to be validated, so hazard
+//! pointers are slower than pinning when many objects are traversed.
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam_epoch::{Atomic, HazardDomain, Owned};
+//! use std::sync::atomic::Ordering::SeqCst;
+//!
+//! let domain = HazardDomain::new();
+//! let a = Atomic::new(1);
+//!
+//! let mut hazard = domain.hazard();
+//! let p = hazard.protect(&a, SeqCst);
+//!
+//! // Unlink the object and retire it. It stays alive as long as `hazard` protects it.
+//! let guard = unsafe { crossbeam_epoch::unprotected() };
+//! let old = a.swap(Owned::new(2), SeqCst, guard);
+//! unsafe { domain.retire(old) };
+//! assert_eq!(unsafe { *p.deref() }, 1);
+//!
+//! hazard.reset();
+//! domain.reclaim();
+//! # unsafe { drop(a.into_owned()) };
+//! ```
+
+use alloc::vec::Vec;
+use core::cmp;
+use core::fmt;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crate::atomic::{Atomic, Owned, Pointer, Shared};
+use crate::deferred::Deferred;
+use crate::guard::unprotected;
+use crate::primitive::sync::atomic::{self, AtomicUsize};
+
+/// A hazard pointer slot.
+struct Slot {
+    /// The address of the protected object, or 0 if nothing is protected.
+    ptr: AtomicUsize,
+
+    /// Whether the slot is owned by a `HazardPointer`.
+    active: AtomicBool,
+
+    /// The next slot in the domain. Slots are never removed until the domain is dropped.
+    next: Atomic<Slot>,
+}
+
+/// An object that was unlinked and is waiting to be destroyed.
+struct Retired {
+    /// The address of the object.
+    ptr: usize,
+
+    /// The function that destroys the object.
+    deferred: Deferred,
+
+    /// The next retired object.
+    next: Atomic<Retired>,
+}
+
+/// A set of hazard pointers and the objects they protect from being destroyed.
+///
+/// Objects retired into a domain are destroyed once no hazard pointer of the same domain protects
+/// them. Retired objects are buffered and destroyed in batches: whenever their number exceeds
+/// twice the number of hazard pointers (but at least a small constant), the retiring thread scans
+/// the hazard pointers and destroys every object that isn't protected. This bounds the amount of
+/// garbage, even if some thread keeps an object protected indefinitely.
+///
+/// Unlike pinning a [`Collector`], retiring objects doesn't need any per-thread registration, and
+/// a [`HazardPointer`] can be acquired from any thread at any time.
+///
+/// [`Collector`]: crate::Collector
+pub struct HazardDomain {
+    /// The list of hazard pointer slots.
+    slots: Atomic<Slot>,
+
+    /// The number of slots in `slots`.
+    num_slots: AtomicUsize,
+
+    /// The stack of retired objects.
+    retired: Atomic<Retired>,
+
+    /// The number of objects in `retired`.
+    num_retired: AtomicUsize,
+}
+
+// Retired objects are only destroyed once, by whichever thread scans them, which is the same
+// contract `Guard::defer_destroy` imposes on its callers.
+unsafe impl Send for HazardDomain {}
+unsafe impl Sync for HazardDomain {}
+
+impl HazardDomain {
+    /// The minimum number of retired objects that triggers a scan.
+    const RETIRE_THRESHOLD: usize = 64;
+
+    /// Creates a new domain.
+    pub fn new() -> Self {
+        HazardDomain {
+            slots: Atomic::null(),
+            num_slots: AtomicUsize::new(0),
+            retired: Atomic::null(),
+            num_retired: AtomicUsize::new(0),
+        }
+    }
+
+    /// Acquires a hazard pointer.
+    ///
+    /// Slots of dropped hazard pointers are reused, so the number of slots in the domain is the
+    /// maximum number of hazard pointers that existed at the same time.
+    pub fn hazard(&self) -> HazardPointer<'_> {
+        let guard = unsafe { unprotected() };
+
+        let mut slot = self.slots.load(Ordering::Acquire, guard);
+        while let Some(s) = unsafe { slot.as_ref() } {
+            if !s.active.load(Ordering::Relaxed)
+                && s.active
+                    .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                    .is_ok()
+            {
+                return HazardPointer { slot: s };
+            }
+            slot = s.next.load(Ordering::Acquire, guard);
+        }
+
+        let new = Owned::new(Slot {
+            ptr: AtomicUsize::new(0),
+            active: AtomicBool::new(true),
+            next: Atomic::null(),
+        })
+        .into_shared(guard);
+        let s = unsafe { new.deref() };
+
+        let mut head = self.slots.load(Ordering::Relaxed, guard);
+        loop {
+            s.next.store(head, Ordering::Relaxed);
+            match self.slots.compare_exchange(
+                head,
+                new,
+                Ordering::Release,
+                Ordering::Relaxed,
+                guard,
+            ) {
+                Ok(_) => break,
+                Err(err) => head = err.current,
+            }
+        }
+        self.num_slots.fetch_add(1, Ordering::Relaxed);
+
+        HazardPointer { slot: s }
+    }
+
+    /// Retires an object so that it gets destroyed once no hazard pointer protects it.
+    ///
+    /// This may destroy some previously retired objects, including `ptr` itself if it isn't
+    /// protected.
+    ///
+    /// # Safety
+    ///
+    /// The object must have been unlinked so that no thread can start protecting it anymore, and
+    /// it must not be retired (or destroyed in any other way) more than once. Threads that access
+    /// the object must have protected it with a hazard pointer of this domain. As with
+    /// [`Guard::defer_destroy`], the object may be destroyed on another thread.
+    ///
+    /// [`Guard::defer_destroy`]: crate::Guard::defer_destroy
+    pub unsafe fn retire<T>(&self, ptr: Shared<'_, T>) {
+        let guard = unprotected();
+        let retired = Owned::new(Retired {
+            ptr: ptr.as_raw() as usize,
+            deferred: Deferred::new(move || drop(ptr.into_owned())),
+            next: Atomic::null(),
+        })
+        .into_shared(guard);
+
+        // Count the object before pushing it, so that a concurrent scan never subtracts more
+        // objects than were counted.
+        let num_retired = self.num_retired.fetch_add(1, Ordering::Relaxed) + 1;
+        self.push_retired(retired, retired);
+
+        let threshold = cmp::max(
+            Self::RETIRE_THRESHOLD,
+            2 * self.num_slots.load(Ordering::Relaxed),
+        );
+        if num_retired >= threshold {
+            self.scan();
+        }
+    }
+
+    /// Destroys all retired objects that aren't protected by any hazard pointer.
+    pub fn reclaim(&self) {
+        self.scan();
+    }
+
+    /// Pushes the list of retired objects from `head` to `tail` onto the stack.
+    fn push_retired(&self, head: Shared<'_, Retired>, tail: Shared<'_, Retired>) {
+        let guard = unsafe { unprotected() };
+        let tail = unsafe { tail.deref() };
+
+        let mut current = self.retired.load(Ordering::Relaxed, guard);
+        loop {
+            tail.next.store(current, Ordering::Relaxed);
+            match self.retired.compare_exchange(
+                current,
+                head,
+                Ordering::Release,
+                Ordering::Relaxed,
+                guard,
+            ) {
+                Ok(_) => break,
+                Err(err) => current = err.current,
+            }
+        }
+    }
+
+    /// Destroys the retired objects that aren't protected and puts back the rest.
+    fn scan(&self) {
+        let guard = unsafe { unprotected() };
+
+        let mut list = self.retired.swap(Shared::null(), Ordering::Acquire, guard);
+        if list.is_null() {
+            return;
+        }
+
+        // Pairs with the fence in `HazardPointer::protect`: either the protecting thread sees
+        // that the object was unlinked, or we see its hazard pointer.
+        atomic::fence(Ordering::SeqCst);
+
+        let mut protected = Vec::new();
+        let mut slot = self.slots.load(Ordering::Acquire, guard);
+        while let Some(s) = unsafe { slot.as_ref() } {
+            let ptr = s.ptr.load(Ordering::Acquire);
+            if ptr != 0 {
+                protected.push(ptr);
+            }
+            slot = s.next.load(Ordering::Acquire, guard);
+        }
+        protected.sort_unstable();
+
+        let mut kept = Shared::null();
+        let mut kept_tail = Shared::null();
+        let mut destroyed = 0;
+
+        while let Some(r) = unsafe { list.as_ref() } {
+            let next = r.next.load(Ordering::Relaxed, guard);
+
+            if protected.binary_search(&r.ptr).is_ok() {
+                r.next.store(kept, Ordering::Relaxed);
+                if kept.is_null() {
+                    kept_tail = list;
+                }
+                kept = list;
+            } else {
+                let retired = unsafe { list.into_owned() }.into_box();
+                retired.deferred.call();
+                destroyed += 1;
+            }
+
+            list = next;
+        }
+
+        self.num_retired.fetch_sub(destroyed, Ordering::Relaxed);
+        if !kept.is_null() {
+            self.push_retired(kept, kept_tail);
+        }
+    }
+}
+
+impl Default for HazardDomain {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Drop for HazardDomain {
+    fn drop(&mut self) {
+        unsafe {
+            // Hazard pointers borrow the domain, so nothing is protected anymore.
+            let guard = unprotected();
+
+            let mut retired = self.retired.load(Ordering::Relaxed, guard);
+            while !retired.is_null() {
+                let r = retired.into_owned().into_box();
+                retired = r.next.load(Ordering::Relaxed, guard);
+                r.deferred.call();
+            }
+
+            let mut slot = self.slots.load(Ordering::Relaxed, guard);
+            while !slot.is_null() {
+                let s = slot.into_owned();
+                slot = s.next.load(Ordering::Relaxed, guard);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for HazardDomain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("HazardDomain { .. }")
+    }
+}
+
+/// A hazard pointer, which protects a single object from being destroyed.
+///
+/// A hazard pointer is acquired with [`HazardDomain::hazard`] and protects at most one object at a
+/// time. Protecting a new object, calling [`reset`], or dropping the hazard pointer releases the
+/// previous one.
+///
+/// [`reset`]: HazardPointer::reset
+pub struct HazardPointer<'d> {
+    slot: &'d Slot,
+}
+
+impl HazardPointer<'_> {
+    /// Loads a pointer from `atomic` and protects the object it points to.
+    ///
+    /// The returned pointer can be dereferenced until this hazard pointer protects something else
+    /// or gets dropped, provided that objects unlinked from `atomic` are retired into the domain
+    /// this hazard pointer belongs to.
+    ///
+    /// This method repeatedly loads `atomic` until the loaded value is stable, so it may spin for
+    /// a while under heavy contention. `ord` is used for the final load.
+    pub fn protect<T>(&mut self, atomic: &Atomic<T>, ord: Ordering) -> Shared<'_, T> {
+        let guard = unsafe { unprotected() };
+
+        let mut current = atomic.load(Ordering::Relaxed, guard);
+        loop {
+            self.slot
+                .ptr
+                .store(current.as_raw() as usize, Ordering::Relaxed);
+            atomic::fence(Ordering::SeqCst);
+
+            let new = atomic.load(ord, guard);
+            if new == current {
+                return unsafe { Shared::from_usize(new.into_usize()) };
+            }
+            current = new;
+        }
+    }
+
+    /// Stops protecting the current object, if any.
+    pub fn reset(&mut self) {
+        self.slot.ptr.store(0, Ordering::Release);
+    }
+}
+
+impl Drop for HazardPointer<'_> {
+    fn drop(&mut self) {
+        self.reset();
+        self.slot.active.store(false, Ordering::Release);
+    }
+}
+
+impl fmt::Debug for HazardPointer<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("HazardPointer { .. }")
+    }
+}
+
+#[cfg(all(test, not(crossbeam_loom)))]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    use crossbeam_utils::thread;
+
+    use super::HazardDomain;
+    use crate::{unprotected, Atomic, Owned};
+
+    #[test]
+    fn protect_delays_destruction() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Elem;
+
+        impl Drop for Elem {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let domain = HazardDomain::new();
+        let a = Atomic::new(Elem);
+        let guard = unsafe { unprotected() };
+
+        let mut hazard = domain.hazard();
+        hazard.protect(&a, Ordering::SeqCst);
+
+        let old = a.swap(Owned::new(Elem), Ordering::SeqCst, guard);
+        unsafe { domain.retire(old) };
+        domain.reclaim();
+        assert_eq!(DROPS.load(Ordering::Relaxed), 0);
+
+        drop(hazard);
+        domain.reclaim();
+        assert_eq!(DROPS.load(Ordering::Relaxed), 1);
+
+        unsafe { drop(a.into_owned()) };
+        assert_eq!(DROPS.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn bounded_garbage() {
+        let domain = HazardDomain::new();
+        let a = Atomic::new(0);
+        let guard = unsafe { unprotected() };
+
+        let mut hazard = domain.hazard();
+        hazard.protect(&a, Ordering::SeqCst);
+
+        for i in 1..1000 {
+            let old = a.swap(Owned::new(i), Ordering::SeqCst, guard);
+            unsafe { domain.retire(old) };
+            assert!(domain.num_retired.load(Ordering::Relaxed) <= HazardDomain::RETIRE_THRESHOLD);
+        }
+
+        unsafe { drop(a.into_owned()) };
+    }
+
+    #[test]
+    fn reuse_slots() {
+        let domain = HazardDomain::new();
+
+        for _ in 0..10 {
+            let _a = domain.hazard();
+            let _b = domain.hazard();
+        }
+        assert_eq!(domain.num_slots.load(Ordering::Relaxed), 2);
+    }
+
+    #[test]
+    fn stress() {
+        const THREADS: usize = 8;
+        const COUNT: usize = 10_000;
+
+        let domain = HazardDomain::new();
+        let a = Atomic::new(0usize);
+
+        thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|_| {
+                    let mut hazard = domain.hazard();
+                    for _ in 0..COUNT {
+                        let p = hazard.protect(&a, Ordering::Acquire);
+                        assert!(unsafe { *p.deref() } <= COUNT);
+                    }
+                });
+            }
+
+            scope.spawn(|_| {
+                let guard = unsafe { unprotected() };
+                for i in 1..=COUNT {
+                    let old = a.swap(Owned::new(i), Ordering::AcqRel, guard);
+                    unsafe { domain.retire(old) };
+                }
+            });
+        })
+        .unwrap();
+
+        unsafe { drop(a.into_owned()) };
+    }
+}
@@ -36,8 +36,10 @@
 //! destroyed as soon as the data structure gets dropped.
 
 use crate::primitive::cell::UnsafeCell;
-use crate::primitive::sync::atomic;
+use crate::primitive::sync::atomic::{self, AtomicUsize};
+use alloc::sync::Arc;
 use core::cell::Cell;
+use core::cmp;
 use core::mem::{self, ManuallyDrop};
 use core::num::Wrapping;
 use core::sync::atomic::Ordering;
@@ -47,7 +49,7 @@ use crossbeam_utils::CachePadded;
 use memoffset::offset_of;
 
 use crate::atomic::{Owned, Shared};
-use crate::collector::{Collector, LocalHandle};
+use crate::collector::{Collector, CollectorStats, LocalHandle, Observer};
 use crate::deferred::Deferred;
 use crate::epoch::{AtomicEpoch, Epoch};
 use crate::guard::{unprotected, Guard};
@@ -56,15 +58,18 @@ use crate::sync::queue::Queue;
 
 /// Maximum number of objects a bag can contain.
 #[cfg(not(crossbeam_sanitize))]
-const MAX_OBJECTS: usize = 62;
+pub(crate) const MAX_OBJECTS: usize = 62;
 #[cfg(crossbeam_sanitize)]
-const MAX_OBJECTS: usize = 4;
+pub(crate) const MAX_OBJECTS: usize = 4;
 
 /// A bag of deferred functions.
 pub(crate) struct Bag {
     /// Stashed objects.
     deferreds: [Deferred; MAX_OBJECTS],
     len: usize,
+
+    /// Estimated number of bytes freed by the stashed objects.
+    bytes: usize,
 }
 
 /// `Bag::try_push()` requires that it is safe for another thread to execute the given functions.
@@ -83,16 +88,23 @@ impl Bag {
 
     /// Attempts to insert a deferred function into the bag.
     ///
+    /// `bytes` is the estimated number of bytes the function frees.
+    ///
     /// Returns `Ok(())` if successful, and `Err(deferred)` for the given `deferred` if the bag is
     /// full.
     ///
     /// # Safety
     ///
     /// It should be safe for another thread to execute the given function.
-    pub(crate) unsafe fn try_push(&mut self, deferred: Deferred) -> Result<(), Deferred> {
+    pub(crate) unsafe fn try_push(
+        &mut self,
+        deferred: Deferred,
+        bytes: usize,
+    ) -> Result<(), Deferred> {
         if self.len < MAX_OBJECTS {
             self.deferreds[self.len] = deferred;
             self.len += 1;
+            self.bytes = self.bytes.saturating_add(bytes);
             Ok(())
         } else {
             Err(deferred)
@@ -112,6 +124,7 @@ impl Default for Bag {
         #[cfg(not(crossbeam_sanitize))]
         return Bag {
             len: 0,
+            bytes: 0,
             deferreds: [
                 Deferred::new(no_op_func),
                 Deferred::new(no_op_func),
@@ -180,6 +193,7 @@ impl Default for Bag {
         #[cfg(crossbeam_sanitize)]
         return Bag {
             len: 0,
+            bytes: 0,
             deferreds: [
                 Deferred::new(no_op_func),
                 Deferred::new(no_op_func),
@@ -241,26 +255,110 @@ pub(crate) struct Global {
 
     /// The global epoch.
     pub(crate) epoch: CachePadded<AtomicEpoch>,
+
+    /// The number of registered participants.
+    ///
+    /// This is read whenever a participant gets unpinned with garbage in its bag, so it is kept
+    /// apart from the counters below, which are modified every time a bag is pushed.
+    participants: CachePadded<AtomicUsize>,
+
+    /// The number of deferred functions in the global queue.
+    deferred: CachePadded<AtomicUsize>,
+
+    /// The estimated number of bytes freed by deferred functions in the global queue.
+    deferred_bytes: CachePadded<AtomicUsize>,
+
+    /// One less than the number of pinnings after which a participant will execute some deferred
+    /// functions from the global queue.
+    ///
+    /// The number of pinnings is a power of two, so this masks the pin counter.
+    collect_mask: usize,
+
+    /// Number of deferred functions after which a local bag is moved into the global queue.
+    bag_capacity: usize,
+
+    /// Number of deferred functions in the global queue above which participants collect all
+    /// expired garbage whenever they get pinned.
+    max_garbage: Option<usize>,
+
+    /// Callbacks notified as garbage moves through the global queue.
+    observer: Option<Arc<dyn Observer>>,
+
+    /// The number of deferred functions that haven't been executed yet, excluding the garbage of
+    /// the collector's own data structures.
+    #[cfg(feature = "leak-check")]
+    outstanding: AtomicUsize,
 }
 
 impl Global {
     /// Number of bags to destroy.
     const COLLECT_STEPS: usize = 8;
 
-    /// Creates a new global data for garbage collection.
+    /// Creates a new global data for garbage collection with the given collection frequency and
+    /// bag capacity.
     #[inline]
-    pub(crate) fn new() -> Self {
+    pub(crate) fn new(
+        pinnings_between_collect: usize,
+        bag_capacity: usize,
+        max_garbage: Option<usize>,
+        observer: Option<Arc<dyn Observer>>,
+    ) -> Self {
+        debug_assert!(pinnings_between_collect.is_power_of_two());
+        debug_assert!(bag_capacity > 0 && bag_capacity <= MAX_OBJECTS);
+
         Self {
             locals: List::new(),
             queue: Queue::new(),
             epoch: CachePadded::new(AtomicEpoch::new(Epoch::starting())),
+            participants: CachePadded::new(AtomicUsize::new(0)),
+            deferred: CachePadded::new(AtomicUsize::new(0)),
+            deferred_bytes: CachePadded::new(AtomicUsize::new(0)),
+            collect_mask: pinnings_between_collect - 1,
+            bag_capacity,
+            max_garbage,
+            observer,
+            #[cfg(feature = "leak-check")]
+            outstanding: AtomicUsize::new(0),
+        }
+    }
+
+    /// Wraps `f` so that it is counted as outstanding garbage until it gets executed.
+    #[cfg(feature = "leak-check")]
+    pub(crate) fn track<F, R>(&self, f: F) -> impl FnOnce() -> R
+    where
+        F: FnOnce() -> R,
+    {
+        self.outstanding.fetch_add(1, Ordering::Relaxed);
+
+        // Deferred functions are executed while the `Global` is still alive, either while
+        // collecting garbage or while dropping the global queue.
+        let outstanding = &self.outstanding as *const AtomicUsize;
+        move || {
+            let result = f();
+            unsafe { (*outstanding).fetch_sub(1, Ordering::Relaxed) };
+            result
         }
     }
 
+    /// Returns the number of deferred functions that haven't been executed yet.
+    #[cfg(feature = "leak-check")]
+    pub(crate) fn outstanding(&self) -> usize {
+        self.outstanding.load(Ordering::Relaxed)
+    }
+
     /// Pushes the bag into the global queue and replaces the bag with a new empty bag.
     pub(crate) fn push_bag(&self, bag: &mut Bag, guard: &Guard) {
         let bag = mem::replace(bag, Bag::new());
 
+        self.deferred.fetch_add(bag.len, Ordering::Relaxed);
+        self.deferred_bytes.fetch_add(bag.bytes, Ordering::Relaxed);
+
+        if let Some(observer) = &self.observer {
+            if !bag.is_empty() {
+                observer.deferred(bag.len, bag.bytes);
+            }
+        }
+
         atomic::fence(Ordering::SeqCst);
 
         let epoch = self.epoch.load(Ordering::Relaxed);
@@ -276,23 +374,82 @@ impl Global {
     /// `collect()` is not called.
     #[cold]
     pub(crate) fn collect(&self, guard: &Guard) {
-        let global_epoch = self.try_advance(guard);
-
         let steps = if cfg!(crossbeam_sanitize) {
             usize::max_value()
         } else {
             Self::COLLECT_STEPS
         };
 
+        self.collect_bags(steps, guard);
+    }
+
+    /// Attempts to advance the global epoch, and then destroys at most `steps` expired bags from
+    /// the global queue.
+    ///
+    /// Returns the number of deferred functions that were executed.
+    pub(crate) fn collect_bags(&self, steps: usize, guard: &Guard) -> usize {
+        let global_epoch = self.try_advance(guard);
+        let mut executed = 0;
+
         for _ in 0..steps {
             match self.queue.try_pop_if(
                 &|sealed_bag: &SealedBag| sealed_bag.is_expired(global_epoch),
                 guard,
             ) {
                 None => break,
-                Some(sealed_bag) => drop(sealed_bag),
+                Some(sealed_bag) => {
+                    self.deferred
+                        .fetch_sub(sealed_bag.bag.len, Ordering::Relaxed);
+                    self.deferred_bytes
+                        .fetch_sub(sealed_bag.bag.bytes, Ordering::Relaxed);
+                    executed += sealed_bag.bag.len;
+
+                    let (len, bytes) = (sealed_bag.bag.len, sealed_bag.bag.bytes);
+                    let epochs = global_epoch.wrapping_sub(sealed_bag.epoch) as usize;
+                    drop(sealed_bag);
+
+                    if let Some(observer) = &self.observer {
+                        observer.reclaimed(len, bytes, epochs);
+                    }
+                }
             }
         }
+
+        executed
+    }
+
+    /// Returns `true` if the global queue holds more deferred functions than the configured
+    /// limit.
+    #[inline]
+    pub(crate) fn is_over_limit(&self) -> bool {
+        match self.max_garbage {
+            Some(max) => self.deferred.load(Ordering::Relaxed) > max,
+            None => false,
+        }
+    }
+
+    /// Returns statistics about the garbage in the global queue.
+    pub(crate) fn stats(&self) -> CollectorStats {
+        CollectorStats {
+            deferred: self.deferred.load(Ordering::Relaxed),
+            deferred_bytes: self.deferred_bytes.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Counts the bags in the global queue by their age in epochs.
+    pub(crate) fn bags(&self, guard: &Guard) -> [usize; 3] {
+        let global_epoch = self.epoch.load(Ordering::Relaxed);
+        let mut bags = [0; 3];
+
+        self.queue.for_each(
+            |sealed_bag: &SealedBag| {
+                let age = global_epoch.wrapping_sub(sealed_bag.epoch);
+                bags[cmp::min(age.max(0) as usize, 2)] += 1;
+            },
+            guard,
+        );
+
+        bags
     }
 
     /// Attempts to advance the global epoch.
@@ -325,6 +482,9 @@ impl Global {
                     // If the participant was pinned in a different epoch, we cannot advance the
                     // global epoch just yet.
                     if local_epoch.is_pinned() && local_epoch.unpinned() != global_epoch {
+                        if let Some(observer) = &self.observer {
+                            observer.advance_blocked();
+                        }
                         return global_epoch;
                     }
                 }
@@ -345,6 +505,25 @@ impl Global {
     }
 }
 
+#[cfg(feature = "leak-check")]
+impl Drop for Global {
+    fn drop(&mut self) {
+        // Execute all remaining garbage before checking that nothing was lost.
+        unsafe {
+            let guard = unprotected();
+            while self.queue.try_pop(guard).is_some() {}
+        }
+
+        let outstanding = self.outstanding();
+        if outstanding != 0 && !std::thread::panicking() {
+            panic!(
+                "collector dropped with {} deferred functions that were never executed",
+                outstanding
+            );
+        }
+    }
+}
+
 /// Participant for garbage collection.
 pub(crate) struct Local {
     /// A node in the intrusive linked list of `Local`s.
@@ -365,7 +544,12 @@ pub(crate) struct Local {
     guard_count: Cell<usize>,
 
     /// The number of active handles.
-    handle_count: Cell<usize>,
+    ///
+    /// This is a `u32` so that it shares a word with `bag_capacity`.
+    handle_count: Cell<u32>,
+
+    /// Number of deferred functions after which the local bag is moved into the global queue.
+    bag_capacity: Cell<u32>,
 
     /// Total number of pinnings performed.
     ///
@@ -385,9 +569,9 @@ fn local_size() {
 }
 
 impl Local {
-    /// Number of pinnings after which a participant will execute some deferred functions from the
-    /// global queue.
-    const PINNINGS_BETWEEN_COLLECT: usize = 128;
+    /// Default number of pinnings after which a participant will execute some deferred functions
+    /// from the global queue.
+    pub(crate) const PINNINGS_BETWEEN_COLLECT: usize = 128;
 
     /// Registers a new `Local` in the provided `Global`.
     pub(crate) fn register(collector: &Collector) -> LocalHandle {
@@ -401,9 +585,14 @@ impl Local {
                 bag: UnsafeCell::new(Bag::new()),
                 guard_count: Cell::new(0),
                 handle_count: Cell::new(1),
+                bag_capacity: Cell::new(collector.global.bag_capacity as u32),
                 pin_count: Cell::new(Wrapping(0)),
             })
             .into_shared(unprotected());
+            collector
+                .global
+                .participants
+                .fetch_add(1, Ordering::Relaxed);
             collector.global.locals.insert(local, unprotected());
             LocalHandle {
                 local: local.as_raw(),
@@ -429,20 +618,51 @@ impl Local {
         self.guard_count.get() > 0
     }
 
+    /// Returns the number of deferred functions after which the local bag is moved into the
+    /// global queue.
+    #[inline]
+    pub(crate) fn bag_capacity(&self) -> usize {
+        self.bag_capacity.get() as usize
+    }
+
+    /// Sets the number of deferred functions after which the local bag is moved into the global
+    /// queue.
+    pub(crate) fn set_bag_capacity(&self, capacity: usize) {
+        debug_assert!(capacity > 0 && capacity <= MAX_OBJECTS);
+        self.bag_capacity.set(capacity as u32);
+    }
+
     /// Adds `deferred` to the thread-local bag.
     ///
     /// # Safety
     ///
     /// It should be safe for another thread to execute the given function.
-    pub(crate) unsafe fn defer(&self, mut deferred: Deferred, guard: &Guard) {
+    pub(crate) unsafe fn defer(&self, mut deferred: Deferred, bytes: usize, guard: &Guard) {
         let bag = self.bag.with_mut(|b| &mut *b);
 
-        while let Err(d) = bag.try_push(deferred) {
+        if bag.len >= self.bag_capacity() {
+            self.global().push_bag(bag, guard);
+        }
+
+        while let Err(d) = bag.try_push(deferred, bytes) {
             self.global().push_bag(bag, guard);
             deferred = d;
         }
     }
 
+    /// Checks whether the global queue can take more garbage, collecting all expired garbage
+    /// first if it holds more than the configured limit.
+    ///
+    /// Returns `false` if the queue is still over the limit afterwards.
+    pub(crate) fn make_room(&self, guard: &Guard) -> bool {
+        if !self.global().is_over_limit() {
+            return true;
+        }
+
+        self.global().collect_bags(usize::max_value(), guard);
+        !self.global().is_over_limit()
+    }
+
     pub(crate) fn flush(&self, guard: &Guard) {
         let bag = self.bag.with_mut(|b| unsafe { &mut *b });
 
@@ -453,6 +673,19 @@ impl Local {
         self.global().collect(guard);
     }
 
+    /// Moves the local bag into the global queue and destroys all expired bags.
+    ///
+    /// Returns the number of deferred functions that were executed.
+    pub(crate) fn try_collect(&self, guard: &Guard) -> usize {
+        let bag = self.bag.with_mut(|b| unsafe { &mut *b });
+
+        if !bag.is_empty() {
+            self.global().push_bag(bag, guard);
+        }
+
+        self.global().collect_bags(usize::max_value(), guard)
+    }
+
     /// Pins the `Local`.
     #[inline]
     pub(crate) fn pin(&self) -> Guard {
@@ -503,9 +736,12 @@ impl Local {
             let count = self.pin_count.get();
             self.pin_count.set(count + Wrapping(1));
 
-            // After every `PINNINGS_BETWEEN_COLLECT` try advancing the epoch and collecting
-            // some garbage.
-            if count.0 % Self::PINNINGS_BETWEEN_COLLECT == 0 {
+            // If too much garbage has piled up, try collecting all of it right away. Otherwise,
+            // after every `pinnings_between_collect` try advancing the epoch and collecting some
+            // garbage.
+            if self.global().is_over_limit() {
+                self.global().collect_bags(usize::max_value(), &guard);
+            } else if count.0 & self.global().collect_mask == 0 {
                 self.global().collect(&guard);
             }
         }
@@ -517,6 +753,14 @@ impl Local {
     #[inline]
     pub(crate) fn unpin(&self) {
         let guard_count = self.guard_count.get();
+
+        if guard_count == 1
+            && !self.bag.with(|b| unsafe { (*b).is_empty() })
+            && self.global().participants.load(Ordering::Relaxed) == 1
+        {
+            self.reclaim_if_alone();
+        }
+
         self.guard_count.set(guard_count - 1);
 
         if guard_count == 1 {
@@ -528,6 +772,38 @@ impl Local {
         }
     }
 
+    /// Executes the garbage in the local bag right away if this is the only participant
+    /// registered with the collector.
+    ///
+    /// This is called just before the last guard gets dropped. With no other participant, nobody
+    /// else can be holding a reference to the garbage: participants that register later can only
+    /// reach objects that haven't been unlinked yet.
+    #[cold]
+    fn reclaim_if_alone(&self) {
+        // The participant is still pinned by the guard being dropped, so this one must not unpin
+        // it again.
+        let guard = &*ManuallyDrop::new(Guard { local: self });
+
+        // The participant count is only a hint. Make sure that participants we don't see in the
+        // list will see the garbage as unlinked.
+        atomic::fence(Ordering::SeqCst);
+
+        for local in self.global().locals.iter(guard) {
+            match local {
+                Ok(local) if ptr::eq(local, self) => {}
+                _ => return,
+            }
+        }
+
+        let bag = mem::replace(self.bag.with_mut(|b| unsafe { &mut *b }), Bag::new());
+        let (len, bytes) = (bag.len, bag.bytes);
+        drop(bag);
+
+        if let Some(observer) = &self.global().observer {
+            observer.reclaimed(len, bytes, 0);
+        }
+    }
+
     /// Unpins and then pins the `Local`.
     #[inline]
     pub(crate) fn repin(&self) {
@@ -556,7 +832,7 @@ impl Local {
     pub(crate) fn acquire_handle(&self) {
         let handle_count = self.handle_count.get();
         debug_assert!(handle_count >= 1);
-        self.handle_count.set(handle_count + 1);
+        self.handle_count.set(handle_count.checked_add(1).unwrap());
     }
 
     /// Decrements the handle count.
@@ -597,6 +873,11 @@ impl Local {
             // `Local` as deleted.
             let collector: Collector = ptr::read(self.collector.with(|c| &*(*c)));
 
+            collector
+                .global
+                .participants
+                .fetch_sub(1, Ordering::Relaxed);
+
             // Mark this node in the linked list as deleted.
             self.entry.delete(unprotected());
 
@@ -622,7 +903,7 @@ impl IsElement<Local> for Local {
     }
 
     unsafe fn finalize(entry: &Entry, guard: &Guard) {
-        guard.defer_destroy(Shared::from(Self::element_of(entry) as *const _));
+        guard.defer_destroy_internal(Shared::from(Self::element_of(entry) as *const _));
     }
 }
 
@@ -656,12 +937,12 @@ mod tests {
         assert!(bag.is_empty());
 
         for _ in 0..MAX_OBJECTS {
-            assert!(unsafe { bag.try_push(Deferred::new(incr)).is_ok() });
+            assert!(unsafe { bag.try_push(Deferred::new(incr), 0).is_ok() });
             assert!(!bag.is_empty());
             assert_eq!(FLAG.load(Ordering::Relaxed), 0);
         }
 
-        let result = unsafe { bag.try_push(Deferred::new(incr)) };
+        let result = unsafe { bag.try_push(Deferred::new(incr), 0) };
         assert!(result.is_err());
         assert!(!bag.is_empty());
         assert_eq!(FLAG.load(Ordering::Relaxed), 0);
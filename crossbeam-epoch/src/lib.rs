@@ -43,6 +43,23 @@
 //! arbitrary function until the global epoch is advanced enough. Most notably, concurrent data
 //! structures may defer the deallocation of an object.
 //!
+//! # Hazard pointers
+//!
+//! A participant that stays pinned for a long time keeps all garbage from being destroyed. Data
+//! structures that can't afford this may protect individual objects with [`HazardPointer`]s
+//! instead, and retire garbage into a [`HazardDomain`]. The [`Reclaim`] trait abstracts over both
+//! schemes.
+//!
+//! # Atomic `Arc`s
+//!
+//! Read-mostly data that is shared through an `Arc` can be put into an [`AtomicArc`], which lets
+//! readers load the current `Arc` without blocking while writers replace it.
+//!
+//! # Read-copy-update
+//!
+//! An [`RcuCell`] holds versions of a value. Writers publish new versions and may register a
+//! callback that receives a replaced version once no reader can observe it anymore.
+//!
 //! # APIs
 //!
 //! For majority of use cases, just use the default garbage collector by invoking [`pin`]. If you
@@ -159,29 +176,37 @@ cfg_if! {
     if #[cfg(feature = "alloc")] {
         extern crate alloc;
 
+        mod arc;
         mod atomic;
         mod collector;
+        mod default;
         mod deferred;
         mod epoch;
         mod guard;
+        mod hazard;
         mod internal;
+        mod rcu;
+        mod reclaim;
         mod sync;
 
+        pub use self::arc::AtomicArc;
         pub use self::atomic::{
             Pointable, Atomic, CompareExchangeError,
-            Owned, Pointer, Shared,
+            Owned, Pointer, Shared, Slice, Unlinked,
         };
-        pub use self::collector::{Collector, LocalHandle};
-        pub use self::guard::{unprotected, Guard};
+        pub use self::collector::{
+            Collector, CollectorBuilder, CollectorStats, LocalHandle, Observer,
+        };
+        pub use self::default::{default_collector, is_pinned, pin, set_registry, Registry};
+        pub use self::guard::{unprotected, Guard, RepinEvery};
+        pub use self::hazard::{HazardDomain, HazardPointer};
+        pub use self::rcu::{RcuCell, RcuRef};
+        pub use self::reclaim::Reclaim;
+
+        #[cfg(has_min_const_generics)]
+        pub use self::atomic::Tagged;
 
         #[allow(deprecated)]
         pub use self::atomic::{CompareAndSetError, CompareAndSetOrdering};
     }
 }
-
-cfg_if! {
-    if #[cfg(feature = "std")] {
-        mod default;
-        pub use self::default::{default_collector, is_pinned, pin};
-    }
-}
@@ -0,0 +1,482 @@
+use core::fmt;
+use core::mem;
+use core::ops::Deref;
+use core::sync::atomic::Ordering;
+
+use crate::atomic::{Atomic, Owned};
+use crate::collector::Collector;
+use crate::default;
+use crate::guard::{unprotected, Guard};
+
+/// A version of the value in an [`RcuCell`].
+struct Node<T> {
+    /// The number of the version, counting from zero.
+    version: u64,
+
+    /// The value.
+    value: T,
+}
+
+/// A read-copy-update cell holding versions of a value.
+///
+/// Readers [`read`] the current version under a [`Guard`] without blocking, while writers
+/// [`store`] a new version that replaces it. A replaced version is not destroyed right away, but
+/// only after a _grace period_: once every thread that was pinned when it got replaced has been
+/// unpinned, so that no reader can observe it anymore.
+///
+/// Writers can also register a callback with [`store_with`], which receives the replaced value at
+/// the end of its grace period. This is useful when the value refers to external resources, such
+/// as file descriptors or connections to a backend, which must only be released after their last
+/// use.
+///
+/// Grace periods end as the global epoch advances. Callbacks run on whichever thread collects the
+/// garbage at that point, and they may be delayed for as long as the collector holds on to it.
+/// [`Guard::flush`] hands local garbage over to the collector sooner.
+///
+/// [`read`]: RcuCell::read
+/// [`store`]: RcuCell::store
+/// [`store_with`]: RcuCell::store_with
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_epoch::RcuCell;
+/// use std::sync::mpsc;
+///
+/// struct Config {
+///     backend: &'static str,
+/// }
+///
+/// let config = RcuCell::new(Config { backend: "10.0.0.1" });
+/// let (tx, rx) = mpsc::channel();
+///
+/// {
+///     let guard = &config.guard();
+///     let old = config.read(guard);
+///
+///     // Reload the configuration, and close the old backend once nobody uses it anymore.
+///     let version = config.store_with(Config { backend: "10.0.0.2" }, move |old| {
+///         tx.send(old.backend).unwrap();
+///     });
+///     assert_eq!(version, 1);
+///
+///     // Readers that got the old version can keep using it.
+///     assert_eq!(old.backend, "10.0.0.1");
+///     assert_eq!(config.read(guard).backend, "10.0.0.2");
+/// }
+///
+/// // The callback runs once the old version can no longer be observed.
+/// while rx.try_recv().is_err() {
+///     config.guard().flush();
+/// }
+/// ```
+pub struct RcuCell<T> {
+    /// The current version.
+    node: Atomic<Node<T>>,
+
+    /// The collector that replaced versions are retired into.
+    collector: Collector,
+}
+
+unsafe impl<T: Send + Sync> Send for RcuCell<T> {}
+unsafe impl<T: Send + Sync> Sync for RcuCell<T> {}
+
+impl<T> RcuCell<T> {
+    /// Creates a new cell whose first version, numbered zero, holds `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::RcuCell;
+    ///
+    /// let c = RcuCell::new(7);
+    /// assert_eq!(c.version(), 0);
+    /// ```
+    pub fn new(value: T) -> RcuCell<T> {
+        RcuCell {
+            node: Atomic::new(Node { version: 0, value }),
+            collector: default::default_collector().clone(),
+        }
+    }
+
+    /// Pins the current thread with the collector of the cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::RcuCell;
+    ///
+    /// let c = RcuCell::new(7);
+    /// let guard = &c.guard();
+    /// assert_eq!(*c.read(guard), 7);
+    /// ```
+    pub fn guard(&self) -> Guard {
+        let guard = default::pin();
+        if guard.collector() == Some(&self.collector) {
+            guard
+        } else {
+            self.collector.register().pin()
+        }
+    }
+
+    /// Returns the current version.
+    ///
+    /// The version stays valid for as long as `guard` is alive, even if it gets replaced in the
+    /// meantime.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `guard` doesn't belong to the collector that was the default one when the cell
+    /// was created.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::RcuCell;
+    ///
+    /// let c = RcuCell::new(7);
+    /// let guard = &c.guard();
+    ///
+    /// let v = c.read(guard);
+    /// c.store(8);
+    /// assert_eq!(*v, 7);
+    /// assert_eq!(v.version(), 0);
+    /// ```
+    pub fn read<'g>(&'g self, guard: &'g Guard) -> RcuRef<'g, T> {
+        if let Some(c) = guard.collector() {
+            assert!(c == &self.collector);
+        }
+        let node = self.node.load(Ordering::Acquire, guard);
+        RcuRef {
+            node: unsafe { node.deref() },
+        }
+    }
+
+    /// Returns the number of the current version.
+    ///
+    /// Every store increments the number by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::RcuCell;
+    ///
+    /// let c = RcuCell::new(7);
+    /// c.store(8);
+    /// c.store(9);
+    /// assert_eq!(c.version(), 2);
+    /// ```
+    pub fn version(&self) -> u64 {
+        let guard = &self.guard();
+        self.read(guard).version()
+    }
+
+    /// Returns a mutable reference to the value of the current version.
+    ///
+    /// Since this call borrows the cell mutably, no readers can observe the change.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::RcuCell;
+    ///
+    /// let mut c = RcuCell::new(7);
+    /// *c.get_mut() = 8;
+    /// assert_eq!(c.into_inner(), 8);
+    /// ```
+    pub fn get_mut(&mut self) -> &mut T {
+        unsafe {
+            let node = self.node.load(Ordering::Relaxed, unprotected());
+            &mut (*(node.as_raw() as *mut Node<T>)).value
+        }
+    }
+
+    /// Consumes the cell, returning the value of the current version.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::RcuCell;
+    ///
+    /// let c = RcuCell::new(7);
+    /// assert_eq!(c.into_inner(), 7);
+    /// ```
+    pub fn into_inner(mut self) -> T {
+        let node = mem::replace(&mut self.node, Atomic::null());
+        unsafe { node.into_owned().into_box().value }
+    }
+}
+
+impl<T: Send + 'static> RcuCell<T> {
+    /// Publishes `value` as a new version, returning its number.
+    ///
+    /// The replaced version is dropped at the end of its grace period.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::RcuCell;
+    ///
+    /// let c = RcuCell::new(7);
+    /// assert_eq!(c.store(8), 1);
+    ///
+    /// let guard = &c.guard();
+    /// assert_eq!(*c.read(guard), 8);
+    /// ```
+    pub fn store(&self, value: T) -> u64 {
+        self.store_with(value, drop)
+    }
+
+    /// Publishes `value` as a new version, returning its number.
+    ///
+    /// At the end of the grace period of the replaced version, `callback` is called with its
+    /// value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::RcuCell;
+    /// use std::sync::atomic::{AtomicBool, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let released = Arc::new(AtomicBool::new(false));
+    /// let c = RcuCell::new("old");
+    ///
+    /// let r = released.clone();
+    /// c.store_with("new", move |old| {
+    ///     assert_eq!(old, "old");
+    ///     r.store(true, Ordering::SeqCst);
+    /// });
+    ///
+    /// while !released.load(Ordering::SeqCst) {
+    ///     c.guard().flush();
+    /// }
+    /// ```
+    pub fn store_with<F>(&self, value: T, callback: F) -> u64
+    where
+        F: FnOnce(T) + Send + 'static,
+    {
+        let guard = &self.guard();
+        let mut new = Owned::new(Node { version: 0, value });
+        let mut current = self.node.load(Ordering::Acquire, guard);
+
+        loop {
+            let version = unsafe { current.deref().version } + 1;
+            new.version = version;
+
+            match self.node.compare_exchange(
+                current,
+                new,
+                Ordering::AcqRel,
+                Ordering::Acquire,
+                guard,
+            ) {
+                Ok(_) => unsafe {
+                    // The replaced node was reachable until now, so retire it into the collector
+                    // instead of dropping it.
+                    guard.defer_unchecked(move || callback(current.into_owned().into_box().value));
+                    return version;
+                },
+                Err(err) => {
+                    current = err.current;
+                    new = err.new;
+                }
+            }
+        }
+    }
+}
+
+impl<T> Drop for RcuCell<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let node = self.node.load(Ordering::Relaxed, unprotected());
+            if !node.is_null() {
+                drop(node.into_owned());
+            }
+        }
+    }
+}
+
+impl<T: Default> Default for RcuCell<T> {
+    fn default() -> RcuCell<T> {
+        RcuCell::new(T::default())
+    }
+}
+
+impl<T> From<T> for RcuCell<T> {
+    fn from(value: T) -> RcuCell<T> {
+        RcuCell::new(value)
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RcuCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let guard = &self.guard();
+        let current = self.read(guard);
+        f.debug_struct("RcuCell")
+            .field("version", &current.version())
+            .field("value", &*current)
+            .finish()
+    }
+}
+
+/// A reference to a version of the value in an [`RcuCell`].
+///
+/// This is created by [`RcuCell::read`].
+pub struct RcuRef<'g, T> {
+    node: &'g Node<T>,
+}
+
+impl<'g, T> RcuRef<'g, T> {
+    /// Returns the number of the version.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::RcuCell;
+    ///
+    /// let c = RcuCell::new(7);
+    /// c.store(8);
+    ///
+    /// let guard = &c.guard();
+    /// assert_eq!(c.read(guard).version(), 1);
+    /// ```
+    pub fn version(&self) -> u64 {
+        self.node.version
+    }
+
+    /// Returns a reference to the value, with the lifetime of the guard.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_epoch::RcuCell;
+    ///
+    /// let c = RcuCell::new(String::from("config"));
+    /// let guard = &c.guard();
+    ///
+    /// let value: &str = c.read(guard).get();
+    /// assert_eq!(value, "config");
+    /// ```
+    pub fn get(&self) -> &'g T {
+        &self.node.value
+    }
+}
+
+impl<T> Clone for RcuRef<'_, T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for RcuRef<'_, T> {}
+
+impl<T> Deref for RcuRef<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.node.value
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for RcuRef<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RcuRef")
+            .field("version", &self.node.version)
+            .field("value", &self.node.value)
+            .finish()
+    }
+}
+
+#[cfg(all(test, not(crossbeam_loom)))]
+mod tests {
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crossbeam_utils::thread;
+
+    use super::RcuCell;
+
+    #[test]
+    fn callback_waits_for_readers() {
+        let released = Arc::new(AtomicBool::new(false));
+
+        let c = RcuCell::new(1);
+        let reader = c.guard();
+        let old = c.read(&reader);
+
+        let r = released.clone();
+        c.store_with(2, move |old| {
+            assert_eq!(old, 1);
+            r.store(true, Ordering::SeqCst);
+        });
+
+        // The reader is still pinned, so the epoch can't advance far enough.
+        for _ in 0..100 {
+            c.guard().flush();
+        }
+        assert!(!released.load(Ordering::SeqCst));
+        assert_eq!(*old, 1);
+        assert_eq!(*c.read(&reader), 2);
+
+        drop(reader);
+        while !released.load(Ordering::SeqCst) {
+            c.guard().flush();
+        }
+    }
+
+    #[test]
+    fn drops_replaced_values() {
+        static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+        struct Elem;
+
+        impl Drop for Elem {
+            fn drop(&mut self) {
+                DROPS.fetch_add(1, Ordering::Relaxed);
+            }
+        }
+
+        let c = RcuCell::new(Elem);
+        for _ in 0..100 {
+            c.store(Elem);
+        }
+        drop(c);
+
+        while DROPS.load(Ordering::Relaxed) < 101 {
+            crate::pin().flush();
+        }
+        assert_eq!(DROPS.load(Ordering::Relaxed), 101);
+    }
+
+    #[test]
+    fn concurrent_stores() {
+        const THREADS: usize = 4;
+        const STEPS: usize = 1000;
+
+        let c = RcuCell::new(0);
+
+        thread::scope(|scope| {
+            for _ in 0..THREADS {
+                scope.spawn(|_| {
+                    for i in 0..STEPS {
+                        c.store(i);
+                    }
+                });
+            }
+
+            scope.spawn(|_| {
+                let mut last = 0;
+                for _ in 0..STEPS {
+                    let guard = &c.guard();
+                    let v = c.read(guard).version();
+                    assert!(v >= last);
+                    last = v;
+                }
+            });
+        })
+        .unwrap();
+
+        assert_eq!(c.version(), (THREADS * STEPS) as u64);
+    }
+}
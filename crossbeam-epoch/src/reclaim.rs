@@ -0,0 +1,51 @@
+use crate::atomic::Shared;
+use crate::guard::Guard;
+use crate::hazard::HazardDomain;
+
+/// A memory reclamation scheme.
+///
+/// Both epoch-based reclamation, through a [`Guard`], and hazard pointers, through a
+/// [`HazardDomain`], can destroy objects that were unlinked from a data structure once no thread
+/// can be accessing them anymore. Data structures that only need to hand their garbage over can be
+/// generic over this trait, and leave the choice of protection to their users.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_epoch::{self as epoch, Atomic, HazardDomain, Owned, Reclaim};
+/// use std::sync::atomic::Ordering::SeqCst;
+///
+/// fn replace<R: Reclaim>(a: &Atomic<i32>, value: i32, reclaim: &R) {
+///     let guard = unsafe { epoch::unprotected() };
+///     let old = a.swap(Owned::new(value), SeqCst, guard);
+///     unsafe { reclaim.retire(old) };
+/// }
+///
+/// let a = Atomic::new(0);
+/// replace(&a, 1, &epoch::pin());
+/// replace(&a, 2, &HazardDomain::new());
+/// # unsafe { drop(a.into_owned()) };
+/// ```
+pub trait Reclaim {
+    /// Destroys the object `ptr` points to once no thread can be accessing it.
+    ///
+    /// # Safety
+    ///
+    /// The object must have been unlinked so that no thread can reach it anymore, and it must not
+    /// be destroyed more than once. It may be destroyed on another thread. Threads that access the
+    /// object must protect it with the scheme this method belongs to: by being pinned by the same
+    /// collector, or by a hazard pointer of the same domain.
+    unsafe fn retire<T>(&self, ptr: Shared<'_, T>);
+}
+
+impl Reclaim for Guard {
+    unsafe fn retire<T>(&self, ptr: Shared<'_, T>) {
+        self.defer_destroy(ptr);
+    }
+}
+
+impl Reclaim for HazardDomain {
+    unsafe fn retire<T>(&self, ptr: Shared<'_, T>) {
+        HazardDomain::retire(self, ptr);
+    }
+}
@@ -131,7 +131,7 @@ impl<T> Queue<T> {
                                 .tail
                                 .compare_exchange(tail, next, Release, Relaxed, guard);
                         }
-                        guard.defer_destroy(head);
+                        guard.defer_destroy_internal(head);
                         // TODO: Replace with MaybeUninit::read when api is stable
                         Some(n.data.as_ptr().read())
                     })
@@ -164,7 +164,7 @@ impl<T> Queue<T> {
                                 .tail
                                 .compare_exchange(tail, next, Release, Relaxed, guard);
                         }
-                        guard.defer_destroy(head);
+                        guard.defer_destroy_internal(head);
                         Some(n.data.as_ptr().read())
                     })
                     .map_err(|_| ())
@@ -199,6 +199,24 @@ impl<T> Queue<T> {
             }
         }
     }
+
+    /// Calls `f` on every item in the queue, from front to back.
+    ///
+    /// Items pushed or popped concurrently may or may not be visited.
+    pub(crate) fn for_each<F>(&self, mut f: F, guard: &Guard)
+    where
+        T: Sync,
+        F: FnMut(&T),
+    {
+        let head = self.head.load(Acquire, guard);
+        let mut next = unsafe { head.deref() }.next.load(Acquire, guard);
+        // Popped nodes are destroyed only after `guard` is unpinned, and the data in a node is
+        // never written after the node is pushed, so it can be read even if it gets popped.
+        while let Some(n) = unsafe { next.as_ref() } {
+            f(unsafe { &*n.data.as_ptr() });
+            next = n.next.load(Acquire, guard);
+        }
+    }
 }
 
 impl<T> Drop for Queue<T> {
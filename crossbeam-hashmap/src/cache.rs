@@ -0,0 +1,429 @@
+//! A concurrent bounded cache. See [`Cache`].
+
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use crossbeam_epoch::Guard;
+
+use crate::map::HashMap;
+
+/// A concurrent cache holding a bounded number of entries.
+///
+/// When the cache is full, inserting a new entry evicts an old one, chosen with the CLOCK
+/// algorithm: every entry has a _referenced_ bit, which is set whenever the entry is read. The
+/// cache sweeps over its entries in a circle, clearing the bits that are set and evicting the
+/// first entry whose bit is already clear. This approximates evicting the least recently used
+/// entry, but reading an entry only sets its bit, so [`get`] is lock-free, just like
+/// [`HashMap::get`].
+///
+/// Insertions and invalidations lock the cache as a whole.
+///
+/// [`get`]: Cache::get
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_hashmap::Cache;
+///
+/// let cache = Cache::new(2);
+/// let guard = &cache.guard();
+///
+/// cache.insert("a", 1, guard);
+/// cache.insert("b", 2, guard);
+/// assert_eq!(cache.get("a", guard), Some(&1));
+///
+/// // The cache is full, so inserting `c` evicts `b`, which hasn't been read.
+/// cache.insert("c", 3, guard);
+/// assert_eq!(cache.get("a", guard), Some(&1));
+/// assert_eq!(cache.get("b", guard), None);
+/// assert_eq!(cache.get("c", guard), Some(&3));
+/// ```
+pub struct Cache<K, V, S = RandomState> {
+    /// The cached entries.
+    map: HashMap<K, Slot<V>, S>,
+
+    /// Held while inserting or removing entries.
+    clock: Mutex<Clock<K>>,
+}
+
+/// A cached value.
+struct Slot<V> {
+    value: V,
+
+    /// Set when the value is read, and cleared when the clock hand passes it.
+    referenced: AtomicBool,
+
+    /// The position of the entry in the clock.
+    index: usize,
+}
+
+/// The circle of cached keys swept over when evicting entries.
+struct Clock<K> {
+    /// The keys of the cached entries, with `None` for free positions.
+    keys: Box<[Option<K>]>,
+
+    /// The free positions.
+    free: Vec<usize>,
+
+    /// The next position to look at when evicting an entry.
+    hand: usize,
+}
+
+impl<V> Slot<V> {
+    /// Creates a slot at position `index` of the clock.
+    fn new(value: V, index: usize) -> Slot<V> {
+        Slot {
+            value,
+            referenced: AtomicBool::new(false),
+            index,
+        }
+    }
+}
+
+impl<K, V> Cache<K, V> {
+    /// Returns a new, empty cache that holds at most `capacity` entries.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::Cache;
+    ///
+    /// let cache: Cache<i32, &str> = Cache::new(100);
+    /// ```
+    pub fn new(capacity: usize) -> Cache<K, V> {
+        Cache::with_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S> Cache<K, V, S> {
+    /// Returns a new, empty cache that holds at most `capacity` entries and hashes keys with
+    /// `hash_builder`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::Cache;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let cache: Cache<i32, &str> = Cache::with_hasher(100, RandomState::new());
+    /// ```
+    pub fn with_hasher(capacity: usize, hash_builder: S) -> Cache<K, V, S> {
+        assert!(capacity > 0, "capacity must be positive");
+
+        Cache {
+            map: HashMap::with_capacity_and_hasher(capacity, hash_builder),
+            clock: Mutex::new(Clock {
+                keys: (0..capacity).map(|_| None).collect(),
+                free: (0..capacity).rev().collect(),
+                hand: 0,
+            }),
+        }
+    }
+
+    /// Returns a reference to the hasher of the cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::Cache;
+    ///
+    /// let cache: Cache<i32, &str> = Cache::new(100);
+    /// let hasher = cache.hasher();
+    /// ```
+    pub fn hasher(&self) -> &S {
+        self.map.hasher()
+    }
+
+    /// Pins the current thread, returning a guard that can be used with the cache.
+    ///
+    /// The references returned by the cache stay valid for as long as the guard is alive, even
+    /// if their entries get evicted in the meantime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::Cache;
+    ///
+    /// let cache = Cache::new(1);
+    /// let guard = &cache.guard();
+    ///
+    /// cache.insert(1, "one", guard);
+    /// let one = cache.get(&1, guard).unwrap();
+    ///
+    /// // The value can still be read after its entry is evicted.
+    /// cache.insert(2, "two", guard);
+    /// assert_eq!(*one, "one");
+    /// ```
+    pub fn guard(&self) -> Guard {
+        self.map.guard()
+    }
+
+    /// Returns the maximum number of entries in the cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::Cache;
+    ///
+    /// let cache: Cache<i32, &str> = Cache::new(100);
+    /// assert_eq!(cache.capacity(), 100);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.lock().keys.len()
+    }
+
+    /// Returns the number of entries in the cache.
+    ///
+    /// If the cache is being concurrently modified, consider the returned number just an
+    /// approximation without any guarantees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::Cache;
+    ///
+    /// let cache = Cache::new(100);
+    /// let guard = &cache.guard();
+    ///
+    /// cache.insert("a", 1, guard);
+    /// cache.insert("b", 2, guard);
+    /// assert_eq!(cache.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.map.len()
+    }
+
+    /// Returns `true` if the cache is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::Cache;
+    ///
+    /// let cache = Cache::new(100);
+    /// assert!(cache.is_empty());
+    ///
+    /// cache.insert("key", "value", &cache.guard());
+    /// assert!(!cache.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.map.is_empty()
+    }
+
+    /// Locks the cache for modification.
+    fn lock(&self) -> MutexGuard<'_, Clock<K>> {
+        // The clock is only modified once nothing can panic anymore, so it is always consistent,
+        // even if a thread panicked while holding the lock.
+        self.clock.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<K, V, S> Cache<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Returns a reference to the value corresponding to the key, and marks the entry as
+    /// recently used.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::Cache;
+    ///
+    /// let cache = Cache::new(100);
+    /// let guard = &cache.guard();
+    ///
+    /// cache.insert("key", "value", guard);
+    /// assert_eq!(cache.get("key", guard), Some(&"value"));
+    /// assert_eq!(cache.get("missing", guard), None);
+    /// ```
+    pub fn get<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Option<&'g V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let slot = self.map.get(key, guard)?;
+        // Avoid writing to the slot if the bit is already set, so that frequently read entries
+        // don't bounce their cache line between threads.
+        if !slot.referenced.load(Ordering::Relaxed) {
+            slot.referenced.store(true, Ordering::Relaxed);
+        }
+        Some(&slot.value)
+    }
+
+    /// Returns `true` if the cache contains a value for the specified key.
+    ///
+    /// Unlike [`get`], this doesn't mark the entry as recently used.
+    ///
+    /// [`get`]: Cache::get
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::Cache;
+    ///
+    /// let cache = Cache::new(100);
+    /// let guard = &cache.guard();
+    ///
+    /// cache.insert(1, "one", guard);
+    /// assert!(cache.contains_key(&1, guard));
+    /// assert!(!cache.contains_key(&2, guard));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q, guard: &Guard) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.map.contains_key(key, guard)
+    }
+}
+
+impl<K, V, S> Cache<K, V, S>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    V: Send + 'static,
+    S: BuildHasher,
+{
+    /// Inserts a key-value pair into the cache, evicting another entry if the cache is full.
+    ///
+    /// If the cache already had an entry with this key, its value is replaced and a reference to
+    /// the old value is returned. The replaced value stays valid for as long as `guard` is alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::Cache;
+    ///
+    /// let cache = Cache::new(100);
+    /// let guard = &cache.guard();
+    ///
+    /// assert_eq!(cache.insert(37, "a", guard), None);
+    /// assert_eq!(cache.insert(37, "b", guard), Some(&"a"));
+    /// assert_eq!(cache.get(&37, guard), Some(&"b"));
+    /// ```
+    pub fn insert<'g>(&'g self, key: K, value: V, guard: &'g Guard) -> Option<&'g V> {
+        let mut clock = self.lock();
+
+        // A replaced entry keeps its position in the clock, and counts as recently used.
+        if let Some(slot) = self.map.get(&key, guard) {
+            let slot = Slot::new(value, slot.index);
+            slot.referenced.store(true, Ordering::Relaxed);
+            return self.map.insert(key, slot, guard).map(|s| &s.value);
+        }
+
+        let copy = key.clone();
+        let index = clock.evict(&self.map, guard);
+        self.map.insert(key, Slot::new(value, index), guard);
+        clock.keys[index] = Some(copy);
+        None
+    }
+
+    /// Removes the entry with the specified key from the cache, and returns a reference to its
+    /// value.
+    ///
+    /// The removed value stays valid for as long as `guard` is alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::Cache;
+    ///
+    /// let cache = Cache::new(100);
+    /// let guard = &cache.guard();
+    ///
+    /// cache.insert(1, "a", guard);
+    /// assert_eq!(cache.invalidate(&1, guard), Some(&"a"));
+    /// assert_eq!(cache.invalidate(&1, guard), None);
+    /// ```
+    pub fn invalidate<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Option<&'g V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let mut clock = self.lock();
+        let slot = self.map.remove(key, guard)?;
+        clock.keys[slot.index] = None;
+        clock.free.push(slot.index);
+        Some(&slot.value)
+    }
+
+    /// Removes all entries from the cache.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::Cache;
+    ///
+    /// let cache = Cache::new(100);
+    /// let guard = &cache.guard();
+    ///
+    /// cache.insert(1, "a", guard);
+    /// cache.insert(2, "b", guard);
+    ///
+    /// cache.invalidate_all(guard);
+    /// assert!(cache.is_empty());
+    /// ```
+    pub fn invalidate_all(&self, guard: &Guard) {
+        let mut clock = self.lock();
+        let clock = &mut *clock;
+        for (index, key) in clock.keys.iter_mut().enumerate() {
+            if let Some(key) = key.take() {
+                self.map.remove(&key, guard);
+                clock.free.push(index);
+            }
+        }
+    }
+}
+
+impl<K, V, S> fmt::Debug for Cache<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Cache { .. }")
+    }
+}
+
+impl<K> Clock<K> {
+    /// Returns a free position in the clock, evicting an entry from `map` if there is none.
+    fn evict<V, S>(&mut self, map: &HashMap<K, Slot<V>, S>, guard: &Guard) -> usize
+    where
+        K: Hash + Eq + Send + 'static,
+        V: Send + 'static,
+        S: BuildHasher,
+    {
+        if let Some(index) = self.free.pop() {
+            return index;
+        }
+
+        // Every pass over a position clears its referenced bit, so this takes at most two sweeps.
+        loop {
+            let index = self.hand;
+            self.hand = (self.hand + 1) % self.keys.len();
+
+            match &self.keys[index] {
+                None => return index,
+                Some(key) => {
+                    let slot = map
+                        .get(key, guard)
+                        .expect("cached key is missing from the map");
+                    if !slot.referenced.swap(false, Ordering::Relaxed) {
+                        map.remove(key, guard);
+                        self.keys[index] = None;
+                        return index;
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,85 @@
+//! A concurrent hash map and set.
+//!
+//! This crate provides the types [`HashMap`] and [`HashSet`], which offer interfaces similar to
+//! [`std::collections::HashMap`] and [`std::collections::HashSet`], except they support safe
+//! concurrent access across multiple threads. It also provides [`Cache`], a hash map that holds a
+//! bounded number of entries and evicts the ones that haven't been used recently.
+//!
+//! # Concurrent access
+//!
+//! [`HashMap`] and [`HashSet`] implement [`Send`] and [`Sync`], so they can be shared across
+//! threads with ease. Methods which mutate the map, such as [`insert`], take `&self` rather than `&mut self`, so they
+//! can be invoked concurrently.
+//!
+//! Lookups and iteration are lock-free. Insertions and removals lock one of a fixed number of
+//! segments the map is split into, so writers only block each other when their keys fall into
+//! the same segment, and never block readers.
+//!
+//! ```
+//! use crossbeam_hashmap::HashMap;
+//! use crossbeam_utils::thread::scope;
+//!
+//! let ages = HashMap::new();
+//!
+//! scope(|s| {
+//!     s.spawn(|_| {
+//!         let guard = &ages.guard();
+//!         ages.insert("Spike Garrett", 22, guard);
+//!         ages.insert("Stan Hancock", 47, guard);
+//!     });
+//!     s.spawn(|_| {
+//!         let guard = &ages.guard();
+//!         ages.insert("Bryon Conroy", 65, guard);
+//!     });
+//! }).unwrap();
+//!
+//! let guard = &ages.guard();
+//! assert_eq!(ages.get("Stan Hancock", guard), Some(&47));
+//! assert_eq!(ages.len(), 3);
+//! ```
+//!
+//! # Garbage collection
+//!
+//! A value returned by [`get`] may be removed from the map by another thread while it is still
+//! being read. To make this safe, the map uses the _epoch-based memory reclamation_ mechanism
+//! implemented in [`crossbeam-epoch`]: removed entries are only destroyed once no thread can
+//! observe them anymore.
+//!
+//! Therefore, every operation takes a [`Guard`], which can be obtained with [`HashMap::guard`],
+//! and references returned by the map borrow from it. Keep in mind that holding on to a guard
+//! prevents garbage from being destroyed, so guards shouldn't be kept around for long.
+//!
+//! [`HashSet`] methods that don't return references into the set, such as
+//! [`HashSet::insert`] and [`HashSet::contains`], pin the current thread on their own and don't
+//! take a guard.
+//!
+//! [`insert`]: HashMap::insert
+//! [`get`]: HashMap::get
+//! [`Guard`]: crossbeam_epoch::Guard
+//! [`crossbeam-epoch`]: https://docs.rs/crossbeam-epoch
+
+#![doc(test(
+    no_crate_inject,
+    attr(
+        deny(warnings, rust_2018_idioms),
+        allow(dead_code, unused_assignments, unused_variables)
+    )
+))]
+#![warn(
+    missing_docs,
+    missing_debug_implementations,
+    rust_2018_idioms,
+    unreachable_pub
+)]
+
+pub mod cache;
+#[doc(inline)]
+pub use crate::cache::Cache;
+
+pub mod map;
+#[doc(inline)]
+pub use crate::map::HashMap;
+
+pub mod set;
+#[doc(inline)]
+pub use crate::set::HashSet;
@@ -0,0 +1,802 @@
+//! A concurrent hash map. See [`HashMap`].
+
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::iter::FromIterator;
+use std::slice;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use crossbeam_epoch::{self as epoch, Atomic, Collector, Guard, Owned, Shared};
+use crossbeam_utils::CachePadded;
+
+/// The number of segments a map is split into. Must be a power of two.
+const SEGMENTS: usize = 16;
+
+/// The minimum number of buckets in a segment. Must be a power of two.
+const MIN_BUCKETS: usize = 4;
+
+/// A concurrent hash map.
+///
+/// The map is split into a fixed number of segments, each of which is a hash table of its own.
+/// Lookups and iteration never block. Insertions and removals lock the segment their key hashes
+/// to, so writers only contend when their keys fall into the same segment. A segment doubles its
+/// number of buckets when it gets three-quarters full.
+///
+/// Every operation takes a [`Guard`], which keeps the entries it returns alive even if they get
+/// removed concurrently. A guard can be obtained with [`guard`].
+///
+/// [`guard`]: HashMap::guard
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_hashmap::HashMap;
+///
+/// let books = HashMap::new();
+/// let guard = &books.guard();
+///
+/// books.insert("Adventures of Huckleberry Finn", "My favorite book.", guard);
+/// books.insert("Grimms' Fairy Tales", "Masterpiece.", guard);
+///
+/// assert_eq!(books.get("Grimms' Fairy Tales", guard), Some(&"Masterpiece."));
+/// assert!(books.remove("Adventures of Huckleberry Finn", guard).is_some());
+/// assert_eq!(books.len(), 1);
+/// ```
+pub struct HashMap<K, V, S = RandomState> {
+    /// The segments, each padded to its own cache line.
+    segments: Box<[CachePadded<Segment<K, V>>]>,
+
+    /// Hashes the keys.
+    hash_builder: S,
+
+    /// The collector that destroys removed entries.
+    collector: Collector,
+}
+
+/// A part of a map, which is a hash table on its own.
+struct Segment<K, V> {
+    /// Held while modifying the segment.
+    lock: Mutex<()>,
+
+    /// The current table of the segment, which is never null.
+    table: Atomic<Table<K, V>>,
+
+    /// The number of entries in the segment.
+    len: AtomicUsize,
+}
+
+/// An array of buckets, each of which is a linked list of nodes.
+struct Table<K, V> {
+    /// The buckets, whose number is a power of two.
+    buckets: Box<[Atomic<Node<K, V>>]>,
+}
+
+/// A node in a bucket of a table.
+///
+/// Nodes don't own their key-value pairs, because when a segment grows, its nodes are copied
+/// into a new table while readers may still be traversing the old one. The copies point to the
+/// same pairs, which are only destroyed when their entry is removed.
+struct Node<K, V> {
+    /// The hash of the key.
+    hash: u64,
+
+    /// The key-value pair, allocated with `Box`.
+    pair: *mut (K, V),
+
+    /// The next node in the bucket.
+    next: Atomic<Node<K, V>>,
+}
+
+unsafe impl<K: Send + Sync, V: Send + Sync, S: Send> Send for HashMap<K, V, S> {}
+unsafe impl<K: Send + Sync, V: Send + Sync, S: Sync> Sync for HashMap<K, V, S> {}
+
+impl<K, V> Segment<K, V> {
+    /// Creates a new segment with `buckets` buckets.
+    fn new(buckets: usize) -> Segment<K, V> {
+        Segment {
+            lock: Mutex::new(()),
+            table: Atomic::new(Table::new(buckets)),
+            len: AtomicUsize::new(0),
+        }
+    }
+
+    /// Locks the segment for modification.
+    fn lock(&self) -> MutexGuard<'_, ()> {
+        // The segment is only modified once nothing can panic anymore, so it is always
+        // consistent, even if a thread panicked while holding the lock.
+        self.lock.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
+
+impl<K, V> Table<K, V> {
+    /// Creates a new table with `buckets` empty buckets.
+    fn new(buckets: usize) -> Table<K, V> {
+        Table {
+            buckets: (0..buckets).map(|_| Atomic::null()).collect(),
+        }
+    }
+
+    /// Returns the bucket a key with the given hash belongs to.
+    fn bucket(&self, hash: u64) -> &Atomic<Node<K, V>> {
+        &self.buckets[hash as usize & (self.buckets.len() - 1)]
+    }
+
+    /// Returns the number of entries after which the table should grow.
+    fn max_len(&self) -> usize {
+        self.buckets.len() / 4 * 3
+    }
+}
+
+impl<K, V> Node<K, V> {
+    /// Returns the key-value pair of the node.
+    fn pair(&self) -> &(K, V) {
+        unsafe { &*self.pair }
+    }
+}
+
+impl<K, V> HashMap<K, V> {
+    /// Returns a new, empty map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    ///
+    /// let map: HashMap<i32, &str> = HashMap::new();
+    /// ```
+    pub fn new() -> HashMap<K, V> {
+        HashMap::with_hasher(RandomState::new())
+    }
+
+    /// Returns a new, empty map with room for about `capacity` entries before it has to grow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    ///
+    /// let map: HashMap<i32, &str> = HashMap::with_capacity(1000);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> HashMap<K, V> {
+        HashMap::with_capacity_and_hasher(capacity, RandomState::new())
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S> {
+    /// Returns a new, empty map that hashes keys with `hash_builder`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let map: HashMap<i32, &str> = HashMap::with_hasher(RandomState::new());
+    /// ```
+    pub fn with_hasher(hash_builder: S) -> HashMap<K, V, S> {
+        HashMap::with_capacity_and_hasher(0, hash_builder)
+    }
+
+    /// Returns a new, empty map with room for about `capacity` entries before it has to grow,
+    /// which hashes keys with `hash_builder`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let map: HashMap<i32, &str> = HashMap::with_capacity_and_hasher(1000, RandomState::new());
+    /// ```
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> HashMap<K, V, S> {
+        // Spread the capacity over the segments, keeping each of them at most three-quarters
+        // full.
+        let per_segment = (capacity + SEGMENTS - 1) / SEGMENTS;
+        let buckets = ((per_segment * 4 + 2) / 3)
+            .next_power_of_two()
+            .max(MIN_BUCKETS);
+
+        HashMap {
+            segments: (0..SEGMENTS)
+                .map(|_| CachePadded::new(Segment::new(buckets)))
+                .collect(),
+            hash_builder,
+            collector: epoch::default_collector().clone(),
+        }
+    }
+
+    /// Returns a reference to the hasher of the map.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    ///
+    /// let map: HashMap<i32, &str> = HashMap::new();
+    /// let hasher = map.hasher();
+    /// ```
+    pub fn hasher(&self) -> &S {
+        &self.hash_builder
+    }
+
+    /// Pins the current thread, returning a guard that can be used with the map.
+    ///
+    /// The references returned by the map stay valid for as long as the guard is alive, even if
+    /// their entries get removed in the meantime. Since that keeps garbage from being destroyed,
+    /// the guard shouldn't be held for long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// map.insert(1, "one", guard);
+    /// let one = map.get(&1, guard).unwrap();
+    ///
+    /// // The value can still be read after its entry is removed.
+    /// map.remove(&1, guard);
+    /// assert_eq!(*one, "one");
+    /// ```
+    pub fn guard(&self) -> Guard {
+        let guard = epoch::pin();
+        if guard.collector() == Some(&self.collector) {
+            guard
+        } else {
+            self.collector.register().pin()
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    ///
+    /// If the map is being concurrently modified, consider the returned number just an
+    /// approximation without any guarantees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// map.insert("a", 1, guard);
+    /// map.insert("b", 2, guard);
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.segments
+            .iter()
+            .map(|s| s.len.load(Ordering::Relaxed))
+            .sum()
+    }
+
+    /// Returns `true` if the map is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// assert!(map.is_empty());
+    ///
+    /// map.insert("key", "value", &map.guard());
+    /// assert!(!map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over all entries in the map, in arbitrary order.
+    ///
+    /// The iterator doesn't see a consistent snapshot of the map: entries that are present for
+    /// the whole iteration are returned exactly once, but entries that are inserted or removed
+    /// concurrently may or may not be returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// map.insert(1, "one", guard);
+    /// map.insert(2, "two", guard);
+    ///
+    /// let mut keys: Vec<_> = map.iter(guard).map(|(k, _)| *k).collect();
+    /// keys.sort();
+    /// assert_eq!(keys, [1, 2]);
+    /// ```
+    pub fn iter<'g>(&'g self, guard: &'g Guard) -> Iter<'g, K, V> {
+        self.check_guard(guard);
+        Iter {
+            segments: self.segments.iter(),
+            buckets: [].iter(),
+            node: None,
+            guard,
+        }
+    }
+
+    /// Ensures that all `Guard`s used with the map come from the same `Collector`.
+    fn check_guard(&self, guard: &Guard) {
+        if let Some(c) = guard.collector() {
+            assert!(c == &self.collector);
+        }
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S>
+where
+    K: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Hashes `key` with the hasher of the map.
+    fn hash<Q>(&self, key: &Q) -> u64
+    where
+        Q: Hash + ?Sized,
+    {
+        let mut hasher = self.hash_builder.build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    /// Returns the segment a key with the given hash belongs to.
+    fn segment(&self, hash: u64) -> &Segment<K, V> {
+        // The low bits of the hash select the bucket, so use the high ones here.
+        &self.segments[(hash >> 32) as usize & (SEGMENTS - 1)]
+    }
+
+    /// Finds the node with the given key.
+    fn find<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Option<&'g Node<K, V>>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.check_guard(guard);
+
+        let hash = self.hash(key);
+        let table = self.segment(hash).table.load(Ordering::Acquire, guard);
+        let mut curr = unsafe { table.deref() }
+            .bucket(hash)
+            .load(Ordering::Acquire, guard);
+
+        while let Some(n) = unsafe { curr.as_ref() } {
+            if n.hash == hash && n.pair().0.borrow() == key {
+                return Some(n);
+            }
+            curr = n.next.load(Ordering::Acquire, guard);
+        }
+        None
+    }
+
+    /// Returns a reference to the value corresponding to the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// map.insert("key", "value", guard);
+    /// assert_eq!(map.get("key", guard), Some(&"value"));
+    /// assert_eq!(map.get("missing", guard), None);
+    /// ```
+    pub fn get<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Option<&'g V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key, guard).map(|n| &n.pair().1)
+    }
+
+    /// Returns references to the key and the value of the entry corresponding to the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// map.insert("key".to_string(), 7, guard);
+    /// assert_eq!(map.get_key_value("key", guard), Some((&"key".to_string(), &7)));
+    /// ```
+    pub fn get_key_value<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Option<(&'g K, &'g V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key, guard).map(|n| {
+            let (k, v) = n.pair();
+            (k, v)
+        })
+    }
+
+    /// Returns `true` if the map contains a value for the specified key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// map.insert(1, "one", guard);
+    /// assert!(map.contains_key(&1, guard));
+    /// assert!(!map.contains_key(&2, guard));
+    /// ```
+    pub fn contains_key<Q>(&self, key: &Q, guard: &Guard) -> bool
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.find(key, guard).is_some()
+    }
+}
+
+impl<K, V, S> HashMap<K, V, S>
+where
+    K: Hash + Eq + Send + 'static,
+    V: Send + 'static,
+    S: BuildHasher,
+{
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map already had an entry with this key, it is replaced and a reference to its value
+    /// is returned. The replaced value stays valid for as long as `guard` is alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// assert_eq!(map.insert(37, "a", guard), None);
+    /// assert_eq!(map.insert(37, "b", guard), Some(&"a"));
+    /// assert_eq!(map.get(&37, guard), Some(&"b"));
+    /// ```
+    pub fn insert<'g>(&'g self, key: K, value: V, guard: &'g Guard) -> Option<&'g V> {
+        match self.insert_internal(key, value, true, guard) {
+            Ok((_, prev)) => prev,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Inserts a key-value pair into the map, unless it already has an entry with this key.
+    ///
+    /// Returns `Ok` with a reference to the inserted value, or `Err` with a reference to the
+    /// value already in the map, in which case `key` and `value` are dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// assert_eq!(map.try_insert(37, "a", guard), Ok(&"a"));
+    /// assert_eq!(map.try_insert(37, "b", guard), Err(&"a"));
+    /// assert_eq!(map.get(&37, guard), Some(&"a"));
+    /// ```
+    pub fn try_insert<'g>(&'g self, key: K, value: V, guard: &'g Guard) -> Result<&'g V, &'g V> {
+        self.insert_internal(key, value, false, guard)
+            .map(|(value, _)| value)
+    }
+
+    /// Inserts a key-value pair into the map.
+    ///
+    /// If the map already has an entry with this key, it is replaced if `replace` is `true`.
+    /// Otherwise, a reference to its value is returned as an error.
+    ///
+    /// On success, returns references to the inserted value and to the replaced one, if any.
+    fn insert_internal<'g>(
+        &'g self,
+        key: K,
+        value: V,
+        replace: bool,
+        guard: &'g Guard,
+    ) -> Result<(&'g V, Option<&'g V>), &'g V> {
+        self.check_guard(guard);
+
+        let hash = self.hash(&key);
+        let segment = self.segment(hash);
+        let _lock = segment.lock();
+
+        unsafe {
+            // The table and its buckets are only modified while holding the lock.
+            let mut table = segment.table.load(Ordering::Relaxed, guard);
+
+            // Look for an existing entry with the key.
+            let mut pred = table.deref().bucket(hash);
+            let mut curr = pred.load(Ordering::Relaxed, guard);
+            while let Some(n) = curr.as_ref() {
+                if n.hash == hash && n.pair().0 == key {
+                    if !replace {
+                        return Err(&n.pair().1);
+                    }
+
+                    // Replace the node with a new one, keeping the rest of the bucket as is.
+                    let pair = Box::into_raw(Box::new((key, value)));
+                    let node = Owned::new(Node {
+                        hash,
+                        pair,
+                        next: Atomic::from(n.next.load(Ordering::Relaxed, guard)),
+                    });
+                    pred.store(node, Ordering::Release);
+                    retire(curr, guard);
+                    return Ok((&(*pair).1, Some(&n.pair().1)));
+                }
+                pred = &n.next;
+                curr = n.next.load(Ordering::Relaxed, guard);
+            }
+
+            // Grow the table if it is getting full.
+            let len = segment.len.load(Ordering::Relaxed);
+            if len >= table.deref().max_len() {
+                table = grow(segment, table, guard);
+            }
+
+            // Push a new node to the front of its bucket.
+            let bucket = table.deref().bucket(hash);
+            let pair = Box::into_raw(Box::new((key, value)));
+            let node = Owned::new(Node {
+                hash,
+                pair,
+                next: Atomic::from(bucket.load(Ordering::Relaxed, guard)),
+            });
+            bucket.store(node, Ordering::Release);
+            segment.len.store(len + 1, Ordering::Relaxed);
+            Ok((&(*pair).1, None))
+        }
+    }
+
+    /// Removes the entry with the specified key from the map, and returns a reference to its
+    /// value.
+    ///
+    /// The removed value stays valid for as long as `guard` is alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// map.insert(1, "a", guard);
+    /// assert_eq!(map.remove(&1, guard), Some(&"a"));
+    /// assert_eq!(map.remove(&1, guard), None);
+    /// ```
+    pub fn remove<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Option<&'g V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.remove_entry(key, guard).map(|(_, v)| v)
+    }
+
+    /// Removes the entry with the specified key from the map, and returns references to its key
+    /// and value.
+    ///
+    /// The removed entry stays valid for as long as `guard` is alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashMap;
+    ///
+    /// let map = HashMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// map.insert("key".to_string(), 1, guard);
+    /// assert_eq!(map.remove_entry("key", guard), Some((&"key".to_string(), &1)));
+    /// assert_eq!(map.remove_entry("key", guard), None);
+    /// ```
+    pub fn remove_entry<'g, Q>(&'g self, key: &Q, guard: &'g Guard) -> Option<(&'g K, &'g V)>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.check_guard(guard);
+
+        let hash = self.hash(key);
+        let segment = self.segment(hash);
+        let _lock = segment.lock();
+
+        unsafe {
+            let table = segment.table.load(Ordering::Relaxed, guard);
+
+            let mut pred = table.deref().bucket(hash);
+            let mut curr = pred.load(Ordering::Relaxed, guard);
+            while let Some(n) = curr.as_ref() {
+                let next = n.next.load(Ordering::Relaxed, guard);
+                if n.hash == hash && n.pair().0.borrow() == key {
+                    // Unlink the node. Readers that are currently at it can still move on to the
+                    // rest of the bucket.
+                    pred.store(next, Ordering::Release);
+                    segment
+                        .len
+                        .store(segment.len.load(Ordering::Relaxed) - 1, Ordering::Relaxed);
+                    retire(curr, guard);
+                    let (k, v) = n.pair();
+                    return Some((k, v));
+                }
+                pred = &n.next;
+                curr = next;
+            }
+        }
+        None
+    }
+}
+
+impl<K, V, S> Drop for HashMap<K, V, S> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = epoch::unprotected();
+
+            for segment in self.segments.iter() {
+                let table = segment.table.load(Ordering::Relaxed, guard);
+                for bucket in table.deref().buckets.iter() {
+                    let mut curr = bucket.load(Ordering::Relaxed, guard);
+                    while let Some(n) = curr.as_ref() {
+                        let next = n.next.load(Ordering::Relaxed, guard);
+                        drop(Box::from_raw(n.pair));
+                        drop(curr.into_owned());
+                        curr = next;
+                    }
+                }
+                drop(table.into_owned());
+            }
+        }
+    }
+}
+
+impl<K, V> Default for HashMap<K, V> {
+    fn default() -> HashMap<K, V> {
+        HashMap::new()
+    }
+}
+
+impl<K, V, S> fmt::Debug for HashMap<K, V, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("HashMap { .. }")
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for HashMap<K, V>
+where
+    K: Hash + Eq + Send + 'static,
+    V: Send + 'static,
+{
+    fn from_iter<I>(iter: I) -> HashMap<K, V>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let map = HashMap::new();
+        {
+            let guard = &map.guard();
+            for (k, v) in iter {
+                map.insert(k, v, guard);
+            }
+        }
+        map
+    }
+}
+
+/// Doubles the number of buckets of `segment`, whose lock must be held, and returns the new
+/// table.
+unsafe fn grow<'g, K, V>(
+    segment: &Segment<K, V>,
+    table: Shared<'g, Table<K, V>>,
+    guard: &'g Guard,
+) -> Shared<'g, Table<K, V>> {
+    let old = table.deref();
+    let new = Table::new(old.buckets.len() * 2);
+
+    // Readers may still be traversing the old table, so its nodes can't be moved into the new
+    // one. Instead, copy them, sharing their key-value pairs.
+    for bucket in old.buckets.iter() {
+        let mut curr = bucket.load(Ordering::Relaxed, guard);
+        while let Some(n) = curr.as_ref() {
+            let b = new.bucket(n.hash);
+            b.store(
+                Owned::new(Node {
+                    hash: n.hash,
+                    pair: n.pair,
+                    next: Atomic::from(b.load(Ordering::Relaxed, guard)),
+                }),
+                Ordering::Relaxed,
+            );
+            curr = n.next.load(Ordering::Relaxed, guard);
+        }
+    }
+
+    let new = Owned::new(new).into_shared(guard);
+    segment.table.store(new, Ordering::Release);
+
+    // Destroy the old table and its nodes, but not the key-value pairs.
+    for bucket in old.buckets.iter() {
+        let mut curr = bucket.load(Ordering::Relaxed, guard);
+        while let Some(n) = curr.as_ref() {
+            let next = n.next.load(Ordering::Relaxed, guard);
+            guard.defer_destroy(curr);
+            curr = next;
+        }
+    }
+    guard.defer_destroy(table);
+
+    new
+}
+
+/// Destroys a node that has been unlinked, along with its key-value pair, once no thread can
+/// observe it anymore.
+unsafe fn retire<K, V>(node: Shared<'_, Node<K, V>>, guard: &Guard)
+where
+    K: Send + 'static,
+    V: Send + 'static,
+{
+    let pair = node.deref().pair;
+    guard.defer_unchecked(move || drop(Box::from_raw(pair)));
+    guard.defer_destroy(node);
+}
+
+/// An iterator over the entries of a `HashMap`.
+///
+/// This struct is created by the [`iter`] method on [`HashMap`].
+///
+/// [`iter`]: HashMap::iter
+pub struct Iter<'g, K, V> {
+    /// The segments that haven't been visited yet.
+    segments: slice::Iter<'g, CachePadded<Segment<K, V>>>,
+
+    /// The buckets of the current segment that haven't been visited yet.
+    buckets: slice::Iter<'g, Atomic<Node<K, V>>>,
+
+    /// The next node in the current bucket.
+    node: Option<&'g Node<K, V>>,
+
+    guard: &'g Guard,
+}
+
+impl<'g, K, V> Iterator for Iter<'g, K, V> {
+    type Item = (&'g K, &'g V);
+
+    fn next(&mut self) -> Option<(&'g K, &'g V)> {
+        loop {
+            if let Some(n) = self.node {
+                self.node = unsafe { n.next.load(Ordering::Acquire, self.guard).as_ref() };
+                let (k, v) = n.pair();
+                return Some((k, v));
+            }
+
+            if let Some(bucket) = self.buckets.next() {
+                self.node = unsafe { bucket.load(Ordering::Acquire, self.guard).as_ref() };
+                continue;
+            }
+
+            let segment = self.segments.next()?;
+            let table = segment.table.load(Ordering::Acquire, self.guard);
+            self.buckets = unsafe { table.deref() }.buckets.iter();
+        }
+    }
+}
+
+impl<K, V> fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Iter { .. }")
+    }
+}
@@ -0,0 +1,387 @@
+//! A concurrent hash set. See [`HashSet`].
+
+use std::borrow::Borrow;
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash};
+use std::iter::FromIterator;
+
+use crossbeam_epoch::Guard;
+
+use crate::map;
+
+/// A concurrent hash set.
+///
+/// The set is a [`HashMap`] with `()` values, so it has the same performance characteristics:
+/// lookups and iteration never block, while insertions and removals only contend with each other
+/// when their elements hash to the same segment.
+///
+/// Since [`insert`], [`contains`], and [`remove`] don't return references into the set, they pin
+/// the current thread on their own and don't take a [`Guard`]. Only methods that return
+/// references, such as [`get`] and [`iter`], need one, which can be obtained with [`guard`].
+///
+/// [`HashMap`]: crate::HashMap
+/// [`insert`]: HashSet::insert
+/// [`contains`]: HashSet::contains
+/// [`remove`]: HashSet::remove
+/// [`get`]: HashSet::get
+/// [`iter`]: HashSet::iter
+/// [`guard`]: HashSet::guard
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_hashmap::HashSet;
+///
+/// let books = HashSet::new();
+///
+/// books.insert("A Dance With Dragons");
+/// books.insert("To Kill a Mockingbird");
+///
+/// assert!(books.contains("To Kill a Mockingbird"));
+/// assert!(books.remove("A Dance With Dragons"));
+/// assert_eq!(books.len(), 1);
+/// ```
+pub struct HashSet<T, S = RandomState> {
+    inner: map::HashMap<T, (), S>,
+}
+
+impl<T> HashSet<T> {
+    /// Returns a new, empty set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashSet;
+    ///
+    /// let set: HashSet<i32> = HashSet::new();
+    /// ```
+    pub fn new() -> HashSet<T> {
+        HashSet {
+            inner: map::HashMap::new(),
+        }
+    }
+
+    /// Returns a new, empty set with room for about `capacity` elements before it has to grow.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashSet;
+    ///
+    /// let set: HashSet<i32> = HashSet::with_capacity(1000);
+    /// ```
+    pub fn with_capacity(capacity: usize) -> HashSet<T> {
+        HashSet {
+            inner: map::HashMap::with_capacity(capacity),
+        }
+    }
+}
+
+impl<T, S> HashSet<T, S> {
+    /// Returns a new, empty set that hashes elements with `hash_builder`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashSet;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let set: HashSet<i32> = HashSet::with_hasher(RandomState::new());
+    /// ```
+    pub fn with_hasher(hash_builder: S) -> HashSet<T, S> {
+        HashSet {
+            inner: map::HashMap::with_hasher(hash_builder),
+        }
+    }
+
+    /// Returns a new, empty set with room for about `capacity` elements before it has to grow,
+    /// which hashes elements with `hash_builder`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashSet;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let set: HashSet<i32> = HashSet::with_capacity_and_hasher(1000, RandomState::new());
+    /// ```
+    pub fn with_capacity_and_hasher(capacity: usize, hash_builder: S) -> HashSet<T, S> {
+        HashSet {
+            inner: map::HashMap::with_capacity_and_hasher(capacity, hash_builder),
+        }
+    }
+
+    /// Returns a reference to the hasher of the set.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashSet;
+    ///
+    /// let set: HashSet<i32> = HashSet::new();
+    /// let hasher = set.hasher();
+    /// ```
+    pub fn hasher(&self) -> &S {
+        self.inner.hasher()
+    }
+
+    /// Pins the current thread, returning a guard that can be used with the set.
+    ///
+    /// The references returned by the set stay valid for as long as the guard is alive, even if
+    /// their elements get removed in the meantime.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// set.insert("one".to_string());
+    ///
+    /// let guard = &set.guard();
+    /// let one = set.get("one", guard).unwrap();
+    ///
+    /// // The element can still be read after it is removed.
+    /// set.remove("one");
+    /// assert_eq!(one, "one");
+    /// ```
+    pub fn guard(&self) -> Guard {
+        self.inner.guard()
+    }
+
+    /// Returns the number of elements in the set.
+    ///
+    /// If the set is being concurrently modified, consider the returned number just an
+    /// approximation without any guarantees.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// set.insert(1);
+    /// set.insert(2);
+    /// assert_eq!(set.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the set is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// assert!(set.is_empty());
+    ///
+    /// set.insert(1);
+    /// assert!(!set.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.inner.is_empty()
+    }
+
+    /// Returns an iterator over all elements in the set, in arbitrary order.
+    ///
+    /// The iterator doesn't see a consistent snapshot of the set: elements that are present for
+    /// the whole iteration are returned exactly once, but elements that are inserted or removed
+    /// concurrently may or may not be returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// set.insert(1);
+    /// set.insert(2);
+    ///
+    /// let guard = &set.guard();
+    /// let mut elements: Vec<_> = set.iter(guard).cloned().collect();
+    /// elements.sort();
+    /// assert_eq!(elements, [1, 2]);
+    /// ```
+    pub fn iter<'g>(&'g self, guard: &'g Guard) -> Iter<'g, T> {
+        Iter {
+            inner: self.inner.iter(guard),
+        }
+    }
+}
+
+impl<T, S> HashSet<T, S>
+where
+    T: Hash + Eq,
+    S: BuildHasher,
+{
+    /// Returns a reference to the element in the set equal to `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// set.insert("apple".to_string());
+    ///
+    /// let guard = &set.guard();
+    /// assert_eq!(set.get("apple", guard), Some(&"apple".to_string()));
+    /// assert_eq!(set.get("banana", guard), None);
+    /// ```
+    pub fn get<'g, Q>(&'g self, value: &Q, guard: &'g Guard) -> Option<&'g T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner.get_key_value(value, guard).map(|(k, _)| k)
+    }
+
+    /// Returns `true` if the set contains `value`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// set.insert(1);
+    ///
+    /// assert!(set.contains(&1));
+    /// assert!(!set.contains(&2));
+    /// ```
+    pub fn contains<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner.contains_key(value, &self.guard())
+    }
+}
+
+impl<T, S> HashSet<T, S>
+where
+    T: Hash + Eq + Send + 'static,
+    S: BuildHasher,
+{
+    /// Adds `value` to the set.
+    ///
+    /// Returns `true` if the set didn't contain an equal element. Otherwise, the set is left
+    /// unchanged and `value` is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashSet;
+    ///
+    /// let set = HashSet::new();
+    ///
+    /// assert!(set.insert(2));
+    /// assert!(!set.insert(2));
+    /// assert_eq!(set.len(), 1);
+    /// ```
+    pub fn insert(&self, value: T) -> bool {
+        self.inner.try_insert(value, (), &self.guard()).is_ok()
+    }
+
+    /// Removes the element equal to `value` from the set.
+    ///
+    /// Returns `true` if the set contained such an element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// set.insert(2);
+    ///
+    /// assert!(set.remove(&2));
+    /// assert!(!set.remove(&2));
+    /// ```
+    pub fn remove<Q>(&self, value: &Q) -> bool
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner.remove(value, &self.guard()).is_some()
+    }
+
+    /// Removes the element equal to `value` from the set, and returns a reference to it.
+    ///
+    /// The removed element stays valid for as long as `guard` is alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_hashmap::HashSet;
+    ///
+    /// let set = HashSet::new();
+    /// set.insert("apple".to_string());
+    ///
+    /// let guard = &set.guard();
+    /// assert_eq!(set.take("apple", guard), Some(&"apple".to_string()));
+    /// assert_eq!(set.take("apple", guard), None);
+    /// ```
+    pub fn take<'g, Q>(&'g self, value: &Q, guard: &'g Guard) -> Option<&'g T>
+    where
+        T: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        self.inner.remove_entry(value, guard).map(|(k, _)| k)
+    }
+}
+
+impl<T> Default for HashSet<T> {
+    fn default() -> HashSet<T> {
+        HashSet::new()
+    }
+}
+
+impl<T, S> fmt::Debug for HashSet<T, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("HashSet { .. }")
+    }
+}
+
+impl<T> FromIterator<T> for HashSet<T>
+where
+    T: Hash + Eq + Send + 'static,
+{
+    fn from_iter<I>(iter: I) -> HashSet<T>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let set = HashSet::new();
+        for value in iter {
+            set.insert(value);
+        }
+        set
+    }
+}
+
+/// An iterator over the elements of a `HashSet`.
+///
+/// This struct is created by the [`iter`] method on [`HashSet`].
+///
+/// [`iter`]: HashSet::iter
+pub struct Iter<'g, T> {
+    inner: map::Iter<'g, T, ()>,
+}
+
+impl<'g, T> Iterator for Iter<'g, T> {
+    type Item = &'g T;
+
+    fn next(&mut self) -> Option<&'g T> {
+        self.inner.next().map(|(k, _)| k)
+    }
+}
+
+impl<T> fmt::Debug for Iter<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Iter { .. }")
+    }
+}
@@ -0,0 +1,162 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crossbeam_hashmap::Cache;
+use crossbeam_utils::thread;
+
+#[test]
+fn smoke() {
+    let c = Cache::new(10);
+    let guard = &c.guard();
+    c.insert(1, 10, guard);
+    c.insert(5, 50, guard);
+    c.insert(7, 70, guard);
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    Cache::<i32, i32>::new(0);
+}
+
+#[test]
+fn insert() {
+    let c = Cache::new(10);
+    let guard = &c.guard();
+
+    assert_eq!(c.insert(1, 10, guard), None);
+    assert_eq!(c.insert(2, 20, guard), None);
+    assert_eq!(c.insert(1, 11, guard), Some(&10));
+
+    assert_eq!(c.get(&1, guard), Some(&11));
+    assert_eq!(c.get(&2, guard), Some(&20));
+    assert_eq!(c.get(&3, guard), None);
+    assert_eq!(c.len(), 2);
+}
+
+#[test]
+fn bounded() {
+    let c = Cache::new(100);
+    let guard = &c.guard();
+
+    for x in 0..1000 {
+        c.insert(x, x, guard);
+        assert!(c.len() <= 100);
+    }
+    assert_eq!(c.len(), 100);
+    assert_eq!((0..1000).filter(|x| c.contains_key(x, guard)).count(), 100);
+}
+
+#[test]
+fn evicts_unused() {
+    let c = Cache::new(10);
+    let guard = &c.guard();
+
+    for x in 0..10 {
+        c.insert(x, x, guard);
+    }
+
+    // Keep reading the even keys, so only odd ones get evicted.
+    for x in 10..15 {
+        for y in (0..10).filter(|y| y % 2 == 0) {
+            assert_eq!(c.get(&y, guard), Some(&y));
+        }
+        c.insert(x, x, guard);
+    }
+
+    for x in 0..10 {
+        assert_eq!(c.contains_key(&x, guard), x % 2 == 0);
+    }
+    for x in 10..15 {
+        assert!(c.contains_key(&x, guard));
+    }
+}
+
+#[test]
+fn invalidate() {
+    let c = Cache::new(10);
+    let guard = &c.guard();
+
+    for x in 0..10 {
+        c.insert(x, x * 10, guard);
+    }
+    for x in (0..10).filter(|x| x % 2 == 0) {
+        assert_eq!(c.invalidate(&x, guard), Some(&(x * 10)));
+        assert_eq!(c.invalidate(&x, guard), None);
+    }
+    assert_eq!(c.len(), 5);
+
+    // Invalidated entries free up room, so nothing has to be evicted.
+    for x in 10..15 {
+        c.insert(x, x * 10, guard);
+    }
+    for x in (0..15).filter(|x| x % 2 == 1 || *x >= 10) {
+        assert_eq!(c.get(&x, guard), Some(&(x * 10)));
+    }
+
+    c.invalidate_all(guard);
+    assert!(c.is_empty());
+    for x in 0..15 {
+        assert!(!c.contains_key(&x, guard));
+    }
+}
+
+#[test]
+fn drops() {
+    #[derive(Debug)]
+    struct Value(Arc<AtomicUsize>);
+
+    impl Drop for Value {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let drops = Arc::new(AtomicUsize::new(0));
+    let c = Cache::new(10);
+    {
+        let guard = &c.guard();
+        for x in 0..100 {
+            c.insert(x, Value(drops.clone()), guard);
+        }
+    }
+
+    drop(c);
+    // Evicted values may still be waiting for their epoch, so flush the garbage out.
+    while drops.load(Ordering::SeqCst) < 100 {
+        crossbeam_epoch::pin().flush();
+        std::thread::yield_now();
+    }
+    assert_eq!(drops.load(Ordering::SeqCst), 100);
+}
+
+#[test]
+fn concurrent() {
+    const THREADS: usize = 4;
+    const N: usize = 1000;
+
+    let c = Cache::new(100);
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let c = &c;
+            scope.spawn(move |_| {
+                for x in 0..N {
+                    let guard = &c.guard();
+                    let key = (t * N + x) % 300;
+                    match c.get(&key, guard) {
+                        Some(v) => assert_eq!(*v, key),
+                        None => {
+                            c.insert(key, key, guard);
+                        }
+                    }
+                    if x % 10 == 0 {
+                        c.invalidate(&key, guard);
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert!(c.len() <= 100);
+}
@@ -0,0 +1,221 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crossbeam_hashmap::HashMap;
+use crossbeam_utils::thread;
+
+#[test]
+fn smoke() {
+    let m = HashMap::new();
+    let guard = &m.guard();
+    m.insert(1, 10, guard);
+    m.insert(5, 50, guard);
+    m.insert(7, 70, guard);
+}
+
+#[test]
+fn is_empty() {
+    let m = HashMap::new();
+    let guard = &m.guard();
+    assert!(m.is_empty());
+
+    m.insert(1, 10, guard);
+    assert!(!m.is_empty());
+    m.insert(2, 20, guard);
+    assert!(!m.is_empty());
+
+    m.remove(&1, guard);
+    assert!(!m.is_empty());
+    m.remove(&2, guard);
+    assert!(m.is_empty());
+}
+
+#[test]
+fn insert() {
+    let m = HashMap::new();
+    let guard = &m.guard();
+
+    assert_eq!(m.insert(1, 10, guard), None);
+    assert_eq!(m.insert(2, 20, guard), None);
+    assert_eq!(m.insert(1, 11, guard), Some(&10));
+    assert_eq!(m.insert(1, 12, guard), Some(&11));
+
+    assert_eq!(m.get(&1, guard), Some(&12));
+    assert_eq!(m.get(&2, guard), Some(&20));
+    assert_eq!(m.get(&3, guard), None);
+    assert_eq!(m.len(), 2);
+}
+
+#[test]
+fn remove() {
+    let m = HashMap::new();
+    let guard = &m.guard();
+
+    for x in 0..100 {
+        m.insert(x, x * 10, guard);
+    }
+    for x in (0..100).filter(|x| x % 3 == 0) {
+        assert_eq!(m.remove(&x, guard), Some(&(x * 10)));
+    }
+    for x in 0..100 {
+        assert_eq!(m.contains_key(&x, guard), x % 3 != 0);
+        assert_eq!(m.remove(&x, guard).is_some(), x % 3 != 0);
+    }
+    assert!(m.is_empty());
+}
+
+#[test]
+fn borrowed_keys() {
+    let m = HashMap::new();
+    let guard = &m.guard();
+
+    m.insert("apple".to_string(), 1, guard);
+    m.insert("banana".to_string(), 2, guard);
+
+    assert_eq!(m.get("apple", guard), Some(&1));
+    assert_eq!(
+        m.get_key_value("banana", guard),
+        Some((&"banana".to_string(), &2))
+    );
+    assert!(m.contains_key("banana", guard));
+    assert_eq!(m.remove("apple", guard), Some(&1));
+    assert!(!m.contains_key("apple", guard));
+}
+
+#[test]
+fn grow() {
+    const N: usize = 10_000;
+
+    let m = HashMap::new();
+    let guard = &m.guard();
+
+    for x in 0..N {
+        m.insert(x, x, guard);
+    }
+    assert_eq!(m.len(), N);
+    for x in 0..N {
+        assert_eq!(m.get(&x, guard), Some(&x));
+    }
+
+    let m = HashMap::with_capacity(N);
+    for x in 0..N {
+        m.insert(x, x, guard);
+    }
+    assert_eq!(m.len(), N);
+}
+
+#[test]
+fn iter() {
+    let m = HashMap::new();
+    let guard = &m.guard();
+    assert_eq!(m.iter(guard).count(), 0);
+
+    for x in 0..1000 {
+        m.insert(x, x + 1, guard);
+    }
+    for x in 0..500 {
+        m.remove(&(x * 2), guard);
+    }
+
+    let mut entries: Vec<_> = m.iter(guard).map(|(k, v)| (*k, *v)).collect();
+    entries.sort();
+    let expected: Vec<_> = (0..500).map(|x| (x * 2 + 1, x * 2 + 2)).collect();
+    assert_eq!(entries, expected);
+}
+
+#[test]
+fn from_iter() {
+    let m: HashMap<_, _> = (0..10).map(|x| (x, x * x)).collect();
+    let guard = &m.guard();
+    assert_eq!(m.len(), 10);
+    assert_eq!(m.get(&7, guard), Some(&49));
+}
+
+#[test]
+fn drops() {
+    #[derive(Debug)]
+    struct Value(Arc<AtomicUsize>);
+
+    impl Drop for Value {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let drops = Arc::new(AtomicUsize::new(0));
+    let m = HashMap::new();
+    {
+        let guard = &m.guard();
+        for x in 0..100 {
+            m.insert(x, Value(drops.clone()), guard);
+        }
+    }
+    assert_eq!(drops.load(Ordering::SeqCst), 0);
+
+    drop(m);
+    assert_eq!(drops.load(Ordering::SeqCst), 100);
+}
+
+#[test]
+fn concurrent_insert() {
+    const THREADS: usize = 4;
+    const N: usize = 1000;
+
+    let m = HashMap::new();
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let m = &m;
+            scope.spawn(move |_| {
+                for x in 0..N {
+                    m.insert(t * N + x, t, &m.guard());
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    let guard = &m.guard();
+    assert_eq!(m.len(), THREADS * N);
+    for t in 0..THREADS {
+        for x in 0..N {
+            assert_eq!(m.get(&(t * N + x), guard), Some(&t));
+        }
+    }
+}
+
+#[test]
+fn concurrent_insert_and_remove() {
+    const THREADS: usize = 4;
+    const N: usize = 1000;
+
+    let m = HashMap::new();
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let m = &m;
+            scope.spawn(move |_| {
+                for x in 0..N {
+                    let guard = &m.guard();
+                    let key = t * N + x;
+                    m.insert(key, x, guard);
+                    assert_eq!(m.get(&key, guard), Some(&x));
+                    if x % 2 == 0 {
+                        assert_eq!(m.remove(&key, guard), Some(&x));
+                    }
+                }
+            });
+        }
+
+        // Readers never miss a key that stays in the map, even while segments grow.
+        let m = &m;
+        scope.spawn(move |_| {
+            let guard = &m.guard();
+            m.insert(usize::max_value(), 0, guard);
+            for _ in 0..N {
+                assert!(m.contains_key(&usize::max_value(), guard));
+            }
+        });
+    })
+    .unwrap();
+
+    assert_eq!(m.len(), THREADS * N / 2 + 1);
+}
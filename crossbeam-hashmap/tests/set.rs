@@ -0,0 +1,134 @@
+use crossbeam_hashmap::HashSet;
+use crossbeam_utils::thread;
+
+#[test]
+fn smoke() {
+    let s = HashSet::new();
+    s.insert(1);
+    s.insert(5);
+    s.insert(7);
+}
+
+#[test]
+fn insert() {
+    let s = HashSet::new();
+
+    assert!(s.insert(1));
+    assert!(s.insert(2));
+    assert!(!s.insert(1));
+    assert!(!s.insert(2));
+
+    assert!(s.contains(&1));
+    assert!(s.contains(&2));
+    assert!(!s.contains(&3));
+    assert_eq!(s.len(), 2);
+}
+
+#[test]
+fn insert_keeps_existing() {
+    #[derive(Debug)]
+    struct Item(i32, &'static str);
+
+    impl PartialEq for Item {
+        fn eq(&self, other: &Item) -> bool {
+            self.0 == other.0
+        }
+    }
+
+    impl Eq for Item {}
+
+    impl std::hash::Hash for Item {
+        fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+            self.0.hash(state);
+        }
+    }
+
+    let s = HashSet::new();
+    assert!(s.insert(Item(1, "first")));
+    assert!(!s.insert(Item(1, "second")));
+
+    let guard = &s.guard();
+    assert_eq!(s.get(&Item(1, ""), guard).unwrap().1, "first");
+}
+
+#[test]
+fn remove() {
+    let s = HashSet::new();
+
+    for x in 0..100 {
+        s.insert(x);
+    }
+    for x in (0..100).filter(|x| x % 3 == 0) {
+        assert!(s.remove(&x));
+    }
+    for x in 0..100 {
+        assert_eq!(s.contains(&x), x % 3 != 0);
+        assert_eq!(s.remove(&x), x % 3 != 0);
+    }
+    assert!(s.is_empty());
+}
+
+#[test]
+fn take() {
+    let s = HashSet::new();
+    s.insert("apple".to_string());
+    s.insert("banana".to_string());
+
+    let guard = &s.guard();
+    let apple = s.take("apple", guard).unwrap();
+    assert_eq!(apple, "apple");
+    assert_eq!(s.take("apple", guard), None);
+    assert!(!s.contains("apple"));
+    assert!(s.contains("banana"));
+}
+
+#[test]
+fn iter() {
+    let s = HashSet::new();
+    let guard = &s.guard();
+    assert_eq!(s.iter(guard).count(), 0);
+
+    for x in 0..1000 {
+        s.insert(x);
+    }
+    for x in 0..500 {
+        s.remove(&(x * 2));
+    }
+
+    let mut elements: Vec<_> = s.iter(guard).cloned().collect();
+    elements.sort();
+    let expected: Vec<_> = (0..500).map(|x| x * 2 + 1).collect();
+    assert_eq!(elements, expected);
+}
+
+#[test]
+fn from_iter() {
+    let s: HashSet<_> = vec![3, 1, 4, 1, 5, 9, 2, 6, 5, 3].into_iter().collect();
+    assert_eq!(s.len(), 7);
+    assert!(s.contains(&9));
+}
+
+#[test]
+fn concurrent_dedup() {
+    const THREADS: usize = 4;
+    const N: usize = 1000;
+
+    let s = HashSet::new();
+    let inserted = thread::scope(|scope| {
+        let handles: Vec<_> = (0..THREADS)
+            .map(|_| {
+                let s = &s;
+                // Every thread inserts the same elements, and exactly one of them wins each.
+                scope.spawn(move |_| (0..N).filter(|&x| s.insert(x)).count())
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .sum::<usize>()
+    })
+    .unwrap();
+
+    assert_eq!(inserted, N);
+    assert_eq!(s.len(), N);
+}
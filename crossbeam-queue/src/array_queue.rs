@@ -1,3 +1,5 @@
+//! A bounded multi-producer multi-consumer queue.
+//!
 //! The implementation is based on Dmitry Vyukov's bounded MPMC queue.
 //!
 //! Source:
@@ -6,8 +8,10 @@
 use alloc::boxed::Box;
 use core::cell::UnsafeCell;
 use core::fmt;
+use core::iter::FusedIterator;
 use core::marker::PhantomData;
 use core::mem::{self, MaybeUninit};
+use core::ptr;
 use core::sync::atomic::{self, AtomicUsize, Ordering};
 
 use crossbeam_utils::{Backoff, CachePadded};
@@ -71,6 +75,12 @@ pub struct ArrayQueue<T> {
     /// A stamp with the value of `{ lap: 1, index: 0 }`.
     one_lap: usize,
 
+    /// A bit in the index part of stamps that marks a slot as a hole.
+    ///
+    /// A hole is a slot that was claimed by [`ArrayQueue::push_slot`] but released without
+    /// writing an element into it. Poppers skip holes instead of reading from them.
+    hole: usize,
+
     /// Indicates that dropping an `ArrayQueue<T>` may drop elements of type `T`.
     _marker: PhantomData<T>,
 }
@@ -117,34 +127,32 @@ impl<T> ArrayQueue<T> {
             ptr
         };
 
-        // One lap is the smallest power of two greater than `cap`.
-        let one_lap = (cap + 1).next_power_of_two();
+        // One lap is twice the smallest power of two greater than `cap`, which leaves the highest
+        // bit of the index unused by real indices so that it can mark holes.
+        let hole = (cap + 1).next_power_of_two();
+        let one_lap = hole * 2;
 
         ArrayQueue {
             buffer,
             cap,
             one_lap,
+            hole,
             head: CachePadded::new(AtomicUsize::new(head)),
             tail: CachePadded::new(AtomicUsize::new(tail)),
             _marker: PhantomData,
         }
     }
 
-    /// Attempts to push an element into the queue.
-    ///
-    /// If the queue is full, the element is returned back as an error.
-    ///
-    /// # Examples
+    /// Claims the slot at the tail of the queue, calling `f` when that slot still holds an
+    /// element from the previous lap.
     ///
-    /// ```
-    /// use crossbeam_queue::ArrayQueue;
-    ///
-    /// let q = ArrayQueue::new(1);
-    ///
-    /// assert_eq!(q.push(10), Ok(()));
-    /// assert_eq!(q.push(20), Err(20));
-    /// ```
-    pub fn push(&self, value: T) -> Result<(), T> {
+    /// `f` receives `value`, the tail, the new tail, and the slot. It returns `Err` to stop
+    /// pushing, or `Ok` with `value` to try again. On success, `value` is returned together with
+    /// the claimed tail and slot, and the caller must then publish the slot by updating its stamp.
+    fn push_or_else<V, R, F>(&self, mut value: V, f: F) -> Result<(V, usize, &Slot<T>), R>
+    where
+        F: Fn(V, usize, usize, &Slot<T>) -> Result<V, R>,
+    {
         let backoff = Backoff::new();
         let mut tail = self.tail.load(Ordering::Relaxed);
 
@@ -153,22 +161,22 @@ impl<T> ArrayQueue<T> {
             let index = tail & (self.one_lap - 1);
             let lap = tail & !(self.one_lap - 1);
 
+            let new_tail = if index + 1 < self.cap {
+                // Same lap, incremented index.
+                // Set to `{ lap: lap, index: index + 1 }`.
+                tail + 1
+            } else {
+                // One lap forward, index wraps around to zero.
+                // Set to `{ lap: lap.wrapping_add(1), index: 0 }`.
+                lap.wrapping_add(self.one_lap)
+            };
+
             // Inspect the corresponding slot.
             let slot = unsafe { &*self.buffer.add(index) };
             let stamp = slot.stamp.load(Ordering::Acquire);
 
             // If the tail and the stamp match, we may attempt to push.
             if tail == stamp {
-                let new_tail = if index + 1 < self.cap {
-                    // Same lap, incremented index.
-                    // Set to `{ lap: lap, index: index + 1 }`.
-                    tail + 1
-                } else {
-                    // One lap forward, index wraps around to zero.
-                    // Set to `{ lap: lap.wrapping_add(1), index: 0 }`.
-                    lap.wrapping_add(self.one_lap)
-                };
-
                 // Try moving the tail.
                 match self.tail.compare_exchange_weak(
                     tail,
@@ -176,29 +184,15 @@ impl<T> ArrayQueue<T> {
                     Ordering::SeqCst,
                     Ordering::Relaxed,
                 ) {
-                    Ok(_) => {
-                        // Write the value into the slot and update the stamp.
-                        unsafe {
-                            slot.value.get().write(MaybeUninit::new(value));
-                        }
-                        slot.stamp.store(tail + 1, Ordering::Release);
-                        return Ok(());
-                    }
+                    Ok(_) => return Ok((value, tail, slot)),
                     Err(t) => {
                         tail = t;
                         backoff.spin();
                     }
                 }
-            } else if stamp.wrapping_add(self.one_lap) == tail + 1 {
+            } else if stamp.wrapping_add(self.one_lap) & !self.hole == tail + 1 {
                 atomic::fence(Ordering::SeqCst);
-                let head = self.head.load(Ordering::Relaxed);
-
-                // If the head lags one lap behind the tail as well...
-                if head.wrapping_add(self.one_lap) == tail {
-                    // ...then the queue is full.
-                    return Err(value);
-                }
-
+                value = f(value, tail, new_tail, slot)?;
                 backoff.spin();
                 tail = self.tail.load(Ordering::Relaxed);
             } else {
@@ -209,6 +203,180 @@ impl<T> ArrayQueue<T> {
         }
     }
 
+    /// Returns `Err` if the queue is full, given the tail of a slot that still holds an element
+    /// from the previous lap.
+    fn check_full<V>(&self, value: V, tail: usize) -> Result<V, V> {
+        let head = self.head.load(Ordering::Relaxed);
+
+        // If the head lags one lap behind the tail as well...
+        if head.wrapping_add(self.one_lap) == tail {
+            // ...then the queue is full.
+            Err(value)
+        } else {
+            Ok(value)
+        }
+    }
+
+    /// Attempts to push an element into the queue.
+    ///
+    /// If the queue is full, the element is returned back as an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::ArrayQueue;
+    ///
+    /// let q = ArrayQueue::new(1);
+    ///
+    /// assert_eq!(q.push(10), Ok(()));
+    /// assert_eq!(q.push(20), Err(20));
+    /// ```
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let (value, tail, slot) =
+            self.push_or_else(value, |v, tail, _, _| self.check_full(v, tail))?;
+
+        // Write the value into the slot and update the stamp.
+        unsafe {
+            slot.value.get().write(MaybeUninit::new(value));
+        }
+        slot.stamp.store(tail + 1, Ordering::Release);
+        Ok(())
+    }
+
+    /// Pushes an element into the queue, replacing the oldest element if necessary.
+    ///
+    /// If the queue is full, the oldest element is replaced and returned, otherwise `None` is
+    /// returned. This makes the queue behave like a ring buffer that keeps the most recent
+    /// elements.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::ArrayQueue;
+    ///
+    /// let q = ArrayQueue::new(2);
+    ///
+    /// assert_eq!(q.force_push(10), None);
+    /// assert_eq!(q.force_push(20), None);
+    /// assert_eq!(q.force_push(30), Some(10));
+    /// assert_eq!(q.pop(), Some(20));
+    /// ```
+    pub fn force_push(&self, value: T) -> Option<T> {
+        let result = self.push_or_else(value, |v, tail, new_tail, slot| {
+            let head = tail.wrapping_sub(self.one_lap);
+            let new_head = new_tail.wrapping_sub(self.one_lap);
+
+            // Try moving the head.
+            if self
+                .head
+                .compare_exchange_weak(head, new_head, Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok()
+            {
+                // Move the tail.
+                self.tail.store(new_tail, Ordering::SeqCst);
+
+                // Mark the slot as busy while the value is replaced, so that `peek` doesn't mistake
+                // the new value for the old one. Every thread takes this stamp to mean that the
+                // slot is not ready yet.
+                let stamp = slot.stamp.swap(head, Ordering::Acquire);
+                atomic::fence(Ordering::Release);
+
+                // Swap the previous value, unless the slot is a hole and doesn't have one.
+                let old = if stamp & self.hole == 0 {
+                    Some(unsafe { slot.value.get().replace(MaybeUninit::new(v)).assume_init() })
+                } else {
+                    unsafe { slot.value.get().write(MaybeUninit::new(v)) };
+                    None
+                };
+
+                // Update the stamp.
+                slot.stamp.store(tail + 1, Ordering::Release);
+
+                Err(old)
+            } else {
+                Ok(v)
+            }
+        });
+
+        match result {
+            Ok((value, tail, slot)) => {
+                // Write the value into the slot and update the stamp.
+                unsafe {
+                    slot.value.get().write(MaybeUninit::new(value));
+                }
+                slot.stamp.store(tail + 1, Ordering::Release);
+                None
+            }
+            Err(old) => old,
+        }
+    }
+
+    /// Attempts to claim a slot at the tail of the queue, so that an element can be written
+    /// directly into it.
+    ///
+    /// If the queue is full, `None` is returned. Otherwise, the element is pushed once it has been
+    /// written into the returned [`PushSlot`] and the slot has been committed. This avoids
+    /// building a large element on the stack only to move it into the queue.
+    ///
+    /// Until the slot is committed or dropped, it counts towards the length of the queue, and
+    /// poppers that reach it wait for it. If the slot is dropped without being committed, it is
+    /// skipped by poppers instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::ArrayQueue;
+    ///
+    /// let q = ArrayQueue::<[u8; 4096]>::new(1);
+    ///
+    /// let mut slot = q.push_slot().unwrap();
+    /// let p = slot.as_mut_ptr();
+    /// unsafe {
+    ///     p.write_bytes(7, 1);
+    ///     slot.commit();
+    /// }
+    ///
+    /// assert!(q.push_slot().is_none());
+    /// assert_eq!(q.pop().unwrap()[4095], 7);
+    /// ```
+    pub fn push_slot(&self) -> Option<PushSlot<'_, T>> {
+        let ((), tail, slot) = self
+            .push_or_else((), |(), tail, _, _| self.check_full((), tail))
+            .ok()?;
+
+        Some(PushSlot {
+            slot,
+            tail,
+            hole: self.hole,
+        })
+    }
+
+    /// Moves the head past a hole and returns the current head.
+    fn skip_hole(&self, head: usize, slot: &Slot<T>) -> usize {
+        // Deconstruct the head.
+        let index = head & (self.one_lap - 1);
+        let lap = head & !(self.one_lap - 1);
+
+        let new = if index + 1 < self.cap {
+            head + 1
+        } else {
+            lap.wrapping_add(self.one_lap)
+        };
+
+        match self
+            .head
+            .compare_exchange_weak(head, new, Ordering::SeqCst, Ordering::Relaxed)
+        {
+            Ok(_) => {
+                // There is nothing to read, so just mark the slot as free for the next lap.
+                slot.stamp
+                    .store(head.wrapping_add(self.one_lap), Ordering::Release);
+                new
+            }
+            Err(h) => h,
+        }
+    }
+
     /// Attempts to pop an element from the queue.
     ///
     /// If the queue is empty, `None` is returned.
@@ -268,6 +436,10 @@ impl<T> ArrayQueue<T> {
                         backoff.spin();
                     }
                 }
+            } else if stamp == (head + 1) | self.hole {
+                // The slot is a hole, so skip it.
+                head = self.skip_hole(head, slot);
+                backoff.spin();
             } else if stamp == head {
                 atomic::fence(Ordering::SeqCst);
                 let tail = self.tail.load(Ordering::Relaxed);
@@ -287,6 +459,82 @@ impl<T> ArrayQueue<T> {
         }
     }
 
+    /// Returns a copy of the element at the head of the queue without removing it.
+    ///
+    /// If the queue is empty, `None` is returned.
+    ///
+    /// The element is read optimistically and the read is retried if the slot was popped or
+    /// overwritten in the meantime. This is why `T` must be `Copy`: a concurrent pop may take
+    /// ownership of the element while it is being read, so the copy must not run any code. By
+    /// the time this method returns, another thread may have already popped the element.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::ArrayQueue;
+    ///
+    /// let q = ArrayQueue::new(2);
+    /// assert_eq!(q.peek(), None);
+    ///
+    /// q.push(10).unwrap();
+    /// q.push(20).unwrap();
+    ///
+    /// assert_eq!(q.peek(), Some(10));
+    /// assert_eq!(q.pop(), Some(10));
+    /// assert_eq!(q.peek(), Some(20));
+    /// ```
+    pub fn peek(&self) -> Option<T>
+    where
+        T: Copy,
+    {
+        let backoff = Backoff::new();
+        let mut head = self.head.load(Ordering::Relaxed);
+
+        loop {
+            // Deconstruct the head.
+            let index = head & (self.one_lap - 1);
+
+            // Inspect the corresponding slot.
+            let slot = unsafe { &*self.buffer.add(index) };
+            let stamp = slot.stamp.load(Ordering::Acquire);
+
+            // If the the stamp is ahead of the head by 1, the slot holds the head element.
+            if head + 1 == stamp {
+                // Read the value without taking ownership of it.
+                let value = unsafe { ptr::read_volatile(slot.value.get()) };
+                atomic::fence(Ordering::Acquire);
+
+                // The slot cannot be written to again until its stamp moves on, so if the stamp is
+                // unchanged the read was not torn.
+                if slot.stamp.load(Ordering::Relaxed) == stamp {
+                    return Some(unsafe { value.assume_init() });
+                }
+
+                backoff.spin();
+                head = self.head.load(Ordering::Relaxed);
+            } else if stamp == (head + 1) | self.hole {
+                // The slot is a hole, so skip it.
+                head = self.skip_hole(head, slot);
+                backoff.spin();
+            } else if stamp == head {
+                atomic::fence(Ordering::SeqCst);
+                let tail = self.tail.load(Ordering::Relaxed);
+
+                // If the tail equals the head, that means the queue is empty.
+                if tail == head {
+                    return None;
+                }
+
+                backoff.spin();
+                head = self.head.load(Ordering::Relaxed);
+            } else {
+                // Snooze because we need to wait for the stamp to get updated.
+                backoff.snooze();
+                head = self.head.load(Ordering::Relaxed);
+            }
+        }
+    }
+
     /// Returns the capacity of the queue.
     ///
     /// # Examples
@@ -390,6 +638,29 @@ impl<T> ArrayQueue<T> {
             }
         }
     }
+
+    /// Returns an iterator that pops elements from the queue until it becomes empty.
+    ///
+    /// Elements pushed by other threads while the iterator is in use may also be returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::ArrayQueue;
+    ///
+    /// let q = ArrayQueue::new(3);
+    /// q.push(1).unwrap();
+    /// q.push(2).unwrap();
+    /// q.push(3).unwrap();
+    ///
+    /// let v: Vec<_> = q.drain().collect();
+    ///
+    /// assert_eq!(v, [1, 2, 3]);
+    /// assert!(q.is_empty());
+    /// ```
+    pub fn drain(&self) -> ArrayQueueDrain<'_, T> {
+        ArrayQueueDrain { queue: self }
+    }
 }
 
 impl<T> Drop for ArrayQueue<T> {
@@ -407,12 +678,13 @@ impl<T> Drop for ArrayQueue<T> {
             };
 
             unsafe {
-                let p = {
-                    let slot = &mut *self.buffer.add(index);
+                let slot = &mut *self.buffer.add(index);
+
+                // Holes don't hold a message.
+                if *slot.stamp.get_mut() & self.hole == 0 {
                     let value = &mut *slot.value.get();
-                    value.as_mut_ptr()
-                };
-                p.drop_in_place();
+                    value.as_mut_ptr().drop_in_place();
+                }
             }
         }
 
@@ -432,3 +704,199 @@ impl<T> fmt::Debug for ArrayQueue<T> {
         f.pad("ArrayQueue { .. }")
     }
 }
+
+impl<T> IntoIterator for ArrayQueue<T> {
+    type Item = T;
+    type IntoIter = ArrayQueueIntoIter<T>;
+
+    fn into_iter(mut self) -> Self::IntoIter {
+        // Count the elements, leaving out holes.
+        let hix = *self.head.get_mut() & (self.one_lap - 1);
+        let remaining = (0..self.len())
+            .filter(|i| {
+                let index = (hix + i) % self.cap;
+                let slot = unsafe { &*self.buffer.add(index) };
+                slot.stamp.load(Ordering::Relaxed) & self.hole == 0
+            })
+            .count();
+
+        ArrayQueueIntoIter {
+            queue: self,
+            remaining,
+        }
+    }
+}
+
+/// A draining iterator over elements of an [`ArrayQueue`].
+///
+/// This iterator is created by [`ArrayQueue::drain`]. Each call to [`next`] pops an element from
+/// the queue, and the iterator ends as soon as the queue is observed empty.
+///
+/// [`next`]: Iterator::next
+pub struct ArrayQueueDrain<'a, T> {
+    queue: &'a ArrayQueue<T>,
+}
+
+impl<T> Iterator for ArrayQueueDrain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.queue.pop()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, None)
+    }
+}
+
+impl<T> fmt::Debug for ArrayQueueDrain<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("ArrayQueueDrain { .. }")
+    }
+}
+
+/// An owning iterator over elements of an [`ArrayQueue`].
+///
+/// This iterator is created by the [`into_iter`] method on [`ArrayQueue`]. Since the queue is
+/// owned, elements are moved out of the buffer without any synchronization.
+///
+/// [`into_iter`]: IntoIterator::into_iter
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_queue::ArrayQueue;
+///
+/// let q = ArrayQueue::new(3);
+/// q.push('a').unwrap();
+/// q.push('b').unwrap();
+///
+/// let v: Vec<_> = q.into_iter().collect();
+///
+/// assert_eq!(v, ['a', 'b']);
+/// ```
+pub struct ArrayQueueIntoIter<T> {
+    queue: ArrayQueue<T>,
+    remaining: usize,
+}
+
+impl<T> Iterator for ArrayQueueIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let queue = &mut self.queue;
+
+        loop {
+            let head = *queue.head.get_mut();
+
+            if head == *queue.tail.get_mut() {
+                return None;
+            }
+
+            // Deconstruct the head.
+            let index = head & (queue.one_lap - 1);
+            let lap = head & !(queue.one_lap - 1);
+
+            *queue.head.get_mut() = if index + 1 < queue.cap {
+                // Same lap, incremented index.
+                head + 1
+            } else {
+                // One lap forward, index wraps around to zero.
+                lap.wrapping_add(queue.one_lap)
+            };
+
+            // Move the value out of the slot, unless it is a hole. The queue is owned, so nobody
+            // else can observe it.
+            let slot = unsafe { &mut *queue.buffer.add(index) };
+            if *slot.stamp.get_mut() & queue.hole == 0 {
+                self.remaining -= 1;
+                return Some(unsafe { slot.value.get().read().assume_init() });
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<T> ExactSizeIterator for ArrayQueueIntoIter<T> {}
+
+impl<T> FusedIterator for ArrayQueueIntoIter<T> {}
+
+impl<T> fmt::Debug for ArrayQueueIntoIter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("ArrayQueueIntoIter { .. }")
+    }
+}
+
+/// A slot claimed at the tail of an [`ArrayQueue`].
+///
+/// This is created by [`ArrayQueue::push_slot`]. Once an element has been written into the slot,
+/// [`commit`] pushes it. If the slot is dropped without being committed, nothing is pushed and
+/// poppers skip the slot.
+///
+/// [`commit`]: PushSlot::commit
+pub struct PushSlot<'a, T> {
+    /// The claimed slot.
+    slot: &'a Slot<T>,
+
+    /// The tail the slot was claimed at.
+    tail: usize,
+
+    /// The bit that marks holes in the queue the slot belongs to.
+    hole: usize,
+}
+
+impl<T> PushSlot<'_, T> {
+    /// Returns a pointer to the uninitialized element in the slot.
+    pub fn as_mut_ptr(&mut self) -> *mut T {
+        unsafe { (*self.slot.value.get()).as_mut_ptr() }
+    }
+
+    /// Writes an element into the slot and pushes it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::ArrayQueue;
+    ///
+    /// let q = ArrayQueue::new(1);
+    ///
+    /// q.push_slot().unwrap().write(10);
+    /// assert_eq!(q.pop(), Some(10));
+    /// ```
+    pub fn write(mut self, value: T) {
+        unsafe {
+            self.as_mut_ptr().write(value);
+            self.commit();
+        }
+    }
+
+    /// Pushes the element that has been written into the slot.
+    ///
+    /// # Safety
+    ///
+    /// The element behind [`as_mut_ptr`] must have been fully initialized.
+    ///
+    /// [`as_mut_ptr`]: PushSlot::as_mut_ptr
+    pub unsafe fn commit(self) {
+        self.slot.stamp.store(self.tail + 1, Ordering::Release);
+        mem::forget(self);
+    }
+}
+
+impl<T> Drop for PushSlot<'_, T> {
+    fn drop(&mut self) {
+        // Nothing was written, so publish the slot as a hole.
+        self.slot
+            .stamp
+            .store((self.tail + 1) | self.hole, Ordering::Release);
+    }
+}
+
+impl<T> fmt::Debug for PushSlot<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("PushSlot { .. }")
+    }
+}
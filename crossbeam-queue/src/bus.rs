@@ -0,0 +1,680 @@
+//! A publish-subscribe event bus.
+//!
+//! An [`EventBus`] delivers every published event to every [`Subscriber`], in the order it was
+//! published. Unlike a channel, where each message is received once, every subscriber gets its
+//! own clone of each event that was published after it subscribed.
+//!
+//! Events are stored once, in a list of blocks shared by all subscribers, and each subscriber
+//! keeps a cursor into it. Blocks that every subscriber has moved past are destroyed through
+//! [`crossbeam-epoch`]. Publishing and receiving don't take any locks, except for the occasional
+//! check of how far the slowest subscriber lags behind.
+//!
+//! A subscriber may lag at most [`capacity`] events behind the publishers. What happens when a
+//! publisher would exceed that is decided by the [`LagPolicy`] of the bus.
+//!
+//! [`crossbeam-epoch`]: https://docs.rs/crossbeam-epoch
+//! [`capacity`]: EventBus::capacity
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam_queue::bus::EventBus;
+//! use std::thread;
+//!
+//! let bus = EventBus::new(16);
+//!
+//! let handles: Vec<_> = (0..3)
+//!     .map(|_| {
+//!         let mut sub = bus.subscribe();
+//!         thread::spawn(move || {
+//!             let mut sum = 0;
+//!             while let Ok(event) = sub.recv() {
+//!                 sum += event;
+//!             }
+//!             sum
+//!         })
+//!     })
+//!     .collect();
+//!
+//! for i in 1..=100 {
+//!     bus.publish(i);
+//! }
+//! drop(bus);
+//!
+//! for h in handles {
+//!     assert_eq!(h.join().unwrap(), 5050);
+//! }
+//! ```
+
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ptr;
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::error;
+use std::sync::Mutex;
+
+use crossbeam_epoch as epoch;
+use crossbeam_utils::CachePadded;
+
+use crate::segment::{Block, Segments, BLOCK_CAP};
+use crate::waiter::Waiter;
+
+/// What publishers do when a subscriber lags too far behind.
+///
+/// A subscriber lags too far behind when publishing an event would put more than
+/// [`capacity`] unreceived events in front of it.
+///
+/// [`capacity`]: EventBus::capacity
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LagPolicy {
+    /// Publishers wait until the subscriber catches up.
+    ///
+    /// A subscriber that stops receiving events therefore stops all publishers.
+    Block,
+
+    /// The subscriber skips its oldest events, which it never receives.
+    ///
+    /// The number of skipped events is reported by [`Subscriber::missed`].
+    Drop,
+
+    /// The subscriber is disconnected, and receives no more events.
+    Disconnect,
+}
+
+impl Default for LagPolicy {
+    fn default() -> LagPolicy {
+        LagPolicy::Block
+    }
+}
+
+/// The position of a subscriber.
+struct Cursor {
+    /// The index of the next event the subscriber receives.
+    ///
+    /// This is advanced by the subscriber, and by publishers under [`LagPolicy::Drop`].
+    pos: CachePadded<AtomicUsize>,
+
+    /// Set when the subscriber is disconnected under [`LagPolicy::Disconnect`].
+    disconnected: AtomicBool,
+}
+
+/// The state shared by publishers and subscribers.
+struct Inner<T> {
+    /// The number of events claimed by publishers.
+    tail: CachePadded<AtomicUsize>,
+
+    /// The slots holding events. Blocks are only reclaimed with `subscribers` locked.
+    segments: Segments<T>,
+
+    /// A lower bound on the positions of all subscribers, updated with `subscribers` locked.
+    low: CachePadded<AtomicUsize>,
+
+    /// The cursors of all connected subscribers.
+    subscribers: Mutex<Vec<Arc<Cursor>>>,
+
+    /// The number of `EventBus` handles that are still alive.
+    publishers: AtomicUsize,
+
+    /// How far subscribers may lag behind.
+    capacity: usize,
+
+    /// What happens to subscribers that lag further behind.
+    policy: LagPolicy,
+
+    /// Subscribers waiting for events.
+    events: Waiter,
+
+    /// Publishers waiting for slow subscribers under [`LagPolicy::Block`].
+    space: Waiter,
+}
+
+unsafe impl<T: Send + Sync> Send for Inner<T> {}
+unsafe impl<T: Send + Sync> Sync for Inner<T> {}
+
+impl<T> Inner<T> {
+    /// Deals with subscribers that would lag too far behind once the event at index `tail` is
+    /// published, and destroys blocks that no subscriber needs anymore.
+    ///
+    /// Returns `true` if the event can be published.
+    fn make_room(&self, tail: usize) -> bool {
+        let guard = &epoch::pin();
+        let mut subscribers = self
+            .subscribers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+
+        // Subscribers at positions below `threshold` would lag too far behind.
+        let threshold = (tail + 1).saturating_sub(self.capacity);
+        match self.policy {
+            LagPolicy::Block => {}
+            LagPolicy::Drop => {
+                for cursor in subscribers.iter() {
+                    let mut pos = cursor.pos.load(Ordering::SeqCst);
+                    while pos < threshold {
+                        match cursor.pos.compare_exchange(
+                            pos,
+                            threshold,
+                            Ordering::SeqCst,
+                            Ordering::SeqCst,
+                        ) {
+                            Ok(_) => break,
+                            Err(p) => pos = p,
+                        }
+                    }
+                }
+            }
+            LagPolicy::Disconnect => {
+                subscribers.retain(|cursor| {
+                    if cursor.pos.load(Ordering::SeqCst) < threshold {
+                        cursor.disconnected.store(true, Ordering::SeqCst);
+                        false
+                    } else {
+                        true
+                    }
+                });
+            }
+        }
+
+        let low = subscribers
+            .iter()
+            .map(|cursor| cursor.pos.load(Ordering::SeqCst))
+            .min()
+            .unwrap_or(tail);
+        self.low.store(low, Ordering::SeqCst);
+        self.segments.reclaim(low, guard);
+        drop(subscribers);
+
+        if self.policy != LagPolicy::Block {
+            // Wake up subscribers that were moved forward or disconnected.
+            self.events.notify();
+        }
+        tail.saturating_sub(low) < self.capacity
+    }
+}
+
+/// A publish-subscribe event bus.
+///
+/// See the [module-level documentation](self) for more.
+///
+/// The bus itself is the publishing handle, and can be cloned to publish from multiple threads.
+/// Subscribers are created with [`subscribe`], and are disconnected once all handles have been
+/// dropped and they have received every event.
+///
+/// [`subscribe`]: EventBus::subscribe
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_queue::bus::EventBus;
+///
+/// let bus = EventBus::new(16);
+/// let mut a = bus.subscribe();
+/// let mut b = bus.subscribe();
+///
+/// bus.publish("started");
+/// assert_eq!(a.try_recv(), Ok("started"));
+/// assert_eq!(b.try_recv(), Ok("started"));
+/// ```
+pub struct EventBus<T> {
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> EventBus<T> {
+    /// Creates a new event bus where subscribers may lag `capacity` events behind, after which
+    /// publishers wait for them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::bus::EventBus;
+    ///
+    /// let bus = EventBus::<i32>::new(1024);
+    /// ```
+    pub fn new(capacity: usize) -> EventBus<T> {
+        EventBus::with_policy(capacity, LagPolicy::Block)
+    }
+
+    /// Creates a new event bus where subscribers may lag `capacity` events behind, after which
+    /// `policy` applies.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::bus::{EventBus, LagPolicy};
+    ///
+    /// let bus = EventBus::with_policy(2, LagPolicy::Drop);
+    /// let mut sub = bus.subscribe();
+    ///
+    /// for i in 0..5 {
+    ///     bus.publish(i);
+    /// }
+    ///
+    /// // The oldest events were skipped.
+    /// assert_eq!(sub.try_recv(), Ok(3));
+    /// assert_eq!(sub.try_recv(), Ok(4));
+    /// assert_eq!(sub.missed(), 3);
+    /// ```
+    pub fn with_policy(capacity: usize, policy: LagPolicy) -> EventBus<T> {
+        assert!(capacity > 0, "capacity must be positive");
+
+        EventBus {
+            inner: Arc::new(Inner {
+                tail: CachePadded::new(AtomicUsize::new(0)),
+                segments: Segments::new(),
+                low: CachePadded::new(AtomicUsize::new(0)),
+                subscribers: Mutex::new(Vec::new()),
+                publishers: AtomicUsize::new(1),
+                capacity,
+                policy,
+                events: Waiter::new(),
+                space: Waiter::new(),
+            }),
+        }
+    }
+
+    /// Creates a new subscriber, which receives every event published from now on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::bus::EventBus;
+    ///
+    /// let bus = EventBus::new(16);
+    /// bus.publish(1);
+    ///
+    /// let mut sub = bus.subscribe();
+    /// bus.publish(2);
+    /// assert_eq!(sub.try_recv(), Ok(2));
+    /// ```
+    pub fn subscribe(&self) -> Subscriber<T> {
+        let mut subscribers = self
+            .inner
+            .subscribers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+
+        let pos = self.inner.tail.load(Ordering::SeqCst);
+        let cursor = Arc::new(Cursor {
+            pos: CachePadded::new(AtomicUsize::new(pos)),
+            disconnected: AtomicBool::new(false),
+        });
+        subscribers.push(cursor.clone());
+
+        Subscriber {
+            inner: self.inner.clone(),
+            cursor,
+            pos,
+            block: ptr::null(),
+            missed: 0,
+        }
+    }
+
+    /// Publishes an event to all subscribers.
+    ///
+    /// Under [`LagPolicy::Block`], this waits while a subscriber lags too far behind. If there
+    /// are no subscribers, the event is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::bus::EventBus;
+    ///
+    /// let bus = EventBus::new(16);
+    /// let mut sub = bus.subscribe();
+    ///
+    /// bus.publish(10);
+    /// bus.publish(20);
+    /// assert_eq!(sub.try_recv(), Ok(10));
+    /// assert_eq!(sub.try_recv(), Ok(20));
+    /// ```
+    pub fn publish(&self, value: T) {
+        let inner = &*self.inner;
+
+        let index = loop {
+            let tail = inner.tail.load(Ordering::SeqCst);
+            if tail.saturating_sub(inner.low.load(Ordering::SeqCst)) >= inner.capacity {
+                if inner.policy == LagPolicy::Block {
+                    inner.space.wait_until(|| inner.make_room(tail));
+                } else {
+                    inner.make_room(tail);
+                }
+                continue;
+            }
+
+            if inner
+                .tail
+                .compare_exchange_weak(tail, tail + 1, Ordering::SeqCst, Ordering::Relaxed)
+                .is_ok()
+            {
+                break tail;
+            }
+        };
+
+        let guard = &epoch::pin();
+        // If the block is gone, every subscriber has skipped the event already.
+        if let Some(block) = inner.segments.block(index, guard) {
+            unsafe { block.slot(index).write(value) };
+        }
+        inner.events.notify();
+    }
+
+    /// Returns the number of events subscribers may lag behind.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::bus::EventBus;
+    ///
+    /// let bus = EventBus::<i32>::new(100);
+    /// assert_eq!(bus.capacity(), 100);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity
+    }
+
+    /// Returns what happens to subscribers that lag too far behind.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::bus::{EventBus, LagPolicy};
+    ///
+    /// let bus = EventBus::<i32>::with_policy(100, LagPolicy::Disconnect);
+    /// assert_eq!(bus.policy(), LagPolicy::Disconnect);
+    /// ```
+    pub fn policy(&self) -> LagPolicy {
+        self.inner.policy
+    }
+
+    /// Returns the number of connected subscribers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::bus::EventBus;
+    ///
+    /// let bus = EventBus::<i32>::new(16);
+    /// let a = bus.subscribe();
+    /// let b = bus.subscribe();
+    /// assert_eq!(bus.subscriber_count(), 2);
+    ///
+    /// drop(a);
+    /// assert_eq!(bus.subscriber_count(), 1);
+    /// ```
+    pub fn subscriber_count(&self) -> usize {
+        self.inner
+            .subscribers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .len()
+    }
+}
+
+impl<T> Clone for EventBus<T> {
+    fn clone(&self) -> EventBus<T> {
+        self.inner.publishers.fetch_add(1, Ordering::SeqCst);
+        EventBus {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl<T> Drop for EventBus<T> {
+    fn drop(&mut self) {
+        if self.inner.publishers.fetch_sub(1, Ordering::SeqCst) == 1 {
+            // Wake up subscribers so that they notice the disconnection.
+            self.inner.events.notify();
+        }
+    }
+}
+
+impl<T> fmt::Debug for EventBus<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("EventBus { .. }")
+    }
+}
+
+/// A subscriber of an [`EventBus`].
+///
+/// Subscribers are created with [`EventBus::subscribe`].
+pub struct Subscriber<T> {
+    /// The shared bus.
+    inner: Arc<Inner<T>>,
+
+    /// The position of this subscriber, shared with publishers.
+    cursor: Arc<Cursor>,
+
+    /// The last position of this subscriber that it knows of.
+    pos: usize,
+
+    /// The block holding the slot at `pos`, or null if it hasn't been looked up yet.
+    ///
+    /// The block can't be destroyed while the subscriber is at `pos`.
+    block: *const Block<T>,
+
+    /// The number of events skipped under [`LagPolicy::Drop`].
+    missed: usize,
+}
+
+unsafe impl<T: Send + Sync> Send for Subscriber<T> {}
+
+impl<T: Clone> Subscriber<T> {
+    /// Attempts to receive an event without blocking.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::bus::{EventBus, TryRecvError};
+    ///
+    /// let bus = EventBus::new(16);
+    /// let mut sub = bus.subscribe();
+    /// assert_eq!(sub.try_recv(), Err(TryRecvError::Empty));
+    ///
+    /// bus.publish(1);
+    /// assert_eq!(sub.try_recv(), Ok(1));
+    ///
+    /// drop(bus);
+    /// assert_eq!(sub.try_recv(), Err(TryRecvError::Disconnected));
+    /// ```
+    pub fn try_recv(&mut self) -> Result<T, TryRecvError> {
+        let inner = &*self.inner;
+        let guard = &epoch::pin();
+
+        loop {
+            if self.cursor.disconnected.load(Ordering::SeqCst) {
+                self.block = ptr::null();
+                return Err(TryRecvError::Disconnected);
+            }
+
+            let pos = self.cursor.pos.load(Ordering::SeqCst);
+            if pos != self.pos {
+                // A publisher moved us forward, and the block we were in may be gone.
+                self.missed += pos - self.pos;
+                self.pos = pos;
+                self.block = ptr::null();
+            }
+
+            if pos >= inner.tail.load(Ordering::SeqCst) {
+                // Check the tail again after the publishers, since the last one may have
+                // published right before leaving.
+                if inner.publishers.load(Ordering::SeqCst) == 0
+                    && pos >= inner.tail.load(Ordering::SeqCst)
+                {
+                    return Err(TryRecvError::Disconnected);
+                }
+                return Err(TryRecvError::Empty);
+            }
+
+            if self.block.is_null() {
+                match inner.segments.block(pos, guard) {
+                    Some(block) => self.block = block,
+                    // The block is gone, so we must have been moved forward in the meantime.
+                    None => continue,
+                }
+            }
+
+            let slot = unsafe { (*self.block).slot(pos) };
+            if !slot.is_ready() {
+                return Err(TryRecvError::Empty);
+            }
+            let value = unsafe { slot.get().clone() };
+
+            if self
+                .cursor
+                .pos
+                .compare_exchange(pos, pos + 1, Ordering::SeqCst, Ordering::SeqCst)
+                .is_err()
+            {
+                // A publisher moved us forward while we were reading the event.
+                continue;
+            }
+
+            self.pos = pos + 1;
+            if self.pos % BLOCK_CAP == 0 {
+                // We are leaving the block, after which it may be destroyed at any time.
+                self.block = ptr::null();
+            }
+            inner.space.notify();
+            return Ok(value);
+        }
+    }
+
+    /// Blocks until an event is received.
+    ///
+    /// Returns an error if the subscriber has been disconnected, or if all [`EventBus`] handles
+    /// have been dropped and every event has been received.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::bus::{EventBus, RecvError};
+    /// use std::thread;
+    ///
+    /// let bus = EventBus::new(16);
+    /// let mut sub = bus.subscribe();
+    ///
+    /// thread::spawn(move || bus.publish(7));
+    ///
+    /// assert_eq!(sub.recv(), Ok(7));
+    /// assert_eq!(sub.recv(), Err(RecvError));
+    /// ```
+    pub fn recv(&mut self) -> Result<T, RecvError> {
+        loop {
+            match self.try_recv() {
+                Ok(value) => return Ok(value),
+                Err(TryRecvError::Disconnected) => return Err(RecvError),
+                Err(TryRecvError::Empty) => {}
+            }
+
+            let inner = self.inner.clone();
+            inner.events.wait_until(|| self.is_ready());
+        }
+    }
+}
+
+impl<T> Subscriber<T> {
+    /// Returns the number of events this subscriber skipped because it lagged too far behind.
+    ///
+    /// This is only ever non-zero under [`LagPolicy::Drop`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::bus::{EventBus, LagPolicy};
+    ///
+    /// let bus = EventBus::with_policy(1, LagPolicy::Drop);
+    /// let mut sub = bus.subscribe();
+    ///
+    /// bus.publish(1);
+    /// bus.publish(2);
+    /// assert_eq!(sub.try_recv(), Ok(2));
+    /// assert_eq!(sub.missed(), 1);
+    /// ```
+    pub fn missed(&self) -> usize {
+        self.missed
+    }
+
+    /// Returns `true` if `try_recv` would not return `TryRecvError::Empty`.
+    fn is_ready(&self) -> bool {
+        let inner = &*self.inner;
+        if self.cursor.disconnected.load(Ordering::SeqCst)
+            || self.cursor.pos.load(Ordering::SeqCst) != self.pos
+        {
+            return true;
+        }
+        if self.pos >= inner.tail.load(Ordering::SeqCst) {
+            return inner.publishers.load(Ordering::SeqCst) == 0;
+        }
+
+        let guard = &epoch::pin();
+        match inner.segments.block(self.pos, guard) {
+            Some(block) => block.slot(self.pos).is_ready(),
+            None => true,
+        }
+    }
+}
+
+impl<T> Drop for Subscriber<T> {
+    fn drop(&mut self) {
+        self.inner
+            .subscribers
+            .lock()
+            .unwrap_or_else(|err| err.into_inner())
+            .retain(|cursor| !Arc::ptr_eq(cursor, &self.cursor));
+
+        // Publishers may be waiting for this subscriber.
+        self.inner.space.notify();
+    }
+}
+
+impl<T> fmt::Debug for Subscriber<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Subscriber { .. }")
+    }
+}
+
+/// An error returned from the [`Subscriber::try_recv`] method.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TryRecvError {
+    /// No event is available right now.
+    Empty,
+
+    /// The subscriber has been disconnected, or all [`EventBus`] handles have been dropped and
+    /// every event has been received.
+    Disconnected,
+}
+
+impl fmt::Display for TryRecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TryRecvError::Empty => "receiving from an empty event bus".fmt(f),
+            TryRecvError::Disconnected => "receiving from a disconnected event bus".fmt(f),
+        }
+    }
+}
+
+impl error::Error for TryRecvError {}
+
+/// An error returned from the [`Subscriber::recv`] method.
+///
+/// The subscriber has been disconnected, or all [`EventBus`] handles have been dropped and every
+/// event has been received.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecvError;
+
+impl fmt::Display for RecvError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        "receiving from a disconnected event bus".fmt(f)
+    }
+}
+
+impl error::Error for RecvError {}
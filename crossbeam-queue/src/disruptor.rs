@@ -0,0 +1,658 @@
+//! A bounded multicast ring buffer in the style of the LMAX Disruptor.
+//!
+//! Every event published into the ring buffer is seen by every consumer. Events live in slots
+//! that are allocated once, when the ring buffer is created, and are overwritten in place by
+//! producers, so publishing never allocates.
+//!
+//! Progress is tracked with sequences, which count the events a producer has claimed or a
+//! consumer has processed. A consumer may depend on other consumers, in which case it only sees an
+//! event once all of its dependencies are done with it. This way consumers form a pipeline, for
+//! example journaling and replicating every event in parallel before handing it to business logic.
+//! Producers wait for the consumers at the end of the pipeline before reusing a slot.
+//!
+//! Both sides handle events in batches: a producer can claim several slots at once, and a
+//! consumer processes everything that is available in one go. How threads wait for each other is
+//! chosen with a [`WaitStrategy`].
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam_queue::disruptor::Disruptor;
+//! use std::thread;
+//!
+//! let mut d = Disruptor::new(64, || 0u64);
+//! let mut journal = d.consumer(&[]);
+//! let mut replicate = d.consumer(&[]);
+//! let mut business = d.consumer(&[&journal, &replicate]);
+//! let producer = d.into_producer();
+//!
+//! let journal = thread::spawn(move || while journal.poll(|_| {}) > 0 {});
+//! let replicate = thread::spawn(move || while replicate.poll(|_| {}) > 0 {});
+//! let business = thread::spawn(move || {
+//!     let mut sum = 0;
+//!     while business.poll(|e| sum += *e) > 0 {}
+//!     sum
+//! });
+//!
+//! for i in 1..=100 {
+//!     producer.publish(|e| *e = i);
+//! }
+//! drop(producer);
+//!
+//! journal.join().unwrap();
+//! replicate.join().unwrap();
+//! assert_eq!(business.join().unwrap(), 5050);
+//! ```
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::mem;
+use core::sync::atomic::{AtomicUsize, Ordering};
+use core::time::Duration;
+use std::thread;
+
+use crossbeam_utils::{Backoff, CachePadded};
+
+use crate::waiter::Waiter;
+
+/// A counter of events claimed by producers or processed by a consumer.
+type Sequence = CachePadded<AtomicUsize>;
+
+/// How a thread waits until the events or slots it needs become available.
+///
+/// Spinning gives the lowest latency at the cost of keeping a core busy, while blocking frees the
+/// core but makes every producer and consumer pay for waking up waiting threads.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WaitStrategy {
+    /// Spins in a loop without ever giving up the thread.
+    Spin,
+
+    /// Spins for a short while, then yields the thread between attempts.
+    Yield,
+
+    /// Sleeps for the given duration between attempts.
+    Sleep(Duration),
+
+    /// Blocks on a condition variable until another thread makes progress.
+    Block,
+}
+
+impl Default for WaitStrategy {
+    fn default() -> WaitStrategy {
+        WaitStrategy::Yield
+    }
+}
+
+/// The ring buffer and sequences shared by producers and consumers.
+struct Inner<T> {
+    /// The slots holding events.
+    slots: Box<[UnsafeCell<T>]>,
+
+    /// For every slot, one more than the sequence number of the last event published into it.
+    published: Box<[AtomicUsize]>,
+
+    /// The number of events claimed by producers.
+    claimed: Sequence,
+
+    /// The number of producers that are still alive.
+    producers: AtomicUsize,
+
+    /// How producers and consumers wait for each other.
+    wait_strategy: WaitStrategy,
+
+    /// Blocked threads, if `wait_strategy` is `Block`.
+    waiter: Waiter,
+}
+
+unsafe impl<T: Send + Sync> Send for Inner<T> {}
+unsafe impl<T: Send + Sync> Sync for Inner<T> {}
+
+impl<T> Inner<T> {
+    /// Returns the capacity of the ring buffer.
+    fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// Returns the slot holding the event with sequence number `seq`.
+    fn slot(&self, seq: usize) -> *mut T {
+        self.slots[seq & (self.capacity() - 1)].get()
+    }
+
+    /// Returns `true` if the event with sequence number `seq` has been published.
+    fn is_published(&self, seq: usize) -> bool {
+        self.published[seq & (self.capacity() - 1)].load(Ordering::Acquire) == seq.wrapping_add(1)
+    }
+
+    /// Waits until `cond` returns `true`.
+    fn wait_until<F: FnMut() -> bool>(&self, mut cond: F) {
+        match self.wait_strategy {
+            WaitStrategy::Spin => {
+                let backoff = Backoff::new();
+                while !cond() {
+                    backoff.spin();
+                }
+            }
+            WaitStrategy::Yield => {
+                let backoff = Backoff::new();
+                while !cond() {
+                    backoff.snooze();
+                }
+            }
+            WaitStrategy::Sleep(dur) => {
+                while !cond() {
+                    thread::sleep(dur);
+                }
+            }
+            WaitStrategy::Block => {
+                if !cond() {
+                    self.waiter.wait_until(cond);
+                }
+            }
+        }
+    }
+
+    /// Wakes up blocked threads after a sequence has moved forward.
+    fn notify(&self) {
+        if self.wait_strategy == WaitStrategy::Block {
+            self.waiter.notify();
+        }
+    }
+}
+
+/// A bounded multicast ring buffer that is being set up.
+///
+/// Consumers are added with [`consumer`], which also declares the consumers each of them depends
+/// on. Once all consumers are in place, [`into_producer`] returns the producer that publishes
+/// events to them.
+///
+/// [`consumer`]: Disruptor::consumer
+/// [`into_producer`]: Disruptor::into_producer
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_queue::disruptor::Disruptor;
+///
+/// let mut d = Disruptor::new(8, String::new);
+/// let mut c = d.consumer(&[]);
+/// let p = d.into_producer();
+///
+/// p.publish(|e| e.push_str("hello"));
+///
+/// let mut events = Vec::new();
+/// c.try_poll(|e| events.push(e.clone()));
+/// assert_eq!(events, ["hello"]);
+/// ```
+pub struct Disruptor<T> {
+    /// The shared ring buffer.
+    inner: Arc<Inner<T>>,
+
+    /// The sequences of all consumers, and whether another consumer depends on each of them.
+    consumers: Vec<(Arc<Sequence>, bool)>,
+}
+
+impl<T> Disruptor<T> {
+    /// Creates a new ring buffer and fills its slots with events returned by `factory`.
+    ///
+    /// The capacity is rounded up to the next power of two. Threads wait using the default
+    /// [`WaitStrategy::Yield`] strategy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::disruptor::Disruptor;
+    ///
+    /// let d = Disruptor::new(100, || 0u64);
+    /// assert_eq!(d.capacity(), 128);
+    /// ```
+    pub fn new<F: FnMut() -> T>(capacity: usize, factory: F) -> Disruptor<T> {
+        Disruptor::with_wait_strategy(capacity, WaitStrategy::default(), factory)
+    }
+
+    /// Creates a new ring buffer whose threads wait using `wait_strategy`.
+    ///
+    /// The capacity is rounded up to the next power of two, and the slots are filled with events
+    /// returned by `factory`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::disruptor::{Disruptor, WaitStrategy};
+    ///
+    /// let d = Disruptor::with_wait_strategy(64, WaitStrategy::Block, || 0u64);
+    /// ```
+    pub fn with_wait_strategy<F: FnMut() -> T>(
+        capacity: usize,
+        wait_strategy: WaitStrategy,
+        mut factory: F,
+    ) -> Disruptor<T> {
+        assert!(capacity > 0, "capacity must be positive");
+        let capacity = capacity.next_power_of_two();
+
+        let slots: Box<[UnsafeCell<T>]> =
+            (0..capacity).map(|_| UnsafeCell::new(factory())).collect();
+        let published: Box<[AtomicUsize]> = (0..capacity).map(|_| AtomicUsize::new(0)).collect();
+
+        Disruptor {
+            inner: Arc::new(Inner {
+                slots,
+                published,
+                claimed: CachePadded::new(AtomicUsize::new(0)),
+                producers: AtomicUsize::new(1),
+                wait_strategy,
+                waiter: Waiter::new(),
+            }),
+            consumers: Vec::new(),
+        }
+    }
+
+    /// Returns the capacity of the ring buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::disruptor::Disruptor;
+    ///
+    /// let d = Disruptor::new(16, || 0u64);
+    /// assert_eq!(d.capacity(), 16);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Adds a consumer that sees every event once all of `dependencies` have processed it.
+    ///
+    /// A consumer without dependencies sees events as soon as they are published.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a dependency belongs to another ring buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::disruptor::Disruptor;
+    ///
+    /// let mut d = Disruptor::new(8, || 0);
+    /// let mut first = d.consumer(&[]);
+    /// let mut second = d.consumer(&[&first]);
+    /// let p = d.into_producer();
+    ///
+    /// p.publish(|e| *e = 1);
+    ///
+    /// // The second consumer has to wait for the first one.
+    /// assert_eq!(second.try_poll(|_| {}), 0);
+    /// assert_eq!(first.try_poll(|_| {}), 1);
+    /// assert_eq!(second.try_poll(|_| {}), 1);
+    /// ```
+    pub fn consumer(&mut self, dependencies: &[&Consumer<T>]) -> Consumer<T> {
+        let mut deps = Vec::with_capacity(dependencies.len());
+        for dep in dependencies {
+            assert!(
+                Arc::ptr_eq(&dep.inner, &self.inner),
+                "dependency belongs to another ring buffer"
+            );
+            for (seq, is_dependency) in &mut self.consumers {
+                if Arc::ptr_eq(seq, &dep.sequence) {
+                    *is_dependency = true;
+                }
+            }
+            deps.push(dep.sequence.clone());
+        }
+
+        let sequence = Arc::new(CachePadded::new(AtomicUsize::new(0)));
+        self.consumers.push((sequence.clone(), false));
+
+        Consumer {
+            inner: self.inner.clone(),
+            sequence,
+            dependencies: deps.into_boxed_slice(),
+        }
+    }
+
+    /// Finishes setting up the consumers and returns the producer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::disruptor::Disruptor;
+    ///
+    /// let mut d = Disruptor::new(8, || 0);
+    /// let mut c = d.consumer(&[]);
+    /// let p = d.into_producer();
+    ///
+    /// p.publish(|e| *e = 10);
+    /// assert_eq!(c.try_poll(|e| assert_eq!(*e, 10)), 1);
+    /// ```
+    pub fn into_producer(mut self) -> Producer<T> {
+        // Consumers that others depend on are never ahead of their dependents, so it's enough to
+        // wait for the ones at the end of the pipeline.
+        let gating: Vec<Arc<Sequence>> = mem::replace(&mut self.consumers, Vec::new())
+            .into_iter()
+            .filter(|(_, is_dependency)| !is_dependency)
+            .map(|(seq, _)| seq)
+            .collect();
+
+        self.inner.producers.fetch_add(1, Ordering::SeqCst);
+        Producer {
+            inner: self.inner.clone(),
+            gating: Arc::from(gating),
+        }
+    }
+}
+
+impl<T> Drop for Disruptor<T> {
+    fn drop(&mut self) {
+        // Until it is turned into a producer, the ring buffer counts as one, so that consumers
+        // polling in the meantime don't think that no events are coming.
+        self.inner.producers.fetch_sub(1, Ordering::SeqCst);
+        self.inner.notify();
+    }
+}
+
+impl<T> fmt::Debug for Disruptor<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Disruptor { .. }")
+    }
+}
+
+/// The publishing side of a [`Disruptor`].
+///
+/// Producers can be cloned to publish from multiple threads. Events are claimed in sequence, and
+/// consumers see an event only once it and all events claimed before it have been published.
+pub struct Producer<T> {
+    /// The shared ring buffer.
+    inner: Arc<Inner<T>>,
+
+    /// The sequences of the consumers at the end of the pipeline.
+    gating: Arc<[Arc<Sequence>]>,
+}
+
+impl<T> Producer<T> {
+    /// Publishes one event, which `f` writes into a slot of the ring buffer.
+    ///
+    /// The slot still holds the event that was previously published into it. If the ring buffer
+    /// is full, this waits until the slowest consumers have processed enough events.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::disruptor::Disruptor;
+    ///
+    /// let mut d = Disruptor::new(8, Vec::new);
+    /// let mut c = d.consumer(&[]);
+    /// let p = d.into_producer();
+    ///
+    /// p.publish(|e| {
+    ///     e.clear();
+    ///     e.extend_from_slice(b"abc");
+    /// });
+    /// assert_eq!(c.try_poll(|e| assert_eq!(e, b"abc")), 1);
+    /// ```
+    pub fn publish<F: FnOnce(&mut T)>(&self, f: F) {
+        let mut f = Some(f);
+        self.publish_batch(1, |_, e| {
+            if let Some(f) = f.take() {
+                f(e)
+            }
+        });
+    }
+
+    /// Claims `n` consecutive slots at once and publishes an event into each of them.
+    ///
+    /// `f` is called with the index of each event within the batch and the slot to write it into.
+    /// All events of the batch become visible to consumers at the same time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is zero or greater than the capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::disruptor::Disruptor;
+    ///
+    /// let mut d = Disruptor::new(8, || 0);
+    /// let mut c = d.consumer(&[]);
+    /// let p = d.into_producer();
+    ///
+    /// p.publish_batch(3, |i, e| *e = i * 10);
+    ///
+    /// let mut events = Vec::new();
+    /// c.try_poll(|e| events.push(*e));
+    /// assert_eq!(events, [0, 10, 20]);
+    /// ```
+    pub fn publish_batch<F: FnMut(usize, &mut T)>(&self, n: usize, mut f: F) {
+        let inner = &*self.inner;
+        assert!(
+            n > 0 && n <= inner.capacity(),
+            "batch size must be positive and at most the capacity"
+        );
+
+        let start = inner.claimed.fetch_add(n, Ordering::SeqCst);
+        let end = start.wrapping_add(n);
+
+        // Wait until the slowest consumers have moved a full lap past the claimed slots.
+        let wrap = end.wrapping_sub(inner.capacity());
+        inner.wait_until(|| {
+            self.gating
+                .iter()
+                .all(|seq| seq.load(Ordering::Acquire).wrapping_sub(wrap) as isize >= 0)
+        });
+
+        // Publish the batch even if `f` panics, since consumers would otherwise wait forever.
+        let _publish = Publish { inner, start, end };
+        let mut seq = start;
+        while seq != end {
+            f(seq.wrapping_sub(start), unsafe { &mut *inner.slot(seq) });
+            seq = seq.wrapping_add(1);
+        }
+    }
+
+    /// Returns the capacity of the ring buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::disruptor::Disruptor;
+    ///
+    /// let p = Disruptor::new(8, || 0).into_producer();
+    /// assert_eq!(p.capacity(), 8);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+}
+
+impl<T> Clone for Producer<T> {
+    fn clone(&self) -> Producer<T> {
+        self.inner.producers.fetch_add(1, Ordering::SeqCst);
+        Producer {
+            inner: self.inner.clone(),
+            gating: self.gating.clone(),
+        }
+    }
+}
+
+impl<T> Drop for Producer<T> {
+    fn drop(&mut self) {
+        self.inner.producers.fetch_sub(1, Ordering::SeqCst);
+        self.inner.notify();
+    }
+}
+
+impl<T> fmt::Debug for Producer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Producer { .. }")
+    }
+}
+
+/// Marks a batch of claimed slots as published when dropped.
+struct Publish<'a, T> {
+    inner: &'a Inner<T>,
+    start: usize,
+    end: usize,
+}
+
+impl<T> Drop for Publish<'_, T> {
+    fn drop(&mut self) {
+        let inner = self.inner;
+        let mut seq = self.start;
+        while seq != self.end {
+            inner.published[seq & (inner.capacity() - 1)]
+                .store(seq.wrapping_add(1), Ordering::Release);
+            seq = seq.wrapping_add(1);
+        }
+        inner.notify();
+    }
+}
+
+/// A consumer of a [`Disruptor`].
+///
+/// Every consumer sees every event, in the order in which the events were claimed.
+///
+/// While producers are publishing, every consumer must keep processing events: a consumer that
+/// stops polling eventually makes producers wait for it forever.
+pub struct Consumer<T> {
+    /// The shared ring buffer.
+    inner: Arc<Inner<T>>,
+
+    /// The number of events this consumer has processed.
+    sequence: Arc<Sequence>,
+
+    /// The sequences of the consumers this one depends on.
+    dependencies: Box<[Arc<Sequence>]>,
+}
+
+impl<T> Consumer<T> {
+    /// Returns the sequence number one past the last event this consumer may process.
+    fn available(&self, next: usize) -> usize {
+        let inner = &*self.inner;
+
+        if self.dependencies.is_empty() {
+            // Look for the end of the contiguous run of published events.
+            let mut end = next;
+            while end.wrapping_sub(next) < inner.capacity() && inner.is_published(end) {
+                end = end.wrapping_add(1);
+            }
+            end
+        } else {
+            self.dependencies
+                .iter()
+                .map(|seq| seq.load(Ordering::Acquire))
+                .min_by_key(|seq| seq.wrapping_sub(next))
+                .unwrap_or(next)
+        }
+    }
+
+    /// Processes all events that are available right now, returning how many there were.
+    ///
+    /// This never waits for producers or other consumers.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::disruptor::Disruptor;
+    ///
+    /// let mut d = Disruptor::new(8, || 0);
+    /// let mut c = d.consumer(&[]);
+    /// let p = d.into_producer();
+    /// assert_eq!(c.try_poll(|_| {}), 0);
+    ///
+    /// p.publish(|e| *e = 1);
+    /// p.publish(|e| *e = 2);
+    ///
+    /// let mut sum = 0;
+    /// assert_eq!(c.try_poll(|e| sum += *e), 2);
+    /// assert_eq!(sum, 3);
+    /// ```
+    pub fn try_poll<F: FnMut(&T)>(&mut self, mut f: F) -> usize {
+        let next = self.sequence.load(Ordering::Relaxed);
+        let end = self.available(next);
+
+        let mut seq = next;
+        while seq != end {
+            f(unsafe { &*self.inner.slot(seq) });
+            seq = seq.wrapping_add(1);
+        }
+
+        if end != next {
+            self.sequence.store(end, Ordering::Release);
+            self.inner.notify();
+        }
+        end.wrapping_sub(next)
+    }
+
+    /// Waits for events to become available and processes them, returning how many there were.
+    ///
+    /// If all producers have been dropped and this consumer has processed every published event,
+    /// this returns zero instead of waiting.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::disruptor::Disruptor;
+    /// use std::thread;
+    ///
+    /// let mut d = Disruptor::new(8, || 0);
+    /// let mut c = d.consumer(&[]);
+    /// let p = d.into_producer();
+    ///
+    /// thread::spawn(move || {
+    ///     for i in 0..100 {
+    ///         p.publish(|e| *e = i);
+    ///     }
+    /// });
+    ///
+    /// let mut received = Vec::new();
+    /// while c.poll(|e| received.push(*e)) > 0 {}
+    /// assert_eq!(received, (0..100).collect::<Vec<_>>());
+    /// ```
+    pub fn poll<F: FnMut(&T)>(&mut self, f: F) -> usize {
+        let inner = &*self.inner;
+        let next = self.sequence.load(Ordering::Relaxed);
+
+        inner.wait_until(|| {
+            self.available(next) != next
+                || (inner.producers.load(Ordering::SeqCst) == 0
+                    && inner.claimed.load(Ordering::SeqCst) == next)
+        });
+        self.try_poll(f)
+    }
+
+    /// Returns the number of events this consumer has processed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::disruptor::Disruptor;
+    ///
+    /// let mut d = Disruptor::new(8, || 0);
+    /// let mut c = d.consumer(&[]);
+    /// let p = d.into_producer();
+    ///
+    /// p.publish_batch(5, |_, _| {});
+    /// c.try_poll(|_| {});
+    /// assert_eq!(c.sequence(), 5);
+    /// ```
+    pub fn sequence(&self) -> usize {
+        self.sequence.load(Ordering::Relaxed)
+    }
+}
+
+impl<T> fmt::Debug for Consumer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Consumer { .. }")
+    }
+}
@@ -0,0 +1,390 @@
+//! An intrusive multi-producer single-consumer queue.
+//!
+//! The implementation is based on Dmitry Vyukov's intrusive MPSC node-based queue.
+//!
+//! Source:
+//!   - <https://www.1024cores.net/home/lock-free-algorithms/queues/intrusive-mpsc-node-based-queue>
+
+use alloc::boxed::Box;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ptr::{self, NonNull};
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use crossbeam_utils::{Backoff, CachePadded};
+
+/// A link embedded in every node of an [`MpscQueue`].
+///
+/// The queue never allocates: nodes are chained through the links they carry, so each node can be
+/// in at most one queue at a time.
+pub struct Link {
+    /// The next link in the queue.
+    next: AtomicPtr<Link>,
+}
+
+impl Link {
+    /// Creates a new link that is not in any queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::intrusive::Link;
+    ///
+    /// let link = Link::new();
+    /// ```
+    pub const fn new() -> Link {
+        Link {
+            next: AtomicPtr::new(ptr::null_mut()),
+        }
+    }
+}
+
+impl Default for Link {
+    fn default() -> Link {
+        Link::new()
+    }
+}
+
+impl fmt::Debug for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Link { .. }")
+    }
+}
+
+/// A type whose values embed a [`Link`] and can be pushed into an [`MpscQueue`].
+///
+/// # Safety
+///
+/// [`link`] must return a pointer to a [`Link`] inside the node, and [`from_link`] must be its
+/// exact inverse.
+///
+/// The simplest way to satisfy this is to make the link the first field of a `#[repr(C)]` struct,
+/// so that both conversions are plain pointer casts.
+///
+/// [`link`]: Linked::link
+/// [`from_link`]: Linked::from_link
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_queue::intrusive::{Link, Linked};
+/// use std::ptr::NonNull;
+///
+/// #[repr(C)]
+/// struct Task {
+///     link: Link,
+///     id: usize,
+/// }
+///
+/// unsafe impl Linked for Task {
+///     fn link(node: NonNull<Task>) -> NonNull<Link> {
+///         node.cast()
+///     }
+///
+///     unsafe fn from_link(link: NonNull<Link>) -> NonNull<Task> {
+///         link.cast()
+///     }
+/// }
+/// ```
+pub unsafe trait Linked {
+    /// Returns a pointer to the link embedded in `node`.
+    fn link(node: NonNull<Self>) -> NonNull<Link>;
+
+    /// Returns a pointer to the node `link` is embedded in.
+    ///
+    /// # Safety
+    ///
+    /// `link` must have been returned by [`Linked::link`].
+    unsafe fn from_link(link: NonNull<Link>) -> NonNull<Self>;
+}
+
+/// An intrusive multi-producer single-consumer queue.
+///
+/// Nodes embed their own [`Link`], so pushing doesn't allocate. This makes the queue suitable for
+/// schedulers and other places where every message is already allocated and an extra allocation
+/// per push would be unacceptable. The queue never owns its nodes: the caller is responsible for
+/// keeping each node alive until it is popped, and for freeing it afterwards.
+///
+/// Any number of threads may push concurrently, but only one thread may pop at a time.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_queue::intrusive::{Link, Linked, MpscQueue};
+/// use std::ptr::NonNull;
+///
+/// #[repr(C)]
+/// struct Task {
+///     link: Link,
+///     id: usize,
+/// }
+///
+/// unsafe impl Linked for Task {
+///     fn link(node: NonNull<Task>) -> NonNull<Link> {
+///         node.cast()
+///     }
+///
+///     unsafe fn from_link(link: NonNull<Link>) -> NonNull<Task> {
+///         link.cast()
+///     }
+/// }
+///
+/// let q = MpscQueue::new();
+///
+/// for id in 0..3 {
+///     let task = Box::new(Task { link: Link::new(), id });
+///     unsafe { q.push(NonNull::from(Box::leak(task))) };
+/// }
+///
+/// unsafe {
+///     while let Some(task) = q.pop() {
+///         let task = Box::from_raw(task.as_ptr());
+///         println!("running task {}", task.id);
+///     }
+/// }
+/// ```
+pub struct MpscQueue<T: Linked> {
+    /// The most recently pushed link, which producers swap themselves into.
+    head: CachePadded<AtomicPtr<Link>>,
+
+    /// The next link to be popped, which only the consumer writes to.
+    tail: CachePadded<AtomicPtr<Link>>,
+
+    /// A dummy link that keeps the list non-empty, so that producers never have to touch `tail`.
+    ///
+    /// It is allocated on the heap so that its address doesn't change when the queue is moved.
+    stub: *mut Link,
+
+    /// Indicates that the queue hands out nodes of type `T`.
+    _marker: PhantomData<*mut T>,
+}
+
+unsafe impl<T: Linked + Send> Send for MpscQueue<T> {}
+unsafe impl<T: Linked + Send> Sync for MpscQueue<T> {}
+
+impl<T: Linked> MpscQueue<T> {
+    /// Creates a new empty queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::intrusive::{Link, Linked, MpscQueue};
+    /// use std::ptr::NonNull;
+    ///
+    /// #[repr(C)]
+    /// struct Node {
+    ///     link: Link,
+    /// }
+    ///
+    /// unsafe impl Linked for Node {
+    ///     fn link(node: NonNull<Node>) -> NonNull<Link> {
+    ///         node.cast()
+    ///     }
+    ///
+    ///     unsafe fn from_link(link: NonNull<Link>) -> NonNull<Node> {
+    ///         link.cast()
+    ///     }
+    /// }
+    ///
+    /// let q = MpscQueue::<Node>::new();
+    /// ```
+    pub fn new() -> MpscQueue<T> {
+        let stub = Box::into_raw(Box::new(Link::new()));
+
+        MpscQueue {
+            head: CachePadded::new(AtomicPtr::new(stub)),
+            tail: CachePadded::new(AtomicPtr::new(stub)),
+            stub,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Appends a link to the list.
+    fn push_link(&self, link: *mut Link) {
+        unsafe {
+            (*link).next.store(ptr::null_mut(), Ordering::Relaxed);
+        }
+
+        // Make the link the new head, then attach it to the previous one. Between the two steps,
+        // the consumer sees the list as cut off after `prev`.
+        let prev = self.head.swap(link, Ordering::AcqRel);
+        unsafe {
+            (*prev).next.store(link, Ordering::Release);
+        }
+    }
+
+    /// Pushes a node into the queue.
+    ///
+    /// # Safety
+    ///
+    /// The node must stay valid, and must not be pushed into any queue again, until it has been
+    /// popped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crossbeam_queue::intrusive::{Link, Linked, MpscQueue};
+    /// # use std::ptr::NonNull;
+    /// # #[repr(C)]
+    /// # struct Node {
+    /// #     link: Link,
+    /// # }
+    /// # unsafe impl Linked for Node {
+    /// #     fn link(node: NonNull<Node>) -> NonNull<Link> {
+    /// #         node.cast()
+    /// #     }
+    /// #     unsafe fn from_link(link: NonNull<Link>) -> NonNull<Node> {
+    /// #         link.cast()
+    /// #     }
+    /// # }
+    /// let q = MpscQueue::new();
+    /// let mut node = Node { link: Link::new() };
+    ///
+    /// unsafe { q.push(NonNull::from(&mut node)) };
+    /// assert!(!q.is_empty());
+    /// ```
+    pub unsafe fn push(&self, node: NonNull<T>) {
+        self.push_link(T::link(node).as_ptr());
+    }
+
+    /// Pops a node from the queue.
+    ///
+    /// If the queue is empty, `None` is returned. If a producer is in the middle of pushing the
+    /// only remaining node, this method waits for it to finish.
+    ///
+    /// # Safety
+    ///
+    /// Only one thread may pop at a time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crossbeam_queue::intrusive::{Link, Linked, MpscQueue};
+    /// # use std::ptr::NonNull;
+    /// # #[repr(C)]
+    /// # struct Node {
+    /// #     link: Link,
+    /// # }
+    /// # unsafe impl Linked for Node {
+    /// #     fn link(node: NonNull<Node>) -> NonNull<Link> {
+    /// #         node.cast()
+    /// #     }
+    /// #     unsafe fn from_link(link: NonNull<Link>) -> NonNull<Node> {
+    /// #         link.cast()
+    /// #     }
+    /// # }
+    /// let q = MpscQueue::new();
+    /// let mut node = Node { link: Link::new() };
+    /// let ptr = NonNull::from(&mut node);
+    ///
+    /// unsafe {
+    ///     q.push(ptr);
+    ///     assert_eq!(q.pop(), Some(ptr));
+    ///     assert_eq!(q.pop(), None);
+    /// }
+    /// ```
+    pub unsafe fn pop(&self) -> Option<NonNull<T>> {
+        let backoff = Backoff::new();
+        let stub = self.stub;
+
+        loop {
+            let mut tail = self.tail.load(Ordering::Relaxed);
+            let mut next = (*tail).next.load(Ordering::Acquire);
+
+            // Skip over the stub.
+            if tail == stub {
+                if next.is_null() {
+                    // If the stub is also the head, the queue is empty.
+                    if self.head.load(Ordering::Acquire) == stub {
+                        return None;
+                    }
+
+                    // Otherwise a producer hasn't attached its node to the stub yet.
+                    backoff.snooze();
+                    continue;
+                }
+
+                self.tail.store(next, Ordering::Relaxed);
+                tail = next;
+                next = (*next).next.load(Ordering::Acquire);
+            }
+
+            // If the tail has a successor, it can be popped right away.
+            if !next.is_null() {
+                self.tail.store(next, Ordering::Relaxed);
+                return Some(T::from_link(NonNull::new_unchecked(tail)));
+            }
+
+            // If the tail is not the head, a producer is in the middle of attaching a node to it.
+            if self.head.load(Ordering::Acquire) != tail {
+                backoff.snooze();
+                continue;
+            }
+
+            // The tail is the last link. Push the stub behind it so that it can be popped.
+            self.push_link(stub);
+
+            next = (*tail).next.load(Ordering::Acquire);
+            if !next.is_null() {
+                self.tail.store(next, Ordering::Relaxed);
+                return Some(T::from_link(NonNull::new_unchecked(tail)));
+            }
+
+            // Another producer got in before the stub. Wait for it to attach its node.
+            backoff.snooze();
+        }
+    }
+
+    /// Returns `true` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crossbeam_queue::intrusive::{Link, Linked, MpscQueue};
+    /// # use std::ptr::NonNull;
+    /// # #[repr(C)]
+    /// # struct Node {
+    /// #     link: Link,
+    /// # }
+    /// # unsafe impl Linked for Node {
+    /// #     fn link(node: NonNull<Node>) -> NonNull<Link> {
+    /// #         node.cast()
+    /// #     }
+    /// #     unsafe fn from_link(link: NonNull<Link>) -> NonNull<Node> {
+    /// #         link.cast()
+    /// #     }
+    /// # }
+    /// let q = MpscQueue::new();
+    /// let mut node = Node { link: Link::new() };
+    ///
+    /// assert!(q.is_empty());
+    /// unsafe { q.push(NonNull::from(&mut node)) };
+    /// assert!(!q.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        // The list runs from the tail to the head, so if both are the stub, it holds no nodes.
+        let stub = self.stub;
+        self.tail.load(Ordering::SeqCst) == stub && self.head.load(Ordering::SeqCst) == stub
+    }
+}
+
+impl<T: Linked> Drop for MpscQueue<T> {
+    fn drop(&mut self) {
+        // Nodes still in the queue are owned by the caller, so only the stub is deallocated.
+        unsafe {
+            drop(Box::from_raw(self.stub));
+        }
+    }
+}
+
+impl<T: Linked> Default for MpscQueue<T> {
+    fn default() -> MpscQueue<T> {
+        MpscQueue::new()
+    }
+}
+
+impl<T: Linked> fmt::Debug for MpscQueue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("MpscQueue { .. }")
+    }
+}
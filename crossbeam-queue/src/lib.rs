@@ -4,6 +4,13 @@
 //!
 //! * [`ArrayQueue`], a bounded MPMC queue that allocates a fixed-capacity buffer on construction.
 //! * [`SegQueue`], an unbounded MPMC queue that allocates small buffers, segments, on demand.
+//! * [`spsc::RingBuffer`], a bounded SPSC queue that is split into a producer and a consumer.
+//! * [`intrusive::MpscQueue`], an unbounded MPSC queue whose nodes embed their own links.
+//! * [`mpsc::Queue`], an unbounded MPSC queue whose producers are wait-free.
+//! * [`disruptor::Disruptor`], a bounded multicast ring buffer whose consumers form a pipeline.
+//!
+//! It also provides [`bus::EventBus`], which delivers every published event to every subscriber,
+//! and [`pool::Pool`], a pool of reusable objects built on top of [`SegQueue`].
 
 #![doc(test(
     no_crate_inject,
@@ -27,9 +34,22 @@ cfg_if::cfg_if! {
         extern crate alloc;
 
         mod array_queue;
+        #[cfg(feature = "std")]
+        pub mod bus;
+        #[cfg(feature = "std")]
+        pub mod disruptor;
+        pub mod intrusive;
+        pub mod mpsc;
+        #[cfg(feature = "std")]
+        pub mod pool;
         mod seg_queue;
+        #[cfg(feature = "std")]
+        mod segment;
+        pub mod spsc;
+        #[cfg(feature = "std")]
+        mod waiter;
 
-        pub use self::array_queue::ArrayQueue;
-        pub use self::seg_queue::SegQueue;
+        pub use self::array_queue::{ArrayQueue, ArrayQueueDrain, ArrayQueueIntoIter};
+        pub use self::seg_queue::{SegQueue, SegQueueDrain, SegQueueIntoIter};
     }
 }
@@ -0,0 +1,468 @@
+//! An unbounded multi-producer single-consumer queue with wait-free producers.
+//!
+//! A push takes the next unused slot from a block owned by the producer, writes the value into it,
+//! and appends the slot to the queue with a single `swap` of the tail pointer, followed by a single
+//! store that links the previous slot to it. Producers never retry, never wait for other threads,
+//! and never free memory, so a push completes in a bounded number of steps no matter what other
+//! threads are doing. This makes the queue suitable for handing off log records or audit events
+//! from latency-critical threads, where even a compare-and-swap loop is unacceptable.
+//!
+//! Every 32nd push by the same producer allocates a fresh block, which is the only step whose
+//! duration depends on something other than the queue: the memory allocator.
+//!
+//! Slots are linked in the order in which they were appended, so the consumer follows a single
+//! list, much like in Dmitry Vyukov's non-intrusive MPSC node-based queue. A block is destroyed by
+//! the consumer once it has moved past all of its slots, and the producer that owned the block has
+//! moved on to another one. Producers only ever touch the slot they appended last, which can't be
+//! destroyed before the next slot is linked to it, so no garbage collection is needed.
+//!
+//! Source:
+//!   - <https://www.1024cores.net/home/lock-free-algorithms/queues/non-intrusive-mpsc-node-based-queue>
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::cell::{Cell, UnsafeCell};
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, AtomicUsize, Ordering};
+
+use crossbeam_utils::CachePadded;
+
+/// The number of slots in a block.
+const BLOCK_CAP: usize = 32;
+
+/// A slot in a block.
+struct Slot<T> {
+    /// The value.
+    value: UnsafeCell<MaybeUninit<T>>,
+
+    /// The slot that was appended after this one.
+    next: AtomicPtr<Slot<T>>,
+
+    /// The block this slot belongs to.
+    block: *mut Block<T>,
+}
+
+/// A block of slots, owned by a single producer until all of its slots are used up.
+struct Block<T> {
+    /// The number of slots that haven't been released yet.
+    ///
+    /// The consumer releases a slot once it has moved past it, and a producer releases all the
+    /// slots it didn't use when it is dropped. The block is destroyed once all slots are released.
+    refs: AtomicUsize,
+
+    /// The slots.
+    slots: [Slot<T>; BLOCK_CAP],
+}
+
+impl<T> Block<T> {
+    /// Allocates a new block with `refs` unreleased slots.
+    fn new(refs: usize) -> *mut Block<T> {
+        // SAFETY: This is safe because:
+        //  [1] `Block::refs` (AtomicUsize) and `Slot::block` (raw pointer) are overwritten right
+        //      away.
+        //  [2] `Slot::value` (UnsafeCell) may be safely zero initialized because it holds a
+        //      MaybeUninit.
+        //  [3] `Slot::next` (AtomicPtr) may be safely zero initialized.
+        let block = Box::into_raw(Box::new(unsafe {
+            MaybeUninit::<Block<T>>::zeroed().assume_init()
+        }));
+
+        unsafe {
+            *(*block).refs.get_mut() = refs;
+            for slot in (*block).slots.iter_mut() {
+                slot.block = block;
+            }
+        }
+        block
+    }
+
+    /// Releases `count` slots of the block, and destroys it if no unreleased slots remain.
+    ///
+    /// The released slots must not be accessed anymore.
+    unsafe fn release(this: *mut Block<T>, count: usize) {
+        if (*this).refs.fetch_sub(count, Ordering::AcqRel) == count {
+            drop(Box::from_raw(this));
+        }
+    }
+}
+
+/// The state shared by the producers and the consumer.
+struct Inner<T> {
+    /// The number of elements pushed so far.
+    tail: CachePadded<AtomicUsize>,
+
+    /// The slot that was appended last.
+    tail_slot: CachePadded<AtomicPtr<Slot<T>>>,
+
+    /// The number of elements popped so far. It is only ever written to by the consumer.
+    head: CachePadded<AtomicUsize>,
+
+    /// The slot that was popped last, or the initial empty slot. It is only ever accessed by the
+    /// consumer.
+    head_slot: AtomicPtr<Slot<T>>,
+}
+
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+impl<T> Inner<T> {
+    /// Returns the number of elements in the queue.
+    fn len(&self) -> usize {
+        loop {
+            // Load the tail, then the head, and make sure the tail didn't change in between, so
+            // that the head can't have overtaken it.
+            let tail = self.tail.load(Ordering::SeqCst);
+            let head = self.head.load(Ordering::SeqCst);
+            if self.tail.load(Ordering::SeqCst) == tail {
+                return tail - head;
+            }
+        }
+    }
+}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let mut slot = *self.head_slot.get_mut();
+
+            // Drop all values after the head slot, and release all slots in the list. The
+            // producers are gone, so they have already released the slots they didn't use.
+            loop {
+                let next = *(*slot).next.get_mut();
+                Block::release((*slot).block, 1);
+
+                if next.is_null() {
+                    break;
+                }
+                (*(*next).value.get()).as_mut_ptr().drop_in_place();
+                slot = next;
+            }
+        }
+    }
+}
+
+/// An unbounded multi-producer single-consumer queue with wait-free producers.
+///
+/// The queue is [split] into a [`Producer`], which can be cloned to push from any number of
+/// threads, and a single [`Consumer`]. Pushing never fails and never waits for other threads.
+///
+/// Values pushed by the same producer are popped in the same order. A value is only popped once
+/// every value appended before it has been linked, so a producer that gets preempted in the middle
+/// of a push briefly holds up the consumer, but never other producers.
+///
+/// [split]: Queue::split
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_queue::mpsc::Queue;
+/// use std::thread;
+///
+/// let (p, mut c) = Queue::new().split();
+///
+/// let handles: Vec<_> = (0..4)
+///     .map(|t| {
+///         let p = p.clone();
+///         thread::spawn(move || p.push(format!("message from thread {}", t)))
+///     })
+///     .collect();
+///
+/// for h in handles {
+///     h.join().unwrap();
+/// }
+///
+/// let mut messages = Vec::new();
+/// while let Some(m) = c.pop() {
+///     messages.push(m);
+/// }
+/// assert_eq!(messages.len(), 4);
+/// ```
+pub struct Queue<T> {
+    inner: Inner<T>,
+}
+
+impl<T> Queue<T> {
+    /// Creates a new unbounded queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::mpsc::Queue;
+    ///
+    /// let q = Queue::<i32>::new();
+    /// ```
+    pub fn new() -> Queue<T> {
+        // The list starts with an empty slot. The rest of its block is never used.
+        let block = Block::new(1);
+        let slot = unsafe { &(*block).slots[0] as *const Slot<T> as *mut Slot<T> };
+
+        Queue {
+            inner: Inner {
+                tail: CachePadded::new(AtomicUsize::new(0)),
+                tail_slot: CachePadded::new(AtomicPtr::new(slot)),
+                head: CachePadded::new(AtomicUsize::new(0)),
+                head_slot: AtomicPtr::new(slot),
+            },
+        }
+    }
+
+    /// Splits the queue into its producer and consumer halves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::mpsc::Queue;
+    ///
+    /// let (p, mut c) = Queue::new().split();
+    ///
+    /// p.push(10);
+    /// assert_eq!(c.pop(), Some(10));
+    /// ```
+    pub fn split(self) -> (Producer<T>, Consumer<T>) {
+        let inner = Arc::new(self.inner);
+
+        let producer = Producer::new(inner.clone());
+        let consumer = Consumer { inner };
+        (producer, consumer)
+    }
+}
+
+impl<T> Default for Queue<T> {
+    fn default() -> Queue<T> {
+        Queue::new()
+    }
+}
+
+impl<T> fmt::Debug for Queue<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Queue { .. }")
+    }
+}
+
+/// The producer half of a [`Queue`].
+///
+/// Producers can be cloned to push from multiple threads. A producer can be sent to another
+/// thread, but not shared between threads: each thread pushes through a clone of its own.
+///
+/// # Blocks
+///
+/// Every producer takes slots from a block of 32 slots that only it pushes into, and allocates a
+/// new block once all slots are used up. A block is freed once the consumer has popped all values
+/// pushed into it, and its producer has either used up or dropped it. Creating a clone for every
+/// value pushed wastes most of a block each time, so clones are best kept around.
+pub struct Producer<T> {
+    /// The shared queue.
+    inner: Arc<Inner<T>>,
+
+    /// The block this producer takes slots from, or null if it doesn't have one yet.
+    block: Cell<*mut Block<T>>,
+
+    /// The index of the next unused slot in `block`.
+    index: Cell<usize>,
+}
+
+unsafe impl<T: Send> Send for Producer<T> {}
+
+impl<T> Producer<T> {
+    /// Creates a producer without a block.
+    fn new(inner: Arc<Inner<T>>) -> Producer<T> {
+        Producer {
+            inner,
+            block: Cell::new(ptr::null_mut()),
+            index: Cell::new(BLOCK_CAP),
+        }
+    }
+
+    /// Pushes an element into the queue.
+    ///
+    /// This never blocks and completes in a bounded number of steps. Every 32nd push allocates a
+    /// new block.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::mpsc::Queue;
+    ///
+    /// let (p, mut c) = Queue::new().split();
+    ///
+    /// p.push(10);
+    /// p.push(20);
+    /// assert_eq!(c.pop(), Some(10));
+    /// assert_eq!(c.pop(), Some(20));
+    /// ```
+    pub fn push(&self, value: T) {
+        let mut index = self.index.get();
+        if index == BLOCK_CAP {
+            // All slots of the current block are in the queue, so the consumer will release them.
+            self.block.set(Block::new(BLOCK_CAP));
+            index = 0;
+        }
+        self.index.set(index + 1);
+
+        unsafe {
+            let slot = &(*self.block.get()).slots[index];
+            slot.value.get().write(MaybeUninit::new(value));
+
+            // Append the slot, then link the previous one to it. The previous slot can't be
+            // released by the consumer until it is linked.
+            let slot = slot as *const Slot<T> as *mut Slot<T>;
+            self.inner.tail.fetch_add(1, Ordering::SeqCst);
+            let prev = self.inner.tail_slot.swap(slot, Ordering::AcqRel);
+            (*prev).next.store(slot, Ordering::Release);
+        }
+    }
+
+    /// Returns the number of elements in the queue.
+    ///
+    /// Elements that are in the middle of being pushed are counted as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::mpsc::Queue;
+    ///
+    /// let (p, c) = Queue::new().split();
+    /// assert_eq!(p.len(), 0);
+    ///
+    /// p.push(10);
+    /// assert_eq!(p.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::mpsc::Queue;
+    ///
+    /// let (p, c) = Queue::new().split();
+    /// assert!(p.is_empty());
+    ///
+    /// p.push(10);
+    /// assert!(!p.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> Clone for Producer<T> {
+    fn clone(&self) -> Producer<T> {
+        Producer::new(self.inner.clone())
+    }
+}
+
+impl<T> Drop for Producer<T> {
+    fn drop(&mut self) {
+        let unused = BLOCK_CAP - self.index.get();
+        if unused > 0 {
+            unsafe {
+                Block::release(self.block.get(), unused);
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Producer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Producer { .. }")
+    }
+}
+
+/// The consumer half of a [`Queue`].
+///
+/// Elements are popped in the order in which they were appended by producers.
+pub struct Consumer<T> {
+    /// The shared queue.
+    inner: Arc<Inner<T>>,
+}
+
+impl<T> Consumer<T> {
+    /// Attempts to pop an element from the queue.
+    ///
+    /// If the queue is empty, or the next element is still being pushed, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::mpsc::Queue;
+    ///
+    /// let (p, mut c) = Queue::new().split();
+    /// assert_eq!(c.pop(), None);
+    ///
+    /// p.push(10);
+    /// assert_eq!(c.pop(), Some(10));
+    /// assert_eq!(c.pop(), None);
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        let inner = &*self.inner;
+
+        let slot = inner.head_slot.load(Ordering::Relaxed);
+        let next = unsafe { (*slot).next.load(Ordering::Acquire) };
+        if next.is_null() {
+            return None;
+        }
+
+        let value = unsafe { (*next).value.get().read().assume_init() };
+        inner.head_slot.store(next, Ordering::Relaxed);
+        inner
+            .head
+            .store(inner.head.load(Ordering::Relaxed) + 1, Ordering::Release);
+
+        // The producer that appended `next` was the last one to access the old head slot.
+        unsafe {
+            Block::release((*slot).block, 1);
+        }
+        Some(value)
+    }
+
+    /// Returns the number of elements in the queue.
+    ///
+    /// Elements that are in the middle of being pushed are counted as well.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::mpsc::Queue;
+    ///
+    /// let (p, mut c) = Queue::new().split();
+    ///
+    /// p.push(10);
+    /// p.push(20);
+    /// assert_eq!(c.len(), 2);
+    ///
+    /// c.pop();
+    /// assert_eq!(c.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    /// Returns `true` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::mpsc::Queue;
+    ///
+    /// let (p, c) = Queue::new().split();
+    /// assert!(c.is_empty());
+    ///
+    /// p.push(10);
+    /// assert!(!c.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<T> fmt::Debug for Consumer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Consumer { .. }")
+    }
+}
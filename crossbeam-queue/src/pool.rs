@@ -0,0 +1,316 @@
+//! A concurrent pool of reusable objects.
+//!
+//! Objects that are expensive to create, such as large buffers or parsers with internal tables,
+//! can be recycled across threads through a [`Pool`]: [`get`] hands out an object wrapped in a
+//! [`Pooled`] handle, which puts it back into the pool when dropped.
+//!
+//! Every thread has a cache slot in the pool, which holds the object it returned last. Most of the
+//! time a thread takes back its own object with a single atomic swap, and only falls back to a
+//! shared [`SegQueue`] when its slot is empty or already occupied. Threads are mapped to slots by
+//! their [`thread_id`], so threads that run at the same time rarely share a slot.
+//!
+//! [`get`]: Pool::get
+//! [`thread_id`]: crossbeam_utils::thread::thread_id
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam_queue::pool::Pool;
+//! use crossbeam_utils::thread::scope;
+//!
+//! let pool = Pool::new(|| Vec::<u8>::with_capacity(4096));
+//!
+//! scope(|s| {
+//!     for _ in 0..4 {
+//!         s.spawn(|_| {
+//!             for i in 0..100 {
+//!                 let mut buf = pool.get();
+//!                 buf.clear();
+//!                 buf.extend_from_slice(format!("request {}", i).as_bytes());
+//!             }
+//!         });
+//!     }
+//! }).unwrap();
+//!
+//! // Buffers were recycled rather than allocated for every request.
+//! assert!(pool.len() <= 4);
+//! ```
+
+use alloc::boxed::Box;
+use core::fmt;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use core::sync::atomic::{AtomicPtr, Ordering};
+
+use crossbeam_utils::thread::thread_id;
+use crossbeam_utils::CachePadded;
+
+use crate::SegQueue;
+
+/// The number of per-thread cache slots in a pool.
+const NUM_SLOTS: usize = 16;
+
+/// A concurrent pool of reusable objects.
+///
+/// See the [module-level documentation](self) for more.
+///
+/// Objects are returned to the pool as they are, so they should be reset before use, for
+/// example by clearing a buffer right after [`get`].
+///
+/// [`get`]: Pool::get
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_queue::pool::Pool;
+///
+/// let pool = Pool::new(String::new);
+///
+/// let mut s = pool.get();
+/// s.push_str("hello");
+/// drop(s);
+///
+/// // The same string comes back.
+/// assert_eq!(*pool.get(), "hello");
+/// ```
+pub struct Pool<T> {
+    /// Per-thread cache slots, each holding a pointer obtained from `Box::into_raw` or null.
+    slots: Box<[CachePadded<AtomicPtr<T>>]>,
+
+    /// Objects that didn't fit into a cache slot.
+    shared: SegQueue<Box<T>>,
+
+    /// Creates new objects when the pool is empty.
+    create: Box<dyn Fn() -> T + Send + Sync>,
+}
+
+unsafe impl<T: Send> Send for Pool<T> {}
+unsafe impl<T: Send> Sync for Pool<T> {}
+
+impl<T> Pool<T> {
+    /// Creates an empty pool, which calls `create` whenever it runs out of objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::pool::Pool;
+    ///
+    /// let pool = Pool::new(|| vec![0u8; 1024]);
+    /// assert!(pool.is_empty());
+    /// ```
+    pub fn new<F>(create: F) -> Pool<T>
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+    {
+        Pool {
+            slots: (0..NUM_SLOTS)
+                .map(|_| CachePadded::new(AtomicPtr::new(ptr::null_mut())))
+                .collect(),
+            shared: SegQueue::new(),
+            create: Box::new(create),
+        }
+    }
+
+    /// Takes an object out of the pool, creating a new one if the pool is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::pool::Pool;
+    ///
+    /// let pool = Pool::new(|| 7);
+    /// assert_eq!(*pool.get(), 7);
+    /// ```
+    pub fn get(&self) -> Pooled<'_, T> {
+        match self.try_get() {
+            Some(obj) => obj,
+            None => Pooled {
+                pool: self,
+                value: Some(Box::new((self.create)())),
+            },
+        }
+    }
+
+    /// Takes an object out of the pool, or returns `None` if the pool is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::pool::Pool;
+    ///
+    /// let pool = Pool::new(|| 7);
+    /// assert!(pool.try_get().is_none());
+    ///
+    /// pool.put(8);
+    /// assert_eq!(*pool.try_get().unwrap(), 8);
+    /// ```
+    pub fn try_get(&self) -> Option<Pooled<'_, T>> {
+        let slot = self.slot();
+        let value = if slot.load(Ordering::Relaxed).is_null() {
+            None
+        } else {
+            let ptr = slot.swap(ptr::null_mut(), Ordering::Acquire);
+            if ptr.is_null() {
+                None
+            } else {
+                Some(unsafe { Box::from_raw(ptr) })
+            }
+        };
+
+        value.or_else(|| self.shared.pop()).map(|value| Pooled {
+            pool: self,
+            value: Some(value),
+        })
+    }
+
+    /// Adds an object to the pool.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::pool::Pool;
+    ///
+    /// let pool = Pool::new(Vec::<u8>::new);
+    /// pool.put(Vec::with_capacity(64));
+    /// assert_eq!(pool.len(), 1);
+    /// ```
+    pub fn put(&self, value: T) {
+        self.recycle(Box::new(value));
+    }
+
+    /// Returns the number of objects in the pool.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::pool::Pool;
+    ///
+    /// let pool = Pool::new(|| 0);
+    /// let a = pool.get();
+    /// let b = pool.get();
+    /// assert_eq!(pool.len(), 0);
+    ///
+    /// drop(a);
+    /// drop(b);
+    /// assert_eq!(pool.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        let cached = self
+            .slots
+            .iter()
+            .filter(|slot| !slot.load(Ordering::Relaxed).is_null())
+            .count();
+        cached + self.shared.len()
+    }
+
+    /// Returns `true` if the pool holds no objects.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::pool::Pool;
+    ///
+    /// let pool = Pool::new(|| 0);
+    /// assert!(pool.is_empty());
+    ///
+    /// drop(pool.get());
+    /// assert!(!pool.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the cache slot of the current thread.
+    fn slot(&self) -> &AtomicPtr<T> {
+        &self.slots[thread_id() % self.slots.len()]
+    }
+
+    /// Puts an object into the cache slot of the current thread, or into the shared queue if the
+    /// slot is occupied.
+    fn recycle(&self, value: Box<T>) {
+        let slot = self.slot();
+        let ptr = Box::into_raw(value);
+        if slot.load(Ordering::Relaxed).is_null()
+            && slot
+                .compare_exchange(ptr::null_mut(), ptr, Ordering::Release, Ordering::Relaxed)
+                .is_ok()
+        {
+            return;
+        }
+        self.shared.push(unsafe { Box::from_raw(ptr) });
+    }
+}
+
+impl<T> Drop for Pool<T> {
+    fn drop(&mut self) {
+        for slot in self.slots.iter_mut() {
+            let ptr = *slot.get_mut();
+            if !ptr.is_null() {
+                unsafe { drop(Box::from_raw(ptr)) };
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Pool { .. }")
+    }
+}
+
+/// An object taken out of a [`Pool`].
+///
+/// The object is put back into the pool when the handle is dropped, unless it has been taken out
+/// of it with [`Pooled::detach`].
+pub struct Pooled<'a, T> {
+    /// The pool the object returns to.
+    pool: &'a Pool<T>,
+
+    /// The object, which is only `None` while the handle is being consumed.
+    value: Option<Box<T>>,
+}
+
+impl<T> Pooled<'_, T> {
+    /// Takes the object out of the handle, so that it doesn't return to the pool.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::pool::{Pool, Pooled};
+    ///
+    /// let pool = Pool::new(|| String::from("hello"));
+    /// let s: String = Pooled::detach(pool.get());
+    /// assert!(pool.is_empty());
+    /// ```
+    pub fn detach(mut this: Self) -> T {
+        *this.value.take().unwrap()
+    }
+}
+
+impl<T> Deref for Pooled<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        self.value.as_ref().unwrap()
+    }
+}
+
+impl<T> DerefMut for Pooled<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        self.value.as_mut().unwrap()
+    }
+}
+
+impl<T> Drop for Pooled<'_, T> {
+    fn drop(&mut self) {
+        if let Some(value) = self.value.take() {
+            self.pool.recycle(value);
+        }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Pooled<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&**self, f)
+    }
+}
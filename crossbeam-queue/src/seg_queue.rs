@@ -1,8 +1,11 @@
-use alloc::boxed::Box;
+//! An unbounded multi-producer multi-consumer queue.
+
+use core::alloc::Layout;
 use core::cell::UnsafeCell;
 use core::fmt;
+use core::iter::FusedIterator;
 use core::marker::PhantomData;
-use core::mem::MaybeUninit;
+use core::mem::{self, MaybeUninit};
 use core::ptr;
 use core::sync::atomic::{self, AtomicPtr, AtomicUsize, Ordering};
 
@@ -16,10 +19,8 @@ const WRITE: usize = 1;
 const READ: usize = 2;
 const DESTROY: usize = 4;
 
-// Each block covers one "lap" of indices.
+// By default, each block covers one "lap" of 32 indices and can hold 31 values.
 const LAP: usize = 32;
-// The maximum number of values a block can hold.
-const BLOCK_CAP: usize = LAP - 1;
 // How many lower bits are reserved for metadata.
 const SHIFT: usize = 1;
 // Indicates that the block is not the last one.
@@ -46,25 +47,53 @@ impl<T> Slot<T> {
 
 /// A block in a linked list.
 ///
-/// Each block in the list can hold up to `BLOCK_CAP` values.
+/// Each block in the list can hold up to `lap - 1` values, where `lap` is chosen when the queue is
+/// created. The slots are allocated together with the block header, right after it.
+#[repr(C)]
 struct Block<T> {
     /// The next block in the linked list.
     next: AtomicPtr<Block<T>>,
 
-    /// Slots for values.
-    slots: [Slot<T>; BLOCK_CAP],
+    /// Slots for values, followed by more slots in the same allocation.
+    slots: [Slot<T>; 0],
 }
 
 impl<T> Block<T> {
-    /// Creates an empty block that starts at `start_index`.
-    fn new() -> Block<T> {
+    /// Returns the memory layout of a block with `cap` slots.
+    fn layout(cap: usize) -> Layout {
+        // The zero-length array is the last field, so `size_of::<Block<T>>()` is its offset.
+        let size = mem::size_of::<Slot<T>>()
+            .checked_mul(cap)
+            .and_then(|slots| slots.checked_add(mem::size_of::<Block<T>>()))
+            .expect("block size overflows `usize`");
+        Layout::from_size_align(size, mem::align_of::<Block<T>>()).unwrap()
+    }
+
+    /// Allocates an empty block with `cap` slots.
+    fn new(cap: usize) -> *mut Block<T> {
         // SAFETY: This is safe because:
         //  [1] `Block::next` (AtomicPtr) may be safely zero initialized.
         //  [2] `Block::slots` (Array) may be safely zero initialized because of [3, 4].
         //  [3] `Slot::value` (UnsafeCell) may be safely zero initialized because it
         //       holds a MaybeUninit.
         //  [4] `Slot::state` (AtomicUsize) may be safely zero initialized.
-        unsafe { MaybeUninit::zeroed().assume_init() }
+        let layout = Self::layout(cap);
+        let ptr = unsafe { alloc::alloc::alloc_zeroed(layout) };
+        if ptr.is_null() {
+            alloc::alloc::handle_alloc_error(layout);
+        }
+        ptr as *mut Block<T>
+    }
+
+    /// Deallocates a block with `cap` slots without dropping any values in it.
+    unsafe fn dealloc(this: *mut Block<T>, cap: usize) {
+        alloc::alloc::dealloc(this as *mut u8, Self::layout(cap));
+    }
+
+    /// Returns the slot at index `i`.
+    unsafe fn slot<'a>(this: *mut Block<T>, i: usize) -> &'a Slot<T> {
+        let slots = (this as *mut u8).add(mem::size_of::<Block<T>>()) as *const Slot<T>;
+        &*slots.add(i)
     }
 
     /// Waits until the next pointer is set.
@@ -79,12 +108,13 @@ impl<T> Block<T> {
         }
     }
 
-    /// Sets the `DESTROY` bit in slots starting from `start` and destroys the block.
-    unsafe fn destroy(this: *mut Block<T>, start: usize) {
+    /// Sets the `DESTROY` bit in slots starting from `start` and destroys the block with `cap`
+    /// slots.
+    unsafe fn destroy(this: *mut Block<T>, start: usize, cap: usize) {
         // It is not necessary to set the `DESTROY` bit in the last slot because that slot has
         // begun destruction of the block.
-        for i in start..BLOCK_CAP - 1 {
-            let slot = (*this).slots.get_unchecked(i);
+        for i in start..cap - 1 {
+            let slot = Block::slot(this, i);
 
             // Mark the `DESTROY` bit if a thread is still using the slot.
             if slot.state.load(Ordering::Acquire) & READ == 0
@@ -96,7 +126,7 @@ impl<T> Block<T> {
         }
 
         // No thread is using the block, now it is safe to destroy it.
-        drop(Box::from_raw(this));
+        Block::dealloc(this, cap);
     }
 }
 
@@ -139,6 +169,12 @@ pub struct SegQueue<T> {
     /// The tail of the queue.
     tail: CachePadded<Position<T>>,
 
+    /// The number of indices covered by each block.
+    ///
+    /// This is always a power of two. The last index of each lap doesn't correspond to a slot, so
+    /// a block holds `lap - 1` values.
+    lap: usize,
+
     /// Indicates that dropping a `SegQueue<T>` may drop values of type `T`.
     _marker: PhantomData<T>,
 }
@@ -149,6 +185,8 @@ unsafe impl<T: Send> Sync for SegQueue<T> {}
 impl<T> SegQueue<T> {
     /// Creates a new unbounded queue.
     ///
+    /// Each segment of the queue holds 31 elements.
+    ///
     /// # Examples
     ///
     /// ```
@@ -166,10 +204,69 @@ impl<T> SegQueue<T> {
                 block: AtomicPtr::new(ptr::null_mut()),
                 index: AtomicUsize::new(0),
             }),
+            lap: LAP,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Creates a new unbounded queue whose segments hold at least `cap` elements each.
+    ///
+    /// Segments are allocated one at a time as elements get pushed, so smaller segments waste less
+    /// memory when the queue is short, while larger segments need fewer allocations when it is
+    /// long. The capacity is rounded up to one less than a power of two, which is what
+    /// [`segment_capacity`] returns.
+    ///
+    /// [`segment_capacity`]: SegQueue::segment_capacity
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::SegQueue;
+    ///
+    /// let q = SegQueue::<i32>::with_segment_capacity(100);
+    /// assert_eq!(q.segment_capacity(), 127);
+    /// ```
+    pub fn with_segment_capacity(cap: usize) -> SegQueue<T> {
+        assert!(cap > 0, "capacity must be non-zero");
+
+        // One lap is the smallest power of two greater than `cap`.
+        let lap = cap
+            .checked_add(1)
+            .and_then(usize::checked_next_power_of_two)
+            .expect("capacity overflow");
+
+        SegQueue {
+            head: CachePadded::new(Position {
+                block: AtomicPtr::new(ptr::null_mut()),
+                index: AtomicUsize::new(0),
+            }),
+            tail: CachePadded::new(Position {
+                block: AtomicPtr::new(ptr::null_mut()),
+                index: AtomicUsize::new(0),
+            }),
+            lap,
             _marker: PhantomData,
         }
     }
 
+    /// Returns the number of elements each segment of the queue can hold.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::SegQueue;
+    ///
+    /// let q = SegQueue::<i32>::new();
+    /// assert_eq!(q.segment_capacity(), 31);
+    /// ```
+    pub fn segment_capacity(&self) -> usize {
+        self.lap - 1
+    }
+
     /// Pushes an element into the queue.
     ///
     /// # Examples
@@ -186,14 +283,15 @@ impl<T> SegQueue<T> {
         let backoff = Backoff::new();
         let mut tail = self.tail.index.load(Ordering::Acquire);
         let mut block = self.tail.block.load(Ordering::Acquire);
-        let mut next_block = None;
+        let mut next_block: *mut Block<T> = ptr::null_mut();
+        let block_cap = self.lap - 1;
 
         loop {
             // Calculate the offset of the index into the block.
-            let offset = (tail >> SHIFT) % LAP;
+            let offset = (tail >> SHIFT) & (self.lap - 1);
 
             // If we reached the end of the block, wait until the next one is installed.
-            if offset == BLOCK_CAP {
+            if offset == block_cap {
                 backoff.snooze();
                 tail = self.tail.index.load(Ordering::Acquire);
                 block = self.tail.block.load(Ordering::Acquire);
@@ -202,13 +300,13 @@ impl<T> SegQueue<T> {
 
             // If we're going to have to install the next block, allocate it in advance in order to
             // make the wait for other threads as short as possible.
-            if offset + 1 == BLOCK_CAP && next_block.is_none() {
-                next_block = Some(Box::new(Block::<T>::new()));
+            if offset + 1 == block_cap && next_block.is_null() {
+                next_block = Block::<T>::new(block_cap);
             }
 
             // If this is the first push operation, we need to allocate the first block.
             if block.is_null() {
-                let new = Box::into_raw(Box::new(Block::<T>::new()));
+                let new = Block::<T>::new(block_cap);
 
                 if self
                     .tail
@@ -219,7 +317,12 @@ impl<T> SegQueue<T> {
                     self.head.block.store(new, Ordering::Release);
                     block = new;
                 } else {
-                    next_block = unsafe { Some(Box::from_raw(new)) };
+                    unsafe {
+                        if !next_block.is_null() {
+                            Block::dealloc(next_block, block_cap);
+                        }
+                    }
+                    next_block = new;
                     tail = self.tail.index.load(Ordering::Acquire);
                     block = self.tail.block.load(Ordering::Acquire);
                     continue;
@@ -237,17 +340,19 @@ impl<T> SegQueue<T> {
             ) {
                 Ok(_) => unsafe {
                     // If we've reached the end of the block, install the next one.
-                    if offset + 1 == BLOCK_CAP {
-                        let next_block = Box::into_raw(next_block.unwrap());
+                    if offset + 1 == block_cap {
                         let next_index = new_tail.wrapping_add(1 << SHIFT);
 
                         self.tail.block.store(next_block, Ordering::Release);
                         self.tail.index.store(next_index, Ordering::Release);
                         (*block).next.store(next_block, Ordering::Release);
+                    } else if !next_block.is_null() {
+                        // The block allocated in advance turned out not to be needed.
+                        Block::dealloc(next_block, block_cap);
                     }
 
                     // Write the value into the slot.
-                    let slot = (*block).slots.get_unchecked(offset);
+                    let slot = Block::slot(block, offset);
                     slot.value.get().write(MaybeUninit::new(value));
                     slot.state.fetch_or(WRITE, Ordering::Release);
 
@@ -281,13 +386,14 @@ impl<T> SegQueue<T> {
         let backoff = Backoff::new();
         let mut head = self.head.index.load(Ordering::Acquire);
         let mut block = self.head.block.load(Ordering::Acquire);
+        let block_cap = self.lap - 1;
 
         loop {
             // Calculate the offset of the index into the block.
-            let offset = (head >> SHIFT) % LAP;
+            let offset = (head >> SHIFT) & (self.lap - 1);
 
             // If we reached the end of the block, wait until the next one is installed.
-            if offset == BLOCK_CAP {
+            if offset == block_cap {
                 backoff.snooze();
                 head = self.head.index.load(Ordering::Acquire);
                 block = self.head.block.load(Ordering::Acquire);
@@ -306,7 +412,7 @@ impl<T> SegQueue<T> {
                 }
 
                 // If head and tail are not in the same block, set `HAS_NEXT` in head.
-                if (head >> SHIFT) / LAP != (tail >> SHIFT) / LAP {
+                if (head >> SHIFT) & !(self.lap - 1) != (tail >> SHIFT) & !(self.lap - 1) {
                     new_head |= HAS_NEXT;
                 }
             }
@@ -329,7 +435,7 @@ impl<T> SegQueue<T> {
             ) {
                 Ok(_) => unsafe {
                     // If we've reached the end of the block, move to the next one.
-                    if offset + 1 == BLOCK_CAP {
+                    if offset + 1 == block_cap {
                         let next = (*block).wait_next();
                         let mut next_index = (new_head & !HAS_NEXT).wrapping_add(1 << SHIFT);
                         if !(*next).next.load(Ordering::Relaxed).is_null() {
@@ -341,16 +447,16 @@ impl<T> SegQueue<T> {
                     }
 
                     // Read the value.
-                    let slot = (*block).slots.get_unchecked(offset);
+                    let slot = Block::slot(block, offset);
                     slot.wait_write();
                     let value = slot.value.get().read().assume_init();
 
                     // Destroy the block if we've reached the end, or if another thread wanted to
                     // destroy but couldn't because we were busy reading from the slot.
-                    if offset + 1 == BLOCK_CAP {
-                        Block::destroy(block, 0);
+                    if offset + 1 == block_cap {
+                        Block::destroy(block, 0, block_cap);
                     } else if slot.state.fetch_or(READ, Ordering::AcqRel) & DESTROY != 0 {
-                        Block::destroy(block, offset + 1);
+                        Block::destroy(block, offset + 1, block_cap);
                     }
 
                     return Some(value);
@@ -412,27 +518,50 @@ impl<T> SegQueue<T> {
                 head &= !((1 << SHIFT) - 1);
 
                 // Fix up indices if they fall onto block ends.
-                if (tail >> SHIFT) & (LAP - 1) == LAP - 1 {
+                if (tail >> SHIFT) & (self.lap - 1) == self.lap - 1 {
                     tail = tail.wrapping_add(1 << SHIFT);
                 }
-                if (head >> SHIFT) & (LAP - 1) == LAP - 1 {
+                if (head >> SHIFT) & (self.lap - 1) == self.lap - 1 {
                     head = head.wrapping_add(1 << SHIFT);
                 }
 
                 // Rotate indices so that head falls into the first block.
-                let lap = (head >> SHIFT) / LAP;
-                tail = tail.wrapping_sub((lap * LAP) << SHIFT);
-                head = head.wrapping_sub((lap * LAP) << SHIFT);
+                let start = (head >> SHIFT) & !(self.lap - 1);
+                tail = tail.wrapping_sub(start << SHIFT);
+                head = head.wrapping_sub(start << SHIFT);
 
                 // Remove the lower bits.
                 tail >>= SHIFT;
                 head >>= SHIFT;
 
                 // Return the difference minus the number of blocks between tail and head.
-                return tail - head - tail / LAP;
+                return tail - head - tail / self.lap;
             }
         }
     }
+
+    /// Returns an iterator that pops elements from the queue until it becomes empty.
+    ///
+    /// Elements pushed by other threads while the iterator is in use may also be returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::SegQueue;
+    ///
+    /// let q = SegQueue::new();
+    /// q.push(1);
+    /// q.push(2);
+    /// q.push(3);
+    ///
+    /// let v: Vec<_> = q.drain().collect();
+    ///
+    /// assert_eq!(v, [1, 2, 3]);
+    /// assert!(q.is_empty());
+    /// ```
+    pub fn drain(&self) -> SegQueueDrain<'_, T> {
+        SegQueueDrain { queue: self }
+    }
 }
 
 impl<T> Drop for SegQueue<T> {
@@ -440,6 +569,7 @@ impl<T> Drop for SegQueue<T> {
         let mut head = self.head.index.load(Ordering::Relaxed);
         let mut tail = self.tail.index.load(Ordering::Relaxed);
         let mut block = self.head.block.load(Ordering::Relaxed);
+        let block_cap = self.lap - 1;
 
         // Erase the lower bits.
         head &= !((1 << SHIFT) - 1);
@@ -448,17 +578,17 @@ impl<T> Drop for SegQueue<T> {
         unsafe {
             // Drop all values between `head` and `tail` and deallocate the heap-allocated blocks.
             while head != tail {
-                let offset = (head >> SHIFT) % LAP;
+                let offset = (head >> SHIFT) & (self.lap - 1);
 
-                if offset < BLOCK_CAP {
+                if offset < block_cap {
                     // Drop the value in the slot.
-                    let slot = (*block).slots.get_unchecked(offset);
+                    let slot = Block::slot(block, offset);
                     let p = &mut *slot.value.get();
                     p.as_mut_ptr().drop_in_place();
                 } else {
                     // Deallocate the block and move to the next one.
                     let next = (*block).next.load(Ordering::Relaxed);
-                    drop(Box::from_raw(block));
+                    Block::dealloc(block, block_cap);
                     block = next;
                 }
 
@@ -467,7 +597,7 @@ impl<T> Drop for SegQueue<T> {
 
             // Deallocate the last remaining block.
             if !block.is_null() {
-                drop(Box::from_raw(block));
+                Block::dealloc(block, block_cap);
             }
         }
     }
@@ -484,3 +614,114 @@ impl<T> Default for SegQueue<T> {
         SegQueue::new()
     }
 }
+
+impl<T> IntoIterator for SegQueue<T> {
+    type Item = T;
+    type IntoIter = SegQueueIntoIter<T>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        SegQueueIntoIter { queue: self }
+    }
+}
+
+/// A draining iterator over elements of a [`SegQueue`].
+///
+/// This iterator is created by [`SegQueue::drain`]. Each call to [`next`] pops an element from
+/// the queue, and the iterator ends as soon as the queue is observed empty.
+///
+/// [`next`]: Iterator::next
+pub struct SegQueueDrain<'a, T> {
+    queue: &'a SegQueue<T>,
+}
+
+impl<T> Iterator for SegQueueDrain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.queue.pop()
+    }
+}
+
+impl<T> fmt::Debug for SegQueueDrain<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("SegQueueDrain { .. }")
+    }
+}
+
+/// An owning iterator over elements of a [`SegQueue`].
+///
+/// This iterator is created by the [`into_iter`] method on [`SegQueue`]. Since the queue is
+/// owned, elements are moved out of their blocks without any synchronization, and each block is
+/// deallocated as soon as it has been emptied.
+///
+/// [`into_iter`]: IntoIterator::into_iter
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_queue::SegQueue;
+///
+/// let q = SegQueue::new();
+/// q.push('a');
+/// q.push('b');
+///
+/// let v: Vec<_> = q.into_iter().collect();
+///
+/// assert_eq!(v, ['a', 'b']);
+/// ```
+pub struct SegQueueIntoIter<T> {
+    queue: SegQueue<T>,
+}
+
+impl<T> Iterator for SegQueueIntoIter<T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let queue = &mut self.queue;
+        let head = *queue.head.index.get_mut();
+        let tail = *queue.tail.index.get_mut();
+
+        if head >> SHIFT == tail >> SHIFT {
+            return None;
+        }
+
+        let block = *queue.head.block.get_mut();
+        let block_cap = queue.lap - 1;
+        let offset = (head >> SHIFT) & (queue.lap - 1);
+
+        unsafe {
+            // Move the value out of the slot. The queue is owned, so nobody else can observe it.
+            let slot = Block::slot(block, offset);
+            let value = slot.value.get().read().assume_init();
+
+            if offset + 1 == block_cap {
+                // Deallocate the block and move to the next one, skipping the index at the end of
+                // the block that doesn't hold a value.
+                let next = *(*block).next.get_mut();
+                Block::dealloc(block, block_cap);
+
+                *queue.head.block.get_mut() = next;
+                *queue.head.index.get_mut() = (head & !HAS_NEXT).wrapping_add(2 << SHIFT);
+            } else {
+                *queue.head.index.get_mut() = head.wrapping_add(1 << SHIFT);
+            }
+
+            Some(value)
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.queue.len();
+        (len, Some(len))
+    }
+}
+
+impl<T> ExactSizeIterator for SegQueueIntoIter<T> {}
+
+impl<T> FusedIterator for SegQueueIntoIter<T> {}
+
+impl<T> fmt::Debug for SegQueueIntoIter<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("SegQueueIntoIter { .. }")
+    }
+}
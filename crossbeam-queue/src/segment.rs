@@ -0,0 +1,204 @@
+//! An unbounded list of slots that is stored in blocks and addressed by index.
+//!
+//! Blocks are allocated on demand, by whichever thread first looks up an index past the end of the
+//! list. The owner of the list decides when the slots below some index aren't needed anymore, and
+//! the blocks holding only such slots are then destroyed through [`crossbeam-epoch`], since other
+//! threads may still be looking at them.
+//!
+//! [`crossbeam-epoch`]: https://docs.rs/crossbeam-epoch
+
+use core::cell::UnsafeCell;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicBool, Ordering};
+
+use crossbeam_epoch::{self as epoch, Atomic, Guard, Owned, Shared};
+use crossbeam_utils::CachePadded;
+
+/// The number of slots in a block.
+pub(crate) const BLOCK_CAP: usize = 32;
+
+/// A slot in a block.
+pub(crate) struct Slot<T> {
+    /// The value.
+    value: UnsafeCell<MaybeUninit<T>>,
+
+    /// Set once the value has been written.
+    ready: AtomicBool,
+}
+
+impl<T> Slot<T> {
+    /// Writes a value into the slot.
+    ///
+    /// Only one thread may write into the slot, and only once.
+    pub(crate) unsafe fn write(&self, value: T) {
+        self.value.get().write(MaybeUninit::new(value));
+        self.ready.store(true, Ordering::Release);
+    }
+
+    /// Returns `true` if a value has been written into the slot.
+    pub(crate) fn is_ready(&self) -> bool {
+        self.ready.load(Ordering::Acquire)
+    }
+
+    /// Returns the value in the slot.
+    ///
+    /// The slot must be ready.
+    pub(crate) unsafe fn get(&self) -> &T {
+        &*(*self.value.get()).as_ptr()
+    }
+}
+
+/// A block in the linked list of slots.
+pub(crate) struct Block<T> {
+    /// The index of the first slot in the block.
+    start: usize,
+
+    /// The next block in the linked list.
+    next: Atomic<Block<T>>,
+
+    /// The slots.
+    slots: [Slot<T>; BLOCK_CAP],
+}
+
+impl<T> Block<T> {
+    /// Creates an empty block starting at index `start`.
+    fn new(start: usize) -> Owned<Block<T>> {
+        // SAFETY: This is safe because:
+        //  [1] `Block::start` (usize) is overwritten right away.
+        //  [2] `Block::next` (Atomic) may be safely zero initialized.
+        //  [3] `Slot::value` (UnsafeCell) may be safely zero initialized because it holds a
+        //      MaybeUninit.
+        //  [4] `Slot::ready` (AtomicBool) may be safely zero initialized.
+        let mut block = Owned::new(unsafe { MaybeUninit::<Block<T>>::zeroed().assume_init() });
+        block.start = start;
+        block
+    }
+
+    /// Returns the slot at `index`, which must be in this block.
+    pub(crate) fn slot(&self, index: usize) -> &Slot<T> {
+        &self.slots[index - self.start]
+    }
+}
+
+impl<T> Drop for Block<T> {
+    fn drop(&mut self) {
+        for slot in self.slots.iter_mut() {
+            if *slot.ready.get_mut() {
+                unsafe { (*slot.value.get()).as_mut_ptr().drop_in_place() };
+            }
+        }
+    }
+}
+
+/// An unbounded list of slots stored in blocks.
+pub(crate) struct Segments<T> {
+    /// A block at or shortly before the end of the list, from which lookups start.
+    tail_block: CachePadded<Atomic<Block<T>>>,
+
+    /// The oldest block that hasn't been destroyed.
+    head_block: Atomic<Block<T>>,
+}
+
+impl<T> Segments<T> {
+    /// Creates a list with a single block, starting at index 0.
+    pub(crate) fn new() -> Segments<T> {
+        let block = Block::new(0).into_shared(unsafe { epoch::unprotected() });
+        Segments {
+            tail_block: CachePadded::new(Atomic::from(block)),
+            head_block: Atomic::from(block),
+        }
+    }
+
+    /// Returns the block holding the slot at `index`, allocating blocks as needed.
+    ///
+    /// Returns `None` if the block has already been destroyed by [`reclaim`].
+    ///
+    /// [`reclaim`]: Segments::reclaim
+    pub(crate) fn block<'g>(&self, index: usize, guard: &'g Guard) -> Option<&'g Block<T>> {
+        let start = index - index % BLOCK_CAP;
+        let mut block = self.tail_block.load(Ordering::Acquire, guard);
+
+        if unsafe { block.deref() }.start > start {
+            // Other threads have moved the tail block past ours, so look from the oldest block.
+            block = self.head_block.load(Ordering::Acquire, guard);
+            if unsafe { block.deref() }.start > start {
+                return None;
+            }
+        }
+
+        loop {
+            let b = unsafe { block.deref() };
+            if b.start == start {
+                return Some(b);
+            }
+
+            let mut next = b.next.load(Ordering::Acquire, guard);
+            if next.is_null() {
+                // Try to link a new block exactly once. If another thread was faster, use its
+                // block instead.
+                next = match b.next.compare_exchange(
+                    Shared::null(),
+                    Block::new(b.start + BLOCK_CAP),
+                    Ordering::AcqRel,
+                    Ordering::Acquire,
+                    guard,
+                ) {
+                    Ok(new) => new,
+                    Err(err) => err.current,
+                };
+            }
+
+            // Move the tail block forward, unless another thread already has.
+            let _ = self.tail_block.compare_exchange(
+                block,
+                next,
+                Ordering::Release,
+                Ordering::Relaxed,
+                guard,
+            );
+            block = next;
+        }
+    }
+
+    /// Destroys the blocks that only hold slots below index `low`, keeping at least one block.
+    ///
+    /// Only one thread may call this at a time.
+    pub(crate) fn reclaim(&self, low: usize, guard: &Guard) {
+        let mut head = self.head_block.load(Ordering::Acquire, guard);
+        loop {
+            let h = unsafe { head.deref() };
+            if h.start + BLOCK_CAP > low {
+                break;
+            }
+            let next = h.next.load(Ordering::Acquire, guard);
+            if next.is_null() {
+                break;
+            }
+
+            self.head_block.store(next, Ordering::Release);
+            let _ = self.tail_block.compare_exchange(
+                head,
+                next,
+                Ordering::Release,
+                Ordering::Relaxed,
+                guard,
+            );
+            unsafe { guard.defer_destroy(head) };
+            head = next;
+        }
+    }
+}
+
+impl<T> Drop for Segments<T> {
+    fn drop(&mut self) {
+        unsafe {
+            let guard = epoch::unprotected();
+            let mut block = self.head_block.load(Ordering::Relaxed, guard);
+            while !block.is_null() {
+                let next = block.deref().next.load(Ordering::Relaxed, guard);
+                drop(block.into_owned());
+                block = next;
+            }
+        }
+    }
+}
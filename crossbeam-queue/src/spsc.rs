@@ -0,0 +1,489 @@
+//! A bounded single-producer single-consumer queue.
+//!
+//! Since only one thread ever pushes and only one thread ever pops, each side owns its index and
+//! publishes it with a plain store instead of a compare-and-swap loop. Each side also keeps a
+//! cached copy of the other side's index, and only reloads it when the cached value suggests the
+//! queue is full (for the producer) or empty (for the consumer).
+
+use alloc::boxed::Box;
+use alloc::sync::Arc;
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::mem::MaybeUninit;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crossbeam_utils::CachePadded;
+
+/// The buffer and indices shared by the producer and the consumer.
+struct Inner<T> {
+    /// The head of the queue.
+    ///
+    /// This value is a position in the range `0..2 * cap`. It is only ever written to by the
+    /// consumer.
+    ///
+    /// Elements are popped from the head of the queue.
+    head: CachePadded<AtomicUsize>,
+
+    /// The tail of the queue.
+    ///
+    /// This value is a position in the range `0..2 * cap`. It is only ever written to by the
+    /// producer.
+    ///
+    /// Elements are pushed into the tail of the queue.
+    tail: CachePadded<AtomicUsize>,
+
+    /// The buffer holding slots.
+    buffer: Box<[UnsafeCell<MaybeUninit<T>>]>,
+}
+
+unsafe impl<T: Send> Send for Inner<T> {}
+unsafe impl<T: Send> Sync for Inner<T> {}
+
+impl<T> Inner<T> {
+    /// Returns the capacity of the queue.
+    fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Returns the position that follows `pos`.
+    ///
+    /// Positions range over two laps so that a full queue can be told apart from an empty one.
+    fn increment(&self, pos: usize) -> usize {
+        if pos < 2 * self.capacity() - 1 {
+            pos + 1
+        } else {
+            0
+        }
+    }
+
+    /// Returns the slot at position `pos`.
+    fn slot(&self, pos: usize) -> *mut MaybeUninit<T> {
+        let index = if pos < self.capacity() {
+            pos
+        } else {
+            pos - self.capacity()
+        };
+        self.buffer[index].get()
+    }
+
+    /// Returns the number of elements between `head` and `tail`.
+    fn distance(&self, head: usize, tail: usize) -> usize {
+        if head <= tail {
+            tail - head
+        } else {
+            2 * self.capacity() - head + tail
+        }
+    }
+
+    /// Returns the number of elements in the queue.
+    fn len(&self) -> usize {
+        let head = self.head.load(Ordering::Acquire);
+        let tail = self.tail.load(Ordering::Acquire);
+        self.distance(head, tail)
+    }
+}
+
+impl<T> Drop for Inner<T> {
+    fn drop(&mut self) {
+        let mut head = *self.head.get_mut();
+        let tail = *self.tail.get_mut();
+
+        // Loop over all slots that hold a value and drop them.
+        while head != tail {
+            unsafe {
+                (*self.slot(head)).as_mut_ptr().drop_in_place();
+            }
+            head = self.increment(head);
+        }
+    }
+}
+
+/// A bounded single-producer single-consumer queue.
+///
+/// The queue allocates a fixed-capacity buffer on construction and is then [split] into a
+/// [`Producer`] and a [`Consumer`]. Neither half can be cloned, which lets both of them avoid the
+/// compare-and-swap operations that [`ArrayQueue`] needs to support multiple producers and
+/// consumers.
+///
+/// [split]: RingBuffer::split
+/// [`ArrayQueue`]: crate::ArrayQueue
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_queue::spsc::RingBuffer;
+/// use std::thread;
+///
+/// let (mut p, mut c) = RingBuffer::new(2).split();
+///
+/// thread::spawn(move || {
+///     for i in 0..10 {
+///         while p.push(i).is_err() {}
+///     }
+/// });
+///
+/// for i in 0..10 {
+///     loop {
+///         if let Some(x) = c.pop() {
+///             assert_eq!(x, i);
+///             break;
+///         }
+///     }
+/// }
+/// ```
+pub struct RingBuffer<T> {
+    inner: Inner<T>,
+}
+
+impl<T> RingBuffer<T> {
+    /// Creates a new bounded queue with the given capacity.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the capacity is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::spsc::RingBuffer;
+    ///
+    /// let rb = RingBuffer::<i32>::new(100);
+    /// ```
+    pub fn new(cap: usize) -> RingBuffer<T> {
+        assert!(cap > 0, "capacity must be non-zero");
+        // Positions range over two laps, so twice the capacity must fit in a `usize`.
+        cap.checked_mul(2).expect("capacity overflow");
+
+        let buffer = (0..cap)
+            .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+            .collect();
+
+        RingBuffer {
+            inner: Inner {
+                head: CachePadded::new(AtomicUsize::new(0)),
+                tail: CachePadded::new(AtomicUsize::new(0)),
+                buffer,
+            },
+        }
+    }
+
+    /// Returns the capacity of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::spsc::RingBuffer;
+    ///
+    /// let rb = RingBuffer::<i32>::new(100);
+    ///
+    /// assert_eq!(rb.capacity(), 100);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Splits the queue into its producer and consumer halves.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::spsc::RingBuffer;
+    ///
+    /// let (mut p, mut c) = RingBuffer::new(1).split();
+    ///
+    /// assert_eq!(p.push(10), Ok(()));
+    /// assert_eq!(c.pop(), Some(10));
+    /// ```
+    pub fn split(self) -> (Producer<T>, Consumer<T>) {
+        let inner = Arc::new(self.inner);
+
+        let producer = Producer {
+            inner: inner.clone(),
+            head: 0,
+            tail: 0,
+        };
+        let consumer = Consumer {
+            inner,
+            head: 0,
+            tail: 0,
+        };
+        (producer, consumer)
+    }
+}
+
+impl<T> fmt::Debug for RingBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("RingBuffer { .. }")
+    }
+}
+
+/// The producer half of a [`RingBuffer`].
+///
+/// Elements pushed by the producer are popped by the matching [`Consumer`] in the same order.
+pub struct Producer<T> {
+    /// The shared queue.
+    inner: Arc<Inner<T>>,
+
+    /// A cached copy of the head, which may lag behind the real one.
+    head: usize,
+
+    /// The tail, which only the producer writes to.
+    tail: usize,
+}
+
+impl<T> Producer<T> {
+    /// Attempts to push an element into the queue.
+    ///
+    /// If the queue is full, the element is returned back as an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::spsc::RingBuffer;
+    ///
+    /// let (mut p, c) = RingBuffer::new(1).split();
+    ///
+    /// assert_eq!(p.push(10), Ok(()));
+    /// assert_eq!(p.push(20), Err(20));
+    /// ```
+    pub fn push(&mut self, value: T) -> Result<(), T> {
+        // If the queue looks full, the cached head may be stale, so reload it.
+        if self.inner.distance(self.head, self.tail) == self.inner.capacity() {
+            self.head = self.inner.head.load(Ordering::Acquire);
+
+            if self.inner.distance(self.head, self.tail) == self.inner.capacity() {
+                return Err(value);
+            }
+        }
+
+        // Write the value into the slot and publish the new tail.
+        unsafe {
+            self.inner.slot(self.tail).write(MaybeUninit::new(value));
+        }
+        self.tail = self.inner.increment(self.tail);
+        self.inner.tail.store(self.tail, Ordering::Release);
+
+        Ok(())
+    }
+
+    /// Returns the capacity of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::spsc::RingBuffer;
+    ///
+    /// let (p, c) = RingBuffer::<i32>::new(100).split();
+    ///
+    /// assert_eq!(p.capacity(), 100);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Returns `true` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::spsc::RingBuffer;
+    ///
+    /// let (mut p, c) = RingBuffer::new(100).split();
+    ///
+    /// assert!(p.is_empty());
+    /// p.push(1).unwrap();
+    /// assert!(!p.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.inner.len() == 0
+    }
+
+    /// Returns `true` if the queue is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::spsc::RingBuffer;
+    ///
+    /// let (mut p, c) = RingBuffer::new(1).split();
+    ///
+    /// assert!(!p.is_full());
+    /// p.push(1).unwrap();
+    /// assert!(p.is_full());
+    /// ```
+    pub fn is_full(&self) -> bool {
+        self.inner.len() == self.inner.capacity()
+    }
+
+    /// Returns the number of elements in the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::spsc::RingBuffer;
+    ///
+    /// let (mut p, c) = RingBuffer::new(100).split();
+    /// assert_eq!(p.len(), 0);
+    ///
+    /// p.push(10).unwrap();
+    /// assert_eq!(p.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<T> fmt::Debug for Producer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Producer { .. }")
+    }
+}
+
+/// The consumer half of a [`RingBuffer`].
+///
+/// Elements are popped in the same order the matching [`Producer`] pushed them.
+pub struct Consumer<T> {
+    /// The shared queue.
+    inner: Arc<Inner<T>>,
+
+    /// The head, which only the consumer writes to.
+    head: usize,
+
+    /// A cached copy of the tail, which may lag behind the real one.
+    tail: usize,
+}
+
+impl<T> Consumer<T> {
+    /// Reloads the cached tail if the queue looks empty, and returns `true` if it is.
+    fn check_empty(&mut self) -> bool {
+        if self.head == self.tail {
+            self.tail = self.inner.tail.load(Ordering::Acquire);
+        }
+        self.head == self.tail
+    }
+
+    /// Attempts to pop an element from the queue.
+    ///
+    /// If the queue is empty, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::spsc::RingBuffer;
+    ///
+    /// let (mut p, mut c) = RingBuffer::new(1).split();
+    /// assert_eq!(p.push(10), Ok(()));
+    ///
+    /// assert_eq!(c.pop(), Some(10));
+    /// assert!(c.pop().is_none());
+    /// ```
+    pub fn pop(&mut self) -> Option<T> {
+        if self.check_empty() {
+            return None;
+        }
+
+        // Read the value from the slot and publish the new head.
+        let value = unsafe { self.inner.slot(self.head).read().assume_init() };
+        self.head = self.inner.increment(self.head);
+        self.inner.head.store(self.head, Ordering::Release);
+
+        Some(value)
+    }
+
+    /// Returns a reference to the element at the head of the queue without removing it.
+    ///
+    /// If the queue is empty, `None` is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::spsc::RingBuffer;
+    ///
+    /// let (mut p, mut c) = RingBuffer::new(2).split();
+    /// assert_eq!(c.peek(), None);
+    ///
+    /// p.push(10).unwrap();
+    /// assert_eq!(c.peek(), Some(&10));
+    /// assert_eq!(c.pop(), Some(10));
+    /// ```
+    pub fn peek(&mut self) -> Option<&T> {
+        if self.check_empty() {
+            return None;
+        }
+
+        // Only the consumer can pop, so the element stays put for as long as it is borrowed.
+        unsafe { Some(&*(*self.inner.slot(self.head)).as_ptr()) }
+    }
+
+    /// Returns the capacity of the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::spsc::RingBuffer;
+    ///
+    /// let (p, c) = RingBuffer::<i32>::new(100).split();
+    ///
+    /// assert_eq!(c.capacity(), 100);
+    /// ```
+    pub fn capacity(&self) -> usize {
+        self.inner.capacity()
+    }
+
+    /// Returns `true` if the queue is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::spsc::RingBuffer;
+    ///
+    /// let (mut p, c) = RingBuffer::new(100).split();
+    ///
+    /// assert!(c.is_empty());
+    /// p.push(1).unwrap();
+    /// assert!(!c.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.inner.len() == 0
+    }
+
+    /// Returns `true` if the queue is full.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::spsc::RingBuffer;
+    ///
+    /// let (mut p, c) = RingBuffer::new(1).split();
+    ///
+    /// assert!(!c.is_full());
+    /// p.push(1).unwrap();
+    /// assert!(c.is_full());
+    /// ```
+    pub fn is_full(&self) -> bool {
+        self.inner.len() == self.inner.capacity()
+    }
+
+    /// Returns the number of elements in the queue.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_queue::spsc::RingBuffer;
+    ///
+    /// let (mut p, c) = RingBuffer::new(100).split();
+    /// assert_eq!(c.len(), 0);
+    ///
+    /// p.push(10).unwrap();
+    /// assert_eq!(c.len(), 1);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+}
+
+impl<T> fmt::Debug for Consumer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Consumer { .. }")
+    }
+}
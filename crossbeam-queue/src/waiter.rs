@@ -0,0 +1,56 @@
+//! Blocking on a condition shared by many threads.
+
+use core::sync::atomic::{self, AtomicUsize, Ordering};
+use std::sync::{Condvar, Mutex};
+
+/// Puts threads to sleep and wakes them up.
+pub(crate) struct Waiter {
+    /// The number of threads that are blocked or about to block.
+    waiting: AtomicUsize,
+
+    /// Held while checking whether to block, so that notifications aren't missed.
+    lock: Mutex<()>,
+
+    /// Signaled whenever the condition threads are waiting for may have changed.
+    condvar: Condvar,
+}
+
+impl Waiter {
+    /// Creates a waiter with no blocked threads.
+    pub(crate) fn new() -> Waiter {
+        Waiter {
+            waiting: AtomicUsize::new(0),
+            lock: Mutex::new(()),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Blocks until `cond` returns `true`.
+    ///
+    /// Whoever changes the outcome of `cond` must call [`notify`] afterwards.
+    ///
+    /// [`notify`]: Waiter::notify
+    pub(crate) fn wait_until<F: FnMut() -> bool>(&self, mut cond: F) {
+        self.waiting.fetch_add(1, Ordering::SeqCst);
+        let mut lock = self.lock.lock().unwrap_or_else(|err| err.into_inner());
+        while !cond() {
+            lock = self
+                .condvar
+                .wait(lock)
+                .unwrap_or_else(|err| err.into_inner());
+        }
+        drop(lock);
+        self.waiting.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    /// Wakes up blocked threads after the condition they are waiting for may have changed.
+    pub(crate) fn notify(&self) {
+        // Pairs with the increment in `wait_until`: either the waiting thread sees the change, or
+        // we see that it is waiting.
+        atomic::fence(Ordering::SeqCst);
+        if self.waiting.load(Ordering::SeqCst) > 0 {
+            let _lock = self.lock.lock().unwrap_or_else(|err| err.into_inner());
+            self.condvar.notify_all();
+        }
+    }
+}
@@ -57,6 +57,30 @@ fn len_empty_full() {
     assert_eq!(q.is_full(), false);
 }
 
+#[test]
+fn peek() {
+    let q = ArrayQueue::new(2);
+    assert_eq!(q.peek(), None);
+
+    q.push(1).unwrap();
+    assert_eq!(q.peek(), Some(1));
+    assert_eq!(q.peek(), Some(1));
+    assert_eq!(q.len(), 1);
+
+    q.push(2).unwrap();
+    assert_eq!(q.peek(), Some(1));
+    assert_eq!(q.pop(), Some(1));
+    assert_eq!(q.peek(), Some(2));
+
+    assert_eq!(q.force_push(3), None);
+    assert_eq!(q.force_push(4), Some(2));
+    assert_eq!(q.peek(), Some(3));
+
+    assert_eq!(q.pop(), Some(3));
+    assert_eq!(q.pop(), Some(4));
+    assert_eq!(q.peek(), None);
+}
+
 #[test]
 fn len() {
     const COUNT: usize = 25_000;
@@ -142,6 +166,99 @@ fn spsc() {
     .unwrap();
 }
 
+#[test]
+fn force_push() {
+    let q = ArrayQueue::new(3);
+
+    assert_eq!(q.force_push(1), None);
+    assert_eq!(q.force_push(2), None);
+    assert_eq!(q.force_push(3), None);
+    assert!(q.is_full());
+
+    assert_eq!(q.force_push(4), Some(1));
+    assert_eq!(q.force_push(5), Some(2));
+    assert_eq!(q.len(), 3);
+    assert_eq!(q.pop(), Some(3));
+
+    assert_eq!(q.force_push(6), None);
+    assert_eq!(q.force_push(7), Some(4));
+    assert_eq!(q.pop(), Some(5));
+    assert_eq!(q.pop(), Some(6));
+    assert_eq!(q.pop(), Some(7));
+    assert_eq!(q.pop(), None);
+}
+
+#[test]
+fn spsc_ring_buffer() {
+    const COUNT: usize = 100_000;
+
+    let t = AtomicUsize::new(1);
+    let q = ArrayQueue::<usize>::new(3);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        scope.spawn(|_| loop {
+            match t.load(Ordering::SeqCst) {
+                0 if q.is_empty() => break,
+
+                _ => {
+                    while let Some(n) = q.pop() {
+                        v[n].fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+            }
+        });
+
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                if let Some(n) = q.force_push(i) {
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            }
+
+            t.fetch_sub(1, Ordering::SeqCst);
+        });
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), 1);
+    }
+}
+
+#[test]
+fn peek_force_push() {
+    const COUNT: usize = 100_000;
+
+    let done = AtomicUsize::new(0);
+    let q = ArrayQueue::<[usize; 8]>::new(2);
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            let mut last = 0;
+            while done.load(Ordering::SeqCst) == 0 {
+                if let Some(a) = q.peek() {
+                    // The element must not be a mix of an overwritten element and its replacement.
+                    assert!(a.iter().all(|&x| x == a[0]));
+                    assert!(a[0] >= last);
+                    last = a[0];
+                }
+            }
+        });
+
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                q.force_push([i; 8]);
+            }
+            done.store(1, Ordering::SeqCst);
+        });
+    })
+    .unwrap();
+
+    assert_eq!(q.pop(), Some([COUNT - 2; 8]));
+    assert_eq!(q.pop(), Some([COUNT - 1; 8]));
+}
+
 #[test]
 fn mpmc() {
     const COUNT: usize = 25_000;
@@ -178,6 +295,49 @@ fn mpmc() {
     }
 }
 
+#[test]
+fn mpmc_ring_buffer() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let t = AtomicUsize::new(THREADS);
+    let q = ArrayQueue::<usize>::new(3);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| loop {
+                match t.load(Ordering::SeqCst) {
+                    0 if q.is_empty() => break,
+
+                    _ => {
+                        while let Some(n) = q.pop() {
+                            v[n].fetch_add(1, Ordering::SeqCst);
+                        }
+                    }
+                }
+            });
+        }
+
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    if let Some(n) = q.force_push(i) {
+                        v[n].fetch_add(1, Ordering::SeqCst);
+                    }
+                }
+
+                t.fetch_sub(1, Ordering::SeqCst);
+            });
+        }
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
+
 #[test]
 fn drops() {
     const RUNS: usize = 100;
@@ -248,3 +408,198 @@ fn linearizable() {
     })
     .unwrap();
 }
+
+#[test]
+fn drain() {
+    let q = ArrayQueue::new(10);
+    assert_eq!(q.drain().next(), None);
+
+    for i in 0..10 {
+        q.push(i).unwrap();
+    }
+
+    assert_eq!(q.drain().take(3).collect::<Vec<_>>(), [0, 1, 2]);
+    assert_eq!(q.len(), 7);
+
+    for i in 10..13 {
+        q.push(i).unwrap();
+    }
+    assert_eq!(q.drain().collect::<Vec<_>>(), (3..13).collect::<Vec<_>>());
+    assert!(q.is_empty());
+}
+
+#[test]
+fn into_iter() {
+    let q = ArrayQueue::new(10);
+    for i in 0..15 {
+        q.force_push(i);
+    }
+
+    let mut iter = q.into_iter();
+    assert_eq!(iter.len(), 10);
+    assert_eq!(iter.next(), Some(5));
+    assert_eq!(iter.len(), 9);
+    assert_eq!(iter.collect::<Vec<_>>(), (6..15).collect::<Vec<_>>());
+}
+
+#[test]
+fn into_iter_drop() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    for (count, take) in [(0, 0), (10, 5), (50, 10), (50, 50)].iter().copied() {
+        DROPS.store(0, Ordering::SeqCst);
+
+        let q = ArrayQueue::new(50);
+        for _ in 0..count {
+            q.push(DropCounter).unwrap();
+        }
+
+        let mut iter = q.into_iter();
+        for _ in 0..take {
+            drop(iter.next().unwrap());
+        }
+        assert_eq!(DROPS.load(Ordering::SeqCst), take);
+
+        drop(iter);
+        assert_eq!(DROPS.load(Ordering::SeqCst), count);
+    }
+}
+
+#[test]
+fn push_slot() {
+    let q = ArrayQueue::new(2);
+
+    q.push_slot().unwrap().write(1);
+    let mut slot = q.push_slot().unwrap();
+    assert!(q.push_slot().is_none());
+    assert_eq!(q.len(), 2);
+
+    unsafe {
+        slot.as_mut_ptr().write(2);
+        slot.commit();
+    }
+
+    assert_eq!(q.pop(), Some(1));
+    assert_eq!(q.pop(), Some(2));
+    assert_eq!(q.pop(), None);
+}
+
+#[test]
+fn push_slot_dropped() {
+    let q = ArrayQueue::new(3);
+
+    q.push(1).unwrap();
+    drop(q.push_slot().unwrap());
+    q.push(2).unwrap();
+    assert_eq!(q.len(), 3);
+    assert!(q.is_full());
+
+    assert_eq!(q.pop(), Some(1));
+    assert_eq!(q.peek(), Some(2));
+    assert_eq!(q.pop(), Some(2));
+    assert_eq!(q.pop(), None);
+
+    for _ in 0..3 {
+        drop(q.push_slot().unwrap());
+    }
+    assert_eq!(q.push(3), Err(3));
+    assert_eq!(q.force_push(4), None);
+    assert_eq!(q.force_push(5), None);
+    assert_eq!(q.pop(), Some(4));
+    assert_eq!(q.pop(), Some(5));
+    assert_eq!(q.pop(), None);
+}
+
+#[test]
+fn push_slot_drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let q = ArrayQueue::new(4);
+    q.push(DropCounter).ok().unwrap();
+    drop(q.push_slot().unwrap());
+    q.push_slot().unwrap().write(DropCounter);
+    drop(q);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+
+    DROPS.store(0, Ordering::SeqCst);
+    let q = ArrayQueue::new(4);
+    drop(q.push_slot().unwrap());
+    q.push(DropCounter).ok().unwrap();
+    drop(q.push_slot().unwrap());
+    q.push(DropCounter).ok().unwrap();
+
+    let mut iter = q.into_iter();
+    assert_eq!(iter.len(), 2);
+    drop(iter.next().unwrap());
+    assert_eq!(iter.len(), 1);
+    drop(iter);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn mpmc_push_slot() {
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let q = ArrayQueue::<usize>::new(3);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT / 2 {
+                    let n = loop {
+                        if let Some(x) = q.pop() {
+                            break x;
+                        }
+                        std::thread::yield_now();
+                    };
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    let slot = loop {
+                        if let Some(slot) = q.push_slot() {
+                            break slot;
+                        }
+                        std::thread::yield_now();
+                    };
+                    // Only push even numbers and leave holes for the odd ones.
+                    if i % 2 == 0 {
+                        slot.write(i);
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for (i, c) in v.iter().enumerate() {
+        let expected = if i % 2 == 0 { THREADS } else { 0 };
+        assert_eq!(c.load(Ordering::SeqCst), expected);
+    }
+
+    // Popping skips any holes left behind by the last pushes.
+    assert_eq!(q.pop(), None);
+    assert!(q.is_empty());
+}
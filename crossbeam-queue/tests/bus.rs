@@ -0,0 +1,206 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crossbeam_queue::bus::{EventBus, LagPolicy, RecvError, TryRecvError};
+use crossbeam_utils::thread::scope;
+
+#[test]
+fn smoke() {
+    let bus = EventBus::new(16);
+    let mut a = bus.subscribe();
+    let mut b = bus.subscribe();
+
+    bus.publish(7);
+    assert_eq!(a.try_recv(), Ok(7));
+    assert_eq!(a.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(b.try_recv(), Ok(7));
+
+    bus.publish(8);
+    assert_eq!(b.try_recv(), Ok(8));
+    assert_eq!(a.try_recv(), Ok(8));
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    EventBus::<i32>::new(0);
+}
+
+#[test]
+fn no_subscribers() {
+    let bus = EventBus::new(4);
+
+    // Events published without subscribers are dropped, and never block.
+    for i in 0..1000 {
+        bus.publish(i);
+    }
+
+    let mut sub = bus.subscribe();
+    bus.publish(1000);
+    assert_eq!(sub.try_recv(), Ok(1000));
+    assert_eq!(sub.try_recv(), Err(TryRecvError::Empty));
+}
+
+#[test]
+fn disconnect_publishers() {
+    let bus = EventBus::new(16);
+    let bus2 = bus.clone();
+    let mut sub = bus.subscribe();
+
+    bus.publish(1);
+    drop(bus);
+    bus2.publish(2);
+    drop(bus2);
+
+    assert_eq!(sub.recv(), Ok(1));
+    assert_eq!(sub.recv(), Ok(2));
+    assert_eq!(sub.recv(), Err(RecvError));
+    assert_eq!(sub.try_recv(), Err(TryRecvError::Disconnected));
+}
+
+#[test]
+fn policy_drop() {
+    let bus = EventBus::with_policy(10, LagPolicy::Drop);
+    let mut slow = bus.subscribe();
+    let mut fast = bus.subscribe();
+
+    for i in 0..100 {
+        bus.publish(i);
+        assert_eq!(fast.try_recv(), Ok(i));
+    }
+    assert_eq!(fast.missed(), 0);
+
+    // The slow subscriber only gets the most recent events.
+    let received: Vec<i32> = (0..10).map(|_| slow.try_recv().unwrap()).collect();
+    assert_eq!(received, (90..100).collect::<Vec<_>>());
+    assert_eq!(slow.try_recv(), Err(TryRecvError::Empty));
+    assert_eq!(slow.missed(), 90);
+}
+
+#[test]
+fn policy_disconnect() {
+    let bus = EventBus::with_policy(10, LagPolicy::Disconnect);
+    let mut slow = bus.subscribe();
+    let mut fast = bus.subscribe();
+
+    for i in 0..100 {
+        bus.publish(i);
+        assert_eq!(fast.try_recv(), Ok(i));
+    }
+
+    assert_eq!(slow.try_recv(), Err(TryRecvError::Disconnected));
+    assert_eq!(slow.recv(), Err(RecvError));
+    assert_eq!(bus.subscriber_count(), 1);
+}
+
+#[test]
+fn policy_block() {
+    #[cfg(miri)]
+    const COUNT: usize = 50;
+    #[cfg(not(miri))]
+    const COUNT: usize = 10_000;
+
+    let bus = EventBus::new(8);
+    let mut sub = bus.subscribe();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..COUNT {
+                bus.publish(i);
+            }
+        });
+
+        // The publisher can't get more than 8 events ahead.
+        for i in 0..COUNT {
+            assert_eq!(sub.recv(), Ok(i));
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn drop_subscriber_unblocks() {
+    let bus = EventBus::new(1);
+    let sub = bus.subscribe();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for i in 0..100 {
+                bus.publish(i);
+            }
+        });
+        drop(sub);
+    })
+    .unwrap();
+}
+
+#[test]
+fn mpmc() {
+    #[cfg(miri)]
+    const COUNT: usize = 50;
+    #[cfg(not(miri))]
+    const COUNT: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let bus = EventBus::new(64);
+    let subs: Vec<_> = (0..THREADS).map(|_| bus.subscribe()).collect();
+
+    scope(|scope| {
+        for t in 0..THREADS {
+            let bus = bus.clone();
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    bus.publish((t, i));
+                }
+            });
+        }
+        drop(bus);
+
+        for mut sub in subs {
+            scope.spawn(move |_| {
+                let mut next = [0; THREADS];
+                while let Ok((t, i)) = sub.recv() {
+                    // Events from the same publisher arrive in order.
+                    assert_eq!(i, next[t]);
+                    next[t] += 1;
+                }
+                assert_eq!(next, [COUNT; THREADS]);
+            });
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Clone for DropCounter {
+        fn clone(&self) -> DropCounter {
+            DropCounter
+        }
+    }
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let bus = EventBus::with_policy(100, LagPolicy::Drop);
+    let mut sub = bus.subscribe();
+    for _ in 0..50 {
+        bus.publish(DropCounter);
+    }
+    for _ in 0..20 {
+        drop(sub.try_recv().unwrap());
+    }
+    assert_eq!(DROPS.load(Ordering::SeqCst), 20);
+
+    // The 50 published events are dropped along with the bus.
+    drop(sub);
+    drop(bus);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 70);
+}
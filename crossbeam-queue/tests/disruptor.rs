@@ -0,0 +1,201 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossbeam_queue::disruptor::{Disruptor, WaitStrategy};
+use crossbeam_utils::thread::scope;
+
+#[test]
+fn smoke() {
+    let mut d = Disruptor::new(4, || 0);
+    let mut c = d.consumer(&[]);
+    let p = d.into_producer();
+
+    p.publish(|e| *e = 7);
+    assert_eq!(c.try_poll(|e| assert_eq!(*e, 7)), 1);
+    assert_eq!(c.try_poll(|_| {}), 0);
+
+    p.publish(|e| *e = 8);
+    assert_eq!(c.try_poll(|e| assert_eq!(*e, 8)), 1);
+    assert_eq!(c.sequence(), 2);
+}
+
+#[test]
+fn capacity() {
+    for i in 1..10 {
+        let d = Disruptor::new(i, || 0);
+        assert_eq!(d.capacity(), i.next_power_of_two());
+    }
+}
+
+#[test]
+#[should_panic(expected = "capacity must be positive")]
+fn zero_capacity() {
+    let _ = Disruptor::new(0, || 0);
+}
+
+#[test]
+fn reuses_slots() {
+    let mut d = Disruptor::new(2, Vec::new);
+    let mut c = d.consumer(&[]);
+    let p = d.into_producer();
+
+    // Events are written into the slot that held the event from the previous lap.
+    for i in 0..10 {
+        p.publish(|e| e.push(i));
+        c.try_poll(|e| assert_eq!(e.len(), i / 2 + 1));
+    }
+}
+
+#[test]
+fn multicast() {
+    let mut d = Disruptor::new(8, || 0);
+    let mut a = d.consumer(&[]);
+    let mut b = d.consumer(&[]);
+    let p = d.into_producer();
+
+    p.publish_batch(5, |i, e| *e = i);
+
+    let mut seen_a = Vec::new();
+    let mut seen_b = Vec::new();
+    assert_eq!(a.try_poll(|e| seen_a.push(*e)), 5);
+    assert_eq!(b.try_poll(|e| seen_b.push(*e)), 5);
+    assert_eq!(seen_a, [0, 1, 2, 3, 4]);
+    assert_eq!(seen_b, seen_a);
+}
+
+#[test]
+fn dependencies() {
+    let mut d = Disruptor::new(8, || 0);
+    let mut a = d.consumer(&[]);
+    let mut b = d.consumer(&[]);
+    let mut c = d.consumer(&[&a, &b]);
+    let p = d.into_producer();
+
+    p.publish_batch(4, |i, e| *e = i);
+
+    assert_eq!(a.try_poll(|_| {}), 4);
+    assert_eq!(c.try_poll(|_| {}), 0);
+    assert_eq!(b.try_poll(|_| {}), 4);
+    assert_eq!(c.try_poll(|_| {}), 4);
+}
+
+#[test]
+#[should_panic(expected = "dependency belongs to another ring buffer")]
+fn foreign_dependency() {
+    let mut d1 = Disruptor::new(8, || 0);
+    let mut d2 = Disruptor::new(8, || 0);
+    let c = d1.consumer(&[]);
+    d2.consumer(&[&c]);
+}
+
+#[test]
+fn disconnect() {
+    let mut d = Disruptor::new(8, || 0);
+    let mut c = d.consumer(&[]);
+    let p = d.into_producer();
+    let p2 = p.clone();
+
+    p.publish(|e| *e = 1);
+    drop(p);
+    assert_eq!(c.poll(|_| {}), 1);
+
+    p2.publish(|e| *e = 2);
+    drop(p2);
+    assert_eq!(c.poll(|_| {}), 1);
+    assert_eq!(c.poll(|_| {}), 0);
+}
+
+#[test]
+fn pipeline() {
+    #[cfg(miri)]
+    const COUNT: usize = 50;
+    #[cfg(not(miri))]
+    const COUNT: usize = 4_000;
+    const PRODUCERS: usize = 2;
+
+    let strategies = [
+        WaitStrategy::Spin,
+        WaitStrategy::Yield,
+        WaitStrategy::Sleep(Duration::from_micros(10)),
+        WaitStrategy::Block,
+    ];
+
+    for &strategy in &strategies {
+        // Each event counts the stages that have processed it.
+        let mut d = Disruptor::with_wait_strategy(16, strategy, || AtomicUsize::new(0));
+        let mut first = d.consumer(&[]);
+        let mut second = d.consumer(&[]);
+        let mut last = d.consumer(&[&first, &second]);
+        let p = d.into_producer();
+
+        scope(|scope| {
+            for _ in 0..PRODUCERS {
+                let p = p.clone();
+                scope.spawn(move |_| {
+                    for i in 0..COUNT / 4 {
+                        // Alternate between single events and batches.
+                        if i % 2 == 0 {
+                            p.publish_batch(4, |_, e| e.store(0, Ordering::Relaxed));
+                        } else {
+                            for _ in 0..4 {
+                                p.publish(|e| e.store(0, Ordering::Relaxed));
+                            }
+                        }
+                    }
+                });
+            }
+            drop(p);
+
+            for c in vec![&mut first, &mut second] {
+                scope.spawn(move |_| {
+                    while c.poll(|e| {
+                        e.fetch_add(1, Ordering::Relaxed);
+                    }) > 0
+                    {}
+                });
+            }
+
+            scope.spawn(|_| {
+                let mut received = 0;
+                while last.poll(|e| {
+                    // Both stages this consumer depends on have seen the event.
+                    assert_eq!(e.load(Ordering::Relaxed), 2);
+                    received += 1;
+                }) > 0
+                {}
+                assert_eq!(received, PRODUCERS * COUNT);
+            });
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn drops() {
+    let drops = Arc::new(AtomicUsize::new(0));
+
+    struct DropCounter(Arc<AtomicUsize>);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let mut d = Disruptor::new(16, || DropCounter(drops.clone()));
+    let mut c = d.consumer(&[]);
+    let p = d.into_producer();
+
+    // Publishing overwrites events in place instead of dropping them.
+    for _ in 0..100 {
+        p.publish(|_| {});
+        c.try_poll(|_| {});
+    }
+    assert_eq!(drops.load(Ordering::SeqCst), 0);
+
+    drop(p);
+    assert_eq!(drops.load(Ordering::SeqCst), 0);
+    drop(c);
+    assert_eq!(drops.load(Ordering::SeqCst), 16);
+}
@@ -0,0 +1,133 @@
+use std::ptr::NonNull;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crossbeam_queue::intrusive::{Link, Linked, MpscQueue};
+use crossbeam_utils::thread::scope;
+
+#[repr(C)]
+struct Node {
+    link: Link,
+    value: usize,
+}
+
+unsafe impl Linked for Node {
+    fn link(node: NonNull<Node>) -> NonNull<Link> {
+        node.cast()
+    }
+
+    unsafe fn from_link(link: NonNull<Link>) -> NonNull<Node> {
+        link.cast()
+    }
+}
+
+fn node(value: usize) -> NonNull<Node> {
+    let node = Box::new(Node {
+        link: Link::new(),
+        value,
+    });
+    NonNull::from(Box::leak(node))
+}
+
+unsafe fn pop(q: &MpscQueue<Node>) -> Option<usize> {
+    q.pop().map(|n| Box::from_raw(n.as_ptr()).value)
+}
+
+#[test]
+fn smoke() {
+    let q = MpscQueue::new();
+
+    unsafe {
+        q.push(node(7));
+        assert_eq!(pop(&q), Some(7));
+
+        q.push(node(8));
+        assert_eq!(pop(&q), Some(8));
+        assert!(pop(&q).is_none());
+    }
+}
+
+#[test]
+fn is_empty() {
+    let q = MpscQueue::new();
+    assert!(q.is_empty());
+
+    unsafe {
+        q.push(node(1));
+        assert!(!q.is_empty());
+        q.push(node(2));
+        assert!(!q.is_empty());
+
+        pop(&q).unwrap();
+        assert!(!q.is_empty());
+        pop(&q).unwrap();
+        assert!(q.is_empty());
+    }
+}
+
+#[test]
+fn fifo() {
+    let q = MpscQueue::new();
+
+    unsafe {
+        for round in 0..10 {
+            for i in 0..round {
+                q.push(node(i));
+            }
+            for i in 0..round {
+                assert_eq!(pop(&q), Some(i));
+            }
+            assert_eq!(pop(&q), None);
+        }
+    }
+}
+
+#[test]
+fn move_queue() {
+    let q = MpscQueue::new();
+    unsafe { q.push(node(1)) };
+
+    let q = Box::new(q);
+    unsafe {
+        q.push(node(2));
+        assert_eq!(pop(&q), Some(1));
+        assert_eq!(pop(&q), Some(2));
+        assert_eq!(pop(&q), None);
+    }
+}
+
+#[test]
+fn mpsc() {
+    const COUNT: usize = 25_000;
+    const THREADS: usize = 4;
+
+    let q = MpscQueue::new();
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        scope.spawn(|_| {
+            for _ in 0..COUNT * THREADS {
+                let n = loop {
+                    if let Some(n) = unsafe { pop(&q) } {
+                        break n;
+                    }
+                    std::thread::yield_now();
+                };
+                v[n].fetch_add(1, Ordering::SeqCst);
+            }
+            assert!(unsafe { pop(&q) }.is_none());
+        });
+
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    unsafe { q.push(node(i)) };
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
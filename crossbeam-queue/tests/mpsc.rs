@@ -0,0 +1,173 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crossbeam_queue::mpsc::Queue;
+use crossbeam_utils::thread::scope;
+use rand::{thread_rng, Rng};
+
+#[test]
+fn smoke() {
+    let (p, mut c) = Queue::new().split();
+
+    p.push(7);
+    assert_eq!(c.pop(), Some(7));
+
+    p.push(8);
+    assert_eq!(c.pop(), Some(8));
+    assert!(c.pop().is_none());
+}
+
+#[test]
+fn len_empty() {
+    let (p, mut c) = Queue::new().split();
+
+    for i in 0..200 {
+        assert_eq!(c.len(), i);
+        p.push(i);
+        assert_eq!(p.len(), i + 1);
+    }
+    for i in 0..200 {
+        assert_eq!(c.pop(), Some(i));
+        assert_eq!(c.len(), 199 - i);
+    }
+    assert!(c.is_empty());
+    assert!(p.is_empty());
+}
+
+#[test]
+fn block_boundaries() {
+    let (p, mut c) = Queue::new().split();
+
+    // Drain the queue completely at every possible position within a block.
+    for i in 0..100 {
+        for j in 0..i {
+            p.push(j);
+        }
+        for j in 0..i {
+            assert_eq!(c.pop(), Some(j));
+        }
+        assert_eq!(c.pop(), None);
+    }
+}
+
+#[test]
+fn mpsc() {
+    #[cfg(miri)]
+    const COUNT: usize = 50;
+    #[cfg(not(miri))]
+    const COUNT: usize = 100_000;
+    const THREADS: usize = 4;
+
+    let (p, mut c) = Queue::new().split();
+
+    scope(|scope| {
+        for t in 0..THREADS {
+            let p = p.clone();
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    p.push((t, i));
+                }
+            });
+        }
+
+        scope.spawn(|_| {
+            let mut next = [0; THREADS];
+            let mut received = 0;
+            while received < THREADS * COUNT {
+                if let Some((t, i)) = c.pop() {
+                    // Values from the same producer arrive in order.
+                    assert_eq!(i, next[t]);
+                    next[t] += 1;
+                    received += 1;
+                }
+            }
+            assert_eq!(c.pop(), None);
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn drops() {
+    #[cfg(miri)]
+    const RUNS: usize = 5;
+    #[cfg(not(miri))]
+    const RUNS: usize = 100;
+
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let mut rng = thread_rng();
+
+    for _ in 0..RUNS {
+        let steps = rng.gen_range(0..10_000);
+        let additional = rng.gen_range(0..1000);
+
+        DROPS.store(0, Ordering::SeqCst);
+        let (p, mut c) = Queue::new().split();
+
+        scope(|scope| {
+            let p = p.clone();
+            scope.spawn(move |_| {
+                for _ in 0..steps {
+                    p.push(DropCounter);
+                }
+            });
+
+            scope.spawn(|_| {
+                for _ in 0..steps {
+                    while c.pop().is_none() {}
+                }
+            });
+        })
+        .unwrap();
+
+        for _ in 0..additional {
+            p.push(DropCounter);
+        }
+
+        assert_eq!(DROPS.load(Ordering::SeqCst), steps);
+        drop(p);
+        drop(c);
+        assert_eq!(DROPS.load(Ordering::SeqCst), steps + additional);
+    }
+}
+
+#[test]
+fn short_lived_producers() {
+    #[cfg(miri)]
+    const COUNT: usize = 20;
+    #[cfg(not(miri))]
+    const COUNT: usize = 1000;
+
+    let (p, mut c) = Queue::new().split();
+
+    // Every clone has a block of its own, which is freed once the clone is dropped and its values
+    // are popped, in either order.
+    for i in 0..COUNT {
+        let q = p.clone();
+        q.push(i);
+        if i % 2 == 0 {
+            drop(q);
+            assert_eq!(c.pop(), Some(i));
+        } else {
+            assert_eq!(c.pop(), Some(i));
+            drop(q);
+        }
+    }
+
+    for i in 0..COUNT {
+        p.clone().push(i);
+    }
+    for i in 0..COUNT {
+        assert_eq!(c.pop(), Some(i));
+    }
+    assert_eq!(c.pop(), None);
+}
@@ -0,0 +1,112 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crossbeam_queue::pool::{Pool, Pooled};
+use crossbeam_utils::thread::scope;
+
+#[test]
+fn smoke() {
+    let pool = Pool::new(|| 0);
+
+    let mut a = pool.get();
+    *a += 1;
+    drop(a);
+    assert_eq!(pool.len(), 1);
+
+    // The object comes back with its state.
+    assert_eq!(*pool.get(), 1);
+}
+
+#[test]
+fn try_get_put() {
+    let pool = Pool::new(|| 0);
+    assert!(pool.try_get().is_none());
+
+    // More objects than fit into the cache slot of this thread.
+    for i in 0..10 {
+        pool.put(i);
+    }
+    assert_eq!(pool.len(), 10);
+
+    let mut taken: Vec<i32> = (0..10)
+        .map(|_| Pooled::detach(pool.try_get().unwrap()))
+        .collect();
+    taken.sort();
+    assert_eq!(taken, (0..10).collect::<Vec<_>>());
+    assert!(pool.try_get().is_none());
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn detach() {
+    let pool = Pool::new(|| String::from("hello"));
+    let mut s = Pooled::detach(pool.get());
+    s.push_str(" world");
+    assert_eq!(s, "hello world");
+    assert!(pool.is_empty());
+}
+
+#[test]
+fn recycles() {
+    #[cfg(miri)]
+    const STEPS: usize = 50;
+    #[cfg(not(miri))]
+    const STEPS: usize = 10_000;
+    const THREADS: usize = 4;
+
+    let created = Arc::new(AtomicUsize::new(0));
+    let pool = {
+        let created = created.clone();
+        Pool::new(move || {
+            created.fetch_add(1, Ordering::SeqCst);
+            Vec::<usize>::new()
+        })
+    };
+
+    scope(|scope| {
+        for t in 0..THREADS {
+            let pool = &pool;
+            scope.spawn(move |_| {
+                for i in 0..STEPS {
+                    let mut v = pool.get();
+                    assert!(v.is_empty());
+                    v.push(t * STEPS + i);
+                    v.clear();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    // Every object is in use by at most one thread at a time, and is handed out again.
+    let created = created.load(Ordering::SeqCst);
+    assert!(created <= THREADS);
+    assert_eq!(pool.len(), created);
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let pool = Pool::new(|| DropCounter);
+    let objects: Vec<_> = (0..10).map(|_| pool.get()).collect();
+    assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+
+    drop(objects);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 0);
+    assert_eq!(pool.len(), 10);
+
+    drop(Pooled::detach(pool.get()));
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    drop(pool);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 10);
+}
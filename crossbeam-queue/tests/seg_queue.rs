@@ -161,3 +161,158 @@ fn drops() {
         assert_eq!(DROPS.load(Ordering::SeqCst), steps + additional);
     }
 }
+
+#[test]
+fn drain() {
+    let q = SegQueue::new();
+    assert_eq!(q.drain().next(), None);
+
+    for i in 0..100 {
+        q.push(i);
+    }
+
+    assert_eq!(
+        q.drain().take(10).collect::<Vec<_>>(),
+        (0..10).collect::<Vec<_>>()
+    );
+    assert_eq!(q.len(), 90);
+    assert_eq!(q.drain().collect::<Vec<_>>(), (10..100).collect::<Vec<_>>());
+    assert!(q.is_empty());
+}
+
+#[test]
+fn into_iter() {
+    let q = SegQueue::new();
+    for i in 0..100 {
+        q.push(i);
+    }
+
+    let mut iter = q.into_iter();
+    assert_eq!(iter.len(), 100);
+    assert_eq!(iter.next(), Some(0));
+    assert_eq!(iter.len(), 99);
+    assert_eq!(iter.collect::<Vec<_>>(), (1..100).collect::<Vec<_>>());
+}
+
+#[test]
+fn into_iter_drop() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    for (count, take) in [(0, 0), (10, 5), (100, 31), (100, 62), (100, 100)]
+        .iter()
+        .copied()
+    {
+        DROPS.store(0, Ordering::SeqCst);
+
+        let q = SegQueue::new();
+        for _ in 0..count {
+            q.push(DropCounter);
+        }
+
+        let mut iter = q.into_iter();
+        for _ in 0..take {
+            drop(iter.next().unwrap());
+        }
+        assert_eq!(DROPS.load(Ordering::SeqCst), take);
+
+        drop(iter);
+        assert_eq!(DROPS.load(Ordering::SeqCst), count);
+    }
+}
+
+#[test]
+fn segment_capacity() {
+    assert_eq!(SegQueue::<i32>::new().segment_capacity(), 31);
+
+    for (cap, rounded) in [(1, 1), (2, 3), (3, 3), (4, 7), (100, 127), (1024, 2047)]
+        .iter()
+        .copied()
+    {
+        let q = SegQueue::<i32>::with_segment_capacity(cap);
+        assert_eq!(q.segment_capacity(), rounded);
+    }
+}
+
+#[test]
+#[should_panic(expected = "capacity must be non-zero")]
+fn zero_segment_capacity() {
+    let _ = SegQueue::<i32>::with_segment_capacity(0);
+}
+
+#[test]
+fn custom_segments() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct DropCounter(usize);
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    for cap in [1, 2, 7, 100].iter().copied() {
+        DROPS.store(0, Ordering::SeqCst);
+        let q = SegQueue::with_segment_capacity(cap);
+
+        for i in 0..500 {
+            q.push(DropCounter(i));
+            assert_eq!(q.len(), i + 1);
+        }
+        for i in 0..200 {
+            assert_eq!(q.pop().map(|d| d.0), Some(i));
+        }
+        assert_eq!(q.len(), 300);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 200);
+
+        let mut iter = q.into_iter();
+        assert_eq!(iter.next().map(|d| d.0), Some(200));
+        drop(iter);
+        assert_eq!(DROPS.load(Ordering::SeqCst), 500);
+    }
+}
+
+#[test]
+fn mpmc_custom_segments() {
+    const COUNT: usize = 5_000;
+    const THREADS: usize = 4;
+
+    let q = SegQueue::<usize>::with_segment_capacity(3);
+    let v = (0..COUNT).map(|_| AtomicUsize::new(0)).collect::<Vec<_>>();
+
+    scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    let n = loop {
+                        if let Some(x) = q.pop() {
+                            break x;
+                        }
+                    };
+                    v[n].fetch_add(1, Ordering::SeqCst);
+                }
+            });
+        }
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..COUNT {
+                    q.push(i);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for c in v {
+        assert_eq!(c.load(Ordering::SeqCst), THREADS);
+    }
+}
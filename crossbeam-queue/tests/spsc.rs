@@ -0,0 +1,156 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crossbeam_queue::spsc::RingBuffer;
+use crossbeam_utils::thread::scope;
+use rand::{thread_rng, Rng};
+
+#[test]
+fn smoke() {
+    let (mut p, mut c) = RingBuffer::new(1).split();
+
+    p.push(7).unwrap();
+    assert_eq!(c.pop(), Some(7));
+
+    p.push(8).unwrap();
+    assert_eq!(c.pop(), Some(8));
+    assert!(c.pop().is_none());
+}
+
+#[test]
+fn capacity() {
+    for i in 1..10 {
+        let rb = RingBuffer::<i32>::new(i);
+        assert_eq!(rb.capacity(), i);
+
+        let (p, c) = rb.split();
+        assert_eq!(p.capacity(), i);
+        assert_eq!(c.capacity(), i);
+    }
+}
+
+#[test]
+#[should_panic(expected = "capacity must be non-zero")]
+fn zero_capacity() {
+    let _ = RingBuffer::<i32>::new(0);
+}
+
+#[test]
+fn len_empty_full() {
+    let (mut p, mut c) = RingBuffer::new(2).split();
+
+    assert_eq!(p.len(), 0);
+    assert!(c.is_empty());
+    assert!(!p.is_full());
+
+    p.push(()).unwrap();
+    p.push(()).unwrap();
+
+    assert_eq!(c.len(), 2);
+    assert!(!p.is_empty());
+    assert!(c.is_full());
+    assert_eq!(p.push(()), Err(()));
+
+    c.pop().unwrap();
+
+    assert_eq!(p.len(), 1);
+    assert!(!c.is_full());
+}
+
+#[test]
+fn wrap_around() {
+    let (mut p, mut c) = RingBuffer::new(3).split();
+
+    for i in 0..100 {
+        p.push(i).unwrap();
+        p.push(i + 1).unwrap();
+        assert_eq!(c.peek(), Some(&i));
+        assert_eq!(c.pop(), Some(i));
+        assert_eq!(c.pop(), Some(i + 1));
+        assert_eq!(c.peek(), None);
+    }
+}
+
+#[test]
+fn spsc() {
+    const COUNT: usize = 100_000;
+
+    let (mut p, mut c) = RingBuffer::new(3).split();
+
+    scope(|scope| {
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                loop {
+                    if let Some(x) = c.pop() {
+                        assert_eq!(x, i);
+                        break;
+                    }
+                    std::thread::yield_now();
+                }
+            }
+            assert!(c.pop().is_none());
+        });
+
+        scope.spawn(move |_| {
+            for i in 0..COUNT {
+                while p.push(i).is_err() {
+                    std::thread::yield_now();
+                }
+            }
+        });
+    })
+    .unwrap();
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Debug, PartialEq)]
+    struct DropCounter;
+
+    impl Drop for DropCounter {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let mut rng = thread_rng();
+
+    for _ in 0..100 {
+        let steps = rng.gen_range(0..10_000);
+        let additional = rng.gen_range(0..50);
+
+        DROPS.store(0, Ordering::SeqCst);
+        let (mut p, mut c) = RingBuffer::new(50).split();
+
+        scope(|scope| {
+            scope.spawn(|_| {
+                for _ in 0..steps {
+                    while c.pop().is_none() {
+                        std::thread::yield_now();
+                    }
+                }
+            });
+
+            scope.spawn(|_| {
+                for _ in 0..steps {
+                    while p.push(DropCounter).is_err() {
+                        DROPS.fetch_sub(1, Ordering::SeqCst);
+                        std::thread::yield_now();
+                    }
+                }
+            });
+        })
+        .unwrap();
+
+        for _ in 0..additional {
+            p.push(DropCounter).unwrap();
+        }
+
+        assert_eq!(DROPS.load(Ordering::SeqCst), steps);
+        drop(p);
+        assert_eq!(DROPS.load(Ordering::SeqCst), steps);
+        drop(c);
+        assert_eq!(DROPS.load(Ordering::SeqCst), steps + additional);
+    }
+}
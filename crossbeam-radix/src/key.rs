@@ -0,0 +1,81 @@
+use std::mem;
+
+/// A key that can be stored in a [`RadixMap`].
+///
+/// A key is turned into an unsigned integer of [`BITS`] bits, which the map splits into 4-bit
+/// digits. The conversion must preserve order: if `a < b`, then `a.to_bits() < b.to_bits()`, and
+/// equal keys must have equal bits. Otherwise lookups and iteration return entries in an
+/// unspecified order, but the map stays memory safe.
+///
+/// This trait is implemented for all primitive integer types. Signed integers flip their sign
+/// bit, so that negative numbers come before positive ones.
+///
+/// [`RadixMap`]: crate::RadixMap
+/// [`BITS`]: Key::BITS
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_radix::{Key, RadixMap};
+///
+/// #[derive(Clone, Copy, Debug, PartialEq)]
+/// struct ConnectionId(u32);
+///
+/// impl Key for ConnectionId {
+///     const BITS: u32 = 32;
+///
+///     fn to_bits(&self) -> u64 {
+///         u64::from(self.0)
+///     }
+/// }
+///
+/// let map = RadixMap::new();
+/// let guard = &map.guard();
+/// map.insert(ConnectionId(7), "open", guard);
+/// assert_eq!(map.get(&ConnectionId(7), guard), Some(&"open"));
+/// ```
+pub trait Key {
+    /// The number of bits in the key.
+    ///
+    /// This must be a positive multiple of 4 and at most 64. Only the lowest `BITS` bits of
+    /// [`to_bits`] may be set.
+    ///
+    /// [`to_bits`]: Key::to_bits
+    const BITS: u32;
+
+    /// Returns the bits of the key, in the same order as the keys themselves.
+    fn to_bits(&self) -> u64;
+}
+
+macro_rules! impl_unsigned {
+    ($($t:ty),*) => {
+        $(
+            impl Key for $t {
+                const BITS: u32 = (mem::size_of::<$t>() * 8) as u32;
+
+                #[inline]
+                fn to_bits(&self) -> u64 {
+                    *self as u64
+                }
+            }
+        )*
+    };
+}
+
+macro_rules! impl_signed {
+    ($($t:ty => $u:ty),*) => {
+        $(
+            impl Key for $t {
+                const BITS: u32 = (mem::size_of::<$t>() * 8) as u32;
+
+                #[inline]
+                fn to_bits(&self) -> u64 {
+                    (*self as $u ^ (1 << (<$t as Key>::BITS - 1))) as u64
+                }
+            }
+        )*
+    };
+}
+
+impl_unsigned!(u8, u16, u32, u64, usize);
+impl_signed!(i8 => u8, i16 => u16, i32 => u32, i64 => u64, isize => usize);
@@ -0,0 +1,75 @@
+//! A concurrent ordered map for integer keys.
+//!
+//! This crate provides [`RadixMap`], a map whose keys are integers, or types that can be turned
+//! into integers while preserving their order. It supports safe concurrent access across
+//! multiple threads, iterates over its entries in key order, and can look up ranges of keys.
+//!
+//! # Radix trees
+//!
+//! The map is a radix tree: a key is split into 4-bit digits, from the most significant to the
+//! least significant one, and every digit selects one of 16 children of a node. A lookup
+//! therefore takes as many steps as the key has digits, no matter how many entries the map
+//! holds. Keys that share a prefix share the nodes along it, which makes the map compact for
+//! dense keys such as connection IDs or sequence numbers, where a skip list would spend a tower
+//! of pointers on every entry.
+//!
+//! # Concurrent access
+//!
+//! [`RadixMap`] implements [`Send`] and [`Sync`], and methods which mutate the map take `&self`.
+//! Lookups and iteration are lock-free. Insertions and removals lock only the node that holds
+//! the entry, and occasionally its parent when a node has to be added or unlinked.
+//!
+//! ```
+//! use crossbeam_radix::RadixMap;
+//! use crossbeam_utils::thread::scope;
+//!
+//! let connections = RadixMap::new();
+//!
+//! scope(|s| {
+//!     for t in 0..4u32 {
+//!         let connections = &connections;
+//!         s.spawn(move |_| {
+//!             let guard = &connections.guard();
+//!             for id in (t * 100)..(t + 1) * 100 {
+//!                 connections.insert(id, format!("connection {}", id), guard);
+//!             }
+//!         });
+//!     }
+//! }).unwrap();
+//!
+//! let guard = &connections.guard();
+//! assert_eq!(connections.len(), 400);
+//! assert_eq!(connections.range(150..153, guard).count(), 3);
+//! ```
+//!
+//! # Garbage collection
+//!
+//! Like the other concurrent maps in Crossbeam, the map uses the _epoch-based memory
+//! reclamation_ mechanism implemented in [`crossbeam-epoch`]: removed entries and unlinked nodes
+//! are only destroyed once no thread can observe them anymore. Every operation takes a
+//! [`Guard`], which can be obtained with [`RadixMap::guard`], and references returned by the map
+//! borrow from it.
+//!
+//! [`Guard`]: crossbeam_epoch::Guard
+//! [`crossbeam-epoch`]: https://docs.rs/crossbeam-epoch
+
+#![doc(test(
+    no_crate_inject,
+    attr(
+        deny(warnings, rust_2018_idioms),
+        allow(dead_code, unused_assignments, unused_variables)
+    )
+))]
+#![warn(
+    missing_docs,
+    missing_debug_implementations,
+    rust_2018_idioms,
+    unreachable_pub
+)]
+
+mod key;
+pub use crate::key::Key;
+
+pub mod map;
+#[doc(inline)]
+pub use crate::map::RadixMap;
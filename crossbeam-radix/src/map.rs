@@ -0,0 +1,832 @@
+//! A concurrent ordered map for integer keys. See [`RadixMap`].
+
+use std::fmt;
+use std::iter::FromIterator;
+use std::ops::{Bound, RangeBounds};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+
+use crossbeam_epoch::{self as epoch, Atomic, Collector, Guard, Owned, Shared};
+
+use crate::key::Key;
+
+/// The number of bits in a digit of a key.
+const DIGIT_BITS: u32 = 4;
+
+/// The number of children of a node.
+const FANOUT: usize = 1 << DIGIT_BITS;
+
+/// The maximum number of levels in a tree, which is reached with 64-bit keys.
+const MAX_LEVELS: usize = 64 / DIGIT_BITS as usize;
+
+/// A concurrent ordered map for integer keys.
+///
+/// The map is a radix tree with 16 children per node. Every level of the tree consumes one 4-bit
+/// digit of the key, starting with the most significant one, and the nodes of the last level
+/// point to the entries. Nodes are created on demand, and unlinked as soon as their last child
+/// is removed.
+///
+/// Lookups and iteration never block. Insertions and removals lock the node of the last level
+/// that holds the entry, so writers only contend when their keys differ in the last digit alone.
+///
+/// Removed entries and unlinked nodes are freed through [`crossbeam-epoch`], so readers can walk
+/// the tree without locking it. Operations therefore take a [`Guard`], obtained with [`guard`],
+/// and the values they return borrow from it.
+///
+/// [`crossbeam-epoch`]: https://docs.rs/crossbeam-epoch
+///
+/// [`guard`]: RadixMap::guard
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_radix::RadixMap;
+///
+/// let orders = RadixMap::new();
+/// let guard = &orders.guard();
+///
+/// orders.insert(1003u64, "sell", guard);
+/// orders.insert(1001u64, "buy", guard);
+/// orders.insert(1002u64, "cancel", guard);
+///
+/// assert_eq!(orders.get(&1002, guard), Some(&"cancel"));
+///
+/// // Entries are visited in key order.
+/// let ids: Vec<u64> = orders.iter(guard).map(|(k, _)| *k).collect();
+/// assert_eq!(ids, [1001, 1002, 1003]);
+/// ```
+pub struct RadixMap<K, V> {
+    /// The root of the tree, which is never unlinked.
+    root: Box<Node<K, V>>,
+
+    /// The number of entries in the map.
+    len: AtomicUsize,
+
+    /// The collector that destroys removed entries and unlinked nodes.
+    collector: Collector,
+}
+
+/// A node in the tree.
+struct Node<K, V> {
+    /// Held while modifying the children of the node. Counts the children that aren't null.
+    live: Mutex<usize>,
+
+    /// Set once the node has been unlinked from its parent, while holding `live`.
+    ///
+    /// Writers check this after locking a node, and start over if it is set, since whatever
+    /// they would write into the node would be lost.
+    obsolete: AtomicBool,
+
+    /// The children of the node.
+    children: Children<K, V>,
+}
+
+/// The children of a node, which are nodes on all levels but the last one.
+enum Children<K, V> {
+    Inner([Atomic<Node<K, V>>; FANOUT]),
+    Leaves([Atomic<Leaf<K, V>>; FANOUT]),
+}
+
+/// An entry of the map.
+struct Leaf<K, V> {
+    key: K,
+    value: V,
+}
+
+impl<K, V> Node<K, V> {
+    /// Creates an empty node, which holds entries if `last` is `true`.
+    fn new(last: bool) -> Node<K, V> {
+        Node {
+            live: Mutex::new(0),
+            obsolete: AtomicBool::new(false),
+            children: if last {
+                Children::Leaves(Default::default())
+            } else {
+                Children::Inner(Default::default())
+            },
+        }
+    }
+
+    /// Locks the node for modification.
+    fn lock(&self) -> MutexGuard<'_, usize> {
+        // The node is only modified once nothing can panic anymore, so it is always consistent,
+        // even if a thread panicked while holding the lock.
+        self.live.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Returns `true` if the node has been unlinked from the tree.
+    fn is_obsolete(&self) -> bool {
+        self.obsolete.load(Ordering::Relaxed)
+    }
+
+    /// Returns the child nodes of a node that isn't on the last level.
+    fn inner(&self) -> &[Atomic<Node<K, V>>; FANOUT] {
+        match &self.children {
+            Children::Inner(c) => c,
+            Children::Leaves(_) => unreachable!(),
+        }
+    }
+}
+
+impl<K: Key, V> RadixMap<K, V> {
+    /// Creates a new map.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `K::BITS` isn't a positive multiple of 4 that is at most 64.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_radix::RadixMap;
+    ///
+    /// let map: RadixMap<u32, &str> = RadixMap::new();
+    /// ```
+    pub fn new() -> RadixMap<K, V> {
+        assert!(
+            K::BITS > 0 && K::BITS <= 64 && K::BITS % DIGIT_BITS == 0,
+            "key bits must be a positive multiple of 4 that is at most 64"
+        );
+
+        RadixMap {
+            root: Box::new(Node::new(K::BITS == DIGIT_BITS)),
+            len: AtomicUsize::new(0),
+            collector: epoch::default_collector().clone(),
+        }
+    }
+
+    /// Returns the number of levels in the tree.
+    fn levels(&self) -> usize {
+        (K::BITS / DIGIT_BITS) as usize
+    }
+
+    /// Returns the digit of `bits` that selects a child on `level`.
+    fn digit(&self, bits: u64, level: usize) -> usize {
+        let shift = K::BITS - DIGIT_BITS * (level as u32 + 1);
+        (bits >> shift) as usize & (FANOUT - 1)
+    }
+
+    /// Returns the bits of the greatest possible key.
+    fn max_bits(&self) -> u64 {
+        u64::max_value() >> (64 - K::BITS)
+    }
+
+    /// Pins the current thread and returns a guard for operations on the map.
+    ///
+    /// While the guard is alive, no leaf or node that it was used to reach is freed, even if other
+    /// threads remove the entry or unlink the node from the tree. Every removal keeps its garbage
+    /// around until all guards that might have seen it are dropped, so a guard is best held for a
+    /// batch of operations rather than for the lifetime of a thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_radix::RadixMap;
+    ///
+    /// let map = RadixMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// map.insert(0x2a_u16, "answer", guard);
+    /// let answer = map.get(&0x2a, guard).unwrap();
+    ///
+    /// // Removing the only entry unlinks every node on its path, but nothing is freed while the
+    /// // guard is alive.
+    /// map.remove(&0x2a, guard);
+    /// assert!(map.is_empty());
+    /// assert_eq!(*answer, "answer");
+    /// ```
+    pub fn guard(&self) -> Guard {
+        let guard = epoch::pin();
+        if guard.collector() == Some(&self.collector) {
+            guard
+        } else {
+            self.collector.register().pin()
+        }
+    }
+
+    /// Returns the number of entries in the map.
+    ///
+    /// The count is kept in a counter next to the tree, which writers update after linking or
+    /// unlinking a leaf. While other threads insert or remove entries, it may briefly differ from
+    /// what iterating over the tree would find.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_radix::RadixMap;
+    ///
+    /// let map = RadixMap::new();
+    /// let guard = &map.guard();
+    /// assert_eq!(map.len(), 0);
+    ///
+    /// // Replacing the leaf of an existing key doesn't change the count.
+    /// map.insert(0x10u32, "a", guard);
+    /// map.insert(0x10u32, "b", guard);
+    /// map.insert(0x11u32, "c", guard);
+    /// assert_eq!(map.len(), 2);
+    /// ```
+    pub fn len(&self) -> usize {
+        self.len.load(Ordering::Relaxed)
+    }
+
+    /// Returns `true` if the map has no entries.
+    ///
+    /// Like [`len`], this reads the entry counter instead of looking at the root node.
+    ///
+    /// [`len`]: RadixMap::len
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_radix::RadixMap;
+    ///
+    /// let map = RadixMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// map.insert(0xbeefu16, (), guard);
+    /// assert!(!map.is_empty());
+    ///
+    /// map.remove(&0xbeef, guard);
+    /// assert!(map.is_empty());
+    /// ```
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Looks up the value stored under the key.
+    ///
+    /// The lookup descends at most one level per digit of the key without taking any locks, so its
+    /// cost depends on `K::BITS` rather than on the number of entries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_radix::RadixMap;
+    ///
+    /// let map = RadixMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// map.insert(-5i32, "minus five", guard);
+    /// assert_eq!(map.get(&-5, guard), Some(&"minus five"));
+    /// assert_eq!(map.get(&5, guard), None);
+    /// ```
+    pub fn get<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
+        self.check_guard(guard);
+        self.find(key.to_bits(), guard).map(|leaf| &leaf.value)
+    }
+
+    /// Returns `true` if a leaf is stored under the key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_radix::RadixMap;
+    ///
+    /// let map = RadixMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// // 0x13 shares the whole path with 0x12, but there is no leaf under it.
+    /// map.insert(0x12u8, 10, guard);
+    /// assert!(map.contains_key(&0x12, guard));
+    /// assert!(!map.contains_key(&0x13, guard));
+    /// ```
+    pub fn contains_key(&self, key: &K, guard: &Guard) -> bool {
+        self.check_guard(guard);
+        self.find(key.to_bits(), guard).is_some()
+    }
+
+    /// Returns the entry with the smallest key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_radix::RadixMap;
+    ///
+    /// let map = RadixMap::new();
+    /// let guard = &map.guard();
+    /// assert_eq!(map.first(guard), None);
+    ///
+    /// map.insert(20, "b", guard);
+    /// map.insert(10, "a", guard);
+    /// assert_eq!(map.first(guard), Some((&10, &"a")));
+    /// ```
+    pub fn first<'g>(&'g self, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        self.check_guard(guard);
+        self.seek(0, guard).map(|leaf| (&leaf.key, &leaf.value))
+    }
+
+    /// Returns the entry with the greatest key.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_radix::RadixMap;
+    ///
+    /// let map = RadixMap::new();
+    /// let guard = &map.guard();
+    /// assert_eq!(map.last(guard), None);
+    ///
+    /// map.insert(20, "b", guard);
+    /// map.insert(10, "a", guard);
+    /// assert_eq!(map.last(guard), Some((&20, &"b")));
+    /// ```
+    pub fn last<'g>(&'g self, guard: &'g Guard) -> Option<(&'g K, &'g V)> {
+        self.check_guard(guard);
+        self.seek_back(self.max_bits(), guard)
+            .map(|leaf| (&leaf.key, &leaf.value))
+    }
+
+    /// Returns an iterator over all entries of the map, in key order.
+    ///
+    /// Entries inserted or removed during iteration may or may not be visited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_radix::RadixMap;
+    ///
+    /// let map = RadixMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// map.insert(3u16, "c", guard);
+    /// map.insert(1u16, "a", guard);
+    /// map.insert(2u16, "b", guard);
+    ///
+    /// let values: Vec<_> = map.iter(guard).map(|(_, v)| *v).collect();
+    /// assert_eq!(values, ["a", "b", "c"]);
+    /// ```
+    pub fn iter<'g>(&'g self, guard: &'g Guard) -> Iter<'g, K, V> {
+        self.range(.., guard)
+    }
+
+    /// Returns an iterator over the entries whose keys fall into `range`, in key order.
+    ///
+    /// Entries inserted or removed during iteration may or may not be visited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_radix::RadixMap;
+    ///
+    /// let map = RadixMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// for i in 0..100u32 {
+    ///     map.insert(i, i * 10, guard);
+    /// }
+    ///
+    /// let keys: Vec<u32> = map.range(10..13, guard).map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [10, 11, 12]);
+    ///
+    /// let keys: Vec<u32> = map.range(..=2, guard).rev().map(|(k, _)| *k).collect();
+    /// assert_eq!(keys, [2, 1, 0]);
+    /// ```
+    pub fn range<'g, R>(&'g self, range: R, guard: &'g Guard) -> Iter<'g, K, V>
+    where
+        R: RangeBounds<K>,
+    {
+        self.check_guard(guard);
+
+        let front = match range.start_bound() {
+            Bound::Included(k) => Some(k.to_bits()),
+            Bound::Excluded(k) => k.to_bits().checked_add(1),
+            Bound::Unbounded => Some(0),
+        };
+        let back = match range.end_bound() {
+            Bound::Included(k) => Some(k.to_bits()),
+            Bound::Excluded(k) => k.to_bits().checked_sub(1),
+            Bound::Unbounded => Some(self.max_bits()),
+        };
+
+        Iter {
+            map: self,
+            bounds: match (front, back) {
+                (Some(front), Some(back)) if front <= back => Some((front, back)),
+                _ => None,
+            },
+            guard,
+        }
+    }
+
+    /// Returns the entry with the given bits.
+    fn find<'g>(&'g self, bits: u64, guard: &'g Guard) -> Option<&'g Leaf<K, V>> {
+        let mut node = &*self.root;
+        let mut level = 0;
+
+        loop {
+            let digit = self.digit(bits, level);
+            match &node.children {
+                Children::Inner(c) => {
+                    node = unsafe { c[digit].load(Ordering::Acquire, guard).as_ref() }?;
+                    level += 1;
+                }
+                Children::Leaves(l) => {
+                    return unsafe { l[digit].load(Ordering::Acquire, guard).as_ref() };
+                }
+            }
+        }
+    }
+
+    /// Returns the entry with the smallest bits that are greater than or equal to `bits`.
+    fn seek<'g>(&'g self, bits: u64, guard: &'g Guard) -> Option<&'g Leaf<K, V>> {
+        self.seek_in(&self.root, 0, bits, true, guard)
+    }
+
+    /// Searches the subtree of `node` for the entry with the smallest bits that are greater than
+    /// or equal to `bits`.
+    ///
+    /// If `bounded` is `false`, the subtree lies entirely above `bits`, so its first entry is
+    /// returned.
+    fn seek_in<'g>(
+        &'g self,
+        node: &'g Node<K, V>,
+        level: usize,
+        bits: u64,
+        bounded: bool,
+        guard: &'g Guard,
+    ) -> Option<&'g Leaf<K, V>> {
+        let start = if bounded { self.digit(bits, level) } else { 0 };
+
+        for digit in start..FANOUT {
+            let bounded = bounded && digit == start;
+            match &node.children {
+                Children::Inner(c) => {
+                    if let Some(child) = unsafe { c[digit].load(Ordering::Acquire, guard).as_ref() }
+                    {
+                        if let Some(leaf) = self.seek_in(child, level + 1, bits, bounded, guard) {
+                            return Some(leaf);
+                        }
+                    }
+                }
+                Children::Leaves(l) => {
+                    if let Some(leaf) = unsafe { l[digit].load(Ordering::Acquire, guard).as_ref() }
+                    {
+                        return Some(leaf);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Returns the entry with the greatest bits that are less than or equal to `bits`.
+    fn seek_back<'g>(&'g self, bits: u64, guard: &'g Guard) -> Option<&'g Leaf<K, V>> {
+        self.seek_back_in(&self.root, 0, bits, true, guard)
+    }
+
+    /// Searches the subtree of `node` for the entry with the greatest bits that are less than or
+    /// equal to `bits`.
+    ///
+    /// If `bounded` is `false`, the subtree lies entirely below `bits`, so its last entry is
+    /// returned.
+    fn seek_back_in<'g>(
+        &'g self,
+        node: &'g Node<K, V>,
+        level: usize,
+        bits: u64,
+        bounded: bool,
+        guard: &'g Guard,
+    ) -> Option<&'g Leaf<K, V>> {
+        let end = if bounded {
+            self.digit(bits, level)
+        } else {
+            FANOUT - 1
+        };
+
+        for digit in (0..=end).rev() {
+            let bounded = bounded && digit == end;
+            match &node.children {
+                Children::Inner(c) => {
+                    if let Some(child) = unsafe { c[digit].load(Ordering::Acquire, guard).as_ref() }
+                    {
+                        if let Some(leaf) =
+                            self.seek_back_in(child, level + 1, bits, bounded, guard)
+                        {
+                            return Some(leaf);
+                        }
+                    }
+                }
+                Children::Leaves(l) => {
+                    if let Some(leaf) = unsafe { l[digit].load(Ordering::Acquire, guard).as_ref() }
+                    {
+                        return Some(leaf);
+                    }
+                }
+            }
+        }
+        None
+    }
+
+    /// Panics if `guard` doesn't belong to the collector of the map.
+    fn check_guard(&self, guard: &Guard) {
+        if let Some(c) = guard.collector() {
+            assert!(c == &self.collector);
+        }
+    }
+}
+
+impl<K, V> RadixMap<K, V>
+where
+    K: Key + Send + 'static,
+    V: Send + 'static,
+{
+    /// Stores a value under the key, creating the nodes on the path to it as needed.
+    ///
+    /// Only the last-level node that holds the leaf is locked while the leaf is stored. If a leaf
+    /// with this key already exists, the new one is swapped in and a reference to the old value is
+    /// returned, which `guard` keeps alive.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_radix::RadixMap;
+    ///
+    /// let map = RadixMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// assert_eq!(map.insert(0x0c0ffeeu32, "brewing", guard), None);
+    /// assert_eq!(map.insert(0x0c0ffeeu32, "ready", guard), Some(&"brewing"));
+    /// assert_eq!(map.get(&0x0c0ffee, guard), Some(&"ready"));
+    /// ```
+    pub fn insert<'g>(&'g self, key: K, value: V, guard: &'g Guard) -> Option<&'g V> {
+        self.check_guard(guard);
+
+        let bits = key.to_bits();
+        let leaf = Owned::new(Leaf { key, value });
+
+        'restart: loop {
+            let mut node = &*self.root;
+            let mut level = 0;
+
+            loop {
+                let digit = self.digit(bits, level);
+                match &node.children {
+                    Children::Inner(c) => {
+                        let mut child = c[digit].load(Ordering::Acquire, guard);
+                        if child.is_null() {
+                            let mut live = node.lock();
+                            if node.is_obsolete() {
+                                continue 'restart;
+                            }
+
+                            child = c[digit].load(Ordering::Relaxed, guard);
+                            if child.is_null() {
+                                let last = level + 2 == self.levels();
+                                child = Owned::new(Node::new(last)).into_shared(guard);
+                                c[digit].store(child, Ordering::Release);
+                                *live += 1;
+                            }
+                        }
+
+                        node = unsafe { child.deref() };
+                        level += 1;
+                    }
+                    Children::Leaves(l) => {
+                        let mut live = node.lock();
+                        if node.is_obsolete() {
+                            continue 'restart;
+                        }
+
+                        let old = l[digit].swap(leaf, Ordering::AcqRel, guard);
+                        if old.is_null() {
+                            *live += 1;
+                            self.len.fetch_add(1, Ordering::Relaxed);
+                            return None;
+                        }
+
+                        unsafe {
+                            guard.defer_destroy(old);
+                            return Some(&old.deref().value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Unlinks the leaf stored under the key, returning a reference to its value.
+    ///
+    /// If the leaf was the last child of its node, the node is unlinked as well, along with every
+    /// ancestor that it leaves empty. The value and the unlinked nodes are kept alive by `guard`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_radix::RadixMap;
+    ///
+    /// let map = RadixMap::new();
+    /// let guard = &map.guard();
+    ///
+    /// map.insert(0x100u16, "a", guard);
+    /// map.insert(0x101u16, "b", guard);
+    /// assert_eq!(map.remove(&0x100, guard), Some(&"a"));
+    /// assert_eq!(map.remove(&0x100, guard), None);
+    /// assert_eq!(map.get(&0x101, guard), Some(&"b"));
+    /// ```
+    pub fn remove<'g>(&'g self, key: &K, guard: &'g Guard) -> Option<&'g V> {
+        self.check_guard(guard);
+
+        let bits = key.to_bits();
+
+        'restart: loop {
+            // The nodes visited on the way down, so that empty ones can be unlinked afterwards.
+            let mut path: [Option<&'g Node<K, V>>; MAX_LEVELS] = [None; MAX_LEVELS];
+            let mut node = &*self.root;
+            let mut level = 0;
+
+            loop {
+                path[level] = Some(node);
+                let digit = self.digit(bits, level);
+                match &node.children {
+                    Children::Inner(c) => {
+                        node = unsafe { c[digit].load(Ordering::Acquire, guard).as_ref() }?;
+                        level += 1;
+                    }
+                    Children::Leaves(l) => {
+                        let mut live = node.lock();
+                        if node.is_obsolete() {
+                            continue 'restart;
+                        }
+
+                        let old = l[digit].load(Ordering::Relaxed, guard);
+                        if old.is_null() {
+                            return None;
+                        }
+                        l[digit].store(Shared::null(), Ordering::Release);
+                        *live -= 1;
+                        self.len.fetch_sub(1, Ordering::Relaxed);
+
+                        let empty = *live == 0;
+                        drop(live);
+                        if empty {
+                            self.prune(&path[..=level], bits, guard);
+                        }
+
+                        unsafe {
+                            guard.defer_destroy(old);
+                            return Some(&old.deref().value);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Unlinks empty nodes at the end of `path`, walking up towards the root.
+    fn prune(&self, path: &[Option<&Node<K, V>>], bits: u64, guard: &Guard) {
+        for level in (1..path.len()).rev() {
+            let parent = path[level - 1].unwrap();
+            let node = path[level].unwrap();
+            let slot = &parent.inner()[self.digit(bits, level - 1)];
+
+            // Always lock the parent first, like writers descending the tree do.
+            let mut parent_live = parent.lock();
+            let live = node.lock();
+            if parent.is_obsolete() || node.is_obsolete() || *live != 0 {
+                return;
+            }
+
+            // Nothing can be added to the node once it's obsolete.
+            node.obsolete.store(true, Ordering::Relaxed);
+            let shared = slot.swap(Shared::null(), Ordering::AcqRel, guard);
+            debug_assert_eq!(shared.as_raw(), node as *const Node<K, V>);
+            *parent_live -= 1;
+
+            let empty = *parent_live == 0;
+            drop(live);
+            drop(parent_live);
+            unsafe { guard.defer_destroy(shared) };
+
+            if !empty {
+                return;
+            }
+        }
+    }
+}
+
+impl<K, V> Drop for RadixMap<K, V> {
+    fn drop(&mut self) {
+        /// Destroys the children of `node`.
+        unsafe fn destroy_children<K, V>(node: &Node<K, V>, guard: &Guard) {
+            match &node.children {
+                Children::Inner(c) => {
+                    for child in c.iter() {
+                        let child = child.load(Ordering::Relaxed, guard);
+                        if let Some(n) = child.as_ref() {
+                            destroy_children(n, guard);
+                            drop(child.into_owned());
+                        }
+                    }
+                }
+                Children::Leaves(l) => {
+                    for leaf in l.iter() {
+                        let leaf = leaf.load(Ordering::Relaxed, guard);
+                        if !leaf.is_null() {
+                            drop(leaf.into_owned());
+                        }
+                    }
+                }
+            }
+        }
+
+        unsafe { destroy_children(&self.root, epoch::unprotected()) }
+    }
+}
+
+impl<K: Key, V> Default for RadixMap<K, V> {
+    fn default() -> RadixMap<K, V> {
+        RadixMap::new()
+    }
+}
+
+impl<K, V> fmt::Debug for RadixMap<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("RadixMap { .. }")
+    }
+}
+
+impl<K, V> FromIterator<(K, V)> for RadixMap<K, V>
+where
+    K: Key + Send + 'static,
+    V: Send + 'static,
+{
+    fn from_iter<I>(iter: I) -> RadixMap<K, V>
+    where
+        I: IntoIterator<Item = (K, V)>,
+    {
+        let map = RadixMap::new();
+        {
+            let guard = &map.guard();
+            for (k, v) in iter {
+                map.insert(k, v, guard);
+            }
+        }
+        map
+    }
+}
+
+/// An iterator over the entries of a `RadixMap`, in key order.
+///
+/// This struct is created by the [`iter`] and [`range`] methods on [`RadixMap`].
+///
+/// [`iter`]: RadixMap::iter
+/// [`range`]: RadixMap::range
+pub struct Iter<'g, K, V> {
+    map: &'g RadixMap<K, V>,
+
+    /// The bits of the smallest and greatest keys that remain to be visited, or `None` if the
+    /// iterator is exhausted.
+    bounds: Option<(u64, u64)>,
+
+    guard: &'g Guard,
+}
+
+impl<'g, K: Key, V> Iterator for Iter<'g, K, V> {
+    type Item = (&'g K, &'g V);
+
+    fn next(&mut self) -> Option<(&'g K, &'g V)> {
+        let (front, back) = self.bounds?;
+        let leaf = match self.map.seek(front, self.guard) {
+            Some(leaf) if leaf.key.to_bits() <= back => leaf,
+            _ => {
+                self.bounds = None;
+                return None;
+            }
+        };
+
+        let bits = leaf.key.to_bits();
+        self.bounds = if bits < back {
+            Some((bits + 1, back))
+        } else {
+            None
+        };
+        Some((&leaf.key, &leaf.value))
+    }
+}
+
+impl<'g, K: Key, V> DoubleEndedIterator for Iter<'g, K, V> {
+    fn next_back(&mut self) -> Option<(&'g K, &'g V)> {
+        let (front, back) = self.bounds?;
+        let leaf = match self.map.seek_back(back, self.guard) {
+            Some(leaf) if leaf.key.to_bits() >= front => leaf,
+            _ => {
+                self.bounds = None;
+                return None;
+            }
+        };
+
+        let bits = leaf.key.to_bits();
+        self.bounds = if bits > front {
+            Some((front, bits - 1))
+        } else {
+            None
+        };
+        Some((&leaf.key, &leaf.value))
+    }
+}
+
+impl<K, V> fmt::Debug for Iter<'_, K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Iter { .. }")
+    }
+}
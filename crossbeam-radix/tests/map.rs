@@ -0,0 +1,218 @@
+use std::ops::Bound::{Excluded, Included};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crossbeam_radix::RadixMap;
+use crossbeam_utils::thread;
+
+#[test]
+fn smoke() {
+    let m = RadixMap::new();
+    let guard = &m.guard();
+    m.insert(1u64, 10, guard);
+    m.insert(5u64, 50, guard);
+    m.insert(7u64, 70, guard);
+}
+
+#[test]
+fn insert() {
+    let m = RadixMap::new();
+    let guard = &m.guard();
+
+    assert_eq!(m.insert(1u32, 10, guard), None);
+    assert_eq!(m.insert(2u32, 20, guard), None);
+    assert_eq!(m.insert(1u32, 11, guard), Some(&10));
+    assert_eq!(m.insert(1u32, 12, guard), Some(&11));
+
+    assert_eq!(m.get(&1, guard), Some(&12));
+    assert_eq!(m.get(&2, guard), Some(&20));
+    assert_eq!(m.get(&3, guard), None);
+    assert_eq!(m.len(), 2);
+}
+
+#[test]
+fn remove() {
+    let m = RadixMap::new();
+    let guard = &m.guard();
+
+    for x in 0..1000u64 {
+        m.insert(x * 7919, x, guard);
+    }
+    for x in (0..1000u64).filter(|x| x % 3 == 0) {
+        assert_eq!(m.remove(&(x * 7919), guard), Some(&x));
+        assert_eq!(m.remove(&(x * 7919), guard), None);
+    }
+    for x in 0..1000u64 {
+        assert_eq!(m.contains_key(&(x * 7919), guard), x % 3 != 0);
+    }
+    assert_eq!(m.len(), 666);
+
+    // Removing everything unlinks all nodes but the root, and the map keeps working.
+    for x in 0..1000u64 {
+        m.remove(&(x * 7919), guard);
+    }
+    assert!(m.is_empty());
+    assert_eq!(m.first(guard), None);
+    m.insert(u64::max_value(), 1, guard);
+    assert_eq!(m.get(&u64::max_value(), guard), Some(&1));
+}
+
+#[test]
+fn key_types() {
+    let m = RadixMap::new();
+    let guard = &m.guard();
+    for x in -100i8..100 {
+        m.insert(x, x, guard);
+    }
+    let keys: Vec<i8> = m.iter(guard).map(|(k, _)| *k).collect();
+    assert_eq!(keys, (-100i8..100).collect::<Vec<_>>());
+
+    let m = RadixMap::new();
+    let guard = &m.guard();
+    for &x in &[i64::min_value(), -1, 0, 1, i64::max_value()] {
+        m.insert(x, (), guard);
+    }
+    let keys: Vec<i64> = m.iter(guard).map(|(k, _)| *k).collect();
+    assert_eq!(keys, [i64::min_value(), -1, 0, 1, i64::max_value()]);
+    assert_eq!(m.first(guard).map(|(k, _)| *k), Some(i64::min_value()));
+    assert_eq!(m.last(guard).map(|(k, _)| *k), Some(i64::max_value()));
+}
+
+#[test]
+fn range() {
+    let m = RadixMap::new();
+    let guard = &m.guard();
+    for x in (0..1000u32).map(|x| x * 3) {
+        m.insert(x, x, guard);
+    }
+
+    fn keys<'g>(it: impl Iterator<Item = (&'g u32, &'g u32)>) -> Vec<u32> {
+        it.map(|(k, _)| *k).collect()
+    }
+
+    assert_eq!(keys(m.range(10..20, guard)), [12, 15, 18]);
+    assert_eq!(keys(m.range(12..=18, guard)), [12, 15, 18]);
+    assert_eq!(keys(m.range(2990.., guard)), [2991, 2994, 2997]);
+    assert_eq!(keys(m.range(..4, guard)), [0, 3]);
+    assert_eq!(keys(m.range(4..4, guard)), []);
+    assert_eq!(keys(m.range((Included(5), Excluded(4)), guard)), []);
+    assert_eq!(keys(m.range(0..0, guard)), []);
+    assert_eq!(m.range(.., guard).count(), 1000);
+    assert_eq!(keys(m.range(10..20, guard).rev()), [18, 15, 12]);
+
+    // Both ends of the iterator meet in the middle.
+    let mut it = m.range(0..=9, guard);
+    assert_eq!(it.next().map(|(k, _)| *k), Some(0));
+    assert_eq!(it.next_back().map(|(k, _)| *k), Some(9));
+    assert_eq!(it.next_back().map(|(k, _)| *k), Some(6));
+    assert_eq!(it.next().map(|(k, _)| *k), Some(3));
+    assert_eq!(it.next(), None);
+    assert_eq!(it.next_back(), None);
+}
+
+#[test]
+fn iter_ordered() {
+    let m = RadixMap::new();
+    let guard = &m.guard();
+
+    let mut keys: Vec<u64> = (0..2000u64)
+        .map(|x| x.wrapping_mul(0x9e37_79b9_7f4a_7c15))
+        .collect();
+    for &k in &keys {
+        m.insert(k, k, guard);
+    }
+    keys.sort();
+
+    let visited: Vec<u64> = m
+        .iter(guard)
+        .map(|(k, v)| {
+            assert_eq!(k, v);
+            *k
+        })
+        .collect();
+    assert_eq!(visited, keys);
+
+    let mut reversed: Vec<u64> = m.iter(guard).rev().map(|(k, _)| *k).collect();
+    reversed.reverse();
+    assert_eq!(reversed, keys);
+}
+
+#[test]
+fn drops() {
+    #[derive(Debug)]
+    struct Value(Arc<AtomicUsize>);
+
+    impl Drop for Value {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let drops = Arc::new(AtomicUsize::new(0));
+    let m = RadixMap::new();
+    {
+        let guard = &m.guard();
+        for x in 0..100u16 {
+            m.insert(x, Value(drops.clone()), guard);
+        }
+        for x in 0..50u16 {
+            m.remove(&x, guard);
+        }
+        for x in 50..60u16 {
+            m.insert(x, Value(drops.clone()), guard);
+        }
+    }
+
+    drop(m);
+    // Removed values may still be waiting for their epoch, so flush the garbage out.
+    while drops.load(Ordering::SeqCst) < 110 {
+        crossbeam_epoch::pin().flush();
+        std::thread::yield_now();
+    }
+    assert_eq!(drops.load(Ordering::SeqCst), 110);
+}
+
+#[test]
+fn concurrent() {
+    const THREADS: u64 = 4;
+    const N: u64 = 2000;
+
+    let m = RadixMap::new();
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let m = &m;
+            scope.spawn(move |_| {
+                for x in 0..N {
+                    let guard = &m.guard();
+                    // Threads share the same subtrees, so nodes get added and unlinked while
+                    // others are using them.
+                    let key = x * THREADS + t;
+                    m.insert(key, key, guard);
+                    assert_eq!(m.get(&key, guard), Some(&key));
+                    if x % 2 == 0 {
+                        assert_eq!(m.remove(&key, guard), Some(&key));
+                    }
+                }
+            });
+        }
+
+        scope.spawn(|_| {
+            for _ in 0..100 {
+                let guard = &m.guard();
+                let mut last = None;
+                for (k, v) in m.iter(guard) {
+                    assert_eq!(k, v);
+                    assert!(last < Some(*k));
+                    last = Some(*k);
+                }
+            }
+        });
+    })
+    .unwrap();
+
+    assert_eq!(m.len() as u64, THREADS * N / 2);
+    let guard = &m.guard();
+    for key in 0..THREADS * N {
+        assert_eq!(m.contains_key(&key, guard), (key / THREADS) % 2 == 1);
+    }
+}
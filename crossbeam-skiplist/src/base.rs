@@ -1,7 +1,6 @@
 //! A lock-free skip list. See [`SkipList`].
 
 use alloc::alloc::{alloc, dealloc, handle_alloc_error, Layout};
-use core::borrow::Borrow;
 use core::cmp;
 use core::fmt;
 use core::marker::PhantomData;
@@ -10,6 +9,7 @@ use core::ops::{Bound, Deref, Index, RangeBounds};
 use core::ptr;
 use core::sync::atomic::{fence, AtomicUsize, Ordering};
 
+use crate::comparator::{BasicComparator, Comparator};
 use crate::epoch::{self, Atomic, Collector, Guard, Shared};
 use crate::utils::CachePadded;
 
@@ -22,6 +22,9 @@ const MAX_HEIGHT: usize = 1 << HEIGHT_BITS;
 /// The bits of `refs_and_height` that keep the height.
 const HEIGHT_MASK: usize = (1 << HEIGHT_BITS) - 1;
 
+/// Number of shards the length counter is split into. Must be a power of two.
+const LEN_SHARDS: usize = 8;
+
 /// The tower of atomic pointers.
 ///
 /// The actual size of the tower will vary depending on the height that a node
@@ -36,7 +39,10 @@ impl<K, V> Index<usize> for Tower<K, V> {
     fn index(&self, index: usize) -> &Atomic<Node<K, V>> {
         // This implementation is actually unsafe since we don't check if the
         // index is in-bounds. But this is fine since this is only used internally.
-        unsafe { self.pointers.get_unchecked(index) }
+        //
+        // The pointers are allocated past the end of the zero-length array, so index through a raw
+        // pointer instead of the array itself.
+        unsafe { &*(self.pointers.as_ptr()).add(index) }
     }
 }
 
@@ -229,7 +235,7 @@ impl<K, V> Node<K, V> {
     /// Decrements the reference count of a node, pinning the thread and destroying the node
     /// if the count become zero.
     #[inline]
-    unsafe fn decrement_with_pin<F>(&self, parent: &SkipList<K, V>, pin: F)
+    unsafe fn decrement_with_pin<C, F>(&self, parent: &SkipList<K, V, C>, pin: F)
     where
         F: FnOnce() -> Guard,
     {
@@ -290,14 +296,14 @@ struct Position<'a, K, V> {
     right: [Shared<'a, Node<K, V>>; MAX_HEIGHT],
 }
 
+/// A newly installed entry, along with the entry it replaced, if any.
+type Replaced<'a, K, V, C> = (RefEntry<'a, K, V, C>, Option<RefEntry<'a, K, V, C>>);
+
 /// Frequently modified data associated with a skip list.
 struct HotData {
     /// The seed for random height generation.
     seed: AtomicUsize,
 
-    /// The number of entries in the skip list.
-    len: AtomicUsize,
-
     /// Highest tower currently in use. This value is used as a hint for where
     /// to start lookups and never decreases.
     max_height: AtomicUsize,
@@ -316,7 +322,7 @@ struct HotData {
 // As a further future optimization, if `!mem::needs_drop::<K>() && !mem::needs_drop::<V>()`
 // (neither key nor the value have destructors), there's no point in creating a new local
 // collector, so we should simply use the global one.
-pub struct SkipList<K, V> {
+pub struct SkipList<K, V, C = BasicComparator> {
     /// The head of the skip list (just a dummy node, not a real entry).
     head: Head<K, V>,
 
@@ -325,25 +331,53 @@ pub struct SkipList<K, V> {
 
     /// Hot data associated with the skip list, stored in a dedicated cache line.
     hot_data: CachePadded<HotData>,
+
+    /// The number of entries in the skip list, split into shards.
+    ///
+    /// Every insertion or removal updates only one of the shards, so threads modifying the skip
+    /// list concurrently rarely contend on the same cache line. The shards may individually wrap
+    /// around, but their sum is the number of entries.
+    len: [CachePadded<AtomicUsize>; LEN_SHARDS],
+
+    /// Defines the order of the keys.
+    comparator: C,
 }
 
-unsafe impl<K: Send + Sync, V: Send + Sync> Send for SkipList<K, V> {}
-unsafe impl<K: Send + Sync, V: Send + Sync> Sync for SkipList<K, V> {}
+unsafe impl<K: Send + Sync, V: Send + Sync, C: Send> Send for SkipList<K, V, C> {}
+unsafe impl<K: Send + Sync, V: Send + Sync, C: Sync> Sync for SkipList<K, V, C> {}
 
 impl<K, V> SkipList<K, V> {
     /// Returns a new, empty skip list.
     pub fn new(collector: Collector) -> SkipList<K, V> {
+        SkipList::with_comparator(collector, BasicComparator)
+    }
+}
+
+impl<K, V, C> SkipList<K, V, C> {
+    /// Returns a new, empty skip list that orders its keys with `comparator`.
+    pub fn with_comparator(collector: Collector, comparator: C) -> SkipList<K, V, C> {
         SkipList {
             head: Head::new(),
             collector,
             hot_data: CachePadded::new(HotData {
                 seed: AtomicUsize::new(1),
-                len: AtomicUsize::new(0),
                 max_height: AtomicUsize::new(1),
             }),
+            len: Default::default(),
+            comparator,
         }
     }
 
+    /// Returns the comparator that orders the keys of the skip list.
+    pub fn comparator(&self) -> &C {
+        &self.comparator
+    }
+
+    /// Returns the collector that destroys the removed entries of the skip list.
+    pub fn collector(&self) -> &Collector {
+        &self.collector
+    }
+
     /// Returns `true` if the skip list is empty.
     pub fn is_empty(&self) -> bool {
         self.len() == 0
@@ -354,10 +388,13 @@ impl<K, V> SkipList<K, V> {
     /// If the skip list is being concurrently modified, consider the returned number just an
     /// approximation without any guarantees.
     pub fn len(&self) -> usize {
-        let len = self.hot_data.len.load(Ordering::Relaxed);
+        let len = self.len.iter().fold(0usize, |sum, shard| {
+            sum.wrapping_add(shard.load(Ordering::Relaxed))
+        });
 
-        // Due to the relaxed memory ordering, the length counter may sometimes
-        // underflow and produce a very large value. We treat such values as 0.
+        // Due to the relaxed memory ordering and concurrent updates while summing up the shards,
+        // the length counter may sometimes underflow and produce a very large value. We treat
+        // such values as 0.
         if len > isize::max_value() as usize {
             0
         } else {
@@ -365,6 +402,25 @@ impl<K, V> SkipList<K, V> {
         }
     }
 
+    /// Returns the shard of the length counter that is updated when `n` is inserted or removed.
+    ///
+    /// The shard is picked by the address of the node, so that threads inserting or removing
+    /// different nodes are likely to update different shards.
+    fn len_shard(&self, n: &Node<K, V>) -> &AtomicUsize {
+        let addr = n as *const Node<K, V> as usize;
+        &self.len[(addr >> 6) & (LEN_SHARDS - 1)]
+    }
+
+    /// Increments the length counter after inserting `n`.
+    fn increment_len(&self, n: &Node<K, V>) {
+        self.len_shard(n).fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Decrements the length counter after removing `n`.
+    fn decrement_len(&self, n: &Node<K, V>) {
+        self.len_shard(n).fetch_sub(1, Ordering::Relaxed);
+    }
+
     /// Ensures that all `Guard`s used with the skip list come from the same
     /// `Collector`.
     fn check_guard(&self, guard: &Guard) {
@@ -374,12 +430,12 @@ impl<K, V> SkipList<K, V> {
     }
 }
 
-impl<K, V> SkipList<K, V>
+impl<K, V, C> SkipList<K, V, C>
 where
-    K: Ord,
+    C: Comparator<K>,
 {
     /// Returns the entry with the smallest key.
-    pub fn front<'a: 'g, 'g>(&'a self, guard: &'g Guard) -> Option<Entry<'a, 'g, K, V>> {
+    pub fn front<'a: 'g, 'g>(&'a self, guard: &'g Guard) -> Option<Entry<'a, 'g, K, V, C>> {
         self.check_guard(guard);
         let n = self.next_node(&self.head, Bound::Unbounded, guard)?;
         Some(Entry {
@@ -390,7 +446,7 @@ where
     }
 
     /// Returns the entry with the largest key.
-    pub fn back<'a: 'g, 'g>(&'a self, guard: &'g Guard) -> Option<Entry<'a, 'g, K, V>> {
+    pub fn back<'a: 'g, 'g>(&'a self, guard: &'g Guard) -> Option<Entry<'a, 'g, K, V, C>> {
         self.check_guard(guard);
         let n = self.search_bound(Bound::Unbounded, true, guard)?;
         Some(Entry {
@@ -403,21 +459,21 @@ where
     /// Returns `true` if the map contains a value for the specified key.
     pub fn contains_key<Q>(&self, key: &Q, guard: &Guard) -> bool
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
         self.get(key, guard).is_some()
     }
 
     /// Returns an entry with the specified `key`.
-    pub fn get<'a: 'g, 'g, Q>(&'a self, key: &Q, guard: &'g Guard) -> Option<Entry<'a, 'g, K, V>>
+    pub fn get<'a: 'g, 'g, Q>(&'a self, key: &Q, guard: &'g Guard) -> Option<Entry<'a, 'g, K, V, C>>
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
         self.check_guard(guard);
         let n = self.search_bound(Bound::Included(key), false, guard)?;
-        if n.key.borrow() != key {
+        if self.comparator.compare(&n.key, key) != cmp::Ordering::Equal {
             return None;
         }
         Some(Entry {
@@ -434,10 +490,10 @@ where
         &'a self,
         bound: Bound<&Q>,
         guard: &'g Guard,
-    ) -> Option<Entry<'a, 'g, K, V>>
+    ) -> Option<Entry<'a, 'g, K, V, C>>
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
         self.check_guard(guard);
         let n = self.search_bound(bound, false, guard)?;
@@ -455,10 +511,10 @@ where
         &'a self,
         bound: Bound<&Q>,
         guard: &'g Guard,
-    ) -> Option<Entry<'a, 'g, K, V>>
+    ) -> Option<Entry<'a, 'g, K, V, C>>
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
         self.check_guard(guard);
         let n = self.search_bound(bound, true, guard)?;
@@ -470,12 +526,41 @@ where
     }
 
     /// Finds an entry with the specified key, or inserts a new `key`-`value` pair if none exist.
-    pub fn get_or_insert(&self, key: K, value: V, guard: &Guard) -> RefEntry<'_, K, V> {
-        self.insert_internal(key, value, false, guard)
+    pub fn get_or_insert(&self, key: K, value: V, guard: &Guard) -> RefEntry<'_, K, V, C> {
+        match self.insert_internal(key, || value, false, guard) {
+            Ok(e) | Err(e) => e,
+        }
+    }
+
+    /// Finds an entry with the specified key, or inserts a new `key`-`value` pair if none exist,
+    /// where value is calculated with a function.
+    ///
+    /// The function is called at most once, and only if the key is not already present.
+    pub fn get_or_insert_with<F>(&self, key: K, value: F, guard: &Guard) -> RefEntry<'_, K, V, C>
+    where
+        F: FnOnce() -> V,
+    {
+        match self.insert_internal(key, value, false, guard) {
+            Ok(e) | Err(e) => e,
+        }
+    }
+
+    /// Inserts a `key`-`value` pair into the skip list, but only if there is no entry with this
+    /// key yet.
+    ///
+    /// On success, the new entry is returned. Otherwise, `value` is dropped and the existing entry
+    /// is returned as an error.
+    pub fn try_insert(
+        &self,
+        key: K,
+        value: V,
+        guard: &Guard,
+    ) -> Result<RefEntry<'_, K, V, C>, RefEntry<'_, K, V, C>> {
+        self.insert_internal(key, || value, false, guard)
     }
 
     /// Returns an iterator over all entries in the skip list.
-    pub fn iter<'a: 'g, 'g>(&'a self, guard: &'g Guard) -> Iter<'a, 'g, K, V> {
+    pub fn iter<'a: 'g, 'g>(&'a self, guard: &'g Guard) -> Iter<'a, 'g, K, V, C> {
         self.check_guard(guard);
         Iter {
             parent: self,
@@ -486,7 +571,7 @@ where
     }
 
     /// Returns an iterator over all entries in the skip list.
-    pub fn ref_iter(&self) -> RefIter<'_, K, V> {
+    pub fn ref_iter(&self) -> RefIter<'_, K, V, C> {
         RefIter {
             parent: self,
             head: None,
@@ -499,11 +584,11 @@ where
         &'a self,
         range: R,
         guard: &'g Guard,
-    ) -> Range<'a, 'g, Q, R, K, V>
+    ) -> Range<'a, 'g, Q, R, K, V, C>
     where
-        K: Borrow<Q>,
+        C: Comparator<K, Q>,
         R: RangeBounds<Q>,
-        Q: Ord + ?Sized,
+        Q: ?Sized,
     {
         self.check_guard(guard);
         Range {
@@ -518,17 +603,18 @@ where
 
     /// Returns an iterator over a subset of entries in the skip list.
     #[allow(clippy::needless_lifetimes)]
-    pub fn ref_range<'a, Q, R>(&'a self, range: R) -> RefRange<'a, Q, R, K, V>
+    pub fn ref_range<'a, Q, R>(&'a self, range: R) -> RefRange<'a, Q, R, K, V, C>
     where
-        K: Borrow<Q>,
+        C: Comparator<K, Q>,
         R: RangeBounds<Q>,
-        Q: Ord + ?Sized,
+        Q: ?Sized,
     {
         RefRange {
             parent: self,
             range,
             head: None,
             tail: None,
+            finished: false,
             _marker: PhantomData,
         }
     }
@@ -664,8 +750,8 @@ where
         guard: &'a Guard,
     ) -> Option<&'a Node<K, V>>
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
         unsafe {
             'search: loop {
@@ -723,11 +809,11 @@ where
                         // bound, we return the last node before the condition became true. For the
                         // lower bound, we return the first node after the condition became true.
                         if upper_bound {
-                            if !below_upper_bound(&bound, c.key.borrow()) {
+                            if !below_upper_bound(&self.comparator, &bound, &c.key) {
                                 break;
                             }
                             result = Some(c);
-                        } else if above_lower_bound(&bound, c.key.borrow()) {
+                        } else if above_lower_bound(&self.comparator, &bound, &c.key) {
                             result = Some(c);
                             break;
                         }
@@ -746,8 +832,8 @@ where
     /// Searches for a key in the skip list and returns a list of all adjacent nodes.
     fn search_position<'a, Q>(&'a self, key: &Q, guard: &'a Guard) -> Position<'a, K, V>
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
         unsafe {
             'search: loop {
@@ -804,7 +890,7 @@ where
 
                         // If `curr` contains a key that is greater than or equal to `key`, we're
                         // done with this level.
-                        match c.key.borrow().cmp(key) {
+                        match self.comparator.compare(&c.key, key) {
                             cmp::Ordering::Greater => break,
                             cmp::Ordering::Equal => {
                                 result.found = Some(c);
@@ -831,13 +917,17 @@ where
     /// Inserts an entry with the specified `key` and `value`.
     ///
     /// If `replace` is `true`, then any existing entry with this key will first be removed.
-    fn insert_internal(
+    /// Otherwise, the existing entry is returned as an error and `value` is never called.
+    fn insert_internal<F>(
         &self,
         key: K,
-        value: V,
+        value: F,
         replace: bool,
         guard: &Guard,
-    ) -> RefEntry<'_, K, V> {
+    ) -> Result<RefEntry<'_, K, V, C>, RefEntry<'_, K, V, C>>
+    where
+        F: FnOnce() -> V,
+    {
         self.check_guard(guard);
 
         unsafe {
@@ -849,7 +939,7 @@ where
             let mut search;
             loop {
                 // First try searching for the key.
-                // Note that the `Ord` implementation for `K` may panic during the search.
+                // Note that the comparator may panic during the search.
                 search = self.search_position(&key, guard);
 
                 let r = match search.found {
@@ -861,13 +951,13 @@ where
                     // If a node with the key was found and we should replace it, mark its tower
                     // and then repeat the search.
                     if r.mark_tower() {
-                        self.hot_data.len.fetch_sub(1, Ordering::Relaxed);
+                        self.decrement_len(r);
                     }
                 } else {
                     // If a node with the key was found and we're not going to replace it, let's
                     // try returning it as an entry.
                     if let Some(e) = RefEntry::try_acquire(self, r) {
-                        return e;
+                        return Err(e);
                     }
 
                     // If we couldn't increment the reference count, that means someone has just
@@ -886,13 +976,13 @@ where
 
                 // Write the key and the value into the node.
                 ptr::write(&mut (*n).key, key);
-                ptr::write(&mut (*n).value, value);
+                ptr::write(&mut (*n).value, value());
 
                 (Shared::<Node<K, V>>::from(n as *const _), &*n)
             };
 
             // Optimistically increment `len`.
-            self.hot_data.len.fetch_add(1, Ordering::Relaxed);
+            self.increment_len(n);
 
             loop {
                 // Set the lowest successor of `n` to `search.right[0]`.
@@ -928,17 +1018,17 @@ where
                         // If a node with the key was found and we should replace it, mark its
                         // tower and then repeat the search.
                         if r.mark_tower() {
-                            self.hot_data.len.fetch_sub(1, Ordering::Relaxed);
+                            self.decrement_len(r);
                         }
                     } else {
                         // If a node with the key was found and we're not going to replace it,
                         // let's try returning it as an entry.
                         if let Some(e) = RefEntry::try_acquire(self, r) {
                             // Destroy the new node.
+                            self.decrement_len(n);
                             Node::finalize(node.as_raw());
-                            self.hot_data.len.fetch_sub(1, Ordering::Relaxed);
 
-                            return e;
+                            return Err(e);
                         }
 
                         // If we couldn't increment the reference count, that means someone has
@@ -954,121 +1044,356 @@ where
             };
 
             // Build the rest of the tower above level 0.
-            'build: for level in 1..height {
-                loop {
-                    // Obtain the predecessor and successor at the current level.
-                    let pred = search.left[level];
-                    let succ = search.right[level];
-
-                    // Load the current value of the pointer in the tower at this level.
-                    // TODO(Amanieu): can we use relaxed ordering here?
-                    let next = n.tower[level].load(Ordering::SeqCst, guard);
-
-                    // If the current pointer is marked, that means another thread is already
-                    // removing the node we've just inserted. In that case, let's just stop
-                    // building the tower.
-                    if next.tag() == 1 {
-                        break 'build;
-                    }
+            self.build_tower(n, node, height, search, guard);
 
-                    // When searching for `key` and traversing the skip list from the highest level
-                    // to the lowest, it is possible to observe a node with an equal key at higher
-                    // levels and then find it missing at the lower levels if it gets removed
-                    // during traversal. Even worse, it is possible to observe completely different
-                    // nodes with the exact same key at different levels.
-                    //
-                    // Linking the new node to a dead successor with an equal key could create
-                    // subtle corner cases that would require special care. It's much easier to
-                    // simply prohibit linking two nodes with equal keys.
-                    //
-                    // If the successor has the same key as the new node, that means it is marked
-                    // as removed and should be unlinked from the skip list. In that case, let's
-                    // repeat the search to make sure it gets unlinked and try again.
-                    //
-                    // If this comparison or the following search panics, we simply stop building
-                    // the tower without breaking any invariants. Note that building higher levels
-                    // is completely optional. Only the lowest level really matters, and all the
-                    // higher levels are there just to make searching faster.
-                    if succ.as_ref().map(|s| &s.key) == Some(&n.key) {
-                        search = self.search_position(&n.key, guard);
-                        continue;
-                    }
+            // Finally, return the new entry.
+            Ok(entry)
+        }
+    }
 
-                    // Change the pointer at the current level from `next` to `succ`. If this CAS
-                    // operation fails, that means another thread has marked the pointer and we
-                    // should stop building the tower.
-                    // TODO(Amanieu): can we use release ordering here?
-                    if n.tower[level]
-                        .compare_exchange(next, succ, Ordering::SeqCst, Ordering::SeqCst, guard)
-                        .is_err()
-                    {
-                        break 'build;
-                    }
+    /// Links a node that has just been installed at level 0 into the rest of its tower.
+    ///
+    /// `search` must be the position the node was installed at.
+    unsafe fn build_tower<'a>(
+        &'a self,
+        n: &'a Node<K, V>,
+        node: Shared<'a, Node<K, V>>,
+        height: usize,
+        mut search: Position<'a, K, V>,
+        guard: &'a Guard,
+    ) {
+        'build: for level in 1..height {
+            loop {
+                // Obtain the predecessor and successor at the current level.
+                let pred = search.left[level];
+                let succ = search.right[level];
+
+                // Load the current value of the pointer in the tower at this level.
+                // TODO(Amanieu): can we use relaxed ordering here?
+                let next = n.tower[level].load(Ordering::SeqCst, guard);
+
+                // If the current pointer is marked, that means another thread is already
+                // removing the node we've just inserted. In that case, let's just stop
+                // building the tower.
+                if next.tag() == 1 {
+                    break 'build;
+                }
 
-                    // Increment the reference count. The current value will always be at least 1
-                    // because we are holding `entry`.
-                    n.refs_and_height
-                        .fetch_add(1 << HEIGHT_BITS, Ordering::Relaxed);
-
-                    // Try installing the new node at the current level.
-                    // TODO(Amanieu): can we use release ordering here?
-                    if pred[level]
-                        .compare_exchange(succ, node, Ordering::SeqCst, Ordering::SeqCst, guard)
-                        .is_ok()
-                    {
-                        // Success! Continue on the next level.
-                        break;
-                    }
+                // When searching for `key` and traversing the skip list from the highest level
+                // to the lowest, it is possible to observe a node with an equal key at higher
+                // levels and then find it missing at the lower levels if it gets removed
+                // during traversal. Even worse, it is possible to observe completely different
+                // nodes with the exact same key at different levels.
+                //
+                // Linking the new node to a dead successor with an equal key could create
+                // subtle corner cases that would require special care. It's much easier to
+                // simply prohibit linking two nodes with equal keys.
+                //
+                // If the successor has the same key as the new node, that means it is marked
+                // as removed and should be unlinked from the skip list. In that case, let's
+                // repeat the search to make sure it gets unlinked and try again.
+                //
+                // If this comparison or the following search panics, we simply stop building
+                // the tower without breaking any invariants. Note that building higher levels
+                // is completely optional. Only the lowest level really matters, and all the
+                // higher levels are there just to make searching faster.
+                if succ
+                    .as_ref()
+                    .map(|s| self.comparator.compare(&s.key, &n.key))
+                    == Some(cmp::Ordering::Equal)
+                {
+                    search = self.search_position(&n.key, guard);
+                    continue;
+                }
 
-                    // Installation failed. Decrement the reference count.
-                    (*n).refs_and_height
-                        .fetch_sub(1 << HEIGHT_BITS, Ordering::Relaxed);
+                // Change the pointer at the current level from `next` to `succ`. If this CAS
+                // operation fails, that means another thread has marked the pointer and we
+                // should stop building the tower.
+                // TODO(Amanieu): can we use release ordering here?
+                if n.tower[level]
+                    .compare_exchange(next, succ, Ordering::SeqCst, Ordering::SeqCst, guard)
+                    .is_err()
+                {
+                    break 'build;
+                }
 
-                    // We don't have the most up-to-date search results. Repeat the search.
-                    //
-                    // If this search panics, we simply stop building the tower without breaking
-                    // any invariants. Note that building higher levels is completely optional.
-                    // Only the lowest level really matters, and all the higher levels are there
-                    // just to make searching faster.
-                    search = self.search_position(&n.key, guard);
+                // Increment the reference count. The current value will always be at least 1
+                // because we are holding `entry`.
+                n.refs_and_height
+                    .fetch_add(1 << HEIGHT_BITS, Ordering::Relaxed);
+
+                // Try installing the new node at the current level.
+                // TODO(Amanieu): can we use release ordering here?
+                if pred[level]
+                    .compare_exchange(succ, node, Ordering::SeqCst, Ordering::SeqCst, guard)
+                    .is_ok()
+                {
+                    // Success! Continue on the next level.
+                    break;
                 }
-            }
 
-            // If any pointer in the tower is marked, that means our node is in the process of
-            // removal or already removed. It is possible that another thread (either partially or
-            // completely) removed the new node while we were building the tower, and just after
-            // that we installed the new node at one of the higher levels. In order to undo that
-            // installation, we must repeat the search, which will unlink the new node at that
-            // level.
-            // TODO(Amanieu): can we use relaxed ordering here?
-            if n.tower[height - 1].load(Ordering::SeqCst, guard).tag() == 1 {
-                self.search_bound(Bound::Included(&n.key), false, guard);
+                // Installation failed. Decrement the reference count.
+                n.refs_and_height
+                    .fetch_sub(1 << HEIGHT_BITS, Ordering::Relaxed);
+
+                // We don't have the most up-to-date search results. Repeat the search.
+                //
+                // If this search panics, we simply stop building the tower without breaking
+                // any invariants. Note that building higher levels is completely optional.
+                // Only the lowest level really matters, and all the higher levels are there
+                // just to make searching faster.
+                search = self.search_position(&n.key, guard);
             }
+        }
 
-            // Finally, return the new entry.
-            entry
+        // If any pointer in the tower is marked, that means our node is in the process of
+        // removal or already removed. It is possible that another thread (either partially or
+        // completely) removed the new node while we were building the tower, and just after
+        // that we installed the new node at one of the higher levels. In order to undo that
+        // installation, we must repeat the search, which will unlink the new node at that
+        // level.
+        // TODO(Amanieu): can we use relaxed ordering here?
+        if n.tower[height - 1].load(Ordering::SeqCst, guard).tag() == 1 {
+            self.search_bound(Bound::Included(&n.key), false, guard);
         }
     }
 }
 
-impl<K, V> SkipList<K, V>
+impl<K, V, C> SkipList<K, V, C>
 where
-    K: Ord + Send + 'static,
+    K: Send + 'static,
+    C: Comparator<K>,
     V: Send + 'static,
 {
     /// Inserts a `key`-`value` pair into the skip list and returns the new entry.
     ///
     /// If there is an existing entry with this key, it will be removed before inserting the new
     /// one.
-    pub fn insert(&self, key: K, value: V, guard: &Guard) -> RefEntry<'_, K, V> {
-        self.insert_internal(key, value, true, guard)
+    pub fn insert(&self, key: K, value: V, guard: &Guard) -> RefEntry<'_, K, V, C> {
+        match self.insert_internal(key, || value, true, guard) {
+            Ok(e) => e,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Inserts a `key`-`value` pair into the skip list and returns the new entry, along with the
+    /// entry it replaced, if any.
+    ///
+    /// Unlike [`insert`], the existing entry is atomically replaced with the new one, so other
+    /// threads never observe the key as missing.
+    ///
+    /// [`insert`]: SkipList::insert
+    pub fn insert_or_replace(&self, key: K, value: V, guard: &Guard) -> Replaced<'_, K, V, C> {
+        let mut value = Some(value);
+        match self.replace_internal(key, |_, prev| prev.or_else(|| value.take()), guard) {
+            Ok(entries) => entries,
+            Err(_) => unreachable!(),
+        }
+    }
+
+    /// Computes a new value for the entry with the specified `key`.
+    ///
+    /// `f` is called with the current value, or `None` if there is no entry with this key. If it
+    /// returns a new value, the current entry is atomically replaced with a new one holding that
+    /// value, which is returned. Otherwise, the current entry is kept and returned.
+    ///
+    /// If the entry is replaced or removed by another thread before the new one is installed, `f`
+    /// is called again with the new current value.
+    pub fn compute<F>(&self, key: K, mut f: F, guard: &Guard) -> Option<RefEntry<'_, K, V, C>>
+    where
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        match self.replace_internal(key, |current, _| f(current), guard) {
+            Ok((entry, prev)) => {
+                if let Some(prev) = prev {
+                    prev.release(guard);
+                }
+                Some(entry)
+            }
+            Err(current) => current,
+        }
+    }
+
+    /// Atomically replaces the entry with the specified `key`, or inserts a new one if there is
+    /// none, with the value computed by `f`.
+    ///
+    /// `f` is called with the current value and the value it returned in the previous attempt, if
+    /// any. On success, the new entry and the replaced entry are returned. If `f` returns `None`,
+    /// the current entry is returned as an error instead.
+    ///
+    /// An existing entry is replaced the way Harris's linked list removes nodes, in two steps:
+    ///
+    /// 1. The upper levels of the current node's tower are marked, just like in `mark_tower`.
+    /// 2. The level 0 pointer of the current node is marked and redirected to the new node with a
+    ///    single CAS. The new node already points to the successor of the current node, so the
+    ///    key is reachable at level 0 before, during and after the replacement.
+    ///
+    /// Searches then treat the current node as removed and unlink it at every level, after which
+    /// the tower of the new node is built above level 0.
+    #[allow(clippy::type_complexity)]
+    fn replace_internal<F>(
+        &self,
+        key: K,
+        mut f: F,
+        guard: &Guard,
+    ) -> Result<Replaced<'_, K, V, C>, Option<RefEntry<'_, K, V, C>>>
+    where
+        F: FnMut(Option<&V>, Option<V>) -> Option<V>,
+    {
+        self.check_guard(guard);
+
+        unsafe {
+            // Rebind the guard to the lifetime of self. This is a bit of a
+            // hack but it allows us to return references that are not bound to
+            // the lifetime of the guard.
+            let guard = &*(guard as *const _);
+
+            // Create the new node up front so that the key can be moved into it. The reference
+            // count is initially two to account for:
+            // 1. The entry that will be returned.
+            // 2. The link at the level 0 of the tower.
+            let height = self.random_height();
+            let ptr = Node::<K, V>::alloc(height, 2);
+            ptr::write(&mut (*ptr).key, key);
+
+            // Create a guard that destroys the new node unless it gets installed. The flag tells
+            // whether a value has been written into the node.
+            let mut has_value = scopeguard::guard(false, move |has_value| {
+                if has_value {
+                    ptr::drop_in_place(&mut (*ptr).value);
+                }
+                ptr::drop_in_place(&mut (*ptr).key);
+                Node::dealloc(ptr);
+            });
+
+            loop {
+                // Note that the comparator may panic during the search.
+                let search = self.search_position(&(*ptr).key, guard);
+
+                // Take back the value written into the new node in the previous attempt.
+                let prev_value = if *has_value {
+                    *has_value = false;
+                    Some(ptr::read(&(*ptr).value))
+                } else {
+                    None
+                };
+
+                let value = match f(search.found.map(|r| &r.value), prev_value) {
+                    Some(value) => value,
+                    None => match search.found {
+                        None => return Err(None),
+                        Some(r) => match RefEntry::try_acquire(self, r) {
+                            Some(e) => return Err(Some(e)),
+                            // Someone has just now removed the node. Let's try again.
+                            None => continue,
+                        },
+                    },
+                };
+
+                // Write the value into the new node.
+                ptr::write(&mut (*ptr).value, value);
+                *has_value = true;
+
+                let node = Shared::<Node<K, V>>::from(ptr as *const _);
+                let mut prev = None;
+                let installed = match search.found {
+                    None => {
+                        // There is no node with the key, so install the new node just like
+                        // `insert` does.
+                        (&(*ptr).tower)[0].store(search.right[0], Ordering::Relaxed);
+                        search.left[0][0]
+                            .compare_exchange(
+                                search.right[0],
+                                node,
+                                Ordering::SeqCst,
+                                Ordering::SeqCst,
+                                guard,
+                            )
+                            .is_ok()
+                    }
+                    Some(r) => {
+                        // Acquire the current node, which will be returned as the replaced entry.
+                        // If this fails, someone has just now removed it.
+                        let e = match RefEntry::try_acquire(self, r) {
+                            Some(e) => e,
+                            None => continue,
+                        };
+
+                        // Mark the upper levels of the current node's tower, just like
+                        // `mark_tower` does.
+                        for level in (1..r.height()).rev() {
+                            r.tower[level].fetch_or(1, Ordering::SeqCst, guard);
+                        }
+
+                        // Then mark its level 0 pointer and point it to the new node in a single
+                        // step. This removes the current node and makes the new one reachable at
+                        // the same time, so the key never appears to be missing.
+                        loop {
+                            let succ = r.tower[0].load(Ordering::SeqCst, guard);
+
+                            // If the pointer is already marked, someone else removed the node.
+                            if succ.tag() == 1 {
+                                e.release(guard);
+                                break false;
+                            }
+
+                            (&(*ptr).tower)[0].store(succ, Ordering::Relaxed);
+                            if r.tower[0]
+                                .compare_exchange(
+                                    succ,
+                                    node.with_tag(1),
+                                    Ordering::SeqCst,
+                                    Ordering::SeqCst,
+                                    guard,
+                                )
+                                .is_ok()
+                            {
+                                prev = Some(e);
+                                break true;
+                            }
+                        }
+                    }
+                };
+
+                if !installed {
+                    // We don't have the most up-to-date search results. Repeat the search and
+                    // compute the value again.
+                    continue;
+                }
+
+                // The new node was successfully installed.
+                scopeguard::ScopeGuard::into_inner(has_value);
+                let n = &*ptr;
+
+                // If a node was replaced, `len` stays the same.
+                if search.found.is_none() {
+                    self.increment_len(n);
+                }
+
+                let entry = RefEntry {
+                    parent: self,
+                    node: n,
+                };
+
+                // Repeat the search, which unlinks the replaced node at every level, and build
+                // the rest of the tower above level 0.
+                //
+                // If this search panics, we simply don't build the tower. Only the lowest level
+                // really matters, and all the higher levels are there just to make searching
+                // faster.
+                let search = self.search_position(&n.key, guard);
+                self.build_tower(n, node, height, search, guard);
+
+                return Ok((entry, prev));
+            }
+        }
     }
 
     /// Removes an entry with the specified `key` from the map and returns it.
-    pub fn remove<Q>(&self, key: &Q, guard: &Guard) -> Option<RefEntry<'_, K, V>>
+    pub fn remove<Q>(&self, key: &Q, guard: &Guard) -> Option<RefEntry<'_, K, V, C>>
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
         self.check_guard(guard);
 
@@ -1093,44 +1418,115 @@ where
 
                 // Try removing the node by marking its tower.
                 if n.mark_tower() {
-                    // Success! Decrement `len`.
-                    self.hot_data.len.fetch_sub(1, Ordering::Relaxed);
-
-                    // Unlink the node at each level of the skip list. We could do this by simply
-                    // repeating the search, but it's usually faster to unlink it manually using
-                    // the `left` and `right` lists.
-                    for level in (0..n.height()).rev() {
-                        // TODO(Amanieu): can we use relaxed ordering here?
-                        let succ = n.tower[level].load(Ordering::SeqCst, guard).with_tag(0);
-
-                        // Try linking the predecessor and successor at this level.
-                        // TODO(Amanieu): can we use release ordering here?
-                        if search.left[level][level]
-                            .compare_exchange(
-                                Shared::from(n as *const _),
-                                succ,
-                                Ordering::SeqCst,
-                                Ordering::SeqCst,
-                                guard,
-                            )
-                            .is_ok()
-                        {
-                            // Success! Decrement the reference count.
-                            n.decrement(guard);
-                        } else {
-                            // Failed! Just repeat the search to completely unlink the node.
-                            self.search_bound(Bound::Included(key), false, guard);
-                            break;
-                        }
-                    }
+                    self.unlink_removed(n, &search, key, guard);
                 }
                 return Some(entry);
             }
         }
     }
 
+    /// Removes an entry with the specified `key` from the map and returns it, but only if `pred`
+    /// returns `true` for its value.
+    ///
+    /// The entry is only removed if it is still the current one for the key after `pred` has
+    /// approved it. If it is replaced or removed by another thread in the meantime, `pred` is
+    /// called again with the new current value.
+    pub fn remove_if<Q, F>(
+        &self,
+        key: &Q,
+        mut pred: F,
+        guard: &Guard,
+    ) -> Option<RefEntry<'_, K, V, C>>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+        F: FnMut(&V) -> bool,
+    {
+        self.check_guard(guard);
+
+        unsafe {
+            // Rebind the guard to the lifetime of self. This is a bit of a
+            // hack but it allows us to return references that are not bound to
+            // the lifetime of the guard.
+            let guard = &*(guard as *const _);
+
+            loop {
+                // Try searching for the key.
+                let search = self.search_position(key, guard);
+
+                let n = search.found?;
+
+                if !pred(&n.value) {
+                    return None;
+                }
+
+                // First try incrementing the reference count because we have to return the node as
+                // an entry. If this fails, repeat the search.
+                let entry = match RefEntry::try_acquire(self, n) {
+                    Some(e) => e,
+                    None => continue,
+                };
+
+                // Try removing the node by marking its tower. If somebody else removed or replaced
+                // it first, repeat the search and check the new value.
+                if !n.mark_tower() {
+                    entry.release(guard);
+                    continue;
+                }
+
+                self.unlink_removed(n, &search, key, guard);
+                return Some(entry);
+            }
+        }
+    }
+
+    /// Unlinks a node that has just been removed by marking its tower.
+    ///
+    /// `search` must be the position the node was found at, and `key` must be its key.
+    unsafe fn unlink_removed<Q>(
+        &self,
+        n: &Node<K, V>,
+        search: &Position<'_, K, V>,
+        key: &Q,
+        guard: &Guard,
+    ) where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        // Decrement `len`.
+        self.decrement_len(n);
+
+        // Unlink the node at each level of the skip list. We could do this by simply
+        // repeating the search, but it's usually faster to unlink it manually using
+        // the `left` and `right` lists.
+        for level in (0..n.height()).rev() {
+            // TODO(Amanieu): can we use relaxed ordering here?
+            let succ = n.tower[level].load(Ordering::SeqCst, guard).with_tag(0);
+
+            // Try linking the predecessor and successor at this level.
+            // TODO(Amanieu): can we use release ordering here?
+            if search.left[level][level]
+                .compare_exchange(
+                    Shared::from(n as *const _),
+                    succ,
+                    Ordering::SeqCst,
+                    Ordering::SeqCst,
+                    guard,
+                )
+                .is_ok()
+            {
+                // Success! Decrement the reference count.
+                n.decrement(guard);
+            } else {
+                // Failed! Just repeat the search to completely unlink the node.
+                self.search_bound(Bound::Included(key), false, guard);
+                break;
+            }
+        }
+    }
+
     /// Removes an entry from the front of the skip list.
-    pub fn pop_front(&self, guard: &Guard) -> Option<RefEntry<'_, K, V>> {
+    pub fn pop_front(&self, guard: &Guard) -> Option<RefEntry<'_, K, V, C>> {
         self.check_guard(guard);
         loop {
             let e = self.front(guard)?;
@@ -1138,12 +1534,14 @@ where
                 if e.remove(guard) {
                     return Some(e);
                 }
+                // Somebody else removed the entry first. Release it and try the next one.
+                e.release(guard);
             }
         }
     }
 
     /// Removes an entry from the back of the skip list.
-    pub fn pop_back(&self, guard: &Guard) -> Option<RefEntry<'_, K, V>> {
+    pub fn pop_back(&self, guard: &Guard) -> Option<RefEntry<'_, K, V, C>> {
         self.check_guard(guard);
         loop {
             let e = self.back(guard)?;
@@ -1151,6 +1549,8 @@ where
                 if e.remove(guard) {
                     return Some(e);
                 }
+                // Somebody else removed the entry first. Release it and try the next one.
+                e.release(guard);
             }
         }
     }
@@ -1185,7 +1585,7 @@ where
                     // Try removing the current entry.
                     if e.node.mark_tower() {
                         // Success! Decrement `len`.
-                        self.hot_data.len.fetch_sub(1, Ordering::Relaxed);
+                        self.decrement_len(e.node);
                     }
 
                     entry = next;
@@ -1199,7 +1599,7 @@ where
     }
 }
 
-impl<K, V> Drop for SkipList<K, V> {
+impl<K, V, C> Drop for SkipList<K, V, C> {
     fn drop(&mut self) {
         unsafe {
             let mut node = self.head[0]
@@ -1223,9 +1623,10 @@ impl<K, V> Drop for SkipList<K, V> {
     }
 }
 
-impl<K, V> fmt::Debug for SkipList<K, V>
+impl<K, V, C> fmt::Debug for SkipList<K, V, C>
 where
-    K: Ord + fmt::Debug,
+    K: fmt::Debug,
+    C: Comparator<K>,
     V: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -1233,7 +1634,7 @@ where
     }
 }
 
-impl<K, V> IntoIterator for SkipList<K, V> {
+impl<K, V, C> IntoIterator for SkipList<K, V, C> {
     type Item = (K, V);
     type IntoIter = IntoIter<K, V>;
 
@@ -1264,13 +1665,13 @@ impl<K, V> IntoIterator for SkipList<K, V> {
 /// The lifetimes of the key and value are the same as that of the `Guard`
 /// used when creating the `Entry` (`'g`). This lifetime is also constrained to
 /// not outlive the `SkipList`.
-pub struct Entry<'a: 'g, 'g, K, V> {
-    parent: &'a SkipList<K, V>,
+pub struct Entry<'a: 'g, 'g, K, V, C = BasicComparator> {
+    parent: &'a SkipList<K, V, C>,
     node: &'g Node<K, V>,
     guard: &'g Guard,
 }
 
-impl<'a: 'g, 'g, K: 'a, V: 'a> Entry<'a, 'g, K, V> {
+impl<'a: 'g, 'g, K: 'a, V: 'a, C> Entry<'a, 'g, K, V, C> {
     /// Returns `true` if the entry is removed from the skip list.
     pub fn is_removed(&self) -> bool {
         self.node.is_removed()
@@ -1287,7 +1688,7 @@ impl<'a: 'g, 'g, K: 'a, V: 'a> Entry<'a, 'g, K, V> {
     }
 
     /// Returns a reference to the parent `SkipList`
-    pub fn skiplist(&self) -> &'a SkipList<K, V> {
+    pub fn skiplist(&self) -> &'a SkipList<K, V, C> {
         self.parent
     }
 
@@ -1296,14 +1697,15 @@ impl<'a: 'g, 'g, K: 'a, V: 'a> Entry<'a, 'g, K, V> {
     ///
     /// This method may return `None` if the reference count is already 0 and
     /// the node has been queued for deletion.
-    pub fn pin(&self) -> Option<RefEntry<'a, K, V>> {
+    pub fn pin(&self) -> Option<RefEntry<'a, K, V, C>> {
         unsafe { RefEntry::try_acquire(self.parent, self.node) }
     }
 }
 
-impl<'a: 'g, 'g, K, V> Entry<'a, 'g, K, V>
+impl<'a: 'g, 'g, K, V, C> Entry<'a, 'g, K, V, C>
 where
-    K: Ord + Send + 'static,
+    K: Send + 'static,
+    C: Comparator<K>,
     V: Send + 'static,
 {
     /// Removes the entry from the skip list.
@@ -1313,7 +1715,7 @@ where
         // Try marking the tower.
         if self.node.mark_tower() {
             // Success - the entry is removed. Now decrement `len`.
-            self.parent.hot_data.len.fetch_sub(1, Ordering::Relaxed);
+            self.parent.decrement_len(self.node);
 
             // Search for the key to unlink the node from the skip list.
             self.parent
@@ -1326,8 +1728,8 @@ where
     }
 }
 
-impl<'a: 'g, 'g, K, V> Clone for Entry<'a, 'g, K, V> {
-    fn clone(&self) -> Entry<'a, 'g, K, V> {
+impl<'a: 'g, 'g, K, V, C> Clone for Entry<'a, 'g, K, V, C> {
+    fn clone(&self) -> Entry<'a, 'g, K, V, C> {
         Entry {
             parent: self.parent,
             node: self.node,
@@ -1336,7 +1738,7 @@ impl<'a: 'g, 'g, K, V> Clone for Entry<'a, 'g, K, V> {
     }
 }
 
-impl<K, V> fmt::Debug for Entry<'_, '_, K, V>
+impl<K, V, C> fmt::Debug for Entry<'_, '_, K, V, C>
 where
     K: fmt::Debug,
     V: fmt::Debug,
@@ -1349,9 +1751,9 @@ where
     }
 }
 
-impl<'a: 'g, 'g, K, V> Entry<'a, 'g, K, V>
+impl<'a: 'g, 'g, K, V, C> Entry<'a, 'g, K, V, C>
 where
-    K: Ord,
+    C: Comparator<K>,
 {
     /// Moves to the next entry in the skip list.
     pub fn move_next(&mut self) -> bool {
@@ -1365,7 +1767,7 @@ where
     }
 
     /// Returns the next entry in the skip list.
-    pub fn next(&self) -> Option<Entry<'a, 'g, K, V>> {
+    pub fn next(&self) -> Option<Entry<'a, 'g, K, V, C>> {
         let n = self.parent.next_node(
             &self.node.tower,
             Bound::Excluded(&self.node.key),
@@ -1390,7 +1792,7 @@ where
     }
 
     /// Returns the previous entry in the skip list.
-    pub fn prev(&self) -> Option<Entry<'a, 'g, K, V>> {
+    pub fn prev(&self) -> Option<Entry<'a, 'g, K, V, C>> {
         let n = self
             .parent
             .search_bound(Bound::Excluded(&self.node.key), true, self.guard)?;
@@ -1406,12 +1808,12 @@ where
 ///
 /// You *must* call `release` to free this type, otherwise the node will be
 /// leaked. This is because releasing the entry requires a `Guard`.
-pub struct RefEntry<'a, K, V> {
-    parent: &'a SkipList<K, V>,
+pub struct RefEntry<'a, K, V, C = BasicComparator> {
+    pub(crate) parent: &'a SkipList<K, V, C>,
     node: &'a Node<K, V>,
 }
 
-impl<'a, K: 'a, V: 'a> RefEntry<'a, K, V> {
+impl<'a, K: 'a, V: 'a, C> RefEntry<'a, K, V, C> {
     /// Returns `true` if the entry is removed from the skip list.
     pub fn is_removed(&self) -> bool {
         self.node.is_removed()
@@ -1428,7 +1830,7 @@ impl<'a, K: 'a, V: 'a> RefEntry<'a, K, V> {
     }
 
     /// Returns a reference to the parent `SkipList`
-    pub fn skiplist(&self) -> &'a SkipList<K, V> {
+    pub fn skiplist(&self) -> &'a SkipList<K, V, C> {
         self.parent
     }
 
@@ -1450,9 +1852,9 @@ impl<'a, K: 'a, V: 'a> RefEntry<'a, K, V> {
     /// Tries to create a new `RefEntry` by incrementing the reference count of
     /// a node.
     unsafe fn try_acquire(
-        parent: &'a SkipList<K, V>,
+        parent: &'a SkipList<K, V, C>,
         node: &Node<K, V>,
-    ) -> Option<RefEntry<'a, K, V>> {
+    ) -> Option<RefEntry<'a, K, V, C>> {
         if node.try_increment() {
             Some(RefEntry {
                 parent,
@@ -1467,9 +1869,10 @@ impl<'a, K: 'a, V: 'a> RefEntry<'a, K, V> {
     }
 }
 
-impl<K, V> RefEntry<'_, K, V>
+impl<K, V, C> RefEntry<'_, K, V, C>
 where
-    K: Ord + Send + 'static,
+    K: Send + 'static,
+    C: Comparator<K>,
     V: Send + 'static,
 {
     /// Removes the entry from the skip list.
@@ -1481,7 +1884,7 @@ where
         // Try marking the tower.
         if self.node.mark_tower() {
             // Success - the entry is removed. Now decrement `len`.
-            self.parent.hot_data.len.fetch_sub(1, Ordering::Relaxed);
+            self.parent.decrement_len(self.node);
 
             // Search for the key to unlink the node from the skip list.
             self.parent
@@ -1494,8 +1897,8 @@ where
     }
 }
 
-impl<'a, K, V> Clone for RefEntry<'a, K, V> {
-    fn clone(&self) -> RefEntry<'a, K, V> {
+impl<'a, K, V, C> Clone for RefEntry<'a, K, V, C> {
+    fn clone(&self) -> RefEntry<'a, K, V, C> {
         unsafe {
             // Incrementing will always succeed since we're already holding a reference to the node.
             Node::try_increment(self.node);
@@ -1507,7 +1910,7 @@ impl<'a, K, V> Clone for RefEntry<'a, K, V> {
     }
 }
 
-impl<K, V> fmt::Debug for RefEntry<'_, K, V>
+impl<K, V, C> fmt::Debug for RefEntry<'_, K, V, C>
 where
     K: fmt::Debug,
     V: fmt::Debug,
@@ -1520,9 +1923,9 @@ where
     }
 }
 
-impl<'a, K, V> RefEntry<'a, K, V>
+impl<'a, K, V, C> RefEntry<'a, K, V, C>
 where
-    K: Ord,
+    C: Comparator<K>,
 {
     /// Moves to the next entry in the skip list.
     pub fn move_next(&mut self, guard: &Guard) -> bool {
@@ -1536,7 +1939,7 @@ where
     }
 
     /// Returns the next entry in the skip list.
-    pub fn next(&self, guard: &Guard) -> Option<RefEntry<'a, K, V>> {
+    pub fn next(&self, guard: &Guard) -> Option<RefEntry<'a, K, V, C>> {
         self.parent.check_guard(guard);
         unsafe {
             let mut n = self.node;
@@ -1563,7 +1966,7 @@ where
     }
 
     /// Returns the previous entry in the skip list.
-    pub fn prev(&self, guard: &Guard) -> Option<RefEntry<'a, K, V>> {
+    pub fn prev(&self, guard: &Guard) -> Option<RefEntry<'a, K, V, C>> {
         self.parent.check_guard(guard);
         unsafe {
             let mut n = self.node;
@@ -1580,20 +1983,20 @@ where
 }
 
 /// An iterator over the entries of a `SkipList`.
-pub struct Iter<'a: 'g, 'g, K, V> {
-    parent: &'a SkipList<K, V>,
+pub struct Iter<'a: 'g, 'g, K, V, C = BasicComparator> {
+    parent: &'a SkipList<K, V, C>,
     head: Option<&'g Node<K, V>>,
     tail: Option<&'g Node<K, V>>,
     guard: &'g Guard,
 }
 
-impl<'a: 'g, 'g, K: 'a, V: 'a> Iterator for Iter<'a, 'g, K, V>
+impl<'a: 'g, 'g, K: 'a, V: 'a, C> Iterator for Iter<'a, 'g, K, V, C>
 where
-    K: Ord,
+    C: Comparator<K>,
 {
-    type Item = Entry<'a, 'g, K, V>;
+    type Item = Entry<'a, 'g, K, V, C>;
 
-    fn next(&mut self) -> Option<Entry<'a, 'g, K, V>> {
+    fn next(&mut self) -> Option<Entry<'a, 'g, K, V, C>> {
         self.head = match self.head {
             Some(n) => self
                 .parent
@@ -1603,7 +2006,7 @@ where
                 .next_node(&self.parent.head, Bound::Unbounded, self.guard),
         };
         if let (Some(h), Some(t)) = (self.head, self.tail) {
-            if h.key >= t.key {
+            if self.parent.comparator.compare(&h.key, &t.key) != cmp::Ordering::Less {
                 self.head = None;
                 self.tail = None;
             }
@@ -1616,11 +2019,11 @@ where
     }
 }
 
-impl<'a: 'g, 'g, K: 'a, V: 'a> DoubleEndedIterator for Iter<'a, 'g, K, V>
+impl<'a: 'g, 'g, K: 'a, V: 'a, C> DoubleEndedIterator for Iter<'a, 'g, K, V, C>
 where
-    K: Ord,
+    C: Comparator<K>,
 {
-    fn next_back(&mut self) -> Option<Entry<'a, 'g, K, V>> {
+    fn next_back(&mut self) -> Option<Entry<'a, 'g, K, V, C>> {
         self.tail = match self.tail {
             Some(n) => self
                 .parent
@@ -1628,7 +2031,7 @@ where
             None => self.parent.search_bound(Bound::Unbounded, true, self.guard),
         };
         if let (Some(h), Some(t)) = (self.head, self.tail) {
-            if h.key >= t.key {
+            if self.parent.comparator.compare(&h.key, &t.key) != cmp::Ordering::Less {
                 self.head = None;
                 self.tail = None;
             }
@@ -1641,7 +2044,7 @@ where
     }
 }
 
-impl<K, V> fmt::Debug for Iter<'_, '_, K, V>
+impl<K, V, C> fmt::Debug for Iter<'_, '_, K, V, C>
 where
     K: fmt::Debug,
     V: fmt::Debug,
@@ -1655,13 +2058,13 @@ where
 }
 
 /// An iterator over reference-counted entries of a `SkipList`.
-pub struct RefIter<'a, K, V> {
-    parent: &'a SkipList<K, V>,
-    head: Option<RefEntry<'a, K, V>>,
-    tail: Option<RefEntry<'a, K, V>>,
+pub struct RefIter<'a, K, V, C = BasicComparator> {
+    pub(crate) parent: &'a SkipList<K, V, C>,
+    head: Option<RefEntry<'a, K, V, C>>,
+    tail: Option<RefEntry<'a, K, V, C>>,
 }
 
-impl<K, V> fmt::Debug for RefIter<'_, K, V>
+impl<K, V, C> fmt::Debug for RefIter<'_, K, V, C>
 where
     K: fmt::Debug,
     V: fmt::Debug,
@@ -1680,12 +2083,12 @@ where
     }
 }
 
-impl<'a, K: 'a, V: 'a> RefIter<'a, K, V>
+impl<'a, K: 'a, V: 'a, C> RefIter<'a, K, V, C>
 where
-    K: Ord,
+    C: Comparator<K>,
 {
     /// Advances the iterator and returns the next value.
-    pub fn next(&mut self, guard: &Guard) -> Option<RefEntry<'a, K, V>> {
+    pub fn next(&mut self, guard: &Guard) -> Option<RefEntry<'a, K, V, C>> {
         self.parent.check_guard(guard);
         self.head = match self.head {
             Some(ref e) => {
@@ -1699,7 +2102,7 @@ where
         };
         let mut finished = false;
         if let (&Some(ref h), &Some(ref t)) = (&self.head, &self.tail) {
-            if h.key() >= t.key() {
+            if self.parent.comparator.compare(h.key(), t.key()) != cmp::Ordering::Less {
                 finished = true;
             }
         }
@@ -1711,7 +2114,7 @@ where
     }
 
     /// Removes and returns an element from the end of the iterator.
-    pub fn next_back(&mut self, guard: &Guard) -> Option<RefEntry<'a, K, V>> {
+    pub fn next_back(&mut self, guard: &Guard) -> Option<RefEntry<'a, K, V, C>> {
         self.parent.check_guard(guard);
         self.tail = match self.tail {
             Some(ref e) => {
@@ -1725,7 +2128,7 @@ where
         };
         let mut finished = false;
         if let (&Some(ref h), &Some(ref t)) = (&self.head, &self.tail) {
-            if h.key() >= t.key() {
+            if self.parent.comparator.compare(h.key(), t.key()) != cmp::Ordering::Less {
                 finished = true;
             }
         }
@@ -1738,13 +2141,13 @@ where
 }
 
 /// An iterator over a subset of entries of a `SkipList`.
-pub struct Range<'a: 'g, 'g, Q, R, K, V>
+pub struct Range<'a: 'g, 'g, Q, R, K, V, C = BasicComparator>
 where
-    K: Ord + Borrow<Q>,
+    C: Comparator<K> + Comparator<K, Q>,
     R: RangeBounds<Q>,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
-    parent: &'a SkipList<K, V>,
+    parent: &'a SkipList<K, V, C>,
     head: Option<&'g Node<K, V>>,
     tail: Option<&'g Node<K, V>>,
     range: R,
@@ -1752,15 +2155,15 @@ where
     _marker: PhantomData<fn() -> Q>, // covariant over `Q`
 }
 
-impl<'a: 'g, 'g, Q, R, K: 'a, V: 'a> Iterator for Range<'a, 'g, Q, R, K, V>
+impl<'a: 'g, 'g, Q, R, K: 'a, V: 'a, C> Iterator for Range<'a, 'g, Q, R, K, V, C>
 where
-    K: Ord + Borrow<Q>,
+    C: Comparator<K> + Comparator<K, Q>,
     R: RangeBounds<Q>,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
-    type Item = Entry<'a, 'g, K, V>;
+    type Item = Entry<'a, 'g, K, V, C>;
 
-    fn next(&mut self) -> Option<Entry<'a, 'g, K, V>> {
+    fn next(&mut self) -> Option<Entry<'a, 'g, K, V, C>> {
         self.head = match self.head {
             Some(n) => self
                 .parent
@@ -1770,11 +2173,13 @@ where
                 .search_bound(self.range.start_bound(), false, self.guard),
         };
         if let Some(h) = self.head {
-            let bound = match self.tail {
-                Some(t) => Bound::Excluded(t.key.borrow()),
-                None => self.range.end_bound(),
+            let below = match self.tail {
+                Some(t) => {
+                    below_upper_bound(&self.parent.comparator, &Bound::Excluded(&t.key), &h.key)
+                }
+                None => below_upper_bound(&self.parent.comparator, &self.range.end_bound(), &h.key),
             };
-            if !below_upper_bound(&bound, h.key.borrow()) {
+            if !below {
                 self.head = None;
                 self.tail = None;
             }
@@ -1787,27 +2192,31 @@ where
     }
 }
 
-impl<'a: 'g, 'g, Q, R, K: 'a, V: 'a> DoubleEndedIterator for Range<'a, 'g, Q, R, K, V>
+impl<'a: 'g, 'g, Q, R, K: 'a, V: 'a, C> DoubleEndedIterator for Range<'a, 'g, Q, R, K, V, C>
 where
-    K: Ord + Borrow<Q>,
+    C: Comparator<K> + Comparator<K, Q>,
     R: RangeBounds<Q>,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
-    fn next_back(&mut self) -> Option<Entry<'a, 'g, K, V>> {
+    fn next_back(&mut self) -> Option<Entry<'a, 'g, K, V, C>> {
         self.tail = match self.tail {
             Some(n) => self
                 .parent
-                .search_bound(Bound::Excluded(&n.key.borrow()), true, self.guard),
+                .search_bound(Bound::Excluded(&n.key), true, self.guard),
             None => self
                 .parent
                 .search_bound(self.range.end_bound(), true, self.guard),
         };
         if let Some(t) = self.tail {
-            let bound = match self.head {
-                Some(h) => Bound::Excluded(h.key.borrow()),
-                None => self.range.start_bound(),
+            let above = match self.head {
+                Some(h) => {
+                    above_lower_bound(&self.parent.comparator, &Bound::Excluded(&h.key), &t.key)
+                }
+                None => {
+                    above_lower_bound(&self.parent.comparator, &self.range.start_bound(), &t.key)
+                }
             };
-            if !above_lower_bound(&bound, t.key.borrow()) {
+            if !above {
                 self.head = None;
                 self.tail = None;
             }
@@ -1820,12 +2229,13 @@ where
     }
 }
 
-impl<Q, R, K, V> fmt::Debug for Range<'_, '_, Q, R, K, V>
+impl<Q, R, K, V, C> fmt::Debug for Range<'_, '_, Q, R, K, V, C>
 where
-    K: Ord + Borrow<Q> + fmt::Debug,
+    K: fmt::Debug,
     V: fmt::Debug,
+    C: Comparator<K> + Comparator<K, Q>,
     R: RangeBounds<Q> + fmt::Debug,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Range")
@@ -1837,41 +2247,44 @@ where
 }
 
 /// An iterator over reference-counted subset of entries of a `SkipList`.
-pub struct RefRange<'a, Q, R, K, V>
+pub struct RefRange<'a, Q, R, K, V, C = BasicComparator>
 where
-    K: Ord + Borrow<Q>,
+    C: Comparator<K> + Comparator<K, Q>,
     R: RangeBounds<Q>,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
-    parent: &'a SkipList<K, V>,
-    pub(crate) head: Option<RefEntry<'a, K, V>>,
-    pub(crate) tail: Option<RefEntry<'a, K, V>>,
+    pub(crate) parent: &'a SkipList<K, V, C>,
+    pub(crate) head: Option<RefEntry<'a, K, V, C>>,
+    pub(crate) tail: Option<RefEntry<'a, K, V, C>>,
     pub(crate) range: R,
+    /// Set once the two ends of the range have met, so that the iterator doesn't start over.
+    finished: bool,
     _marker: PhantomData<fn() -> Q>, // covariant over `Q`
 }
 
-unsafe impl<Q, R, K, V> Send for RefRange<'_, Q, R, K, V>
+unsafe impl<Q, R, K, V, C> Send for RefRange<'_, Q, R, K, V, C>
 where
-    K: Ord + Borrow<Q>,
+    C: Comparator<K> + Comparator<K, Q>,
     R: RangeBounds<Q>,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
 }
 
-unsafe impl<Q, R, K, V> Sync for RefRange<'_, Q, R, K, V>
+unsafe impl<Q, R, K, V, C> Sync for RefRange<'_, Q, R, K, V, C>
 where
-    K: Ord + Borrow<Q>,
+    C: Comparator<K> + Comparator<K, Q>,
     R: RangeBounds<Q>,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
 }
 
-impl<Q, R, K, V> fmt::Debug for RefRange<'_, Q, R, K, V>
+impl<Q, R, K, V, C> fmt::Debug for RefRange<'_, Q, R, K, V, C>
 where
-    K: Ord + Borrow<Q> + fmt::Debug,
+    K: fmt::Debug,
     V: fmt::Debug,
+    C: Comparator<K> + Comparator<K, Q>,
     R: RangeBounds<Q> + fmt::Debug,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("RefRange")
@@ -1882,15 +2295,18 @@ where
     }
 }
 
-impl<'a, Q, R, K: 'a, V: 'a> RefRange<'a, Q, R, K, V>
+impl<'a, Q, R, K: 'a, V: 'a, C> RefRange<'a, Q, R, K, V, C>
 where
-    K: Ord + Borrow<Q>,
+    C: Comparator<K> + Comparator<K, Q>,
     R: RangeBounds<Q>,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
     /// Advances the iterator and returns the next value.
-    pub fn next(&mut self, guard: &Guard) -> Option<RefEntry<'a, K, V>> {
+    pub fn next(&mut self, guard: &Guard) -> Option<RefEntry<'a, K, V, C>> {
         self.parent.check_guard(guard);
+        if self.finished {
+            return None;
+        }
         self.head = match self.head {
             Some(ref e) => {
                 let next_head = e.next(guard);
@@ -1901,29 +2317,29 @@ where
             }
             None => try_pin_loop(|| self.parent.lower_bound(self.range.start_bound(), guard)),
         };
-        let mut finished = false;
         if let Some(ref h) = self.head {
-            let bound = match self.tail {
-                Some(ref t) => Bound::Excluded(t.key().borrow()),
-                None => self.range.end_bound(),
-            };
-            if !below_upper_bound(&bound, h.key().borrow()) {
-                finished = true;
-                unsafe {
-                    h.node.decrement(guard);
+            let below = match self.tail {
+                Some(ref t) => {
+                    below_upper_bound(&self.parent.comparator, &Bound::Excluded(t.key()), h.key())
+                }
+                None => {
+                    below_upper_bound(&self.parent.comparator, &self.range.end_bound(), h.key())
                 }
+            };
+            if below {
+                return self.head.clone();
             }
         }
-        if finished {
-            self.head = None;
-            self.tail = None;
-        }
-        self.head.clone()
+        self.finish(guard);
+        None
     }
 
     /// Removes and returns an element from the end of the iterator.
-    pub fn next_back(&mut self, guard: &Guard) -> Option<RefEntry<'a, K, V>> {
+    pub fn next_back(&mut self, guard: &Guard) -> Option<RefEntry<'a, K, V, C>> {
         self.parent.check_guard(guard);
+        if self.finished {
+            return None;
+        }
         self.tail = match self.tail {
             Some(ref e) => {
                 let next_tail = e.prev(guard);
@@ -1932,26 +2348,34 @@ where
                 }
                 next_tail
             }
-            None => try_pin_loop(|| self.parent.upper_bound(self.range.start_bound(), guard)),
+            None => try_pin_loop(|| self.parent.upper_bound(self.range.end_bound(), guard)),
         };
-        let mut finished = false;
         if let Some(ref t) = self.tail {
-            let bound = match self.head {
-                Some(ref h) => Bound::Excluded(h.key().borrow()),
-                None => self.range.end_bound(),
-            };
-            if !above_lower_bound(&bound, t.key().borrow()) {
-                finished = true;
-                unsafe {
-                    t.node.decrement(guard);
+            let above = match self.head {
+                Some(ref h) => {
+                    above_lower_bound(&self.parent.comparator, &Bound::Excluded(h.key()), t.key())
+                }
+                None => {
+                    above_lower_bound(&self.parent.comparator, &self.range.start_bound(), t.key())
                 }
+            };
+            if above {
+                return self.tail.clone();
             }
         }
-        if finished {
-            self.head = None;
-            self.tail = None;
+        self.finish(guard);
+        None
+    }
+
+    /// Releases the entries at both ends and marks the iterator as exhausted.
+    fn finish(&mut self, guard: &Guard) {
+        self.finished = true;
+        if let Some(e) = self.head.take() {
+            unsafe { e.node.decrement(guard) }
+        }
+        if let Some(e) = self.tail.take() {
+            unsafe { e.node.decrement(guard) }
         }
-        self.tail.clone()
     }
 }
 
@@ -2025,9 +2449,9 @@ impl<K, V> fmt::Debug for IntoIter<K, V> {
 
 /// Helper function to retry an operation until pinning succeeds or `None` is
 /// returned.
-pub(crate) fn try_pin_loop<'a: 'g, 'g, F, K, V>(mut f: F) -> Option<RefEntry<'a, K, V>>
+pub(crate) fn try_pin_loop<'a: 'g, 'g, F, K, V, C>(mut f: F) -> Option<RefEntry<'a, K, V, C>>
 where
-    F: FnMut() -> Option<Entry<'a, 'g, K, V>>,
+    F: FnMut() -> Option<Entry<'a, 'g, K, V, C>>,
 {
     loop {
         if let Some(e) = f()?.pin() {
@@ -2036,20 +2460,30 @@ where
     }
 }
 
-/// Helper function to check if a value is above a lower bound
-fn above_lower_bound<T: Ord + ?Sized>(bound: &Bound<&T>, other: &T) -> bool {
+/// Helper function to check if a key is above a lower bound
+fn above_lower_bound<K, Q, C>(comparator: &C, bound: &Bound<&Q>, other: &K) -> bool
+where
+    K: ?Sized,
+    Q: ?Sized,
+    C: Comparator<K, Q>,
+{
     match *bound {
         Bound::Unbounded => true,
-        Bound::Included(key) => other >= key,
-        Bound::Excluded(key) => other > key,
+        Bound::Included(key) => comparator.compare(other, key) != cmp::Ordering::Less,
+        Bound::Excluded(key) => comparator.compare(other, key) == cmp::Ordering::Greater,
     }
 }
 
-/// Helper function to check if a value is below an upper bound
-fn below_upper_bound<T: Ord + ?Sized>(bound: &Bound<&T>, other: &T) -> bool {
+/// Helper function to check if a key is below an upper bound
+fn below_upper_bound<K, Q, C>(comparator: &C, bound: &Bound<&Q>, other: &K) -> bool
+where
+    K: ?Sized,
+    Q: ?Sized,
+    C: Comparator<K, Q>,
+{
     match *bound {
         Bound::Unbounded => true,
-        Bound::Included(key) => other <= key,
-        Bound::Excluded(key) => other < key,
+        Bound::Included(key) => comparator.compare(other, key) != cmp::Ordering::Greater,
+        Bound::Excluded(key) => comparator.compare(other, key) == cmp::Ordering::Less,
     }
 }
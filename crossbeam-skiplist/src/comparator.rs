@@ -0,0 +1,94 @@
+//! Custom orderings for skip list keys. See [`Comparator`].
+//!
+//! The skip list types take a comparator as their last type parameter, which defaults to
+//! [`BasicComparator`]. Code that never names a comparator orders keys by their [`Ord`]
+//! implementation, and the bounds `K: Ord` and `K: Borrow<Q>, Q: Ord` imply the comparator bounds
+//! required by [`SkipList`] methods.
+//!
+//! [`SkipList`]: crate::SkipList
+
+use core::borrow::Borrow;
+use core::cmp::Ordering;
+
+/// Defines the order of keys in a skip list.
+///
+/// A skip list with comparator `C` and keys of type `K` requires `C: Comparator<K>` to insert keys
+/// and to keep them sorted. Lookups with a borrowed form `Q` of the key additionally require
+/// `C: Comparator<K, Q>`.
+///
+/// The comparator must define a [total order] that stays the same for as long as the skip list
+/// is in use. If it doesn't, the behavior is unspecified, but not undefined.
+///
+/// By default, skip lists use [`BasicComparator`], which orders keys by their [`Ord`]
+/// implementation. This trait is also implemented for closures and function pointers of type
+/// `Fn(&L, &R) -> Ordering`, which is the easiest way to order keys by one of their fields or in
+/// reverse order.
+///
+/// [total order]: core::cmp::Ord
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_skiplist::comparator::Comparator;
+/// use std::cmp::Ordering;
+///
+/// /// Orders strings case-insensitively.
+/// struct CaseInsensitive;
+///
+/// impl Comparator<String> for CaseInsensitive {
+///     fn compare(&self, lhs: &String, rhs: &String) -> Ordering {
+///         lhs.to_lowercase().cmp(&rhs.to_lowercase())
+///     }
+/// }
+///
+/// assert_eq!(
+///     CaseInsensitive.compare(&"Apple".to_string(), &"apple".to_string()),
+///     Ordering::Equal,
+/// );
+/// ```
+pub trait Comparator<L: ?Sized, R: ?Sized = L> {
+    /// Compares the key `lhs` of a skip list entry with `rhs`.
+    fn compare(&self, lhs: &L, rhs: &R) -> Ordering;
+}
+
+/// The default comparator, which orders keys by their [`Ord`] implementation.
+///
+/// Keys of type `K` can be compared with any borrowed form `Q` of the key, just like lookups in
+/// [`BTreeMap`].
+///
+/// [`BTreeMap`]: https://doc.rust-lang.org/std/collections/struct.BTreeMap.html
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_skiplist::comparator::{BasicComparator, Comparator};
+/// use std::cmp::Ordering;
+///
+/// let key = "b".to_string();
+/// assert_eq!(BasicComparator.compare(&key, "a"), Ordering::Greater);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BasicComparator;
+
+impl<K, Q> Comparator<K, Q> for BasicComparator
+where
+    K: Borrow<Q> + ?Sized,
+    Q: Ord + ?Sized,
+{
+    #[inline]
+    fn compare(&self, lhs: &K, rhs: &Q) -> Ordering {
+        lhs.borrow().cmp(rhs)
+    }
+}
+
+impl<L, R, F> Comparator<L, R> for F
+where
+    L: ?Sized,
+    R: ?Sized,
+    F: Fn(&L, &R) -> Ordering,
+{
+    #[inline]
+    fn compare(&self, lhs: &L, rhs: &R) -> Ordering {
+        self(lhs, rhs)
+    }
+}
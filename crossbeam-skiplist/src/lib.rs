@@ -5,6 +5,9 @@
 //! respectively, except they support safe concurrent access across
 //! multiple threads.
 //!
+//! The lower-level [`SkipList`] and [`List`], a lock-free sorted linked list, are meant as
+//! building blocks for other concurrent data structures.
+//!
 //! # Concurrent access
 //! [`SkipMap`] and [`SkipSet`] implement [`Send`] and [`Sync`],
 //! so they can be shared across threads with ease.
@@ -147,8 +150,8 @@
 //! purposes, unordered maps will suffice. In addition, unordered maps
 //! often have better performance characteristics than their ordered alternatives.
 //!
-//! Crossbeam [does not currently provide a concurrent unordered map](https://github.com/crossbeam-rs/rfcs/issues/32).
-//! That said, here are some other crates which may suit you:
+//! Crossbeam provides a concurrent unordered map in the experimental [`crossbeam-hashmap`]
+//! crate. Here are some other crates which may suit you as well:
 //! * [`DashMap`](https://docs.rs/dashmap) implements a novel concurrent hash map
 //! with good performance characteristics.
 //! * [`flurry`](https://docs.rs/flurry) is a Rust port of Java's `ConcurrentHashMap`.
@@ -158,6 +161,7 @@
 //! [`Entry`]: map::Entry
 //! [skip lists]: https://en.wikipedia.org/wiki/Skip_list
 //! [`crossbeam-epoch`]: https://docs.rs/crossbeam-epoch
+//! [`crossbeam-hashmap`]: https://docs.rs/crossbeam-hashmap
 //! [`BTreeMap`]: std::collections::BTreeMap
 //! [`BTreeSet`]: std::collections::BTreeSet
 //! [`RwLock`]: std::sync::RwLock
@@ -257,6 +261,12 @@ cfg_if! {
         pub mod base;
         #[doc(inline)]
         pub use crate::base::SkipList;
+
+        pub mod comparator;
+
+        pub mod list;
+        #[doc(inline)]
+        pub use crate::list::List;
     }
 }
 
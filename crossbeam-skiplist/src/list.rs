@@ -0,0 +1,391 @@
+//! A lock-free sorted linked list. See [`List`].
+
+use core::cmp;
+use core::fmt;
+use core::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::comparator::{BasicComparator, Comparator};
+use crate::epoch::{self, Atomic, Collector, Guard, Owned, Shared};
+
+/// A node in the list.
+struct Node<T> {
+    /// The value.
+    value: T,
+
+    /// The next node in the list.
+    ///
+    /// The tag is set once the node has been removed. After that, the pointer never changes.
+    next: Atomic<Node<T>>,
+}
+
+/// A lock-free sorted linked list.
+///
+/// This is the list of Harris, with the improvements of Michael: a node is removed by first
+/// marking its `next` pointer, after which any thread may unlink it from the list. Removed nodes
+/// are destroyed through the collector of the list, once no thread pinned with it can be reading
+/// them anymore.
+///
+/// Operations take `O(n)` time, so the list is meant as a building block for other concurrent
+/// data structures, such as the buckets of a hash table, rather than as a collection on its own.
+/// Lookups and iteration never write to the list. Insertions and removals are lock-free.
+///
+/// Like [`SkipList`], the list is sorted by a [`Comparator`] and doesn't hold duplicates.
+///
+/// [`SkipList`]: crate::SkipList
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_epoch as epoch;
+/// use crossbeam_skiplist::List;
+///
+/// let list = List::new(epoch::default_collector().clone());
+/// let guard = &epoch::pin();
+///
+/// list.insert(3, guard).unwrap();
+/// list.insert(1, guard).unwrap();
+/// list.insert(2, guard).unwrap();
+/// assert_eq!(list.insert(2, guard), Err(2));
+///
+/// assert!(list.contains(&1, guard));
+/// assert_eq!(list.remove(&1, guard), Some(&1));
+///
+/// let values: Vec<_> = list.iter(guard).cloned().collect();
+/// assert_eq!(values, [2, 3]);
+/// ```
+pub struct List<T, C = BasicComparator> {
+    /// The first node of the list.
+    head: Atomic<Node<T>>,
+
+    /// The `Collector` associated with this list.
+    collector: Collector,
+
+    /// The number of values in the list.
+    len: AtomicUsize,
+
+    /// Defines the order of the values.
+    comparator: C,
+}
+
+unsafe impl<T: Send + Sync, C: Send> Send for List<T, C> {}
+unsafe impl<T: Send + Sync, C: Sync> Sync for List<T, C> {}
+
+impl<T> List<T> {
+    /// Returns a new, empty list.
+    pub fn new(collector: Collector) -> List<T> {
+        List::with_comparator(collector, BasicComparator)
+    }
+}
+
+impl<T, C> List<T, C> {
+    /// Returns a new, empty list that orders its values with `comparator`.
+    pub fn with_comparator(collector: Collector, comparator: C) -> List<T, C> {
+        List {
+            head: Atomic::null(),
+            collector,
+            len: AtomicUsize::new(0),
+            comparator,
+        }
+    }
+
+    /// Returns the comparator that orders the values of the list.
+    pub fn comparator(&self) -> &C {
+        &self.comparator
+    }
+
+    /// Returns the collector that destroys the removed values of the list.
+    pub fn collector(&self) -> &Collector {
+        &self.collector
+    }
+
+    /// Returns `true` if the list is empty.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the number of values in the list.
+    ///
+    /// If the list is being concurrently modified, consider the returned number just an
+    /// approximation without any guarantees.
+    pub fn len(&self) -> usize {
+        let len = self.len.load(Ordering::Relaxed);
+
+        // Concurrent insertions and removals may briefly make the counter underflow. We treat
+        // such values as 0.
+        if len > isize::max_value() as usize {
+            0
+        } else {
+            len
+        }
+    }
+
+    /// Ensures that all `Guard`s used with the list come from the same `Collector`.
+    fn check_guard(&self, guard: &Guard) {
+        if let Some(c) = guard.collector() {
+            assert!(c == &self.collector);
+        }
+    }
+
+    /// Returns an iterator over all values in the list.
+    pub fn iter<'a: 'g, 'g>(&'a self, guard: &'g Guard) -> Iter<'g, T> {
+        self.check_guard(guard);
+        Iter {
+            next: self.head.load(Ordering::Acquire, guard),
+            guard,
+        }
+    }
+
+    /// Returns the smallest value in the list.
+    pub fn front<'a: 'g, 'g>(&'a self, guard: &'g Guard) -> Option<&'g T> {
+        self.iter(guard).next()
+    }
+
+    /// Returns the first node whose value is not less than `key`, without modifying the list.
+    ///
+    /// Removed nodes are skipped.
+    fn find<'g, Q>(&self, key: &Q, guard: &'g Guard) -> Option<&'g Node<T>>
+    where
+        Q: ?Sized,
+        C: Comparator<T, Q>,
+    {
+        let mut curr = self.head.load(Ordering::Acquire, guard);
+        while let Some(c) = unsafe { curr.as_ref() } {
+            let next = c.next.load(Ordering::Acquire, guard);
+            if next.tag() == 0 && self.comparator.compare(&c.value, key) != cmp::Ordering::Less {
+                return Some(c);
+            }
+            // Removed nodes keep pointing into the list, so it is fine to walk past them.
+            curr = next.with_tag(0);
+        }
+        None
+    }
+
+    /// Returns `true` if the list contains a value equal to `key`.
+    pub fn contains<Q>(&self, key: &Q, guard: &Guard) -> bool
+    where
+        Q: ?Sized,
+        C: Comparator<T, Q>,
+    {
+        self.get(key, guard).is_some()
+    }
+
+    /// Returns the value equal to `key`, if there is one.
+    pub fn get<'a: 'g, 'g, Q>(&'a self, key: &Q, guard: &'g Guard) -> Option<&'g T>
+    where
+        Q: ?Sized,
+        C: Comparator<T, Q>,
+    {
+        self.check_guard(guard);
+        let n = self.find(key, guard)?;
+        if self.comparator.compare(&n.value, key) == cmp::Ordering::Equal {
+            Some(&n.value)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the smallest value that is not less than `key`, if there is one.
+    pub fn lower_bound<'a: 'g, 'g, Q>(&'a self, key: &Q, guard: &'g Guard) -> Option<&'g T>
+    where
+        Q: ?Sized,
+        C: Comparator<T, Q>,
+    {
+        self.check_guard(guard);
+        self.find(key, guard).map(|n| &n.value)
+    }
+}
+
+impl<T, C> List<T, C>
+where
+    T: Send + 'static,
+{
+    /// Searches for the first node whose value is not less than `key`, unlinking removed nodes
+    /// on the way.
+    ///
+    /// Returns that node, which may be null, and the pointer that links it into the list.
+    fn search<'g, Q>(
+        &'g self,
+        key: &Q,
+        guard: &'g Guard,
+    ) -> (&'g Atomic<Node<T>>, Shared<'g, Node<T>>)
+    where
+        Q: ?Sized,
+        C: Comparator<T, Q>,
+    {
+        'search: loop {
+            let mut pred = &self.head;
+            let mut curr = pred.load(Ordering::Acquire, guard);
+
+            while let Some(c) = unsafe { curr.as_ref() } {
+                let succ = c.next.load(Ordering::Acquire, guard);
+
+                if succ.tag() == 1 {
+                    // `curr` has been removed, so try unlinking it. If `pred` has changed in the
+                    // meantime, or has been removed itself, start over from the head.
+                    match pred.compare_exchange(
+                        curr,
+                        succ.with_tag(0),
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                        guard,
+                    ) {
+                        Ok(_) => {
+                            // Only the thread that unlinks a node destroys it.
+                            unsafe { guard.defer_destroy(curr) };
+                            curr = succ.with_tag(0);
+                            continue;
+                        }
+                        Err(_) => continue 'search,
+                    }
+                }
+
+                if self.comparator.compare(&c.value, key) != cmp::Ordering::Less {
+                    break;
+                }
+                pred = &c.next;
+                curr = succ;
+            }
+
+            return (pred, curr);
+        }
+    }
+
+    /// Inserts `value` into the list and returns a reference to it.
+    ///
+    /// If the list already contains an equal value, nothing is inserted and `value` is returned
+    /// as an error.
+    pub fn insert<'a: 'g, 'g>(&'a self, value: T, guard: &'g Guard) -> Result<&'g T, T>
+    where
+        C: Comparator<T>,
+    {
+        self.check_guard(guard);
+        let mut new = Owned::new(Node {
+            value,
+            next: Atomic::null(),
+        });
+
+        loop {
+            let (pred, curr) = self.search(&new.value, guard);
+            if let Some(c) = unsafe { curr.as_ref() } {
+                if self.comparator.compare(&c.value, &new.value) == cmp::Ordering::Equal {
+                    return Err(new.into_box().value);
+                }
+            }
+
+            new.next.store(curr, Ordering::Relaxed);
+            match pred.compare_exchange(curr, new, Ordering::AcqRel, Ordering::Acquire, guard) {
+                Ok(n) => {
+                    self.len.fetch_add(1, Ordering::Relaxed);
+                    return Ok(unsafe { &n.deref().value });
+                }
+                Err(err) => new = err.new,
+            }
+        }
+    }
+
+    /// Removes the value equal to `key` from the list, and returns a reference to it.
+    ///
+    /// The value is destroyed once no thread pinned with the collector of the list can be reading
+    /// it anymore, so the reference stays valid for as long as `guard` is alive.
+    pub fn remove<'a: 'g, 'g, Q>(&'a self, key: &Q, guard: &'g Guard) -> Option<&'g T>
+    where
+        Q: ?Sized,
+        C: Comparator<T, Q>,
+    {
+        self.check_guard(guard);
+
+        loop {
+            let (pred, curr) = self.search(key, guard);
+            let c = unsafe { curr.as_ref() }?;
+            if self.comparator.compare(&c.value, key) != cmp::Ordering::Equal {
+                return None;
+            }
+
+            // Mark the node as removed. If this fails, the node has either been removed by
+            // another thread, or a node has been inserted right after it, so search again.
+            let succ = c.next.load(Ordering::Acquire, guard);
+            if succ.tag() == 1
+                || c.next
+                    .compare_exchange(
+                        succ,
+                        succ.with_tag(1),
+                        Ordering::AcqRel,
+                        Ordering::Acquire,
+                        guard,
+                    )
+                    .is_err()
+            {
+                continue;
+            }
+            self.len.fetch_sub(1, Ordering::Relaxed);
+
+            // Try unlinking the node. If this fails, a search will unlink it.
+            if pred
+                .compare_exchange(curr, succ, Ordering::AcqRel, Ordering::Acquire, guard)
+                .is_ok()
+            {
+                unsafe { guard.defer_destroy(curr) };
+            } else {
+                self.search(key, guard);
+            }
+            return Some(&c.value);
+        }
+    }
+}
+
+impl<T, C> Drop for List<T, C> {
+    fn drop(&mut self) {
+        unsafe {
+            // Unprotected loads are okay because this function is the only one currently using
+            // the list. Nodes that have been unlinked are no longer reachable, and have been
+            // handed over to the collector.
+            let guard = epoch::unprotected();
+            let mut node = self.head.load(Ordering::Relaxed, guard);
+            while !node.is_null() {
+                let next = node.deref().next.load(Ordering::Relaxed, guard);
+                drop(node.into_owned());
+                node = next.with_tag(0);
+            }
+        }
+    }
+}
+
+impl<T, C> fmt::Debug for List<T, C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("List { .. }")
+    }
+}
+
+/// An iterator over the values of a `List`.
+pub struct Iter<'g, T> {
+    next: Shared<'g, Node<T>>,
+    guard: &'g Guard,
+}
+
+impl<'g, T> Iterator for Iter<'g, T> {
+    type Item = &'g T;
+
+    fn next(&mut self) -> Option<&'g T> {
+        loop {
+            let n = unsafe { self.next.as_ref() }?;
+            let succ = n.next.load(Ordering::Acquire, self.guard);
+            self.next = succ.with_tag(0);
+
+            // Skip values that have been removed.
+            if succ.tag() == 0 {
+                return Some(&n.value);
+            }
+        }
+    }
+}
+
+impl<T> fmt::Debug for Iter<'_, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Iter")
+            .field("next", &unsafe { self.next.as_ref() }.map(|n| &n.value))
+            .finish()
+    }
+}
@@ -1,6 +1,5 @@
 //! An ordered map based on a lock-free skip list. See [`SkipMap`].
 
-use std::borrow::Borrow;
 use std::fmt;
 use std::iter::FromIterator;
 use std::mem::ManuallyDrop;
@@ -8,7 +7,8 @@ use std::ops::{Bound, RangeBounds};
 use std::ptr;
 
 use crate::base::{self, try_pin_loop};
-use crate::epoch;
+use crate::comparator::{BasicComparator, Comparator};
+use crate::epoch::{self, Collector, Guard};
 
 /// An ordered map based on a lock-free skip list.
 ///
@@ -16,8 +16,8 @@ use crate::epoch;
 /// concurrent access across multiple threads.
 ///
 /// [`BTreeMap`]: std::collections::BTreeMap
-pub struct SkipMap<K, V> {
-    inner: base::SkipList<K, V>,
+pub struct SkipMap<K, V, C = BasicComparator> {
+    inner: base::SkipList<K, V, C>,
 }
 
 impl<K, V> SkipMap<K, V> {
@@ -36,6 +36,153 @@ impl<K, V> SkipMap<K, V> {
         }
     }
 
+    /// Returns a new, empty map that hands the garbage of removed entries to `collector`.
+    ///
+    /// Removed entries are destroyed by an epoch-based garbage collector once no thread can
+    /// observe them anymore. By default, that is the global collector shared with the rest of the
+    /// program, where garbage may linger for a long time. A dedicated collector, possibly tuned
+    /// with a [`CollectorBuilder`], keeps the garbage of this map separate, so that it can be
+    /// reclaimed promptly with [`flush`].
+    ///
+    /// Every operation on the map pins the current thread with its collector. If the current
+    /// thread isn't registered with it through [`Collector::with`], a new participant is
+    /// registered for every operation, which is much slower.
+    ///
+    /// [`CollectorBuilder`]: crossbeam_epoch::CollectorBuilder
+    /// [`flush`]: SkipMap::flush
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crossbeam_epoch::Collector;
+    /// use crossbeam_skiplist::SkipMap;
+    ///
+    /// let collector = Collector::new();
+    /// let cache = SkipMap::with_collector(collector.clone());
+    ///
+    /// collector.with(|| {
+    ///     for i in 0..1000 {
+    ///         cache.insert(i, vec![0u8; 64]);
+    ///     }
+    ///
+    ///     // Evict the oldest half of the cache and reclaim it right away.
+    ///     while cache.front().map_or(false, |e| *e.key() < 500) {
+    ///         cache.pop_front();
+    ///     }
+    ///     cache.flush();
+    /// });
+    /// assert_eq!(cache.len(), 500);
+    /// ```
+    pub fn with_collector(collector: Collector) -> SkipMap<K, V> {
+        SkipMap {
+            inner: base::SkipList::new(collector),
+        }
+    }
+}
+
+impl<K, V, C> SkipMap<K, V, C> {
+    /// Returns a new, empty map that orders its keys with `comparator`.
+    ///
+    /// This is useful for keys that have no [`Ord`] implementation, or should be ordered
+    /// differently from it. Any closure or function of type `Fn(&K, &K) -> Ordering` can be used
+    /// as a comparator, as well as any type implementing [`Comparator`].
+    ///
+    /// Entries can only be looked up by a borrowed form `Q` of the key if the comparator
+    /// implements `Comparator<K, Q>`. Closures only support lookups by `&K`.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crossbeam_skiplist::SkipMap;
+    ///
+    /// struct Task {
+    ///     deadline: u64,
+    ///     name: &'static str,
+    /// }
+    ///
+    /// // Order tasks by their deadline only, latest first.
+    /// let tasks = SkipMap::with_comparator(|a: &Task, b: &Task| b.deadline.cmp(&a.deadline));
+    /// tasks.insert(Task { deadline: 10, name: "write" }, ());
+    /// tasks.insert(Task { deadline: 30, name: "review" }, ());
+    /// tasks.insert(Task { deadline: 20, name: "test" }, ());
+    ///
+    /// let names: Vec<_> = tasks.iter().map(|e| e.key().name).collect();
+    /// assert_eq!(names, ["review", "test", "write"]);
+    /// ```
+    pub fn with_comparator(comparator: C) -> SkipMap<K, V, C> {
+        SkipMap {
+            inner: base::SkipList::with_comparator(epoch::default_collector().clone(), comparator),
+        }
+    }
+
+    /// Returns the comparator that orders the keys of the map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crossbeam_skiplist::comparator::BasicComparator;
+    /// use crossbeam_skiplist::SkipMap;
+    ///
+    /// let map: SkipMap<i32, i32> = SkipMap::new();
+    /// assert_eq!(*map.comparator(), BasicComparator);
+    /// ```
+    pub fn comparator(&self) -> &C {
+        self.inner.comparator()
+    }
+
+    /// Returns the collector that destroys the removed entries of the map.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crossbeam_epoch::{self as epoch, Collector};
+    /// use crossbeam_skiplist::SkipMap;
+    ///
+    /// let map: SkipMap<i32, i32> = SkipMap::new();
+    /// assert!(map.collector() == epoch::default_collector());
+    ///
+    /// let collector = Collector::new();
+    /// let map: SkipMap<i32, i32> = SkipMap::with_collector(collector.clone());
+    /// assert!(map.collector() == &collector);
+    /// ```
+    pub fn collector(&self) -> &Collector {
+        self.inner.collector()
+    }
+
+    /// Hands the garbage of removed entries over to the collector and destroys all garbage that
+    /// is ready.
+    ///
+    /// Removed entries aren't destroyed right away, since other threads may still be reading
+    /// them. Their garbage is first buffered by the thread that removed them, and only destroyed
+    /// after the epoch of the collector has advanced a couple of times. This method moves the
+    /// garbage buffered by the current thread into the global queue of the collector, tries to
+    /// advance the epoch, and then destroys everything that has expired.
+    ///
+    /// Garbage from the most recent removals usually isn't ready yet, so this is meant to be
+    /// called regularly, for example after each round of evicting a large range of entries. It
+    /// is most effective together with a dedicated collector, see [`with_collector`].
+    ///
+    /// [`with_collector`]: SkipMap::with_collector
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crossbeam_skiplist::SkipMap;
+    ///
+    /// let map = SkipMap::new();
+    /// for i in 0..100 {
+    ///     map.insert(i, i.to_string());
+    /// }
+    ///
+    /// map.clear();
+    /// map.flush();
+    /// ```
+    pub fn flush(&self) {
+        let guard = &pin(&self.inner);
+        guard.flush();
+        self.inner.collector().try_collect(guard);
+    }
+
     /// Returns `true` if the map is empty.
     ///
     /// # Example
@@ -57,6 +204,9 @@ impl<K, V> SkipMap<K, V> {
     /// If the map is being concurrently modified, consider the returned number just an
     /// approximation without any guarantees.
     ///
+    /// The number of entries is tracked by a counter that is updated on every insertion and
+    /// removal, so this method doesn't need to traverse the map.
+    ///
     /// # Example
     /// ```
     /// use crossbeam_skiplist::SkipMap;
@@ -76,9 +226,9 @@ impl<K, V> SkipMap<K, V> {
     }
 }
 
-impl<K, V> SkipMap<K, V>
+impl<K, V, C> SkipMap<K, V, C>
 where
-    K: Ord,
+    C: Comparator<K>,
 {
     /// Returns the entry with the smallest key.
     ///
@@ -95,8 +245,8 @@ where
     /// numbers.insert(6, "six");
     /// assert_eq!(*numbers.front().unwrap().value(), "five");
     /// ```
-    pub fn front(&self) -> Option<Entry<'_, K, V>> {
-        let guard = &epoch::pin();
+    pub fn front(&self) -> Option<Entry<'_, K, V, C>> {
+        let guard = &pin(&self.inner);
         try_pin_loop(|| self.inner.front(guard)).map(Entry::new)
     }
 
@@ -115,8 +265,8 @@ where
     /// numbers.insert(6, "six");
     /// assert_eq!(*numbers.back().unwrap().value(), "six");
     /// ```
-    pub fn back(&self) -> Option<Entry<'_, K, V>> {
-        let guard = &epoch::pin();
+    pub fn back(&self) -> Option<Entry<'_, K, V, C>> {
+        let guard = &pin(&self.inner);
         try_pin_loop(|| self.inner.back(guard)).map(Entry::new)
     }
 
@@ -134,10 +284,10 @@ where
     /// ```
     pub fn contains_key<Q>(&self, key: &Q) -> bool
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
-        let guard = &epoch::pin();
+        let guard = &pin(&self.inner);
         self.inner.contains_key(key, guard)
     }
 
@@ -156,12 +306,12 @@ where
     /// numbers.insert("six", 6);
     /// assert_eq!(*numbers.get("six").unwrap().value(), 6);
     /// ```
-    pub fn get<Q>(&self, key: &Q) -> Option<Entry<'_, K, V>>
+    pub fn get<Q>(&self, key: &Q) -> Option<Entry<'_, K, V, C>>
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
-        let guard = &epoch::pin();
+        let guard = &pin(&self.inner);
         try_pin_loop(|| self.inner.get(key, guard)).map(Entry::new)
     }
 
@@ -191,12 +341,12 @@ where
     /// let greater_than_thirteen = numbers.lower_bound(Excluded(&13));
     /// assert!(greater_than_thirteen.is_none());
     /// ```
-    pub fn lower_bound<'a, Q>(&'a self, bound: Bound<&Q>) -> Option<Entry<'a, K, V>>
+    pub fn lower_bound<'a, Q>(&'a self, bound: Bound<&Q>) -> Option<Entry<'a, K, V, C>>
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
-        let guard = &epoch::pin();
+        let guard = &pin(&self.inner);
         try_pin_loop(|| self.inner.lower_bound(bound, guard)).map(Entry::new)
     }
 
@@ -223,12 +373,12 @@ where
     /// let less_than_six = numbers.upper_bound(Excluded(&6));
     /// assert!(less_than_six.is_none());
     /// ```
-    pub fn upper_bound<'a, Q>(&'a self, bound: Bound<&Q>) -> Option<Entry<'a, K, V>>
+    pub fn upper_bound<'a, Q>(&'a self, bound: Bound<&Q>) -> Option<Entry<'a, K, V, C>>
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
-        let guard = &epoch::pin();
+        let guard = &pin(&self.inner);
         try_pin_loop(|| self.inner.upper_bound(bound, guard)).map(Entry::new)
     }
 
@@ -249,11 +399,66 @@ where
     /// let jobs_age = ages.get_or_insert("Steve Jobs", -1);
     /// assert_eq!(*jobs_age.value(), 65);
     /// ```
-    pub fn get_or_insert(&self, key: K, value: V) -> Entry<'_, K, V> {
-        let guard = &epoch::pin();
+    pub fn get_or_insert(&self, key: K, value: V) -> Entry<'_, K, V, C> {
+        let guard = &pin(&self.inner);
         Entry::new(self.inner.get_or_insert(key, value, guard))
     }
 
+    /// Finds an entry with the specified key, or inserts a new `key`-`value` pair if none exist,
+    /// where value is calculated with a function.
+    ///
+    /// The function is called at most once, and only if there is no entry with the key. This
+    /// avoids computing the value up front when it is expensive and usually already present.
+    ///
+    /// This function returns an [`Entry`] which
+    /// can be used to access the key's associated value.
+    ///
+    /// # Example
+    /// ```
+    /// use crossbeam_skiplist::SkipMap;
+    ///
+    /// let ages = SkipMap::new();
+    /// let gates_age = ages.get_or_insert_with("Bill Gates", || 64);
+    /// assert_eq!(*gates_age.value(), 64);
+    ///
+    /// ages.insert("Steve Jobs", 65);
+    /// let jobs_age = ages.get_or_insert_with("Steve Jobs", || unreachable!());
+    /// assert_eq!(*jobs_age.value(), 65);
+    /// ```
+    pub fn get_or_insert_with<F>(&self, key: K, value_fn: F) -> Entry<'_, K, V, C>
+    where
+        F: FnOnce() -> V,
+    {
+        let guard = &pin(&self.inner);
+        Entry::new(self.inner.get_or_insert_with(key, value_fn, guard))
+    }
+
+    /// Inserts a `key`-`value` pair into the map, but only if there is no entry with this key yet.
+    ///
+    /// On success, this function returns the new [`Entry`]. If the key is already present, the
+    /// map is left unchanged, `value` is dropped, and the existing [`Entry`] is returned as an
+    /// error.
+    ///
+    /// # Example
+    /// ```
+    /// use crossbeam_skiplist::SkipMap;
+    ///
+    /// let sessions = SkipMap::new();
+    ///
+    /// let entry = sessions.try_insert(7, "alice").unwrap();
+    /// assert_eq!(*entry.value(), "alice");
+    ///
+    /// let existing = sessions.try_insert(7, "bob").unwrap_err();
+    /// assert_eq!(*existing.value(), "alice");
+    /// ```
+    pub fn try_insert(&self, key: K, value: V) -> Result<Entry<'_, K, V, C>, Entry<'_, K, V, C>> {
+        let guard = &pin(&self.inner);
+        match self.inner.try_insert(key, value, guard) {
+            Ok(e) => Ok(Entry::new(e)),
+            Err(e) => Err(Entry::new(e)),
+        }
+    }
+
     /// Returns an iterator over all entries in the map,
     /// sorted by key.
     ///
@@ -276,7 +481,7 @@ where
     ///     println!("{} is {}", number, number_str);
     /// }
     /// ```
-    pub fn iter(&self) -> Iter<'_, K, V> {
+    pub fn iter(&self) -> Iter<'_, K, V, C> {
         Iter {
             inner: self.inner.ref_iter(),
         }
@@ -287,9 +492,14 @@ where
     /// This iterator returns [`Entry`]s which
     /// can be used to access keys and their associated values.
     ///
+    /// The range can be given as any range expression, or as a pair of [`Bound`]s. The iterator is
+    /// double-ended, so it can also be used to scan the range backwards, starting from its last
+    /// entry.
+    ///
     /// # Example
     /// ```
     /// use crossbeam_skiplist::SkipMap;
+    /// use std::ops::Bound::{Excluded, Unbounded};
     ///
     /// let numbers = SkipMap::new();
     /// numbers.insert(6, "six");
@@ -302,22 +512,67 @@ where
     ///     let number_str = entry.value();
     ///     println!("{} is {}", number, number_str);
     /// }
+    ///
+    /// // Scan the numbers greater than 6 backwards.
+    /// let keys: Vec<_> = numbers
+    ///     .range((Excluded(6), Unbounded))
+    ///     .rev()
+    ///     .map(|entry| *entry.key())
+    ///     .collect();
+    /// assert_eq!(keys, [12, 7]);
     /// ```
-    pub fn range<Q, R>(&self, range: R) -> Range<'_, Q, R, K, V>
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, Q, R, K, V, C>
     where
-        K: Borrow<Q>,
+        C: Comparator<K, Q>,
         R: RangeBounds<Q>,
-        Q: Ord + ?Sized,
+        Q: ?Sized,
     {
         Range {
             inner: self.inner.ref_range(range),
         }
     }
+
+    /// Returns a cursor over the map, which doesn't point at any entry yet.
+    ///
+    /// Unlike an iterator, a [`Cursor`] can move back and forth and seek to any key. It stays
+    /// valid while other threads modify the map: if its current entry gets removed, it simply
+    /// continues from that entry's key when it moves again. This makes cursors a good fit for
+    /// long-running scans, such as compacting or snapshotting the map.
+    ///
+    /// # Example
+    /// ```
+    /// use crossbeam_skiplist::SkipMap;
+    /// use std::ops::Bound::Included;
+    ///
+    /// let numbers = SkipMap::new();
+    /// for i in 0..10 {
+    ///     numbers.insert(i, i * 10);
+    /// }
+    ///
+    /// let mut cursor = numbers.cursor();
+    /// assert!(cursor.seek_lower_bound(Included(&4)));
+    /// assert_eq!(cursor.key(), Some(&4));
+    ///
+    /// // The current entry may be removed without disturbing the cursor.
+    /// numbers.remove(&4);
+    /// numbers.remove(&5);
+    /// assert!(cursor.move_next());
+    /// assert_eq!(cursor.key(), Some(&6));
+    /// assert!(cursor.move_prev());
+    /// assert_eq!(cursor.key(), Some(&3));
+    /// ```
+    pub fn cursor(&self) -> Cursor<'_, K, V, C> {
+        Cursor {
+            map: self,
+            current: None,
+        }
+    }
 }
 
-impl<K, V> SkipMap<K, V>
+impl<K, V, C> SkipMap<K, V, C>
 where
-    K: Ord + Send + 'static,
+    K: Send + 'static,
+    C: Comparator<K>,
     V: Send + 'static,
 {
     /// Inserts a `key`-`value` pair into the map and returns the new entry.
@@ -337,11 +592,86 @@ where
     ///
     /// assert_eq!(*map.get("key").unwrap().value(), "value");
     /// ```
-    pub fn insert(&self, key: K, value: V) -> Entry<'_, K, V> {
-        let guard = &epoch::pin();
+    pub fn insert(&self, key: K, value: V) -> Entry<'_, K, V, C> {
+        let guard = &pin(&self.inner);
         Entry::new(self.inner.insert(key, value, guard))
     }
 
+    /// Inserts a `key`-`value` pair into the map and returns the new entry, along with the entry
+    /// it replaced, if any.
+    ///
+    /// Unlike [`insert`], which removes the existing entry before inserting the new one, this
+    /// replaces the existing entry atomically: other threads never observe the key as missing.
+    /// The replaced [`Entry`] can still be used to access the previous value, which will not
+    /// actually be dropped until all references to it have gone out of scope.
+    ///
+    /// [`insert`]: SkipMap::insert
+    ///
+    /// # Example
+    /// ```
+    /// use crossbeam_skiplist::SkipMap;
+    ///
+    /// let config = SkipMap::new();
+    ///
+    /// let (entry, prev) = config.insert_or_replace("timeout", 30);
+    /// assert_eq!(*entry.value(), 30);
+    /// assert!(prev.is_none());
+    ///
+    /// let (entry, prev) = config.insert_or_replace("timeout", 60);
+    /// assert_eq!(*entry.value(), 60);
+    /// assert_eq!(*prev.unwrap().value(), 30);
+    /// assert_eq!(config.len(), 1);
+    /// ```
+    pub fn insert_or_replace(&self, key: K, value: V) -> Replaced<'_, K, V, C> {
+        let guard = &pin(&self.inner);
+        let (entry, prev) = self.inner.insert_or_replace(key, value, guard);
+        (Entry::new(entry), prev.map(Entry::new))
+    }
+
+    /// Computes a new value for the entry with the specified `key`.
+    ///
+    /// The function is called with the current value, or `None` if there is no entry with this
+    /// key. If it returns a new value, the current entry is replaced with a new one holding that
+    /// value. Unlike removing the entry and calling [`insert`], the replacement is atomic: other
+    /// threads never observe the key as missing. If it returns `None`, the map is left unchanged.
+    ///
+    /// The new value is only installed if the entry the function looked at is still the current
+    /// one. If another thread replaces or removes it in the meantime, the function is called
+    /// again with the new current value, so it may be called several times.
+    ///
+    /// This function returns the resulting [`Entry`], or `None` if there is no entry with the key
+    /// afterwards.
+    ///
+    /// [`insert`]: SkipMap::insert
+    ///
+    /// # Example
+    /// ```
+    /// use crossbeam_skiplist::SkipMap;
+    ///
+    /// let counts = SkipMap::new();
+    ///
+    /// // Increment the count, starting from zero.
+    /// for _ in 0..3 {
+    ///     counts.compute("apples", |old| Some(old.map_or(1, |n| n + 1)));
+    /// }
+    /// assert_eq!(*counts.get("apples").unwrap().value(), 3);
+    ///
+    /// // Only update the count if it is below a limit.
+    /// let entry = counts.compute("apples", |old| old.filter(|&&n| n < 3).map(|n| n + 1));
+    /// assert_eq!(*entry.unwrap().value(), 3);
+    ///
+    /// // Returning `None` for a missing key doesn't insert it.
+    /// assert!(counts.compute("pears", |_| None).is_none());
+    /// assert!(!counts.contains_key("pears"));
+    /// ```
+    pub fn compute<F>(&self, key: K, f: F) -> Option<Entry<'_, K, V, C>>
+    where
+        F: FnMut(Option<&V>) -> Option<V>,
+    {
+        let guard = &pin(&self.inner);
+        self.inner.compute(key, f, guard).map(Entry::new)
+    }
+
     /// Removes an entry with the specified `key` from the map and returns it.
     ///
     /// The value will not actually be dropped until all references to it have gone
@@ -360,18 +690,63 @@ where
     /// map.insert("key", "value");
     /// assert_eq!(*map.remove("key").unwrap().value(), "value");
     /// ```
-    pub fn remove<Q>(&self, key: &Q) -> Option<Entry<'_, K, V>>
+    pub fn remove<Q>(&self, key: &Q) -> Option<Entry<'_, K, V, C>>
     where
-        K: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<K, Q>,
+        Q: ?Sized,
     {
-        let guard = &epoch::pin();
+        let guard = &pin(&self.inner);
         self.inner.remove(key, guard).map(Entry::new)
     }
 
+    /// Removes an entry with the specified `key` from the map and returns it, but only if the
+    /// predicate returns `true` for its value.
+    ///
+    /// The check and the removal happen atomically: if another thread replaces the entry, for
+    /// example with [`insert`] or [`compute`], after the predicate has looked at it, the new
+    /// entry is not removed blindly. Instead, the predicate is called again with the new value,
+    /// so it may be called several times.
+    ///
+    /// This function returns the removed [`Entry`], or `None` if there is no entry with the key or
+    /// the predicate returned `false`.
+    ///
+    /// [`insert`]: SkipMap::insert
+    /// [`compute`]: SkipMap::compute
+    ///
+    /// # Example
+    /// ```
+    /// use crossbeam_skiplist::SkipMap;
+    ///
+    /// // Cached values along with their version.
+    /// let cache = SkipMap::new();
+    /// cache.insert("config", 1);
+    ///
+    /// // Invalidate the entry only if it hasn't been refreshed since version 1.
+    /// cache.insert("config", 2);
+    /// assert!(cache.remove_if("config", |&version| version <= 1).is_none());
+    /// assert!(cache.contains_key("config"));
+    ///
+    /// let removed = cache.remove_if("config", |&version| version <= 2).unwrap();
+    /// assert_eq!(*removed.value(), 2);
+    /// assert!(!cache.contains_key("config"));
+    /// ```
+    pub fn remove_if<Q, F>(&self, key: &Q, pred: F) -> Option<Entry<'_, K, V, C>>
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+        F: FnMut(&V) -> bool,
+    {
+        let guard = &pin(&self.inner);
+        self.inner.remove_if(key, pred, guard).map(Entry::new)
+    }
+
     /// Removes the entry with the lowest key
     /// from the map. Returns the removed entry.
     ///
+    /// Finding and removing the entry is a single atomic step: when several threads pop
+    /// concurrently, each entry is returned to exactly one of them. This makes `SkipMap` usable
+    /// as a concurrent priority queue.
+    ///
     /// The value will not actually be dropped until all references to it have gone
     /// out of scope.
     ///
@@ -391,14 +766,18 @@ where
     /// // All entries have been removed now.
     /// assert!(numbers.is_empty());
     /// ```
-    pub fn pop_front(&self) -> Option<Entry<'_, K, V>> {
-        let guard = &epoch::pin();
+    pub fn pop_front(&self) -> Option<Entry<'_, K, V, C>> {
+        let guard = &pin(&self.inner);
         self.inner.pop_front(guard).map(Entry::new)
     }
 
     /// Removes the entry with the greatest key from the map.
     /// Returns the removed entry.
     ///
+    /// Finding and removing the entry is a single atomic step: when several threads pop
+    /// concurrently, each entry is returned to exactly one of them. This makes `SkipMap` usable
+    /// as a concurrent priority queue.
+    ///
     /// The value will not actually be dropped until all references to it have gone
     /// out of scope.
     ///
@@ -418,8 +797,8 @@ where
     /// // All entries have been removed now.
     /// assert!(numbers.is_empty());
     /// ```
-    pub fn pop_back(&self) -> Option<Entry<'_, K, V>> {
-        let guard = &epoch::pin();
+    pub fn pop_back(&self) -> Option<Entry<'_, K, V, C>> {
+        let guard = &pin(&self.inner);
         self.inner.pop_back(guard).map(Entry::new)
     }
 
@@ -437,7 +816,7 @@ where
     /// assert!(people.is_empty());
     /// ```
     pub fn clear(&self) {
-        let guard = &mut epoch::pin();
+        let guard = &mut pin(&self.inner);
         self.inner.clear(guard);
     }
 }
@@ -448,9 +827,10 @@ impl<K, V> Default for SkipMap<K, V> {
     }
 }
 
-impl<K, V> fmt::Debug for SkipMap<K, V>
+impl<K, V, C> fmt::Debug for SkipMap<K, V, C>
 where
-    K: Ord + fmt::Debug,
+    K: fmt::Debug,
+    C: Comparator<K>,
     V: fmt::Debug,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -458,7 +838,7 @@ where
     }
 }
 
-impl<K, V> IntoIterator for SkipMap<K, V> {
+impl<K, V, C> IntoIterator for SkipMap<K, V, C> {
     type Item = (K, V);
     type IntoIter = IntoIter<K, V>;
 
@@ -469,14 +849,14 @@ impl<K, V> IntoIterator for SkipMap<K, V> {
     }
 }
 
-impl<'a, K, V> IntoIterator for &'a SkipMap<K, V>
+impl<'a, K, V, C> IntoIterator for &'a SkipMap<K, V, C>
 where
-    K: Ord,
+    C: Comparator<K>,
 {
-    type Item = Entry<'a, K, V>;
-    type IntoIter = Iter<'a, K, V>;
+    type Item = Entry<'a, K, V, C>;
+    type IntoIter = Iter<'a, K, V, C>;
 
-    fn into_iter(self) -> Iter<'a, K, V> {
+    fn into_iter(self) -> Iter<'a, K, V, C> {
         self.iter()
     }
 }
@@ -497,13 +877,16 @@ where
     }
 }
 
+/// A newly inserted entry, along with the entry it replaced, if any.
+type Replaced<'a, K, V, C> = (Entry<'a, K, V, C>, Option<Entry<'a, K, V, C>>);
+
 /// A reference-counted entry in a map.
-pub struct Entry<'a, K, V> {
-    inner: ManuallyDrop<base::RefEntry<'a, K, V>>,
+pub struct Entry<'a, K, V, C = BasicComparator> {
+    inner: ManuallyDrop<base::RefEntry<'a, K, V, C>>,
 }
 
-impl<'a, K, V> Entry<'a, K, V> {
-    fn new(inner: base::RefEntry<'a, K, V>) -> Entry<'a, K, V> {
+impl<'a, K, V, C> Entry<'a, K, V, C> {
+    fn new(inner: base::RefEntry<'a, K, V, C>) -> Entry<'a, K, V, C> {
         Entry {
             inner: ManuallyDrop::new(inner),
         }
@@ -525,66 +908,69 @@ impl<'a, K, V> Entry<'a, K, V> {
     }
 }
 
-impl<K, V> Drop for Entry<'_, K, V> {
+impl<K, V, C> Drop for Entry<'_, K, V, C> {
     fn drop(&mut self) {
         unsafe {
-            ManuallyDrop::into_inner(ptr::read(&self.inner)).release_with_pin(epoch::pin);
+            let inner = ManuallyDrop::into_inner(ptr::read(&self.inner));
+            let parent = inner.parent;
+            inner.release_with_pin(|| pin(parent));
         }
     }
 }
 
-impl<'a, K, V> Entry<'a, K, V>
+impl<'a, K, V, C> Entry<'a, K, V, C>
 where
-    K: Ord,
+    C: Comparator<K>,
 {
     /// Moves to the next entry in the map.
     pub fn move_next(&mut self) -> bool {
-        let guard = &epoch::pin();
+        let guard = &pin(self.inner.parent);
         self.inner.move_next(guard)
     }
 
     /// Moves to the previous entry in the map.
     pub fn move_prev(&mut self) -> bool {
-        let guard = &epoch::pin();
+        let guard = &pin(self.inner.parent);
         self.inner.move_prev(guard)
     }
 
     /// Returns the next entry in the map.
-    pub fn next(&self) -> Option<Entry<'a, K, V>> {
-        let guard = &epoch::pin();
+    pub fn next(&self) -> Option<Entry<'a, K, V, C>> {
+        let guard = &pin(self.inner.parent);
         self.inner.next(guard).map(Entry::new)
     }
 
     /// Returns the previous entry in the map.
-    pub fn prev(&self) -> Option<Entry<'a, K, V>> {
-        let guard = &epoch::pin();
+    pub fn prev(&self) -> Option<Entry<'a, K, V, C>> {
+        let guard = &pin(self.inner.parent);
         self.inner.prev(guard).map(Entry::new)
     }
 }
 
-impl<K, V> Entry<'_, K, V>
+impl<K, V, C> Entry<'_, K, V, C>
 where
-    K: Ord + Send + 'static,
+    K: Send + 'static,
+    C: Comparator<K>,
     V: Send + 'static,
 {
     /// Removes the entry from the map.
     ///
     /// Returns `true` if this call removed the entry and `false` if it was already removed.
     pub fn remove(&self) -> bool {
-        let guard = &epoch::pin();
+        let guard = &pin(self.inner.parent);
         self.inner.remove(guard)
     }
 }
 
-impl<'a, K, V> Clone for Entry<'a, K, V> {
-    fn clone(&self) -> Entry<'a, K, V> {
+impl<'a, K, V, C> Clone for Entry<'a, K, V, C> {
+    fn clone(&self) -> Entry<'a, K, V, C> {
         Entry {
             inner: self.inner.clone(),
         }
     }
 }
 
-impl<K, V> fmt::Debug for Entry<'_, K, V>
+impl<K, V, C> fmt::Debug for Entry<'_, K, V, C>
 where
     K: fmt::Debug,
     V: fmt::Debug,
@@ -617,80 +1003,81 @@ impl<K, V> fmt::Debug for IntoIter<K, V> {
 }
 
 /// An iterator over the entries of a `SkipMap`.
-pub struct Iter<'a, K, V> {
-    inner: base::RefIter<'a, K, V>,
+pub struct Iter<'a, K, V, C = BasicComparator> {
+    inner: base::RefIter<'a, K, V, C>,
 }
 
-impl<'a, K, V> Iterator for Iter<'a, K, V>
+impl<'a, K, V, C> Iterator for Iter<'a, K, V, C>
 where
-    K: Ord,
+    C: Comparator<K>,
 {
-    type Item = Entry<'a, K, V>;
+    type Item = Entry<'a, K, V, C>;
 
-    fn next(&mut self) -> Option<Entry<'a, K, V>> {
-        let guard = &epoch::pin();
+    fn next(&mut self) -> Option<Entry<'a, K, V, C>> {
+        let guard = &pin(self.inner.parent);
         self.inner.next(guard).map(Entry::new)
     }
 }
 
-impl<'a, K, V> DoubleEndedIterator for Iter<'a, K, V>
+impl<'a, K, V, C> DoubleEndedIterator for Iter<'a, K, V, C>
 where
-    K: Ord,
+    C: Comparator<K>,
 {
-    fn next_back(&mut self) -> Option<Entry<'a, K, V>> {
-        let guard = &epoch::pin();
+    fn next_back(&mut self) -> Option<Entry<'a, K, V, C>> {
+        let guard = &pin(self.inner.parent);
         self.inner.next_back(guard).map(Entry::new)
     }
 }
 
-impl<K, V> fmt::Debug for Iter<'_, K, V> {
+impl<K, V, C> fmt::Debug for Iter<'_, K, V, C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.pad("Iter { .. }")
     }
 }
 
 /// An iterator over a subset of entries of a `SkipMap`.
-pub struct Range<'a, Q, R, K, V>
+pub struct Range<'a, Q, R, K, V, C = BasicComparator>
 where
-    K: Ord + Borrow<Q>,
+    C: Comparator<K> + Comparator<K, Q>,
     R: RangeBounds<Q>,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
-    pub(crate) inner: base::RefRange<'a, Q, R, K, V>,
+    pub(crate) inner: base::RefRange<'a, Q, R, K, V, C>,
 }
 
-impl<'a, Q, R, K, V> Iterator for Range<'a, Q, R, K, V>
+impl<'a, Q, R, K, V, C> Iterator for Range<'a, Q, R, K, V, C>
 where
-    K: Ord + Borrow<Q>,
+    C: Comparator<K> + Comparator<K, Q>,
     R: RangeBounds<Q>,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
-    type Item = Entry<'a, K, V>;
+    type Item = Entry<'a, K, V, C>;
 
-    fn next(&mut self) -> Option<Entry<'a, K, V>> {
-        let guard = &epoch::pin();
+    fn next(&mut self) -> Option<Entry<'a, K, V, C>> {
+        let guard = &pin(self.inner.parent);
         self.inner.next(guard).map(Entry::new)
     }
 }
 
-impl<'a, Q, R, K, V> DoubleEndedIterator for Range<'a, Q, R, K, V>
+impl<'a, Q, R, K, V, C> DoubleEndedIterator for Range<'a, Q, R, K, V, C>
 where
-    K: Ord + Borrow<Q>,
+    C: Comparator<K> + Comparator<K, Q>,
     R: RangeBounds<Q>,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
-    fn next_back(&mut self) -> Option<Entry<'a, K, V>> {
-        let guard = &epoch::pin();
+    fn next_back(&mut self) -> Option<Entry<'a, K, V, C>> {
+        let guard = &pin(self.inner.parent);
         self.inner.next_back(guard).map(Entry::new)
     }
 }
 
-impl<Q, R, K, V> fmt::Debug for Range<'_, Q, R, K, V>
+impl<Q, R, K, V, C> fmt::Debug for Range<'_, Q, R, K, V, C>
 where
-    K: Ord + Borrow<Q> + fmt::Debug,
+    K: fmt::Debug,
     V: fmt::Debug,
+    C: Comparator<K> + Comparator<K, Q>,
     R: RangeBounds<Q> + fmt::Debug,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Range")
@@ -700,3 +1087,153 @@ where
             .finish()
     }
 }
+
+/// A cursor over the entries of a `SkipMap`.
+///
+/// A cursor either points at an entry of the map or at no entry, which is where it starts. Moving
+/// forward from no entry goes to the first entry, and moving backward goes to the last one.
+/// Moving past either end makes the cursor point at no entry again.
+///
+/// Moving the cursor only relies on the key of its current entry, so it keeps working when that
+/// entry is removed concurrently. The current entry itself stays readable after removal, which
+/// can be checked with [`Entry::is_removed`].
+///
+/// This struct is created by the [`cursor`] method on [`SkipMap`].
+///
+/// [`cursor`]: SkipMap::cursor
+pub struct Cursor<'a, K, V, C = BasicComparator> {
+    map: &'a SkipMap<K, V, C>,
+    current: Option<Entry<'a, K, V, C>>,
+}
+
+impl<'a, K, V, C> Cursor<'a, K, V, C> {
+    /// Returns the entry the cursor points at, or `None` if it points at no entry.
+    pub fn entry(&self) -> Option<&Entry<'a, K, V, C>> {
+        self.current.as_ref()
+    }
+
+    /// Returns the key of the entry the cursor points at.
+    pub fn key(&self) -> Option<&K> {
+        self.current.as_ref().map(Entry::key)
+    }
+
+    /// Returns the value of the entry the cursor points at.
+    pub fn value(&self) -> Option<&V> {
+        self.current.as_ref().map(Entry::value)
+    }
+
+    /// Makes the cursor point at no entry.
+    pub fn reset(&mut self) {
+        self.current = None;
+    }
+}
+
+impl<'a, K, V, C> Cursor<'a, K, V, C>
+where
+    C: Comparator<K>,
+{
+    /// Moves the cursor to the next entry.
+    ///
+    /// If the cursor points at no entry, it moves to the first entry of the map. If the current
+    /// entry has been removed, it moves to the first entry whose key follows the removed one.
+    ///
+    /// Returns `false` if there is no such entry, in which case the cursor now points at no entry.
+    pub fn move_next(&mut self) -> bool {
+        self.current = match &self.current {
+            Some(e) => e.next(),
+            None => self.map.front(),
+        };
+        self.current.is_some()
+    }
+
+    /// Moves the cursor to the previous entry.
+    ///
+    /// If the cursor points at no entry, it moves to the last entry of the map. If the current
+    /// entry has been removed, it moves to the last entry whose key precedes the removed one.
+    ///
+    /// Returns `false` if there is no such entry, in which case the cursor now points at no entry.
+    pub fn move_prev(&mut self) -> bool {
+        self.current = match &self.current {
+            Some(e) => e.prev(),
+            None => self.map.back(),
+        };
+        self.current.is_some()
+    }
+
+    /// Moves the cursor to the first entry of the map.
+    ///
+    /// Returns `false` if the map is empty, in which case the cursor now points at no entry.
+    pub fn seek_front(&mut self) -> bool {
+        self.current = self.map.front();
+        self.current.is_some()
+    }
+
+    /// Moves the cursor to the last entry of the map.
+    ///
+    /// Returns `false` if the map is empty, in which case the cursor now points at no entry.
+    pub fn seek_back(&mut self) -> bool {
+        self.current = self.map.back();
+        self.current.is_some()
+    }
+
+    /// Moves the cursor to the first entry whose key is above the given bound, like
+    /// [`SkipMap::lower_bound`].
+    ///
+    /// Returns `false` if there is no such entry, in which case the cursor now points at no entry.
+    pub fn seek_lower_bound<Q>(&mut self, bound: Bound<&Q>) -> bool
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        self.current = self.map.lower_bound(bound);
+        self.current.is_some()
+    }
+
+    /// Moves the cursor to the last entry whose key is below the given bound, like
+    /// [`SkipMap::upper_bound`].
+    ///
+    /// Returns `false` if there is no such entry, in which case the cursor now points at no entry.
+    pub fn seek_upper_bound<Q>(&mut self, bound: Bound<&Q>) -> bool
+    where
+        C: Comparator<K, Q>,
+        Q: ?Sized,
+    {
+        self.current = self.map.upper_bound(bound);
+        self.current.is_some()
+    }
+}
+
+impl<'a, K, V, C> Clone for Cursor<'a, K, V, C> {
+    fn clone(&self) -> Cursor<'a, K, V, C> {
+        Cursor {
+            map: self.map,
+            current: self.current.clone(),
+        }
+    }
+}
+
+impl<K, V, C> fmt::Debug for Cursor<'_, K, V, C>
+where
+    K: fmt::Debug,
+    V: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Cursor")
+            .field("current", &self.current)
+            .finish()
+    }
+}
+
+/// Pins the current thread with the collector of `list`.
+///
+/// The participant of the current thread is reused if it belongs to that collector, which is the
+/// case for the default collector, or inside [`Collector::with`]. Otherwise, a new participant is
+/// registered just for the returned guard.
+fn pin<K, V, C>(list: &base::SkipList<K, V, C>) -> Guard {
+    let guard = epoch::pin();
+    if guard.collector() == Some(list.collector()) {
+        guard
+    } else {
+        list.collector().register().pin()
+    }
+}
@@ -1,11 +1,13 @@
 //! A set based on a lock-free skip list. See [`SkipSet`].
 
-use std::borrow::Borrow;
+use std::cmp::Ordering;
 use std::fmt;
 use std::iter::FromIterator;
 use std::ops::Deref;
 use std::ops::{Bound, RangeBounds};
 
+use crate::comparator::{BasicComparator, Comparator};
+use crate::epoch::Collector;
 use crate::map;
 
 /// A set based on a lock-free skip list.
@@ -14,8 +16,8 @@ use crate::map;
 /// concurrent access across multiple threads.
 ///
 /// [`BTreeSet`]: std::collections::BTreeSet
-pub struct SkipSet<T> {
-    inner: map::SkipMap<T, ()>,
+pub struct SkipSet<T, C = BasicComparator> {
+    inner: map::SkipMap<T, (), C>,
 }
 
 impl<T> SkipSet<T> {
@@ -34,6 +36,111 @@ impl<T> SkipSet<T> {
         }
     }
 
+    /// Returns a new, empty set that hands the garbage of removed elements to `collector`.
+    ///
+    /// See [`SkipMap::with_collector`] for details.
+    ///
+    /// [`SkipMap::with_collector`]: crate::SkipMap::with_collector
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crossbeam_epoch::Collector;
+    /// use crossbeam_skiplist::SkipSet;
+    ///
+    /// let collector = Collector::new();
+    /// let set = SkipSet::with_collector(collector.clone());
+    ///
+    /// collector.with(|| {
+    ///     set.insert(1);
+    ///     set.remove(&1);
+    ///     set.flush();
+    /// });
+    /// ```
+    pub fn with_collector(collector: Collector) -> SkipSet<T> {
+        SkipSet {
+            inner: map::SkipMap::with_collector(collector),
+        }
+    }
+}
+
+impl<T, C> SkipSet<T, C> {
+    /// Returns a new, empty set that orders its elements with `comparator`.
+    ///
+    /// See [`SkipMap::with_comparator`] for details.
+    ///
+    /// [`SkipMap::with_comparator`]: crate::SkipMap::with_comparator
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crossbeam_skiplist::SkipSet;
+    ///
+    /// // Order the elements from largest to smallest.
+    /// let set = SkipSet::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+    /// set.insert(1);
+    /// set.insert(3);
+    /// set.insert(2);
+    ///
+    /// assert_eq!(set.iter().map(|e| *e).collect::<Vec<_>>(), [3, 2, 1]);
+    /// ```
+    pub fn with_comparator(comparator: C) -> SkipSet<T, C> {
+        SkipSet {
+            inner: map::SkipMap::with_comparator(comparator),
+        }
+    }
+
+    /// Returns the comparator that orders the elements of the set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crossbeam_skiplist::comparator::BasicComparator;
+    /// use crossbeam_skiplist::SkipSet;
+    ///
+    /// let set: SkipSet<i32> = SkipSet::new();
+    /// assert_eq!(*set.comparator(), BasicComparator);
+    /// ```
+    pub fn comparator(&self) -> &C {
+        self.inner.comparator()
+    }
+
+    /// Returns the collector that destroys the removed elements of the set.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crossbeam_epoch as epoch;
+    /// use crossbeam_skiplist::SkipSet;
+    ///
+    /// let set: SkipSet<i32> = SkipSet::new();
+    /// assert!(set.collector() == epoch::default_collector());
+    /// ```
+    pub fn collector(&self) -> &Collector {
+        self.inner.collector()
+    }
+
+    /// Hands the garbage of removed elements over to the collector and destroys all garbage that
+    /// is ready.
+    ///
+    /// See [`SkipMap::flush`] for details.
+    ///
+    /// [`SkipMap::flush`]: crate::SkipMap::flush
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crossbeam_skiplist::SkipSet;
+    ///
+    /// let set = SkipSet::new();
+    /// set.insert("a");
+    /// set.clear();
+    /// set.flush();
+    /// ```
+    pub fn flush(&self) {
+        self.inner.flush();
+    }
+
     /// Returns `true` if the set is empty.
     ///
     /// # Example
@@ -56,6 +163,9 @@ impl<T> SkipSet<T> {
     /// If the set is being concurrently modified, consider the returned number just an
     /// approximation without any guarantees.
     ///
+    /// The number of entries is tracked by a counter that is updated on every insertion and
+    /// removal, so this method doesn't need to traverse the set.
+    ///
     /// # Example
     ///
     /// ```
@@ -72,9 +182,9 @@ impl<T> SkipSet<T> {
     }
 }
 
-impl<T> SkipSet<T>
+impl<T, C> SkipSet<T, C>
 where
-    T: Ord,
+    C: Comparator<T>,
 {
     /// Returns the entry with the smallest key.
     ///
@@ -89,7 +199,7 @@ where
     /// set.insert(2);
     /// assert_eq!(*set.front().unwrap(), 1);
     /// ```
-    pub fn front(&self) -> Option<Entry<'_, T>> {
+    pub fn front(&self) -> Option<Entry<'_, T, C>> {
         self.inner.front().map(Entry::new)
     }
 
@@ -106,7 +216,7 @@ where
     /// set.insert(2);
     /// assert_eq!(*set.back().unwrap(), 2);
     /// ```
-    pub fn back(&self) -> Option<Entry<'_, T>> {
+    pub fn back(&self) -> Option<Entry<'_, T, C>> {
         self.inner.back().map(Entry::new)
     }
 
@@ -123,8 +233,8 @@ where
     /// ```
     pub fn contains<Q>(&self, key: &Q) -> bool
     where
-        T: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<T, Q>,
+        Q: ?Sized,
     {
         self.inner.contains_key(key)
     }
@@ -140,10 +250,10 @@ where
     /// assert_eq!(*set.get(&3).unwrap(), 3);
     /// assert!(set.get(&4).is_none());
     /// ```
-    pub fn get<Q>(&self, key: &Q) -> Option<Entry<'_, T>>
+    pub fn get<Q>(&self, key: &Q) -> Option<Entry<'_, T, C>>
     where
-        T: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<T, Q>,
+        Q: ?Sized,
     {
         self.inner.get(key).map(Entry::new)
     }
@@ -172,10 +282,10 @@ where
     /// let greater_than_thirteen = set.lower_bound(Excluded(&13));
     /// assert!(greater_than_thirteen.is_none());
     /// ```
-    pub fn lower_bound<'a, Q>(&'a self, bound: Bound<&Q>) -> Option<Entry<'a, T>>
+    pub fn lower_bound<'a, Q>(&'a self, bound: Bound<&Q>) -> Option<Entry<'a, T, C>>
     where
-        T: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<T, Q>,
+        Q: ?Sized,
     {
         self.inner.lower_bound(bound).map(Entry::new)
     }
@@ -201,10 +311,10 @@ where
     /// let less_than_six = set.upper_bound(Excluded(&6));
     /// assert!(less_than_six.is_none());
     /// ```
-    pub fn upper_bound<'a, Q>(&'a self, bound: Bound<&Q>) -> Option<Entry<'a, T>>
+    pub fn upper_bound<'a, Q>(&'a self, bound: Bound<&Q>) -> Option<Entry<'a, T, C>>
     where
-        T: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<T, Q>,
+        Q: ?Sized,
     {
         self.inner.upper_bound(bound).map(Entry::new)
     }
@@ -220,7 +330,7 @@ where
     /// let entry = set.get_or_insert(2);
     /// assert_eq!(*entry, 2);
     /// ```
-    pub fn get_or_insert(&self, key: T) -> Entry<'_, T> {
+    pub fn get_or_insert(&self, key: T) -> Entry<'_, T, C> {
         Entry::new(self.inner.get_or_insert(key, ()))
     }
 
@@ -242,7 +352,7 @@ where
     /// assert_eq!(*set_iter.next().unwrap(), 12);
     /// assert!(set_iter.next().is_none());
     /// ```
-    pub fn iter(&self) -> Iter<'_, T> {
+    pub fn iter(&self) -> Iter<'_, T, C> {
         Iter {
             inner: self.inner.iter(),
         }
@@ -264,22 +374,118 @@ where
     /// assert_eq!(*set_range.next().unwrap(), 6);
     /// assert_eq!(*set_range.next().unwrap(), 7);
     /// assert!(set_range.next().is_none());
+    ///
+    /// // Ranges can also be scanned backwards.
+    /// let mut set_range = set.range(7..).rev();
+    /// assert_eq!(*set_range.next().unwrap(), 12);
+    /// assert_eq!(*set_range.next().unwrap(), 7);
+    /// assert!(set_range.next().is_none());
     /// ```
-    pub fn range<Q, R>(&self, range: R) -> Range<'_, Q, R, T>
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, Q, R, T, C>
     where
-        T: Borrow<Q>,
+        C: Comparator<T, Q>,
         R: RangeBounds<Q>,
-        Q: Ord + ?Sized,
+        Q: ?Sized,
     {
         Range {
             inner: self.inner.range(range),
         }
     }
+
+    /// Returns an iterator over the values that are in `self` or `other`, in ascending order.
+    ///
+    /// The sets are merged in a single pass over both of them. Values present in both sets are
+    /// yielded once, as entries of `self`. Both sets are ordered by the comparator of `self`.
+    ///
+    /// If the sets are being concurrently modified, values inserted or removed during the
+    /// iteration may or may not be observed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crossbeam_skiplist::SkipSet;
+    ///
+    /// let a: SkipSet<_> = vec![1, 3, 5].into_iter().collect();
+    /// let b: SkipSet<_> = vec![2, 3, 4].into_iter().collect();
+    ///
+    /// let union: Vec<_> = a.union(&b).map(|e| *e).collect();
+    /// assert_eq!(union, [1, 2, 3, 4, 5]);
+    /// ```
+    pub fn union<'a>(&'a self, other: &'a SkipSet<T, C>) -> Union<'a, T, C> {
+        Union {
+            comparator: self.comparator(),
+            a: self.front(),
+            b: other.front(),
+        }
+    }
+
+    /// Returns an iterator over the values that are in both `self` and `other`, in ascending
+    /// order.
+    ///
+    /// Whenever one set is behind the other, it skips ahead to the next candidate value with a
+    /// search instead of visiting every value in between, so intersecting a small set with a
+    /// large one is fast. The values are yielded as entries of `self`. Both sets are ordered by
+    /// the comparator of `self`.
+    ///
+    /// If the sets are being concurrently modified, values inserted or removed during the
+    /// iteration may or may not be observed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crossbeam_skiplist::SkipSet;
+    ///
+    /// let a: SkipSet<_> = vec![1, 3, 5, 7].into_iter().collect();
+    /// let b: SkipSet<_> = (3..=5).collect();
+    ///
+    /// let intersection: Vec<_> = a.intersection(&b).map(|e| *e).collect();
+    /// assert_eq!(intersection, [3, 5]);
+    /// ```
+    pub fn intersection<'a>(&'a self, other: &'a SkipSet<T, C>) -> Intersection<'a, T, C> {
+        Intersection {
+            comparator: self.comparator(),
+            a_set: self,
+            b_set: other,
+            a: self.front(),
+            b: other.front(),
+        }
+    }
+
+    /// Returns an iterator over the values that are in `self` but not in `other`, in ascending
+    /// order.
+    ///
+    /// Whenever `other` is behind `self`, it skips ahead to the next candidate value with a
+    /// search instead of visiting every value in between. Both sets are ordered by the
+    /// comparator of `self`.
+    ///
+    /// If the sets are being concurrently modified, values inserted or removed during the
+    /// iteration may or may not be observed.
+    ///
+    /// # Example
+    ///
+    /// ```
+    /// use crossbeam_skiplist::SkipSet;
+    ///
+    /// let a: SkipSet<_> = (1..=5).collect();
+    /// let b: SkipSet<_> = vec![2, 4, 6].into_iter().collect();
+    ///
+    /// let difference: Vec<_> = a.difference(&b).map(|e| *e).collect();
+    /// assert_eq!(difference, [1, 3, 5]);
+    /// ```
+    pub fn difference<'a>(&'a self, other: &'a SkipSet<T, C>) -> Difference<'a, T, C> {
+        Difference {
+            comparator: self.comparator(),
+            b_set: other,
+            a: self.front(),
+            b: other.front(),
+        }
+    }
 }
 
-impl<T> SkipSet<T>
+impl<T, C> SkipSet<T, C>
 where
-    T: Ord + Send + 'static,
+    T: Send + 'static,
+    C: Comparator<T>,
 {
     /// Inserts a `key`-`value` pair into the set and returns the new entry.
     ///
@@ -295,7 +501,7 @@ where
     /// set.insert(2);
     /// assert_eq!(*set.get(&2).unwrap(), 2);
     /// ```
-    pub fn insert(&self, key: T) -> Entry<'_, T> {
+    pub fn insert(&self, key: T) -> Entry<'_, T, C> {
         Entry::new(self.inner.insert(key, ()))
     }
 
@@ -314,10 +520,10 @@ where
     /// assert_eq!(*set.remove(&2).unwrap(), 2);
     /// assert!(set.remove(&2).is_none());
     /// ```
-    pub fn remove<Q>(&self, key: &Q) -> Option<Entry<'_, T>>
+    pub fn remove<Q>(&self, key: &Q) -> Option<Entry<'_, T, C>>
     where
-        T: Borrow<Q>,
-        Q: Ord + ?Sized,
+        C: Comparator<T, Q>,
+        Q: ?Sized,
     {
         self.inner.remove(key).map(Entry::new)
     }
@@ -343,7 +549,7 @@ where
     /// // All entries have been removed now.
     /// assert!(set.is_empty());
     /// ```
-    pub fn pop_front(&self) -> Option<Entry<'_, T>> {
+    pub fn pop_front(&self) -> Option<Entry<'_, T, C>> {
         self.inner.pop_front().map(Entry::new)
     }
 
@@ -368,7 +574,7 @@ where
     /// // All entries have been removed now.
     /// assert!(set.is_empty());
     /// ```
-    pub fn pop_back(&self) -> Option<Entry<'_, T>> {
+    pub fn pop_back(&self) -> Option<Entry<'_, T, C>> {
         self.inner.pop_back().map(Entry::new)
     }
 
@@ -397,16 +603,17 @@ impl<T> Default for SkipSet<T> {
     }
 }
 
-impl<T> fmt::Debug for SkipSet<T>
+impl<T, C> fmt::Debug for SkipSet<T, C>
 where
-    T: Ord + fmt::Debug,
+    T: fmt::Debug,
+    C: Comparator<T>,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.pad("SkipSet { .. }")
     }
 }
 
-impl<T> IntoIterator for SkipSet<T> {
+impl<T, C> IntoIterator for SkipSet<T, C> {
     type Item = T;
     type IntoIter = IntoIter<T>;
 
@@ -417,14 +624,14 @@ impl<T> IntoIterator for SkipSet<T> {
     }
 }
 
-impl<'a, T> IntoIterator for &'a SkipSet<T>
+impl<'a, T, C> IntoIterator for &'a SkipSet<T, C>
 where
-    T: Ord,
+    C: Comparator<T>,
 {
-    type Item = Entry<'a, T>;
-    type IntoIter = Iter<'a, T>;
+    type Item = Entry<'a, T, C>;
+    type IntoIter = Iter<'a, T, C>;
 
-    fn into_iter(self) -> Iter<'a, T> {
+    fn into_iter(self) -> Iter<'a, T, C> {
         self.iter()
     }
 }
@@ -446,12 +653,12 @@ where
 }
 
 /// A reference-counted entry in a set.
-pub struct Entry<'a, T> {
-    inner: map::Entry<'a, T, ()>,
+pub struct Entry<'a, T, C = BasicComparator> {
+    inner: map::Entry<'a, T, (), C>,
 }
 
-impl<'a, T> Entry<'a, T> {
-    fn new(inner: map::Entry<'a, T, ()>) -> Entry<'a, T> {
+impl<'a, T, C> Entry<'a, T, C> {
+    fn new(inner: map::Entry<'a, T, (), C>) -> Entry<'a, T, C> {
         Entry { inner }
     }
 
@@ -466,9 +673,9 @@ impl<'a, T> Entry<'a, T> {
     }
 }
 
-impl<'a, T> Entry<'a, T>
+impl<'a, T, C> Entry<'a, T, C>
 where
-    T: Ord,
+    C: Comparator<T>,
 {
     /// Moves to the next entry in the set.
     pub fn move_next(&mut self) -> bool {
@@ -481,19 +688,20 @@ where
     }
 
     /// Returns the next entry in the set.
-    pub fn next(&self) -> Option<Entry<'a, T>> {
+    pub fn next(&self) -> Option<Entry<'a, T, C>> {
         self.inner.next().map(Entry::new)
     }
 
     /// Returns the previous entry in the set.
-    pub fn prev(&self) -> Option<Entry<'a, T>> {
+    pub fn prev(&self) -> Option<Entry<'a, T, C>> {
         self.inner.prev().map(Entry::new)
     }
 }
 
-impl<T> Entry<'_, T>
+impl<T, C> Entry<'_, T, C>
 where
-    T: Ord + Send + 'static,
+    T: Send + 'static,
+    C: Comparator<T>,
 {
     /// Removes the entry from the set.
     ///
@@ -503,15 +711,15 @@ where
     }
 }
 
-impl<'a, T> Clone for Entry<'a, T> {
-    fn clone(&self) -> Entry<'a, T> {
+impl<'a, T, C> Clone for Entry<'a, T, C> {
+    fn clone(&self) -> Entry<'a, T, C> {
         Entry {
             inner: self.inner.clone(),
         }
     }
 }
 
-impl<T> fmt::Debug for Entry<'_, T>
+impl<T, C> fmt::Debug for Entry<'_, T, C>
 where
     T: fmt::Debug,
 {
@@ -522,7 +730,7 @@ where
     }
 }
 
-impl<T> Deref for Entry<'_, T> {
+impl<T, C> Deref for Entry<'_, T, C> {
     type Target = T;
 
     fn deref(&self) -> &Self::Target {
@@ -550,75 +758,76 @@ impl<T> fmt::Debug for IntoIter<T> {
 }
 
 /// An iterator over the entries of a `SkipSet`.
-pub struct Iter<'a, T> {
-    inner: map::Iter<'a, T, ()>,
+pub struct Iter<'a, T, C = BasicComparator> {
+    inner: map::Iter<'a, T, (), C>,
 }
 
-impl<'a, T> Iterator for Iter<'a, T>
+impl<'a, T, C> Iterator for Iter<'a, T, C>
 where
-    T: Ord,
+    C: Comparator<T>,
 {
-    type Item = Entry<'a, T>;
+    type Item = Entry<'a, T, C>;
 
-    fn next(&mut self) -> Option<Entry<'a, T>> {
+    fn next(&mut self) -> Option<Entry<'a, T, C>> {
         self.inner.next().map(Entry::new)
     }
 }
 
-impl<'a, T> DoubleEndedIterator for Iter<'a, T>
+impl<'a, T, C> DoubleEndedIterator for Iter<'a, T, C>
 where
-    T: Ord,
+    C: Comparator<T>,
 {
-    fn next_back(&mut self) -> Option<Entry<'a, T>> {
+    fn next_back(&mut self) -> Option<Entry<'a, T, C>> {
         self.inner.next_back().map(Entry::new)
     }
 }
 
-impl<T> fmt::Debug for Iter<'_, T> {
+impl<T, C> fmt::Debug for Iter<'_, T, C> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.pad("Iter { .. }")
     }
 }
 
 /// An iterator over a subset of entries of a `SkipSet`.
-pub struct Range<'a, Q, R, T>
+pub struct Range<'a, Q, R, T, C = BasicComparator>
 where
-    T: Ord + Borrow<Q>,
+    C: Comparator<T> + Comparator<T, Q>,
     R: RangeBounds<Q>,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
-    inner: map::Range<'a, Q, R, T, ()>,
+    inner: map::Range<'a, Q, R, T, (), C>,
 }
 
-impl<'a, Q, R, T> Iterator for Range<'a, Q, R, T>
+impl<'a, Q, R, T, C> Iterator for Range<'a, Q, R, T, C>
 where
-    T: Ord + Borrow<Q>,
+    C: Comparator<T> + Comparator<T, Q>,
     R: RangeBounds<Q>,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
-    type Item = Entry<'a, T>;
+    type Item = Entry<'a, T, C>;
 
-    fn next(&mut self) -> Option<Entry<'a, T>> {
+    fn next(&mut self) -> Option<Entry<'a, T, C>> {
         self.inner.next().map(Entry::new)
     }
 }
 
-impl<'a, Q, R, T> DoubleEndedIterator for Range<'a, Q, R, T>
+impl<'a, Q, R, T, C> DoubleEndedIterator for Range<'a, Q, R, T, C>
 where
-    T: Ord + Borrow<Q>,
+    C: Comparator<T> + Comparator<T, Q>,
     R: RangeBounds<Q>,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
-    fn next_back(&mut self) -> Option<Entry<'a, T>> {
+    fn next_back(&mut self) -> Option<Entry<'a, T, C>> {
         self.inner.next_back().map(Entry::new)
     }
 }
 
-impl<Q, R, T> fmt::Debug for Range<'_, Q, R, T>
+impl<Q, R, T, C> fmt::Debug for Range<'_, Q, R, T, C>
 where
-    T: Ord + Borrow<Q> + fmt::Debug,
+    T: fmt::Debug,
+    C: Comparator<T> + Comparator<T, Q>,
     R: RangeBounds<Q> + fmt::Debug,
-    Q: Ord + ?Sized,
+    Q: ?Sized,
 {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("Range")
@@ -628,3 +837,182 @@ where
             .finish()
     }
 }
+
+/// An iterator over the union of two `SkipSet`s.
+///
+/// This struct is created by the [`union`] method on [`SkipSet`].
+///
+/// [`union`]: SkipSet::union
+pub struct Union<'a, T, C = BasicComparator> {
+    comparator: &'a C,
+    a: Option<Entry<'a, T, C>>,
+    b: Option<Entry<'a, T, C>>,
+}
+
+impl<'a, T, C> Iterator for Union<'a, T, C>
+where
+    C: Comparator<T>,
+{
+    type Item = Entry<'a, T, C>;
+
+    fn next(&mut self) -> Option<Entry<'a, T, C>> {
+        match (self.a.take(), self.b.take()) {
+            (None, None) => None,
+            (Some(a), None) => {
+                self.a = a.next();
+                Some(a)
+            }
+            (None, Some(b)) => {
+                self.b = b.next();
+                Some(b)
+            }
+            (Some(a), Some(b)) => match self.comparator.compare(a.value(), b.value()) {
+                Ordering::Less => {
+                    self.a = a.next();
+                    self.b = Some(b);
+                    Some(a)
+                }
+                Ordering::Greater => {
+                    self.a = Some(a);
+                    self.b = b.next();
+                    Some(b)
+                }
+                Ordering::Equal => {
+                    self.a = a.next();
+                    self.b = b.next();
+                    Some(a)
+                }
+            },
+        }
+    }
+}
+
+impl<T, C> fmt::Debug for Union<'_, T, C>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Union")
+            .field("a", &self.a)
+            .field("b", &self.b)
+            .finish()
+    }
+}
+
+/// An iterator over the intersection of two `SkipSet`s.
+///
+/// This struct is created by the [`intersection`] method on [`SkipSet`].
+///
+/// [`intersection`]: SkipSet::intersection
+pub struct Intersection<'a, T, C = BasicComparator> {
+    comparator: &'a C,
+    a_set: &'a SkipSet<T, C>,
+    b_set: &'a SkipSet<T, C>,
+    a: Option<Entry<'a, T, C>>,
+    b: Option<Entry<'a, T, C>>,
+}
+
+impl<'a, T, C> Iterator for Intersection<'a, T, C>
+where
+    C: Comparator<T>,
+{
+    type Item = Entry<'a, T, C>;
+
+    fn next(&mut self) -> Option<Entry<'a, T, C>> {
+        loop {
+            let a = self.a.take()?;
+            let b = self.b.take()?;
+
+            match self.comparator.compare(a.value(), b.value()) {
+                // Skip ahead to the first value in `a` that is not less than `b`.
+                Ordering::Less => {
+                    self.a = self.a_set.lower_bound(Bound::Included(b.value()));
+                    self.b = Some(b);
+                }
+                // Skip ahead to the first value in `b` that is not less than `a`.
+                Ordering::Greater => {
+                    self.b = self.b_set.lower_bound(Bound::Included(a.value()));
+                    self.a = Some(a);
+                }
+                Ordering::Equal => {
+                    self.a = a.next();
+                    self.b = b.next();
+                    return Some(a);
+                }
+            }
+        }
+    }
+}
+
+impl<T, C> fmt::Debug for Intersection<'_, T, C>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Intersection")
+            .field("a", &self.a)
+            .field("b", &self.b)
+            .finish()
+    }
+}
+
+/// An iterator over the difference of two `SkipSet`s.
+///
+/// This struct is created by the [`difference`] method on [`SkipSet`].
+///
+/// [`difference`]: SkipSet::difference
+pub struct Difference<'a, T, C = BasicComparator> {
+    comparator: &'a C,
+    b_set: &'a SkipSet<T, C>,
+    a: Option<Entry<'a, T, C>>,
+    b: Option<Entry<'a, T, C>>,
+}
+
+impl<'a, T, C> Iterator for Difference<'a, T, C>
+where
+    C: Comparator<T>,
+{
+    type Item = Entry<'a, T, C>;
+
+    fn next(&mut self) -> Option<Entry<'a, T, C>> {
+        loop {
+            let a = self.a.take()?;
+            let b = match self.b.take() {
+                Some(b) => b,
+                None => {
+                    self.a = a.next();
+                    return Some(a);
+                }
+            };
+
+            match self.comparator.compare(a.value(), b.value()) {
+                Ordering::Less => {
+                    self.a = a.next();
+                    self.b = Some(b);
+                    return Some(a);
+                }
+                // Skip ahead to the first value in `b` that is not less than `a`.
+                Ordering::Greater => {
+                    self.b = self.b_set.lower_bound(Bound::Included(a.value()));
+                    self.a = Some(a);
+                }
+                Ordering::Equal => {
+                    self.a = a.next();
+                    self.b = b.next();
+                }
+            }
+        }
+    }
+}
+
+impl<T, C> fmt::Debug for Difference<'_, T, C>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Difference")
+            .field("a", &self.a)
+            .field("b", &self.b)
+            .finish()
+    }
+}
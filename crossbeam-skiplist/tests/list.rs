@@ -0,0 +1,155 @@
+use std::cmp::Reverse;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crossbeam_epoch as epoch;
+use crossbeam_skiplist::List;
+use crossbeam_utils::thread;
+
+#[test]
+fn smoke() {
+    let guard = &epoch::pin();
+    let l = List::new(epoch::default_collector().clone());
+    assert!(l.is_empty());
+
+    assert_eq!(l.insert(2, guard), Ok(&2));
+    assert_eq!(l.insert(1, guard), Ok(&1));
+    assert_eq!(l.insert(3, guard), Ok(&3));
+    assert_eq!(l.insert(2, guard), Err(2));
+    assert_eq!(l.len(), 3);
+
+    assert_eq!(l.front(guard), Some(&1));
+    assert_eq!(l.get(&2, guard), Some(&2));
+    assert_eq!(l.get(&4, guard), None);
+    assert_eq!(l.lower_bound(&0, guard), Some(&1));
+    assert_eq!(l.lower_bound(&4, guard), None);
+}
+
+#[test]
+fn remove() {
+    let guard = &epoch::pin();
+    let l = List::new(epoch::default_collector().clone());
+
+    for x in 0..100 {
+        l.insert(x, guard).unwrap();
+    }
+    for x in (0..100).filter(|x| x % 3 == 0) {
+        assert_eq!(l.remove(&x, guard), Some(&x));
+        assert_eq!(l.remove(&x, guard), None);
+    }
+    for x in 0..100 {
+        assert_eq!(l.contains(&x, guard), x % 3 != 0);
+    }
+    assert_eq!(l.len(), 66);
+
+    let values: Vec<i32> = l.iter(guard).cloned().collect();
+    assert_eq!(values, (0..100).filter(|x| x % 3 != 0).collect::<Vec<_>>());
+}
+
+#[test]
+fn borrowed_keys() {
+    let guard = &epoch::pin();
+    let l = List::new(epoch::default_collector().clone());
+    l.insert("banana".to_string(), guard).unwrap();
+    l.insert("apple".to_string(), guard).unwrap();
+
+    assert!(l.contains("apple", guard));
+    assert_eq!(
+        l.remove("banana", guard).map(|s| s.as_str()),
+        Some("banana")
+    );
+    assert_eq!(l.front(guard).map(|s| s.as_str()), Some("apple"));
+}
+
+#[test]
+fn comparator() {
+    let guard = &epoch::pin();
+    let l = List::with_comparator(epoch::default_collector().clone(), |a: &i32, b: &i32| {
+        Reverse(a).cmp(&Reverse(b))
+    });
+    for x in [3, 1, 4, 1, 5, 9, 2, 6].iter() {
+        let _ = l.insert(*x, guard);
+    }
+
+    let values: Vec<i32> = l.iter(guard).cloned().collect();
+    assert_eq!(values, [9, 6, 5, 4, 3, 2, 1]);
+}
+
+#[test]
+fn drops() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    #[derive(Eq, PartialEq, Ord, PartialOrd)]
+    struct Elem(i32);
+
+    impl Drop for Elem {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let collector = epoch::Collector::new();
+    let handle = collector.register();
+    {
+        let guard = &handle.pin();
+        let l = List::new(collector.clone());
+        for x in 0..10 {
+            l.insert(Elem(x), guard).ok().unwrap();
+        }
+        // The rejected duplicate is handed back and dropped right away.
+        drop(l.insert(Elem(0), guard));
+        assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+        for x in 0..5 {
+            l.remove(&Elem(x), guard);
+        }
+        // The temporary keys above were dropped as well.
+        assert_eq!(DROPS.load(Ordering::SeqCst), 6);
+    }
+
+    // Dropping the collector destroys the removed values.
+    drop(handle);
+    drop(collector);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 16);
+}
+
+#[test]
+fn concurrent() {
+    const THREADS: i32 = 4;
+    const N: i32 = 500;
+
+    let l = List::new(epoch::default_collector().clone());
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let l = &l;
+            scope.spawn(move |_| {
+                for x in 0..N {
+                    let guard = &epoch::pin();
+                    // Threads insert into and remove from the same region of the list.
+                    let key = x * THREADS + t;
+                    assert_eq!(l.insert(key, guard), Ok(&key));
+                    if x % 2 == 0 {
+                        assert_eq!(l.remove(&key, guard), Some(&key));
+                    }
+                }
+            });
+        }
+
+        scope.spawn(|_| {
+            for _ in 0..100 {
+                let guard = &epoch::pin();
+                let mut last = None;
+                for x in l.iter(guard) {
+                    assert!(last < Some(*x));
+                    last = Some(*x);
+                }
+            }
+        });
+    })
+    .unwrap();
+
+    let guard = &epoch::pin();
+    assert_eq!(l.len() as i32, THREADS * N / 2);
+    for key in 0..THREADS * N {
+        assert_eq!(l.contains(&key, guard), (key / THREADS) % 2 == 1);
+    }
+}
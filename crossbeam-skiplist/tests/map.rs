@@ -1,5 +1,13 @@
-use std::{iter, ops::Bound, sync::Barrier};
-
+use std::{
+    iter,
+    ops::Bound,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Barrier,
+    },
+};
+
+use crossbeam_epoch::{self as epoch, Collector};
 use crossbeam_skiplist::SkipMap;
 use crossbeam_utils::thread;
 
@@ -215,6 +223,44 @@ fn len() {
     assert_eq!(s.len(), 5);
 }
 
+#[test]
+fn concurrent_len() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 1000;
+
+    let s = SkipMap::new();
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let s = &s;
+            scope.spawn(move |_| {
+                for i in 0..COUNT {
+                    s.insert(t * COUNT + i, ());
+                }
+                // Remove every other key, along with some keys that were already removed.
+                for i in (0..COUNT).step_by(2) {
+                    s.remove(&(t * COUNT + i));
+                    s.remove(&(t * COUNT + i));
+                }
+                // Replacing entries doesn't change the length.
+                for i in 0..COUNT {
+                    s.insert(t * COUNT + i, ());
+                    s.compute(t * COUNT + i, |_| Some(()));
+                }
+                for i in (0..COUNT).step_by(2) {
+                    s.remove(&(t * COUNT + i));
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(s.len(), THREADS * COUNT / 2);
+    assert_eq!(s.iter().count(), THREADS * COUNT / 2);
+
+    s.clear();
+    assert_eq!(s.len(), 0);
+}
+
 #[test]
 fn insert_and_remove() {
     let s = SkipMap::new();
@@ -370,6 +416,209 @@ fn get_or_insert() {
     assert_eq!(*s.get_or_insert(6, 600).value(), 600);
 }
 
+#[test]
+fn get_or_insert_with() {
+    let s = SkipMap::new();
+    s.insert(3, 3);
+    s.insert(1, 1);
+
+    assert_eq!(*s.get_or_insert_with(3, || panic!()).value(), 3);
+    assert_eq!(*s.get_or_insert_with(2, || 20).value(), 20);
+    assert_eq!(*s.get_or_insert_with(2, || panic!()).value(), 20);
+    assert_eq!(s.len(), 3);
+}
+
+#[test]
+fn compute() {
+    let s = SkipMap::new();
+
+    assert!(s.compute(1, |v| v.map(|v| v + 1)).is_none());
+    assert!(s.is_empty());
+
+    assert_eq!(
+        *s.compute(1, |v| Some(v.map_or(10, |v| v + 1)))
+            .unwrap()
+            .value(),
+        10
+    );
+    assert_eq!(
+        *s.compute(1, |v| Some(v.map_or(10, |v| v + 1)))
+            .unwrap()
+            .value(),
+        11
+    );
+    assert_eq!(*s.get(&1).unwrap().value(), 11);
+    assert_eq!(s.len(), 1);
+
+    let e = s.get(&1).unwrap();
+    assert_eq!(*s.compute(1, |_| None).unwrap().value(), 11);
+    assert_eq!(*s.compute(1, |_| Some(12)).unwrap().value(), 12);
+    assert!(e.is_removed());
+    assert_eq!(*e.value(), 11);
+    assert_eq!(s.len(), 1);
+
+    s.insert(0, 0);
+    s.insert(2, 2);
+    assert_eq!(*s.compute(2, |_| Some(20)).unwrap().value(), 20);
+    assert_eq!(
+        s.iter().map(|e| (*e.key(), *e.value())).collect::<Vec<_>>(),
+        vec![(0, 0), (1, 12), (2, 20)]
+    );
+}
+
+#[test]
+fn remove_if() {
+    let s = SkipMap::new();
+    s.insert(1, 10);
+    s.insert(2, 20);
+
+    assert!(s.remove_if(&3, |_| panic!()).is_none());
+    assert!(s.remove_if(&1, |&v| v > 10).is_none());
+    assert_eq!(*s.get(&1).unwrap().value(), 10);
+    assert_eq!(s.len(), 2);
+
+    let e = s.get(&1).unwrap();
+    assert_eq!(*s.remove_if(&1, |&v| v == 10).unwrap().value(), 10);
+    assert!(e.is_removed());
+    assert!(s.get(&1).is_none());
+    assert_eq!(s.len(), 1);
+
+    assert!(s.remove_if(&1, |_| true).is_none());
+    assert_eq!(*s.remove_if(&2, |_| true).unwrap().value(), 20);
+    assert!(s.is_empty());
+}
+
+#[test]
+fn concurrent_remove_if() {
+    const STEPS: usize = 1000;
+
+    let s = SkipMap::new();
+    thread::scope(|scope| {
+        scope.spawn(|_| {
+            for _ in 0..STEPS {
+                s.compute(0, |v| Some(v.map_or(1, |v| v + 1)));
+            }
+        });
+        scope.spawn(|_| {
+            for _ in 0..STEPS {
+                // Only values the predicate approved may ever be removed.
+                if let Some(e) = s.remove_if(&0, |&v| v % 2 == 0) {
+                    assert_eq!(*e.value() % 2, 0);
+                }
+            }
+        });
+    })
+    .unwrap();
+
+    assert!(s.len() <= 1);
+}
+
+#[test]
+fn concurrent_compute() {
+    const THREADS: usize = 4;
+    const STEPS: usize = 1000;
+
+    let s = SkipMap::new();
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for i in 0..STEPS {
+                    s.compute(i % 10, |v| Some(v.map_or(1, |v| v + 1)));
+                    // The key is never missing while it is being replaced.
+                    if i >= 10 {
+                        assert!(s.contains_key(&(i % 10)));
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(s.len(), 10);
+    for e in s.iter() {
+        assert_eq!(*e.value(), THREADS * STEPS / 10);
+    }
+}
+
+#[test]
+fn try_insert() {
+    let s = SkipMap::new();
+
+    assert_eq!(*s.try_insert(1, 10).unwrap().value(), 10);
+    assert_eq!(*s.try_insert(1, 20).unwrap_err().value(), 10);
+    assert_eq!(*s.get(&1).unwrap().value(), 10);
+    assert_eq!(*s.try_insert(2, 20).unwrap().value(), 20);
+    assert_eq!(s.len(), 2);
+
+    s.remove(&1);
+    assert_eq!(*s.try_insert(1, 30).unwrap().value(), 30);
+    assert_eq!(s.len(), 2);
+}
+
+#[test]
+fn insert_or_replace() {
+    let s = SkipMap::new();
+
+    let (e, prev) = s.insert_or_replace(1, 10);
+    assert_eq!(*e.value(), 10);
+    assert!(prev.is_none());
+
+    let (e, prev) = s.insert_or_replace(1, 20);
+    assert_eq!(*e.value(), 20);
+    let prev = prev.unwrap();
+    assert_eq!(*prev.value(), 10);
+    assert!(prev.is_removed());
+    assert!(!e.is_removed());
+    assert_eq!(*s.get(&1).unwrap().value(), 20);
+    assert_eq!(s.len(), 1);
+
+    s.insert(0, 0);
+    s.insert(2, 2);
+    assert_eq!(*s.insert_or_replace(2, 3).1.unwrap().value(), 2);
+    assert_eq!(
+        s.iter().map(|e| (*e.key(), *e.value())).collect::<Vec<_>>(),
+        vec![(0, 0), (1, 20), (2, 3)]
+    );
+}
+
+#[test]
+fn concurrent_insert_or_replace() {
+    const THREADS: usize = 4;
+    const STEPS: usize = 1000;
+
+    let s = SkipMap::new();
+    let replaced = thread::scope(|scope| {
+        let handles: Vec<_> = (0..THREADS)
+            .map(|t| {
+                let s = &s;
+                scope.spawn(move |_| {
+                    let mut replaced = Vec::new();
+                    for i in 0..STEPS {
+                        if let Some(prev) = s.insert_or_replace(0, t * STEPS + i).1 {
+                            replaced.push(*prev.value());
+                        }
+                        // The key is never missing while it is being replaced.
+                        assert!(s.contains_key(&0));
+                    }
+                    replaced
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect::<Vec<_>>()
+    })
+    .unwrap();
+
+    // Every value except the last one was replaced exactly once.
+    let mut values = replaced;
+    values.push(*s.get(&0).unwrap().value());
+    values.sort();
+    assert_eq!(values, (0..THREADS * STEPS).collect::<Vec<_>>());
+    assert_eq!(s.len(), 1);
+}
+
 #[test]
 fn get_next_prev() {
     let s = SkipMap::new();
@@ -417,6 +666,73 @@ fn front_and_back() {
     assert_eq!(*s.back().unwrap().key(), 12);
 }
 
+#[test]
+fn pop_front_and_back() {
+    let s = SkipMap::new();
+    assert!(s.pop_front().is_none());
+    assert!(s.pop_back().is_none());
+
+    for &x in &[4, 2, 12, 8, 7] {
+        s.insert(x, x * 10);
+    }
+
+    assert_eq!(*s.pop_front().unwrap().key(), 2);
+    assert_eq!(*s.pop_back().unwrap().key(), 12);
+    assert_eq!(*s.pop_front().unwrap().value(), 40);
+    assert_eq!(*s.pop_back().unwrap().value(), 80);
+    assert_eq!(s.len(), 1);
+    assert_eq!(*s.pop_back().unwrap().key(), 7);
+    assert!(s.pop_front().is_none());
+    assert!(s.is_empty());
+}
+
+#[test]
+fn concurrent_pop() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 10_000;
+
+    let s: SkipMap<usize, ()> = (0..COUNT).map(|x| (x, ())).collect();
+    let popped = thread::scope(|scope| {
+        let handles = (0..THREADS)
+            .map(|t| {
+                let s = &s;
+                scope.spawn(move |_| {
+                    let mut keys = Vec::new();
+                    loop {
+                        let e = if t % 2 == 0 {
+                            s.pop_front()
+                        } else {
+                            s.pop_back()
+                        };
+                        match e {
+                            Some(e) => keys.push(*e.key()),
+                            None => break keys,
+                        }
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        handles
+            .into_iter()
+            .map(|h| h.join().unwrap())
+            .collect::<Vec<_>>()
+    })
+    .unwrap();
+
+    // Every entry is popped by exactly one thread, in order.
+    for (t, keys) in popped.iter().enumerate() {
+        if t % 2 == 0 {
+            assert!(keys.windows(2).all(|w| w[0] < w[1]));
+        } else {
+            assert!(keys.windows(2).all(|w| w[0] > w[1]));
+        }
+    }
+    let mut all = popped.concat();
+    all.sort();
+    assert_eq!(all, (0..COUNT).collect::<Vec<_>>());
+    assert!(s.is_empty());
+}
+
 #[test]
 fn iter() {
     let s = SkipMap::new();
@@ -662,6 +978,65 @@ fn iter_range() {
     );
 }
 
+#[test]
+fn iter_range_rev() {
+    use std::ops::Bound::*;
+    let s = SkipMap::new();
+    for x in (0..10).map(|x| x * 10) {
+        s.insert(x, x);
+    }
+
+    assert_eq!(
+        s.range(..).rev().map(|x| *x.value()).collect::<Vec<_>>(),
+        vec![90, 80, 70, 60, 50, 40, 30, 20, 10, 0]
+    );
+    assert_eq!(
+        s.range((Excluded(&25), Included(&80)))
+            .rev()
+            .map(|x| *x.value())
+            .collect::<Vec<_>>(),
+        vec![80, 70, 60, 50, 40, 30]
+    );
+    assert_eq!(
+        s.range((Included(&30), Excluded(&80)))
+            .rev()
+            .map(|x| *x.value())
+            .collect::<Vec<_>>(),
+        vec![70, 60, 50, 40, 30]
+    );
+    assert_eq!(
+        s.range((Unbounded, Included(&25)))
+            .rev()
+            .map(|x| *x.value())
+            .collect::<Vec<_>>(),
+        vec![20, 10, 0]
+    );
+    assert_eq!(
+        s.range((Included(&75), Unbounded))
+            .rev()
+            .map(|x| *x.value())
+            .collect::<Vec<_>>(),
+        vec![90, 80]
+    );
+    assert_eq!(
+        s.range((Included(&50), Excluded(&50)))
+            .rev()
+            .map(|x| *x.value())
+            .collect::<Vec<_>>(),
+        vec![]
+    );
+
+    // Iterating from both ends stops where they meet.
+    let mut it = s.range(15..65);
+    assert_eq!(*it.next().unwrap().key(), 20);
+    assert_eq!(*it.next_back().unwrap().key(), 60);
+    assert_eq!(*it.next().unwrap().key(), 30);
+    assert_eq!(*it.next_back().unwrap().key(), 50);
+    assert_eq!(*it.next_back().unwrap().key(), 40);
+    assert!(it.next().is_none());
+    assert!(it.next_back().is_none());
+}
+
 // https://github.com/crossbeam-rs/crossbeam/issues/671
 #[test]
 fn iter_range2() {
@@ -691,6 +1066,117 @@ fn into_iter() {
     );
 }
 
+#[test]
+fn cursor() {
+    let s = SkipMap::new();
+    let mut c = s.cursor();
+    assert!(c.entry().is_none());
+    assert!(!c.move_next());
+    assert!(!c.move_prev());
+    assert!(!c.seek_front());
+
+    for &x in &[2, 4, 6, 8] {
+        s.insert(x, x * 10);
+    }
+
+    assert!(c.move_next());
+    assert_eq!(c.key(), Some(&2));
+    assert_eq!(c.value(), Some(&20));
+    assert!(!c.move_prev());
+    assert!(c.key().is_none());
+    assert!(c.move_prev());
+    assert_eq!(c.key(), Some(&8));
+    assert!(!c.move_next());
+    assert!(c.key().is_none());
+
+    assert!(c.seek_lower_bound(Bound::Included(&3)));
+    assert_eq!(c.key(), Some(&4));
+    assert!(c.seek_lower_bound(Bound::Excluded(&4)));
+    assert_eq!(c.key(), Some(&6));
+    assert!(!c.seek_lower_bound(Bound::Excluded(&8)));
+    assert!(c.seek_upper_bound(Bound::Excluded(&6)));
+    assert_eq!(c.key(), Some(&4));
+    assert!(!c.seek_upper_bound(Bound::Included(&1)));
+    assert!(c.seek_back());
+    assert_eq!(c.key(), Some(&8));
+    assert!(c.seek_front());
+    assert_eq!(c.key(), Some(&2));
+
+    let mut d = c.clone();
+    c.reset();
+    assert!(c.key().is_none());
+    assert_eq!(d.key(), Some(&2));
+    assert!(d.move_next());
+    assert_eq!(d.key(), Some(&4));
+}
+
+#[test]
+fn cursor_removed_entry() {
+    let s = SkipMap::new();
+    for x in 0..10 {
+        s.insert(x, x);
+    }
+
+    let mut c = s.cursor();
+    assert!(c.seek_lower_bound(Bound::Included(&5)));
+
+    // Removing the current entry and its neighbors doesn't disturb the cursor.
+    s.remove(&4);
+    s.remove(&5);
+    s.remove(&6);
+    assert!(c.entry().unwrap().is_removed());
+    assert_eq!(c.value(), Some(&5));
+    assert!(c.move_next());
+    assert_eq!(c.key(), Some(&7));
+
+    assert!(c.seek_lower_bound(Bound::Included(&7)));
+    s.remove(&7);
+    assert!(c.move_prev());
+    assert_eq!(c.key(), Some(&3));
+
+    // A replaced entry counts as removed as well.
+    s.insert(3, 30);
+    assert!(c.entry().unwrap().is_removed());
+    assert!(c.move_next());
+    assert_eq!(c.key(), Some(&8));
+    assert!(c.move_prev());
+    assert_eq!(c.value(), Some(&30));
+}
+
+#[test]
+fn concurrent_cursor() {
+    const N: usize = 1000;
+
+    let s = SkipMap::new();
+    for x in 0..N {
+        s.insert(x, ());
+    }
+
+    thread::scope(|scope| {
+        scope.spawn(|_| {
+            for x in (1..N).step_by(2) {
+                s.remove(&x);
+            }
+        });
+        scope.spawn(|_| {
+            let mut c = s.cursor();
+            let mut seen = Vec::new();
+            while c.move_next() {
+                seen.push(*c.key().unwrap());
+            }
+
+            // The scan made progress in order and saw every entry that was never removed.
+            assert!(seen.windows(2).all(|w| w[0] < w[1]));
+            for x in (0..N).step_by(2) {
+                assert!(seen.contains(&x));
+            }
+        });
+    })
+    .unwrap();
+
+    assert_eq!(s.len(), N / 2);
+}
+
 #[test]
 fn clear() {
     let s = SkipMap::new();
@@ -704,3 +1190,138 @@ fn clear() {
     assert!(s.is_empty());
     assert_eq!(s.len(), 0);
 }
+
+#[test]
+fn with_comparator() {
+    let s = SkipMap::with_comparator(|a: &i32, b: &i32| b.cmp(a));
+    for &x in &[4, 2, 12, 8, 7, 11, 5] {
+        s.insert(x, x * 10);
+    }
+
+    assert_eq!(s.len(), 7);
+    assert_eq!(*s.front().unwrap().key(), 12);
+    assert_eq!(*s.back().unwrap().key(), 2);
+    assert_eq!(*s.get(&7).unwrap().value(), 70);
+    assert!(s.get(&3).is_none());
+    assert_eq!(*s.get(&8).unwrap().next().unwrap().key(), 7);
+
+    assert_eq!(
+        s.iter().map(|e| *e.key()).collect::<Vec<_>>(),
+        vec![12, 11, 8, 7, 5, 4, 2]
+    );
+    assert_eq!(
+        s.range((Bound::Included(8), Bound::Included(4)))
+            .map(|e| *e.key())
+            .collect::<Vec<_>>(),
+        vec![8, 7, 5, 4]
+    );
+    assert_eq!(
+        s.range((Bound::Included(8), Bound::Included(4)))
+            .rev()
+            .map(|e| *e.key())
+            .collect::<Vec<_>>(),
+        vec![4, 5, 7, 8]
+    );
+    assert_eq!(*s.lower_bound(Bound::Excluded(&8)).unwrap().key(), 7);
+
+    assert_eq!(*s.pop_front().unwrap().key(), 12);
+    assert_eq!(*s.remove(&2).unwrap().value(), 20);
+    assert_eq!(
+        s.iter().map(|e| *e.key()).collect::<Vec<_>>(),
+        vec![11, 8, 7, 5, 4]
+    );
+}
+
+#[test]
+fn with_comparator_borrowed() {
+    use crossbeam_skiplist::comparator::Comparator;
+    use std::cmp::Ordering;
+
+    struct CaseInsensitive;
+
+    impl Comparator<String, str> for CaseInsensitive {
+        fn compare(&self, lhs: &String, rhs: &str) -> Ordering {
+            lhs.to_lowercase().cmp(&rhs.to_lowercase())
+        }
+    }
+
+    impl Comparator<String> for CaseInsensitive {
+        fn compare(&self, lhs: &String, rhs: &String) -> Ordering {
+            self.compare(lhs, rhs.as_str())
+        }
+    }
+
+    let s = SkipMap::with_comparator(CaseInsensitive);
+    s.insert("Banana".to_string(), 1);
+    s.insert("apple".to_string(), 2);
+    s.insert("BANANA".to_string(), 3);
+
+    assert_eq!(s.len(), 2);
+    assert_eq!(*s.get("banana").unwrap().value(), 3);
+    assert_eq!(s.get("banana").unwrap().key(), "BANANA");
+    assert_eq!(*s.get("APPLE").unwrap().value(), 2);
+    assert!(s.contains_key("Apple"));
+    assert_eq!(
+        s.range::<str, _>((Bound::Excluded("APPLE"), Bound::Unbounded))
+            .map(|e| *e.value())
+            .collect::<Vec<_>>(),
+        vec![3]
+    );
+}
+
+#[test]
+fn with_collector() {
+    struct Value(Arc<AtomicUsize>);
+
+    impl Drop for Value {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let drops = Arc::new(AtomicUsize::new(0));
+    let collector = Collector::new();
+    let s = SkipMap::with_collector(collector.clone());
+    assert!(s.collector() == &collector);
+
+    collector.with(|| {
+        for i in 0..100 {
+            s.insert(i, Value(drops.clone()));
+        }
+        for i in 0..50 {
+            assert!(s.remove(&i).is_some());
+        }
+
+        // The removed entries are destroyed after a few flushes.
+        for _ in 0..100 {
+            if drops.load(Ordering::SeqCst) == 50 {
+                break;
+            }
+            s.flush();
+        }
+        assert_eq!(drops.load(Ordering::SeqCst), 50);
+    });
+    assert_eq!(s.len(), 50);
+
+    // The map can be used without registering the current thread with the collector.
+    s.insert(0, Value(drops.clone()));
+    assert!(s.contains_key(&0));
+    assert_eq!(*s.front().unwrap().key(), 0);
+    s.clear();
+    assert!(s.is_empty());
+}
+
+#[test]
+fn default_collector_inside_other_collector() {
+    let collector = Collector::new();
+    let s = SkipMap::new();
+    assert!(s.collector() == epoch::default_collector());
+
+    collector.with(|| {
+        s.insert(1, 10);
+        assert_eq!(*s.get(&1).unwrap().value(), 10);
+        assert_eq!(*s.remove(&1).unwrap().value(), 10);
+        s.flush();
+    });
+    assert!(s.is_empty());
+}
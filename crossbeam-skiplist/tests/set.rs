@@ -1,4 +1,4 @@
-use crossbeam_skiplist::SkipSet;
+use crossbeam_skiplist::{set::Entry, SkipSet};
 use crossbeam_utils::thread;
 use std::{iter, ops::Bound, sync::Barrier};
 
@@ -661,6 +661,65 @@ fn iter_range() {
     );
 }
 
+#[test]
+fn iter_range_rev() {
+    use std::ops::Bound::*;
+    let s = SkipSet::new();
+    for x in (0..10).map(|x| x * 10) {
+        s.insert(x);
+    }
+
+    assert_eq!(
+        s.range(..).rev().map(|x| *x.value()).collect::<Vec<_>>(),
+        vec![90, 80, 70, 60, 50, 40, 30, 20, 10, 0]
+    );
+    assert_eq!(
+        s.range((Excluded(&25), Included(&80)))
+            .rev()
+            .map(|x| *x.value())
+            .collect::<Vec<_>>(),
+        vec![80, 70, 60, 50, 40, 30]
+    );
+    assert_eq!(
+        s.range((Included(&30), Excluded(&80)))
+            .rev()
+            .map(|x| *x.value())
+            .collect::<Vec<_>>(),
+        vec![70, 60, 50, 40, 30]
+    );
+    assert_eq!(
+        s.range((Unbounded, Included(&25)))
+            .rev()
+            .map(|x| *x.value())
+            .collect::<Vec<_>>(),
+        vec![20, 10, 0]
+    );
+    assert_eq!(
+        s.range((Included(&75), Unbounded))
+            .rev()
+            .map(|x| *x.value())
+            .collect::<Vec<_>>(),
+        vec![90, 80]
+    );
+    assert_eq!(
+        s.range((Included(&50), Excluded(&50)))
+            .rev()
+            .map(|x| *x.value())
+            .collect::<Vec<_>>(),
+        vec![]
+    );
+
+    // Iterating from both ends stops where they meet.
+    let mut it = s.range(15..65);
+    assert_eq!(*it.next().unwrap().value(), 20);
+    assert_eq!(*it.next_back().unwrap().value(), 60);
+    assert_eq!(*it.next().unwrap().value(), 30);
+    assert_eq!(*it.next_back().unwrap().value(), 50);
+    assert_eq!(*it.next_back().unwrap().value(), 40);
+    assert!(it.next().is_none());
+    assert!(it.next_back().is_none());
+}
+
 // https://github.com/crossbeam-rs/crossbeam/issues/671
 #[test]
 fn iter_range2() {
@@ -692,3 +751,105 @@ fn clear() {
     assert!(s.is_empty());
     assert_eq!(s.len(), 0);
 }
+
+#[test]
+fn with_comparator() {
+    let s = SkipSet::with_comparator(|a: &(i32, &str), b: &(i32, &str)| a.0.cmp(&b.0));
+    s.insert((3, "c"));
+    s.insert((1, "a"));
+    s.insert((2, "b"));
+    s.insert((1, "z"));
+
+    assert_eq!(s.len(), 3);
+    assert_eq!(*s.get(&(1, "")).unwrap(), (1, "z"));
+    assert_eq!(s.front().unwrap().0, 1);
+    assert_eq!(s.back().unwrap().0, 3);
+    assert_eq!(
+        s.iter().map(|e| e.1).collect::<Vec<_>>(),
+        vec!["z", "b", "c"]
+    );
+    assert!(s.remove(&(2, "")).is_some());
+    assert!(!s.contains(&(2, "b")));
+}
+
+fn values<'a>(iter: impl Iterator<Item = Entry<'a, i32>>) -> Vec<i32> {
+    iter.map(|e| *e).collect()
+}
+
+#[test]
+fn union() {
+    let a: SkipSet<i32> = vec![1, 3, 5, 7].into_iter().collect();
+    let b: SkipSet<i32> = vec![0, 3, 4, 8, 9].into_iter().collect();
+    let empty = SkipSet::new();
+
+    assert_eq!(values(a.union(&b)), vec![0, 1, 3, 4, 5, 7, 8, 9]);
+    assert_eq!(values(b.union(&a)), vec![0, 1, 3, 4, 5, 7, 8, 9]);
+    assert_eq!(values(a.union(&empty)), vec![1, 3, 5, 7]);
+    assert_eq!(values(empty.union(&b)), vec![0, 3, 4, 8, 9]);
+    assert_eq!(values(a.union(&a)), vec![1, 3, 5, 7]);
+
+    // Common values come from `self`.
+    let e = a.union(&b).find(|e| **e == 3).unwrap();
+    assert!(e.remove());
+    assert!(!a.contains(&3));
+    assert!(b.contains(&3));
+}
+
+#[test]
+fn intersection() {
+    let a: SkipSet<i32> = vec![1, 3, 5, 7, 9].into_iter().collect();
+    let b: SkipSet<i32> = vec![0, 3, 4, 7, 8, 9, 10].into_iter().collect();
+    let empty = SkipSet::new();
+
+    assert_eq!(values(a.intersection(&b)), vec![3, 7, 9]);
+    assert_eq!(values(b.intersection(&a)), vec![3, 7, 9]);
+    assert_eq!(values(a.intersection(&empty)), vec![]);
+    assert_eq!(values(empty.intersection(&a)), vec![]);
+    assert_eq!(values(a.intersection(&a)), vec![1, 3, 5, 7, 9]);
+
+    let large: SkipSet<i32> = (0..10_000).collect();
+    let small: SkipSet<i32> = vec![-1, 17, 4096, 9999, 10_000].into_iter().collect();
+    assert_eq!(values(small.intersection(&large)), vec![17, 4096, 9999]);
+    assert_eq!(values(large.intersection(&small)), vec![17, 4096, 9999]);
+}
+
+#[test]
+fn difference() {
+    let a: SkipSet<i32> = vec![1, 3, 5, 7, 9].into_iter().collect();
+    let b: SkipSet<i32> = vec![0, 3, 4, 7, 8].into_iter().collect();
+    let empty = SkipSet::new();
+
+    assert_eq!(values(a.difference(&b)), vec![1, 5, 9]);
+    assert_eq!(values(b.difference(&a)), vec![0, 4, 8]);
+    assert_eq!(values(a.difference(&empty)), vec![1, 3, 5, 7, 9]);
+    assert_eq!(values(empty.difference(&a)), vec![]);
+    assert_eq!(values(a.difference(&a)), vec![]);
+
+    let large: SkipSet<i32> = (0..10_000).collect();
+    let small: SkipSet<i32> = vec![-1, 17, 10_000].into_iter().collect();
+    assert_eq!(values(small.difference(&large)), vec![-1, 10_000]);
+    assert_eq!(large.difference(&small).count(), 9999);
+}
+
+#[test]
+fn set_operations_with_comparator() {
+    let rev = |a: &i32, b: &i32| b.cmp(a);
+    let a = SkipSet::with_comparator(rev);
+    let b = SkipSet::with_comparator(rev);
+    for &x in &[1, 2, 3, 4] {
+        a.insert(x);
+    }
+    for &x in &[3, 4, 5] {
+        b.insert(x);
+    }
+
+    assert_eq!(
+        a.union(&b).map(|e| *e).collect::<Vec<_>>(),
+        vec![5, 4, 3, 2, 1]
+    );
+    assert_eq!(
+        a.intersection(&b).map(|e| *e).collect::<Vec<_>>(),
+        vec![4, 3]
+    );
+    assert_eq!(a.difference(&b).map(|e| *e).collect::<Vec<_>>(), vec![2, 1]);
+}
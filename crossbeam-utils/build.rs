@@ -1,4 +1,5 @@
 use autocfg::AutoCfg;
+use std::env;
 
 // The rustc-cfg strings below are *not* public API. Please let us know by
 // opening a GitHub issue if your build environment requires some way to enable
@@ -20,4 +21,16 @@ fn main() {
     cfg.emit_type_cfg("core::sync::atomic::AtomicU32", "has_atomic_u32");
     cfg.emit_type_cfg("core::sync::atomic::AtomicU64", "has_atomic_u64");
     cfg.emit_type_cfg("core::sync::atomic::AtomicU128", "has_atomic_u128");
+
+    // Without `AtomicU128`, 16-byte atomics can still be implemented with inline assembly on
+    // x86-64 if `cmpxchg16b` is statically known to be available.
+    let target_arch = env::var("CARGO_CFG_TARGET_ARCH").unwrap_or_default();
+    let target_features = env::var("CARGO_CFG_TARGET_FEATURE").unwrap_or_default();
+    if target_arch == "x86_64"
+        && target_features.split(',').any(|f| f == "cmpxchg16b")
+        && !cfg.probe_type("core::sync::atomic::AtomicU128")
+        && cfg.probe_rustc_version(1, 59)
+    {
+        println!("cargo:rustc-cfg=has_cmpxchg16b");
+    }
 }
@@ -17,6 +17,9 @@ use std::panic::{RefUnwindSafe, UnwindSafe};
 #[cfg(not(crossbeam_loom))]
 use super::seq_lock::SeqLock;
 
+#[cfg(all(has_cmpxchg16b, not(crossbeam_loom)))]
+use super::cmpxchg16b;
+
 /// A thread-safe mutable memory location.
 ///
 /// This type is equivalent to [`Cell`], except it can also be shared among multiple threads.
@@ -109,6 +112,10 @@ impl<T> AtomicCell<T> {
     /// // operations on them will have to use global locks for synchronization.
     /// assert_eq!(AtomicCell::<[u8; 1000]>::is_lock_free(), false);
     /// ```
+    ///
+    /// On x86-64, 16-byte types aligned to 16 bytes (including `u128` and `i128`) are lock-free if
+    /// the `cmpxchg16b` target feature is enabled at compile time, e.g. with
+    /// `-C target-cpu=native`. Structs can opt into this with `#[repr(align(16))]`.
     pub const fn is_lock_free() -> bool {
         atomic_is_lock_free::<T>()
     }
@@ -175,6 +182,8 @@ impl<T: ?Sized> AtomicCell<T> {
 impl<T: Default> AtomicCell<T> {
     /// Takes the value of the atomic cell, leaving `Default::default()` in its place.
     ///
+    /// For an `AtomicCell<Option<T>>`, this claims the value and leaves `None` behind.
+    ///
     /// # Examples
     ///
     /// ```
@@ -185,6 +194,11 @@ impl<T: Default> AtomicCell<T> {
     ///
     /// assert_eq!(five, 5);
     /// assert_eq!(a.into_inner(), 0);
+    ///
+    /// let slot = AtomicCell::new(Some(String::from("job")));
+    ///
+    /// assert_eq!(slot.take(), Some(String::from("job")));
+    /// assert_eq!(slot.take(), None);
     /// ```
     pub fn take(&self) -> T {
         self.swap(Default::default())
@@ -258,6 +272,67 @@ impl<T: Copy + Eq> AtomicCell<T> {
     pub fn compare_exchange(&self, current: T, new: T) -> Result<T, T> {
         unsafe { atomic_compare_exchange_weak(self.value.get(), current, new) }
     }
+
+    /// Fetches the value, and applies a function to it that returns an optional new value.
+    ///
+    /// Returns `Ok(previous_value)` if the function returned `Some(_)`, else
+    /// `Err(previous_value)`.
+    ///
+    /// The function may be called multiple times if the value has been changed by other threads in
+    /// the meantime, as long as it returns `Some(_)`, but it will have been applied only once to
+    /// the stored value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::atomic::AtomicCell;
+    ///
+    /// let a = AtomicCell::new(7);
+    ///
+    /// assert_eq!(a.fetch_update(|_| None), Err(7));
+    /// assert_eq!(a.fetch_update(|a| Some(a + 1)), Ok(7));
+    /// assert_eq!(a.fetch_update(|a| Some(a + 1)), Ok(8));
+    /// assert_eq!(a.load(), 9);
+    /// ```
+    pub fn fetch_update<F>(&self, mut f: F) -> Result<T, T>
+    where
+        F: FnMut(T) -> Option<T>,
+    {
+        let mut prev = self.load();
+        while let Some(next) = f(prev) {
+            match self.compare_exchange(prev, next) {
+                x @ Ok(_) => return x,
+                Err(next_prev) => prev = next_prev,
+            }
+        }
+        Err(prev)
+    }
+
+    /// Replaces the value with the one computed by `f` from the current value, and returns the
+    /// previous value.
+    ///
+    /// The function may be called multiple times if the value has been changed by other threads in
+    /// the meantime, but it will have been applied only once to the stored value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::atomic::AtomicCell;
+    ///
+    /// let a = AtomicCell::new(Some(7));
+    ///
+    /// assert_eq!(a.replace_with(|x| x.filter(|&x| x > 10)), Some(7));
+    /// assert_eq!(a.load(), None);
+    /// ```
+    pub fn replace_with<F>(&self, mut f: F) -> T
+    where
+        F: FnMut(T) -> T,
+    {
+        match self.fetch_update(|x| Some(f(x))) {
+            Ok(prev) => prev,
+            Err(_) => unreachable!(),
+        }
+    }
 }
 
 macro_rules! impl_arithmetic {
@@ -517,6 +592,18 @@ impl_arithmetic!(i64, atomic::AtomicI64, "let a = AtomicCell::new(7i64);");
 impl_arithmetic!(u128, atomic::AtomicU128, "let a = AtomicCell::new(7u128);");
 #[cfg(all(has_atomic_u128, not(crossbeam_loom)))]
 impl_arithmetic!(i128, atomic::AtomicI128, "let  a = AtomicCell::new(7i128);");
+#[cfg(all(has_cmpxchg16b, not(crossbeam_loom)))]
+impl_arithmetic!(
+    u128,
+    cmpxchg16b::AtomicU128,
+    "let a = AtomicCell::new(7u128);"
+);
+#[cfg(all(has_cmpxchg16b, not(crossbeam_loom)))]
+impl_arithmetic!(
+    i128,
+    cmpxchg16b::AtomicI128,
+    "let a = AtomicCell::new(7i128);"
+);
 
 impl_arithmetic!(
     usize,
@@ -819,6 +906,8 @@ macro_rules! atomic {
             atomic!(@check, $t, atomic::AtomicU64, $a, $atomic_op);
             #[cfg(has_atomic_u128)]
             atomic!(@check, $t, atomic::AtomicU128, $a, $atomic_op);
+            #[cfg(all(has_cmpxchg16b, not(crossbeam_loom)))]
+            atomic!(@check, $t, cmpxchg16b::AtomicU128, $a, $atomic_op);
 
             #[cfg(crossbeam_loom)]
             unimplemented!("loom does not support non-atomic atomic ops");
@@ -842,6 +931,8 @@ const fn atomic_is_lock_free<T>() -> bool {
     let is_lock_free = is_lock_free | can_transmute::<T, atomic::AtomicU64>();
     #[cfg(has_atomic_u128)]
     let is_lock_free = is_lock_free | can_transmute::<T, atomic::AtomicU128>();
+    #[cfg(all(has_cmpxchg16b, not(crossbeam_loom)))]
+    let is_lock_free = is_lock_free | can_transmute::<T, cmpxchg16b::AtomicU128>();
     is_lock_free
 }
 
@@ -0,0 +1,138 @@
+//! 128-bit atomic integers built on the `cmpxchg16b` instruction.
+//!
+//! `core::sync::atomic::AtomicU128` is not available on stable Rust, but x86-64 processors that
+//! support `cmpxchg16b` can still perform 16-byte atomic operations. Every operation is a
+//! compare-and-swap loop around that one instruction, which also acts as a full barrier, so the
+//! requested orderings are always satisfied.
+//!
+//! This module is only compiled if the `cmpxchg16b` target feature is enabled at compile time,
+//! e.g. with `-C target-cpu=native` or `-C target-feature=+cmpxchg16b`.
+
+use core::arch::asm;
+use core::cell::UnsafeCell;
+use core::sync::atomic::Ordering;
+
+/// Atomically compares the value at `dst` to `old` and, if equal, replaces it with `new`.
+///
+/// Returns the value at `dst` before the operation.
+///
+/// # Safety
+///
+/// `dst` must be valid for writes and aligned to 16 bytes.
+#[inline]
+unsafe fn cmpxchg16b(dst: *mut u128, old: u128, new: u128) -> u128 {
+    let (prev_lo, prev_hi): (u64, u64);
+
+    // LLVM reserves `rbx`, so the low half of `new` is swapped into it around the instruction.
+    asm!(
+        "xchg {new_lo}, rbx",
+        "lock cmpxchg16b xmmword ptr [{dst}]",
+        "mov rbx, {new_lo}",
+        dst = in(reg) dst,
+        new_lo = inout(reg) new as u64 => _,
+        in("rcx") (new >> 64) as u64,
+        inout("rax") old as u64 => prev_lo,
+        inout("rdx") (old >> 64) as u64 => prev_hi,
+        options(nostack),
+    );
+
+    (prev_hi as u128) << 64 | prev_lo as u128
+}
+
+macro_rules! atomic_int {
+    ($atomic:ident, $int:ty) => {
+        /// An integer type which can be safely shared between threads.
+        #[repr(C, align(16))]
+        pub(crate) struct $atomic {
+            v: UnsafeCell<$int>,
+        }
+
+        #[allow(dead_code)] // `AtomicI128` is only used for arithmetic.
+        impl $atomic {
+            /// Performs a compare-and-swap, returning the previous value.
+            #[inline]
+            fn cas(&self, current: $int, new: $int) -> $int {
+                unsafe {
+                    cmpxchg16b(self.v.get() as *mut u128, current as u128, new as u128) as $int
+                }
+            }
+
+            /// Repeatedly applies `f` to the current value until the update succeeds, returning
+            /// the previous value.
+            #[inline]
+            fn update<F: Fn($int) -> $int>(&self, f: F) -> $int {
+                // Reading the value non-atomically would be a data race, so start with a guess
+                // and let the first compare-and-swap fetch the real value.
+                let mut current = 0;
+                loop {
+                    let prev = self.cas(current, f(current));
+                    if prev == current {
+                        return prev;
+                    }
+                    current = prev;
+                }
+            }
+
+            #[inline]
+            pub(crate) fn load(&self, _order: Ordering) -> $int {
+                // Exchanging zero for zero either fails and returns the current value, or succeeds
+                // without changing it.
+                self.cas(0, 0)
+            }
+
+            #[inline]
+            pub(crate) fn store(&self, val: $int, order: Ordering) {
+                self.swap(val, order);
+            }
+
+            #[inline]
+            pub(crate) fn swap(&self, val: $int, _order: Ordering) -> $int {
+                self.update(|_| val)
+            }
+
+            #[inline]
+            pub(crate) fn compare_exchange_weak(
+                &self,
+                current: $int,
+                new: $int,
+                _success: Ordering,
+                _failure: Ordering,
+            ) -> Result<$int, $int> {
+                let prev = self.cas(current, new);
+                if prev == current {
+                    Ok(prev)
+                } else {
+                    Err(prev)
+                }
+            }
+
+            #[inline]
+            pub(crate) fn fetch_add(&self, val: $int, _order: Ordering) -> $int {
+                self.update(|x| x.wrapping_add(val))
+            }
+
+            #[inline]
+            pub(crate) fn fetch_sub(&self, val: $int, _order: Ordering) -> $int {
+                self.update(|x| x.wrapping_sub(val))
+            }
+
+            #[inline]
+            pub(crate) fn fetch_and(&self, val: $int, _order: Ordering) -> $int {
+                self.update(|x| x & val)
+            }
+
+            #[inline]
+            pub(crate) fn fetch_or(&self, val: $int, _order: Ordering) -> $int {
+                self.update(|x| x | val)
+            }
+
+            #[inline]
+            pub(crate) fn fetch_xor(&self, val: $int, _order: Ordering) -> $int {
+                self.update(|x| x ^ val)
+            }
+        }
+    };
+}
+
+atomic_int!(AtomicU128, u128);
+atomic_int!(AtomicI128, i128);
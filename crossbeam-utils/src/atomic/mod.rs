@@ -26,6 +26,8 @@ cfg_if! {
 }
 
 mod atomic_cell;
+#[cfg(all(has_cmpxchg16b, not(crossbeam_loom)))]
+mod cmpxchg16b;
 mod consume;
 
 pub use self::atomic_cell::AtomicCell;
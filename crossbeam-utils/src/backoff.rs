@@ -4,6 +4,7 @@ use core::fmt;
 
 const SPIN_LIMIT: u32 = 6;
 const YIELD_LIMIT: u32 = 10;
+const MAX_LIMIT: u32 = 31;
 
 /// Performs exponential backoff in spin loops.
 ///
@@ -72,6 +73,8 @@ const YIELD_LIMIT: u32 = 10;
 /// }
 /// ```
 ///
+/// The number of steps and jitter can be configured with [`BackoffBuilder`].
+///
 /// [`is_completed`]: Backoff::is_completed
 /// [`std::thread::park()`]: std::thread::park
 /// [`Condvar`]: std::sync::Condvar
@@ -79,6 +82,10 @@ const YIELD_LIMIT: u32 = 10;
 /// [`unpark()`]: std::thread::Thread::unpark
 pub struct Backoff {
     step: Cell<u32>,
+    spin_limit: u32,
+    yield_limit: u32,
+    /// The state of the jitter generator, or zero if jitter is disabled.
+    rng: Cell<u32>,
 }
 
 impl Backoff {
@@ -93,7 +100,46 @@ impl Backoff {
     /// ```
     #[inline]
     pub fn new() -> Self {
-        Backoff { step: Cell::new(0) }
+        Backoff {
+            step: Cell::new(0),
+            spin_limit: SPIN_LIMIT,
+            yield_limit: YIELD_LIMIT,
+            rng: Cell::new(0),
+        }
+    }
+
+    /// Creates a builder for configuring a `Backoff`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::Backoff;
+    ///
+    /// let backoff = Backoff::builder().spin_limit(4).jitter(true).build();
+    /// ```
+    #[inline]
+    pub fn builder() -> BackoffBuilder {
+        BackoffBuilder::new()
+    }
+
+    /// Returns the number of times to execute the *YIELD* or *PAUSE* instruction at the current
+    /// step.
+    #[inline]
+    fn spins(&self, step: u32) -> u32 {
+        let max: u32 = 1 << step;
+
+        let x = self.rng.get();
+        if x == 0 {
+            return max;
+        }
+
+        // Advance the xorshift generator and pick a count in `1..=max`.
+        let mut x = x;
+        x ^= x << 13;
+        x ^= x >> 17;
+        x ^= x << 5;
+        self.rng.set(x);
+        1 + x % max
     }
 
     /// Resets the `Backoff`.
@@ -144,14 +190,14 @@ impl Backoff {
     /// ```
     #[inline]
     pub fn spin(&self) {
-        for _ in 0..1 << self.step.get().min(SPIN_LIMIT) {
+        for _ in 0..self.spins(self.step.get().min(self.spin_limit)) {
             // TODO(taiki-e): once we bump the minimum required Rust version to 1.49+,
             // use [`core::hint::spin_loop`] instead.
             #[allow(deprecated)]
             atomic::spin_loop_hint();
         }
 
-        if self.step.get() <= SPIN_LIMIT {
+        if self.step.get() <= self.spin_limit {
             self.step.set(self.step.get() + 1);
         }
     }
@@ -206,8 +252,8 @@ impl Backoff {
     /// [`AtomicBool`]: std::sync::atomic::AtomicBool
     #[inline]
     pub fn snooze(&self) {
-        if self.step.get() <= SPIN_LIMIT {
-            for _ in 0..1 << self.step.get() {
+        if self.step.get() <= self.spin_limit {
+            for _ in 0..self.spins(self.step.get()) {
                 // TODO(taiki-e): once we bump the minimum required Rust version to 1.49+,
                 // use [`core::hint::spin_loop`] instead.
                 #[allow(deprecated)]
@@ -215,7 +261,7 @@ impl Backoff {
             }
         } else {
             #[cfg(not(feature = "std"))]
-            for _ in 0..1 << self.step.get() {
+            for _ in 0..self.spins(self.step.get().min(MAX_LIMIT)) {
                 // TODO(taiki-e): once we bump the minimum required Rust version to 1.49+,
                 // use [`core::hint::spin_loop`] instead.
                 #[allow(deprecated)]
@@ -226,7 +272,7 @@ impl Backoff {
             ::std::thread::yield_now();
         }
 
-        if self.step.get() <= YIELD_LIMIT {
+        if self.step.get() <= self.yield_limit {
             self.step.set(self.step.get() + 1);
         }
     }
@@ -274,7 +320,7 @@ impl Backoff {
     /// [`AtomicBool`]: std::sync::atomic::AtomicBool
     #[inline]
     pub fn is_completed(&self) -> bool {
-        self.step.get() > YIELD_LIMIT
+        self.step.get() > self.yield_limit
     }
 }
 
@@ -292,3 +338,165 @@ impl Default for Backoff {
         Backoff::new()
     }
 }
+
+/// Configures a [`Backoff`].
+///
+/// The number of steps is controlled by two limits:
+///
+/// - [`spin_limit`]: Up to this step, [`spin`] and [`snooze`] execute *YIELD* or *PAUSE*
+///   instructions, twice as many at each step. Defaults to 6.
+/// - [`yield_limit`]: After this step, [`is_completed`] returns `true`. Between the two limits,
+///   [`snooze`] yields the current thread to the OS scheduler. Defaults to 10.
+///
+/// Lowering the spin limit bounds the time a thread spends in a single call to [`spin`], which
+/// is at most 2<sup>`spin_limit`</sup> instructions.
+///
+/// With [`jitter`] enabled, each step executes a random number of instructions between one and
+/// the usual count, so that threads retrying contended operations at the same time drift apart
+/// instead of colliding again.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::Backoff;
+///
+/// let builder = Backoff::builder().spin_limit(3).yield_limit(5).jitter(true);
+///
+/// let backoff = builder.build();
+/// while !backoff.is_completed() {
+///     backoff.snooze();
+/// }
+/// ```
+///
+/// [`spin_limit`]: BackoffBuilder::spin_limit
+/// [`yield_limit`]: BackoffBuilder::yield_limit
+/// [`jitter`]: BackoffBuilder::jitter
+/// [`spin`]: Backoff::spin
+/// [`snooze`]: Backoff::snooze
+/// [`is_completed`]: Backoff::is_completed
+#[derive(Clone, Debug)]
+pub struct BackoffBuilder {
+    spin_limit: u32,
+    yield_limit: u32,
+    jitter: bool,
+}
+
+impl BackoffBuilder {
+    /// Creates a builder with the default configuration of [`Backoff::new`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::BackoffBuilder;
+    ///
+    /// let backoff = BackoffBuilder::new().build();
+    /// ```
+    #[inline]
+    pub fn new() -> BackoffBuilder {
+        BackoffBuilder {
+            spin_limit: SPIN_LIMIT,
+            yield_limit: YIELD_LIMIT,
+            jitter: false,
+        }
+    }
+
+    /// Sets the last step that spins instead of yielding.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is greater than 31.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::Backoff;
+    ///
+    /// // Never execute more than 8 *PAUSE* instructions in a row.
+    /// let backoff = Backoff::builder().spin_limit(3).build();
+    /// ```
+    #[inline]
+    pub fn spin_limit(mut self, limit: u32) -> BackoffBuilder {
+        assert!(limit <= MAX_LIMIT, "spin limit must be at most 31");
+        self.spin_limit = limit;
+        self
+    }
+
+    /// Sets the last step before backoff is completed.
+    ///
+    /// If this is not greater than the spin limit, [`Backoff::snooze`] never yields the thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::Backoff;
+    ///
+    /// let backoff = Backoff::builder().yield_limit(0).build();
+    ///
+    /// backoff.snooze();
+    /// assert!(backoff.is_completed());
+    /// ```
+    #[inline]
+    pub fn yield_limit(mut self, limit: u32) -> BackoffBuilder {
+        self.yield_limit = limit;
+        self
+    }
+
+    /// Enables or disables randomized jitter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::Backoff;
+    ///
+    /// let backoff = Backoff::builder().jitter(true).build();
+    /// ```
+    #[inline]
+    pub fn jitter(mut self, jitter: bool) -> BackoffBuilder {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Creates a `Backoff` with this configuration.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::Backoff;
+    ///
+    /// let builder = Backoff::builder().spin_limit(4);
+    ///
+    /// let a = builder.build();
+    /// let b = builder.build();
+    /// ```
+    #[inline]
+    pub fn build(&self) -> Backoff {
+        let rng = if self.jitter {
+            // Seed the generator with the address of a local variable. Threads have separate
+            // stacks, so concurrent backoffs get different sequences. The seed is scrambled with
+            // the finalizer of MurmurHash3 and must not be zero.
+            let local = 0u8;
+            let mut x = &local as *const u8 as usize as u32;
+            x ^= x >> 16;
+            x = x.wrapping_mul(0x85eb_ca6b);
+            x ^= x >> 13;
+            x = x.wrapping_mul(0xc2b2_ae35);
+            x ^= x >> 16;
+            x | 1
+        } else {
+            0
+        };
+
+        Backoff {
+            step: Cell::new(0),
+            spin_limit: self.spin_limit,
+            yield_limit: self.yield_limit,
+            rng: Cell::new(rng),
+        }
+    }
+}
+
+impl Default for BackoffBuilder {
+    fn default() -> BackoffBuilder {
+        BackoffBuilder::new()
+    }
+}
@@ -1,3 +1,8 @@
+//! Padding and alignment to the length of a cache line.
+//!
+//! [`CachePadded`] aligns its value to [`CacheLine`], the cache line length of the target
+//! architecture. The other alignment markers in this module can be used to override it.
+
 use core::fmt;
 use core::ops::{Deref, DerefMut};
 
@@ -28,6 +33,25 @@ use core::ops::{Deref, DerefMut};
 ///
 /// The alignment of `CachePadded<T>` is the maximum of N bytes and the alignment of `T`.
 ///
+/// # Custom alignment
+///
+/// If the guess for the target architecture is wrong for the machines a program runs on, the
+/// alignment can be chosen explicitly with the second type parameter, which defaults to
+/// [`CacheLine`]. The alignment markers [`Align32`], [`Align64`], [`Align128`], and [`Align256`]
+/// are provided in the [`cache_padded`](crate::cache_padded) module, but any type works: the value is aligned to
+/// the alignment of `A`.
+///
+/// ```
+/// use crossbeam_utils::cache_padded::Align128;
+/// use crossbeam_utils::CachePadded;
+/// use std::mem;
+///
+/// let padded = CachePadded::<u8, Align128>::with_alignment(1);
+///
+/// assert_eq!(mem::align_of_val(&padded), 128);
+/// assert_eq!(mem::size_of_val(&padded), 128);
+/// ```
+///
 /// # Examples
 ///
 /// Alignment and padding:
@@ -59,6 +83,17 @@ use core::ops::{Deref, DerefMut};
 /// }
 /// ```
 #[derive(Clone, Copy, Default, Hash, PartialEq, Eq)]
+#[repr(C)]
+pub struct CachePadded<T, A = CacheLine> {
+    value: T,
+    align: [A; 0],
+}
+
+/// The assumed cache line length of the target architecture.
+///
+/// This is the default alignment of [`CachePadded`]. See its documentation for the length
+/// assumed on each architecture.
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
 // Starting from Intel's Sandy Bridge, spatial prefetcher is now pulling pairs of 64-byte cache
 // lines at a time, so we have to align to 128 bytes rather than 64.
 //
@@ -125,12 +160,30 @@ use core::ops::{Deref, DerefMut};
     )),
     repr(align(64))
 )]
-pub struct CachePadded<T> {
-    value: T,
-}
+pub struct CacheLine;
 
-unsafe impl<T: Send> Send for CachePadded<T> {}
-unsafe impl<T: Sync> Sync for CachePadded<T> {}
+/// An alignment of 32 bytes, for use with [`CachePadded`].
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+#[repr(align(32))]
+pub struct Align32;
+
+/// An alignment of 64 bytes, for use with [`CachePadded`].
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+#[repr(align(64))]
+pub struct Align64;
+
+/// An alignment of 128 bytes, for use with [`CachePadded`].
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+#[repr(align(128))]
+pub struct Align128;
+
+/// An alignment of 256 bytes, for use with [`CachePadded`].
+#[derive(Clone, Copy, Debug, Default, Hash, PartialEq, Eq)]
+#[repr(align(256))]
+pub struct Align256;
+
+unsafe impl<T: Send, A> Send for CachePadded<T, A> {}
+unsafe impl<T: Sync, A> Sync for CachePadded<T, A> {}
 
 impl<T> CachePadded<T> {
     /// Pads and aligns a value to the length of a cache line.
@@ -143,7 +196,30 @@ impl<T> CachePadded<T> {
     /// let padded_value = CachePadded::new(1);
     /// ```
     pub const fn new(t: T) -> CachePadded<T> {
-        CachePadded::<T> { value: t }
+        CachePadded::<T> {
+            value: t,
+            align: [],
+        }
+    }
+}
+
+impl<T, A> CachePadded<T, A> {
+    /// Pads and aligns a value to the alignment `A`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::cache_padded::Align256;
+    /// use crossbeam_utils::CachePadded;
+    ///
+    /// let padded_value = CachePadded::<_, Align256>::with_alignment(1);
+    /// assert_eq!(&*padded_value as *const i32 as usize % 256, 0);
+    /// ```
+    pub const fn with_alignment(t: T) -> CachePadded<T, A> {
+        CachePadded {
+            value: t,
+            align: [],
+        }
     }
 
     /// Returns the inner value.
@@ -162,7 +238,7 @@ impl<T> CachePadded<T> {
     }
 }
 
-impl<T> Deref for CachePadded<T> {
+impl<T, A> Deref for CachePadded<T, A> {
     type Target = T;
 
     fn deref(&self) -> &T {
@@ -170,13 +246,13 @@ impl<T> Deref for CachePadded<T> {
     }
 }
 
-impl<T> DerefMut for CachePadded<T> {
+impl<T, A> DerefMut for CachePadded<T, A> {
     fn deref_mut(&mut self) -> &mut T {
         &mut self.value
     }
 }
 
-impl<T: fmt::Debug> fmt::Debug for CachePadded<T> {
+impl<T: fmt::Debug, A> fmt::Debug for CachePadded<T, A> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("CachePadded")
             .field("value", &self.value)
@@ -184,8 +260,8 @@ impl<T: fmt::Debug> fmt::Debug for CachePadded<T> {
     }
 }
 
-impl<T> From<T> for CachePadded<T> {
+impl<T, A> From<T> for CachePadded<T, A> {
     fn from(t: T) -> Self {
-        CachePadded::new(t)
+        CachePadded::with_alignment(t)
     }
 }
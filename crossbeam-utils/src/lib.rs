@@ -16,6 +16,7 @@
 //! * [`Backoff`], for exponential backoff in spin loops.
 //! * [`CachePadded`], for padding and aligning a value to the length of a cache line.
 //! * [`scope`], for spawning threads that borrow local variables from the stack.
+//! * [`thread_id`], for compact per-thread IDs that can index into tables.
 //!
 //! [`AtomicCell`]: atomic::AtomicCell
 //! [`AtomicConsume`]: atomic::AtomicConsume
@@ -23,6 +24,7 @@
 //! [`ShardedLock`]: sync::ShardedLock
 //! [`WaitGroup`]: sync::WaitGroup
 //! [`scope`]: thread::scope
+//! [`thread_id`]: thread::thread_id
 
 #![doc(test(
     no_crate_inject,
@@ -97,11 +99,11 @@ cfg_if! {
 #[cfg_attr(feature = "nightly", cfg(target_has_atomic = "ptr"))]
 pub mod atomic;
 
-mod cache_padded;
+pub mod cache_padded;
 pub use crate::cache_padded::CachePadded;
 
 mod backoff;
-pub use crate::backoff::Backoff;
+pub use crate::backoff::{Backoff, BackoffBuilder};
 
 use cfg_if::cfg_if;
 
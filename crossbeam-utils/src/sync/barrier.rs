@@ -0,0 +1,216 @@
+use crate::primitive::sync::atomic::AtomicUsize;
+use crate::primitive::sync::{Condvar, Mutex};
+use crate::{Backoff, CachePadded};
+use core::sync::atomic::Ordering;
+use std::fmt;
+
+/// A reusable barrier that lets a fixed number of threads synchronize in phases.
+///
+/// Every call to [`wait`] blocks until `n` threads have called it, after which all of them are
+/// released and the barrier moves on to the next phase. The barrier can then be used again right
+/// away, which makes it a good fit for iterative algorithms where threads work in lockstep rounds.
+///
+/// Exactly one thread per phase is reported as the leader, which is convenient for doing
+/// per-round bookkeeping such as swapping buffers or checking for convergence.
+///
+/// Waiting threads spin for a short while before blocking, so that short phases don't pay for a
+/// round-trip through the OS.
+///
+/// # Barriers vs `std::sync::Barrier`
+///
+/// [`std::sync::Barrier`] is also reusable, but doesn't tell threads which phase they completed.
+/// This barrier reports the phase number as well, and avoids blocking when phases are short.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::sync::Barrier;
+/// use crossbeam_utils::thread;
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+///
+/// let barrier = Barrier::new(4);
+/// let leaders = AtomicUsize::new(0);
+///
+/// thread::scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(|_| {
+///             for round in 0..3 {
+///                 // Do some work for this round.
+///
+///                 let res = barrier.wait();
+///                 assert_eq!(res.phase(), round);
+///                 if res.is_leader() {
+///                     leaders.fetch_add(1, Ordering::SeqCst);
+///                 }
+///             }
+///         });
+///     }
+/// }).unwrap();
+///
+/// // One leader per round.
+/// assert_eq!(leaders.load(Ordering::SeqCst), 3);
+/// ```
+///
+/// [`wait`]: Barrier::wait
+pub struct Barrier {
+    /// The number of threads that need to arrive in each phase.
+    num_threads: usize,
+
+    /// The number of threads that have arrived in the current phase.
+    count: CachePadded<AtomicUsize>,
+
+    /// The current phase, incremented by the last thread to arrive.
+    ///
+    /// Waiting threads watch this value change, which serves the same purpose as flipping the
+    /// sense bit in a classic sense-reversing barrier.
+    phase: CachePadded<AtomicUsize>,
+
+    /// Used by threads that have spun for too long to block until the phase changes.
+    lock: Mutex<()>,
+    cvar: Condvar,
+}
+
+impl Barrier {
+    /// Creates a new barrier that releases threads in groups of `n`.
+    ///
+    /// A barrier created with `n == 0` behaves the same as one created with `n == 1`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::Barrier;
+    ///
+    /// let barrier = Barrier::new(8);
+    /// ```
+    pub fn new(n: usize) -> Barrier {
+        Barrier {
+            num_threads: n.max(1),
+            count: CachePadded::new(AtomicUsize::new(0)),
+            phase: CachePadded::new(AtomicUsize::new(0)),
+            lock: Mutex::new(()),
+            cvar: Condvar::new(),
+        }
+    }
+
+    /// Blocks the current thread until all threads have reached the barrier in this phase.
+    ///
+    /// The last thread to arrive becomes the leader of the phase.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::Barrier;
+    ///
+    /// let barrier = Barrier::new(1);
+    ///
+    /// let res = barrier.wait();
+    /// assert!(res.is_leader());
+    /// assert_eq!(res.phase(), 0);
+    ///
+    /// assert_eq!(barrier.wait().phase(), 1);
+    /// ```
+    pub fn wait(&self) -> BarrierWaitResult {
+        // The phase cannot advance before this thread arrives, so this is the current phase.
+        let phase = self.phase.load(Ordering::Acquire);
+
+        if self.count.fetch_add(1, Ordering::AcqRel) + 1 == self.num_threads {
+            // Reset the count for the next phase. Threads only start arriving for the next phase
+            // after they see the phase change below, so they will see the reset as well.
+            self.count.store(0, Ordering::Relaxed);
+
+            {
+                let _guard = self.lock.lock().unwrap();
+                self.phase.store(phase.wrapping_add(1), Ordering::Release);
+            }
+            self.cvar.notify_all();
+
+            return BarrierWaitResult {
+                leader: true,
+                phase,
+            };
+        }
+
+        let backoff = Backoff::new();
+        while self.phase.load(Ordering::Acquire) == phase {
+            if backoff.is_completed() {
+                let mut guard = self.lock.lock().unwrap();
+                while self.phase.load(Ordering::Acquire) == phase {
+                    guard = self.cvar.wait(guard).unwrap();
+                }
+                break;
+            }
+            backoff.snooze();
+        }
+
+        BarrierWaitResult {
+            leader: false,
+            phase,
+        }
+    }
+
+    /// Returns the number of threads that need to arrive in each phase.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::Barrier;
+    ///
+    /// let barrier = Barrier::new(3);
+    /// assert_eq!(barrier.num_threads(), 3);
+    /// ```
+    pub fn num_threads(&self) -> usize {
+        self.num_threads
+    }
+
+    /// Returns the number of phases that have been completed.
+    ///
+    /// If other threads are waiting on the barrier, the result may be out of date by the time it
+    /// is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::Barrier;
+    ///
+    /// let barrier = Barrier::new(1);
+    /// assert_eq!(barrier.phase(), 0);
+    ///
+    /// barrier.wait();
+    /// assert_eq!(barrier.phase(), 1);
+    /// ```
+    pub fn phase(&self) -> usize {
+        self.phase.load(Ordering::Acquire)
+    }
+}
+
+impl fmt::Debug for Barrier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Barrier")
+            .field("num_threads", &self.num_threads)
+            .field("phase", &self.phase())
+            .finish()
+    }
+}
+
+/// The result of [`Barrier::wait`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BarrierWaitResult {
+    leader: bool,
+    phase: usize,
+}
+
+impl BarrierWaitResult {
+    /// Returns `true` if this thread was the leader of the phase.
+    ///
+    /// Exactly one thread per phase is the leader.
+    pub fn is_leader(&self) -> bool {
+        self.leader
+    }
+
+    /// Returns the phase that was completed, starting from zero.
+    ///
+    /// The phase number wraps around on overflow.
+    pub fn phase(&self) -> usize {
+        self.phase
+    }
+}
@@ -0,0 +1,178 @@
+// Necessary for using `Mutex<usize>` for conditional variables
+#![allow(clippy::mutex_atomic)]
+
+use crate::primitive::sync::{Condvar, Mutex};
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// A synchronization aid that lets threads wait until a number of events have happened.
+///
+/// A latch is created with a count, which is decremented by calls to [`count_down`]. Threads
+/// calling [`wait`] block until the count reaches zero, after which all current and future calls
+/// to [`wait`] return immediately. The count cannot be reset.
+///
+/// # Latches vs wait groups
+///
+/// [`WaitGroup`] counts references to itself, so every participant needs its own clone. A
+/// `CountdownLatch` is better suited when the number of events is known up front, or when the
+/// events are reported by code that can only be handed a shared reference, such as callbacks.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::sync::CountdownLatch;
+/// use std::sync::Arc;
+/// use std::thread;
+///
+/// let latch = Arc::new(CountdownLatch::new(4));
+///
+/// for _ in 0..4 {
+///     let latch = latch.clone();
+///     thread::spawn(move || {
+///         // Do some work.
+///
+///         // Report that this worker is done.
+///         latch.count_down();
+///     });
+/// }
+///
+/// // Block until all workers have counted down.
+/// latch.wait();
+/// ```
+///
+/// [`count_down`]: CountdownLatch::count_down
+/// [`wait`]: CountdownLatch::wait
+/// [`WaitGroup`]: super::WaitGroup
+pub struct CountdownLatch {
+    cvar: Condvar,
+    count: Mutex<usize>,
+}
+
+impl CountdownLatch {
+    /// Creates a new latch that opens after `count` calls to [`count_down`].
+    ///
+    /// A latch created with a count of zero is open from the start.
+    ///
+    /// [`count_down`]: CountdownLatch::count_down
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::CountdownLatch;
+    ///
+    /// let latch = CountdownLatch::new(3);
+    /// ```
+    pub fn new(count: usize) -> CountdownLatch {
+        CountdownLatch {
+            cvar: Condvar::new(),
+            count: Mutex::new(count),
+        }
+    }
+
+    /// Decrements the count, waking up all waiting threads if it reaches zero.
+    ///
+    /// If the count is already zero, this method does nothing.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::CountdownLatch;
+    ///
+    /// let latch = CountdownLatch::new(1);
+    /// latch.count_down();
+    /// assert_eq!(latch.count(), 0);
+    ///
+    /// latch.count_down();
+    /// assert_eq!(latch.count(), 0);
+    /// ```
+    pub fn count_down(&self) {
+        let mut count = self.count.lock().unwrap();
+        if *count > 0 {
+            *count -= 1;
+            if *count == 0 {
+                self.cvar.notify_all();
+            }
+        }
+    }
+
+    /// Returns the current count.
+    ///
+    /// If other threads can count down concurrently, the result may be out of date by the time it
+    /// is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::CountdownLatch;
+    ///
+    /// let latch = CountdownLatch::new(2);
+    /// assert_eq!(latch.count(), 2);
+    ///
+    /// latch.count_down();
+    /// assert_eq!(latch.count(), 1);
+    /// ```
+    pub fn count(&self) -> usize {
+        *self.count.lock().unwrap()
+    }
+
+    /// Blocks the current thread until the count reaches zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::CountdownLatch;
+    /// use crossbeam_utils::thread;
+    ///
+    /// let latch = CountdownLatch::new(1);
+    ///
+    /// thread::scope(|s| {
+    ///     s.spawn(|_| latch.count_down());
+    ///     latch.wait();
+    /// }).unwrap();
+    /// ```
+    pub fn wait(&self) {
+        let mut count = self.count.lock().unwrap();
+        while *count > 0 {
+            count = self.cvar.wait(count).unwrap();
+        }
+    }
+
+    /// Blocks the current thread until the count reaches zero, but only for a limited time.
+    ///
+    /// Returns `true` if the count reached zero, or `false` if the timeout expired first.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::CountdownLatch;
+    /// use std::time::Duration;
+    ///
+    /// let latch = CountdownLatch::new(1);
+    /// assert!(!latch.wait_timeout(Duration::from_millis(10)));
+    ///
+    /// latch.count_down();
+    /// assert!(latch.wait_timeout(Duration::from_millis(10)));
+    /// ```
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+
+        let mut count = self.count.lock().unwrap();
+        while *count > 0 {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            count = self.cvar.wait_timeout(count, deadline - now).unwrap().0;
+        }
+        true
+    }
+}
+
+impl fmt::Debug for CountdownLatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let count = *self.count.lock().unwrap();
+        f.debug_struct("CountdownLatch")
+            .field("count", &count)
+            .finish()
+    }
+}
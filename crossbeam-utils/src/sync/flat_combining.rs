@@ -0,0 +1,314 @@
+use std::cell::UnsafeCell;
+use std::fmt;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+
+use crate::thread::current_index;
+use crate::{Backoff, CachePadded};
+
+/// The number of publication slots used by [`FlatCombining::new`].
+const DEFAULT_SLOTS: usize = 32;
+
+/// The slot holds no operation and can be claimed.
+const EMPTY: usize = 0;
+
+/// A thread has claimed the slot and is writing its operation into it.
+const CLAIMED: usize = 1;
+
+/// The slot holds an operation waiting for a combiner.
+const PENDING: usize = 2;
+
+/// A combiner has applied the operation, and the thread that published it can take the result.
+const DONE: usize = 3;
+
+/// An operation published by a waiting thread.
+///
+/// The closure lives on the stack of that thread, which doesn't return before a combiner has
+/// called it.
+type Op<T> = *mut (dyn FnMut(&mut T) + Send);
+
+/// A slot of the publication list.
+struct Slot<T> {
+    /// One of `EMPTY`, `CLAIMED`, `PENDING`, and `DONE`.
+    state: AtomicUsize,
+
+    /// The published operation, which is only valid while the slot is `PENDING`.
+    op: UnsafeCell<Option<Op<T>>>,
+}
+
+/// A wrapper that applies operations from many threads to a sequential data structure in
+/// batches.
+///
+/// Threads don't lock the data themselves. Instead, each one publishes its operation in a slot of
+/// a publication list and then tries to become the combiner. The thread that succeeds applies all
+/// published operations in one go, while the others wait for their results. Since the data stays
+/// in the cache of a single thread and the lock changes hands far less often, this gives better
+/// throughput than a [`Mutex`] under contention, for structures that are hard to make lock-free,
+/// such as priority queues.
+///
+/// If all slots of the publication list are taken, a thread applies its operation directly once
+/// it gets hold of the lock.
+///
+/// Operations run on whichever thread is the combiner, so they must be [`Send`], and so must
+/// their results. A panic in an operation is propagated to the thread that published it. Unlike
+/// [`Mutex`], the wrapper is not poisoned when that happens.
+///
+/// [`Mutex`]: std::sync::Mutex
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::sync::FlatCombining;
+/// use crossbeam_utils::thread;
+/// use std::collections::BinaryHeap;
+///
+/// let heap = FlatCombining::new(BinaryHeap::new());
+///
+/// thread::scope(|s| {
+///     for i in 0..4 {
+///         let heap = &heap;
+///         s.spawn(move |_| heap.apply(|h| h.push(i)));
+///     }
+/// }).unwrap();
+///
+/// assert_eq!(heap.apply(|h| h.pop()), Some(3));
+/// assert_eq!(heap.into_inner().len(), 3);
+/// ```
+pub struct FlatCombining<T> {
+    /// Set while a thread is the combiner.
+    locked: AtomicBool,
+
+    /// The wrapped data structure.
+    value: UnsafeCell<T>,
+
+    /// The publication list.
+    slots: Box<[CachePadded<Slot<T>>]>,
+}
+
+unsafe impl<T: Send> Send for FlatCombining<T> {}
+unsafe impl<T: Send> Sync for FlatCombining<T> {}
+
+impl<T> FlatCombining<T> {
+    /// Wraps `value` with a publication list of 32 slots.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::FlatCombining;
+    ///
+    /// let fc = FlatCombining::new(Vec::<i32>::new());
+    /// ```
+    pub fn new(value: T) -> FlatCombining<T> {
+        FlatCombining::with_slots(value, DEFAULT_SLOTS)
+    }
+
+    /// Wraps `value` with a publication list of `slots` slots.
+    ///
+    /// There should be about as many slots as threads that use the wrapper at the same time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `slots` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::FlatCombining;
+    ///
+    /// let fc = FlatCombining::with_slots(Vec::<i32>::new(), 8);
+    /// ```
+    pub fn with_slots(value: T, slots: usize) -> FlatCombining<T> {
+        assert!(slots > 0, "number of slots must be positive");
+
+        FlatCombining {
+            locked: AtomicBool::new(false),
+            value: UnsafeCell::new(value),
+            slots: (0..slots)
+                .map(|_| {
+                    CachePadded::new(Slot {
+                        state: AtomicUsize::new(EMPTY),
+                        op: UnsafeCell::new(None),
+                    })
+                })
+                .collect(),
+        }
+    }
+
+    /// Applies `op` to the wrapped value and returns its result.
+    ///
+    /// The operation may be applied by another thread, in a batch with operations from other
+    /// threads. This function returns once it has been applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::FlatCombining;
+    ///
+    /// let fc = FlatCombining::new(Vec::new());
+    /// fc.apply(|v| v.push(1));
+    /// fc.apply(|v| v.push(2));
+    /// assert_eq!(fc.apply(|v| v.len()), 2);
+    /// ```
+    pub fn apply<F, R>(&self, op: F) -> R
+    where
+        F: FnOnce(&mut T) -> R + Send,
+        R: Send,
+    {
+        let mut op = Some(op);
+        let mut result = None;
+        let mut run = |value: &mut T| {
+            if let Some(op) = op.take() {
+                // Catch panics, so that they don't unwind through the combiner.
+                result = Some(panic::catch_unwind(AssertUnwindSafe(|| op(value))));
+            }
+        };
+
+        {
+            let run: &mut (dyn FnMut(&mut T) + Send + '_) = &mut run;
+            // Erase the lifetime of `run`. This is sound because `execute` doesn't return before
+            // the closure has been called, and it is never called afterwards.
+            let run: Op<T> = unsafe { mem::transmute(run) };
+            self.execute(run);
+        }
+
+        match result {
+            Some(Ok(res)) => res,
+            Some(Err(payload)) => panic::resume_unwind(payload),
+            None => unreachable!(),
+        }
+    }
+
+    /// Returns a mutable reference to the wrapped value.
+    ///
+    /// Since this call borrows the wrapper mutably, no actual locking needs to take place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::FlatCombining;
+    ///
+    /// let mut fc = FlatCombining::new(0);
+    /// *fc.get_mut() = 10;
+    /// assert_eq!(fc.apply(|x| *x), 10);
+    /// ```
+    pub fn get_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.value.get() }
+    }
+
+    /// Consumes the wrapper, returning the wrapped value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::FlatCombining;
+    ///
+    /// let fc = FlatCombining::new(7);
+    /// assert_eq!(fc.into_inner(), 7);
+    /// ```
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Publishes `op` and waits until it has been applied, combining operations whenever the lock
+    /// is free.
+    fn execute(&self, op: Op<T>) {
+        let backoff = Backoff::new();
+
+        let slot = loop {
+            if let Some(slot) = self.claim() {
+                break slot;
+            }
+
+            // All slots are taken, so apply the operation directly once the lock is free.
+            if self.try_lock() {
+                unsafe { (*op)(&mut *self.value.get()) };
+                self.combine();
+                self.unlock();
+                return;
+            }
+            backoff.snooze();
+        };
+
+        unsafe { *slot.op.get() = Some(op) };
+        slot.state.store(PENDING, Ordering::Release);
+
+        backoff.reset();
+        loop {
+            if slot.state.load(Ordering::Acquire) == DONE {
+                unsafe { *slot.op.get() = None };
+                slot.state.store(EMPTY, Ordering::Release);
+                return;
+            }
+
+            if self.try_lock() {
+                // Our own operation is pending, so it is applied in this pass.
+                self.combine();
+                self.unlock();
+            } else {
+                backoff.snooze();
+            }
+        }
+    }
+
+    /// Claims an empty slot, starting the search at a slot derived from the current thread.
+    fn claim(&self) -> Option<&Slot<T>> {
+        let start = current_index().unwrap_or(0);
+        (0..self.slots.len())
+            .map(|i| &*self.slots[(start + i) % self.slots.len()])
+            .find(|slot| {
+                slot.state.load(Ordering::Relaxed) == EMPTY
+                    && slot
+                        .state
+                        .compare_exchange(EMPTY, CLAIMED, Ordering::Acquire, Ordering::Relaxed)
+                        .is_ok()
+            })
+    }
+
+    /// Applies all pending operations. The lock must be held.
+    fn combine(&self) {
+        let value = unsafe { &mut *self.value.get() };
+        for slot in self.slots.iter() {
+            if slot.state.load(Ordering::Acquire) == PENDING {
+                unsafe {
+                    if let Some(op) = *slot.op.get() {
+                        (*op)(value);
+                    }
+                }
+                slot.state.store(DONE, Ordering::Release);
+            }
+        }
+    }
+
+    /// Attempts to become the combiner.
+    fn try_lock(&self) -> bool {
+        !self.locked.load(Ordering::Relaxed)
+            && self
+                .locked
+                .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+    }
+
+    /// Stops being the combiner.
+    fn unlock(&self) {
+        self.locked.store(false, Ordering::Release);
+    }
+}
+
+impl<T: Default> Default for FlatCombining<T> {
+    fn default() -> FlatCombining<T> {
+        FlatCombining::new(Default::default())
+    }
+}
+
+impl<T> From<T> for FlatCombining<T> {
+    fn from(t: T) -> Self {
+        FlatCombining::new(t)
+    }
+}
+
+impl<T> fmt::Debug for FlatCombining<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("FlatCombining { .. }")
+    }
+}
@@ -1,15 +1,50 @@
 //! Thread synchronization primitives.
 //!
+//! * [`Barrier`], a reusable barrier for threads working in lockstep phases.
+//! * [`CountdownLatch`], for waiting until a known number of events have happened.
+//! * [`FlatCombining`], for applying operations to a sequential data structure in batches.
+//! * [`OnceCell`] and [`Lazy`], for values that are initialized exactly once.
 //! * [`Parker`], a thread parking primitive.
+//! * [`Semaphore`], a counting semaphore for limiting concurrency.
+//! * [`SeqLock`], a sequence lock for small data that is read often and written rarely.
+//! * [`ShardedCounter`], a concurrent counter with fast concurrent updates.
 //! * [`ShardedLock`], a sharded reader-writer lock with fast concurrent reads.
+//! * [`SpinLock`], a mutual exclusion lock that spins while waiting.
+//! * [`StripedLock`], for locking keys through a fixed number of cache-padded locks.
 //! * [`WaitGroup`], for synchronizing the beginning or end of some computation.
 
+mod barrier;
+mod countdown_latch;
+#[cfg(not(crossbeam_loom))]
+mod flat_combining;
+mod once_cell;
 mod parker;
+mod semaphore;
+mod seq_lock;
+#[cfg(not(crossbeam_loom))]
+mod sharded_counter;
 #[cfg(not(crossbeam_loom))]
 mod sharded_lock;
+mod spin_lock;
+#[cfg(not(crossbeam_loom))]
+mod striped_lock;
 mod wait_group;
 
+pub use self::barrier::{Barrier, BarrierWaitResult};
+pub use self::countdown_latch::CountdownLatch;
+#[cfg(not(crossbeam_loom))]
+pub use self::flat_combining::FlatCombining;
+pub use self::once_cell::{Lazy, OnceCell};
 pub use self::parker::{Parker, Unparker};
+pub use self::semaphore::{Semaphore, SemaphorePermit};
+pub use self::seq_lock::{SeqLock, SeqLockWriteGuard};
+#[cfg(not(crossbeam_loom))]
+pub use self::sharded_counter::ShardedCounter;
+#[cfg(not(crossbeam_loom))]
+pub use self::sharded_lock::{
+    ShardedLock, ShardedLockReadGuard, ShardedLockUpgradableReadGuard, ShardedLockWriteGuard,
+};
+pub use self::spin_lock::{SpinLock, SpinLockGuard};
 #[cfg(not(crossbeam_loom))]
-pub use self::sharded_lock::{ShardedLock, ShardedLockReadGuard, ShardedLockWriteGuard};
+pub use self::striped_lock::{StripedLock, StripedLockGuard};
 pub use self::wait_group::WaitGroup;
@@ -0,0 +1,514 @@
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::cell::{Cell, UnsafeCell};
+use std::fmt;
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::ops::Deref;
+use std::panic::{RefUnwindSafe, UnwindSafe};
+use std::ptr;
+
+use super::{Parker, Unparker};
+
+/// No thread has started initializing the cell yet.
+const INCOMPLETE: usize = 0;
+
+/// A thread is initializing the cell. The remaining bits point to the list of waiting threads.
+const RUNNING: usize = 1;
+
+/// The cell has been initialized.
+const COMPLETE: usize = 2;
+
+/// Masks the bits of the state that are not part of the waiter pointer.
+const STATE_MASK: usize = 3;
+
+/// A thread waiting for the cell to be initialized.
+///
+/// Waiters live on the stacks of their threads and form a linked list whose head is stored in the
+/// state of the cell. The alignment leaves room for the state bits in the pointer.
+#[repr(align(4))]
+struct Waiter {
+    unparker: Unparker,
+    signaled: AtomicBool,
+    next: *const Waiter,
+}
+
+/// A cell that can be written to only once, and is safe to share between threads.
+///
+/// The first thread that calls [`get_or_init`] runs the initialization function. Other threads
+/// calling it in the meantime are parked until the value is ready, and then all of them get a
+/// reference to the same value.
+///
+/// If the initialization function panics, the cell stays uninitialized, and one of the waiting
+/// threads takes over by running its own initialization function.
+///
+/// [`get_or_init`]: OnceCell::get_or_init
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::sync::OnceCell;
+/// use crossbeam_utils::thread;
+///
+/// let cell = OnceCell::new();
+///
+/// thread::scope(|s| {
+///     for i in 0..4 {
+///         let cell = &cell;
+///         s.spawn(move |_| {
+///             let value: &usize = cell.get_or_init(|| i);
+///             assert!(*value < 4);
+///         });
+///     }
+/// }).unwrap();
+///
+/// assert!(cell.get().is_some());
+/// ```
+pub struct OnceCell<T> {
+    /// One of `INCOMPLETE`, `RUNNING`, or `COMPLETE`, possibly combined with a waiter pointer.
+    state: AtomicUsize,
+
+    /// The value, which is initialized once the state is `COMPLETE`.
+    value: UnsafeCell<MaybeUninit<T>>,
+
+    /// Indicates that the cell owns a `T`.
+    _marker: PhantomData<T>,
+}
+
+unsafe impl<T: Send> Send for OnceCell<T> {}
+unsafe impl<T: Send + Sync> Sync for OnceCell<T> {}
+
+impl<T: UnwindSafe> UnwindSafe for OnceCell<T> {}
+impl<T: RefUnwindSafe + UnwindSafe> RefUnwindSafe for OnceCell<T> {}
+
+impl<T> OnceCell<T> {
+    /// Creates a new uninitialized cell.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::OnceCell;
+    ///
+    /// static CELL: OnceCell<String> = OnceCell::new();
+    /// ```
+    pub const fn new() -> OnceCell<T> {
+        OnceCell {
+            state: AtomicUsize::new(INCOMPLETE),
+            value: UnsafeCell::new(MaybeUninit::uninit()),
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns a reference to the value, or `None` if the cell is not initialized yet.
+    ///
+    /// This method never blocks.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::OnceCell;
+    ///
+    /// let cell = OnceCell::new();
+    /// assert_eq!(cell.get(), None);
+    ///
+    /// cell.get_or_init(|| 5);
+    /// assert_eq!(cell.get(), Some(&5));
+    /// ```
+    pub fn get(&self) -> Option<&T> {
+        if self.is_initialized() {
+            Some(unsafe { self.get_unchecked() })
+        } else {
+            None
+        }
+    }
+
+    /// Returns a mutable reference to the value, or `None` if the cell is not initialized yet.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::OnceCell;
+    ///
+    /// let mut cell = OnceCell::new();
+    /// cell.get_or_init(|| 5);
+    ///
+    /// *cell.get_mut().unwrap() += 1;
+    /// assert_eq!(cell.get(), Some(&6));
+    /// ```
+    pub fn get_mut(&mut self) -> Option<&mut T> {
+        if *self.state.get_mut() == COMPLETE {
+            Some(unsafe { &mut *(*self.value.get()).as_mut_ptr() })
+        } else {
+            None
+        }
+    }
+
+    /// Initializes the cell with `value`.
+    ///
+    /// If the cell is already initialized, or another thread initializes it first, `value` is
+    /// returned back in the error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::OnceCell;
+    ///
+    /// let cell = OnceCell::new();
+    /// assert_eq!(cell.set(1), Ok(()));
+    /// assert_eq!(cell.set(2), Err(2));
+    /// assert_eq!(cell.get(), Some(&1));
+    /// ```
+    pub fn set(&self, value: T) -> Result<(), T> {
+        let mut value = Some(value);
+        self.get_or_init(|| value.take().unwrap());
+        match value {
+            None => Ok(()),
+            Some(value) => Err(value),
+        }
+    }
+
+    /// Returns the value, initializing it with `f` if the cell is not initialized yet.
+    ///
+    /// If another thread is initializing the cell, the current thread is parked until it
+    /// finishes.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panics, the panic is propagated and the cell stays uninitialized.
+    ///
+    /// Calling this method on the same cell from within `f` results in a deadlock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::OnceCell;
+    ///
+    /// let cell = OnceCell::new();
+    /// assert_eq!(cell.get_or_init(|| 1), &1);
+    /// assert_eq!(cell.get_or_init(|| 2), &1);
+    /// ```
+    pub fn get_or_init<F>(&self, f: F) -> &T
+    where
+        F: FnOnce() -> T,
+    {
+        enum Void {}
+
+        match self.get_or_try_init(|| Ok::<T, Void>(f())) {
+            Ok(value) => value,
+            Err(void) => match void {},
+        }
+    }
+
+    /// Returns the value, initializing it with `f` if the cell is not initialized yet.
+    ///
+    /// If `f` returns an error, the error is returned and the cell stays uninitialized.
+    ///
+    /// # Panics
+    ///
+    /// If `f` panics, the panic is propagated and the cell stays uninitialized.
+    ///
+    /// Calling this method on the same cell from within `f` results in a deadlock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::OnceCell;
+    ///
+    /// let cell = OnceCell::new();
+    /// assert_eq!(cell.get_or_try_init(|| Err("oops")), Err("oops"));
+    /// assert_eq!(cell.get(), None);
+    ///
+    /// assert_eq!(cell.get_or_try_init(|| Ok::<_, ()>(1)), Ok(&1));
+    /// assert_eq!(cell.get(), Some(&1));
+    /// ```
+    pub fn get_or_try_init<F, E>(&self, f: F) -> Result<&T, E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        if let Some(value) = self.get() {
+            return Ok(value);
+        }
+        self.initialize(f)?;
+
+        debug_assert!(self.is_initialized());
+        Ok(unsafe { self.get_unchecked() })
+    }
+
+    /// Consumes the cell, returning the value if it was initialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::OnceCell;
+    ///
+    /// let cell = OnceCell::new();
+    /// cell.get_or_init(|| "hello".to_string());
+    /// assert_eq!(cell.into_inner(), Some("hello".to_string()));
+    /// ```
+    pub fn into_inner(mut self) -> Option<T> {
+        self.take()
+    }
+
+    /// Takes the value out of the cell, leaving it uninitialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::OnceCell;
+    ///
+    /// let mut cell = OnceCell::new();
+    /// cell.get_or_init(|| 1);
+    ///
+    /// assert_eq!(cell.take(), Some(1));
+    /// assert_eq!(cell.get(), None);
+    /// ```
+    pub fn take(&mut self) -> Option<T> {
+        if *self.state.get_mut() == COMPLETE {
+            *self.state.get_mut() = INCOMPLETE;
+            Some(unsafe { ptr::read((*self.value.get()).as_ptr()) })
+        } else {
+            None
+        }
+    }
+
+    /// Returns `true` if the cell has been initialized.
+    fn is_initialized(&self) -> bool {
+        self.state.load(Ordering::Acquire) == COMPLETE
+    }
+
+    /// Returns a reference to the value without checking that it is initialized.
+    unsafe fn get_unchecked(&self) -> &T {
+        &*(*self.value.get()).as_ptr()
+    }
+
+    /// Runs `f` to initialize the cell, or waits for another thread to do so.
+    #[cold]
+    fn initialize<F, E>(&self, f: F) -> Result<(), E>
+    where
+        F: FnOnce() -> Result<T, E>,
+    {
+        let mut f = Some(f);
+        let mut state = self.state.load(Ordering::Acquire);
+
+        loop {
+            match state {
+                COMPLETE => return Ok(()),
+                INCOMPLETE => {
+                    if let Err(s) = self.state.compare_exchange(
+                        INCOMPLETE,
+                        RUNNING,
+                        Ordering::Acquire,
+                        Ordering::Acquire,
+                    ) {
+                        state = s;
+                        continue;
+                    }
+
+                    // If `f` fails or panics, the guard resets the state and wakes up the waiting
+                    // threads so that one of them can try again.
+                    let mut guard = CompletionGuard {
+                        state: &self.state,
+                        new_state: INCOMPLETE,
+                    };
+
+                    let value = (f.take().unwrap())()?;
+                    unsafe {
+                        (*self.value.get()).as_mut_ptr().write(value);
+                    }
+                    guard.new_state = COMPLETE;
+                    return Ok(());
+                }
+                _ => {
+                    debug_assert_eq!(state & STATE_MASK, RUNNING);
+                    self.wait(state);
+                    state = self.state.load(Ordering::Acquire);
+                }
+            }
+        }
+    }
+
+    /// Parks the current thread until the initializing thread finishes.
+    fn wait(&self, mut state: usize) {
+        let parker = Parker::new();
+        let mut waiter = Waiter {
+            unparker: parker.unparker().clone(),
+            signaled: AtomicBool::new(false),
+            next: ptr::null(),
+        };
+
+        // Push the waiter onto the list.
+        loop {
+            if state & STATE_MASK != RUNNING {
+                return;
+            }
+
+            waiter.next = (state & !STATE_MASK) as *const Waiter;
+            let new = &waiter as *const Waiter as usize | RUNNING;
+            match self
+                .state
+                .compare_exchange(state, new, Ordering::Release, Ordering::Acquire)
+            {
+                Ok(_) => break,
+                Err(s) => state = s,
+            }
+        }
+
+        // The waiter must stay alive until the initializing thread has signaled it.
+        while !waiter.signaled.load(Ordering::Acquire) {
+            parker.park();
+        }
+    }
+}
+
+impl<T> Drop for OnceCell<T> {
+    fn drop(&mut self) {
+        if *self.state.get_mut() == COMPLETE {
+            unsafe {
+                ptr::drop_in_place((*self.value.get()).as_mut_ptr());
+            }
+        }
+    }
+}
+
+impl<T> Default for OnceCell<T> {
+    fn default() -> OnceCell<T> {
+        OnceCell::new()
+    }
+}
+
+impl<T> From<T> for OnceCell<T> {
+    fn from(value: T) -> Self {
+        let cell = OnceCell::new();
+        let _ = cell.set(value);
+        cell
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for OnceCell<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("OnceCell")
+            .field("value", &self.get())
+            .finish()
+    }
+}
+
+/// Sets the final state of an initialization and wakes up the waiting threads when dropped.
+struct CompletionGuard<'a> {
+    state: &'a AtomicUsize,
+    new_state: usize,
+}
+
+impl Drop for CompletionGuard<'_> {
+    fn drop(&mut self) {
+        let state = self.state.swap(self.new_state, Ordering::AcqRel);
+        debug_assert_eq!(state & STATE_MASK, RUNNING);
+
+        let mut waiter = (state & !STATE_MASK) as *const Waiter;
+        while !waiter.is_null() {
+            unsafe {
+                // The waiter may be deallocated as soon as it is signaled, so read everything
+                // needed from it first.
+                let next = (*waiter).next;
+                let unparker = (*waiter).unparker.clone();
+                (*waiter).signaled.store(true, Ordering::Release);
+                unparker.unpark();
+                waiter = next;
+            }
+        }
+    }
+}
+
+/// A value that is initialized on first access.
+///
+/// The initialization function runs at most once, even if the value is first accessed from
+/// several threads at the same time. It is backed by a [`OnceCell`], so threads that arrive while
+/// the value is being initialized are parked until it is ready.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::sync::Lazy;
+/// use std::collections::HashMap;
+///
+/// static PRIMES: Lazy<HashMap<u32, &str>> = Lazy::new(|| {
+///     let mut m = HashMap::new();
+///     m.insert(2, "two");
+///     m.insert(3, "three");
+///     m
+/// });
+///
+/// assert_eq!(PRIMES.get(&3), Some(&"three"));
+/// ```
+pub struct Lazy<T, F = fn() -> T> {
+    cell: OnceCell<T>,
+    init: Cell<Option<F>>,
+}
+
+unsafe impl<T, F: Send> Sync for Lazy<T, F> where OnceCell<T>: Sync {}
+
+impl<T: UnwindSafe, F: UnwindSafe> RefUnwindSafe for Lazy<T, F> where OnceCell<T>: RefUnwindSafe {}
+
+impl<T, F> Lazy<T, F> {
+    /// Creates a new lazy value that will be initialized with `f`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::Lazy;
+    ///
+    /// let lazy = Lazy::new(|| 1 + 2);
+    /// assert_eq!(*lazy, 3);
+    /// ```
+    pub const fn new(f: F) -> Lazy<T, F> {
+        Lazy {
+            cell: OnceCell::new(),
+            init: Cell::new(Some(f)),
+        }
+    }
+}
+
+impl<T, F: FnOnce() -> T> Lazy<T, F> {
+    /// Forces the evaluation of the lazy value and returns a reference to it.
+    ///
+    /// This is equivalent to dereferencing the value.
+    ///
+    /// # Panics
+    ///
+    /// If the initialization function panicked during a previous access, this method panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::Lazy;
+    ///
+    /// let lazy = Lazy::new(|| 92);
+    /// assert_eq!(Lazy::force(&lazy), &92);
+    /// ```
+    pub fn force(this: &Lazy<T, F>) -> &T {
+        this.cell.get_or_init(|| match this.init.take() {
+            Some(f) => f(),
+            None => panic!("`Lazy` instance has previously been poisoned"),
+        })
+    }
+}
+
+impl<T, F: FnOnce() -> T> Deref for Lazy<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        Lazy::force(self)
+    }
+}
+
+impl<T: Default> Default for Lazy<T> {
+    fn default() -> Lazy<T> {
+        Lazy::new(T::default)
+    }
+}
+
+impl<T: fmt::Debug, F> fmt::Debug for Lazy<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Lazy")
+            .field("value", &self.cell.get())
+            .finish()
+    }
+}
@@ -22,6 +22,11 @@ use std::time::{Duration, Instant};
 /// In other words, each `Parker` acts a bit like a spinlock that can be locked and unlocked using
 /// [`park`] and [`unpark`].
 ///
+/// A `Parker` created with [`new_counting`] keeps a count of tokens instead: every call to
+/// [`unpark`] adds a token, and every call to [`park`] consumes one. This is useful when a single
+/// parked thread serves several event sources and must not lose wakeups that arrive while it is
+/// busy.
+///
 /// # Examples
 ///
 /// ```
@@ -50,6 +55,7 @@ use std::time::{Duration, Instant};
 /// [`park_timeout`]: Parker::park_timeout
 /// [`park_deadline`]: Parker::park_deadline
 /// [`unpark`]: Unparker::unpark
+/// [`new_counting`]: Parker::new_counting
 pub struct Parker {
     unparker: Unparker,
     _marker: PhantomData<*const ()>,
@@ -59,16 +65,7 @@ unsafe impl Send for Parker {}
 
 impl Default for Parker {
     fn default() -> Self {
-        Self {
-            unparker: Unparker {
-                inner: Arc::new(Inner {
-                    state: AtomicUsize::new(EMPTY),
-                    lock: Mutex::new(()),
-                    cvar: Condvar::new(),
-                }),
-            },
-            _marker: PhantomData,
-        }
+        Self::with_counting(false)
     }
 }
 
@@ -87,6 +84,51 @@ impl Parker {
         Self::default()
     }
 
+    /// Creates a new `Parker` that counts tokens.
+    ///
+    /// Every call to [`unpark`] makes one more token available, and every call to [`park`]
+    /// consumes one, so no wakeup is lost even if the thread parks long after being unparked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::Parker;
+    ///
+    /// let p = Parker::new_counting();
+    /// let u = p.unparker().clone();
+    ///
+    /// // Make three tokens available.
+    /// u.unpark();
+    /// u.unpark();
+    /// u.unpark();
+    ///
+    /// // Each of these wakes up immediately and consumes one token.
+    /// p.park();
+    /// p.park();
+    /// p.park();
+    /// ```
+    ///
+    /// [`park`]: Parker::park
+    /// [`unpark`]: Unparker::unpark
+    pub fn new_counting() -> Parker {
+        Self::with_counting(true)
+    }
+
+    fn with_counting(counting: bool) -> Parker {
+        Parker {
+            unparker: Unparker {
+                inner: Arc::new(Inner {
+                    state: AtomicUsize::new(EMPTY),
+                    counting,
+                    tokens: AtomicUsize::new(0),
+                    lock: Mutex::new(()),
+                    cvar: Condvar::new(),
+                }),
+            },
+            _marker: PhantomData,
+        }
+    }
+
     /// Blocks the current thread until the token is made available.
     ///
     /// # Examples
@@ -220,6 +262,8 @@ unsafe impl Sync for Unparker {}
 impl Unparker {
     /// Atomically makes the token available if it is not already.
     ///
+    /// If the [`Parker`] counts tokens, this always makes one more token available.
+    ///
     /// This method will wake up the thread blocked on [`park`] or [`park_timeout`], if there is
     /// any.
     ///
@@ -307,12 +351,19 @@ const NOTIFIED: usize = 2;
 
 struct Inner {
     state: AtomicUsize,
+    /// Whether tokens are counted, in which case they are kept in `tokens` rather than `state`.
+    counting: bool,
+    tokens: AtomicUsize,
     lock: Mutex<()>,
     cvar: Condvar,
 }
 
 impl Inner {
     fn park(&self, deadline: Option<Instant>) {
+        if self.counting {
+            return self.park_counting(deadline);
+        }
+
         // If we were previously notified then we consume this notification and return quickly.
         if self
             .state
@@ -383,7 +434,74 @@ impl Inner {
         }
     }
 
+    /// Consumes a token if one is available.
+    fn try_take_token(&self) -> bool {
+        let mut tokens = self.tokens.load(SeqCst);
+        while tokens > 0 {
+            match self
+                .tokens
+                .compare_exchange(tokens, tokens - 1, SeqCst, SeqCst)
+            {
+                Ok(_) => return true,
+                Err(t) => tokens = t,
+            }
+        }
+        false
+    }
+
+    fn park_counting(&self, deadline: Option<Instant>) {
+        if self.try_take_token() {
+            return;
+        }
+
+        if let Some(deadline) = deadline {
+            if deadline <= Instant::now() {
+                return;
+            }
+        }
+
+        let mut m = self.lock.lock().unwrap();
+
+        loop {
+            // Announce that we are going to sleep, then check for tokens again. Because both
+            // `state` and `tokens` are accessed with `SeqCst`, either we see the token added by a
+            // concurrent `unpark`, or it sees `PARKED` and notifies us once we wait on `cvar`.
+            self.state.store(PARKED, SeqCst);
+            if self.try_take_token() {
+                self.state.store(EMPTY, SeqCst);
+                return;
+            }
+
+            m = match deadline {
+                None => self.cvar.wait(m).unwrap(),
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if now >= deadline {
+                        self.state.store(EMPTY, SeqCst);
+                        return;
+                    }
+                    self.cvar.wait_timeout(m, deadline - now).unwrap().0
+                }
+            };
+        }
+    }
+
+    fn unpark_counting(&self) {
+        self.tokens.fetch_add(1, SeqCst);
+
+        if self.state.load(SeqCst) == PARKED {
+            // The parked thread holds `lock` until it waits on `cvar`, so acquiring it ensures the
+            // notification isn't lost.
+            drop(self.lock.lock().unwrap());
+            self.cvar.notify_one();
+        }
+    }
+
     pub(crate) fn unpark(&self) {
+        if self.counting {
+            return self.unpark_counting();
+        }
+
         // To ensure the unparked thread will observe any writes we made before this call, we must
         // perform a release operation that `park` can synchronize with. To do that we must write
         // `NOTIFIED` even if `state` is already `NOTIFIED`. That is why this must be a swap rather
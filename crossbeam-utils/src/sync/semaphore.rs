@@ -0,0 +1,291 @@
+use crate::primitive::sync::atomic::AtomicBool;
+use crate::primitive::sync::{Arc, Mutex};
+use core::sync::atomic::Ordering;
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem;
+use std::time::{Duration, Instant};
+
+use super::{Parker, Unparker};
+
+/// A counting semaphore.
+///
+/// A semaphore holds a number of permits. [`acquire`] takes a permit, blocking until one is
+/// available, and returns a [`SemaphorePermit`] that gives the permit back when dropped. This is
+/// useful for limiting how many threads may use a resource at the same time.
+///
+/// The semaphore is fair: blocked threads are granted permits in the order they started waiting,
+/// and a permit is never taken by [`try_acquire`] while another thread is waiting for it.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::sync::Semaphore;
+/// use crossbeam_utils::thread;
+///
+/// // Allow at most two workers to run at the same time.
+/// let sem = Semaphore::new(2);
+///
+/// thread::scope(|s| {
+///     for _ in 0..8 {
+///         s.spawn(|_| {
+///             let _permit = sem.acquire();
+///             // Do some work.
+///         });
+///     }
+/// }).unwrap();
+///
+/// assert_eq!(sem.available_permits(), 2);
+/// ```
+///
+/// [`acquire`]: Semaphore::acquire
+/// [`try_acquire`]: Semaphore::try_acquire
+pub struct Semaphore {
+    inner: Mutex<Inner>,
+}
+
+/// Inner state of a `Semaphore`.
+struct Inner {
+    /// The number of available permits.
+    permits: usize,
+
+    /// Threads waiting for a permit, in the order they arrived.
+    waiters: VecDeque<Arc<Waiter>>,
+}
+
+/// A thread blocked in `acquire`.
+struct Waiter {
+    unparker: Unparker,
+
+    /// Set when a released permit has been handed to this thread.
+    granted: AtomicBool,
+}
+
+impl Semaphore {
+    /// Creates a new semaphore with the given number of permits.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::Semaphore;
+    ///
+    /// let sem = Semaphore::new(4);
+    /// ```
+    pub fn new(permits: usize) -> Semaphore {
+        Semaphore {
+            inner: Mutex::new(Inner {
+                permits,
+                waiters: VecDeque::new(),
+            }),
+        }
+    }
+
+    /// Acquires a permit, blocking until one is available.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::Semaphore;
+    ///
+    /// let sem = Semaphore::new(1);
+    ///
+    /// let permit = sem.acquire();
+    /// assert_eq!(sem.available_permits(), 0);
+    ///
+    /// drop(permit);
+    /// assert_eq!(sem.available_permits(), 1);
+    /// ```
+    pub fn acquire(&self) -> SemaphorePermit<'_> {
+        let acquired = self.acquire_until(None);
+        debug_assert!(acquired);
+        SemaphorePermit { sem: self }
+    }
+
+    /// Attempts to acquire a permit without blocking.
+    ///
+    /// Returns `None` if no permit is available, or if other threads are already waiting for one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::Semaphore;
+    ///
+    /// let sem = Semaphore::new(1);
+    ///
+    /// let permit = sem.try_acquire().unwrap();
+    /// assert!(sem.try_acquire().is_none());
+    ///
+    /// drop(permit);
+    /// assert!(sem.try_acquire().is_some());
+    /// ```
+    pub fn try_acquire(&self) -> Option<SemaphorePermit<'_>> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.waiters.is_empty() && inner.permits > 0 {
+            inner.permits -= 1;
+            Some(SemaphorePermit { sem: self })
+        } else {
+            None
+        }
+    }
+
+    /// Acquires a permit, blocking for at most `timeout`.
+    ///
+    /// Returns `None` if no permit became available before the timeout expired.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::Semaphore;
+    /// use std::time::Duration;
+    ///
+    /// let sem = Semaphore::new(1);
+    ///
+    /// let permit = sem.acquire();
+    /// assert!(sem.acquire_timeout(Duration::from_millis(10)).is_none());
+    ///
+    /// drop(permit);
+    /// assert!(sem.acquire_timeout(Duration::from_millis(10)).is_some());
+    /// ```
+    pub fn acquire_timeout(&self, timeout: Duration) -> Option<SemaphorePermit<'_>> {
+        if self.acquire_until(Some(Instant::now() + timeout)) {
+            Some(SemaphorePermit { sem: self })
+        } else {
+            None
+        }
+    }
+
+    /// Adds `n` permits to the semaphore, waking up waiting threads as needed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::Semaphore;
+    ///
+    /// let sem = Semaphore::new(0);
+    /// sem.add_permits(2);
+    /// assert_eq!(sem.available_permits(), 2);
+    /// ```
+    pub fn add_permits(&self, n: usize) {
+        let mut inner = self.inner.lock().unwrap();
+        for _ in 0..n {
+            match inner.waiters.pop_front() {
+                Some(waiter) => {
+                    waiter.granted.store(true, Ordering::Release);
+                    waiter.unparker.unpark();
+                }
+                None => inner.permits += 1,
+            }
+        }
+    }
+
+    /// Returns the number of permits that are currently available.
+    ///
+    /// If other threads can acquire or release permits concurrently, the result may be out of
+    /// date by the time it is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::Semaphore;
+    ///
+    /// let sem = Semaphore::new(3);
+    /// let _permit = sem.acquire();
+    /// assert_eq!(sem.available_permits(), 2);
+    /// ```
+    pub fn available_permits(&self) -> usize {
+        self.inner.lock().unwrap().permits
+    }
+
+    /// Takes a permit, blocking until `deadline` if one is given.
+    ///
+    /// Returns `true` if a permit was taken.
+    fn acquire_until(&self, deadline: Option<Instant>) -> bool {
+        let (parker, waiter) = {
+            let mut inner = self.inner.lock().unwrap();
+            if inner.waiters.is_empty() && inner.permits > 0 {
+                inner.permits -= 1;
+                return true;
+            }
+
+            let parker = Parker::new();
+            let waiter = Arc::new(Waiter {
+                unparker: parker.unparker().clone(),
+                granted: AtomicBool::new(false),
+            });
+            inner.waiters.push_back(waiter.clone());
+            (parker, waiter)
+        };
+
+        loop {
+            if waiter.granted.load(Ordering::Acquire) {
+                return true;
+            }
+
+            match deadline {
+                None => parker.park(),
+                Some(deadline) => {
+                    if Instant::now() < deadline {
+                        parker.park_deadline(deadline);
+                        continue;
+                    }
+
+                    // Permits are granted while holding the lock, so after locking it the waiter
+                    // has either been granted one or is still in the queue.
+                    let mut inner = self.inner.lock().unwrap();
+                    if waiter.granted.load(Ordering::Acquire) {
+                        return true;
+                    }
+                    inner.waiters.retain(|w| !Arc::ptr_eq(w, &waiter));
+                    return false;
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Debug for Semaphore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let permits = self.available_permits();
+        f.debug_struct("Semaphore")
+            .field("permits", &permits)
+            .finish()
+    }
+}
+
+/// A permit acquired from a [`Semaphore`].
+///
+/// The permit is given back to the semaphore when dropped.
+pub struct SemaphorePermit<'a> {
+    sem: &'a Semaphore,
+}
+
+impl SemaphorePermit<'_> {
+    /// Consumes the permit without giving it back to the semaphore.
+    ///
+    /// This permanently reduces the number of permits in the semaphore by one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::Semaphore;
+    ///
+    /// let sem = Semaphore::new(2);
+    /// sem.acquire().forget();
+    /// assert_eq!(sem.available_permits(), 1);
+    /// ```
+    pub fn forget(self) {
+        mem::forget(self);
+    }
+}
+
+impl Drop for SemaphorePermit<'_> {
+    fn drop(&mut self) {
+        self.sem.add_permits(1);
+    }
+}
+
+impl fmt::Debug for SemaphorePermit<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("SemaphorePermit { .. }")
+    }
+}
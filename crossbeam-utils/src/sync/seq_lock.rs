@@ -0,0 +1,323 @@
+use core::cell::UnsafeCell;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::{Deref, DerefMut};
+use core::ptr;
+use core::sync::atomic::{self, AtomicUsize, Ordering};
+
+use crate::Backoff;
+
+/// A sequence lock, optimized for data that is read often and written rarely.
+///
+/// Readers never write to shared memory. Instead, they copy the data optimistically and retry if
+/// a writer modified it in the meantime. This makes reads very cheap and keeps them from slowing
+/// down each other or the writer, at the cost of requiring `T: Copy`, since a reader may observe
+/// a torn value before discarding it.
+///
+/// Writers are mutually exclusive and spin while waiting, so a `SeqLock` should only be used if
+/// writes are short.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::sync::SeqLock;
+/// use crossbeam_utils::thread;
+///
+/// let lock = SeqLock::new((0, 0));
+///
+/// thread::scope(|s| {
+///     s.spawn(|_| {
+///         for i in 1..=100 {
+///             lock.write((i, i));
+///         }
+///     });
+///
+///     // Readers always see both halves of the pair updated together.
+///     let (a, b) = lock.read();
+///     assert_eq!(a, b);
+/// }).unwrap();
+///
+/// assert_eq!(lock.read(), (100, 100));
+/// ```
+pub struct SeqLock<T> {
+    /// The current stamp.
+    ///
+    /// The stamp is odd while a writer holds the lock, and it is incremented twice by every
+    /// write, so readers can detect a concurrent write by comparing it before and after a read.
+    state: AtomicUsize,
+
+    /// The protected data.
+    value: UnsafeCell<T>,
+}
+
+unsafe impl<T: Send> Send for SeqLock<T> {}
+unsafe impl<T: Send> Sync for SeqLock<T> {}
+
+impl<T> SeqLock<T> {
+    /// Creates a new sequence lock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::SeqLock;
+    ///
+    /// let lock = SeqLock::new(5);
+    /// ```
+    pub const fn new(value: T) -> SeqLock<T> {
+        SeqLock {
+            state: AtomicUsize::new(0),
+            value: UnsafeCell::new(value),
+        }
+    }
+
+    /// Consumes the lock, returning the underlying data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::SeqLock;
+    ///
+    /// let lock = SeqLock::new(7);
+    /// assert_eq!(lock.into_inner(), 7);
+    /// ```
+    pub fn into_inner(self) -> T {
+        self.value.into_inner()
+    }
+
+    /// Returns a mutable reference to the underlying data.
+    ///
+    /// Since this call borrows the lock mutably, no actual locking needs to take place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::SeqLock;
+    ///
+    /// let mut lock = SeqLock::new(0);
+    /// *lock.get_mut() = 10;
+    /// assert_eq!(lock.read(), 10);
+    /// ```
+    pub fn get_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.value.get() }
+    }
+
+    /// Acquires the write lock, spinning until no other writer holds it.
+    ///
+    /// Readers that overlap with the returned guard retry until it is dropped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::SeqLock;
+    ///
+    /// let lock = SeqLock::new([0; 4]);
+    ///
+    /// let mut guard = lock.lock();
+    /// guard[1] = 1;
+    /// guard[2] = 2;
+    /// drop(guard);
+    ///
+    /// assert_eq!(lock.read(), [0, 1, 2, 0]);
+    /// ```
+    pub fn lock(&self) -> SeqLockWriteGuard<'_, T> {
+        let backoff = Backoff::new();
+        loop {
+            if let Some(guard) = self.try_lock() {
+                return guard;
+            }
+
+            while self.state.load(Ordering::Relaxed) & 1 == 1 {
+                backoff.snooze();
+            }
+        }
+    }
+
+    /// Attempts to acquire the write lock without spinning.
+    ///
+    /// Returns `None` if another writer holds the lock.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::SeqLock;
+    ///
+    /// let lock = SeqLock::new(1);
+    ///
+    /// let guard = lock.try_lock().unwrap();
+    /// assert!(lock.try_lock().is_none());
+    ///
+    /// drop(guard);
+    /// assert!(lock.try_lock().is_some());
+    /// ```
+    pub fn try_lock(&self) -> Option<SeqLockWriteGuard<'_, T>> {
+        let state = self.state.load(Ordering::Relaxed);
+        if state & 1 == 0
+            && self
+                .state
+                .compare_exchange(state, state + 1, Ordering::Acquire, Ordering::Relaxed)
+                .is_ok()
+        {
+            // Readers that load the new stamp must not see any of the writes made through the
+            // guard before it.
+            atomic::fence(Ordering::Release);
+
+            Some(SeqLockWriteGuard {
+                lock: self,
+                state,
+                _marker: PhantomData,
+            })
+        } else {
+            None
+        }
+    }
+}
+
+impl<T: Copy> SeqLock<T> {
+    /// Reads the current value.
+    ///
+    /// If a writer modifies the value while it is being read, the read is retried.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::SeqLock;
+    ///
+    /// let lock = SeqLock::new(3);
+    /// assert_eq!(lock.read(), 3);
+    /// ```
+    pub fn read(&self) -> T {
+        let backoff = Backoff::new();
+        loop {
+            if let Some(value) = self.try_read() {
+                return value;
+            }
+            backoff.snooze();
+        }
+    }
+
+    /// Attempts to read the current value without retrying.
+    ///
+    /// Returns `None` if a writer holds the lock or modifies the value while it is being read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::SeqLock;
+    ///
+    /// let lock = SeqLock::new(3);
+    /// assert_eq!(lock.try_read(), Some(3));
+    ///
+    /// let guard = lock.lock();
+    /// assert_eq!(lock.try_read(), None);
+    /// ```
+    pub fn try_read(&self) -> Option<T> {
+        let state = self.state.load(Ordering::Acquire);
+        if state & 1 == 1 {
+            return None;
+        }
+
+        // We need a volatile read here because other threads might concurrently modify the
+        // value. In theory, data races are *always* UB, even if we use volatile reads and
+        // discard the data when a data race is detected. The proper solution would be to do
+        // atomic reads and atomic writes, but we can't atomically read and write all kinds of
+        // data since `AtomicU8` is not available on stable versions of Rust yet.
+        let value = unsafe { ptr::read_volatile(self.value.get()) };
+
+        // The read must be complete before the stamp is checked again.
+        atomic::fence(Ordering::Acquire);
+        if self.state.load(Ordering::Relaxed) == state {
+            Some(value)
+        } else {
+            None
+        }
+    }
+
+    /// Replaces the current value.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::SeqLock;
+    ///
+    /// let lock = SeqLock::new(3);
+    /// lock.write(4);
+    /// assert_eq!(lock.read(), 4);
+    /// ```
+    pub fn write(&self, value: T) {
+        *self.lock() = value;
+    }
+}
+
+impl<T: Default> Default for SeqLock<T> {
+    fn default() -> SeqLock<T> {
+        SeqLock::new(Default::default())
+    }
+}
+
+impl<T> From<T> for SeqLock<T> {
+    fn from(t: T) -> Self {
+        SeqLock::new(t)
+    }
+}
+
+impl<T: Copy + fmt::Debug> fmt::Debug for SeqLock<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.try_read() {
+            Some(value) => f.debug_struct("SeqLock").field("data", &value).finish(),
+            None => {
+                struct LockedPlaceholder;
+                impl fmt::Debug for LockedPlaceholder {
+                    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                        f.write_str("<locked>")
+                    }
+                }
+                f.debug_struct("SeqLock")
+                    .field("data", &LockedPlaceholder)
+                    .finish()
+            }
+        }
+    }
+}
+
+/// A guard used to release the write lock of a [`SeqLock`] when dropped.
+pub struct SeqLockWriteGuard<'a, T> {
+    lock: &'a SeqLock<T>,
+
+    /// The stamp before the lock was acquired.
+    state: usize,
+
+    _marker: PhantomData<*mut ()>,
+}
+
+unsafe impl<T: Sync> Sync for SeqLockWriteGuard<'_, T> {}
+
+impl<T> Drop for SeqLockWriteGuard<'_, T> {
+    fn drop(&mut self) {
+        // Release the lock and increment the stamp.
+        self.lock
+            .state
+            .store(self.state.wrapping_add(2), Ordering::Release);
+    }
+}
+
+impl<T> Deref for SeqLockWriteGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
+}
+
+impl<T> DerefMut for SeqLockWriteGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.value.get() }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for SeqLockWriteGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SeqLockWriteGuard")
+            .field("data", &**self)
+            .finish()
+    }
+}
@@ -0,0 +1,217 @@
+use std::fmt;
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::thread::current_index;
+use crate::CachePadded;
+
+/// The default number of shards per counter.
+const NUM_SHARDS: usize = 16;
+
+/// A concurrent counter that spreads updates across multiple cache lines.
+///
+/// A single atomic integer updated by many threads at once becomes a bottleneck, because every
+/// update needs exclusive access to the cache line holding it. `ShardedCounter` is made of a list
+/// of *shards*, each being an atomic integer occupying a single cache line. Updates pick one of the
+/// shards depending on the current thread, while reads sum up all shards.
+///
+/// This makes updates fast and scalable, at the cost of reads being slower. It is a good fit for
+/// statistics and metrics that are updated often but read rarely.
+///
+/// All arithmetic wraps around on overflow, so the counter can be decremented below zero and the
+/// sum reinterpreted as an `isize`.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::sync::ShardedCounter;
+/// use crossbeam_utils::thread;
+///
+/// let counter = ShardedCounter::new();
+///
+/// thread::scope(|s| {
+///     for _ in 0..4 {
+///         s.spawn(|_| {
+///             for _ in 0..1000 {
+///                 counter.increment();
+///             }
+///         });
+///     }
+/// }).unwrap();
+///
+/// assert_eq!(counter.sum(), 4000);
+/// ```
+pub struct ShardedCounter {
+    /// The shards holding parts of the sum.
+    shards: Box<[CachePadded<AtomicUsize>]>,
+}
+
+impl ShardedCounter {
+    /// Creates a new counter starting at zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::ShardedCounter;
+    ///
+    /// let counter = ShardedCounter::new();
+    /// assert_eq!(counter.sum(), 0);
+    /// ```
+    pub fn new() -> ShardedCounter {
+        ShardedCounter::with_shards(NUM_SHARDS)
+    }
+
+    /// Creates a new counter starting at zero with the given number of shards.
+    ///
+    /// The number of shards is rounded up to the nearest power of two. More shards reduce
+    /// contention between concurrently updating threads, but make reads slower.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of shards is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::ShardedCounter;
+    ///
+    /// let counter = ShardedCounter::with_shards(64);
+    /// ```
+    pub fn with_shards(shards: usize) -> ShardedCounter {
+        assert!(shards > 0, "number of shards must be non-zero");
+
+        ShardedCounter {
+            shards: (0..shards.next_power_of_two())
+                .map(|_| CachePadded::new(AtomicUsize::new(0)))
+                .collect(),
+        }
+    }
+
+    /// Returns the shard for the current thread.
+    #[inline]
+    fn shard(&self) -> &AtomicUsize {
+        // Thread indices tend to be consecutive numbers, so threads spread across all shards.
+        let current_index = current_index().unwrap_or(0);
+        &self.shards[current_index & (self.shards.len() - 1)]
+    }
+
+    /// Adds one to the counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::ShardedCounter;
+    ///
+    /// let counter = ShardedCounter::new();
+    /// counter.increment();
+    /// assert_eq!(counter.sum(), 1);
+    /// ```
+    #[inline]
+    pub fn increment(&self) {
+        self.add(1);
+    }
+
+    /// Subtracts one from the counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::ShardedCounter;
+    ///
+    /// let counter = ShardedCounter::new();
+    /// counter.add(2);
+    /// counter.decrement();
+    /// assert_eq!(counter.sum(), 1);
+    /// ```
+    #[inline]
+    pub fn decrement(&self) {
+        self.sub(1);
+    }
+
+    /// Adds `n` to the counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::ShardedCounter;
+    ///
+    /// let counter = ShardedCounter::new();
+    /// counter.add(10);
+    /// assert_eq!(counter.sum(), 10);
+    /// ```
+    #[inline]
+    pub fn add(&self, n: usize) {
+        self.shard().fetch_add(n, Ordering::Relaxed);
+    }
+
+    /// Subtracts `n` from the counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::ShardedCounter;
+    ///
+    /// let counter = ShardedCounter::new();
+    /// counter.sub(3);
+    /// assert_eq!(counter.sum() as isize, -3);
+    /// ```
+    #[inline]
+    pub fn sub(&self, n: usize) {
+        self.shard().fetch_sub(n, Ordering::Relaxed);
+    }
+
+    /// Returns the current value of the counter.
+    ///
+    /// The shards are read one by one, so updates made concurrently with this call may or may not
+    /// be included. If the counter is not being updated, the result is exact.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::ShardedCounter;
+    ///
+    /// let counter = ShardedCounter::new();
+    /// counter.add(5);
+    /// counter.increment();
+    /// assert_eq!(counter.sum(), 6);
+    /// ```
+    pub fn sum(&self) -> usize {
+        self.shards.iter().fold(0, |sum, shard| {
+            sum.wrapping_add(shard.load(Ordering::Relaxed))
+        })
+    }
+
+    /// Resets the counter to zero and returns its previous value.
+    ///
+    /// Each shard is reset atomically, so no concurrent update is lost: it is either included in
+    /// the returned value or kept in the counter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::ShardedCounter;
+    ///
+    /// let counter = ShardedCounter::new();
+    /// counter.add(5);
+    /// assert_eq!(counter.reset(), 5);
+    /// assert_eq!(counter.sum(), 0);
+    /// ```
+    pub fn reset(&self) -> usize {
+        self.shards.iter().fold(0, |sum, shard| {
+            sum.wrapping_add(shard.swap(0, Ordering::Relaxed))
+        })
+    }
+}
+
+impl Default for ShardedCounter {
+    fn default() -> ShardedCounter {
+        ShardedCounter::new()
+    }
+}
+
+impl fmt::Debug for ShardedCounter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShardedCounter")
+            .field("sum", &self.sum())
+            .finish()
+    }
+}
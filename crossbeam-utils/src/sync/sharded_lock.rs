@@ -1,16 +1,17 @@
 use std::cell::UnsafeCell;
-use std::collections::HashMap;
 use std::fmt;
 use std::marker::PhantomData;
-use std::mem;
+use std::mem::{self, ManuallyDrop};
 use std::ops::{Deref, DerefMut};
 use std::panic::{RefUnwindSafe, UnwindSafe};
+use std::ptr;
 use std::sync::{LockResult, PoisonError, TryLockError, TryLockResult};
-use std::sync::{Mutex, RwLock, RwLockReadGuard, RwLockWriteGuard};
-use std::thread::{self, ThreadId};
+use std::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::thread;
+use std::time::{Duration, Instant};
 
-use crate::CachePadded;
-use lazy_static::lazy_static;
+use crate::thread::current_index;
+use crate::{Backoff, CachePadded};
 
 /// The number of shards per sharded lock. Must be a power of two.
 const NUM_SHARDS: usize = 8;
@@ -77,6 +78,12 @@ pub struct ShardedLock<T: ?Sized> {
     /// A list of locks protecting the internal data.
     shards: Box<[CachePadded<Shard>]>,
 
+    /// A lock held by writers and upgradable readers.
+    ///
+    /// Holding it while write-locking the shards ensures that an upgradable reader never has to
+    /// compete with a writer that has already locked some of the shards.
+    upgrade: Mutex<()>,
+
     /// The internal data.
     value: UnsafeCell<T>,
 }
@@ -107,6 +114,7 @@ impl<T> ShardedLock<T> {
                     })
                 })
                 .collect::<Box<[_]>>(),
+            upgrade: Mutex::new(()),
             value: UnsafeCell::new(value),
         }
     }
@@ -245,6 +253,67 @@ impl<T: ?Sized> ShardedLock<T> {
         }
     }
 
+    /// Attempts to acquire this lock with shared read access, blocking the current thread for at
+    /// most `timeout`.
+    ///
+    /// If the access could not be granted before the timeout expires, an error is returned.
+    /// Otherwise, a guard is returned which will release the shared access when it is dropped.
+    ///
+    /// The standard library doesn't provide timed locking, so this method polls the lock,
+    /// spinning first and then sleeping for increasingly long periods.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the lock is poisoned. A lock gets poisoned when a write
+    /// operation panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::ShardedLock;
+    /// use std::time::Duration;
+    ///
+    /// let lock = ShardedLock::new(1);
+    ///
+    /// let n = lock.write().unwrap();
+    /// assert!(lock.try_read_for(Duration::from_millis(10)).is_err());
+    ///
+    /// drop(n);
+    /// assert_eq!(*lock.try_read_for(Duration::from_millis(10)).unwrap(), 1);
+    /// ```
+    pub fn try_read_for(&self, timeout: Duration) -> TryLockResult<ShardedLockReadGuard<'_, T>> {
+        self.try_read_until(Instant::now() + timeout)
+    }
+
+    /// Attempts to acquire this lock with shared read access, blocking the current thread until
+    /// at most `deadline`.
+    ///
+    /// If the access could not be granted before the deadline, an error is returned. Otherwise, a
+    /// guard is returned which will release the shared access when it is dropped.
+    ///
+    /// The standard library doesn't provide timed locking, so this method polls the lock,
+    /// spinning first and then sleeping for increasingly long periods.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the lock is poisoned. A lock gets poisoned when a write
+    /// operation panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::ShardedLock;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let lock = ShardedLock::new(1);
+    /// let deadline = Instant::now() + Duration::from_millis(10);
+    ///
+    /// assert_eq!(*lock.try_read_until(deadline).unwrap(), 1);
+    /// ```
+    pub fn try_read_until(&self, deadline: Instant) -> TryLockResult<ShardedLockReadGuard<'_, T>> {
+        poll_until(deadline, || self.try_read())
+    }
+
     /// Locks with shared read access, blocking the current thread until it can be acquired.
     ///
     /// The calling thread will be blocked until there are no more writers which hold the lock.
@@ -326,6 +395,12 @@ impl<T: ?Sized> ShardedLock<T> {
     /// assert!(lock.try_write().is_err());
     /// ```
     pub fn try_write(&self) -> TryLockResult<ShardedLockWriteGuard<'_, T>> {
+        let upgrade = match self.upgrade.try_lock() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(TryLockError::WouldBlock) => return Err(TryLockError::WouldBlock),
+        };
+
         let mut poisoned = false;
         let mut blocked = None;
 
@@ -364,17 +439,85 @@ impl<T: ?Sized> ShardedLock<T> {
         } else if poisoned {
             let guard = ShardedLockWriteGuard {
                 lock: self,
+                _upgrade: upgrade,
                 _marker: PhantomData,
             };
             Err(TryLockError::Poisoned(PoisonError::new(guard)))
         } else {
             Ok(ShardedLockWriteGuard {
                 lock: self,
+                _upgrade: upgrade,
                 _marker: PhantomData,
             })
         }
     }
 
+    /// Attempts to acquire this lock with exclusive write access, blocking the current thread for
+    /// at most `timeout`.
+    ///
+    /// If the access could not be granted before the timeout expires, an error is returned.
+    /// Otherwise, a guard is returned which will release the exclusive access when it is dropped.
+    ///
+    /// The standard library doesn't provide timed locking, so this method polls the lock,
+    /// spinning first and then sleeping for increasingly long periods. Under a steady stream of
+    /// readers, it may not get the lock before the timeout expires.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the lock is poisoned. A lock gets poisoned when a write
+    /// operation panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::ShardedLock;
+    /// use std::time::Duration;
+    ///
+    /// let lock = ShardedLock::new(1);
+    ///
+    /// let n = lock.read().unwrap();
+    /// assert!(lock.try_write_for(Duration::from_millis(10)).is_err());
+    ///
+    /// drop(n);
+    /// *lock.try_write_for(Duration::from_millis(10)).unwrap() = 2;
+    /// ```
+    pub fn try_write_for(&self, timeout: Duration) -> TryLockResult<ShardedLockWriteGuard<'_, T>> {
+        self.try_write_until(Instant::now() + timeout)
+    }
+
+    /// Attempts to acquire this lock with exclusive write access, blocking the current thread
+    /// until at most `deadline`.
+    ///
+    /// If the access could not be granted before the deadline, an error is returned. Otherwise, a
+    /// guard is returned which will release the exclusive access when it is dropped.
+    ///
+    /// The standard library doesn't provide timed locking, so this method polls the lock,
+    /// spinning first and then sleeping for increasingly long periods. Under a steady stream of
+    /// readers, it may not get the lock before the deadline.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the lock is poisoned. A lock gets poisoned when a write
+    /// operation panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::ShardedLock;
+    /// use std::time::{Duration, Instant};
+    ///
+    /// let lock = ShardedLock::new(1);
+    /// let deadline = Instant::now() + Duration::from_millis(10);
+    ///
+    /// *lock.try_write_until(deadline).unwrap() = 2;
+    /// ```
+    pub fn try_write_until(
+        &self,
+        deadline: Instant,
+    ) -> TryLockResult<ShardedLockWriteGuard<'_, T>> {
+        poll_until(deadline, || self.try_write())
+    }
+
     /// Locks with exclusive write access, blocking the current thread until it can be acquired.
     ///
     /// The calling thread will be blocked until there are no more writers which hold the lock.
@@ -406,9 +549,147 @@ impl<T: ?Sized> ShardedLock<T> {
     /// assert!(lock.try_read().is_err());
     /// ```
     pub fn write(&self) -> LockResult<ShardedLockWriteGuard<'_, T>> {
+        let upgrade = self.upgrade.lock().unwrap_or_else(PoisonError::into_inner);
+        let poisoned = self.write_shards();
+
+        let guard = ShardedLockWriteGuard {
+            lock: self,
+            _upgrade: upgrade,
+            _marker: PhantomData,
+        };
+        if poisoned {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
+
+    /// Attempts to acquire this lock with upgradable read access.
+    ///
+    /// An upgradable read guard gives shared access like [`read`], but it can later be turned into
+    /// a write guard with [`ShardedLockUpgradableReadGuard::upgrade`] without letting any writer
+    /// in between. Only one upgradable reader can hold the lock at a time, but it may coexist with
+    /// any number of ordinary readers.
+    ///
+    /// If the access could not be granted at this time, an error is returned.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the lock is poisoned. A lock gets poisoned when a write
+    /// operation panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::ShardedLock;
+    ///
+    /// let lock = ShardedLock::new(1);
+    ///
+    /// let n = lock.try_read_upgradable().unwrap();
+    /// assert_eq!(*n, 1);
+    ///
+    /// // Readers may still come in, but other upgradable readers may not.
+    /// assert!(lock.try_read().is_ok());
+    /// assert!(lock.try_read_upgradable().is_err());
+    /// ```
+    ///
+    /// [`read`]: ShardedLock::read
+    pub fn try_read_upgradable(&self) -> TryLockResult<ShardedLockUpgradableReadGuard<'_, T>> {
+        let upgrade = match self.upgrade.try_lock() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(TryLockError::WouldBlock) => return Err(TryLockError::WouldBlock),
+        };
+
+        let current_index = current_index().unwrap_or(0);
+        let shard_index = current_index & (self.shards.len() - 1);
+
+        match self.shards[shard_index].lock.try_read() {
+            Ok(guard) => Ok(ShardedLockUpgradableReadGuard {
+                lock: self,
+                upgrade,
+                read: guard,
+                _marker: PhantomData,
+            }),
+            Err(TryLockError::Poisoned(err)) => {
+                let guard = ShardedLockUpgradableReadGuard {
+                    lock: self,
+                    upgrade,
+                    read: err.into_inner(),
+                    _marker: PhantomData,
+                };
+                Err(TryLockError::Poisoned(PoisonError::new(guard)))
+            }
+            Err(TryLockError::WouldBlock) => Err(TryLockError::WouldBlock),
+        }
+    }
+
+    /// Locks with upgradable read access, blocking the current thread until it can be acquired.
+    ///
+    /// An upgradable read guard gives shared access like [`read`], but it can later be turned into
+    /// a write guard with [`ShardedLockUpgradableReadGuard::upgrade`] without letting any writer
+    /// in between. Only one upgradable reader can hold the lock at a time, but it may coexist with
+    /// any number of ordinary readers.
+    ///
+    /// The calling thread will be blocked until there are no more writers or upgradable readers
+    /// which hold the lock.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the lock is poisoned. A lock gets poisoned when a write
+    /// operation panics.
+    ///
+    /// # Panics
+    ///
+    /// This method might panic when called if the lock is already held by the current thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::{ShardedLock, ShardedLockUpgradableReadGuard};
+    ///
+    /// let lock = ShardedLock::new(Vec::new());
+    ///
+    /// let v = lock.read_upgradable().unwrap();
+    /// if v.is_empty() {
+    ///     // No writer can get in between the check above and the write below.
+    ///     let mut v = ShardedLockUpgradableReadGuard::upgrade(v).unwrap();
+    ///     v.push(1);
+    /// }
+    ///
+    /// assert_eq!(*lock.read().unwrap(), [1]);
+    /// ```
+    ///
+    /// [`read`]: ShardedLock::read
+    pub fn read_upgradable(&self) -> LockResult<ShardedLockUpgradableReadGuard<'_, T>> {
+        let upgrade = self.upgrade.lock().unwrap_or_else(PoisonError::into_inner);
+
+        let current_index = current_index().unwrap_or(0);
+        let shard_index = current_index & (self.shards.len() - 1);
+
+        match self.shards[shard_index].lock.read() {
+            Ok(guard) => Ok(ShardedLockUpgradableReadGuard {
+                lock: self,
+                upgrade,
+                read: guard,
+                _marker: PhantomData,
+            }),
+            Err(err) => Err(PoisonError::new(ShardedLockUpgradableReadGuard {
+                lock: self,
+                upgrade,
+                read: err.into_inner(),
+                _marker: PhantomData,
+            })),
+        }
+    }
+
+    /// Write-locks each shard in succession, returning `true` if any of them is poisoned.
+    ///
+    /// The guards are stored in the shards and released when the write guard is dropped. The
+    /// caller must hold the `upgrade` lock.
+    fn write_shards(&self) -> bool {
         let mut poisoned = false;
 
-        // Write-lock each shard in succession.
         for shard in self.shards.iter() {
             let guard = match shard.lock.write() {
                 Ok(guard) => guard,
@@ -427,16 +708,18 @@ impl<T: ?Sized> ShardedLock<T> {
             }
         }
 
-        if poisoned {
-            Err(PoisonError::new(ShardedLockWriteGuard {
-                lock: self,
-                _marker: PhantomData,
-            }))
-        } else {
-            Ok(ShardedLockWriteGuard {
-                lock: self,
-                _marker: PhantomData,
-            })
+        poisoned
+    }
+
+    /// Releases the write locks stored in the shards by [`ShardedLock::write_shards`].
+    fn unlock_shards(&self) {
+        // Unlock the shards in reverse order of locking.
+        for shard in self.shards.iter().rev() {
+            unsafe {
+                let dest: *mut _ = shard.write_guard.get();
+                let guard = (*dest).take();
+                drop(guard);
+            }
         }
     }
 }
@@ -513,21 +796,60 @@ impl<T: ?Sized + fmt::Display> fmt::Display for ShardedLockReadGuard<'_, T> {
 /// A guard used to release the exclusive write access of a [`ShardedLock`] when dropped.
 pub struct ShardedLockWriteGuard<'a, T: ?Sized> {
     lock: &'a ShardedLock<T>,
+    _upgrade: MutexGuard<'a, ()>,
     _marker: PhantomData<RwLockWriteGuard<'a, T>>,
 }
 
 unsafe impl<T: ?Sized + Sync> Sync for ShardedLockWriteGuard<'_, T> {}
 
+impl<'a, T: ?Sized> ShardedLockWriteGuard<'a, T> {
+    /// Downgrades the guard to shared read access.
+    ///
+    /// No writer can acquire the lock between the write access and the read access, though other
+    /// readers may come in as soon as this method is called.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::{ShardedLock, ShardedLockWriteGuard};
+    ///
+    /// let lock = ShardedLock::new(1);
+    ///
+    /// let mut n = lock.write().unwrap();
+    /// *n = 2;
+    ///
+    /// // Keep reading the published value, knowing that no other writer has changed it.
+    /// let n = ShardedLockWriteGuard::downgrade(n);
+    /// assert_eq!(*n, 2);
+    /// assert!(lock.try_write().is_err());
+    /// ```
+    pub fn downgrade(this: Self) -> ShardedLockReadGuard<'a, T> {
+        let this = ManuallyDrop::new(this);
+        let lock = this.lock;
+        // Keep holding the `upgrade` lock so that writers stay out while we switch guards.
+        let upgrade = unsafe { ptr::read(&this._upgrade) };
+
+        lock.unlock_shards();
+
+        let current_index = current_index().unwrap_or(0);
+        let shard_index = current_index & (lock.shards.len() - 1);
+        let guard = lock.shards[shard_index]
+            .lock
+            .read()
+            .unwrap_or_else(PoisonError::into_inner);
+
+        drop(upgrade);
+        ShardedLockReadGuard {
+            lock,
+            _guard: guard,
+            _marker: PhantomData,
+        }
+    }
+}
+
 impl<T: ?Sized> Drop for ShardedLockWriteGuard<'_, T> {
     fn drop(&mut self) {
-        // Unlock the shards in reverse order of locking.
-        for shard in self.lock.shards.iter().rev() {
-            unsafe {
-                let dest: *mut _ = shard.write_guard.get();
-                let guard = mem::replace(&mut *dest, None);
-                drop(guard);
-            }
-        }
+        self.lock.unlock_shards();
     }
 }
 
@@ -559,72 +881,120 @@ impl<T: ?Sized> DerefMut for ShardedLockWriteGuard<'_, T> {
     }
 }
 
-/// Returns a `usize` that identifies the current thread.
+/// A guard used to release the upgradable read access of a [`ShardedLock`] when dropped.
 ///
-/// Each thread is associated with an 'index'. While there are no particular guarantees, indices
-/// usually tend to be consecutive numbers between 0 and the number of running threads.
+/// It can be turned into a [`ShardedLockWriteGuard`] with [`upgrade`].
 ///
-/// Since this function accesses TLS, `None` might be returned if the current thread's TLS is
-/// tearing down.
-#[inline]
-fn current_index() -> Option<usize> {
-    REGISTRATION.try_with(|reg| reg.index).ok()
+/// [`upgrade`]: ShardedLockUpgradableReadGuard::upgrade
+pub struct ShardedLockUpgradableReadGuard<'a, T: ?Sized> {
+    lock: &'a ShardedLock<T>,
+    upgrade: MutexGuard<'a, ()>,
+    read: RwLockReadGuard<'a, ()>,
+    _marker: PhantomData<RwLockReadGuard<'a, T>>,
 }
 
-/// The global registry keeping track of registered threads and indices.
-struct ThreadIndices {
-    /// Mapping from `ThreadId` to thread index.
-    mapping: HashMap<ThreadId, usize>,
-
-    /// A list of free indices.
-    free_list: Vec<usize>,
+unsafe impl<T: ?Sized + Sync> Sync for ShardedLockUpgradableReadGuard<'_, T> {}
 
-    /// The next index to allocate if the free list is empty.
-    next_index: usize,
+impl<'a, T: ?Sized> ShardedLockUpgradableReadGuard<'a, T> {
+    /// Upgrades the guard to exclusive write access, blocking the current thread until all other
+    /// readers have released the lock.
+    ///
+    /// No writer can acquire the lock between the upgradable read access and the write access.
+    ///
+    /// # Errors
+    ///
+    /// This method will return an error if the lock is poisoned. A lock gets poisoned when a write
+    /// operation panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::{ShardedLock, ShardedLockUpgradableReadGuard};
+    ///
+    /// let lock = ShardedLock::new(1);
+    ///
+    /// let n = lock.read_upgradable().unwrap();
+    /// let mut n = ShardedLockUpgradableReadGuard::upgrade(n).unwrap();
+    /// *n = 2;
+    /// drop(n);
+    ///
+    /// assert_eq!(*lock.read().unwrap(), 2);
+    /// ```
+    pub fn upgrade(this: Self) -> LockResult<ShardedLockWriteGuard<'a, T>> {
+        let ShardedLockUpgradableReadGuard {
+            lock,
+            upgrade,
+            read,
+            ..
+        } = this;
+
+        // Our own read lock would block the write lock on its shard. Releasing it is fine since
+        // writers are kept out by the `upgrade` lock.
+        drop(read);
+        let poisoned = lock.write_shards();
+
+        let guard = ShardedLockWriteGuard {
+            lock,
+            _upgrade: upgrade,
+            _marker: PhantomData,
+        };
+        if poisoned {
+            Err(PoisonError::new(guard))
+        } else {
+            Ok(guard)
+        }
+    }
 }
 
-lazy_static! {
-    static ref THREAD_INDICES: Mutex<ThreadIndices> = Mutex::new(ThreadIndices {
-        mapping: HashMap::new(),
-        free_list: Vec::new(),
-        next_index: 0,
-    });
+impl<T: ?Sized> Deref for ShardedLockUpgradableReadGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.value.get() }
+    }
 }
 
-/// A registration of a thread with an index.
-///
-/// When dropped, unregisters the thread and frees the reserved index.
-struct Registration {
-    index: usize,
-    thread_id: ThreadId,
+impl<T: fmt::Debug> fmt::Debug for ShardedLockUpgradableReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ShardedLockUpgradableReadGuard")
+            .field("lock", &self.lock)
+            .finish()
+    }
 }
 
-impl Drop for Registration {
-    fn drop(&mut self) {
-        let mut indices = THREAD_INDICES.lock().unwrap();
-        indices.mapping.remove(&self.thread_id);
-        indices.free_list.push(self.index);
+impl<T: ?Sized + fmt::Display> fmt::Display for ShardedLockUpgradableReadGuard<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        (**self).fmt(f)
     }
 }
 
-thread_local! {
-    static REGISTRATION: Registration = {
-        let thread_id = thread::current().id();
-        let mut indices = THREAD_INDICES.lock().unwrap();
-
-        let index = match indices.free_list.pop() {
-            Some(i) => i,
-            None => {
-                let i = indices.next_index;
-                indices.next_index += 1;
-                i
-            }
-        };
-        indices.mapping.insert(thread_id, index);
+/// Repeatedly calls `try_lock` until it doesn't return `WouldBlock` or `deadline` passes.
+fn poll_until<G, F>(deadline: Instant, mut try_lock: F) -> TryLockResult<G>
+where
+    F: FnMut() -> TryLockResult<G>,
+{
+    /// The longest time to sleep between two attempts.
+    const MAX_SLEEP: Duration = Duration::from_millis(1);
+
+    let backoff = Backoff::new();
+    let mut sleep = Duration::from_micros(10);
+
+    loop {
+        match try_lock() {
+            Err(TryLockError::WouldBlock) => {}
+            res => return res,
+        }
 
-        Registration {
-            index,
-            thread_id,
+        let now = Instant::now();
+        if now >= deadline {
+            return Err(TryLockError::WouldBlock);
         }
-    };
+
+        if backoff.is_completed() {
+            thread::sleep(sleep.min(deadline - now));
+            sleep = (sleep * 2).min(MAX_SLEEP);
+        } else {
+            backoff.snooze();
+        }
+    }
 }
@@ -0,0 +1,287 @@
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hash, Hasher};
+use std::marker::PhantomData;
+use std::sync::{Mutex, MutexGuard, TryLockError};
+
+use crate::CachePadded;
+
+/// The default number of stripes per lock. Must be a power of two.
+const NUM_STRIPES: usize = 64;
+
+/// A lock that guards a set of keys by hashing each key to one of a fixed number of stripes.
+///
+/// Locking a key only blocks threads that lock keys hashing to the same stripe, so unrelated keys
+/// can usually be locked concurrently, while memory usage stays constant no matter how many keys
+/// there are. This is useful for guarding resources that live outside the lock, such as files or
+/// rows identified by an ID.
+///
+/// Each stripe is padded to its own cache line, so threads locking different stripes don't
+/// contend on the same memory.
+///
+/// Since different keys may share a stripe, holding the guard for one key and then locking
+/// another can deadlock. Use [`lock_many`] to lock several keys at once.
+///
+/// Unlike [`std::sync::Mutex`], a `StripedLock` is not poisoned when a thread panics while
+/// holding a stripe, since it doesn't protect any data of its own.
+///
+/// [`lock_many`]: StripedLock::lock_many
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::sync::StripedLock;
+/// use crossbeam_utils::thread;
+///
+/// let locks = StripedLock::new();
+///
+/// thread::scope(|s| {
+///     for user_id in 0..8u64 {
+///         let locks = &locks;
+///         s.spawn(move |_| {
+///             let _guard = locks.lock(&user_id);
+///             // Update the record of `user_id`.
+///         });
+///     }
+/// }).unwrap();
+/// ```
+pub struct StripedLock<K: ?Sized, S = RandomState> {
+    /// The stripes, whose number is a power of two.
+    stripes: Box<[CachePadded<Mutex<()>>]>,
+
+    /// Hashes keys to stripes.
+    hash_builder: S,
+
+    /// Indicates that the lock is used with keys of type `K`.
+    _marker: PhantomData<fn(&K)>,
+}
+
+impl<K: Hash + ?Sized> StripedLock<K> {
+    /// Creates a new striped lock with the default number of stripes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::StripedLock;
+    ///
+    /// let locks = StripedLock::<str>::new();
+    /// ```
+    pub fn new() -> StripedLock<K> {
+        StripedLock::with_stripes(NUM_STRIPES)
+    }
+
+    /// Creates a new striped lock with at least `stripes` stripes.
+    ///
+    /// The number of stripes is rounded up to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stripes` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::StripedLock;
+    ///
+    /// let locks = StripedLock::<u64>::with_stripes(100);
+    /// assert_eq!(locks.num_stripes(), 128);
+    /// ```
+    pub fn with_stripes(stripes: usize) -> StripedLock<K> {
+        StripedLock::with_stripes_and_hasher(stripes, RandomState::new())
+    }
+}
+
+impl<K: Hash + ?Sized, S: BuildHasher> StripedLock<K, S> {
+    /// Creates a new striped lock with at least `stripes` stripes, which uses `hash_builder` to
+    /// hash keys.
+    ///
+    /// The number of stripes is rounded up to the next power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `stripes` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::StripedLock;
+    /// use std::collections::hash_map::RandomState;
+    ///
+    /// let locks = StripedLock::<u64, _>::with_stripes_and_hasher(16, RandomState::new());
+    /// ```
+    pub fn with_stripes_and_hasher(stripes: usize, hash_builder: S) -> StripedLock<K, S> {
+        assert!(stripes > 0, "number of stripes must be positive");
+
+        StripedLock {
+            stripes: (0..stripes.next_power_of_two())
+                .map(|_| CachePadded::new(Mutex::new(())))
+                .collect::<Vec<_>>()
+                .into_boxed_slice(),
+            hash_builder,
+            _marker: PhantomData,
+        }
+    }
+
+    /// Returns the number of stripes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::StripedLock;
+    ///
+    /// let locks = StripedLock::<u64>::with_stripes(8);
+    /// assert_eq!(locks.num_stripes(), 8);
+    /// ```
+    pub fn num_stripes(&self) -> usize {
+        self.stripes.len()
+    }
+
+    /// Returns the index of the stripe `key` maps to.
+    ///
+    /// Two keys with the same stripe index cannot be locked at the same time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::StripedLock;
+    ///
+    /// let locks = StripedLock::<u64>::with_stripes(8);
+    /// assert!(locks.stripe(&42) < 8);
+    /// assert_eq!(locks.stripe(&42), locks.stripe(&42));
+    /// ```
+    pub fn stripe(&self, key: &K) -> usize {
+        let mut hasher = self.hash_builder.build_hasher();
+        key.hash(&mut hasher);
+        hasher.finish() as usize & (self.stripes.len() - 1)
+    }
+
+    /// Locks the stripe `key` maps to, blocking until it is available.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::StripedLock;
+    ///
+    /// let locks = StripedLock::new();
+    ///
+    /// let guard = locks.lock("alice");
+    /// assert!(locks.try_lock("alice").is_none());
+    /// ```
+    pub fn lock(&self, key: &K) -> StripedLockGuard<'_> {
+        self.lock_stripe(self.stripe(key))
+    }
+
+    /// Attempts to lock the stripe `key` maps to without blocking.
+    ///
+    /// Returns `None` if the stripe is locked by someone else.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::StripedLock;
+    ///
+    /// let locks = StripedLock::new();
+    ///
+    /// let guard = locks.try_lock(&1).unwrap();
+    /// assert!(locks.try_lock(&1).is_none());
+    ///
+    /// drop(guard);
+    /// assert!(locks.try_lock(&1).is_some());
+    /// ```
+    pub fn try_lock(&self, key: &K) -> Option<StripedLockGuard<'_>> {
+        let stripe = self.stripe(key);
+        let guard = match self.stripes[stripe].try_lock() {
+            Ok(guard) => guard,
+            Err(TryLockError::Poisoned(err)) => err.into_inner(),
+            Err(TryLockError::WouldBlock) => return None,
+        };
+        Some(StripedLockGuard {
+            stripe,
+            _guard: guard,
+        })
+    }
+
+    /// Locks the stripes of all the given keys, blocking until all of them are available.
+    ///
+    /// Stripes are always locked in the same order and each stripe is locked only once, so
+    /// threads locking overlapping sets of keys this way cannot deadlock each other.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::StripedLock;
+    ///
+    /// let locks = StripedLock::new();
+    ///
+    /// // Transfer money between two accounts.
+    /// let guards = locks.lock_many(&[&1u64, &2]);
+    /// assert!(locks.try_lock(&1).is_none());
+    /// assert!(locks.try_lock(&2).is_none());
+    /// ```
+    pub fn lock_many(&self, keys: &[&K]) -> Vec<StripedLockGuard<'_>> {
+        let mut stripes: Vec<usize> = keys.iter().map(|key| self.stripe(key)).collect();
+        stripes.sort();
+        stripes.dedup();
+
+        stripes
+            .into_iter()
+            .map(|stripe| self.lock_stripe(stripe))
+            .collect()
+    }
+
+    /// Locks the stripe with the given index.
+    fn lock_stripe(&self, stripe: usize) -> StripedLockGuard<'_> {
+        let guard = self.stripes[stripe]
+            .lock()
+            .unwrap_or_else(|err| err.into_inner());
+        StripedLockGuard {
+            stripe,
+            _guard: guard,
+        }
+    }
+}
+
+impl<K: Hash + ?Sized> Default for StripedLock<K> {
+    fn default() -> StripedLock<K> {
+        StripedLock::new()
+    }
+}
+
+impl<K: ?Sized, S> fmt::Debug for StripedLock<K, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StripedLock")
+            .field("num_stripes", &self.stripes.len())
+            .finish()
+    }
+}
+
+/// A guard used to release a stripe of a [`StripedLock`] when dropped.
+pub struct StripedLockGuard<'a> {
+    stripe: usize,
+    _guard: MutexGuard<'a, ()>,
+}
+
+impl StripedLockGuard<'_> {
+    /// Returns the index of the locked stripe.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::StripedLock;
+    ///
+    /// let locks = StripedLock::new();
+    /// let guard = locks.lock(&7);
+    /// assert_eq!(guard.stripe(), locks.stripe(&7));
+    /// ```
+    pub fn stripe(&self) -> usize {
+        self.stripe
+    }
+}
+
+impl fmt::Debug for StripedLockGuard<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StripedLockGuard")
+            .field("stripe", &self.stripe)
+            .finish()
+    }
+}
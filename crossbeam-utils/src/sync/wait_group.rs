@@ -3,6 +3,7 @@
 
 use crate::primitive::sync::{Arc, Condvar, Mutex};
 use std::fmt;
+use std::time::{Duration, Instant};
 
 /// Enables threads to synchronize the beginning or end of some computation.
 ///
@@ -114,6 +115,85 @@ impl WaitGroup {
             count = inner.cvar.wait(count).unwrap();
         }
     }
+
+    /// Waits until all other references are dropped, but only for a limited time.
+    ///
+    /// Returns `true` if this is the only remaining reference, or `false` if the timeout expired
+    /// first.
+    ///
+    /// Unlike [`wait`], this method keeps the reference, so that [`count`] can be used afterwards
+    /// to find out how many other references are still alive. Because of that, it never returns
+    /// `true` while another thread is waiting on a clone of this wait group.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::WaitGroup;
+    /// use std::thread;
+    /// use std::time::Duration;
+    ///
+    /// let wg = WaitGroup::new();
+    ///
+    /// for i in 0..4 {
+    ///     let wg = wg.clone();
+    ///     thread::spawn(move || {
+    ///         // The last worker takes too long.
+    ///         if i == 3 {
+    ///             thread::sleep(Duration::from_secs(1));
+    ///         }
+    ///         drop(wg);
+    ///     });
+    /// }
+    ///
+    /// // Give the workers a grace period to finish.
+    /// if !wg.wait_timeout(Duration::from_millis(100)) {
+    ///     println!("{} workers are still running", wg.count() - 1);
+    /// }
+    /// ```
+    ///
+    /// [`wait`]: WaitGroup::wait
+    /// [`count`]: WaitGroup::count
+    pub fn wait_timeout(&self, timeout: Duration) -> bool {
+        let deadline = Instant::now() + timeout;
+
+        let mut count = self.inner.count.lock().unwrap();
+        while *count > 1 {
+            let now = Instant::now();
+            if now >= deadline {
+                return false;
+            }
+            count = self
+                .inner
+                .cvar
+                .wait_timeout(count, deadline - now)
+                .unwrap()
+                .0;
+        }
+        true
+    }
+
+    /// Returns the number of references to this wait group, including this one.
+    ///
+    /// If other threads can clone or drop references concurrently, the result may be out of date
+    /// by the time it is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::sync::WaitGroup;
+    ///
+    /// let wg = WaitGroup::new();
+    /// assert_eq!(wg.count(), 1);
+    ///
+    /// let wg2 = wg.clone();
+    /// assert_eq!(wg.count(), 2);
+    ///
+    /// drop(wg2);
+    /// assert_eq!(wg.count(), 1);
+    /// ```
+    pub fn count(&self) -> usize {
+        *self.inner.count.lock().unwrap()
+    }
 }
 
 impl Drop for WaitGroup {
@@ -121,7 +201,8 @@ impl Drop for WaitGroup {
         let mut count = self.inner.count.lock().unwrap();
         *count -= 1;
 
-        if *count == 0 {
+        // Threads in `wait` are waiting for zero references, and threads in `wait_timeout` for one.
+        if *count <= 1 {
             self.inner.cvar.notify_all();
         }
     }
@@ -111,16 +111,19 @@
 //! }).unwrap();
 //! ```
 
+use std::collections::HashMap;
 use std::fmt;
 use std::io;
 use std::marker::PhantomData;
 use std::mem;
 use std::panic;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, Mutex};
-use std::thread;
+use std::thread::{self, ThreadId};
 
 use crate::sync::WaitGroup;
 use cfg_if::cfg_if;
+use lazy_static::lazy_static;
 
 type SharedVec<T> = Arc<Mutex<Vec<T>>>;
 type SharedOption<T> = Arc<Mutex<Option<T>>>;
@@ -146,6 +149,66 @@ type SharedOption<T> = Arc<Mutex<Option<T>>>;
 /// }).unwrap();
 /// ```
 pub fn scope<'env, F, R>(f: F) -> thread::Result<R>
+where
+    F: FnOnce(&Scope<'env>) -> R,
+{
+    scope_with_policy(PanicPolicy::Collect, f)
+}
+
+/// What a scope does when one of its threads panics.
+///
+/// With the default policy, [`Collect`], a panic doesn't affect the other threads. A large
+/// computation then runs to completion even though its result will be discarded. The other
+/// policies mark the scope as cancelled as soon as a thread panics, so that threads checking
+/// [`Scope::is_cancelled`] can stop early.
+///
+/// [`Collect`]: PanicPolicy::Collect
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PanicPolicy {
+    /// Returns an `Err` containing errors from all panicked threads once every thread has been
+    /// joined. This is the behavior of [`scope`].
+    Collect,
+
+    /// Like `Collect`, but cancels the scope as soon as a thread panics.
+    Cancel,
+
+    /// Cancels the scope as soon as a thread panics and, once every thread has been joined,
+    /// resumes unwinding with the panic of the first panicked thread in spawn order.
+    Resume,
+}
+
+impl Default for PanicPolicy {
+    fn default() -> PanicPolicy {
+        PanicPolicy::Collect
+    }
+}
+
+/// Creates a new scope for spawning threads, with a policy for handling panics in child threads.
+///
+/// This function works like [`scope`], except that panics in child threads are handled according
+/// to `policy`. If `f` itself panics, the scope is cancelled before its threads are joined and
+/// the panic is resumed afterwards, regardless of the policy.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::thread::{self, PanicPolicy};
+/// use std::time::Duration;
+///
+/// let result = thread::scope_with_policy(PanicPolicy::Cancel, |s| {
+///     s.spawn(|_| panic!("failed early"));
+///
+///     s.spawn(|s| {
+///         // A long computation that gives up once another thread has failed.
+///         while !s.is_cancelled() {
+///             std::thread::sleep(Duration::from_millis(1));
+///         }
+///     });
+/// });
+///
+/// assert!(result.is_err());
+/// ```
+pub fn scope_with_policy<'env, F, R>(policy: PanicPolicy, f: F) -> thread::Result<R>
 where
     F: FnOnce(&Scope<'env>) -> R,
 {
@@ -153,18 +216,23 @@ where
     let scope = Scope::<'env> {
         handles: SharedVec::default(),
         wait_group: wg.clone(),
+        cancelled: Arc::new(AtomicBool::new(false)),
+        policy,
         _marker: PhantomData,
     };
 
     // Execute the scoped function, but catch any panics.
     let result = panic::catch_unwind(panic::AssertUnwindSafe(|| f(&scope)));
+    if result.is_err() {
+        scope.cancel();
+    }
 
     // Wait until all nested scopes are dropped.
     drop(scope.wait_group);
     wg.wait();
 
     // Join all remaining spawned threads.
-    let panics: Vec<_> = scope
+    let mut panics: Vec<_> = scope
         .handles
         .lock()
         .unwrap()
@@ -175,13 +243,15 @@ where
         .collect();
 
     // If `f` has panicked, resume unwinding.
-    // If any of the child threads have panicked, return the panic errors.
+    // If any of the child threads have panicked, return the panic errors or resume the first one.
     // Otherwise, everything is OK and return the result of `f`.
     match result {
         Err(err) => panic::resume_unwind(err),
         Ok(res) => {
             if panics.is_empty() {
                 Ok(res)
+            } else if policy == PanicPolicy::Resume {
+                panic::resume_unwind(panics.remove(0))
             } else {
                 Err(Box::new(panics))
             }
@@ -189,6 +259,61 @@ where
     }
 }
 
+/// Runs `f` on each item of `items` in its own scoped thread and collects the results.
+///
+/// The results are returned in the order of `items`. This is a shorthand for spawning one thread
+/// per item inside a [`scope`] and joining all of them, so fork-join computations don't need a
+/// side channel to get their results back.
+///
+/// If any of the threads has panicked, an `Err` is returned containing errors from panicked
+/// threads, just like [`scope`] does.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::thread;
+///
+/// let chunks = vec![vec![1, 2], vec![3, 4], vec![5, 6]];
+///
+/// let sums = thread::scope_map(&chunks, |chunk| chunk.iter().sum::<i32>()).unwrap();
+/// assert_eq!(sums, [3, 7, 11]);
+/// ```
+pub fn scope_map<'env, I, F, T>(items: I, f: F) -> thread::Result<Vec<T>>
+where
+    I: IntoIterator,
+    I::Item: Send + 'env,
+    F: Fn(I::Item) -> T + Sync + 'env,
+    T: Send + 'env,
+{
+    let f = &f;
+    let results = scope(|s| {
+        let handles: Vec<_> = items
+            .into_iter()
+            .map(|item| s.spawn(move |_| f(item)))
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join())
+            .collect::<Vec<_>>()
+    })?;
+
+    let mut values = Vec::with_capacity(results.len());
+    let mut panics = Vec::new();
+    for res in results {
+        match res {
+            Ok(value) => values.push(value),
+            Err(err) => panics.push(err),
+        }
+    }
+
+    if panics.is_empty() {
+        Ok(values)
+    } else {
+        Err(Box::new(panics))
+    }
+}
+
 /// A scope for spawning threads.
 pub struct Scope<'env> {
     /// The list of the thread join handles.
@@ -197,6 +322,12 @@ pub struct Scope<'env> {
     /// Used to wait until all subscopes all dropped.
     wait_group: WaitGroup,
 
+    /// Set when the scope is cancelled.
+    cancelled: Arc<AtomicBool>,
+
+    /// What to do when a thread panics.
+    policy: PanicPolicy,
+
     /// Borrows data with invariant lifetime `'env`.
     _marker: PhantomData<&'env mut &'env ()>,
 }
@@ -272,6 +403,50 @@ impl<'env> Scope<'env> {
             builder: thread::Builder::new(),
         }
     }
+
+    /// Cancels the scope.
+    ///
+    /// Cancellation is only a flag: threads are not interrupted, but they can check
+    /// [`is_cancelled`] and stop early. Depending on the [`PanicPolicy`], a scope is also
+    /// cancelled automatically when one of its threads panics.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::thread;
+    ///
+    /// thread::scope(|s| {
+    ///     s.spawn(|s| {
+    ///         while !s.is_cancelled() {
+    ///             std::thread::yield_now();
+    ///         }
+    ///     });
+    ///
+    ///     s.cancel();
+    /// }).unwrap();
+    /// ```
+    ///
+    /// [`is_cancelled`]: Scope::is_cancelled
+    pub fn cancel(&self) {
+        self.cancelled.store(true, Ordering::Release);
+    }
+
+    /// Returns `true` if the scope has been cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam_utils::thread;
+    ///
+    /// thread::scope(|s| {
+    ///     assert!(!s.is_cancelled());
+    ///     s.cancel();
+    ///     assert!(s.is_cancelled());
+    /// }).unwrap();
+    /// ```
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Acquire)
+    }
 }
 
 impl fmt::Debug for Scope<'_> {
@@ -424,6 +599,8 @@ impl<'scope, 'env> ScopedThreadBuilder<'scope, 'env> {
             let scope = Scope::<'env> {
                 handles: Arc::clone(&self.scope.handles),
                 wait_group: self.scope.wait_group.clone(),
+                cancelled: Arc::clone(&self.scope.cancelled),
+                policy: self.scope.policy,
                 _marker: PhantomData,
             };
 
@@ -433,8 +610,12 @@ impl<'scope, 'env> ScopedThreadBuilder<'scope, 'env> {
                     // Make sure the scope is inside the closure with the proper `'env` lifetime.
                     let scope: Scope<'env> = scope;
 
+                    // Cancel the scope if the closure panics, unless panics are merely collected.
+                    let guard = CancelOnPanic(&scope);
+
                     // Run the closure.
                     let res = f(&scope);
+                    drop(guard);
 
                     // Store the result if the closure didn't panic.
                     *result.lock().unwrap() = Some(res);
@@ -466,6 +647,17 @@ impl<'scope, 'env> ScopedThreadBuilder<'scope, 'env> {
     }
 }
 
+/// Cancels a scope if dropped while panicking, depending on the scope's panic policy.
+struct CancelOnPanic<'a, 'env>(&'a Scope<'env>);
+
+impl Drop for CancelOnPanic<'_, '_> {
+    fn drop(&mut self) {
+        if thread::panicking() && self.0.policy != PanicPolicy::Collect {
+            self.0.cancel();
+        }
+    }
+}
+
 unsafe impl<T> Send for ScopedJoinHandle<'_, T> {}
 unsafe impl<T> Sync for ScopedJoinHandle<'_, T> {}
 
@@ -583,3 +775,102 @@ impl<T> fmt::Debug for ScopedJoinHandle<'_, T> {
         f.pad("ScopedJoinHandle { .. }")
     }
 }
+
+/// Returns a small integer that identifies the current thread.
+///
+/// Unlike [`std::thread::ThreadId`], these IDs are densely packed: they start at zero, and when a
+/// thread exits its ID is recycled for the next thread that asks for one. This makes them suitable
+/// for indexing into a table of per-thread slots instead of looking up a hash map.
+///
+/// A thread keeps the same ID for as long as it runs, but IDs are not unique over the lifetime of
+/// the process, so two threads that never run at the same time may get the same ID.
+///
+/// # Panics
+///
+/// Panics if called while the current thread's thread-local storage is being torn down.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam_utils::thread;
+///
+/// let id = thread::thread_id();
+/// assert_eq!(id, thread::thread_id());
+///
+/// thread::scope(|s| {
+///     s.spawn(|_| assert_ne!(thread::thread_id(), id));
+/// }).unwrap();
+/// ```
+pub fn thread_id() -> usize {
+    current_index().expect("cannot access a thread ID during or after TLS destruction")
+}
+
+/// Returns a `usize` that identifies the current thread.
+///
+/// Each thread is associated with an 'index'. While there are no particular guarantees, indices
+/// usually tend to be consecutive numbers between 0 and the number of running threads.
+///
+/// Since this function accesses TLS, `None` might be returned if the current thread's TLS is
+/// tearing down.
+#[inline]
+pub(crate) fn current_index() -> Option<usize> {
+    REGISTRATION.try_with(|reg| reg.index).ok()
+}
+
+/// The global registry keeping track of registered threads and indices.
+struct ThreadIndices {
+    /// Mapping from `ThreadId` to thread index.
+    mapping: HashMap<ThreadId, usize>,
+
+    /// A list of free indices.
+    free_list: Vec<usize>,
+
+    /// The next index to allocate if the free list is empty.
+    next_index: usize,
+}
+
+lazy_static! {
+    static ref THREAD_INDICES: Mutex<ThreadIndices> = Mutex::new(ThreadIndices {
+        mapping: HashMap::new(),
+        free_list: Vec::new(),
+        next_index: 0,
+    });
+}
+
+/// A registration of a thread with an index.
+///
+/// When dropped, unregisters the thread and frees the reserved index.
+struct Registration {
+    index: usize,
+    thread_id: ThreadId,
+}
+
+impl Drop for Registration {
+    fn drop(&mut self) {
+        let mut indices = THREAD_INDICES.lock().unwrap();
+        indices.mapping.remove(&self.thread_id);
+        indices.free_list.push(self.index);
+    }
+}
+
+thread_local! {
+    static REGISTRATION: Registration = {
+        let thread_id = thread::current().id();
+        let mut indices = THREAD_INDICES.lock().unwrap();
+
+        let index = match indices.free_list.pop() {
+            Some(i) => i,
+            None => {
+                let i = indices.next_index;
+                indices.next_index += 1;
+                i
+            }
+        };
+        indices.mapping.insert(thread_id, index);
+
+        Registration {
+            index,
+            thread_id,
+        }
+    };
+}
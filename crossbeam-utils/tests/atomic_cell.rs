@@ -19,7 +19,78 @@ fn is_lock_free() {
 
     assert_eq!(AtomicCell::<I16Wrap>::is_lock_free(), cfg!(has_atomic_u16));
 
-    assert_eq!(AtomicCell::<u128>::is_lock_free(), cfg!(has_atomic_u128));
+    assert_eq!(
+        AtomicCell::<u128>::is_lock_free(),
+        cfg!(any(has_atomic_u128, has_cmpxchg16b))
+    );
+}
+
+#[test]
+fn is_lock_free_16_bytes() {
+    #[repr(align(16))]
+    #[derive(Clone, Copy)]
+    struct Aligned(u64, u64);
+    #[derive(Clone, Copy)]
+    struct Unaligned(u64, u64);
+
+    assert_eq!(
+        AtomicCell::<Aligned>::is_lock_free(),
+        cfg!(any(has_atomic_u128, has_cmpxchg16b))
+    );
+    assert!(!AtomicCell::<Unaligned>::is_lock_free());
+
+    // Both kinds of cells hold their values correctly.
+    let a = AtomicCell::new(Aligned(1, 2));
+    let a = a.swap(Aligned(3, 4));
+    assert_eq!((a.0, a.1), (1, 2));
+
+    let u = AtomicCell::new(Unaligned(5, 6));
+    let u = u.swap(Unaligned(7, 8));
+    assert_eq!((u.0, u.1), (5, 6));
+}
+
+#[cfg(any(has_atomic_u128, has_cmpxchg16b))]
+#[test]
+fn u128_ops() {
+    const HIGH: u128 = 1 << 64;
+
+    let a = AtomicCell::new(u64::max_value() as u128);
+    assert_eq!(a.fetch_add(1), u64::max_value() as u128);
+    assert_eq!(a.load(), HIGH);
+    assert_eq!(a.swap(HIGH | 7), HIGH);
+    assert_eq!(a.compare_exchange(7, 0), Err(HIGH | 7));
+    assert_eq!(a.compare_exchange(HIGH | 7, 7), Ok(HIGH | 7));
+    assert_eq!(a.fetch_sub(8), 7);
+    assert_eq!(a.load(), u128::max_value());
+
+    let b = AtomicCell::new(-1i128);
+    assert_eq!(b.fetch_add(1), -1);
+    assert_eq!(b.fetch_xor(-1), 0);
+    assert_eq!(b.load(), -1);
+}
+
+#[cfg(any(has_atomic_u128, has_cmpxchg16b))]
+#[test]
+fn u128_concurrent() {
+    const THREADS: u128 = 4;
+    const STEPS: u128 = 1000;
+    const HIGH: u128 = 1 << 64;
+
+    let a = AtomicCell::new(0u128);
+
+    crossbeam_utils::thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..STEPS {
+                    // Touch both halves so that a torn update would be noticed.
+                    a.fetch_add(HIGH + 1);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(a.load(), THREADS * STEPS * (HIGH + 1));
 }
 
 #[test]
@@ -234,3 +305,82 @@ fn const_atomic_cell_new() {
     CELL.store(1);
     assert_eq!(CELL.load(), 1);
 }
+
+#[test]
+fn fetch_update() {
+    let a = AtomicCell::new(7u8);
+    assert_eq!(a.fetch_update(|_| None), Err(7));
+    assert_eq!(a.fetch_update(|x| x.checked_add(1)), Ok(7));
+    assert_eq!(a.load(), 8);
+
+    // Types without native atomics go through the lock.
+    #[derive(Copy, Clone, Debug, Eq, PartialEq)]
+    struct Wide([u64; 4]);
+
+    let a = AtomicCell::new(Wide([1, 2, 3, 4]));
+    assert!(!AtomicCell::<Wide>::is_lock_free());
+    assert_eq!(
+        a.fetch_update(|Wide(w)| Some(Wide([w[3], w[2], w[1], w[0]]))),
+        Ok(Wide([1, 2, 3, 4]))
+    );
+    assert_eq!(a.load(), Wide([4, 3, 2, 1]));
+}
+
+#[test]
+fn fetch_update_concurrent() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 1000;
+
+    let a = AtomicCell::new((0u64, 0u64));
+
+    crossbeam_utils::thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..COUNT {
+                    a.fetch_update(|(x, y)| Some((x + 1, y + 2))).unwrap();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    let n = (THREADS * COUNT) as u64;
+    assert_eq!(a.load(), (n, 2 * n));
+}
+
+#[test]
+fn take_option() {
+    let a = AtomicCell::new(Some(Box::new(7)));
+    assert_eq!(a.take(), Some(Box::new(7)));
+    assert_eq!(a.take(), None);
+
+    a.store(Some(Box::new(8)));
+    assert_eq!(a.swap(None), Some(Box::new(8)));
+    assert_eq!(a.into_inner(), None);
+}
+
+#[test]
+fn replace_with() {
+    let a = AtomicCell::new(Some(3u8));
+    assert_eq!(a.replace_with(|x| x.map(|x| x * 2)), Some(3));
+    assert_eq!(a.replace_with(|_| None), Some(6));
+    assert_eq!(a.replace_with(|x| x.or(Some(1))), None);
+    assert_eq!(a.load(), Some(1));
+
+    // Claiming a slot only succeeds for one of several threads, even on the lock path.
+    let slot = AtomicCell::new(Some([7u64; 4]));
+    let claimed = AtomicUsize::new(0);
+
+    crossbeam_utils::thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|_| {
+                if slot.replace_with(|_| None).is_some() {
+                    claimed.fetch_add(1, SeqCst);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(claimed.load(SeqCst), 1);
+}
@@ -0,0 +1,55 @@
+use crossbeam_utils::Backoff;
+
+#[test]
+fn default_limits() {
+    let backoff = Backoff::new();
+    for _ in 0..11 {
+        assert!(!backoff.is_completed());
+        backoff.snooze();
+    }
+    assert!(backoff.is_completed());
+
+    backoff.reset();
+    assert!(!backoff.is_completed());
+}
+
+#[test]
+fn custom_limits() {
+    let backoff = Backoff::builder().spin_limit(2).yield_limit(4).build();
+    for _ in 0..5 {
+        assert!(!backoff.is_completed());
+        backoff.snooze();
+    }
+    assert!(backoff.is_completed());
+}
+
+#[test]
+fn spin_never_completes() {
+    let backoff = Backoff::builder().spin_limit(1).yield_limit(3).build();
+    for _ in 0..100 {
+        backoff.spin();
+    }
+    assert!(!backoff.is_completed());
+}
+
+#[test]
+fn jitter() {
+    let builder = Backoff::builder()
+        .spin_limit(12)
+        .yield_limit(16)
+        .jitter(true);
+
+    let backoff = builder.build();
+    for _ in 0..20 {
+        backoff.spin();
+    }
+    while !backoff.is_completed() {
+        backoff.snooze();
+    }
+}
+
+#[test]
+#[should_panic(expected = "spin limit must be at most 31")]
+fn spin_limit_too_large() {
+    Backoff::builder().spin_limit(32);
+}
@@ -0,0 +1,74 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::sleep;
+use std::time::Duration;
+
+use crossbeam_utils::sync::Barrier;
+use crossbeam_utils::thread;
+
+const THREADS: usize = 8;
+const ROUNDS: usize = 100;
+
+#[test]
+fn single_thread() {
+    let barrier = Barrier::new(1);
+    for i in 0..10 {
+        let res = barrier.wait();
+        assert!(res.is_leader());
+        assert_eq!(res.phase(), i);
+    }
+    assert_eq!(barrier.phase(), 10);
+}
+
+#[test]
+fn zero_threads() {
+    let barrier = Barrier::new(0);
+    assert_eq!(barrier.num_threads(), 1);
+    assert!(barrier.wait().is_leader());
+}
+
+#[test]
+fn lockstep() {
+    let barrier = Barrier::new(THREADS);
+    let arrived = AtomicUsize::new(0);
+    let leaders = AtomicUsize::new(0);
+
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|_| {
+                for round in 0..ROUNDS {
+                    arrived.fetch_add(1, Ordering::SeqCst);
+
+                    let res = barrier.wait();
+                    assert_eq!(res.phase(), 2 * round);
+                    // Nobody can arrive for the next round before everyone left this one.
+                    assert!(arrived.load(Ordering::SeqCst) >= (round + 1) * THREADS);
+                    if res.is_leader() {
+                        leaders.fetch_add(1, Ordering::SeqCst);
+                    }
+
+                    barrier.wait();
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(leaders.load(Ordering::SeqCst), ROUNDS);
+    assert_eq!(barrier.phase(), 2 * ROUNDS);
+}
+
+#[test]
+fn blocking() {
+    let barrier = Barrier::new(2);
+
+    thread::scope(|s| {
+        s.spawn(|_| {
+            // Make the other thread give up spinning and block.
+            sleep(Duration::from_millis(100));
+            barrier.wait();
+        });
+
+        assert_eq!(barrier.wait().phase(), 0);
+    })
+    .unwrap();
+}
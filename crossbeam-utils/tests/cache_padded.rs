@@ -110,3 +110,32 @@ fn runs_custom_clone() {
 
     assert_eq!(count.get(), 1);
 }
+
+#[test]
+fn custom_alignment() {
+    use crossbeam_utils::cache_padded::{Align128, Align256, Align32, Align64};
+
+    assert_eq!(mem::align_of::<CachePadded<u8, Align32>>(), 32);
+    assert_eq!(mem::align_of::<CachePadded<u8, Align64>>(), 64);
+    assert_eq!(mem::align_of::<CachePadded<u8, Align128>>(), 128);
+    assert_eq!(mem::align_of::<CachePadded<u8, Align256>>(), 256);
+
+    assert_eq!(mem::size_of::<CachePadded<u8, Align32>>(), 32);
+    assert_eq!(mem::size_of::<CachePadded<[u8; 33], Align32>>(), 64);
+
+    // The alignment of the value wins if it is larger.
+    assert_eq!(
+        mem::align_of::<CachePadded<CachePadded<u8, Align64>, Align32>>(),
+        64
+    );
+
+    let arr = [
+        CachePadded::<u8, Align256>::with_alignment(17),
+        CachePadded::from(37),
+    ];
+    let a = &*arr[0] as *const u8;
+    let b = &*arr[1] as *const u8;
+    assert_eq!(a as usize % 256, 0);
+    assert_eq!(unsafe { a.add(256) }, b);
+    assert_eq!(arr[1].into_inner(), 37);
+}
@@ -0,0 +1,73 @@
+use std::thread::sleep;
+use std::time::Duration;
+
+use crossbeam_utils::sync::CountdownLatch;
+use crossbeam_utils::thread;
+
+const THREADS: usize = 10;
+
+#[test]
+fn zero() {
+    let latch = CountdownLatch::new(0);
+    latch.wait();
+    assert!(latch.wait_timeout(Duration::from_millis(0)));
+    latch.count_down();
+    assert_eq!(latch.count(), 0);
+}
+
+#[test]
+fn wait() {
+    let latch = CountdownLatch::new(THREADS);
+
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|_| {
+                sleep(Duration::from_millis(10));
+                latch.count_down();
+            });
+        }
+
+        latch.wait();
+        assert_eq!(latch.count(), 0);
+    })
+    .unwrap();
+}
+
+#[test]
+fn many_waiters() {
+    let latch = CountdownLatch::new(1);
+
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|_| latch.wait());
+        }
+
+        sleep(Duration::from_millis(50));
+        latch.count_down();
+    })
+    .unwrap();
+}
+
+#[test]
+fn wait_timeout() {
+    let latch = CountdownLatch::new(2);
+
+    thread::scope(|s| {
+        s.spawn(|_| {
+            latch.count_down();
+            sleep(Duration::from_millis(500));
+            latch.count_down();
+        });
+
+        assert!(!latch.wait_timeout(Duration::from_millis(100)));
+        assert_eq!(latch.count(), 1);
+        assert!(latch.wait_timeout(Duration::from_secs(10)));
+    })
+    .unwrap();
+}
+
+#[test]
+fn debug() {
+    let latch = CountdownLatch::new(3);
+    assert_eq!(format!("{:?}", latch), "CountdownLatch { count: 3 }");
+}
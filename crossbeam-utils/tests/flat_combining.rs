@@ -0,0 +1,111 @@
+use std::collections::BinaryHeap;
+use std::panic::{self, AssertUnwindSafe};
+
+use crossbeam_utils::sync::FlatCombining;
+use crossbeam_utils::thread;
+
+const THREADS: usize = 4;
+const STEPS: usize = 10_000;
+
+#[test]
+fn smoke() {
+    let fc = FlatCombining::new(1);
+    assert_eq!(fc.apply(|x| *x), 1);
+    fc.apply(|x| *x += 1);
+    assert_eq!(fc.apply(|x| *x), 2);
+    assert_eq!(fc.into_inner(), 2);
+}
+
+#[test]
+fn get_mut() {
+    let mut fc = FlatCombining::new(vec![1]);
+    fc.get_mut().push(2);
+    assert_eq!(fc.apply(|v| v.clone()), [1, 2]);
+}
+
+#[test]
+#[should_panic(expected = "number of slots must be positive")]
+fn zero_slots() {
+    FlatCombining::with_slots((), 0);
+}
+
+#[test]
+fn counter() {
+    let fc = FlatCombining::new(0);
+
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..STEPS {
+                    fc.apply(|x| *x += 1);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(fc.into_inner(), THREADS * STEPS);
+}
+
+#[test]
+fn few_slots() {
+    // With fewer slots than threads, some operations are applied directly under the lock.
+    let fc = FlatCombining::with_slots(Vec::new(), 1);
+
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let fc = &fc;
+            scope.spawn(move |_| {
+                for i in 0..STEPS {
+                    fc.apply(|v| v.push(t * STEPS + i));
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    let mut v = fc.into_inner();
+    v.sort();
+    assert_eq!(v, (0..THREADS * STEPS).collect::<Vec<_>>());
+}
+
+#[test]
+fn results() {
+    let fc = FlatCombining::new(BinaryHeap::new());
+
+    thread::scope(|scope| {
+        for t in 0..THREADS {
+            let fc = &fc;
+            scope.spawn(move |_| {
+                for i in 0..STEPS {
+                    fc.apply(|h| h.push(t * STEPS + i));
+                    // Every thread pops as many elements as it pushes, so the heap is never
+                    // empty when it pops.
+                    assert!(fc.apply(|h| h.pop()).is_some());
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert!(fc.into_inner().is_empty());
+}
+
+#[test]
+fn panics() {
+    let fc = FlatCombining::new(0);
+
+    let res = panic::catch_unwind(AssertUnwindSafe(|| fc.apply(|_| panic!("boom"))));
+    let payload = res.unwrap_err();
+    assert_eq!(payload.downcast_ref::<&str>(), Some(&"boom"));
+
+    // The wrapper keeps working after a panic.
+    fc.apply(|x| *x += 1);
+    assert_eq!(fc.into_inner(), 1);
+}
+
+#[test]
+fn debug() {
+    let fc = FlatCombining::new(5);
+    assert_eq!(format!("{:?}", fc), "FlatCombining { .. }");
+}
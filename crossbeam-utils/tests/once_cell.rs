@@ -0,0 +1,125 @@
+use std::panic;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::sleep;
+use std::time::Duration;
+
+use crossbeam_utils::sync::{Lazy, OnceCell};
+use crossbeam_utils::thread;
+
+const THREADS: usize = 10;
+
+#[test]
+fn smoke() {
+    let cell = OnceCell::new();
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.get_or_init(|| 1), &1);
+    assert_eq!(cell.get_or_init(|| 2), &1);
+    assert_eq!(cell.set(3), Err(3));
+    assert_eq!(cell.into_inner(), Some(1));
+}
+
+#[test]
+fn init_once() {
+    let cell = OnceCell::new();
+    let calls = AtomicUsize::new(0);
+
+    thread::scope(|s| {
+        for i in 0..THREADS {
+            let cell = &cell;
+            let calls = &calls;
+            s.spawn(move |_| {
+                let value = cell.get_or_init(|| {
+                    calls.fetch_add(1, Ordering::SeqCst);
+                    // Give the other threads time to start waiting.
+                    sleep(Duration::from_millis(100));
+                    i
+                });
+                assert!(*value < THREADS);
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+    assert!(cell.get().is_some());
+}
+
+#[test]
+fn panic_then_retry() {
+    let cell = OnceCell::new();
+
+    let res = panic::catch_unwind(|| {
+        cell.get_or_init(|| -> usize { panic!("oops") });
+    });
+    assert!(res.is_err());
+    assert_eq!(cell.get(), None);
+
+    assert_eq!(cell.get_or_init(|| 7), &7);
+}
+
+#[test]
+fn waiters_take_over_after_panic() {
+    let cell = OnceCell::new();
+
+    thread::scope(|s| {
+        let h = s.spawn(|_| {
+            cell.get_or_init(|| -> usize {
+                sleep(Duration::from_millis(100));
+                panic!("oops")
+            });
+        });
+
+        sleep(Duration::from_millis(50));
+        assert_eq!(cell.get_or_init(|| 2), &2);
+        assert!(h.join().is_err());
+    })
+    .unwrap();
+}
+
+#[test]
+fn try_init() {
+    let cell = OnceCell::new();
+    assert_eq!(cell.get_or_try_init(|| Err(())), Err(()));
+    assert_eq!(cell.get(), None);
+    assert_eq!(cell.get_or_try_init(|| Ok::<_, ()>(5)), Ok(&5));
+}
+
+#[test]
+fn drops_value() {
+    static DROPS: AtomicUsize = AtomicUsize::new(0);
+
+    struct Elem;
+
+    impl Drop for Elem {
+        fn drop(&mut self) {
+            DROPS.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    let cell = OnceCell::new();
+    drop(cell.set(Elem));
+    assert!(cell.set(Elem).is_err());
+    assert_eq!(DROPS.load(Ordering::SeqCst), 1);
+
+    drop(cell);
+    assert_eq!(DROPS.load(Ordering::SeqCst), 2);
+}
+
+#[test]
+fn lazy() {
+    static CALLS: AtomicUsize = AtomicUsize::new(0);
+    static VALUE: Lazy<usize> = Lazy::new(|| {
+        CALLS.fetch_add(1, Ordering::SeqCst);
+        42
+    });
+
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|_| assert_eq!(*VALUE, 42));
+        }
+    })
+    .unwrap();
+
+    assert_eq!(CALLS.load(Ordering::SeqCst), 1);
+    assert_eq!(format!("{:?}", VALUE), "Lazy { value: Some(42) }");
+}
@@ -1,5 +1,5 @@
 use std::thread::sleep;
-use std::time::Duration;
+use std::time::{Duration, Instant};
 use std::u32;
 
 use crossbeam_utils::sync::Parker;
@@ -39,3 +39,79 @@ fn park_timeout_unpark_called_other_thread() {
         .unwrap();
     }
 }
+
+#[test]
+fn park_deadline_in_past() {
+    let p = Parker::new();
+    let past = Instant::now();
+    sleep(Duration::from_millis(1));
+
+    // Returns immediately without a token.
+    p.park_deadline(past);
+
+    // Consumes the token if there is one.
+    p.unparker().unpark();
+    p.park_deadline(past);
+
+    let start = Instant::now();
+    p.park_deadline(Instant::now() + Duration::from_millis(50));
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn park_deadline_unpark_called_other_thread() {
+    for _ in 0..10 {
+        let p = Parker::new();
+        let u = p.unparker().clone();
+
+        thread::scope(|scope| {
+            scope.spawn(move |_| {
+                sleep(Duration::from_millis(50));
+                u.unpark();
+            });
+
+            p.park_deadline(Instant::now() + Duration::from_millis(u32::MAX as u64))
+        })
+        .unwrap();
+    }
+}
+
+#[test]
+fn counting_keeps_all_tokens() {
+    let p = Parker::new_counting();
+    for _ in 0..10 {
+        p.unparker().unpark();
+    }
+    for _ in 0..10 {
+        p.park();
+    }
+
+    // All tokens have been consumed.
+    let start = Instant::now();
+    p.park_timeout(Duration::from_millis(50));
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn counting_unpark_called_other_threads() {
+    const THREADS: usize = 4;
+    const UNPARKS: usize = 100;
+
+    let p = Parker::new_counting();
+
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            let u = p.unparker().clone();
+            scope.spawn(move |_| {
+                for _ in 0..UNPARKS {
+                    u.unpark();
+                }
+            });
+        }
+
+        for _ in 0..THREADS * UNPARKS {
+            p.park();
+        }
+    })
+    .unwrap();
+}
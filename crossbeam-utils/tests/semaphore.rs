@@ -0,0 +1,110 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread::sleep;
+use std::time::Duration;
+
+use crossbeam_utils::sync::Semaphore;
+use crossbeam_utils::thread;
+
+const THREADS: usize = 10;
+
+#[test]
+fn smoke() {
+    let sem = Semaphore::new(2);
+    let a = sem.acquire();
+    let b = sem.try_acquire().unwrap();
+    assert!(sem.try_acquire().is_none());
+    assert_eq!(sem.available_permits(), 0);
+
+    drop(a);
+    assert_eq!(sem.available_permits(), 1);
+    drop(b);
+    assert_eq!(sem.available_permits(), 2);
+}
+
+#[test]
+fn limits_concurrency() {
+    let sem = Semaphore::new(3);
+    let running = AtomicUsize::new(0);
+
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|_| {
+                for _ in 0..10 {
+                    let _permit = sem.acquire();
+                    let now = running.fetch_add(1, Ordering::SeqCst) + 1;
+                    assert!(now <= 3);
+                    sleep(Duration::from_millis(1));
+                    running.fetch_sub(1, Ordering::SeqCst);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(sem.available_permits(), 3);
+}
+
+#[test]
+fn acquire_timeout() {
+    let sem = Semaphore::new(1);
+
+    thread::scope(|s| {
+        let permit = sem.acquire();
+        s.spawn(move |_| {
+            sleep(Duration::from_millis(200));
+            drop(permit);
+        });
+
+        assert!(sem.acquire_timeout(Duration::from_millis(50)).is_none());
+        assert!(sem.acquire_timeout(Duration::from_secs(10)).is_some());
+    })
+    .unwrap();
+
+    // A timed out waiter must not keep a permit.
+    assert_eq!(sem.available_permits(), 1);
+}
+
+#[test]
+fn fifo() {
+    let sem = Semaphore::new(0);
+    let order = std::sync::Mutex::new(Vec::new());
+
+    thread::scope(|s| {
+        for i in 0..3 {
+            let sem = &sem;
+            let order = &order;
+            s.spawn(move |_| {
+                sem.acquire().forget();
+                order.lock().unwrap().push(i);
+            });
+            // Let the thread start waiting before spawning the next one.
+            sleep(Duration::from_millis(50));
+        }
+
+        assert!(sem.try_acquire().is_none());
+        sem.add_permits(1);
+        for _ in 0..2 {
+            sleep(Duration::from_millis(50));
+            sem.add_permits(1);
+        }
+    })
+    .unwrap();
+
+    assert_eq!(order.into_inner().unwrap(), [0, 1, 2]);
+    assert_eq!(sem.available_permits(), 0);
+}
+
+#[test]
+fn add_permits() {
+    let sem = Semaphore::new(0);
+
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|_| sem.acquire().forget());
+        }
+        sem.add_permits(THREADS + 1);
+    })
+    .unwrap();
+
+    assert_eq!(sem.available_permits(), 1);
+}
@@ -0,0 +1,86 @@
+use crossbeam_utils::sync::SeqLock;
+use crossbeam_utils::thread;
+
+const STEPS: usize = 10_000;
+
+#[test]
+fn smoke() {
+    let lock = SeqLock::new(1);
+    assert_eq!(lock.read(), 1);
+
+    lock.write(2);
+    assert_eq!(lock.try_read(), Some(2));
+
+    {
+        let mut guard = lock.lock();
+        *guard += 1;
+        assert!(lock.try_lock().is_none());
+        assert_eq!(lock.try_read(), None);
+    }
+
+    assert_eq!(lock.read(), 3);
+    assert_eq!(lock.into_inner(), 3);
+}
+
+#[test]
+fn get_mut() {
+    let mut lock = SeqLock::new(0);
+    *lock.get_mut() = 4;
+    assert_eq!(lock.read(), 4);
+}
+
+#[test]
+fn debug() {
+    let lock = SeqLock::new(5);
+    assert_eq!(format!("{:?}", lock), "SeqLock { data: 5 }");
+
+    let _guard = lock.lock();
+    assert_eq!(format!("{:?}", lock), "SeqLock { data: <locked> }");
+}
+
+#[test]
+fn no_torn_reads() {
+    let lock = SeqLock::new([0usize; 8]);
+
+    thread::scope(|s| {
+        s.spawn(|_| {
+            for i in 1..=STEPS {
+                lock.write([i; 8]);
+            }
+        });
+
+        for _ in 0..2 {
+            s.spawn(|_| {
+                let mut last = 0;
+                loop {
+                    let value = lock.read();
+                    assert!(value.iter().all(|&x| x == value[0]));
+                    assert!(value[0] >= last);
+                    last = value[0];
+                    if last == STEPS {
+                        break;
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+}
+
+#[test]
+fn writers() {
+    let lock = SeqLock::new(0);
+
+    thread::scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|_| {
+                for _ in 0..STEPS {
+                    *lock.lock() += 1;
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(lock.read(), 4 * STEPS);
+}
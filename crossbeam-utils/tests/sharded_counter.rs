@@ -0,0 +1,84 @@
+use crossbeam_utils::sync::ShardedCounter;
+use crossbeam_utils::thread;
+
+const THREADS: usize = 8;
+const STEPS: usize = 10_000;
+
+#[test]
+fn smoke() {
+    let counter = ShardedCounter::new();
+    assert_eq!(counter.sum(), 0);
+
+    counter.increment();
+    counter.add(10);
+    counter.decrement();
+    counter.sub(3);
+    assert_eq!(counter.sum(), 7);
+
+    assert_eq!(counter.reset(), 7);
+    assert_eq!(counter.sum(), 0);
+}
+
+#[test]
+fn wrapping() {
+    let counter = ShardedCounter::with_shards(3);
+    counter.sub(5);
+    assert_eq!(counter.sum() as isize, -5);
+    counter.add(8);
+    assert_eq!(counter.sum(), 3);
+}
+
+#[test]
+#[should_panic(expected = "number of shards must be non-zero")]
+fn zero_shards() {
+    ShardedCounter::with_shards(0);
+}
+
+#[test]
+fn concurrent() {
+    let counter = ShardedCounter::new();
+
+    thread::scope(|scope| {
+        for i in 0..THREADS {
+            let counter = &counter;
+            scope.spawn(move |_| {
+                for _ in 0..STEPS {
+                    if i % 2 == 0 {
+                        counter.add(3);
+                    } else {
+                        counter.decrement();
+                    }
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(counter.sum(), THREADS / 2 * STEPS * 2);
+}
+
+#[test]
+fn concurrent_reset() {
+    let counter = ShardedCounter::new();
+    let mut total = 0;
+
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|_| {
+                for _ in 0..STEPS {
+                    counter.increment();
+                }
+            });
+        }
+
+        // Resetting while other threads are incrementing must not lose any updates.
+        for _ in 0..100 {
+            total += counter.reset();
+            std::thread::yield_now();
+        }
+    })
+    .unwrap();
+
+    total += counter.reset();
+    assert_eq!(total, THREADS * STEPS);
+}
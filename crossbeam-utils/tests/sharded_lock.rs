@@ -2,8 +2,9 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use std::sync::mpsc::channel;
 use std::sync::{Arc, TryLockError};
 use std::thread;
+use std::time::{Duration, Instant};
 
-use crossbeam_utils::sync::ShardedLock;
+use crossbeam_utils::sync::{ShardedLock, ShardedLockUpgradableReadGuard, ShardedLockWriteGuard};
 use rand::Rng;
 
 #[derive(Eq, PartialEq, Debug)]
@@ -183,6 +184,173 @@ fn try_write() {
     drop(read_guard);
 }
 
+#[test]
+fn read_upgradable() {
+    let lock = ShardedLock::new(0isize);
+    let upgradable = lock.read_upgradable().unwrap();
+
+    // Readers may coexist with an upgradable reader, but writers and upgradable readers may not.
+    drop(lock.try_read().unwrap());
+    assert!(match lock.try_write() {
+        Err(TryLockError::WouldBlock) => true,
+        _ => false,
+    });
+    assert!(match lock.try_read_upgradable() {
+        Err(TryLockError::WouldBlock) => true,
+        _ => false,
+    });
+
+    let mut write = ShardedLockUpgradableReadGuard::upgrade(upgradable).unwrap();
+    *write += 1;
+    assert!(lock.try_read().is_err());
+    drop(write);
+
+    assert_eq!(*lock.try_read_upgradable().unwrap(), 1);
+}
+
+#[test]
+fn upgrade_waits_for_readers() {
+    let lock = Arc::new(ShardedLock::new(0));
+    let reader = lock.read().unwrap();
+
+    let lock2 = lock.clone();
+    let handle = thread::spawn(move || {
+        let upgradable = lock2.read_upgradable().unwrap();
+        let mut write = ShardedLockUpgradableReadGuard::upgrade(upgradable).unwrap();
+        *write += 1;
+    });
+
+    thread::sleep(Duration::from_millis(50));
+    assert_eq!(*reader, 0);
+    drop(reader);
+
+    handle.join().unwrap();
+    assert_eq!(*lock.read().unwrap(), 1);
+}
+
+#[test]
+fn upgrade_is_atomic() {
+    const THREADS: usize = 4;
+    const STEPS: usize = 100;
+
+    let lock = Arc::new(ShardedLock::new(0));
+
+    let handles: Vec<_> = (0..THREADS)
+        .map(|i| {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                for _ in 0..STEPS {
+                    if i % 2 == 0 {
+                        // Increment the value, checking that no writer slipped in between.
+                        let upgradable = lock.read_upgradable().unwrap();
+                        let n = *upgradable;
+                        let mut write =
+                            ShardedLockUpgradableReadGuard::upgrade(upgradable).unwrap();
+                        assert_eq!(*write, n);
+                        *write += 1;
+                    } else {
+                        *lock.write().unwrap() += 1;
+                    }
+                }
+            })
+        })
+        .collect();
+
+    for h in handles {
+        h.join().unwrap();
+    }
+    assert_eq!(*lock.read().unwrap(), THREADS * STEPS);
+}
+
+#[test]
+fn try_read_for() {
+    let lock = Arc::new(ShardedLock::new(0));
+    let write = lock.write().unwrap();
+
+    let start = Instant::now();
+    assert!(match lock.try_read_for(Duration::from_millis(50)) {
+        Err(TryLockError::WouldBlock) => true,
+        _ => false,
+    });
+    assert!(start.elapsed() >= Duration::from_millis(50));
+
+    let lock2 = lock.clone();
+    let handle = thread::spawn(move || *lock2.try_read_for(Duration::from_secs(10)).unwrap());
+    thread::sleep(Duration::from_millis(50));
+    drop(write);
+    assert_eq!(handle.join().unwrap(), 0);
+}
+
+#[test]
+fn try_write_until() {
+    let lock = Arc::new(ShardedLock::new(0));
+    let read = lock.read().unwrap();
+
+    let deadline = Instant::now() + Duration::from_millis(50);
+    assert!(lock.try_write_until(deadline).is_err());
+    assert!(Instant::now() >= deadline);
+
+    // A deadline in the past still tries once.
+    drop(read);
+    *lock.try_write_until(deadline).unwrap() += 1;
+
+    let read = lock.read().unwrap();
+    let lock2 = lock.clone();
+    let handle = thread::spawn(move || {
+        *lock2.try_write_for(Duration::from_secs(10)).unwrap() += 1;
+    });
+    thread::sleep(Duration::from_millis(50));
+    drop(read);
+    handle.join().unwrap();
+    assert_eq!(*lock.read().unwrap(), 2);
+}
+
+#[test]
+fn downgrade() {
+    let lock = ShardedLock::new(0);
+
+    let mut write = lock.write().unwrap();
+    *write = 1;
+    let read = ShardedLockWriteGuard::downgrade(write);
+    assert_eq!(*read, 1);
+
+    // Other readers may come in, but writers may not.
+    assert_eq!(*lock.try_read().unwrap(), 1);
+    assert!(lock.try_write().is_err());
+    assert!(lock.try_read_upgradable().is_ok());
+
+    drop(read);
+    *lock.try_write().unwrap() = 2;
+}
+
+#[test]
+fn downgrade_keeps_writers_out() {
+    const THREADS: usize = 4;
+    const STEPS: usize = 100;
+
+    let lock = Arc::new(ShardedLock::new(0));
+
+    let handles: Vec<_> = (0..THREADS)
+        .map(|_| {
+            let lock = lock.clone();
+            thread::spawn(move || {
+                for _ in 0..STEPS {
+                    let mut write = lock.write().unwrap();
+                    *write += 1;
+                    let n = *write;
+                    let read = ShardedLockWriteGuard::downgrade(write);
+                    assert_eq!(*read, n);
+                }
+            })
+        })
+        .collect();
+
+    for h in handles {
+        h.join().unwrap();
+    }
+    assert_eq!(*lock.read().unwrap(), THREADS * STEPS);
+}
+
 #[test]
 fn test_into_inner() {
     let m = ShardedLock::new(NonCopy(10));
@@ -0,0 +1,61 @@
+use crossbeam_utils::sync::SpinLock;
+use crossbeam_utils::thread;
+
+const THREADS: usize = 4;
+const STEPS: usize = 10_000;
+
+#[test]
+fn smoke() {
+    let lock = SpinLock::new(1);
+    {
+        let mut guard = lock.lock();
+        assert!(lock.is_locked());
+        assert!(lock.try_lock().is_none());
+        *guard += 1;
+    }
+    assert!(!lock.is_locked());
+    assert_eq!(*lock.try_lock().unwrap(), 2);
+    assert_eq!(lock.into_inner(), 2);
+}
+
+#[test]
+fn get_mut() {
+    let mut lock = SpinLock::new(vec![1]);
+    lock.get_mut().push(2);
+    assert_eq!(*lock.lock(), [1, 2]);
+}
+
+#[test]
+fn unsized_value() {
+    let lock: &SpinLock<[i32]> = &SpinLock::new([1, 2, 3]);
+    lock.lock()[0] = 4;
+    assert_eq!(*lock.lock(), [4, 2, 3]);
+}
+
+#[test]
+fn debug() {
+    let lock = SpinLock::new(5);
+    assert_eq!(format!("{:?}", lock), "SpinLock { data: 5 }");
+
+    let guard = lock.lock();
+    assert_eq!(format!("{:?}", lock), "SpinLock { data: <locked> }");
+    assert_eq!(format!("{}", guard), "5");
+}
+
+#[test]
+fn contended() {
+    let lock = SpinLock::new(0);
+
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|_| {
+                for _ in 0..STEPS {
+                    *lock.lock() += 1;
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(lock.into_inner(), THREADS * STEPS);
+}
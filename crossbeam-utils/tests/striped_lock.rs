@@ -0,0 +1,78 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crossbeam_utils::sync::StripedLock;
+use crossbeam_utils::thread;
+
+const THREADS: usize = 8;
+const KEYS: usize = 4;
+const STEPS: usize = 1000;
+
+#[test]
+fn stripes() {
+    assert_eq!(StripedLock::<u64>::with_stripes(1).num_stripes(), 1);
+    assert_eq!(StripedLock::<u64>::with_stripes(5).num_stripes(), 8);
+    assert_eq!(StripedLock::<u64>::new().num_stripes(), 64);
+}
+
+#[test]
+#[should_panic(expected = "number of stripes must be positive")]
+fn zero_stripes() {
+    StripedLock::<u64>::with_stripes(0);
+}
+
+#[test]
+fn same_key_excludes() {
+    let locks = StripedLock::new();
+    let guard = locks.lock("key");
+    assert!(locks.try_lock("key").is_none());
+    drop(guard);
+    assert!(locks.try_lock("key").is_some());
+}
+
+#[test]
+fn mutual_exclusion() {
+    let locks = StripedLock::new();
+    let counters: Vec<AtomicUsize> = (0..KEYS).map(|_| AtomicUsize::new(0)).collect();
+
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|_| {
+                for i in 0..STEPS {
+                    let key = i % KEYS;
+                    let _guard = locks.lock(&key);
+                    // A racy read-modify-write, which is only correct under the lock.
+                    let v = counters[key].load(Ordering::Relaxed);
+                    counters[key].store(v + 1, Ordering::Relaxed);
+                }
+            });
+        }
+    })
+    .unwrap();
+
+    for c in &counters {
+        assert_eq!(c.load(Ordering::Relaxed), THREADS * STEPS / KEYS);
+    }
+}
+
+#[test]
+fn lock_many() {
+    // With a single stripe every key collides, which must not deadlock.
+    let locks = StripedLock::with_stripes(1);
+    let guards = locks.lock_many(&[&1, &2, &3]);
+    assert_eq!(guards.len(), 1);
+    assert!(locks.try_lock(&4).is_none());
+    drop(guards);
+
+    let locks = StripedLock::<u32>::with_stripes(16);
+    thread::scope(|s| {
+        for t in 0..THREADS as u32 {
+            let locks = &locks;
+            s.spawn(move |_| {
+                for i in 0..STEPS as u32 {
+                    let _guards = locks.lock_many(&[&(i + t), &i, &(i * 7)]);
+                }
+            });
+        }
+    })
+    .unwrap();
+}
@@ -1,9 +1,11 @@
 use std::any::Any;
+use std::panic;
 use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Barrier, Mutex};
 use std::thread::sleep;
 use std::time::Duration;
 
-use crossbeam_utils::thread;
+use crossbeam_utils::thread::{self, PanicPolicy};
 
 const THREADS: usize = 10;
 const SMALL_STACK_SIZE: usize = 20;
@@ -213,3 +215,146 @@ fn as_raw_handle() {
     })
     .unwrap();
 }
+
+#[test]
+fn scope_map() {
+    let items: Vec<usize> = (0..THREADS).collect();
+    let counter = AtomicUsize::new(0);
+
+    let results = thread::scope_map(&items, |&i| {
+        counter.fetch_add(1, Ordering::Relaxed);
+        i * 2
+    })
+    .unwrap();
+
+    assert_eq!(results, (0..THREADS).map(|i| i * 2).collect::<Vec<_>>());
+    assert_eq!(THREADS, counter.load(Ordering::Relaxed));
+}
+
+#[test]
+fn scope_map_panic() {
+    let result = thread::scope_map(0..THREADS, |i| {
+        if i % 3 == 0 {
+            panic!("deliberate panic");
+        }
+        i
+    });
+
+    let err = result.unwrap_err();
+    let vec = err
+        .downcast_ref::<Vec<Box<dyn Any + Send + 'static>>>()
+        .unwrap();
+    assert_eq!(4, vec.len());
+}
+
+#[test]
+fn cancel() {
+    let counter = AtomicUsize::new(0);
+    thread::scope(|scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|scope| {
+                while !scope.is_cancelled() {
+                    std::thread::yield_now();
+                }
+                counter.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        scope.cancel();
+    })
+    .unwrap();
+
+    assert_eq!(THREADS, counter.load(Ordering::Relaxed));
+}
+
+#[test]
+fn panic_policy_collect() {
+    let result = thread::scope_with_policy(PanicPolicy::Collect, |scope| {
+        scope.spawn(|_| panic!("deliberate panic"));
+        sleep(Duration::from_millis(100));
+        assert!(!scope.is_cancelled());
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn panic_policy_cancel() {
+    let counter = AtomicUsize::new(0);
+    let result = thread::scope_with_policy(PanicPolicy::Cancel, |scope| {
+        for _ in 0..THREADS {
+            scope.spawn(|scope| {
+                while !scope.is_cancelled() {
+                    std::thread::yield_now();
+                }
+                counter.fetch_add(1, Ordering::Relaxed);
+            });
+        }
+        scope.spawn(|scope| {
+            // Panics in nested threads cancel the whole scope too.
+            scope.spawn(|_| panic!("deliberate panic"));
+        });
+    });
+
+    assert_eq!(THREADS, counter.load(Ordering::Relaxed));
+    let err = result.unwrap_err();
+    let vec = err
+        .downcast_ref::<Vec<Box<dyn Any + Send + 'static>>>()
+        .unwrap();
+    assert_eq!(1, vec.len());
+}
+
+#[test]
+fn panic_policy_resume() {
+    let result = panic::catch_unwind(|| {
+        thread::scope_with_policy(PanicPolicy::Resume, |scope| {
+            scope.spawn(|scope| {
+                while !scope.is_cancelled() {
+                    std::thread::yield_now();
+                }
+            });
+            scope.spawn(|_| panic!("deliberate panic"));
+        })
+    });
+
+    let err = result.unwrap_err();
+    assert_eq!(*err.downcast_ref::<&str>().unwrap(), "deliberate panic");
+}
+
+#[test]
+fn panic_in_scope_cancels() {
+    let result = panic::catch_unwind(|| {
+        thread::scope(|scope| {
+            scope.spawn(|scope| {
+                while !scope.is_cancelled() {
+                    std::thread::yield_now();
+                }
+            });
+            panic!("deliberate panic");
+        })
+    });
+    assert!(result.is_err());
+}
+
+#[test]
+fn thread_id_distinct() {
+    let barrier = Barrier::new(THREADS);
+    let ids = Mutex::new(Vec::new());
+
+    thread::scope(|s| {
+        for _ in 0..THREADS {
+            s.spawn(|_| {
+                let id = thread::thread_id();
+                ids.lock().unwrap().push(id);
+
+                // Keep every thread alive until all of them have an ID.
+                barrier.wait();
+                assert_eq!(thread::thread_id(), id);
+            });
+        }
+    })
+    .unwrap();
+
+    let mut ids = ids.into_inner().unwrap();
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), THREADS);
+}
@@ -62,3 +62,44 @@ fn wait_and_drop() {
         rx.try_recv().unwrap();
     }
 }
+
+#[test]
+fn wait_timeout() {
+    let wg = WaitGroup::new();
+
+    for _ in 0..THREADS {
+        let wg = wg.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(10));
+            drop(wg);
+        });
+    }
+
+    assert!(wg.wait_timeout(Duration::from_secs(10)));
+    assert_eq!(wg.count(), 1);
+}
+
+#[test]
+fn wait_timeout_stragglers() {
+    let wg = WaitGroup::new();
+
+    let stragglers: Vec<_> = (0..3)
+        .map(|_| {
+            let wg = wg.clone();
+            let (tx, rx) = mpsc::channel::<()>();
+            thread::spawn(move || {
+                // Block until the sender is dropped.
+                let _ = rx.recv();
+                drop(wg);
+            });
+            tx
+        })
+        .collect();
+
+    assert!(!wg.wait_timeout(Duration::from_millis(100)));
+    assert_eq!(wg.count(), 4);
+
+    drop(stragglers);
+    assert!(wg.wait_timeout(Duration::from_secs(10)));
+    assert_eq!(wg.count(), 1);
+}
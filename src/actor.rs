@@ -0,0 +1,447 @@
+//! Mailboxes and handler threads built on channels.
+//!
+//! An actor is a thread that owns some state and handles messages from its [`Mailbox`] one at a
+//! time. Other threads talk to it through the mailbox's [`Sender`]s.
+//!
+//! An actor runs until all senders are dropped, or until its [`Actor`] handle is stopped or
+//! dropped. Handles always join the actor's thread, so an actor never outlives its handle. If the
+//! handler panics, the actor exits and a [`Panicked`] notification can be delivered on a channel
+//! chosen with [`Builder::notify_panics`].
+//!
+//! Actors can run on a dedicated thread with [`spawn`], or on a scoped thread with
+//! [`spawn_scoped`], in which case the handler can borrow local variables from the stack.
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam::actor::{self, Mailbox};
+//! use crossbeam::channel;
+//!
+//! enum Message {
+//!     Add(i32),
+//!     Get(channel::Sender<i32>),
+//! }
+//!
+//! let mailbox = Mailbox::unbounded();
+//! let s = mailbox.sender();
+//!
+//! let mut total = 0;
+//! let actor = actor::spawn(mailbox, move |msg| match msg {
+//!     Message::Add(x) => total += x,
+//!     Message::Get(reply) => reply.send(total).unwrap(),
+//! });
+//!
+//! s.send(Message::Add(2)).unwrap();
+//! s.send(Message::Add(3)).unwrap();
+//!
+//! let (reply, r) = channel::bounded(1);
+//! s.send(Message::Get(reply)).unwrap();
+//! assert_eq!(r.recv(), Ok(5));
+//!
+//! actor.stop().unwrap();
+//! ```
+
+use std::any::Any;
+use std::fmt;
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::thread::{self, JoinHandle};
+
+use crate::channel::{self, Receiver, Sender};
+use crate::select;
+use crate::thread::{Scope, ScopedJoinHandle};
+
+/// A channel from which an actor receives its messages.
+///
+/// Senders are obtained with [`sender`] before the mailbox is handed to the actor.
+///
+/// [`sender`]: Mailbox::sender
+pub struct Mailbox<T> {
+    /// Kept to hand out senders. Dropped when the actor is spawned.
+    sender: Sender<T>,
+
+    /// The receiving side, which is moved into the actor.
+    receiver: Receiver<T>,
+}
+
+impl<T> Mailbox<T> {
+    /// Creates a mailbox of unbounded capacity.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam::actor::Mailbox;
+    ///
+    /// let mailbox = Mailbox::<i32>::unbounded();
+    /// ```
+    pub fn unbounded() -> Mailbox<T> {
+        let (sender, receiver) = channel::unbounded();
+        Mailbox { sender, receiver }
+    }
+
+    /// Creates a mailbox of bounded capacity.
+    ///
+    /// Senders block while the mailbox is full. See [`channel::bounded`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam::actor::Mailbox;
+    ///
+    /// let mailbox = Mailbox::<i32>::bounded(16);
+    /// ```
+    pub fn bounded(cap: usize) -> Mailbox<T> {
+        let (sender, receiver) = channel::bounded(cap);
+        Mailbox { sender, receiver }
+    }
+
+    /// Returns a sender for this mailbox.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam::actor::Mailbox;
+    ///
+    /// let mailbox = Mailbox::unbounded();
+    /// let s = mailbox.sender();
+    /// s.send(1).unwrap();
+    /// ```
+    pub fn sender(&self) -> Sender<T> {
+        self.sender.clone()
+    }
+}
+
+impl<T> fmt::Debug for Mailbox<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Mailbox { .. }")
+    }
+}
+
+/// A notification that an actor's handler panicked.
+#[derive(Clone, Debug)]
+pub struct Panicked {
+    /// The name of the actor's thread.
+    name: Option<String>,
+
+    /// The panic message.
+    message: Option<String>,
+}
+
+impl Panicked {
+    /// Returns the name of the actor's thread, if it has one.
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_ref().map(|s| &**s)
+    }
+
+    /// Returns the panic message, if it is a string.
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_ref().map(|s| &**s)
+    }
+}
+
+/// Configures an actor before it is spawned.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam::actor::{Builder, Mailbox};
+/// use crossbeam::channel;
+///
+/// let (panics, r) = channel::unbounded();
+/// let mailbox = Mailbox::unbounded();
+/// let s = mailbox.sender();
+///
+/// let actor = Builder::new()
+///     .name("parser".to_string())
+///     .notify_panics(panics)
+///     .spawn(mailbox, |msg: &str| {
+///         msg.parse::<i32>().unwrap();
+///     })
+///     .unwrap();
+///
+/// s.send("12").unwrap();
+/// s.send("twelve").unwrap();
+///
+/// let p = r.recv().unwrap();
+/// assert_eq!(p.name(), Some("parser"));
+/// assert!(actor.join().is_err());
+/// ```
+#[derive(Debug, Default)]
+pub struct Builder {
+    /// The name of the actor's thread.
+    name: Option<String>,
+
+    /// The stack size of the actor's thread.
+    stack_size: Option<usize>,
+
+    /// Receives a notification if the handler panics.
+    panics: Option<Sender<Panicked>>,
+}
+
+impl Builder {
+    /// Creates a builder with the default configuration.
+    pub fn new() -> Builder {
+        Builder::default()
+    }
+
+    /// Sets the name of the actor's thread.
+    pub fn name(mut self, name: String) -> Builder {
+        self.name = Some(name);
+        self
+    }
+
+    /// Sets the stack size of the actor's thread.
+    pub fn stack_size(mut self, size: usize) -> Builder {
+        self.stack_size = Some(size);
+        self
+    }
+
+    /// Sends a [`Panicked`] notification to `panics` if the handler panics.
+    pub fn notify_panics(mut self, panics: Sender<Panicked>) -> Builder {
+        self.panics = Some(panics);
+        self
+    }
+
+    /// Spawns an actor on a dedicated thread, which calls `handler` for every message received
+    /// from `mailbox`.
+    ///
+    /// Returns an error if the thread can't be spawned.
+    pub fn spawn<T, F>(self, mailbox: Mailbox<T>, handler: F) -> io::Result<Actor<'static>>
+    where
+        T: Send + 'static,
+        F: FnMut(T) + Send + 'static,
+    {
+        let (stop, stopped) = channel::bounded(0);
+        let receiver = mailbox.receiver;
+
+        let mut builder = thread::Builder::new();
+        if let Some(name) = self.name.clone() {
+            builder = builder.name(name);
+        }
+        if let Some(size) = self.stack_size {
+            builder = builder.stack_size(size);
+        }
+
+        let handle = builder.spawn(move || self.run(receiver, stopped, handler))?;
+        Ok(Actor {
+            stop: Some(stop),
+            handle: Some(Handle::Dedicated(handle)),
+        })
+    }
+
+    /// Spawns an actor on a scoped thread, which calls `handler` for every message received from
+    /// `mailbox`.
+    ///
+    /// Returns an error if the thread can't be spawned.
+    pub fn spawn_scoped<'scope, 'env, T, F>(
+        self,
+        scope: &'scope Scope<'env>,
+        mailbox: Mailbox<T>,
+        handler: F,
+    ) -> io::Result<Actor<'scope>>
+    where
+        T: Send + 'env,
+        F: FnMut(T) + Send + 'env,
+    {
+        let (stop, stopped) = channel::bounded(0);
+        let receiver = mailbox.receiver;
+
+        let mut builder = scope.builder();
+        if let Some(name) = self.name.clone() {
+            builder = builder.name(name);
+        }
+        if let Some(size) = self.stack_size {
+            builder = builder.stack_size(size);
+        }
+
+        let handle = builder.spawn(move |_| self.run(receiver, stopped, handler))?;
+        Ok(Actor {
+            stop: Some(stop),
+            handle: Some(Handle::Scoped(handle)),
+        })
+    }
+
+    /// Runs the handler loop on the actor's thread.
+    fn run<T, F>(self, receiver: Receiver<T>, stopped: Receiver<()>, mut handler: F)
+    where
+        F: FnMut(T),
+    {
+        let result = panic::catch_unwind(AssertUnwindSafe(|| loop {
+            select! {
+                recv(receiver) -> msg => match msg {
+                    Ok(msg) => handler(msg),
+                    Err(_) => break,
+                },
+                recv(stopped) -> _ => break,
+            }
+        }));
+
+        if let Err(payload) = result {
+            if let Some(panics) = &self.panics {
+                let _ = panics.send(Panicked {
+                    name: self.name,
+                    message: panic_message(&*payload),
+                });
+            }
+            panic::resume_unwind(payload);
+        }
+    }
+}
+
+/// Returns the message of a panic, if it is a string.
+fn panic_message(payload: &(dyn Any + Send)) -> Option<String> {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        Some(s.to_string())
+    } else {
+        payload.downcast_ref::<String>().cloned()
+    }
+}
+
+/// Spawns an actor on a dedicated thread, which calls `handler` for every message received from
+/// `mailbox`.
+///
+/// # Panics
+///
+/// Panics if the thread can't be spawned. Use [`Builder::spawn`] to handle that error instead.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam::actor::{self, Mailbox};
+///
+/// let mailbox = Mailbox::unbounded();
+/// let s = mailbox.sender();
+/// let actor = actor::spawn(mailbox, |msg: String| println!("{}", msg));
+///
+/// s.send("hello".to_string()).unwrap();
+///
+/// // The actor exits once all senders are dropped.
+/// drop(s);
+/// actor.join().unwrap();
+/// ```
+pub fn spawn<T, F>(mailbox: Mailbox<T>, handler: F) -> Actor<'static>
+where
+    T: Send + 'static,
+    F: FnMut(T) + Send + 'static,
+{
+    Builder::new()
+        .spawn(mailbox, handler)
+        .expect("failed to spawn an actor thread")
+}
+
+/// Spawns an actor on a scoped thread, which calls `handler` for every message received from
+/// `mailbox`.
+///
+/// # Panics
+///
+/// Panics if the thread can't be spawned. Use [`Builder::spawn_scoped`] to handle that error
+/// instead.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam::actor::{self, Mailbox};
+/// use crossbeam::thread;
+///
+/// let mut log = Vec::new();
+///
+/// thread::scope(|scope| {
+///     let mailbox = Mailbox::unbounded();
+///     let s = mailbox.sender();
+///     let actor = actor::spawn_scoped(scope, mailbox, |msg| log.push(msg));
+///
+///     s.send(1).unwrap();
+///     s.send(2).unwrap();
+///     drop(s);
+///     actor.join().unwrap();
+/// })
+/// .unwrap();
+///
+/// assert_eq!(log, [1, 2]);
+/// ```
+pub fn spawn_scoped<'scope, 'env, T, F>(
+    scope: &'scope Scope<'env>,
+    mailbox: Mailbox<T>,
+    handler: F,
+) -> Actor<'scope>
+where
+    T: Send + 'env,
+    F: FnMut(T) + Send + 'env,
+{
+    Builder::new()
+        .spawn_scoped(scope, mailbox, handler)
+        .expect("failed to spawn an actor thread")
+}
+
+/// The thread an actor runs on.
+enum Handle<'scope> {
+    Dedicated(JoinHandle<()>),
+    Scoped(ScopedJoinHandle<'scope, ()>),
+}
+
+impl Handle<'_> {
+    /// Waits for the thread to finish.
+    fn join(self) -> thread::Result<()> {
+        match self {
+            Handle::Dedicated(handle) => handle.join(),
+            Handle::Scoped(handle) => handle.join(),
+        }
+    }
+}
+
+/// A handle to a running actor.
+///
+/// Dropping the handle stops the actor and waits for its thread to finish.
+pub struct Actor<'scope> {
+    /// Dropped to ask the actor to stop. No message is ever sent through it.
+    stop: Option<Sender<()>>,
+
+    /// The actor's thread.
+    handle: Option<Handle<'scope>>,
+}
+
+impl Actor<'_> {
+    /// Waits for the actor to exit on its own, which happens once all senders are dropped.
+    ///
+    /// If the handler panicked, an error is returned.
+    pub fn join(mut self) -> thread::Result<()> {
+        self.handle.take().unwrap().join()
+    }
+
+    /// Asks the actor to stop and waits for it to exit.
+    ///
+    /// The actor finishes handling the current message and stops without looking at the rest of
+    /// its mailbox. If the handler panicked, an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam::actor::{self, Mailbox};
+    ///
+    /// let mailbox = Mailbox::unbounded();
+    /// let s = mailbox.sender();
+    /// let actor = actor::spawn(mailbox, |msg: i32| println!("{}", msg));
+    ///
+    /// actor.stop().unwrap();
+    /// assert!(s.send(1).is_err());
+    /// ```
+    pub fn stop(mut self) -> thread::Result<()> {
+        drop(self.stop.take());
+        self.handle.take().unwrap().join()
+    }
+}
+
+impl Drop for Actor<'_> {
+    fn drop(&mut self) {
+        drop(self.stop.take());
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl fmt::Debug for Actor<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Actor { .. }")
+    }
+}
@@ -18,6 +18,7 @@
 //! ## Thread synchronization
 //!
 //! * [`channel`], multi-producer multi-consumer channels for message passing.
+//! * [`actor`], mailboxes and handler threads built on channels.
 //! * [`Parker`], a thread parking primitive.
 //! * [`ShardedLock`], a sharded reader-writer lock with fast concurrent reads.
 //! * [`WaitGroup`], for synchronizing the beginning or end of some computation.
@@ -27,6 +28,7 @@
 //! * [`Backoff`], for exponential backoff in spin loops.
 //! * [`CachePadded`], for padding and aligning a value to the length of a cache line.
 //! * [`scope`], for spawning threads that borrow local variables from the stack.
+//! * [`pool`], a work-stealing thread pool for structured parallelism.
 //!
 //! [`AtomicCell`]: atomic::AtomicCell
 //! [`AtomicConsume`]: atomic::AtomicConsume
@@ -94,5 +96,10 @@ cfg_if! {
         pub use crossbeam_utils::thread;
         #[cfg(not(crossbeam_loom))]
         pub use crossbeam_utils::thread::scope;
+
+        #[cfg(not(crossbeam_loom))]
+        pub mod actor;
+        #[cfg(not(crossbeam_loom))]
+        pub mod pool;
     }
 }
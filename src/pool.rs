@@ -0,0 +1,441 @@
+//! A work-stealing thread pool for structured parallelism.
+//!
+//! A [`ThreadPool`] runs closures on a fixed set of worker threads. Closures are pushed into a
+//! global [`Injector`] queue, and idle workers steal them in batches into their own [`Worker`]
+//! deques, or from the deques of busy workers.
+//!
+//! Like [`scope`], [`ThreadPool::scope`] spawns closures that borrow local variables from the
+//! stack, and waits for all of them to finish before returning. This is not an async runtime:
+//! closures run to completion on the thread that picked them up.
+//!
+//! Dropping the pool shuts it down gracefully: workers finish all spawned closures, then exit.
+//!
+//! [`Injector`]: crate::deque::Injector
+//! [`Worker`]: crate::deque::Worker
+//! [`scope`]: crate::scope
+//!
+//! # Examples
+//!
+//! ```
+//! use crossbeam::pool::ThreadPool;
+//!
+//! let pool = ThreadPool::new(4);
+//! let mut chunks = vec![vec![1, 2, 3], vec![4, 5], vec![6]];
+//!
+//! pool.scope(|s| {
+//!     for chunk in &mut chunks {
+//!         s.spawn(move |_| chunk.iter_mut().for_each(|x| *x *= 10));
+//!     }
+//! })
+//! .unwrap();
+//!
+//! assert_eq!(chunks, [vec![10, 20, 30], vec![40, 50], vec![60]]);
+//! ```
+
+use std::any::Any;
+use std::fmt;
+use std::iter;
+use std::marker::PhantomData;
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use crate::channel::{self, Receiver, Sender};
+use crate::deque::{Injector, Steal, Stealer, Worker};
+use crate::select;
+
+/// A closure spawned onto the pool.
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+/// The queues shared by the pool and its workers.
+struct Registry {
+    /// Closures spawned onto the pool.
+    injector: Injector<Job>,
+
+    /// Stealers for the deques of all workers.
+    stealers: Vec<Stealer<Job>>,
+
+    /// Sends a token to wake up an idle worker whenever a closure is spawned.
+    wake: Sender<()>,
+
+    /// Receives tokens sent through `wake`.
+    wakeups: Receiver<()>,
+}
+
+impl Registry {
+    /// Pushes a closure into the global queue and wakes up a worker.
+    fn push(&self, job: Job) {
+        self.injector.push(job);
+
+        // If the channel is full, workers that haven't received their tokens yet will look for
+        // the closure when they do.
+        let _ = self.wake.try_send(());
+    }
+
+    /// Looks for a closure to run, first in `local`, then in the global queue, then in the deques
+    /// of other workers.
+    fn find_job(&self, local: Option<&Worker<Job>>) -> Option<Job> {
+        if let Some(job) = local.and_then(|local| local.pop()) {
+            return Some(job);
+        }
+
+        iter::repeat_with(|| {
+            let steal = match local {
+                Some(local) => self.injector.steal_batch_and_pop(local),
+                None => self.injector.steal(),
+            };
+            steal.or_else(|| self.stealers.iter().map(|s| s.steal()).collect())
+        })
+        .find(|s| !s.is_retry())
+        .and_then(Steal::success)
+    }
+}
+
+/// Runs closures on a worker thread until the pool shuts down.
+fn run_worker(registry: Arc<Registry>, local: Worker<Job>, shutdown: Receiver<()>) {
+    loop {
+        if let Some(job) = registry.find_job(Some(&local)) {
+            job();
+            continue;
+        }
+
+        select! {
+            recv(registry.wakeups) -> _ => {}
+            recv(shutdown) -> _ => {
+                // The pool was dropped. Finish whatever is left and exit.
+                while let Some(job) = registry.find_job(Some(&local)) {
+                    job();
+                }
+                return;
+            }
+        }
+    }
+}
+
+/// A pool of worker threads that steal work from each other.
+///
+/// # Examples
+///
+/// ```
+/// use crossbeam::channel;
+/// use crossbeam::pool::ThreadPool;
+///
+/// let pool = ThreadPool::new(2);
+/// let (s, r) = channel::unbounded();
+///
+/// for i in 0..10 {
+///     let s = s.clone();
+///     pool.spawn(move || s.send(i * i).unwrap());
+/// }
+/// drop(s);
+///
+/// let sum: i32 = r.iter().sum();
+/// assert_eq!(sum, 285);
+/// ```
+pub struct ThreadPool {
+    /// The queues shared with the workers.
+    registry: Arc<Registry>,
+
+    /// Dropped to tell the workers to shut down. No message is ever sent through it.
+    shutdown: Option<Sender<()>>,
+
+    /// Handles of the worker threads.
+    handles: Vec<JoinHandle<()>>,
+}
+
+impl ThreadPool {
+    /// Creates a new pool with `num_threads` worker threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `num_threads` is zero, or if a worker thread can't be spawned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam::pool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(4);
+    /// assert_eq!(pool.num_threads(), 4);
+    /// ```
+    pub fn new(num_threads: usize) -> ThreadPool {
+        assert!(num_threads > 0, "number of threads must be positive");
+
+        let workers: Vec<Worker<Job>> = (0..num_threads).map(|_| Worker::new_fifo()).collect();
+        let (wake, wakeups) = channel::bounded(num_threads);
+        let registry = Arc::new(Registry {
+            injector: Injector::new(),
+            stealers: workers.iter().map(Worker::stealer).collect(),
+            wake,
+            wakeups,
+        });
+
+        let (shutdown, shutdown_rx) = channel::bounded(0);
+        let handles = workers
+            .into_iter()
+            .enumerate()
+            .map(|(i, local)| {
+                let registry = registry.clone();
+                let shutdown = shutdown_rx.clone();
+                thread::Builder::new()
+                    .name(format!("crossbeam-pool-{}", i))
+                    .spawn(move || run_worker(registry, local, shutdown))
+                    .expect("failed to spawn a worker thread")
+            })
+            .collect();
+
+        ThreadPool {
+            registry,
+            shutdown: Some(shutdown),
+            handles,
+        }
+    }
+
+    /// Returns the number of worker threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam::pool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(2);
+    /// assert_eq!(pool.num_threads(), 2);
+    /// ```
+    pub fn num_threads(&self) -> usize {
+        self.handles.len()
+    }
+
+    /// Spawns a closure onto the pool.
+    ///
+    /// If the closure panics, the panic is caught so that the worker thread keeps running.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam::pool::ThreadPool;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    /// use std::sync::Arc;
+    ///
+    /// let counter = Arc::new(AtomicUsize::new(0));
+    /// let pool = ThreadPool::new(2);
+    ///
+    /// for _ in 0..10 {
+    ///     let counter = counter.clone();
+    ///     pool.spawn(move || {
+    ///         counter.fetch_add(1, Ordering::SeqCst);
+    ///     });
+    /// }
+    ///
+    /// // Dropping the pool waits for all spawned closures.
+    /// drop(pool);
+    /// assert_eq!(counter.load(Ordering::SeqCst), 10);
+    /// ```
+    pub fn spawn<F>(&self, f: F)
+    where
+        F: FnOnce() + Send + 'static,
+    {
+        self.registry.push(Box::new(move || {
+            let _ = panic::catch_unwind(AssertUnwindSafe(f));
+        }));
+    }
+
+    /// Creates a new scope for spawning closures that borrow local variables.
+    ///
+    /// All closures spawned within the scope are finished before this function returns. While
+    /// waiting, the current thread helps by running closures from the pool.
+    ///
+    /// If `f` or any of the spawned closures panicked, an error is returned containing the
+    /// panics, once all closures have finished.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam::pool::ThreadPool;
+    ///
+    /// let pool = ThreadPool::new(2);
+    /// let v = vec![1, 2, 3];
+    ///
+    /// let sum = pool
+    ///     .scope(|s| {
+    ///         s.spawn(|_| println!("{:?}", v));
+    ///         v.iter().sum::<i32>()
+    ///     })
+    ///     .unwrap();
+    /// assert_eq!(sum, 6);
+    /// ```
+    pub fn scope<'env, F, R>(&'env self, f: F) -> thread::Result<R>
+    where
+        F: FnOnce(&Scope<'env>) -> R,
+    {
+        let (done, done_rx) = channel::bounded(1);
+        let scope = Scope {
+            registry: self.registry.clone(),
+            state: Arc::new(ScopeState {
+                pending: AtomicUsize::new(0),
+                panics: Mutex::new(Vec::new()),
+                done,
+            }),
+            _marker: PhantomData,
+        };
+
+        // Execute the scoped function, but catch any panics.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| f(&scope)));
+
+        // Wait until all spawned closures are finished, even if `f` panicked, since they may
+        // borrow from its environment.
+        while scope.state.pending.load(Ordering::SeqCst) != 0 {
+            match self.registry.find_job(None) {
+                Some(job) => job(),
+                // Wake up once in a while to help with closures spawned in the meantime.
+                None => {
+                    let _ = done_rx.recv_timeout(Duration::from_millis(1));
+                }
+            }
+        }
+
+        let mut panics = mem::replace(
+            &mut *scope
+                .state
+                .panics
+                .lock()
+                .unwrap_or_else(|err| err.into_inner()),
+            Vec::new(),
+        );
+
+        match result {
+            Err(err) => {
+                panics.insert(0, err);
+                Err(Box::new(panics))
+            }
+            Ok(res) => {
+                if panics.is_empty() {
+                    Ok(res)
+                } else {
+                    Err(Box::new(panics))
+                }
+            }
+        }
+    }
+}
+
+impl Drop for ThreadPool {
+    fn drop(&mut self) {
+        // Disconnect the shutdown channel, then wait for the workers to finish.
+        drop(self.shutdown.take());
+        for handle in self.handles.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl fmt::Debug for ThreadPool {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ThreadPool")
+            .field("num_threads", &self.num_threads())
+            .finish()
+    }
+}
+
+/// The state of a scope shared by all closures spawned within it.
+struct ScopeState {
+    /// The number of spawned closures that haven't finished yet.
+    pending: AtomicUsize,
+
+    /// Panics caught in spawned closures.
+    panics: Mutex<Vec<Box<dyn Any + Send + 'static>>>,
+
+    /// Signaled when the last pending closure finishes.
+    done: Sender<()>,
+}
+
+/// A scope for spawning closures onto a [`ThreadPool`].
+///
+/// Created by [`ThreadPool::scope`].
+pub struct Scope<'env> {
+    /// The queues of the pool.
+    registry: Arc<Registry>,
+
+    /// The state shared by all closures spawned within this scope.
+    state: Arc<ScopeState>,
+
+    /// Borrows data with invariant lifetime `'env`.
+    _marker: PhantomData<&'env mut &'env ()>,
+}
+
+impl<'env> Scope<'env> {
+    /// Spawns a closure onto the pool.
+    ///
+    /// The closure may borrow local variables from the enclosing environment, and receives a
+    /// reference to the scope so that it can spawn more closures.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crossbeam::pool::ThreadPool;
+    /// use std::sync::atomic::{AtomicUsize, Ordering};
+    ///
+    /// let pool = ThreadPool::new(2);
+    /// let counter = AtomicUsize::new(0);
+    ///
+    /// pool.scope(|s| {
+    ///     for _ in 0..4 {
+    ///         s.spawn(|s| {
+    ///             counter.fetch_add(1, Ordering::SeqCst);
+    ///             s.spawn(|_| {
+    ///                 counter.fetch_add(1, Ordering::SeqCst);
+    ///             });
+    ///         });
+    ///     }
+    /// })
+    /// .unwrap();
+    ///
+    /// assert_eq!(counter.load(Ordering::SeqCst), 8);
+    /// ```
+    pub fn spawn<F>(&self, f: F)
+    where
+        F: FnOnce(&Scope<'env>) + Send + 'env,
+    {
+        self.state.pending.fetch_add(1, Ordering::SeqCst);
+
+        let registry = self.registry.clone();
+        let state = self.state.clone();
+        let closure = move || {
+            let scope = Scope {
+                registry,
+                state,
+                _marker: PhantomData,
+            };
+
+            if let Err(err) = panic::catch_unwind(AssertUnwindSafe(|| f(&scope))) {
+                scope
+                    .state
+                    .panics
+                    .lock()
+                    .unwrap_or_else(|err| err.into_inner())
+                    .push(err);
+            }
+
+            // Once the count drops to zero, the scope may return and `'env` may end, so nothing
+            // borrowed may be touched afterwards.
+            let state = scope.state;
+            if state.pending.fetch_sub(1, Ordering::SeqCst) == 1 {
+                let _ = state.done.try_send(());
+            }
+        };
+
+        // Allocate `closure` on the heap and erase the `'env` bound.
+        let closure: Box<dyn FnOnce() + Send + 'env> = Box::new(closure);
+        let closure: Job = unsafe { mem::transmute(closure) };
+
+        self.registry.push(closure);
+    }
+}
+
+impl fmt::Debug for Scope<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.pad("Scope { .. }")
+    }
+}
@@ -0,0 +1,121 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crossbeam::actor::{self, Builder, Mailbox};
+use crossbeam::channel;
+use crossbeam::thread;
+
+#[test]
+fn smoke() {
+    let mailbox = Mailbox::unbounded();
+    let s = mailbox.sender();
+    let (reply, r) = channel::unbounded();
+
+    let actor = actor::spawn(mailbox, move |x: i32| reply.send(x * 2).unwrap());
+    s.send(1).unwrap();
+    s.send(2).unwrap();
+    assert_eq!(r.recv(), Ok(2));
+    assert_eq!(r.recv(), Ok(4));
+
+    drop(s);
+    actor.join().unwrap();
+}
+
+#[test]
+fn handles_in_order() {
+    let mailbox = Mailbox::bounded(4);
+    let s = mailbox.sender();
+    let (reply, r) = channel::unbounded();
+
+    let mut next = 0;
+    let actor = actor::spawn(mailbox, move |x: usize| {
+        assert_eq!(x, next);
+        next += 1;
+        reply.send(x).unwrap();
+    });
+
+    for i in 0..1000 {
+        s.send(i).unwrap();
+    }
+    drop(s);
+    actor.join().unwrap();
+    assert_eq!(r.iter().count(), 1000);
+}
+
+#[test]
+fn stop() {
+    let mailbox = Mailbox::unbounded();
+    let s = mailbox.sender();
+    let handled = Arc::new(AtomicUsize::new(0));
+
+    let h = handled.clone();
+    let actor = actor::spawn(mailbox, move |_: ()| {
+        h.fetch_add(1, Ordering::SeqCst);
+    });
+
+    // Senders are still alive, so only `stop` makes the actor exit.
+    actor.stop().unwrap();
+    assert!(s.send(()).is_err());
+    assert_eq!(handled.load(Ordering::SeqCst), 0);
+}
+
+#[test]
+fn drop_stops() {
+    let mailbox = Mailbox::<()>::unbounded();
+    let s = mailbox.sender();
+
+    drop(actor::spawn(mailbox, |_| {}));
+    assert!(s.send(()).is_err());
+}
+
+#[test]
+fn panics() {
+    let (panics, r) = channel::unbounded();
+    let mailbox = Mailbox::unbounded();
+    let s = mailbox.sender();
+
+    let actor = Builder::new()
+        .name("worker".to_string())
+        .notify_panics(panics)
+        .spawn(mailbox, |x: i32| {
+            if x < 0 {
+                panic!("negative: {}", x);
+            }
+        })
+        .unwrap();
+
+    s.send(1).unwrap();
+    s.send(-1).unwrap();
+
+    let p = r.recv_timeout(Duration::from_secs(10)).unwrap();
+    assert_eq!(p.name(), Some("worker"));
+    assert_eq!(p.message(), Some("negative: -1"));
+    assert!(actor.join().is_err());
+
+    // The actor is gone, along with its mailbox.
+    assert!(s.send(2).is_err());
+}
+
+#[test]
+fn scoped() {
+    let mut sum = 0;
+
+    thread::scope(|scope| {
+        let mailbox = Mailbox::unbounded();
+        let s = mailbox.sender();
+        let actor = Builder::new()
+            .name("summer".to_string())
+            .spawn_scoped(scope, mailbox, |x| sum += x)
+            .unwrap();
+
+        for x in 1..=10 {
+            s.send(x).unwrap();
+        }
+        drop(s);
+        actor.join().unwrap();
+    })
+    .unwrap();
+
+    assert_eq!(sum, 55);
+}
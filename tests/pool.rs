@@ -0,0 +1,142 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use crossbeam::channel;
+use crossbeam::pool::{Scope, ThreadPool};
+
+#[test]
+fn smoke() {
+    let pool = ThreadPool::new(2);
+    let (s, r) = channel::bounded(1);
+    pool.spawn(move || s.send(7).unwrap());
+    assert_eq!(r.recv(), Ok(7));
+}
+
+#[test]
+#[should_panic(expected = "number of threads must be positive")]
+fn zero_threads() {
+    ThreadPool::new(0);
+}
+
+#[test]
+fn scope_borrows() {
+    let pool = ThreadPool::new(3);
+    let mut v: Vec<usize> = (0..1000).collect();
+
+    pool.scope(|s| {
+        for chunk in v.chunks_mut(7) {
+            s.spawn(move |_| chunk.iter_mut().for_each(|x| *x *= 2));
+        }
+    })
+    .unwrap();
+
+    assert!(v.iter().enumerate().all(|(i, x)| *x == i * 2));
+}
+
+#[test]
+fn nested_spawn() {
+    fn count<'env>(s: &Scope<'env>, depth: usize, counter: &'env AtomicUsize) {
+        counter.fetch_add(1, Ordering::SeqCst);
+        if depth > 0 {
+            for _ in 0..2 {
+                s.spawn(move |s| count(s, depth - 1, counter));
+            }
+        }
+    }
+
+    let pool = ThreadPool::new(2);
+    let counter = AtomicUsize::new(0);
+    pool.scope(|s| count(s, 8, &counter)).unwrap();
+    assert_eq!(counter.load(Ordering::SeqCst), (1 << 9) - 1);
+}
+
+#[test]
+fn nested_scope() {
+    // Workers waiting for an inner scope help run closures, so this can't deadlock.
+    let pool = ThreadPool::new(1);
+    let counter = AtomicUsize::new(0);
+
+    pool.scope(|s| {
+        for _ in 0..4 {
+            s.spawn(|_| {
+                pool.scope(|s| {
+                    for _ in 0..4 {
+                        s.spawn(|_| {
+                            counter.fetch_add(1, Ordering::SeqCst);
+                        });
+                    }
+                })
+                .unwrap();
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(counter.load(Ordering::SeqCst), 16);
+}
+
+#[test]
+fn panics() {
+    let pool = ThreadPool::new(2);
+    let counter = AtomicUsize::new(0);
+
+    let result = pool.scope(|s| {
+        s.spawn(|_| panic!("oops"));
+        s.spawn(|_| {
+            thread::sleep(Duration::from_millis(50));
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+    });
+
+    let panics = result.unwrap_err();
+    let panics = panics
+        .downcast_ref::<Vec<Box<dyn std::any::Any + Send>>>()
+        .unwrap();
+    assert_eq!(panics.len(), 1);
+    // The scope still waited for the other closure.
+    assert_eq!(counter.load(Ordering::SeqCst), 1);
+
+    // Workers survive panics.
+    pool.spawn(|| panic!("oops"));
+    assert_eq!(pool.scope(|_| 1).unwrap(), 1);
+}
+
+#[test]
+fn graceful_shutdown() {
+    let counter = Arc::new(AtomicUsize::new(0));
+    let pool = ThreadPool::new(2);
+
+    for _ in 0..100 {
+        let counter = counter.clone();
+        pool.spawn(move || {
+            thread::sleep(Duration::from_micros(100));
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+    }
+
+    drop(pool);
+    assert_eq!(counter.load(Ordering::SeqCst), 100);
+}
+
+#[test]
+fn stress() {
+    const THREADS: usize = 4;
+    const COUNT: usize = 10_000;
+
+    let pool = ThreadPool::new(THREADS);
+    let sum = AtomicUsize::new(0);
+
+    pool.scope(|s| {
+        for i in 0..COUNT {
+            let sum = &sum;
+            s.spawn(move |_| {
+                sum.fetch_add(i, Ordering::Relaxed);
+            });
+        }
+    })
+    .unwrap();
+
+    assert_eq!(sum.load(Ordering::Relaxed), COUNT * (COUNT - 1) / 2);
+}